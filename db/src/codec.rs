@@ -0,0 +1,23 @@
+use crate::errors::Result;
+use serde::{Deserialize, Serialize};
+
+// 值的序列化方式，存储为blob时可按需替换
+pub trait Codec {
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>>;
+    fn decode<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T>;
+}
+
+pub struct JsonCodec;
+
+impl Codec for JsonCodec {
+    fn encode<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(value)?)
+    }
+
+    fn decode<T: for<'de> Deserialize<'de>>(bytes: &[u8]) -> Result<T> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+// 与 QueryResult::into_struct 保持一致，默认使用json
+pub type DefaultCodec = JsonCodec;
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use crate::codec::{Codec, DefaultCodec, JsonCodec};
+    use crate::errors::DBError;
+    use crate::sqlite::SQLiteDB;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestData {
+        id: i64,
+        name: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct OtherData {
+        price: f64,
+    }
+
+    #[test]
+    fn test_json_codec_round_trip_through_blob() {
+        let db = SQLiteDB::new(":memory:").expect("Failed to create database");
+        db.execute_update("CREATE TABLE kv (key TEXT PRIMARY KEY, value BLOB)", &[])
+            .unwrap();
+
+        let data = TestData {
+            id: 1,
+            name: "张三".to_string(),
+        };
+        let bytes = JsonCodec::encode(&data).unwrap();
+        db.execute_update(
+            "INSERT INTO kv (key, value) VALUES (?1, ?2)",
+            &[&"k1", &bytes],
+        )
+        .unwrap();
+
+        let result = db
+            .execute_query("SELECT value FROM kv WHERE key = ?1", &[&"k1"])
+            .unwrap();
+        let stored = match result.first().and_then(|row| row.get("value")) {
+            Some(crate::common::Value::Blob(b)) => b.clone(),
+            other => panic!("unexpected value: {:?}", other),
+        };
+        let decoded: TestData = DefaultCodec::decode(&stored).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_decode_mismatch_returns_error() {
+        let bytes = JsonCodec::encode(&TestData {
+            id: 1,
+            name: "a".to_string(),
+        })
+        .unwrap();
+        let result: Result<OtherData, DBError> = JsonCodec::decode(&bytes);
+        assert!(matches!(result, Err(DBError::SerializationError(_))));
+
+        // 非json字节
+        let result: Result<TestData, DBError> = JsonCodec::decode(&[0xc1, 0x00, 0xff]);
+        assert!(matches!(result, Err(DBError::SerializationError(_))));
+    }
+}
@@ -87,6 +87,15 @@ impl Row {
     pub fn get_bool(&self, column: &str) -> Option<bool> {
         self.get(column)?.as_bool()
     }
+
+    // 按Codec编码的列，兼容以文本写入的旧数据
+    pub fn get_bytes(&self, column: &str) -> Option<Vec<u8>> {
+        match self.get(column)? {
+            Value::Blob(b) => Some(b.clone()),
+            Value::Text(s) => Some(s.clone().into_bytes()),
+            _ => None,
+        }
+    }
 }
 
 impl Default for Row {
@@ -1,3 +1,7 @@
+pub mod codec;
+mod codec_tests;
 pub mod common;
 pub mod errors;
 pub mod sqlite;
+
+pub use sqlite::transaction;
@@ -1,4 +1,7 @@
+mod secondary_index;
+mod secondary_index_tests;
 mod sqlite_db;
 mod sqlite_db_tests;
 
-pub use sqlite_db::SQLiteDB;
+pub use secondary_index::{IndexOp, SecondaryIndex};
+pub use sqlite_db::{transaction, SQLiteDB};
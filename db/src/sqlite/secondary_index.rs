@@ -0,0 +1,126 @@
+use crate::errors::{DBError, Result};
+use crate::sqlite::SQLiteDB;
+
+// 从值中提取索引key
+type IndexKeyFn<T> = Box<dyn Fn(&T) -> Vec<u8> + Send + Sync>;
+
+/// 按值建立的二级索引：index_key -> 主键集合，存储在独立的索引表中
+/// 调用方在写主表的同一 db::transaction 内调用 on_insert/on_remove/apply_batch，
+/// 主表与索引一起提交或回滚
+pub struct SecondaryIndex<T> {
+    db: SQLiteDB,
+    table: String,
+    extractor: IndexKeyFn<T>,
+}
+
+/// apply_batch 的单个操作，old 为写入前的值（不存在时为 None）
+pub enum IndexOp<'a, T> {
+    Insert {
+        primary_key: &'a [u8],
+        old: Option<&'a T>,
+        new: &'a T,
+    },
+    Remove {
+        primary_key: &'a [u8],
+        old: &'a T,
+    },
+}
+
+impl<T> SecondaryIndex<T> {
+    pub fn new<F>(db: SQLiteDB, table: &str, extractor: F) -> Result<Self>
+    where
+        F: Fn(&T) -> Vec<u8> + Send + Sync + 'static,
+    {
+        // 表名拼接进sql，只允许字母数字下划线
+        if table.is_empty() || !table.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(DBError::InvalidParameter {
+                message: format!("invalid index table name: {}", table),
+            });
+        }
+        db.execute_update(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    index_key BLOB NOT NULL,
+                    primary_key BLOB NOT NULL,
+                    PRIMARY KEY(index_key, primary_key)
+                )",
+                table
+            ),
+            &[],
+        )?;
+        Ok(SecondaryIndex {
+            db,
+            table: table.to_string(),
+            extractor: Box::new(extractor),
+        })
+    }
+
+    pub fn index_key(&self, value: &T) -> Vec<u8> {
+        (self.extractor)(value)
+    }
+
+    // 新增或更新主表记录；索引字段变化时移除旧映射
+    pub fn on_insert(&self, primary_key: &[u8], old: Option<&T>, new: &T) -> Result<()> {
+        let new_key = self.index_key(new);
+        if let Some(old) = old {
+            let old_key = self.index_key(old);
+            if old_key == new_key {
+                return Ok(());
+            }
+            self.delete_entry(&old_key, primary_key)?;
+        }
+        self.db.execute_update(
+            &format!(
+                "INSERT OR IGNORE INTO {} (index_key, primary_key) VALUES (?1, ?2)",
+                self.table
+            ),
+            &[&new_key, &primary_key],
+        )?;
+        Ok(())
+    }
+
+    pub fn on_remove(&self, primary_key: &[u8], old: &T) -> Result<()> {
+        self.delete_entry(&self.index_key(old), primary_key)
+    }
+
+    pub fn apply_batch(&self, ops: &[IndexOp<'_, T>]) -> Result<()> {
+        for op in ops {
+            match op {
+                IndexOp::Insert {
+                    primary_key,
+                    old,
+                    new,
+                } => self.on_insert(primary_key, *old, new)?,
+                IndexOp::Remove { primary_key, old } => self.on_remove(primary_key, old)?,
+            }
+        }
+        Ok(())
+    }
+
+    // 索引值对应的全部主键，按主键字节序排列
+    pub fn lookup(&self, index_key: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let result = self.db.execute_query(
+            &format!(
+                "SELECT primary_key FROM {} WHERE index_key = ?1 ORDER BY primary_key",
+                self.table
+            ),
+            &[&index_key],
+        )?;
+        Ok(result
+            .rows
+            .iter()
+            .filter_map(|row| row.get_bytes("primary_key"))
+            .collect())
+    }
+
+    fn delete_entry(&self, index_key: &[u8], primary_key: &[u8]) -> Result<()> {
+        self.db.execute_update(
+            &format!(
+                "DELETE FROM {} WHERE index_key = ?1 AND primary_key = ?2",
+                self.table
+            ),
+            &[&index_key, &primary_key],
+        )?;
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+#[cfg(test)]
+mod tests {
+    use crate::codec::{Codec, DefaultCodec};
+    use crate::errors::{DBError, Result};
+    use crate::sqlite::{IndexOp, SQLiteDB, SecondaryIndex};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+    struct TestData {
+        id: i64,
+        name: String,
+        city: String,
+    }
+
+    fn data(id: i64, name: &str, city: &str) -> TestData {
+        TestData {
+            id,
+            name: name.to_string(),
+            city: city.to_string(),
+        }
+    }
+
+    fn pk(id: i64) -> Vec<u8> {
+        id.to_be_bytes().to_vec()
+    }
+
+    fn setup() -> (SQLiteDB, SecondaryIndex<TestData>) {
+        let db = SQLiteDB::new(":memory:").expect("Failed to create database");
+        db.execute_update(
+            "CREATE TABLE test_data (id BLOB PRIMARY KEY, value BLOB NOT NULL)",
+            &[],
+        )
+        .unwrap();
+        let index = SecondaryIndex::new(db.clone(), "test_data_by_city", |d: &TestData| {
+            d.city.as_bytes().to_vec()
+        })
+        .unwrap();
+        (db, index)
+    }
+
+    // 主表与索引在同一事务内写入
+    fn put(db: &SQLiteDB, index: &SecondaryIndex<TestData>, value: &TestData) -> Result<()> {
+        crate::transaction(db, |tx| {
+            let old = get(tx, value.id);
+            tx.execute_update(
+                "INSERT OR REPLACE INTO test_data (id, value) VALUES (?1, ?2)",
+                &[&pk(value.id), &DefaultCodec::encode(value)?],
+            )?;
+            index.on_insert(&pk(value.id), old.as_ref(), value)
+        })
+    }
+
+    fn remove(db: &SQLiteDB, index: &SecondaryIndex<TestData>, id: i64) -> Result<()> {
+        crate::transaction(db, |tx| {
+            let Some(old) = get(tx, id) else {
+                return Ok(());
+            };
+            tx.execute_update("DELETE FROM test_data WHERE id = ?1", &[&pk(id)])?;
+            index.on_remove(&pk(id), &old)
+        })
+    }
+
+    fn get(db: &SQLiteDB, id: i64) -> Option<TestData> {
+        let result = db
+            .execute_query("SELECT value FROM test_data WHERE id = ?1", &[&pk(id)])
+            .unwrap();
+        let bytes = result.first()?.get_bytes("value")?;
+        Some(DefaultCodec::decode(&bytes).unwrap())
+    }
+
+    #[test]
+    fn test_index_follows_insert_update_remove() {
+        let (db, index) = setup();
+        put(&db, &index, &data(1, "张三", "beijing")).unwrap();
+        put(&db, &index, &data(2, "李四", "beijing")).unwrap();
+        put(&db, &index, &data(3, "王五", "shanghai")).unwrap();
+        assert_eq!(index.lookup(b"beijing").unwrap(), vec![pk(1), pk(2)]);
+        assert_eq!(index.lookup(b"shanghai").unwrap(), vec![pk(3)]);
+
+        // 更新非索引字段，索引不变
+        put(&db, &index, &data(1, "张三丰", "beijing")).unwrap();
+        assert_eq!(index.lookup(b"beijing").unwrap(), vec![pk(1), pk(2)]);
+
+        // 更新索引字段，旧映射移除
+        put(&db, &index, &data(2, "李四", "shanghai")).unwrap();
+        assert_eq!(index.lookup(b"beijing").unwrap(), vec![pk(1)]);
+        assert_eq!(index.lookup(b"shanghai").unwrap(), vec![pk(2), pk(3)]);
+
+        remove(&db, &index, 3).unwrap();
+        assert_eq!(index.lookup(b"shanghai").unwrap(), vec![pk(2)]);
+        assert!(index.lookup(b"guangzhou").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_batch() {
+        let (_db, index) = setup();
+        let (a, b, b_moved) = (
+            data(1, "a", "beijing"),
+            data(2, "b", "beijing"),
+            data(2, "b", "shenzhen"),
+        );
+        let (pk1, pk2) = (pk(1), pk(2));
+        index
+            .apply_batch(&[
+                IndexOp::Insert {
+                    primary_key: &pk1,
+                    old: None,
+                    new: &a,
+                },
+                IndexOp::Insert {
+                    primary_key: &pk2,
+                    old: None,
+                    new: &b,
+                },
+                IndexOp::Insert {
+                    primary_key: &pk2,
+                    old: Some(&b),
+                    new: &b_moved,
+                },
+                IndexOp::Remove {
+                    primary_key: &pk1,
+                    old: &a,
+                },
+            ])
+            .unwrap();
+        assert!(index.lookup(b"beijing").unwrap().is_empty());
+        assert_eq!(index.lookup(b"shenzhen").unwrap(), vec![pk(2)]);
+    }
+
+    #[test]
+    fn test_index_rolls_back_with_transaction() {
+        let (db, index) = setup();
+        put(&db, &index, &data(1, "a", "beijing")).unwrap();
+
+        // 索引已更新后闭包失败，主表与索引一起回滚
+        let result: Result<()> = crate::transaction(&db, |_| {
+            index.on_insert(
+                &pk(1),
+                Some(&data(1, "a", "beijing")),
+                &data(1, "a", "tianjin"),
+            )?;
+            Err(DBError::InvalidParameter {
+                message: "abort".to_string(),
+            })
+        });
+        assert!(result.is_err());
+        assert_eq!(index.lookup(b"beijing").unwrap(), vec![pk(1)]);
+        assert!(index.lookup(b"tianjin").unwrap().is_empty());
+        assert_eq!(get(&db, 1).unwrap().city, "beijing");
+    }
+
+    #[test]
+    fn test_invalid_table_name() {
+        let db = SQLiteDB::new(":memory:").expect("Failed to create database");
+        let result = SecondaryIndex::new(db, "idx; DROP TABLE x", |d: &TestData| {
+            d.city.as_bytes().to_vec()
+        });
+        assert!(matches!(result, Err(DBError::InvalidParameter { .. })));
+    }
+}
@@ -1,12 +1,14 @@
 use crate::common::{QueryResult, Row, Value};
 use crate::errors::{DBError, Result};
-use rusqlite::types::ValueRef;
+use parking_lot::ReentrantMutex;
 use rusqlite::Connection;
+use rusqlite::types::ValueRef;
 use std::cell::RefCell;
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
+// 连接锁可重入：事务期间持有锁，闭包内同一线程的语句可再次加锁，其他线程的语句等待事务结束
 pub struct SQLiteDB {
-    connection: Arc<Mutex<RefCell<Connection>>>,
+    connection: Arc<ReentrantMutex<RefCell<Connection>>>,
 }
 
 impl SQLiteDB {
@@ -20,7 +22,7 @@ impl SQLiteDB {
         conn.busy_timeout(std::time::Duration::from_secs(5))?;
 
         Ok(SQLiteDB {
-            connection: Arc::new(Mutex::new(RefCell::new(conn))),
+            connection: Arc::new(ReentrantMutex::new(RefCell::new(conn))),
         })
     }
 
@@ -29,9 +31,7 @@ impl SQLiteDB {
         query: &str,
         params: &[&dyn rusqlite::ToSql],
     ) -> Result<QueryResult> {
-        let lock = self.connection.lock().map_err(|e| DBError::LockError {
-            message: format!("Failed to acquire lock: {}", e),
-        })?;
+        let lock = self.connection.lock();
 
         let conn = lock.borrow();
         let mut stmt = conn.prepare(query)?;
@@ -69,9 +69,7 @@ impl SQLiteDB {
     }
 
     pub fn execute_update(&self, query: &str, params: &[&dyn rusqlite::ToSql]) -> Result<usize> {
-        let lock = self.connection.lock().map_err(|e| DBError::LockError {
-            message: format!("Failed to acquire lock: {}", e),
-        })?;
+        let lock = self.connection.lock();
 
         let conn = lock.borrow();
         let affected = conn.execute(query, params)?;
@@ -79,9 +77,7 @@ impl SQLiteDB {
     }
 
     pub fn begin_transaction(&self) -> Result<()> {
-        let lock = self.connection.lock().map_err(|e| DBError::LockError {
-            message: format!("Failed to acquire lock: {}", e),
-        })?;
+        let lock = self.connection.lock();
 
         let conn = lock.borrow();
         conn.execute("BEGIN TRANSACTION", [])?;
@@ -89,9 +85,7 @@ impl SQLiteDB {
     }
 
     pub fn commit_transaction(&self) -> Result<()> {
-        let lock = self.connection.lock().map_err(|e| DBError::LockError {
-            message: format!("Failed to acquire lock: {}", e),
-        })?;
+        let lock = self.connection.lock();
 
         let conn = lock.borrow();
         conn.execute("COMMIT", [])?;
@@ -99,19 +93,21 @@ impl SQLiteDB {
     }
 
     pub fn rollback_transaction(&self) -> Result<()> {
-        let lock = self.connection.lock().map_err(|e| DBError::LockError {
-            message: format!("Failed to acquire lock: {}", e),
-        })?;
+        let lock = self.connection.lock();
 
         let conn = lock.borrow();
         conn.execute("ROLLBACK", [])?;
         Ok(())
     }
 
-    pub fn with_transaction<F, T>(&self, operation: F) -> Result<T>
+    // 闭包可返回调用方自己的错误类型，只要能由DBError转换
+    // 整个事务期间持有连接锁，其他线程的写入不会混入本事务或随本事务回滚
+    pub fn with_transaction<F, T, E>(&self, operation: F) -> std::result::Result<T, E>
     where
-        F: FnOnce() -> Result<T>,
+        F: FnOnce() -> std::result::Result<T, E>,
+        E: From<DBError>,
     {
+        let _lock = self.connection.lock();
         self.begin_transaction()?;
 
         match operation() {
@@ -127,9 +123,7 @@ impl SQLiteDB {
     }
 
     pub fn insert(&self, query: &str, params: &[&dyn rusqlite::ToSql]) -> Result<i64> {
-        let lock = self.connection.lock().map_err(|e| DBError::LockError {
-            message: format!("Failed to acquire lock: {}", e),
-        })?;
+        let lock = self.connection.lock();
 
         let conn = lock.borrow();
         conn.execute(query, params)?;
@@ -162,6 +156,16 @@ impl SQLiteDB {
     }
 }
 
+// 在同一事务内跨多张表执行写操作，闭包返回错误时全部回滚
+// 事务期间其他线程对同一连接的读写会等待事务提交或回滚
+pub fn transaction<F, T, E>(db: &SQLiteDB, operation: F) -> std::result::Result<T, E>
+where
+    F: FnOnce(&SQLiteDB) -> std::result::Result<T, E>,
+    E: From<DBError>,
+{
+    db.with_transaction(|| operation(db))
+}
+
 // 实现 Clone，使得 SQLiteDB 可以在多线程环境中共享
 impl Clone for SQLiteDB {
     fn clone(&self) -> Self {
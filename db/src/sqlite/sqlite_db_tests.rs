@@ -1,5 +1,6 @@
 #[cfg(test)]
 mod tests {
+    use crate::errors::{DBError, Result};
     use crate::sqlite::SQLiteDB;
     use serde::{Deserialize, Serialize};
 
@@ -227,7 +228,7 @@ mod tests {
         .unwrap();
 
         // 使用事务
-        let result = db.with_transaction(|| {
+        let result: Result<()> = db.with_transaction(|| {
             db.insert(
                 "INSERT INTO users (name, email, age) VALUES (?1, ?2, ?3)",
                 &[&"张三", &"zhangsan@test.com", &25i64],
@@ -264,7 +265,7 @@ mod tests {
         .unwrap();
 
         // 使用事务，但会失败回滚
-        let result = db.with_transaction(|| {
+        let result: Result<()> = db.with_transaction(|| {
             db.insert(
                 "INSERT INTO users (name, email, age) VALUES (?1, ?2, ?3)",
                 &[&"张三", &"zhangsan@test.com", &25i64],
@@ -286,6 +287,98 @@ mod tests {
         assert_eq!(users.len(), 0);
     }
 
+    fn create_entity_and_index(db: &SQLiteDB) {
+        db.execute_update(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+            &[],
+        )
+        .unwrap();
+        db.execute_update(
+            "CREATE TABLE users_by_name (name TEXT NOT NULL, user_id INTEGER NOT NULL)",
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_cross_table_transaction() {
+        let db = SQLiteDB::new(":memory:").expect("Failed to create database");
+        create_entity_and_index(&db);
+
+        let result: Result<i64> = crate::transaction(&db, |tx| {
+            let id = tx.insert("INSERT INTO users (name) VALUES (?1)", &[&"张三"])?;
+            tx.insert(
+                "INSERT INTO users_by_name (name, user_id) VALUES (?1, ?2)",
+                &[&"张三", &id],
+            )?;
+            Ok(id)
+        });
+        assert!(result.is_ok());
+
+        let users = db.execute_query("SELECT * FROM users", &[]).unwrap();
+        let index = db
+            .execute_query("SELECT * FROM users_by_name", &[])
+            .unwrap();
+        assert_eq!(users.len(), 1);
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.first().unwrap().get_i64("user_id"), result.ok());
+    }
+
+    #[test]
+    fn test_cross_table_transaction_rollback() {
+        let db = SQLiteDB::new(":memory:").expect("Failed to create database");
+        create_entity_and_index(&db);
+
+        // 第一张表写入成功后闭包返回错误，两张表都应回滚
+        let result: crate::errors::Result<()> = crate::transaction(&db, |tx| {
+            tx.insert("INSERT INTO users (name) VALUES (?1)", &[&"张三"])?;
+            Err(DBError::InvalidParameter {
+                message: "abort".to_string(),
+            })
+        });
+        assert!(matches!(result, Err(DBError::InvalidParameter { .. })));
+
+        let users = db.execute_query("SELECT * FROM users", &[]).unwrap();
+        let index = db
+            .execute_query("SELECT * FROM users_by_name", &[])
+            .unwrap();
+        assert_eq!(users.len(), 0);
+        assert_eq!(index.len(), 0);
+    }
+
+    #[derive(Debug)]
+    enum CallerError {
+        Db,
+        Abort,
+    }
+
+    impl From<DBError> for CallerError {
+        fn from(_: DBError) -> Self {
+            CallerError::Db
+        }
+    }
+
+    #[test]
+    fn test_transaction_with_caller_error() {
+        let db = SQLiteDB::new(":memory:").expect("Failed to create database");
+        create_entity_and_index(&db);
+
+        // 调用方自己的错误类型同样触发回滚，db错误经From转换
+        let result: std::result::Result<(), CallerError> = crate::transaction(&db, |tx| {
+            tx.insert("INSERT INTO users (name) VALUES (?1)", &[&"张三"])?;
+            Err(CallerError::Abort)
+        });
+        assert!(matches!(result, Err(CallerError::Abort)));
+        let users = db.execute_query("SELECT * FROM users", &[]).unwrap();
+        assert_eq!(users.len(), 0);
+
+        let result: std::result::Result<(), CallerError> = crate::transaction(&db, |tx| {
+            tx.insert("INSERT INTO missing (name) VALUES (?1)", &[&"张三"])?;
+            Ok(())
+        });
+        assert!(matches!(result, Err(CallerError::Db)));
+    }
+
     #[test]
     fn test_table_exists() {
         let db = SQLiteDB::new(":memory:").expect("Failed to create database");
@@ -359,6 +452,50 @@ mod tests {
         assert_eq!(result.rows[0].get_i64("value"), Some(100));
     }
 
+    #[test]
+    fn test_transaction_isolated_from_other_threads() {
+        use std::sync::{Arc, mpsc};
+        use std::thread;
+        use std::time::Duration;
+
+        let db = Arc::new(SQLiteDB::new(":memory:").expect("Failed to create database"));
+
+        db.execute_update(
+            "CREATE TABLE logs (id INTEGER PRIMARY KEY AUTOINCREMENT, source TEXT NOT NULL)",
+            &[],
+        )
+        .unwrap();
+
+        let (started_tx, started_rx) = mpsc::channel();
+
+        // 事务内写入后等待另一线程写入，再回滚
+        let tx_db = Arc::clone(&db);
+        let tx_handle = thread::spawn(move || {
+            let result: Result<()> = tx_db.with_transaction(|| {
+                tx_db.insert("INSERT INTO logs (source) VALUES ('tx')", &[])?;
+                started_tx.send(()).unwrap();
+                thread::sleep(Duration::from_millis(100));
+                Err(DBError::QueryError {
+                    message: "rollback".to_string(),
+                })
+            });
+            assert!(result.is_err());
+        });
+
+        // 另一线程的写入应等待事务结束，不会被卷入事务一起回滚
+        started_rx.recv().unwrap();
+        db.insert("INSERT INTO logs (source) VALUES ('other')", &[])
+            .unwrap();
+        tx_handle.join().unwrap();
+
+        let result = db.execute_query("SELECT source FROM logs", &[]).unwrap();
+        assert_eq!(result.rows.len(), 1);
+        assert_eq!(
+            result.rows[0].get_string("source"),
+            Some("other".to_string())
+        );
+    }
+
     #[test]
     fn test_multiple_inserts() {
         let db = SQLiteDB::new(":memory:").expect("Failed to create database");
@@ -16,6 +16,18 @@ pub enum BinanceError {
 
     #[error("Client error: {message}")]
     ClientError { message: String },
+
+    // ws 连接及收发错误，保留原始错误作为 source
+    #[error("Ws error")]
+    WsError(#[from] ws::WsError),
+
+    // 交易所返回的业务错误，code 见 binance 错误码文档，如 -2010 下单被拒、-2011 撤单被拒
+    #[error("Api error: status: {status}, code: {code}, msg: {message}")]
+    ApiError {
+        status: u16,
+        code: i64,
+        message: String,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, BinanceError>;
@@ -5,3 +5,6 @@ pub mod spot;
 pub mod consts;
 pub mod errors;
 pub mod utils;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
@@ -3,17 +3,51 @@ use super::super::utils::*;
 use super::parser::*;
 use super::requests::market::*;
 use super::responses::market::*;
-use log::error;
+use log::{error, trace, warn};
 use rate_limiter::RateLimiter;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
+// 瞬时错误（超时、连接失败、5xx）的重试策略，仅对幂等请求（GET）生效
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub backoff_milli_secs: u64, // 退避基数，按指数递增
+}
+
+// 单次重试退避上限
+const MAX_RETRY_BACKOFF_MILLI_SECS: u64 = 60_000;
+
+impl RetryPolicy {
+    // 第attempt次重试前的等待时间，按指数递增，不超过MAX_RETRY_BACKOFF_MILLI_SECS
+    pub fn retry_backoff_milli_secs(&self, attempt: u32) -> u64 {
+        self.backoff_milli_secs
+            .saturating_mul(2u64.saturating_pow(attempt))
+            .min(MAX_RETRY_BACKOFF_MILLI_SECS)
+    }
+}
+
+// 调试模式下解析失败时错误中附带的原始响应最大长度
+const DEBUG_BODY_MAX_LEN: usize = 512;
+
+// 原始响应及请求地址（敏感参数已脱敏），用于解析失败时定位问题
+struct RawResponse {
+    url: String,
+    text: String,
+}
+
 pub struct MarketApi {
     client: Option<reqwest::Client>,
     base_url: String,
     proxy_url: Option<String>,
     rate_limiters: Option<Arc<Vec<RateLimiter>>>,
     timeout_milli_secs: u64,
+    endpoint_timeout_milli_secs: HashMap<String, u64>, // endpoint -> 超时，覆盖默认超时
+    retry_policy: Option<RetryPolicy>,
+    debug_responses: bool,
+    api_key: Option<String>, // 仅historicalTrades等MARKET_DATA接口需要
+    tls_config: Option<ws::TlsConfig>, // 自定义根证书/证书公钥固定，None时使用系统根证书
 }
 
 impl MarketApi {
@@ -29,17 +63,59 @@ impl MarketApi {
             proxy_url,
             rate_limiters: rate_limiters,
             timeout_milli_secs,
+            endpoint_timeout_milli_secs: HashMap::new(),
+            retry_policy: None,
+            debug_responses: false,
+            api_key: None,
+            tls_config: None,
         }
     }
 
+    // endpoint 如 "/api/v3/exchangeInfo"
+    pub fn set_endpoint_timeout(&mut self, endpoint: &str, timeout_milli_secs: u64) {
+        self.endpoint_timeout_milli_secs
+            .insert(endpoint.to_string(), timeout_milli_secs);
+    }
+
+    pub fn set_retry_policy(&mut self, retry_policy: Option<RetryPolicy>) {
+        self.retry_policy = retry_policy;
+    }
+
+    // 调试模式：解析失败时错误中附带请求地址及截断的原始响应，并以trace级别记录所有原始响应
+    pub fn set_debug_responses(&mut self, debug_responses: bool) {
+        self.debug_responses = debug_responses;
+    }
+
+    // 空字符串视为未配置
+    pub fn set_api_key(&mut self, api_key: &str) {
+        self.api_key = Some(api_key.to_string()).filter(|key| !key.is_empty());
+    }
+
+    // 需在init前设置
+    pub fn set_tls_config(&mut self, tls_config: Option<ws::TlsConfig>) {
+        self.tls_config = tls_config;
+    }
+
     pub fn init(&mut self) -> Result<()> {
-        let client_builder = reqwest::Client::builder();
+        let mut client_builder = reqwest::Client::builder();
+        if let Some(tls_config) = &self.tls_config {
+            let config = tls_config.client_config().map_err(|e| {
+                crate::binance::errors::BinanceError::ParametersInvalid {
+                    message: format!("tls config invalid: {}", e),
+                }
+            })?;
+            client_builder = client_builder.use_preconfigured_tls((*config).clone());
+        }
 
         let client = if let Some(proxy_url) = &self.proxy_url {
             client_builder
                 .proxy(reqwest::Proxy::all(proxy_url).map_err(|e| {
                     crate::binance::errors::BinanceError::ParametersInvalid {
-                        message: format!("proxy url invalid: {}, error: {}", proxy_url, e),
+                        message: format!(
+                            "proxy url invalid: {}, error: {}",
+                            ws::redact_url(proxy_url),
+                            e
+                        ),
                     }
                 })?)
                 .build()
@@ -47,7 +123,8 @@ impl MarketApi {
                     |e| crate::binance::errors::BinanceError::ParametersInvalid {
                         message: format!(
                             "build client with proxy url: {} failed: {}",
-                            proxy_url, e
+                            ws::redact_url(proxy_url),
+                            e
                         ),
                     },
                 )?
@@ -76,17 +153,13 @@ impl MarketApi {
             params.push(("endTime", end_time.to_string()));
         }
 
-        let text = self
+        // klines权重固定为2，与limit无关，limit上限1000，更多数据需分页请求
+        let resp = self
             .send_request(reqwest::Method::GET, "/api/v3/klines", params, 2)
             .await?;
 
-        let mut klines =
-            parse_klines(req.symbol.clone(), req.interval.clone(), &text).map_err(|e| {
-                error!("Parse result: {:?} error: {:?}", text, e);
-                BinanceError::ParseResultError {
-                    message: e.to_string(),
-                }
-            })?;
+        let mut klines = parse_klines(req.symbol.clone(), req.interval.clone(), &resp.text)
+            .map_err(|e| self.parse_error(&resp, e))?;
         klines.sort_by(|a, b| a.open_time.cmp(&b.open_time));
 
         Ok(klines)
@@ -107,22 +180,56 @@ impl MarketApi {
             params.push(("endTime", end_time.to_string()));
         }
 
-        let text = self
+        let resp = self
             .send_request(reqwest::Method::GET, "/api/v3/aggTrades", params, 4)
             .await?;
 
-        let mut trades = parse_agg_trades(req.symbol.clone(), &text).map_err(|e| {
-            error!("Parse result: {:?} error: {:?}", text, e);
-            BinanceError::ParseResultError {
-                message: e.to_string(),
-            }
-        })?;
+        let mut trades = parse_agg_trades(req.symbol.clone(), &resp.text)
+            .map_err(|e| self.parse_error(&resp, e))?;
 
         trades.sort_by(|a, b| a.agg_trade_id.cmp(&b.agg_trade_id));
 
         Ok(trades)
     }
 
+    // 逐笔成交，按from_id向后翻页，limit上限1000；需要api key（无需签名）
+    pub async fn get_historical_trades(
+        &self,
+        req: GetHistoricalTradesRequest,
+    ) -> Result<GetHistoricalTradesResponse> {
+        let api_key = self
+            .api_key
+            .as_deref()
+            .ok_or(BinanceError::ParametersInvalid {
+                message: "historicalTrades requires api key, please call set_api_key() first"
+                    .to_string(),
+            })?;
+        let mut params = vec![
+            ("symbol", req.symbol.clone()),
+            ("limit", req.limit.unwrap_or(500).to_string()),
+        ];
+        if let Some(from_id) = req.from_id {
+            params.push(("fromId", from_id.to_string()));
+        }
+
+        let resp = self
+            .send_request_with_api_key(
+                reqwest::Method::GET,
+                "/api/v3/historicalTrades",
+                params,
+                25,
+                Some(api_key),
+            )
+            .await?;
+
+        let mut trades = parse_market_trades(req.symbol.clone(), &resp.text)
+            .map_err(|e| self.parse_error(&resp, e))?;
+
+        trades.sort_by_key(|t| t.trade_id);
+
+        Ok(trades)
+    }
+
     pub async fn get_depth(&self, req: GetDepthRequest) -> Result<GetDepthResponse> {
         let mut params = vec![("symbol", req.symbol.clone())];
 
@@ -139,16 +246,11 @@ impl MarketApi {
             _ => 250,
         };
 
-        let text = self
+        let resp = self
             .send_request(reqwest::Method::GET, "/api/v3/depth", params, weight)
             .await?;
 
-        parse_depth(req.symbol.clone(), &text).map_err(|e| {
-            error!("Parse result: {:?} error: {:?}", text, e);
-            BinanceError::ParseResultError {
-                message: e.to_string(),
-            }
-        })
+        parse_depth(req.symbol.clone(), &resp.text).map_err(|e| self.parse_error(&resp, e))
     }
 
     pub async fn get_exchange_info(
@@ -169,16 +271,11 @@ impl MarketApi {
             params.push(("symbols", symbols_json));
         }
 
-        let text = self
+        let resp = self
             .send_request(reqwest::Method::GET, "/api/v3/exchangeInfo", params, 20)
             .await?;
 
-        parse_exchange_info(&text).map_err(|e| {
-            error!("Parse result: {:?} error: {:?}", text, e);
-            BinanceError::ParseResultError {
-                message: e.to_string(),
-            }
-        })
+        parse_exchange_info(&resp.text).map_err(|e| self.parse_error(&resp, e))
     }
 
     pub async fn get_ticker_24hr(
@@ -213,25 +310,47 @@ impl MarketApi {
             params.push(("symbols", symbols_json));
         }
 
-        let text = self
+        let resp = self
             .send_request(reqwest::Method::GET, "/api/v3/ticker/24hr", params, weight)
             .await?;
 
-        parse_ticker_24hr(&req, &text).map_err(|e| {
-            error!("Parse result: {:?} error: {:?}", text, e);
-            BinanceError::ParseResultError {
-                message: e.to_string(),
-            }
-        })
+        parse_ticker_24hr(&req, &resp.text).map_err(|e| self.parse_error(&resp, e))
+    }
+
+    fn parse_error<E: std::fmt::Display>(&self, resp: &RawResponse, e: E) -> BinanceError {
+        error!("Parse result: {:?} error: {}", resp.text, e);
+        let message = if self.debug_responses {
+            format!(
+                "{}, url: {}, body: {}",
+                e,
+                resp.url,
+                truncate_body(&resp.text, DEBUG_BODY_MAX_LEN)
+            )
+        } else {
+            e.to_string()
+        };
+        BinanceError::ParseResultError { message }
     }
 
     async fn send_request(
+        &self,
+        method: reqwest::Method,
+        endpoint: &str,
+        params: Vec<(&str, String)>,
+        weight: u64,
+    ) -> Result<RawResponse> {
+        self.send_request_with_api_key(method, endpoint, params, weight, None)
+            .await
+    }
+
+    async fn send_request_with_api_key(
         &self,
         method: reqwest::Method,
         endpoint: &str,
         mut params: Vec<(&str, String)>,
         weight: u64,
-    ) -> Result<String> {
+        api_key: Option<&str>,
+    ) -> Result<RawResponse> {
         if let None = &self.client {
             return Err(BinanceError::ParametersInvalid {
                 message: "client is not initialized, please call init() first".to_string(),
@@ -241,42 +360,76 @@ impl MarketApi {
 
         sort_params(&mut params);
 
-        if let Some(rate_limiters) = &self.rate_limiters {
-            for rl in rate_limiters.iter() {
-                _ = rl.wait(weight).await;
-            }
+        if !matches!(
+            method,
+            reqwest::Method::GET | reqwest::Method::POST | reqwest::Method::DELETE
+        ) {
+            return Err(BinanceError::ParametersInvalid {
+                message: format!("unsupported http method: {}", method),
+            });
         }
 
-        let resp = match method {
-            reqwest::Method::GET => {
-                client
-                    .get(format!("{}{}", self.base_url, endpoint))
-                    .query(&params)
-                    .timeout(Duration::from_millis(self.timeout_milli_secs))
-                    .send()
-                    .await
+        let timeout_milli_secs = self
+            .endpoint_timeout_milli_secs
+            .get(endpoint)
+            .copied()
+            .unwrap_or(self.timeout_milli_secs);
+        // 非幂等请求不重试
+        let max_retries = match (&self.retry_policy, &method) {
+            (Some(policy), &reqwest::Method::GET) => policy.max_retries,
+            _ => 0,
+        };
+
+        let mut attempt = 0;
+        let resp = loop {
+            if let Some(rate_limiters) = &self.rate_limiters {
+                for rl in rate_limiters.iter() {
+                    _ = rl.wait(weight).await;
+                }
             }
-            reqwest::Method::POST => {
-                client
-                    .post(format!("{}{}", self.base_url, endpoint))
-                    .query(&params)
-                    .timeout(Duration::from_millis(self.timeout_milli_secs))
-                    .send()
-                    .await
+
+            let mut builder = client
+                .request(method.clone(), format!("{}{}", self.base_url, endpoint))
+                .query(&params)
+                .timeout(Duration::from_millis(timeout_milli_secs));
+            if let Some(api_key) = api_key {
+                builder = builder.header("X-MBX-APIKEY", api_key);
             }
-            reqwest::Method::DELETE => {
-                client
-                    .delete(format!("{}{}", self.base_url, endpoint))
-                    .query(&params)
-                    .timeout(Duration::from_millis(self.timeout_milli_secs))
-                    .send()
-                    .await
+            let resp = builder.send().await;
+
+            let transient = match &resp {
+                Ok(resp) => resp.status().is_server_error(),
+                Err(e) => e.is_timeout() || e.is_connect(),
+            };
+            if !transient || attempt >= max_retries {
+                break resp;
             }
-            _ => {
-                return Err(BinanceError::ParametersInvalid {
-                    message: format!("unsupported http method: {}", method),
-                })
+
+            let backoff = self
+                .retry_policy
+                .as_ref()
+                .map(|policy| policy.retry_backoff_milli_secs(attempt))
+                .unwrap_or_default();
+            match &resp {
+                Ok(resp) => warn!(
+                    "Request {} status: {}, retry {}/{} after {}ms",
+                    endpoint,
+                    resp.status(),
+                    attempt + 1,
+                    max_retries,
+                    backoff
+                ),
+                Err(e) => warn!(
+                    "Request {} error: {:?}, retry {}/{} after {}ms",
+                    endpoint,
+                    e,
+                    attempt + 1,
+                    max_retries,
+                    backoff
+                ),
             }
+            tokio::time::sleep(Duration::from_millis(backoff)).await;
+            attempt += 1;
         };
 
         let resp = resp.map_err(|e| {
@@ -300,6 +453,15 @@ impl MarketApi {
             }
         })?;
 
-        Ok(text)
+        let url = format!(
+            "{}{}?{}",
+            self.base_url,
+            endpoint,
+            encode_params(&redact_params(&params))
+        );
+        if self.debug_responses {
+            trace!("Response {}: {}", url, text);
+        }
+        Ok(RawResponse { url, text })
     }
 }
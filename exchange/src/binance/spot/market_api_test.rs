@@ -1,9 +1,13 @@
 use super::super::consts::*;
-use super::market_api::MarketApi;
+use super::super::errors::BinanceError;
+use super::market_api::{MarketApi, RetryPolicy};
 use super::requests::market::*;
 use crate::binance::spot::models::KlineInterval;
+use crate::binance::test_utils::{start_mock_server, start_mock_tls_server, test_cert};
+use crate::binance::utils::{encode_params, redact_params};
 use env_logger::Env;
 use rate_limiter::RateLimiter;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -177,3 +181,340 @@ async fn test_market_get_ticker_24hr_multiple() {
     }
     json::dump(&tickers, "ticker_24hr_multiple.json").unwrap();
 }
+
+fn klines_request() -> GetKlinesRequest {
+    GetKlinesRequest {
+        symbol: "BTCUSDT".to_string(),
+        interval: KlineInterval::OneMinute,
+        start_time: None,
+        end_time: None,
+        limit: Some(5),
+    }
+}
+
+fn agg_trades_request() -> GetAggTradesRequest {
+    GetAggTradesRequest {
+        symbol: "BTCUSDT".to_string(),
+        from_id: None,
+        start_time: None,
+        end_time: None,
+        limit: Some(5),
+    }
+}
+
+#[tokio::test]
+async fn test_market_endpoint_timeout_override() {
+    let base_url = start_mock_server(|_| (200, "[]".to_string(), 300)).await;
+
+    let mut api = MarketApi::new(base_url, None, None, 5000);
+    api.set_endpoint_timeout("/api/v3/klines", 100);
+    api.init().unwrap();
+
+    // 仅klines使用较短超时
+    let resp = api.get_klines(klines_request()).await;
+    assert!(matches!(resp, Err(BinanceError::NetworkError { .. })));
+
+    let resp = api.get_agg_trades(agg_trades_request()).await;
+    assert!(resp.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_market_retry_on_503() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let server_calls = calls.clone();
+    let base_url = start_mock_server(move |_| {
+        if server_calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            (503, "unavailable".to_string(), 0)
+        } else {
+            (200, "[]".to_string(), 0)
+        }
+    })
+    .await;
+
+    // 未配置重试时直接返回错误
+    let mut api = MarketApi::new(base_url.clone(), None, None, 5000);
+    api.init().unwrap();
+    let resp = api.get_agg_trades(agg_trades_request()).await;
+    assert!(matches!(resp, Err(BinanceError::ParseResultError { .. })));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    calls.store(0, Ordering::SeqCst);
+    api.set_retry_policy(Some(RetryPolicy {
+        max_retries: 2,
+        backoff_milli_secs: 10,
+    }));
+    let resp = api.get_agg_trades(agg_trades_request()).await;
+    assert!(resp.unwrap().is_empty());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_market_authenticated_proxy() {
+    let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    // 作为http代理接收请求
+    let proxy_url = start_mock_server(move |request| {
+        recorded.lock().unwrap().push(request.to_string());
+        (200, "[]".to_string(), 0)
+    })
+    .await;
+    let proxy_url = proxy_url.replace("http://", "http://user:p%40ss@");
+
+    let mut api = MarketApi::new(
+        "http://api.binance.test".to_string(),
+        Some(proxy_url),
+        None,
+        5000,
+    );
+    api.init().unwrap();
+    let resp = api.get_klines(klines_request()).await;
+    assert!(resp.unwrap().is_empty());
+
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].starts_with("GET http://api.binance.test/api/v3/klines?"));
+    // base64("user:p@ss")，账号密码百分号解码后编码
+    assert!(
+        requests[0].contains("proxy-authorization: Basic dXNlcjpwQHNz"),
+        "{}",
+        requests[0]
+    );
+
+    // 代理地址非法时错误信息不包含账号密码
+    let mut api = MarketApi::new(
+        "http://api.binance.test".to_string(),
+        Some("://user:secret@".to_string()),
+        None,
+        5000,
+    );
+    match api.init() {
+        Err(BinanceError::ParametersInvalid { message }) => {
+            assert!(!message.contains("secret"), "{}", message)
+        }
+        _ => panic!("expect invalid proxy url"),
+    }
+}
+
+#[tokio::test]
+async fn test_market_tls_pinning() {
+    let base_url = start_mock_tls_server(|_| (200, "[]".to_string(), 0)).await;
+
+    // 默认系统根证书不信任自签名证书
+    let mut api = MarketApi::new(base_url.clone(), None, None, 5000);
+    api.init().unwrap();
+    let resp = api.get_klines(klines_request()).await;
+    assert!(matches!(resp, Err(BinanceError::NetworkError { .. })));
+
+    // 固定公钥匹配
+    let mut api = MarketApi::new(base_url.clone(), None, None, 5000);
+    api.set_tls_config(Some(ws::TlsConfig {
+        root_store: None,
+        pinned_spki_sha256: vec![ws::spki_sha256(&test_cert()).unwrap()],
+    }));
+    api.init().unwrap();
+    let resp = api.get_klines(klines_request()).await;
+    assert!(resp.unwrap().is_empty());
+
+    // 公钥不匹配
+    let mut api = MarketApi::new(base_url.clone(), None, None, 5000);
+    api.set_tls_config(Some(ws::TlsConfig {
+        root_store: None,
+        pinned_spki_sha256: vec![[0u8; 32]],
+    }));
+    api.init().unwrap();
+    let resp = api.get_klines(klines_request()).await;
+    assert!(matches!(resp, Err(BinanceError::NetworkError { .. })));
+
+    // 自定义根证书
+    let mut root_store = tokio_rustls::rustls::RootCertStore::empty();
+    root_store.add(test_cert()).unwrap();
+    let mut api = MarketApi::new(base_url, None, None, 5000);
+    api.set_tls_config(Some(ws::TlsConfig {
+        root_store: Some(Arc::new(root_store)),
+        pinned_spki_sha256: vec![],
+    }));
+    api.init().unwrap();
+    let resp = api.get_klines(klines_request()).await;
+    assert!(resp.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_market_debug_responses_on_parse_error() {
+    // kline字段缺失
+    let body = r#"[[1499040000000,"0.01634790","0.80000000"]]"#;
+    let base_url = start_mock_server(move |_| (200, body.to_string(), 0)).await;
+
+    // 默认不附带原始响应
+    let mut api = MarketApi::new(base_url.clone(), None, None, 5000);
+    api.init().unwrap();
+    match api.get_klines(klines_request()).await {
+        Err(BinanceError::ParseResultError { message }) => {
+            assert!(!message.contains("0.80000000"), "{}", message)
+        }
+        other => panic!("expect parse error, got: {:?}", other),
+    }
+
+    api.set_debug_responses(true);
+    match api.get_klines(klines_request()).await {
+        Err(BinanceError::ParseResultError { message }) => {
+            assert!(message.contains(body), "{}", message);
+            assert!(
+                message.contains(&format!(
+                    "url: {}/api/v3/klines?interval=1m&limit=5&symbol=BTCUSDT",
+                    base_url
+                )),
+                "{}",
+                message
+            );
+        }
+        other => panic!("expect parse error, got: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_market_debug_responses_truncated() {
+    let body = format!("{{\"unexpected\":\"{}\"}}", "x".repeat(2000));
+    let server_body = body.clone();
+    let base_url = start_mock_server(move |_| (200, server_body.clone(), 0)).await;
+
+    let mut api = MarketApi::new(base_url, None, None, 5000);
+    api.set_debug_responses(true);
+    api.init().unwrap();
+    match api.get_klines(klines_request()).await {
+        Err(BinanceError::ParseResultError { message }) => {
+            assert!(message.contains(&body[..512]), "{}", message);
+            assert!(!message.contains(&body[..513]), "{}", message);
+            assert!(message.contains(&format!("...({} bytes)", body.len())));
+        }
+        other => panic!("expect parse error, got: {:?}", other),
+    }
+}
+
+// 模拟historicalTrades：共total笔成交（id从1开始），按fromId/limit返回，未带fromId时返回最新的limit笔
+fn mock_historical_trades(request: &str, total: u64) -> String {
+    let query = request
+        .lines()
+        .next()
+        .and_then(|line| line.split(' ').nth(1))
+        .and_then(|path| path.split_once('?'))
+        .map(|(_, query)| query.to_string())
+        .unwrap_or_default();
+    let param = |key: &str| {
+        query
+            .split('&')
+            .find_map(|kv| kv.strip_prefix(&format!("{}=", key)))
+            .map(|v| v.parse::<u64>().unwrap())
+    };
+    let limit = param("limit").unwrap_or(500);
+    let from_id = param("fromId").unwrap_or((total + 1).saturating_sub(limit).max(1));
+    let trades: Vec<String> = (from_id..=total)
+        .take(limit as usize)
+        .map(|id| {
+            format!(
+                r#"{{"id":{},"price":"100.5","qty":"0.{}","quoteQty":"1","time":{},"isBuyerMaker":{},"isBestMatch":true}}"#,
+                id,
+                id,
+                1000 + id,
+                id % 2 == 0
+            )
+        })
+        .collect();
+    format!("[{}]", trades.join(","))
+}
+
+#[tokio::test]
+async fn test_market_get_historical_trades_paginated() {
+    let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let base_url = start_mock_server(move |request| {
+        recorded.lock().unwrap().push(request.to_string());
+        (200, mock_historical_trades(request, 25), 0)
+    })
+    .await;
+
+    // 未配置api key时不发送请求
+    let mut api = MarketApi::new(base_url, None, None, 5000);
+    api.set_api_key("");
+    api.init().unwrap();
+    let req = |from_id| GetHistoricalTradesRequest {
+        symbol: "BTCUSDT".to_string(),
+        from_id,
+        limit: Some(10),
+    };
+    match api.get_historical_trades(req(None)).await {
+        Err(BinanceError::ParametersInvalid { message }) => {
+            assert!(message.contains("api key"), "{}", message)
+        }
+        other => panic!("expect missing api key error, got: {:?}", other),
+    }
+    assert!(requests.lock().unwrap().is_empty());
+
+    api.set_api_key("test-api-key");
+    let latest = api.get_historical_trades(req(None)).await.unwrap();
+    assert_eq!(
+        latest.iter().map(|t| t.trade_id).collect::<Vec<_>>(),
+        (16..=25).collect::<Vec<_>>()
+    );
+
+    // 从头按from_id翻页直到不足一页
+    let mut trades = Vec::new();
+    let mut from_id = 1;
+    loop {
+        let page = api.get_historical_trades(req(Some(from_id))).await.unwrap();
+        let len = page.len();
+        if let Some(last) = page.last() {
+            from_id = last.trade_id + 1;
+        }
+        trades.extend(page);
+        if len < 10 {
+            break;
+        }
+    }
+    assert_eq!(
+        trades.iter().map(|t| t.trade_id).collect::<Vec<_>>(),
+        (1..=25).collect::<Vec<_>>()
+    );
+    let trade = &trades[11];
+    assert_eq!(trade.symbol, "BTCUSDT");
+    assert_eq!(trade.price.to_string(), "100.5");
+    assert_eq!(trade.quantity.to_string(), "0.12");
+    assert_eq!(trade.timestamp, 1012);
+    assert!(trade.is_buyer_maker);
+
+    let requests = requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 4);
+    assert!(
+        requests[1].starts_with("GET /api/v3/historicalTrades?fromId=1&limit=10&symbol=BTCUSDT")
+    );
+    assert!(requests[3].contains("fromId=21&"));
+    // api key通过header传递，不出现在query中
+    assert!(requests
+        .iter()
+        .all(|r| r.contains("x-mbx-apikey: test-api-key")));
+}
+
+#[test]
+fn test_redact_params() {
+    let params = vec![
+        ("symbol", "BTCUSDT".to_string()),
+        ("apiKey", "key".to_string()),
+        ("signature", "abcdef".to_string()),
+    ];
+    assert_eq!(
+        encode_params(&redact_params(&params)),
+        "symbol=BTCUSDT&apiKey=REDACTED&signature=REDACTED"
+    );
+}
+
+#[test]
+fn test_retry_backoff_capped() {
+    let policy = RetryPolicy {
+        max_retries: 100,
+        backoff_milli_secs: 10,
+    };
+    assert_eq!(policy.retry_backoff_milli_secs(0), 10);
+    assert_eq!(policy.retry_backoff_milli_secs(3), 80);
+    // 指数溢出时不panic，且不超过上限
+    assert_eq!(policy.retry_backoff_milli_secs(64), 60_000);
+    assert_eq!(policy.retry_backoff_milli_secs(u32::MAX), 60_000);
+}
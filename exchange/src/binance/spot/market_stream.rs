@@ -22,6 +22,7 @@ use ws::SendMsg;
 enum MarketStreamType {
     UpdateDepth,
     AggTrade,
+    Trade,
     Kline,
     Ticker,
 }
@@ -42,6 +43,7 @@ pub struct MarketStream {
     sub_details: HashMap<String, SubDetail>,
     update_depth_cb: Option<Arc<dyn Fn(DepthUpdate) -> Fut + Send + Sync + 'static>>,
     agg_trade_cb: Option<Arc<dyn Fn(AggTrade) -> Fut + Send + Sync + 'static>>,
+    trade_cb: Option<Arc<dyn Fn(MarketTrade) -> Fut + Send + Sync + 'static>>,
     kline_cb: Option<Arc<dyn Fn(KlineData) -> Fut + Send + Sync + 'static>>,
     ticker_cb: Option<Arc<dyn Fn(Ticker24hr) -> Fut + Send + Sync + 'static>>,
 
@@ -63,6 +65,7 @@ impl MarketStream {
             sub_details: HashMap::new(),
             update_depth_cb: None,
             agg_trade_cb: None,
+            trade_cb: None,
             kline_cb: None,
             ticker_cb: None,
             client: None,
@@ -89,6 +92,17 @@ impl MarketStream {
         );
     }
 
+    // 逐笔成交，与归集成交按需二选一订阅
+    pub fn subscribe_trade(&mut self, symbol: &str) {
+        self.sub_details.insert(
+            format!("{}@trade", symbol.to_lowercase()),
+            SubDetail {
+                stream_type: MarketStreamType::Trade,
+                symbol: symbol.to_string(),
+            },
+        );
+    }
+
     pub fn subscribe_kline(&mut self, symbol: &str, interval: &KlineInterval) {
         self.sub_details.insert(
             format!("{}@kline_{}", symbol.to_lowercase(), interval.as_str()),
@@ -123,6 +137,13 @@ impl MarketStream {
         self.agg_trade_cb = Some(Arc::new(cb));
     }
 
+    pub fn register_trade_callback<F>(&mut self, cb: F)
+    where
+        F: Fn(MarketTrade) -> Fut + Send + Sync + 'static,
+    {
+        self.trade_cb = Some(Arc::new(cb));
+    }
+
     pub fn register_kline_callback<F>(&mut self, cb: F)
     where
         F: Fn(KlineData) -> Fut + Send + Sync + 'static,
@@ -142,6 +163,7 @@ impl MarketStream {
         let sub_details = Arc::new(self.sub_details.clone());
         let update_depth_cb = self.update_depth_cb.clone();
         let agg_trade_cb = self.agg_trade_cb.clone();
+        let trade_cb = self.trade_cb.clone();
         let kline_cb = self.kline_cb.clone();
         let ticker_cb = self.ticker_cb.clone();
         let mut config = ws::Config::default(
@@ -151,6 +173,7 @@ impl MarketStream {
                 let sub_details = sub_details.clone();
                 let update_depth_cb = update_depth_cb.clone();
                 let agg_trade_cb = agg_trade_cb.clone();
+                let trade_cb = trade_cb.clone();
                 let kline_cb = kline_cb.clone();
                 let ticker_cb = ticker_cb.clone();
                 Box::pin(async move {
@@ -159,6 +182,7 @@ impl MarketStream {
                         sub_details,
                         update_depth_cb,
                         agg_trade_cb,
+                        trade_cb,
                         kline_cb,
                         ticker_cb,
                     )
@@ -171,13 +195,11 @@ impl MarketStream {
 
         let mut ws_client = ws::Client::new(config).map_err(|e| {
             error!("WebSocket client error: {:?}", e);
-            BinanceError::ExternalError(Box::new(e))
+            BinanceError::WsError(e)
         })?;
         ws_client.connect().await.map_err(|e| {
             error!("WebSocket connect error: {:?}", e);
-            BinanceError::NetworkError {
-                message: e.to_string(),
-            }
+            BinanceError::WsError(e)
         })?;
 
         // 发生订阅消息
@@ -196,9 +218,7 @@ impl MarketStream {
             .await
             .map_err(|e| {
                 error!("WebSocket send subscribe message error: {:?}", e);
-                BinanceError::NetworkError {
-                    message: e.to_string(),
-                }
+                BinanceError::WsError(e)
             });
         if recv_msg.is_err() {
             return Err(BinanceError::NetworkError {
@@ -259,6 +279,7 @@ impl MarketStream {
                     + 'static,
             >,
         >,
+        trade_cb: Option<Arc<dyn Fn(MarketTrade) -> Fut + Send + Sync + 'static>>,
         kline_cb: Option<
             Arc<
                 dyn Fn(KlineData) -> Pin<Box<dyn Future<Output = ws::Result<()>> + Send>>
@@ -336,6 +357,16 @@ impl MarketStream {
                     cb(agg_trade).await?;
                 }
             }
+            MarketStreamType::Trade => {
+                let trade = parse_market_trade_stream(stream_msg.data.get()).map_err(|e| {
+                    ws::WsError::HandleError {
+                        message: e.to_string(),
+                    }
+                })?;
+                if let Some(cb) = trade_cb {
+                    cb(trade).await?;
+                }
+            }
             MarketStreamType::Kline => {
                 let kline = parse_kline_stream(stream_msg.data.get()).map_err(|e| {
                     ws::WsError::HandleError {
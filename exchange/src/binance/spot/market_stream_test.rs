@@ -153,3 +153,24 @@ async fn test_market_stream_ticker() {
     println!("Received {} tickers", tickers.len());
     json::dump(&*tickers, "tickers_stream.json").unwrap();
 }
+
+#[test]
+fn test_parse_raw_trade_stream_frame() {
+    let frame = r#"{"e":"trade","E":1672515782136,"s":"BNBBTC","t":12345,"p":"0.001","q":"100","T":1672515782134,"m":true,"M":true}"#;
+    let trade = super::parser::parse_market_trade_stream(frame).unwrap();
+    assert_eq!(trade.symbol, "BNBBTC");
+    assert_eq!(trade.trade_id, 12345);
+    assert_eq!(trade.price.to_string(), "0.001");
+    assert_eq!(trade.quantity.to_string(), "100");
+    assert_eq!(trade.timestamp, 1672515782134);
+    assert!(trade.is_buyer_maker);
+
+    let trades = super::parser::parse_market_trades(
+        "BNBBTC".to_string(),
+        r#"[{"id":28457,"price":"4.00000100","qty":"12.00000000","quoteQty":"48.000012","time":1499865549590,"isBuyerMaker":true,"isBestMatch":true}]"#,
+    )
+    .unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].trade_id, 28457);
+    assert_eq!(trades[0].timestamp, 1499865549590);
+}
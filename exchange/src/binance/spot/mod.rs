@@ -10,6 +10,10 @@ pub mod market_stream;
 #[cfg(test)]
 mod market_stream_test;
 
+pub mod signer;
+#[cfg(test)]
+mod signer_test;
+
 pub mod trade_api;
 #[cfg(test)]
 mod trade_api_test;
@@ -45,10 +45,13 @@ pub enum OrderStatus {
     Expired,
     #[serde(rename = "EXPIRED_IN_MATCH")]
     ExpiredInMatch,
+    // 交易所新增的状态，保留原始值
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl OrderStatus {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             OrderStatus::New => "NEW",
             OrderStatus::PendingNew => "PENDING_NEW",
@@ -59,6 +62,7 @@ impl OrderStatus {
             OrderStatus::Rejected => "REJECTED",
             OrderStatus::Expired => "EXPIRED",
             OrderStatus::ExpiredInMatch => "EXPIRED_IN_MATCH",
+            OrderStatus::Unknown(s) => s,
         }
     }
 
@@ -97,14 +101,18 @@ pub enum TimeInForce {
     Ioc,
     #[serde(rename = "FOK")]
     Fok,
+    // 交易所新增的类型，保留原始值
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl TimeInForce {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             TimeInForce::Gtc => "GTC",
             TimeInForce::Ioc => "IOC",
             TimeInForce::Fok => "FOK",
+            TimeInForce::Unknown(s) => s,
         }
     }
 
@@ -134,10 +142,13 @@ pub enum OrderType {
     TakeProfitLimit,
     #[serde(rename = "LIMIT_MAKER")]
     LimitMaker,
+    // 交易所新增的类型，保留原始值
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl OrderType {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             OrderType::Market => "MARKET",
             OrderType::Limit => "LIMIT",
@@ -146,6 +157,7 @@ impl OrderType {
             OrderType::StopLossLimit => "STOP_LOSS_LIMIT",
             OrderType::TakeProfitLimit => "TAKE_PROFIT_LIMIT",
             OrderType::LimitMaker => "LIMIT_MAKER",
+            OrderType::Unknown(s) => s,
         }
     }
 
@@ -179,10 +191,13 @@ pub enum ExecutionType {
     Expired,
     #[serde(rename = "TRADE_PREVENTION")]
     TradePrevention,
+    // 交易所新增的类型，保留原始值
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl ExecutionType {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             ExecutionType::New => "NEW",
             ExecutionType::Canceled => "CANCELED",
@@ -191,6 +206,7 @@ impl ExecutionType {
             ExecutionType::Trade => "TRADE",
             ExecutionType::Expired => "EXPIRED",
             ExecutionType::TradePrevention => "TRADE_PREVENTION",
+            ExecutionType::Unknown(s) => s,
         }
     }
 
@@ -70,6 +70,8 @@ pub struct DepthUpdate {
     pub bids: Vec<PriceLevel>,
     pub asks: Vec<PriceLevel>,
     pub timestamp: u64,
+    #[serde(default)]
+    pub checksum: Option<i32>, // 应用更新后前若干档的CRC32校验值，推送未提供时为None
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,6 +86,17 @@ pub struct AggTrade {
     pub is_buyer_maker: bool,
 }
 
+// 逐笔成交（未归集）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketTrade {
+    pub symbol: String,
+    pub trade_id: u64,
+    pub price: Decimal,
+    pub quantity: Decimal,
+    pub timestamp: u64,
+    pub is_buyer_maker: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Filter {
     pub filter_type: String,
@@ -17,6 +17,7 @@ pub struct Order {
     pub time_in_force: TimeInForce,
     pub stop_price: Decimal,
     pub iceberg_qty: Decimal,
+    pub quote_order_qty: Decimal, // 按quote金额下的市价单，其余为0
     pub create_time: u64,
     pub update_time: u64,
 }
@@ -79,6 +80,7 @@ pub struct ExecutionReport {
     pub is_maker: bool,
     pub stop_price: Decimal,
     pub iceberg_qty: Decimal,
+    pub quote_order_qty: Decimal,
 }
 
 impl ExecutionReport {
@@ -97,6 +99,7 @@ impl ExecutionReport {
             time_in_force: self.time_in_force.clone(),
             stop_price: self.stop_price,
             iceberg_qty: self.iceberg_qty,
+            quote_order_qty: self.quote_order_qty,
             create_time: self.create_time,
             update_time: self.transaction_time,
         }
@@ -148,6 +148,8 @@ impl From<(String, DepthUpdateRaw)> for DepthUpdate {
                 })
                 .collect(),
             timestamp: raw.timestamp,
+            // 现货增量深度推送不含checksum
+            checksum: None,
         }
     }
 }
@@ -198,6 +200,44 @@ pub fn parse_agg_trades(symbol: String, data: &str) -> Result<Vec<AggTrade>, ser
         .collect())
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MarketTradeRaw {
+    #[serde(rename = "id")]
+    trade_id: u64, // 交易ID
+    #[serde(rename = "price")]
+    price: Decimal, // 成交价格
+    #[serde(rename = "qty")]
+    quantity: Decimal, // 成交数量
+    #[serde(rename = "time")]
+    timestamp: u64, // 成交时间
+    #[serde(rename = "isBuyerMaker")]
+    is_buyer_maker: bool, // 买方是否是做市方
+}
+
+impl From<(String, MarketTradeRaw)> for MarketTrade {
+    fn from((symbol, raw): (String, MarketTradeRaw)) -> Self {
+        MarketTrade {
+            symbol,
+            trade_id: raw.trade_id,
+            price: raw.price,
+            quantity: raw.quantity,
+            timestamp: raw.timestamp,
+            is_buyer_maker: raw.is_buyer_maker,
+        }
+    }
+}
+
+pub fn parse_market_trades(
+    symbol: String,
+    data: &str,
+) -> Result<Vec<MarketTrade>, serde_json::Error> {
+    let raw_trades: Vec<MarketTradeRaw> = serde_json::from_str(data)?;
+    Ok(raw_trades
+        .into_iter()
+        .map(|raw| (symbol.clone(), raw).into())
+        .collect())
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DepthDataRaw {
     #[serde(rename = "lastUpdateId")]
@@ -293,6 +333,44 @@ pub fn parse_agg_trade_stream(data: &str) -> Result<AggTrade, serde_json::Error>
     Ok(raw.into())
 }
 
+#[derive(Debug, Deserialize)]
+pub struct MarketTradeStreamRaw {
+    #[serde(rename = "e")]
+    event_type: String, // 事件类型
+    #[serde(rename = "E")]
+    event_time: u64, // 事件时间
+    #[serde(rename = "s")]
+    symbol: String, // 交易对
+    #[serde(rename = "t")]
+    trade_id: u64, // 交易ID
+    #[serde(rename = "p")]
+    price: Decimal, // 成交价格
+    #[serde(rename = "q")]
+    quantity: Decimal, // 成交数量
+    #[serde(rename = "T")]
+    timestamp: u64, // 成交时间
+    #[serde(rename = "m")]
+    is_buyer_maker: bool, // 买方是否是做市方
+}
+
+impl From<MarketTradeStreamRaw> for MarketTrade {
+    fn from(raw: MarketTradeStreamRaw) -> Self {
+        MarketTrade {
+            symbol: raw.symbol,
+            trade_id: raw.trade_id,
+            price: raw.price,
+            quantity: raw.quantity,
+            timestamp: raw.timestamp,
+            is_buyer_maker: raw.is_buyer_maker,
+        }
+    }
+}
+
+pub fn parse_market_trade_stream(data: &str) -> Result<MarketTrade, serde_json::Error> {
+    let raw: MarketTradeStreamRaw = serde_json::from_str(data)?;
+    Ok(raw.into())
+}
+
 #[derive(Debug, Deserialize)]
 pub struct KlineStreamDataRaw {
     #[serde(rename = "t")]
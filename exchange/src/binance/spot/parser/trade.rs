@@ -1,6 +1,7 @@
 use crate::binance::spot::{
-    models::{ExecutionType, Order, OrderStatus, OrderType, Side, TimeInForce},
+    models::{ExecutionType, Order, OrderStatus, OrderType, Side, TimeInForce, Trade},
     requests::PlaceOrderRequest,
+    responses::PlaceOrderFullResponse,
 };
 use rust_decimal::Decimal;
 use serde::Deserialize;
@@ -45,6 +46,50 @@ where
     deserializer.deserialize_any(U64Visitor)
 }
 
+// 交易所新增的枚举值解析为Unknown并保留原始值，记录告警以便及时适配
+fn warn_unknown_enums(
+    client_order_id: &str,
+    order_status: &OrderStatus,
+    order_type: Option<&OrderType>,
+    time_in_force: Option<&TimeInForce>,
+    execution_type: Option<&ExecutionType>,
+) {
+    let unknowns = [
+        (
+            "status",
+            matches!(order_status, OrderStatus::Unknown(_)).then(|| order_status.as_str()),
+        ),
+        (
+            "type",
+            order_type
+                .filter(|t| matches!(t, OrderType::Unknown(_)))
+                .map(|t| t.as_str()),
+        ),
+        (
+            "timeInForce",
+            time_in_force
+                .filter(|t| matches!(t, TimeInForce::Unknown(_)))
+                .map(|t| t.as_str()),
+        ),
+        (
+            "executionType",
+            execution_type
+                .filter(|t| matches!(t, ExecutionType::Unknown(_)))
+                .map(|t| t.as_str()),
+        ),
+    ];
+    for (field, value) in unknowns {
+        if let Some(value) = value {
+            log::warn!(
+                "unknown order {} {} for client_order_id {}",
+                field,
+                value,
+                client_order_id
+            );
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PlaceOrderRaw {
     symbol: String,
@@ -54,10 +99,34 @@ pub struct PlaceOrderRaw {
     client_order_id: String,
     #[serde(rename = "transactTime")]
     timestamp: u64,
+    // newOrderRespType 为 RESULT/FULL 时返回
+    #[serde(default)]
+    status: Option<OrderStatus>,
+    #[serde(rename = "executedQty", default)]
+    executed_qty: Option<Decimal>,
+    #[serde(rename = "cummulativeQuoteQty", default)]
+    cummulative_quote_qty: Option<Decimal>,
+    // newOrderRespType 为 FULL 时返回
+    #[serde(default)]
+    fills: Vec<FillRaw>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FillRaw {
+    price: Decimal,
+    qty: Decimal,
+    commission: Decimal,
+    #[serde(rename = "commissionAsset")]
+    commission_asset: String,
+    #[serde(rename = "tradeId")]
+    trade_id: u64,
 }
 
 impl From<(PlaceOrderRequest, PlaceOrderRaw)> for Order {
     fn from((req, raw): (PlaceOrderRequest, PlaceOrderRaw)) -> Self {
+        if let Some(status) = raw.status.as_ref() {
+            warn_unknown_enums(&raw.client_order_id, status, None, None, None);
+        }
         Order {
             order_id: raw.order_id,
             client_order_id: raw.client_order_id,
@@ -70,9 +139,9 @@ impl From<(PlaceOrderRequest, PlaceOrderRaw)> for Order {
             } else {
                 Decimal::new(0, 0)
             },
-            executed_qty: Decimal::new(0, 0),
-            cummulative_quote_qty: Decimal::new(0, 0),
-            order_status: OrderStatus::New,
+            executed_qty: raw.executed_qty.unwrap_or_default(),
+            cummulative_quote_qty: raw.cummulative_quote_qty.unwrap_or_default(),
+            order_status: raw.status.unwrap_or(OrderStatus::New),
             time_in_force: if req.time_in_force.is_some() {
                 req.time_in_force.unwrap()
             } else {
@@ -88,6 +157,7 @@ impl From<(PlaceOrderRequest, PlaceOrderRaw)> for Order {
             } else {
                 Decimal::new(0, 0)
             },
+            quote_order_qty: req.quote_order_qty.unwrap_or_default(),
             create_time: raw.timestamp,
             update_time: raw.timestamp,
         }
@@ -99,6 +169,47 @@ pub fn parse_place_order(req: PlaceOrderRequest, data: &str) -> Result<Order, se
     Ok((req, raw).into())
 }
 
+// FULL 响应中的 fills 均为下单时立即成交（taker），成交时间取 transactTime
+pub fn parse_place_order_full(
+    req: PlaceOrderRequest,
+    data: &str,
+) -> Result<PlaceOrderFullResponse, serde_json::Error> {
+    let mut raw: PlaceOrderRaw = serde_json::from_str(data)?;
+    let fills = std::mem::take(&mut raw.fills);
+    let side = req.side.clone();
+    let order: Order = (req, raw).into();
+    let fills = fills
+        .into_iter()
+        .map(|fill| Trade {
+            trade_id: fill.trade_id,
+            order_id: order.order_id,
+            symbol: order.symbol.clone(),
+            order_side: side.clone(),
+            trade_price: fill.price,
+            trade_quantity: fill.qty,
+            commission: fill.commission,
+            commission_asset: fill.commission_asset,
+            is_maker: false,
+            timestamp: order.update_time,
+        })
+        .collect();
+    Ok(PlaceOrderFullResponse { order, fills })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancelReplaceOrderRaw {
+    #[serde(rename = "newOrderResponse")]
+    new_order_response: PlaceOrderRaw,
+}
+
+pub fn parse_cancel_replace_order(
+    new_order: PlaceOrderRequest,
+    data: &str,
+) -> Result<Order, serde_json::Error> {
+    let raw: CancelReplaceOrderRaw = serde_json::from_str(data)?;
+    Ok((new_order, raw.new_order_response).into())
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GetOrderRaw {
     symbol: String,
@@ -131,6 +242,13 @@ pub struct GetOrderRaw {
 
 impl From<GetOrderRaw> for Order {
     fn from(raw: GetOrderRaw) -> Self {
+        warn_unknown_enums(
+            &raw.client_order_id,
+            &raw.status,
+            Some(&raw.r#type),
+            Some(&raw.time_in_force),
+            None,
+        );
         Order {
             order_id: raw.order_id,
             client_order_id: raw.client_order_id,
@@ -145,6 +263,7 @@ impl From<GetOrderRaw> for Order {
             time_in_force: raw.time_in_force,
             stop_price: raw.stop_price,
             iceberg_qty: raw.iceberg_qty,
+            quote_order_qty: raw.orig_quote_order_qty,
             create_time: raw.time,
             update_time: raw.update_time,
         }
@@ -421,34 +540,44 @@ impl AccountUpdateRaw {
                 create_time,
                 cumulative_quote_qty,
                 last_quote_qty: _,
-                quote_order_qty: _,
+                quote_order_qty,
                 working_time: _,
                 self_trade_prevention_mode: _,
-            } => Ok(crate::binance::spot::models::ExecutionReport {
-                symbol,
-                order_id,
-                client_order_id,
-                original_client_order_id,
-                order_side: side,
-                time_in_force,
-                order_type,
-                execution_type,
-                order_status,
-                order_quantity: quantity,
-                order_price: price,
-                last_executed_qty,
-                last_executed_price,
-                cumulative_filled_qty,
-                cumulative_quote_qty,
-                commission,
-                commission_asset: commission_asset.unwrap_or_default(),
-                transaction_time,
-                create_time,
-                trade_id,
-                is_maker,
-                stop_price,
-                iceberg_qty,
-            }),
+            } => {
+                warn_unknown_enums(
+                    &client_order_id,
+                    &order_status,
+                    Some(&order_type),
+                    Some(&time_in_force),
+                    Some(&execution_type),
+                );
+                Ok(crate::binance::spot::models::ExecutionReport {
+                    symbol,
+                    order_id,
+                    client_order_id,
+                    original_client_order_id,
+                    order_side: side,
+                    time_in_force,
+                    order_type,
+                    execution_type,
+                    order_status,
+                    order_quantity: quantity,
+                    order_price: price,
+                    last_executed_qty,
+                    last_executed_price,
+                    cumulative_filled_qty,
+                    cumulative_quote_qty,
+                    commission,
+                    commission_asset: commission_asset.unwrap_or_default(),
+                    transaction_time,
+                    create_time,
+                    trade_id,
+                    is_maker,
+                    stop_price,
+                    iceberg_qty,
+                    quote_order_qty,
+                })
+            }
             _ => Err(crate::binance::errors::BinanceError::ParseResultError {
                 message: "Not an execution report event".to_string(),
             }),
@@ -456,14 +585,25 @@ impl AccountUpdateRaw {
     }
 }
 
+// 交易所错误，形如 {"code":-2010,"msg":"..."}
+#[derive(Debug, Deserialize)]
+pub struct ApiErrorRaw {
+    pub code: i64,
+    pub msg: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct PlaceOrderStreamRaw {
     pub status: u32,
     pub result: Option<PlaceOrderRaw>,
+    #[serde(default)]
+    pub error: Option<ApiErrorRaw>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CancelOrderStreamRaw {
     pub status: u32,
     pub result: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<ApiErrorRaw>,
 }
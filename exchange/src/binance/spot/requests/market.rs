@@ -18,6 +18,12 @@ pub struct GetAggTradesRequest {
     pub limit: Option<u32>,
 }
 
+pub struct GetHistoricalTradesRequest {
+    pub symbol: String,
+    pub from_id: Option<u64>, // 不指定时返回最新的成交
+    pub limit: Option<u32>,
+}
+
 pub struct GetDepthRequest {
     pub symbol: String,
     pub limit: Option<u32>,
@@ -12,6 +12,7 @@ pub struct PlaceOrderRequest {
     pub new_client_order_id: Option<String>,
     pub stop_price: Option<Decimal>, // STOP_LOSS/STOP_LOSS_LIMIT/TAKE_PROFIT/TAKE_PROFIT_LIMIT
     pub iceberg_qty: Option<Decimal>, // LIMIT/LIMIT_MAKER
+    pub quote_order_qty: Option<Decimal>, // MARKET，按quote金额下单，与quantity二选一
 }
 
 pub struct CancelOrderRequest {
@@ -21,6 +22,13 @@ pub struct CancelOrderRequest {
     pub new_client_order_id: Option<String>,
 }
 
+// 撤销订单并下新单，撤单失败时不下新单（STOP_ON_FAILURE）
+pub struct CancelReplaceOrderRequest {
+    pub cancel_order_id: Option<u64>,
+    pub cancel_orig_client_order_id: Option<String>,
+    pub new_order: PlaceOrderRequest,
+}
+
 pub struct GetAccountRequest {}
 
 pub struct GetOrderRequest {
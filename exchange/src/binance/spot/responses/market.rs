@@ -2,6 +2,8 @@ pub type GetKlinesResponse = Vec<super::super::models::KlineData>;
 
 pub type GetAggTradesResponse = Vec<super::super::models::AggTrade>;
 
+pub type GetHistoricalTradesResponse = Vec<super::super::models::MarketTrade>;
+
 pub type GetDepthResponse = super::super::models::DepthData;
 
 pub type GetExchangeInfoResponse = super::super::models::ExchangeInfo;
@@ -1,7 +1,16 @@
 use crate::binance::spot::models::{Account, Order, Trade};
 
 pub type PlaceOrderResponse = Order;
+
+// newOrderRespType=FULL 的下单结果，fills 为下单时立即成交的部分
+#[derive(Debug, Clone)]
+pub struct PlaceOrderFullResponse {
+    pub order: Order,
+    pub fills: Vec<Trade>,
+}
+
 pub type CancelOrderResponse = ();
+pub type CancelReplaceOrderResponse = Order;
 pub type GetAccountResponse = Account;
 pub type GetOrderResponse = Order;
 pub type GetOpenOrdersResponse = Vec<Order>;
@@ -0,0 +1,71 @@
+use crate::binance::{
+    errors::{BinanceError, Result},
+    utils::{encode_params, hmac_sha256, sort_params},
+};
+use zeroize::Zeroizing;
+
+pub const DEFAULT_RECV_WINDOW_MILLI_SECS: u64 = 5000;
+pub const MAX_RECV_WINDOW_MILLI_SECS: u64 = 60000; // binance允许的最大recvWindow
+
+// 签名请求使用的api key与secret，secret在drop时清零
+pub struct Signer {
+    api_key: String,
+    secret_key: Zeroizing<String>,
+    recv_window_milli_secs: u64,
+}
+
+impl Signer {
+    pub fn new(api_key: String, secret_key: String) -> Self {
+        Signer {
+            api_key,
+            secret_key: Zeroizing::new(secret_key),
+            recv_window_milli_secs: DEFAULT_RECV_WINDOW_MILLI_SECS,
+        }
+    }
+
+    // 本地时钟与服务端偏差较大时可适当放宽
+    pub fn set_recv_window(&mut self, recv_window_milli_secs: u64) -> Result<()> {
+        if recv_window_milli_secs == 0 || recv_window_milli_secs > MAX_RECV_WINDOW_MILLI_SECS {
+            return Err(BinanceError::ParametersInvalid {
+                message: format!(
+                    "recvWindow must be in (0, {}], got {}",
+                    MAX_RECV_WINDOW_MILLI_SECS, recv_window_milli_secs
+                ),
+            });
+        }
+        self.recv_window_milli_secs = recv_window_milli_secs;
+        Ok(())
+    }
+
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    pub fn recv_window(&self) -> u64 {
+        self.recv_window_milli_secs
+    }
+
+    // 对已编码的query string计算签名
+    pub fn signature(&self, payload: &str) -> String {
+        hmac_sha256(&self.secret_key, payload)
+    }
+
+    // 追加timestamp/recvWindow，按key排序后追加signature
+    pub fn sign(&self, params: &mut Vec<(&str, String)>) {
+        self.sign_at(params, time::get_current_milli_timestamp());
+    }
+
+    pub fn sign_at(&self, params: &mut Vec<(&str, String)>, timestamp: u64) {
+        params.push(("timestamp", timestamp.to_string()));
+        params.push(("recvWindow", self.recv_window_milli_secs.to_string()));
+
+        sort_params(params);
+
+        let signature = self.signature(encode_params(params).as_str());
+        params.push(("signature", signature));
+    }
+
+    pub fn attach_api_key(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        builder.header("X-MBX-APIKEY", self.api_key.as_str())
+    }
+}
@@ -0,0 +1,58 @@
+use super::signer::Signer;
+use crate::binance::utils::encode_params;
+
+// binance文档中的签名示例
+const DOC_API_KEY: &str = "vmPUZE6mv9SD5VNHk4HlWFsOr6aKE2zvsw0MuIgwCIPy6utIco14y7Ju91duEh8A";
+const DOC_SECRET_KEY: &str = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+
+#[test]
+fn test_signature_matches_doc_example() {
+    let signer = Signer::new(DOC_API_KEY.to_string(), DOC_SECRET_KEY.to_string());
+    let payload = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+    assert_eq!(
+        signer.signature(payload),
+        "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+    );
+}
+
+#[test]
+fn test_sign_params() {
+    let mut signer = Signer::new(DOC_API_KEY.to_string(), DOC_SECRET_KEY.to_string());
+    signer.set_recv_window(10000).unwrap();
+
+    let mut params = vec![
+        ("symbol", "LTCBTC".to_string()),
+        ("side", "BUY".to_string()),
+        ("quantity", "1".to_string()),
+    ];
+    signer.sign_at(&mut params, 1499827319559);
+
+    let (signature_key, signature) = params.pop().unwrap();
+    assert_eq!(signature_key, "signature");
+    let query = encode_params(&params);
+    assert_eq!(
+        query,
+        "quantity=1&recvWindow=10000&side=BUY&symbol=LTCBTC&timestamp=1499827319559"
+    );
+    assert_eq!(signature, signer.signature(&query));
+}
+
+#[test]
+fn test_set_recv_window_bounds() {
+    let mut signer = Signer::new(DOC_API_KEY.to_string(), DOC_SECRET_KEY.to_string());
+    assert_eq!(signer.recv_window(), 5000);
+    assert!(signer.set_recv_window(0).is_err());
+    assert!(signer.set_recv_window(60001).is_err());
+    assert!(signer.set_recv_window(60000).is_ok());
+    assert_eq!(signer.recv_window(), 60000);
+}
+
+#[test]
+fn test_attach_api_key() {
+    let signer = Signer::new(DOC_API_KEY.to_string(), DOC_SECRET_KEY.to_string());
+    let req = signer
+        .attach_api_key(reqwest::Client::new().get("http://localhost/api/v3/account"))
+        .build()
+        .unwrap();
+    assert_eq!(req.headers()["X-MBX-APIKEY"], DOC_API_KEY);
+}
@@ -3,13 +3,14 @@ use crate::binance::{
     spot::{
         models::{OrderType, TimeInForce},
         parser::{
-            parse_get_account, parse_get_all_orders, parse_get_open_orders, parse_get_order,
-            parse_get_trades, parse_place_order,
+            parse_cancel_replace_order, parse_get_account, parse_get_all_orders,
+            parse_get_open_orders, parse_get_order, parse_get_trades, parse_place_order,
+            parse_place_order_full, ApiErrorRaw,
         },
         requests::*,
         responses::*,
+        signer::Signer,
     },
-    utils::{encode_params, hmac_sha256, sort_params},
 };
 use log::error;
 use rate_limiter::RateLimiter;
@@ -20,8 +21,7 @@ pub struct TradeApi {
     base_url: String,
     proxy_url: Option<String>,
     rate_limiters: Option<Arc<Vec<RateLimiter>>>,
-    api_key: String,
-    secret_key: String,
+    signer: Signer,
     timeout_milli_secs: u64,
 }
 
@@ -39,12 +39,15 @@ impl TradeApi {
             base_url,
             proxy_url,
             rate_limiters: rate_limiters,
-            api_key,
-            secret_key,
+            signer: Signer::new(api_key, secret_key),
             timeout_milli_secs,
         }
     }
 
+    pub fn set_recv_window(&mut self, recv_window_milli_secs: u64) -> Result<()> {
+        self.signer.set_recv_window(recv_window_milli_secs)
+    }
+
     pub fn init(&mut self) -> Result<()> {
         let client_builder = reqwest::Client::builder();
 
@@ -52,7 +55,11 @@ impl TradeApi {
             client_builder
                 .proxy(reqwest::Proxy::all(proxy_url).map_err(|e| {
                     crate::binance::errors::BinanceError::ParametersInvalid {
-                        message: format!("proxy url invalid: {}, error: {}", proxy_url, e),
+                        message: format!(
+                            "proxy url invalid: {}, error: {}",
+                            ws::redact_url(proxy_url),
+                            e
+                        ),
                     }
                 })?)
                 .build()
@@ -60,7 +67,8 @@ impl TradeApi {
                     |e| crate::binance::errors::BinanceError::ParametersInvalid {
                         message: format!(
                             "build client with proxy url: {} failed: {}",
-                            proxy_url, e
+                            ws::redact_url(proxy_url),
+                            e
                         ),
                     },
                 )?
@@ -76,7 +84,15 @@ impl TradeApi {
         Ok(())
     }
 
-    pub async fn place_order(&self, req: PlaceOrderRequest) -> Result<PlaceOrderResponse> {
+    // 校验下单参数并生成请求参数，下单与撤单重下共用
+    fn place_order_params(req: &PlaceOrderRequest) -> Result<Vec<(&'static str, String)>> {
+        Self::place_order_params_with_resp_type(req, "ACK")
+    }
+
+    fn place_order_params_with_resp_type(
+        req: &PlaceOrderRequest,
+        resp_type: &str,
+    ) -> Result<Vec<(&'static str, String)>> {
         match &req.r#type {
             OrderType::Limit => {
                 if req.time_in_force.is_none() || req.price.is_none() || req.quantity.is_none() {
@@ -87,9 +103,11 @@ impl TradeApi {
                 }
             }
             OrderType::Market => {
-                if req.quantity.is_none() {
+                if req.quantity.is_some() == req.quote_order_qty.is_some() {
                     return Err(crate::binance::errors::BinanceError::ParametersInvalid {
-                        message: "quantity is required for MARKET order".to_string(),
+                        message:
+                            "exactly one of quantity, quote_order_qty is required for MARKET order"
+                                .to_string(),
                     });
                 }
             }
@@ -140,6 +158,16 @@ impl TradeApi {
                     });
                 }
             }
+            OrderType::Unknown(order_type) => {
+                return Err(crate::binance::errors::BinanceError::ParametersInvalid {
+                    message: format!("unsupported order type: {}", order_type),
+                });
+            }
+        }
+        if req.quote_order_qty.is_some() && !matches!(req.r#type, OrderType::Market) {
+            return Err(crate::binance::errors::BinanceError::ParametersInvalid {
+                message: "quote_order_qty is only valid for MARKET orders".to_string(),
+            });
         }
         if req.iceberg_qty.is_some() {
             if !matches!(req.r#type, OrderType::Limit | OrderType::LimitMaker) {
@@ -157,11 +185,11 @@ impl TradeApi {
             }
         }
 
-        let mut params = vec![
+        let mut params: Vec<(&'static str, String)> = vec![
             ("symbol", req.symbol.clone()),
             ("side", req.side.as_str().to_string()),
             ("type", req.r#type.as_str().to_string()),
-            ("newOrderRespType", "ACK".to_string()),
+            ("newOrderRespType", resp_type.to_string()),
         ];
         if req.time_in_force.is_some() {
             params.push((
@@ -187,6 +215,15 @@ impl TradeApi {
         if req.iceberg_qty.is_some() {
             params.push(("icebergQty", req.iceberg_qty.unwrap().to_string()));
         }
+        if let Some(quote_order_qty) = req.quote_order_qty {
+            params.push(("quoteOrderQty", quote_order_qty.to_string()));
+        }
+
+        Ok(params)
+    }
+
+    pub async fn place_order(&self, req: PlaceOrderRequest) -> Result<PlaceOrderResponse> {
+        let params = Self::place_order_params(&req)?;
 
         let text = self
             .send_signed_request(reqwest::Method::POST, "/api/v3/order", params, 1)
@@ -199,6 +236,19 @@ impl TradeApi {
         })
     }
 
+    // 返回订单最新状态及下单时立即成交的明细
+    pub async fn place_order_full(&self, req: PlaceOrderRequest) -> Result<PlaceOrderFullResponse> {
+        let params = Self::place_order_params_with_resp_type(&req, "FULL")?;
+
+        let text = self
+            .send_signed_request(reqwest::Method::POST, "/api/v3/order", params, 1)
+            .await?;
+
+        parse_place_order_full(req, &text).map_err(|e| BinanceError::ParseResultError {
+            message: format!("{}, {}", text, e),
+        })
+    }
+
     pub async fn cancel_order(&self, req: CancelOrderRequest) -> Result<CancelOrderResponse> {
         if req.order_id.is_none() && req.orig_client_order_id.is_none() {
             return Err(crate::binance::errors::BinanceError::ParametersInvalid {
@@ -229,6 +279,43 @@ impl TradeApi {
         Ok(())
     }
 
+    pub async fn cancel_replace_order(
+        &self,
+        req: CancelReplaceOrderRequest,
+    ) -> Result<CancelReplaceOrderResponse> {
+        if req.cancel_order_id.is_none() && req.cancel_orig_client_order_id.is_none() {
+            return Err(crate::binance::errors::BinanceError::ParametersInvalid {
+                message: "cancel_order_id or cancel_orig_client_order_id is required".to_string(),
+            });
+        }
+        let mut params = Self::place_order_params(&req.new_order)?;
+        params.push(("cancelReplaceMode", "STOP_ON_FAILURE".to_string()));
+        if let Some(cancel_order_id) = req.cancel_order_id {
+            params.push(("cancelOrderId", cancel_order_id.to_string()));
+        }
+        if let Some(cancel_orig_client_order_id) = &req.cancel_orig_client_order_id {
+            params.push((
+                "cancelOrigClientOrderId",
+                cancel_orig_client_order_id.clone(),
+            ));
+        }
+
+        let text = self
+            .send_signed_request(
+                reqwest::Method::POST,
+                "/api/v3/order/cancelReplace",
+                params,
+                1,
+            )
+            .await?;
+
+        parse_cancel_replace_order(req.new_order, &text).map_err(|e| {
+            crate::binance::errors::BinanceError::ParseResultError {
+                message: format!("{}, {}", text, e),
+            }
+        })
+    }
+
     pub async fn get_order(&self, req: GetOrderRequest) -> Result<GetOrderResponse> {
         if req.order_id.is_none() && req.orig_client_order_id.is_none() {
             return Err(crate::binance::errors::BinanceError::ParametersInvalid {
@@ -404,15 +491,8 @@ impl TradeApi {
         }
         let client = self.client.as_ref().unwrap();
 
-        // 添加默认窗口和时间戳参数
-        params.push(("timestamp", time::get_current_milli_timestamp().to_string()));
-        params.push(("recvWindow", "5000".to_string()));
-
-        sort_params(&mut params);
-
-        // 添加签名
-        let signature = hmac_sha256(&self.secret_key, encode_params(&params).as_str());
-        params.push(("signature", signature));
+        // 添加时间戳、窗口及签名
+        self.signer.sign(&mut params);
 
         if let Some(rate_limiters) = &self.rate_limiters {
             for rl in rate_limiters.iter() {
@@ -422,27 +502,26 @@ impl TradeApi {
 
         let resp = match method {
             reqwest::Method::GET => {
-                client
-                    .get(format!("{}{}", self.base_url, endpoint).as_str())
-                    .header("X-MBX-APIKEY", self.api_key.clone())
+                self.signer
+                    .attach_api_key(client.get(format!("{}{}", self.base_url, endpoint).as_str()))
                     .query(&params)
                     .timeout(Duration::from_millis(self.timeout_milli_secs))
                     .send()
                     .await
             }
             reqwest::Method::POST => {
-                client
-                    .post(format!("{}{}", self.base_url, endpoint).as_str())
-                    .header("X-MBX-APIKEY", self.api_key.clone())
+                self.signer
+                    .attach_api_key(client.post(format!("{}{}", self.base_url, endpoint).as_str()))
                     .query(&params)
                     .timeout(Duration::from_millis(self.timeout_milli_secs))
                     .send()
                     .await
             }
             reqwest::Method::DELETE => {
-                client
-                    .delete(format!("{}{}", self.base_url, endpoint).as_str())
-                    .header("X-MBX-APIKEY", self.api_key.clone())
+                self.signer
+                    .attach_api_key(
+                        client.delete(format!("{}{}", self.base_url, endpoint).as_str()),
+                    )
                     .query(&params)
                     .timeout(Duration::from_millis(self.timeout_milli_secs))
                     .send()
@@ -466,9 +545,7 @@ impl TradeApi {
                 "Response error: status: {}, text: {}. endpoint: {}, req: {:?}",
                 status, text, endpoint, params
             );
-            return Err(BinanceError::ParseResultError {
-                message: format!("status: {}, text: {}", status, text),
-            });
+            return Err(parse_api_error(status, &text));
         }
 
         let text =
@@ -481,3 +558,17 @@ impl TradeApi {
         Ok(text)
     }
 }
+
+// 错误响应体形如 {"code":-2010,"msg":"..."}，无法解析时保留原文
+fn parse_api_error(status: reqwest::StatusCode, text: &str) -> BinanceError {
+    match serde_json::from_str::<ApiErrorRaw>(text) {
+        Ok(raw) => BinanceError::ApiError {
+            status: status.as_u16(),
+            code: raw.code,
+            message: raw.msg,
+        },
+        Err(_) => BinanceError::ParseResultError {
+            message: format!("status: {}, text: {}", status, text),
+        },
+    }
+}
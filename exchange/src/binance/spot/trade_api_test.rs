@@ -1,7 +1,9 @@
 use super::super::consts::*;
-use super::models::{OrderType, Side, TimeInForce};
+use super::super::errors::BinanceError;
+use super::models::{OrderStatus, OrderType, Side, TimeInForce};
 use super::requests::trade::*;
 use super::trade_api::TradeApi;
+use crate::binance::test_utils::start_mock_server;
 use env_logger::Env;
 use rate_limiter::RateLimiter;
 use rust_decimal::Decimal;
@@ -43,6 +45,7 @@ async fn test_place_limit_order_parameter_validation() {
         new_client_order_id: None,
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -66,6 +69,7 @@ async fn test_place_market_order_parameter_validation() {
         new_client_order_id: None,
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -89,6 +93,7 @@ async fn test_place_stop_loss_order_parameter_validation() {
         new_client_order_id: None,
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -112,6 +117,7 @@ async fn test_place_stop_loss_limit_order_parameter_validation() {
         new_client_order_id: None,
         stop_price: Some(Decimal::from_str("29000.00").unwrap()),
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -135,6 +141,7 @@ async fn test_place_take_profit_order_parameter_validation() {
         new_client_order_id: None,
         stop_price: Some(Decimal::from_str("35000.00").unwrap()),
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -158,6 +165,7 @@ async fn test_place_take_profit_limit_order_parameter_validation() {
         new_client_order_id: None,
         stop_price: Some(Decimal::from_str("35000.00").unwrap()),
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -181,6 +189,7 @@ async fn test_place_limit_maker_order_parameter_validation() {
         new_client_order_id: None,
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -204,6 +213,7 @@ async fn test_iceberg_qty_validation() {
         new_client_order_id: None,
         stop_price: None,
         iceberg_qty: Some(Decimal::from_str("0.0001").unwrap()), // 无效的组合
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -227,6 +237,7 @@ async fn test_iceberg_qty_time_in_force_validation() {
         new_client_order_id: None,
         stop_price: None,
         iceberg_qty: Some(Decimal::from_str("0.0001").unwrap()),
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -236,6 +247,49 @@ async fn test_iceberg_qty_time_in_force_validation() {
     }
 }
 
+#[tokio::test]
+async fn test_quote_order_qty_validation() {
+    let trade_api = setup_test_trade_api();
+
+    // quantity与quote_order_qty不能同时指定
+    let req = PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: Side::Buy,
+        r#type: OrderType::Market,
+        time_in_force: None,
+        quantity: Some(Decimal::from_str("0.001").unwrap()),
+        price: None,
+        new_client_order_id: None,
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: Some(Decimal::from_str("10").unwrap()),
+    };
+    let result = trade_api.place_order(req).await;
+    assert!(matches!(
+        result,
+        Err(crate::binance::errors::BinanceError::ParametersInvalid { .. })
+    ));
+
+    // quote_order_qty仅用于MARKET订单
+    let req = PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: Side::Buy,
+        r#type: OrderType::Limit,
+        time_in_force: Some(TimeInForce::Gtc),
+        quantity: Some(Decimal::from_str("0.001").unwrap()),
+        price: Some(Decimal::from_str("30000.00").unwrap()),
+        new_client_order_id: None,
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: Some(Decimal::from_str("10").unwrap()),
+    };
+    let result = trade_api.place_order(req).await;
+    assert!(matches!(
+        result,
+        Err(crate::binance::errors::BinanceError::ParametersInvalid { .. })
+    ));
+}
+
 #[tokio::test]
 async fn test_place_valid_limit_order() {
     let trade_api = setup_test_trade_api();
@@ -251,6 +305,7 @@ async fn test_place_valid_limit_order() {
         new_client_order_id: Some(client_order_id.clone()),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -337,6 +392,7 @@ async fn test_place_valid_market_order() {
         )),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let result = trade_api.place_order(req).await;
@@ -387,6 +443,53 @@ async fn test_cancel_order_parameter_validation() {
     }
 }
 
+#[tokio::test]
+async fn test_cancel_replace_order_parameter_validation() {
+    let trade_api = setup_test_trade_api();
+    let new_order = || PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: Side::Buy,
+        r#type: OrderType::Limit,
+        time_in_force: Some(TimeInForce::Gtc),
+        quantity: Some(Decimal::from_str("0.001").unwrap()),
+        price: Some(Decimal::from_str("30000.00").unwrap()),
+        new_client_order_id: None,
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: None,
+    };
+
+    // 缺少待撤订单标识
+    let req = CancelReplaceOrderRequest {
+        cancel_order_id: None,
+        cancel_orig_client_order_id: None,
+        new_order: new_order(),
+    };
+    let result = trade_api.cancel_replace_order(req).await;
+    assert!(result.is_err());
+    if let Err(e) = result {
+        println!(
+            "Expected error for missing cancel order identification: {:?}",
+            e
+        );
+    }
+
+    // 新订单参数按下单规则校验
+    let req = CancelReplaceOrderRequest {
+        cancel_order_id: None,
+        cancel_orig_client_order_id: Some("test_order".to_string()),
+        new_order: PlaceOrderRequest {
+            price: None,
+            ..new_order()
+        },
+    };
+    let result = trade_api.cancel_replace_order(req).await;
+    assert!(result.is_err());
+    if let Err(e) = result {
+        println!("Expected error for missing new order price: {:?}", e);
+    }
+}
+
 #[tokio::test]
 async fn test_cancel_order_by_order_id() {
     let trade_api = setup_test_trade_api();
@@ -457,6 +560,7 @@ async fn test_get_open_orders() {
         )),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let _ = trade_api.place_order(req).await;
@@ -546,6 +650,7 @@ async fn test_get_account() {
         )),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let resp = trade_api.place_order(req).await.unwrap();
@@ -574,3 +679,169 @@ async fn test_get_account() {
         .await
         .unwrap();
 }
+
+// binance文档中 newOrderRespType=FULL 的下单响应
+const FULL_ORDER_RESPONSE: &str = r#"{
+    "symbol": "BTCUSDT",
+    "orderId": 28,
+    "orderListId": -1,
+    "clientOrderId": "6gCrw2kRUAF9CvJDGP16IP",
+    "transactTime": 1507725176595,
+    "price": "0.00000000",
+    "origQty": "10.00000000",
+    "executedQty": "10.00000000",
+    "origQuoteOrderQty": "0.000000",
+    "cummulativeQuoteQty": "10.00000000",
+    "status": "FILLED",
+    "timeInForce": "GTC",
+    "type": "MARKET",
+    "side": "SELL",
+    "workingTime": 1507725176595,
+    "selfTradePreventionMode": "NONE",
+    "fills": [
+        {"price": "4000.00000000", "qty": "1.00000000", "commission": "4.00000000", "commissionAsset": "USDT", "tradeId": 56},
+        {"price": "3999.00000000", "qty": "5.00000000", "commission": "19.99500000", "commissionAsset": "USDT", "tradeId": 57},
+        {"price": "3998.00000000", "qty": "2.00000000", "commission": "7.99600000", "commissionAsset": "USDT", "tradeId": 58},
+        {"price": "3997.00000000", "qty": "1.00000000", "commission": "3.99700000", "commissionAsset": "USDT", "tradeId": 59},
+        {"price": "3995.00000000", "qty": "1.00000000", "commission": "3.99500000", "commissionAsset": "USDT", "tradeId": 60}
+    ]
+}"#;
+
+fn market_sell_request() -> PlaceOrderRequest {
+    PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: Side::Sell,
+        r#type: OrderType::Market,
+        time_in_force: None,
+        quantity: Some(Decimal::from(10)),
+        price: None,
+        new_client_order_id: Some("6gCrw2kRUAF9CvJDGP16IP".to_string()),
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: None,
+    }
+}
+
+#[tokio::test]
+async fn test_place_order_full_response() {
+    let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = requests.clone();
+    let base_url = start_mock_server(move |request| {
+        recorded.lock().unwrap().push(request.to_string());
+        (200, FULL_ORDER_RESPONSE.to_string(), 0)
+    })
+    .await;
+
+    let mut api = TradeApi::new(
+        base_url,
+        None,
+        None,
+        "key".to_string(),
+        "secret".to_string(),
+        5000,
+    );
+    api.init().unwrap();
+    let resp = api.place_order_full(market_sell_request()).await.unwrap();
+
+    assert!(requests.lock().unwrap()[0].contains("newOrderRespType=FULL"));
+
+    let order = resp.order;
+    assert_eq!(order.order_id, 28);
+    assert!(matches!(order.order_status, OrderStatus::Filled));
+    assert_eq!(order.executed_qty, Decimal::from(10));
+    assert_eq!(order.cummulative_quote_qty, Decimal::from(10));
+    assert_eq!(order.update_time, 1507725176595);
+
+    assert_eq!(resp.fills.len(), 5);
+    let fill = &resp.fills[1];
+    assert_eq!(fill.trade_id, 57);
+    assert_eq!(fill.order_id, 28);
+    assert_eq!(fill.symbol, "BTCUSDT");
+    assert!(matches!(fill.order_side, Side::Sell));
+    assert_eq!(fill.trade_price, Decimal::from_str("3999").unwrap());
+    assert_eq!(fill.trade_quantity, Decimal::from(5));
+    assert_eq!(fill.commission, Decimal::from_str("19.995").unwrap());
+    assert_eq!(fill.commission_asset, "USDT");
+    assert!(!fill.is_maker);
+    assert_eq!(fill.timestamp, 1507725176595);
+    let filled: Decimal = resp.fills.iter().map(|f| f.trade_quantity).sum();
+    assert_eq!(filled, order.executed_qty);
+}
+
+#[tokio::test]
+async fn test_place_order_api_error() {
+    let base_url = start_mock_server(|_| {
+        (
+            400,
+            r#"{"code":-2010,"msg":"Account has insufficient balance for requested action."}"#
+                .to_string(),
+            0,
+        )
+    })
+    .await;
+
+    let mut api = TradeApi::new(
+        base_url,
+        None,
+        None,
+        "key".to_string(),
+        "secret".to_string(),
+        5000,
+    );
+    api.init().unwrap();
+    match api.place_order_full(market_sell_request()).await {
+        Err(BinanceError::ApiError {
+            status,
+            code,
+            message,
+        }) => {
+            assert_eq!(status, 400);
+            assert_eq!(code, -2010);
+            assert!(message.contains("insufficient balance"));
+        }
+        other => panic!("expect api error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_get_order_with_unknown_enum_values() {
+    let base_url = start_mock_server(|_| {
+        (
+            200,
+            r#"{"symbol":"BTCUSDT","orderId":1,"orderListId":-1,"clientOrderId":"test_order",
+            "price":"30000.00","origQty":"0.001","executedQty":"0","cummulativeQuoteQty":"0",
+            "status":"PENDING_REVIEW","timeInForce":"GTD","type":"LIMIT","side":"BUY",
+            "stopPrice":"0","icebergQty":"0","time":1000,"updateTime":1000,"isWorking":true,
+            "workingTime":1000,"origQuoteOrderQty":"0","selfTradePreventionMode":"NONE"}"#
+                .to_string(),
+            0,
+        )
+    })
+    .await;
+
+    let mut api = TradeApi::new(
+        base_url,
+        None,
+        None,
+        "key".to_string(),
+        "secret".to_string(),
+        5000,
+    );
+    api.init().unwrap();
+    let order = api
+        .get_order(GetOrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            order_id: Some(1),
+            orig_client_order_id: None,
+        })
+        .await
+        .unwrap();
+
+    // 未识别的取值保留原始字符串，不影响其他字段解析
+    assert!(matches!(&order.order_status, OrderStatus::Unknown(s) if s == "PENDING_REVIEW"));
+    assert_eq!(order.order_status.as_str(), "PENDING_REVIEW");
+    assert!(!order.order_status.is_final());
+    assert!(matches!(&order.time_in_force, TimeInForce::Unknown(s) if s == "GTD"));
+    assert!(matches!(order.order_type, OrderType::Limit));
+    assert_eq!(order.order_price, Decimal::from(30000));
+}
@@ -5,8 +5,8 @@ use crate::binance::{
         parser::{AccountUpdateRaw, CancelOrderStreamRaw, PlaceOrderStreamRaw},
         requests::{CancelOrderRequest, PlaceOrderRequest},
         responses::{CancelOrderResponse, PlaceOrderResponse},
+        signer::Signer,
     },
-    utils::{encode_params, hmac_sha256, sort_params},
 };
 use log::error;
 use rand::{distr::Alphanumeric, Rng};
@@ -21,8 +21,7 @@ pub struct TradeStream {
     url: String,
     proxy_url: Option<String>,
     rate_limiters: Option<Arc<Vec<RateLimiter>>>,
-    api_key: String,
-    secret_key: String,
+    signer: Signer,
 
     execution_report_cb: Option<Arc<dyn Fn(ExecutionReport) -> Fut + Send + Sync + 'static>>,
     outbound_account_position_cb:
@@ -43,14 +42,17 @@ impl TradeStream {
             url,
             proxy_url,
             rate_limiters,
-            api_key,
-            secret_key,
+            signer: Signer::new(api_key, secret_key),
             execution_report_cb: None,
             outbound_account_position_cb: None,
             client: None,
         }
     }
 
+    pub fn set_recv_window(&mut self, recv_window_milli_secs: u64) -> Result<()> {
+        self.signer.set_recv_window(recv_window_milli_secs)
+    }
+
     pub fn register_execution_report_callback<F>(&mut self, cb: F)
     where
         F: Fn(ExecutionReport) -> Fut + Send + Sync + 'static,
@@ -85,14 +87,12 @@ impl TradeStream {
 
         let mut ws_client = ws::Client::new(config).map_err(|e| {
             error!("WebSocket client error: {:?}", e);
-            BinanceError::ExternalError(Box::new(e))
+            BinanceError::WsError(e)
         })?;
 
         ws_client.connect().await.map_err(|e| {
             error!("WebSocket connect error: {:?}", e);
-            BinanceError::NetworkError {
-                message: e.to_string(),
-            }
+            BinanceError::WsError(e)
         })?;
 
         let msg_id = Self::rand_id();
@@ -113,9 +113,7 @@ impl TradeStream {
             .await
             .map_err(|e| {
                 error!("WebSocket send subscribe message error: {:?}", e);
-                BinanceError::NetworkError {
-                    message: e.to_string(),
-                }
+                BinanceError::WsError(e)
             })?;
         #[derive(Debug, Deserialize)]
         struct Resp {
@@ -179,9 +177,11 @@ impl TradeStream {
                 }
             }
             OrderType::Market => {
-                if req.quantity.is_none() {
+                if req.quantity.is_some() == req.quote_order_qty.is_some() {
                     return Err(crate::binance::errors::BinanceError::ParametersInvalid {
-                        message: "quantity is required for MARKET order".to_string(),
+                        message:
+                            "exactly one of quantity, quote_order_qty is required for MARKET order"
+                                .to_string(),
                     });
                 }
             }
@@ -232,6 +232,16 @@ impl TradeStream {
                     });
                 }
             }
+            OrderType::Unknown(order_type) => {
+                return Err(crate::binance::errors::BinanceError::ParametersInvalid {
+                    message: format!("unsupported order type: {}", order_type),
+                });
+            }
+        }
+        if req.quote_order_qty.is_some() && !matches!(req.r#type, OrderType::Market) {
+            return Err(crate::binance::errors::BinanceError::ParametersInvalid {
+                message: "quote_order_qty is only valid for MARKET orders".to_string(),
+            });
         }
         if req.iceberg_qty.is_some() {
             if !matches!(req.r#type, OrderType::Limit | OrderType::LimitMaker) {
@@ -279,6 +289,9 @@ impl TradeStream {
         if req.iceberg_qty.is_some() {
             params.push(("icebergQty", req.iceberg_qty.unwrap().to_string()));
         }
+        if let Some(quote_order_qty) = req.quote_order_qty {
+            params.push(("quoteOrderQty", quote_order_qty.to_string()));
+        }
 
         self.sign_params(&mut params);
 
@@ -301,9 +314,7 @@ impl TradeStream {
             .await
             .map_err(|e| {
                 error!("WebSocket send place order message error: {:?}", e);
-                BinanceError::NetworkError {
-                    message: e.to_string(),
-                }
+                BinanceError::WsError(e)
             })?;
 
         match recv_msg {
@@ -314,6 +325,13 @@ impl TradeStream {
                         message: e.to_string(),
                     }
                 })?;
+                if let Some(error) = resp.error {
+                    return Err(BinanceError::ApiError {
+                        status: resp.status as u16,
+                        code: error.code,
+                        message: error.msg,
+                    });
+                }
                 if resp.status != 200 {
                     return Err(BinanceError::NetworkError {
                         message: format!("Place order failed: {:?}", content),
@@ -384,9 +402,7 @@ impl TradeStream {
             .await
             .map_err(|e| {
                 error!("WebSocket send cancel order message error: {:?}", e);
-                BinanceError::NetworkError {
-                    message: e.to_string(),
-                }
+                BinanceError::WsError(e)
             })?;
 
         match recv_msg {
@@ -397,6 +413,13 @@ impl TradeStream {
                         message: e.to_string(),
                     }
                 })?;
+                if let Some(error) = resp.error {
+                    return Err(BinanceError::ApiError {
+                        status: resp.status as u16,
+                        code: error.code,
+                        message: error.msg,
+                    });
+                }
                 if resp.status != 200 {
                     return Err(BinanceError::NetworkError {
                         message: format!("Cancel order failed: {:?}", content),
@@ -495,14 +518,7 @@ impl TradeStream {
     }
 
     fn sign_params(&self, params: &mut Vec<(&str, String)>) {
-        params.push(("apiKey", self.api_key.clone()));
-        params.push(("timestamp", time::get_current_milli_timestamp().to_string()));
-        params.push(("recvWindow", "5000".to_string()));
-
-        sort_params(params);
-
-        // 添加签名
-        let signature = hmac_sha256(&self.secret_key, encode_params(&params).as_str());
-        params.push(("signature", signature));
+        params.push(("apiKey", self.signer.api_key().to_string()));
+        self.signer.sign(params);
     }
 }
@@ -71,6 +71,7 @@ async fn test_trade_stream() {
         )),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
     let _ = trade_stream.place_order(req).await;
     let req = PlaceOrderRequest {
@@ -86,6 +87,7 @@ async fn test_trade_stream() {
         )),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
     let resp = trade_stream.place_order(req).await.unwrap();
     let req = CancelOrderRequest {
@@ -12,6 +12,30 @@ pub fn encode_params<T: ToString>(params: &Vec<(&str, T)>) -> String {
         .join("&")
 }
 
+// 日志及错误信息中需隐藏的参数
+const SENSITIVE_PARAMS: [&str; 3] = ["signature", "apiKey", "listenKey"];
+
+pub fn redact_params<'a, T: ToString>(params: &[(&'a str, T)]) -> Vec<(&'a str, String)> {
+    params
+        .iter()
+        .map(|(k, v)| {
+            if SENSITIVE_PARAMS.contains(k) {
+                (*k, "REDACTED".to_string())
+            } else {
+                (*k, v.to_string())
+            }
+        })
+        .collect()
+}
+
+// 按字符截断，超出部分以省略号及总长度标记
+pub fn truncate_body(text: &str, max_len: usize) -> String {
+    match text.char_indices().nth(max_len) {
+        None => text.to_string(),
+        Some((idx, _)) => format!("{}...({} bytes)", &text[..idx], text.len()),
+    }
+}
+
 pub fn hmac_sha256(key: &str, data: &str) -> String {
     use hmac::{Hmac, Mac};
     use sha2::Sha256;
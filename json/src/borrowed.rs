@@ -0,0 +1,403 @@
+use crate::error::{JsonError, Result};
+use serde_json::{Map, Number, Value};
+use std::borrow::Cow;
+
+// 嵌套层数上限，与serde_json默认的递归限制一致，避免恶意输入导致栈溢出
+const MAX_DEPTH: usize = 128;
+
+// 借用输入缓冲区的JSON值，用于成交/深度推送等高频解析路径
+// 字符串与数字叶子直接引用输入中的片段，仅当字符串含转义时才分配；数字保留原始文本，按需解析
+// 生命周期'a绑定输入字符串：ValueRef不能比输入活得更久，需要跨帧保存时先调用to_value转为owned
+// 数字的严格处理：NaN/Infinity字面量及超出f64范围的小数（如1e400）解析失败，返回NonFiniteNumber；
+// 超出i64与u64范围的整数解析为BigInt保留原始文本，不截断、不转为f64
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueRef<'a> {
+    Null,
+    Bool(bool),
+    Number(&'a str),
+    BigInt(&'a str),
+    String(Cow<'a, str>),
+    Array(Vec<ValueRef<'a>>),
+    Object(Vec<(Cow<'a, str>, ValueRef<'a>)>), // 保持输入中的字段顺序
+}
+
+impl<'a> ValueRef<'a> {
+    // 对象按key取值，重复key时取第一个
+    pub fn get(&self, key: &str) -> Option<&ValueRef<'a>> {
+        match self {
+            ValueRef::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn get_index(&self, index: usize) -> Option<&ValueRef<'a>> {
+        match self {
+            ValueRef::Array(values) => values.get(index),
+            _ => None,
+        }
+    }
+
+    // 与ValuePath::path相同的路径规则，如 "b.0.1"
+    pub fn path(&self, path: &str) -> Option<&ValueRef<'a>> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        path.split('.')
+            .try_fold(self, |value, segment| match value {
+                ValueRef::Object(_) => value.get(segment),
+                ValueRef::Array(_) => value.get_index(segment.parse::<usize>().ok()?),
+                _ => None,
+            })
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ValueRef::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    // 数字的原始文本，包括BigInt
+    pub fn as_number_str(&self) -> Option<&'a str> {
+        match self {
+            ValueRef::Number(n) | ValueRef::BigInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number_str()?.parse().ok()
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number_str()?.parse().ok()
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number_str()?
+            .parse::<f64>()
+            .ok()
+            .filter(|v| v.is_finite())
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ValueRef::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[ValueRef<'a>]> {
+        match self {
+            ValueRef::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, ValueRef::Null)
+    }
+
+    // 转为owned的serde_json::Value，BigInt转为保留原始文本的Value::String
+    pub fn to_value(&self) -> Result<Value> {
+        Ok(match self {
+            ValueRef::Null => Value::Null,
+            ValueRef::Bool(b) => Value::Bool(*b),
+            ValueRef::Number(n) => {
+                Value::Number(n.parse::<Number>().map_err(JsonError::SerdeError)?)
+            }
+            ValueRef::BigInt(n) => Value::String(n.to_string()),
+            ValueRef::String(s) => Value::String(s.to_string()),
+            ValueRef::Array(values) => Value::Array(
+                values
+                    .iter()
+                    .map(|v| v.to_value())
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            ValueRef::Object(fields) => {
+                let mut map = Map::new();
+                for (k, v) in fields {
+                    map.insert(k.to_string(), v.to_value()?);
+                }
+                Value::Object(map)
+            }
+        })
+    }
+}
+
+// 解析为借用输入的ValueRef，不含转义的字符串与所有数字均不分配内存
+pub fn loads_borrowed<'a>(s: &'a str) -> Result<ValueRef<'a>> {
+    let mut parser = Parser { input: s, pos: 0 };
+    let value = parser.parse_value(0)?;
+    parser.skip_whitespace();
+    if parser.pos != s.len() {
+        return Err(parser.error("trailing characters"));
+    }
+    Ok(value)
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> JsonError {
+        JsonError::ParseError {
+            offset: self.pos,
+            message: message.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_literal(&mut self, literal: &str) -> Result<()> {
+        if self.input[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(())
+        } else {
+            Err(self.error("invalid literal"))
+        }
+    }
+
+    fn parse_value(&mut self, depth: usize) -> Result<ValueRef<'a>> {
+        if depth > MAX_DEPTH {
+            return Err(self.error("recursion limit exceeded"));
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(self.error("unexpected end of input")),
+            Some(b'n') => self.expect_literal("null").map(|_| ValueRef::Null),
+            Some(b't') => self.expect_literal("true").map(|_| ValueRef::Bool(true)),
+            Some(b'f') => self.expect_literal("false").map(|_| ValueRef::Bool(false)),
+            Some(b'"') => self.parse_string().map(ValueRef::String),
+            Some(b'[') => self.parse_array(depth),
+            Some(b'{') => self.parse_object(depth),
+            Some(b'-' | b'0'..=b'9') => self.parse_number(),
+            Some(b'N' | b'I' | b'+') => Err(self.non_finite_error()),
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    fn parse_digits(&mut self) -> usize {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    // -?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?
+    fn parse_number(&mut self) -> Result<ValueRef<'a>> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+            if self.peek() == Some(b'I') {
+                self.pos = start;
+                return Err(self.non_finite_error());
+            }
+        }
+        match self.peek() {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => {
+                self.parse_digits();
+            }
+            _ => return Err(self.error("invalid number")),
+        }
+        let mut is_integer = true;
+        if self.peek() == Some(b'.') {
+            is_integer = false;
+            self.pos += 1;
+            if self.parse_digits() == 0 {
+                return Err(self.error("invalid number"));
+            }
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            is_integer = false;
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+            if self.parse_digits() == 0 {
+                return Err(self.error("invalid number"));
+            }
+        }
+
+        let text = &self.input[start..self.pos];
+        if is_integer {
+            // 19位以内的整数必然在i64范围内，无需解析
+            if text.len() >= 19 && text.parse::<i64>().is_err() && text.parse::<u64>().is_err() {
+                return Ok(ValueRef::BigInt(text));
+            }
+        } else if !text.parse::<f64>().is_ok_and(|v| v.is_finite()) {
+            return Err(JsonError::NonFiniteNumber {
+                offset: start,
+                literal: text.to_string(),
+            });
+        }
+        Ok(ValueRef::Number(text))
+    }
+
+    // NaN/Infinity等非标准字面量
+    fn non_finite_error(&self) -> JsonError {
+        let rest = &self.input[self.pos..];
+        let literal = ["NaN", "Infinity", "-Infinity", "+Infinity"]
+            .into_iter()
+            .find(|literal| rest.starts_with(literal));
+        match literal {
+            Some(literal) => JsonError::NonFiniteNumber {
+                offset: self.pos,
+                literal: literal.to_string(),
+            },
+            None => self.error("unexpected character"),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<Cow<'a, str>> {
+        self.pos += 1; // 跳过起始引号
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    let s = &self.input[start..self.pos];
+                    self.pos += 1;
+                    return Ok(Cow::Borrowed(s));
+                }
+                Some(b'\\') => break,
+                Some(0x00..=0x1f) => return Err(self.error("control character in string")),
+                Some(_) => self.pos += 1,
+            }
+        }
+
+        // 含转义时才分配
+        let mut owned = self.input[start..self.pos].to_string();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(Cow::Owned(owned));
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escaped = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    match escaped {
+                        b'"' => owned.push('"'),
+                        b'\\' => owned.push('\\'),
+                        b'/' => owned.push('/'),
+                        b'b' => owned.push('\u{8}'),
+                        b'f' => owned.push('\u{c}'),
+                        b'n' => owned.push('\n'),
+                        b'r' => owned.push('\r'),
+                        b't' => owned.push('\t'),
+                        b'u' => owned.push(self.parse_unicode_escape()?),
+                        _ => return Err(self.error("invalid escape")),
+                    }
+                }
+                Some(0x00..=0x1f) => return Err(self.error("control character in string")),
+                Some(_) => {
+                    // 按字符拷贝，保证多字节字符完整
+                    let ch = self.input[self.pos..].chars().next().unwrap();
+                    owned.push(ch);
+                    self.pos += ch.len_utf8();
+                }
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32> {
+        let hex = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        let code =
+            u32::from_str_radix(hex, 16).map_err(|_| self.error("invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(code)
+    }
+
+    // \uXXXX，代理对需连续出现
+    fn parse_unicode_escape(&mut self) -> Result<char> {
+        let high = self.parse_hex4()?;
+        let code = if (0xD800..0xDC00).contains(&high) {
+            if !self.input[self.pos..].starts_with("\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let low = self.parse_hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn parse_array(&mut self, depth: usize) -> Result<ValueRef<'a>> {
+        self.pos += 1;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(ValueRef::Array(values));
+        }
+        loop {
+            values.push(self.parse_value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(ValueRef::Array(values));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self, depth: usize) -> Result<ValueRef<'a>> {
+        self.pos += 1;
+        let mut fields = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(ValueRef::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected object key"));
+            }
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            if self.peek() != Some(b':') {
+                return Err(self.error("expected ':'"));
+            }
+            self.pos += 1;
+            fields.push((key, self.parse_value(depth + 1)?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(ValueRef::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use crate::borrowed::{ValueRef, loads_borrowed};
+use crate::error::JsonError;
+use crate::json::loads;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::time::Instant;
+
+// 深度增量推送帧
+fn depth_frame() -> String {
+    let levels = |base: u64| {
+        (0..20)
+            .map(|i| format!(r#"["{}.{:02}","{}.00010000"]"#, base - i, i, i + 1))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    format!(
+        r#"{{"stream":"btcusdt@depth@100ms","data":{{"e":"depthUpdate","E":1700000000123,"s":"BTCUSDT","U":157,"u":160,"b":[{}],"a":[{}]}}}}"#,
+        levels(30000),
+        levels(30100)
+    )
+}
+
+// 统计分配了内存的字符串（key与字符串值）
+fn owned_strings(value: &ValueRef) -> usize {
+    match value {
+        ValueRef::String(Cow::Owned(_)) => 1,
+        ValueRef::Array(values) => values.iter().map(owned_strings).sum(),
+        ValueRef::Object(fields) => fields
+            .iter()
+            .map(|(k, v)| matches!(k, Cow::Owned(_)) as usize + owned_strings(v))
+            .sum(),
+        _ => 0,
+    }
+}
+
+fn owned_value_strings(value: &Value) -> usize {
+    match value {
+        Value::String(_) => 1,
+        Value::Array(values) => values.iter().map(owned_value_strings).sum(),
+        Value::Object(map) => map.iter().map(|(_, v)| 1 + owned_value_strings(v)).sum(),
+        _ => 0,
+    }
+}
+
+fn assert_borrowed_from(value: &ValueRef, input: &str) {
+    let range = input.as_ptr() as usize..input.as_ptr() as usize + input.len();
+    let within = |s: &str| range.contains(&(s.as_ptr() as usize));
+    match value {
+        ValueRef::Number(n) | ValueRef::BigInt(n) => assert!(within(n)),
+        ValueRef::String(s) => assert!(within(s)),
+        ValueRef::Array(values) => values.iter().for_each(|v| assert_borrowed_from(v, input)),
+        ValueRef::Object(fields) => fields.iter().for_each(|(k, v)| {
+            assert!(within(k));
+            assert_borrowed_from(v, input);
+        }),
+        _ => {}
+    }
+}
+
+#[test]
+fn test_borrowed_depth_frame_matches_owned() {
+    let frame = depth_frame();
+    let borrowed = loads_borrowed(&frame).unwrap();
+    let owned: Value = loads(&frame).unwrap();
+    assert_eq!(borrowed.to_value().unwrap(), owned);
+
+    // 叶子均引用输入缓冲区，不分配字符串
+    assert_borrowed_from(&borrowed, &frame);
+    assert_eq!(owned_strings(&borrowed), 0);
+    assert!(owned_value_strings(&owned) > 80);
+
+    let data = borrowed.get("data").unwrap();
+    assert_eq!(
+        data.path("E").and_then(ValueRef::as_u64),
+        Some(1700000000123)
+    );
+    assert_eq!(data.path("s").and_then(ValueRef::as_str), Some("BTCUSDT"));
+    assert_eq!(
+        data.path("b.1.0").and_then(ValueRef::as_str),
+        Some("29999.01")
+    );
+    assert_eq!(
+        data.path("a").and_then(ValueRef::as_array).map(|a| a.len()),
+        Some(20)
+    );
+    assert_eq!(data.path("b.20"), None);
+    assert_eq!(data.path("s.0"), None);
+}
+
+#[test]
+fn test_borrowed_scalars_and_escapes() {
+    let input = r#"{"n": null, "t": true, "f": false, "i": -12, "x": 1.5e-3,
+        "esc": "a\"b\\c\/\né😀", "plain": "中文", "e": [], "o": {}}"#;
+    let value = loads_borrowed(input).unwrap();
+    assert_eq!(value.to_value().unwrap(), loads::<Value>(input).unwrap());
+
+    assert!(value.get("n").unwrap().is_null());
+    assert_eq!(value.get("t").and_then(ValueRef::as_bool), Some(true));
+    assert_eq!(value.get("i").and_then(ValueRef::as_i64), Some(-12));
+    assert_eq!(value.get("i").and_then(ValueRef::as_u64), None);
+    assert_eq!(
+        value.get("x").and_then(ValueRef::as_number_str),
+        Some("1.5e-3")
+    );
+    assert_eq!(value.get("x").and_then(ValueRef::as_f64), Some(0.0015));
+    // 仅含转义的字符串分配内存
+    assert_eq!(
+        value.get("esc"),
+        Some(&ValueRef::String(Cow::Owned("a\"b\\c/\né😀".to_string())))
+    );
+    assert_eq!(
+        value.get("plain"),
+        Some(&ValueRef::String(Cow::Borrowed("中文")))
+    );
+    assert_eq!(owned_strings(&value), 1);
+}
+
+#[test]
+fn test_borrowed_invalid_input() {
+    for input in [
+        "",
+        "{",
+        r#"{"a" 1}"#,
+        r#"{"a": 1,}"#,
+        "[1, 2",
+        "01",
+        "-",
+        "1.",
+        "1e",
+        "tru",
+        r#""abc"#,
+        r#""\x""#,
+        r#""\ud83d""#,
+        "\"a\nb\"",
+        "[1] 2",
+        &"[".repeat(200),
+    ] {
+        assert!(loads_borrowed(input).is_err(), "input: {}", input);
+    }
+}
+
+#[test]
+fn test_borrowed_non_finite() {
+    for (input, literal, offset) in [
+        ("1e400", "1e400", 0),
+        ("[1, -1.5e400]", "-1.5e400", 4),
+        ("NaN", "NaN", 0),
+        ("Infinity", "Infinity", 0),
+        ("{\"p\": -Infinity}", "-Infinity", 6),
+        ("[+Infinity]", "+Infinity", 1),
+    ] {
+        match loads_borrowed(input) {
+            Err(JsonError::NonFiniteNumber {
+                offset: o,
+                literal: l,
+            }) => {
+                assert_eq!(l, literal, "input: {}", input);
+                assert_eq!(o, offset, "input: {}", input);
+            }
+            other => panic!("input: {}, unexpected: {:?}", input, other),
+        }
+    }
+    // 其他非法字面量仍为ParseError
+    assert!(matches!(
+        loads_borrowed("Nope"),
+        Err(JsonError::ParseError { .. })
+    ));
+    // 边界内的小数正常解析
+    assert_eq!(loads_borrowed("1e300").unwrap().as_f64(), Some(1e300));
+}
+
+#[test]
+fn test_borrowed_big_int() {
+    let digits = "123456789012345678901234567890";
+    let value = loads_borrowed(digits).unwrap();
+    assert_eq!(value, ValueRef::BigInt(digits));
+    assert_eq!(value.as_number_str(), Some(digits));
+    assert_eq!(value.as_i64(), None);
+    assert_eq!(value.as_u64(), None);
+    assert_eq!(value.to_value().unwrap(), Value::String(digits.to_string()));
+
+    let input = format!("{{\"id\": -{}}}", digits);
+    let value = loads_borrowed(&input).unwrap();
+    assert_eq!(
+        value.path("id").unwrap().to_value().unwrap(),
+        Value::String(format!("-{}", digits))
+    );
+
+    // i64/u64边界内仍为Number
+    for input in [
+        "9223372036854775807",
+        "-9223372036854775808",
+        "18446744073709551615",
+    ] {
+        let value = loads_borrowed(input).unwrap();
+        assert_eq!(value, ValueRef::Number(input));
+        assert!(value.to_value().unwrap().is_number());
+    }
+    assert_eq!(
+        loads_borrowed("18446744073709551616").unwrap(),
+        ValueRef::BigInt("18446744073709551616")
+    );
+    assert_eq!(
+        loads_borrowed("-9223372036854775809").unwrap(),
+        ValueRef::BigInt("-9223372036854775809")
+    );
+}
+
+// cargo test -p json --release -- --ignored bench_depth_frame --nocapture
+#[test]
+#[ignore]
+fn bench_depth_frame() {
+    let frame = depth_frame();
+    let rounds = 20000;
+    let start = Instant::now();
+    for _ in 0..rounds {
+        std::hint::black_box(loads::<Value>(&frame).unwrap());
+    }
+    let owned = start.elapsed();
+    let start = Instant::now();
+    for _ in 0..rounds {
+        std::hint::black_box(loads_borrowed(&frame).unwrap());
+    }
+    let borrowed = start.elapsed();
+    println!(
+        "depth frame x{}: owned {:?}, borrowed {:?}",
+        rounds, owned, borrowed
+    );
+}
@@ -7,6 +7,12 @@ pub enum JsonError {
 
     #[error(transparent)]
     SerdeError(#[from] serde_json::Error),
+
+    #[error("parse error at byte {offset}: {message}")]
+    ParseError { offset: usize, message: String },
+
+    #[error("non-finite number {literal} at byte {offset} is not allowed")]
+    NonFiniteNumber { offset: usize, literal: String },
 }
 
 pub type Result<T> = std::result::Result<T, JsonError>;
@@ -1,6 +1,7 @@
 use crate::error::{JsonError, Result};
 use serde::de::DeserializeOwned;
 use serde::ser::Serialize;
+use serde_json::Value;
 use std::fs::File;
 
 pub fn dump<T: Serialize>(value: &T, filepath: &str) -> Result<()> {
@@ -13,12 +14,67 @@ pub fn dumps<T: Serialize>(value: &T) -> Result<String> {
     serde_json::to_string_pretty(value).map_err(|e| JsonError::SerdeError(e))
 }
 
+// 确定性输出，用于请求体签名/哈希与golden文件比对：紧凑格式，对象key按字节序排序（含嵌套对象），
+// 数字规范化：整数值的浮点数（如1.0、-0.0）按整数输出，其余浮点数使用最短往返表示
+pub fn dumps_canonical<T: Serialize>(value: &T) -> Result<String> {
+    let value = serde_json::to_value(value).map_err(JsonError::SerdeError)?;
+    let mut out = String::new();
+    write_canonical(&value, &mut out)?;
+    Ok(out)
+}
+
+// 2^53以内的浮点数可精确表示为整数
+const MAX_SAFE_INTEGER: f64 = 9007199254740992.0;
+
+fn write_canonical(value: &Value, out: &mut String) -> Result<()> {
+    match value {
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if n.is_f64() && f.fract() == 0.0 && f.abs() <= MAX_SAFE_INTEGER => {
+                out.push_str(&(f as i64).to_string())
+            }
+            _ => out.push_str(&n.to_string()),
+        },
+        Value::String(s) => {
+            out.push_str(&serde_json::to_string(s).map_err(JsonError::SerdeError)?);
+        }
+        Value::Array(values) => {
+            out.push('[');
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(v, out)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            // 不依赖Map的迭代顺序（开启preserve_order时为插入序）
+            let mut fields: Vec<(&String, &Value)> = map.iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (k, v)) in fields.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::to_string(k).map_err(JsonError::SerdeError)?);
+                out.push(':');
+                write_canonical(v, out)?;
+            }
+            out.push('}');
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+    Ok(())
+}
+
 pub fn load<T: DeserializeOwned>(filepath: &str) -> Result<T> {
     let file = File::open(filepath).map_err(|e| JsonError::IOError(e))?;
     let data = serde_json::from_reader(file).map_err(|e| JsonError::SerdeError(e))?;
     Ok(data)
 }
 
+// 基于serde_json：NaN/Infinity字面量及超出f64范围的数字返回SerdeError；
+// 解析为Value时超出i64/u64范围的整数会转为f64丢失精度，需保留原值时使用loads_borrowed
 pub fn loads<T: DeserializeOwned>(s: &str) -> Result<T> {
     let data = serde_json::from_str(s).map_err(|e| JsonError::SerdeError(e))?;
     Ok(data)
@@ -1,4 +1,6 @@
-use crate::json::{dump, dumps, load, loads};
+use crate::json::{dump, dumps, dumps_canonical, load, loads};
+use serde_json::json;
+use std::collections::HashMap;
 use std::fs;
 
 #[test]
@@ -51,3 +53,56 @@ fn test_loads() {
     assert!(result.is_ok());
     assert_eq!(result.unwrap(), data);
 }
+
+#[test]
+fn test_loads_non_finite() {
+    for input in ["NaN", "Infinity", "-Infinity", "1e400", "[1, -1e400]"] {
+        assert!(
+            loads::<serde_json::Value>(input).is_err(),
+            "input: {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_dumps_canonical_sorted_keys() {
+    let mut a = HashMap::new();
+    let mut b = HashMap::new();
+    for (k, v) in [("symbol", "BTCUSDT"), ("side", "BUY"), ("quantity", "0.1")] {
+        a.insert(k, v);
+    }
+    for (k, v) in [("quantity", "0.1"), ("symbol", "BTCUSDT"), ("side", "BUY")] {
+        b.insert(k, v);
+    }
+    let expected = r#"{"quantity":"0.1","side":"BUY","symbol":"BTCUSDT"}"#;
+    assert_eq!(dumps_canonical(&a).unwrap(), expected);
+    assert_eq!(dumps_canonical(&b).unwrap(), expected);
+}
+
+#[test]
+fn test_dumps_canonical_nested() {
+    let mut params = HashMap::new();
+    params.insert("y", json!({"d": 1, "c": [{"f": 2, "e": 3}]}));
+    params.insert("x", json!("\u{1}\"q\""));
+    let mut req = HashMap::new();
+    req.insert("z", json!(1));
+    req.insert("params", json!(params));
+    req.insert("a", json!([{"b": null, "a": true}]));
+    assert_eq!(
+        dumps_canonical(&req).unwrap(),
+        r#"{"a":[{"a":true,"b":null}],"params":{"x":"\u0001\"q\"","y":{"c":[{"e":3,"f":2}],"d":1}},"z":1}"#
+    );
+}
+
+#[test]
+fn test_dumps_canonical_numbers() {
+    assert_eq!(
+        dumps_canonical(&json!([1.0, -0.0, 2.5, 100, -7, 1e300, 0.1])).unwrap(),
+        "[1,0,2.5,100,-7,1e300,0.1]"
+    );
+    // 同一逻辑值的不同写法输出一致
+    let a: serde_json::Value = loads(r#"{"p": 10.0, "q": 1E1}"#).unwrap();
+    let b: serde_json::Value = loads(r#"{"q": 10, "p": 10}"#).unwrap();
+    assert_eq!(dumps_canonical(&a).unwrap(), dumps_canonical(&b).unwrap());
+}
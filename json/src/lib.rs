@@ -1,7 +1,15 @@
+pub mod borrowed;
 pub mod error;
 pub mod json;
+pub mod value;
+pub use borrowed::{ValueRef, loads_borrowed};
 pub use error::{JsonError, Result};
-pub use json::{dump, dumps, load, loads};
+pub use json::{dump, dumps, dumps_canonical, load, loads};
+pub use value::{Value, ValuePath};
 
+#[cfg(test)]
+mod borrowed_test;
 #[cfg(test)]
 mod json_test;
+#[cfg(test)]
+mod value_test;
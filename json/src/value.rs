@@ -0,0 +1,21 @@
+pub use serde_json::Value;
+
+// 按路径取嵌套字段，如 "a.b.0.c"：对象按key取值，数组按下标取值
+// 任一段不存在、下标越界或类型不匹配时返回None，空路径返回自身
+pub trait ValuePath {
+    fn path(&self, path: &str) -> Option<&Value>;
+}
+
+impl ValuePath for Value {
+    fn path(&self, path: &str) -> Option<&Value> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        path.split('.')
+            .try_fold(self, |value, segment| match value {
+                Value::Object(map) => map.get(segment),
+                Value::Array(values) => values.get(segment.parse::<usize>().ok()?),
+                _ => None,
+            })
+    }
+}
@@ -0,0 +1,55 @@
+use crate::json::loads;
+use crate::value::{Value, ValuePath};
+
+fn document() -> Value {
+    loads(
+        r#"{
+            "symbol": "BTCUSDT",
+            "filters": [
+                {"filterType": "PRICE_FILTER", "tickSize": "0.01"},
+                {"filterType": "LOT_SIZE", "stepSize": "0.00001", "limits": [1, 2]}
+            ],
+            "rateLimits": {"0": {"limit": 6000}},
+            "empty": []
+        }"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_path_present() {
+    let doc = document();
+    assert_eq!(doc.path(""), Some(&doc));
+    assert_eq!(doc.path("symbol").and_then(Value::as_str), Some("BTCUSDT"));
+    assert_eq!(
+        doc.path("filters.1.stepSize").and_then(Value::as_str),
+        Some("0.00001")
+    );
+    assert_eq!(
+        doc.path("filters.1.limits.1").and_then(Value::as_u64),
+        Some(2)
+    );
+    // 对象的数字key按key取值
+    assert_eq!(
+        doc.path("rateLimits.0.limit").and_then(Value::as_u64),
+        Some(6000)
+    );
+    assert!(doc.path("filters.0").unwrap().is_object());
+}
+
+#[test]
+fn test_path_missing() {
+    let doc = document();
+    // 缺失的key
+    assert_eq!(doc.path("status"), None);
+    assert_eq!(doc.path("filters.0.stepSize"), None);
+    assert_eq!(doc.path("symbol."), None);
+    // 下标越界
+    assert_eq!(doc.path("filters.2"), None);
+    assert_eq!(doc.path("empty.0"), None);
+    // 类型不匹配：数组用非数字下标、标量继续取值
+    assert_eq!(doc.path("filters.first"), None);
+    assert_eq!(doc.path("filters.-1"), None);
+    assert_eq!(doc.path("symbol.0"), None);
+    assert_eq!(doc.path("filters.1.limits.0.value"), None);
+}
@@ -0,0 +1,197 @@
+use crate::{
+    backtest::factors::traits::FactorCalculator,
+    data_manager::{db::*, migration::migrate, MarketDataManager},
+    errors::{PlatformError, Result},
+    models::MarketType,
+};
+use async_trait::async_trait;
+use db::{
+    codec::{Codec, DefaultCodec},
+    sqlite::SQLiteDB,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+use tokio::sync::Mutex;
+
+// (市场, symbol) -> 每个子因子最近 zscore_window 个值
+type FactorHistories = HashMap<(MarketType, String), Vec<VecDeque<f64>>>;
+
+/// 多因子组合：各子因子在滚动窗口内做 z-score 后按权重加权求和
+/// 某个子因子计算失败（如数据不足）时将其排除，剩余权重重新归一化
+/// 配置 state store 后，每个 (市场, symbol) 的窗口在更新时写入 db，重启后从 db 恢复
+pub struct CompositeFactor {
+    factors: Vec<(Box<dyn FactorCalculator + Send + Sync>, f64)>,
+    zscore_window: usize,
+    histories: Mutex<FactorHistories>,
+    state_store: Option<(Arc<SQLiteDB>, String)>, // (db, factor_id)
+}
+
+impl CompositeFactor {
+    pub fn new(
+        factors: Vec<(Box<dyn FactorCalculator + Send + Sync>, f64)>,
+        zscore_window: usize,
+    ) -> Result<Self> {
+        if factors.is_empty() || zscore_window == 0 {
+            return Err(PlatformError::StrategyError {
+                message: "composite factor requires factors and a positive zscore window"
+                    .to_string(),
+            });
+        }
+        if factors.iter().any(|(_, w)| !w.is_finite() || *w < 0.0) {
+            return Err(PlatformError::StrategyError {
+                message: "composite factor weights must be non-negative".to_string(),
+            });
+        }
+        Ok(Self {
+            factors,
+            zscore_window,
+            histories: Mutex::new(HashMap::new()),
+            state_store: None,
+        })
+    }
+
+    /// 开启窗口状态持久化，key 为 {factor_id}:{market}:{symbol}
+    pub fn with_state_store(mut self, db: Arc<SQLiteDB>, factor_id: &str) -> Result<Self> {
+        migrate(db.clone())?;
+        self.state_store = Some((db, factor_id.to_string()));
+        Ok(self)
+    }
+
+    fn state_key(factor_id: &str, market_type: &MarketType, symbol: &str) -> String {
+        format!("{}:{}:{}", factor_id, market_type.as_str(), symbol)
+    }
+
+    // 从 db 恢复窗口，不存在或与当前因子配置不匹配时从空窗口开始
+    async fn load_histories(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+    ) -> Result<Vec<VecDeque<f64>>> {
+        let empty = vec![VecDeque::with_capacity(self.zscore_window); self.factors.len()];
+        let Some((db, factor_id)) = &self.state_store else {
+            return Ok(empty);
+        };
+        let state_key = Self::state_key(factor_id, market_type, symbol);
+        let (db, key) = (db.clone(), state_key.clone());
+        let state = tokio::task::spawn_blocking(move || get_factor_state(db, &key))
+            .await
+            .map_err(|e| PlatformError::StrategyError {
+                message: format!("load factor state join err: {}", e),
+            })??;
+        let Some(state) = state else {
+            return Ok(empty);
+        };
+        match DefaultCodec::decode::<Vec<VecDeque<f64>>>(&state) {
+            Ok(histories)
+                if histories.len() == self.factors.len()
+                    && histories.iter().all(|h| h.len() <= self.zscore_window) =>
+            {
+                Ok(histories)
+            }
+            Ok(_) => {
+                log::warn!(
+                    "factor state {} mismatches factor config, ignored",
+                    state_key
+                );
+                Ok(empty)
+            }
+            Err(e) => {
+                log::warn!("parse factor state {} err: {}, ignored", state_key, e);
+                Ok(empty)
+            }
+        }
+    }
+
+    async fn save_histories(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+        histories: &[VecDeque<f64>],
+        ts: u64,
+    ) -> Result<()> {
+        let Some((db, factor_id)) = &self.state_store else {
+            return Ok(());
+        };
+        let state = DefaultCodec::encode(&histories).map_err(|e| PlatformError::StrategyError {
+            message: format!("serialize factor state err: {}", e),
+        })?;
+        let (db, key) = (db.clone(), Self::state_key(factor_id, market_type, symbol));
+        tokio::task::spawn_blocking(move || update_factor_state(db, &key, &state, ts))
+            .await
+            .map_err(|e| PlatformError::StrategyError {
+                message: format!("save factor state join err: {}", e),
+            })?
+    }
+
+    // 加入新值后计算其在窗口内的 z-score，样本不足或方差为 0 时返回 0
+    fn zscore(history: &mut VecDeque<f64>, window: usize, value: f64) -> f64 {
+        if history.len() >= window {
+            history.pop_front();
+        }
+        history.push_back(value);
+        let n = history.len() as f64;
+        if history.len() < 2 {
+            return 0.0;
+        }
+        let mean = history.iter().sum::<f64>() / n;
+        let var = history.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        let std = var.sqrt();
+        if std == 0.0 {
+            return 0.0;
+        }
+        (value - mean) / std
+    }
+}
+
+#[async_trait]
+impl FactorCalculator for CompositeFactor {
+    async fn calculate(
+        &self,
+        manager: &dyn MarketDataManager,
+        market_type: &MarketType,
+        symbol: &str,
+    ) -> Result<(f64, u64)> {
+        let mut results = Vec::with_capacity(self.factors.len());
+        for (factor, _) in self.factors.iter() {
+            results.push(factor.calculate(manager, market_type, symbol).await);
+        }
+
+        // 持锁直到写回，保证同一窗口的更新与落库顺序一致；db读写在阻塞线程中执行
+        let mut all_histories = self.histories.lock().await;
+        let key = (market_type.clone(), symbol.to_string());
+        if !all_histories.contains_key(&key) {
+            let loaded = self.load_histories(market_type, symbol).await?;
+            all_histories.insert(key.clone(), loaded);
+        }
+        let histories = all_histories.get_mut(&key).unwrap();
+        let mut weighted_sum = 0.0;
+        let mut weight_sum = 0.0;
+        let mut factor_ts: Option<u64> = None;
+        for (i, result) in results.into_iter().enumerate() {
+            let (value, ts) = match result {
+                Ok(r) if r.0.is_finite() => r,
+                _ => continue,
+            };
+            let weight = self.factors[i].1;
+            let z = Self::zscore(&mut histories[i], self.zscore_window, value);
+            weighted_sum += weight * z;
+            weight_sum += weight;
+            // 取最旧的行情时间戳，让调用方的延迟检查覆盖所有子因子
+            factor_ts = Some(factor_ts.map_or(ts, |t| t.min(ts)));
+        }
+
+        if let Some(ts) = factor_ts {
+            self.save_histories(market_type, symbol, histories, ts)
+                .await?;
+        }
+
+        match factor_ts {
+            Some(ts) if weight_sum > 0.0 => Ok((weighted_sum / weight_sum, ts)),
+            _ => Err(PlatformError::StrategyError {
+                message: format!("no sub factor available for {:?} {}", market_type, symbol),
+            }),
+        }
+    }
+}
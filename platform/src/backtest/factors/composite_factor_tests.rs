@@ -0,0 +1,259 @@
+use crate::{
+    backtest::{
+        factors::{composite_factor::CompositeFactor, traits::FactorCalculator},
+        test_utils::test_market_mgr,
+    },
+    data_manager::{local_data_manager::Clock, MarketDataManager},
+    errors::{PlatformError, Result},
+    models::MarketType,
+};
+use async_trait::async_trait;
+use db::{
+    codec::{Codec, DefaultCodec},
+    sqlite::SQLiteDB,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use tempfile::NamedTempFile;
+
+/// 按调用次数依次返回预设值，None 表示数据不足
+struct ScriptedFactor {
+    values: Vec<Option<f64>>,
+    ts: u64,
+    calls: AtomicUsize,
+}
+
+impl ScriptedFactor {
+    fn boxed(values: Vec<Option<f64>>, ts: u64) -> Box<dyn FactorCalculator + Send + Sync> {
+        Box::new(Self {
+            values,
+            ts,
+            calls: AtomicUsize::new(0),
+        })
+    }
+}
+
+#[async_trait]
+impl FactorCalculator for ScriptedFactor {
+    async fn calculate(
+        &self,
+        _manager: &dyn MarketDataManager,
+        _market_type: &MarketType,
+        _symbol: &str,
+    ) -> Result<(f64, u64)> {
+        let i = self.calls.fetch_add(1, Ordering::SeqCst);
+        match self.values[i] {
+            Some(v) => Ok((v, self.ts)),
+            None => Err(PlatformError::StrategyError {
+                message: "insufficient data".to_string(),
+            }),
+        }
+    }
+}
+
+// 总体标准差下最后一个值的 z-score
+fn zscore(window: &[f64]) -> f64 {
+    let n = window.len() as f64;
+    let mean = window.iter().sum::<f64>() / n;
+    let std = (window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+    if std == 0.0 {
+        0.0
+    } else {
+        (window[window.len() - 1] - mean) / std
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "actual {} expected {}",
+        actual,
+        expected
+    );
+}
+
+#[tokio::test]
+async fn test_composite_factor_weighted_zscore() {
+    let db_file = NamedTempFile::new().unwrap();
+    let market_mgr = test_market_mgr(&db_file, Arc::new(Clock::new(0)));
+    let market_type = MarketType::BinanceSpot;
+    let composite = CompositeFactor::new(
+        vec![
+            (
+                ScriptedFactor::boxed(vec![Some(1.0), Some(2.0), Some(3.0), Some(4.0)], 2000),
+                2.0,
+            ),
+            (
+                ScriptedFactor::boxed(vec![Some(10.0), Some(10.0), Some(20.0), None], 1000),
+                1.0,
+            ),
+        ],
+        3,
+    )
+    .unwrap();
+
+    let mut results = vec![];
+    for _ in 0..4 {
+        results.push(
+            composite
+                .calculate(market_mgr.as_ref(), &market_type, "BTCUSDT")
+                .await
+                .unwrap(),
+        );
+    }
+
+    // 只有一个样本时 z-score 为 0
+    assert_close(results[0].0, 0.0);
+    assert_close(
+        results[1].0,
+        (2.0 * zscore(&[1.0, 2.0]) + zscore(&[10.0, 10.0])) / 3.0,
+    );
+    assert_close(
+        results[2].0,
+        (2.0 * zscore(&[1.0, 2.0, 3.0]) + zscore(&[10.0, 10.0, 20.0])) / 3.0,
+    );
+    // 第二个因子数据不足被排除，权重重新归一化；窗口滑动到 [2, 3, 4]
+    assert_close(results[3].0, zscore(&[2.0, 3.0, 4.0]));
+
+    // 时间戳取可用子因子中最旧的
+    assert_eq!(results[2].1, 1000);
+    assert_eq!(results[3].1, 2000);
+}
+
+#[tokio::test]
+async fn test_composite_factor_all_unavailable() {
+    let db_file = NamedTempFile::new().unwrap();
+    let market_mgr = test_market_mgr(&db_file, Arc::new(Clock::new(0)));
+    let composite = CompositeFactor::new(
+        vec![
+            (ScriptedFactor::boxed(vec![None], 1000), 1.0),
+            (ScriptedFactor::boxed(vec![Some(1.0)], 1000), 0.0),
+        ],
+        10,
+    )
+    .unwrap();
+    assert!(composite
+        .calculate(market_mgr.as_ref(), &MarketType::BinanceSpot, "BTCUSDT")
+        .await
+        .is_err());
+
+    assert!(CompositeFactor::new(vec![], 10).is_err());
+    assert!(CompositeFactor::new(vec![(ScriptedFactor::boxed(vec![], 0), -1.0)], 10).is_err());
+}
+
+fn restart_test_factor(values: &[f64]) -> CompositeFactor {
+    let doubled = values.iter().map(|v| Some(v * 2.0)).collect::<Vec<_>>();
+    CompositeFactor::new(
+        vec![
+            (
+                ScriptedFactor::boxed(values.iter().map(|v| Some(*v)).collect(), 1000),
+                1.0,
+            ),
+            (ScriptedFactor::boxed(doubled, 1000), 3.0),
+        ],
+        4,
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_composite_factor_resumes_from_state_store() {
+    let db_file = NamedTempFile::new().unwrap();
+    let market_mgr = test_market_mgr(&db_file, Arc::new(Clock::new(0)));
+    let market_type = MarketType::BinanceSpot;
+    let values = [1.0, 4.0, 2.0, 8.0, 5.0, 7.0];
+
+    // 不重启的基准
+    let baseline_factor = restart_test_factor(&values);
+    let mut baseline = vec![];
+    for _ in 0..values.len() {
+        baseline.push(
+            baseline_factor
+                .calculate(market_mgr.as_ref(), &market_type, "BTCUSDT")
+                .await
+                .unwrap(),
+        );
+    }
+
+    let state_file = NamedTempFile::new().unwrap();
+    let state_db = || Arc::new(SQLiteDB::new(state_file.path().to_str().unwrap()).unwrap());
+    {
+        let factor = restart_test_factor(&values[..3])
+            .with_state_store(state_db(), "composite_1")
+            .unwrap();
+        for _ in 0..3 {
+            factor
+                .calculate(market_mgr.as_ref(), &market_type, "BTCUSDT")
+                .await
+                .unwrap();
+        }
+    }
+
+    // 重建后窗口从 db 恢复，后续因子值与基准一致
+    let factor = restart_test_factor(&values[3..])
+        .with_state_store(state_db(), "composite_1")
+        .unwrap();
+    for expected in baseline.iter().skip(3) {
+        let result = factor
+            .calculate(market_mgr.as_ref(), &market_type, "BTCUSDT")
+            .await
+            .unwrap();
+        assert_close(result.0, expected.0);
+        assert_eq!(result.1, expected.1);
+    }
+
+    // 其他 factor_id 的状态互不影响，从空窗口开始
+    let other = restart_test_factor(&values[3..])
+        .with_state_store(state_db(), "composite_2")
+        .unwrap();
+    let result = other
+        .calculate(market_mgr.as_ref(), &market_type, "BTCUSDT")
+        .await
+        .unwrap();
+    assert_close(result.0, 0.0);
+}
+
+#[tokio::test]
+async fn test_composite_factor_reads_legacy_text_state() {
+    let db_file = NamedTempFile::new().unwrap();
+    let market_mgr = test_market_mgr(&db_file, Arc::new(Clock::new(0)));
+    let market_type = MarketType::BinanceSpot;
+    let state_file = NamedTempFile::new().unwrap();
+    let state_db = Arc::new(SQLiteDB::new(state_file.path().to_str().unwrap()).unwrap());
+
+    // 旧版本以json文本写入的窗口
+    let factor = restart_test_factor(&[5.0])
+        .with_state_store(state_db.clone(), "composite_1")
+        .unwrap();
+    state_db
+        .execute_update(
+            "INSERT INTO factor_state (state_key, state, updated_at) VALUES (?1, ?2, 1)",
+            &[
+                &"composite_1:binance_spot:BTCUSDT",
+                &"[[1.0,4.0,2.0],[2.0,8.0,4.0]]",
+            ],
+        )
+        .unwrap();
+    let result = factor
+        .calculate(market_mgr.as_ref(), &market_type, "BTCUSDT")
+        .await
+        .unwrap();
+    let expected = (zscore(&[1.0, 4.0, 2.0, 5.0]) + 3.0 * zscore(&[2.0, 8.0, 4.0, 10.0])) / 4.0;
+    assert_close(result.0, expected);
+
+    // 更新后按codec写回，仍可读取
+    let stored = state_db
+        .execute_query(
+            "SELECT state FROM factor_state WHERE state_key = ?1",
+            &[&"composite_1:binance_spot:BTCUSDT"],
+        )
+        .unwrap();
+    let state: Vec<Vec<f64>> =
+        DefaultCodec::decode(&stored.first().unwrap().get_bytes("state").unwrap()).unwrap();
+    assert_eq!(
+        state,
+        vec![vec![1.0, 4.0, 2.0, 5.0], vec![2.0, 8.0, 4.0, 10.0]]
+    );
+}
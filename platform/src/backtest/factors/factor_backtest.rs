@@ -1,30 +1,187 @@
 use crate::{
-    backtest::factors::traits::{FactorCalculator, PriceProvider},
+    backtest::factors::{
+        factor_cache::{FactorCache, FactorCacheKey},
+        traits::{FactorCalculator, PriceProvider},
+    },
     data_manager::local_data_manager::{Clock, LocalMarketDataManager},
-    errors::Result,
-    models::MarketType,
+    errors::{PlatformError, Result},
+    models::{KlineInterval, MarketType},
 };
-use std::{collections::HashMap, sync::Arc};
+use futures_util::{stream, StreamExt};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use tokio_util::sync::CancellationToken;
+
+/// Rank 并列判断的相对误差
+const RANK_TIE_RELATIVE_EPSILON: f64 = 1e-12;
 
 /// 因子回测记录
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FactorRecord {
-    pub timestamp: u64,              // 回测时钟时间
-    pub factor_value: f64,           // 因子值
-    pub factor_timestamp: u64,       // 因子数据的行情时间戳
-    pub price: f64,                  // 基准价格
-    pub price_timestamp: u64,        // 价格数据的行情时间戳
+    pub symbol: String,
+    pub timestamp: u64, // 回测时钟时间
+    #[serde(with = "nan_as_null")]
+    pub factor_value: f64, // 因子值
+    pub factor_timestamp: u64, // 因子数据的行情时间戳
+    #[serde(with = "nan_as_null")]
+    pub price: f64, // 基准价格
+    pub price_timestamp: u64, // 价格数据的行情时间戳
     pub forward_return: Option<f64>, // 未来 N 个周期的收益率
 }
 
+/// json 不支持 NaN/Infinity，非有限值写为 null，读回为 NaN
+mod nan_as_null {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_finite() {
+            serializer.serialize_f64(*value)
+        } else {
+            serializer.serialize_none()
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::NAN))
+    }
+}
+
+/// walk-forward 单个窗口的评估结果，窗口区间均为左闭右开
+#[derive(Debug, Clone)]
+pub struct WalkForwardWindow {
+    pub train_start: u64,
+    pub train_end: u64,
+    pub test_start: u64,
+    pub test_end: u64,
+    pub train_ic: f64,        // 训练窗口内的 Rank IC
+    pub test_ic: f64,         // 测试窗口内的 Rank IC
+    pub train_samples: usize, // 参与计算 IC 的有效样本数
+    pub test_samples: usize,
+}
+
+/// walk-forward 汇总结果，统计量基于各窗口的 test_ic
+#[derive(Debug, Clone)]
+pub struct WalkForwardReport {
+    pub windows: Vec<WalkForwardWindow>,
+    pub ic_mean: f64,
+    pub ic_std: f64,
+    pub ic_ir: f64,            // ic_mean / ic_std，std 为 0 时为 0
+    pub positive_ratio: f64,   // test_ic > 0 的窗口占比
+    pub sign_consistency: f64, // test_ic 与 train_ic 同号的窗口占比
+}
+
+/// 因子相关系数的计算方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CorrelationMethod {
+    Pearson,
+    Rank, // Spearman，对单调变换不敏感
+}
+
+/// 因子两两相关系数矩阵，factors 按名称排序，matrix[i][j] 对应 (factors[i], factors[j])
+#[derive(Debug, Clone)]
+pub struct FactorCorrelation {
+    pub factors: Vec<String>,
+    pub matrix: Vec<Vec<f64>>,
+    pub samples: usize, // 所有因子共有的 (symbol, timestamp) 样本数
+}
+
+impl FactorCorrelation {
+    pub fn get(&self, a: &str, b: &str) -> Option<f64> {
+        let i = self.factors.iter().position(|f| f == a)?;
+        let j = self.factors.iter().position(|f| f == b)?;
+        Some(self.matrix[i][j])
+    }
+}
+
+/// 回测进度回调，参数为 (当前时钟时间, 已处理步数, 预估总步数)
+pub type ProgressCallback = Box<dyn Fn(u64, u64, u64) + Send + Sync>;
+
+/// 回测时钟的推进方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepMode {
+    Fixed,                     // 按 step_ms 固定步长推进
+    KlineClose(KlineInterval), // 推进到每根 kline 的收盘时间
+    Trade,                     // 推进到每个成交时间（同一时间戳的多笔成交只评估一次）
+}
+
+/// 按 symbol 创建因子计算器/价格提供者，有状态的实现（如滚动窗口）不能在并发的 symbol 间共享
+pub type CalculatorFactory = dyn Fn(&str) -> Box<dyn FactorCalculator> + Send + Sync;
+pub type PriceProviderFactory = dyn Fn(&str) -> Box<dyn PriceProvider> + Send + Sync;
+
 pub struct FactorBacktester {
     market_mgr: Arc<LocalMarketDataManager>,
     clock: Arc<Clock>,
+    progress: Option<(u64, ProgressCallback)>, // (每 N 步回调一次, 回调)
+    cancel_token: Option<CancellationToken>,
+    factor_cache: Option<(Arc<FactorCache>, String)>, // (缓存, factor_id)
+    step_mode: StepMode,
 }
 
 impl FactorBacktester {
     pub fn new(market_mgr: Arc<LocalMarketDataManager>, clock: Arc<Clock>) -> Self {
-        Self { market_mgr, clock }
+        Self {
+            market_mgr,
+            clock,
+            progress: None,
+            cancel_token: None,
+            factor_cache: None,
+            step_mode: StepMode::Fixed,
+        }
+    }
+
+    /// 事件模式下评估点与行情到达对齐，forward_steps 按事件个数计算，step_ms 仅作为行情延迟阈值
+    pub fn with_step_mode(mut self, step_mode: StepMode) -> Self {
+        self.step_mode = step_mode;
+        self
+    }
+
+    /// 每 every_steps 步回调一次进度，结束时再回调一次
+    pub fn with_progress(mut self, every_steps: u64, callback: ProgressCallback) -> Self {
+        self.progress = Some((every_steps.max(1), callback));
+        self
+    }
+
+    /// 取消后停止遍历，返回已收集的记录（forward_return 照常计算）
+    pub fn with_cancel_token(mut self, cancel_token: CancellationToken) -> Self {
+        self.cancel_token = Some(cancel_token);
+        self
+    }
+
+    /// 计算因子前先按 (factor_id, symbol, 时钟时间) 查缓存，命中时不调用 calculator
+    /// factor_id 需唯一标识因子实现及其参数，多个回测共享同一个缓存时才能复用结果
+    pub fn with_factor_cache(mut self, cache: Arc<FactorCache>, factor_id: &str) -> Self {
+        self.factor_cache = Some((cache, factor_id.to_string()));
+        self
+    }
+
+    async fn calculate_factor(
+        &self,
+        calculator: &dyn FactorCalculator,
+        market_type: &MarketType,
+        symbol: &str,
+        cur_ts: u64,
+    ) -> Result<(f64, u64)> {
+        let Some((cache, factor_id)) = &self.factor_cache else {
+            return calculator
+                .calculate(self.market_mgr.as_ref(), market_type, symbol)
+                .await;
+        };
+        let key = FactorCacheKey {
+            factor_id: factor_id.clone(),
+            market_type: market_type.clone(),
+            symbol: symbol.to_string(),
+            ts: cur_ts,
+        };
+        if let Some(value) = cache.get(&key) {
+            return Ok(value);
+        }
+        // 只缓存成功的结果，失败（如数据不足）下次重新计算
+        let value = calculator
+            .calculate(self.market_mgr.as_ref(), market_type, symbol)
+            .await?;
+        cache.insert(key, value);
+        Ok(value)
     }
 
     /// 执行回测
@@ -34,8 +191,8 @@ impl FactorBacktester {
     /// - symbol: 交易对
     /// - start_ts: 开始时间戳
     /// - end_ts: 结束时间戳
-    /// - step_ms: 步进时间（例如 1分钟 = 60000ms）
-    /// - forward_steps: 计算未来多少个 step 的收益率
+    /// - step_ms: 步进时间（例如 1分钟 = 60000ms），事件模式下仅作为行情延迟阈值
+    /// - forward_steps: 计算未来多少个 step（事件模式下为事件）的收益率
     pub async fn run_test(
         &self,
         calculator: &dyn FactorCalculator,
@@ -48,15 +205,64 @@ impl FactorBacktester {
         forward_steps: usize,
     ) -> Result<Vec<FactorRecord>> {
         let mut records = Vec::new();
-        let mut cur_ts = start_ts;
+        log::info!(
+            "Backtesting {} from {} to {}, price method: {}, step mode: {:?}",
+            symbol,
+            start_ts,
+            end_ts,
+            price_provider.method().as_str(),
+            self.step_mode
+        );
+
+        // 事件模式下预先从 db 取出 [start_ts, end_ts] 内的事件时间
+        let events = match &self.step_mode {
+            StepMode::Fixed => None,
+            StepMode::KlineClose(interval) => Some(
+                self.market_mgr
+                    .kline_close_times(&market_type, symbol, interval, start_ts, end_ts)
+                    .await?,
+            ),
+            StepMode::Trade => Some(
+                self.market_mgr
+                    .trade_timestamps(&market_type, symbol, start_ts, end_ts)
+                    .await?,
+            ),
+        };
+        let timeline: Box<dyn Iterator<Item = u64> + Send + '_> = match &events {
+            None => Box::new((start_ts..=end_ts).step_by(step_ms.max(1) as usize)),
+            Some(events) => Box::new(events.iter().copied()),
+        };
 
         // 允许的最大时间间隔：step_ms
         let max_lag_ms = step_ms;
-        let mut loop_cnt = 0;
+        let mut loop_cnt: u64 = 0;
+        let total_estimate = match &events {
+            None => end_ts.saturating_sub(start_ts) / step_ms.max(1) + 1,
+            Some(events) => events.len() as u64,
+        };
+        let mut last_ts = start_ts;
 
         // 1. 遍历时间轴，计算因子值和价格
-        while cur_ts <= end_ts {
-            if loop_cnt % 1000 == 0 {
+        for cur_ts in timeline {
+            if self.cancel_token.as_ref().is_some_and(|t| t.is_cancelled()) {
+                log::warn!(
+                    "Backtesting {} cancelled at time {}, processed {}/{} steps",
+                    symbol,
+                    cur_ts,
+                    loop_cnt,
+                    total_estimate
+                );
+                break;
+            }
+            match &self.progress {
+                Some((every_steps, callback))
+                    if loop_cnt > 0 && loop_cnt.is_multiple_of(*every_steps) =>
+                {
+                    callback(cur_ts, loop_cnt, total_estimate)
+                }
+                _ => {}
+            }
+            if loop_cnt.is_multiple_of(1000) {
                 log::info!(
                     "Backtesting {} at time {}, progress: {:.2}%",
                     symbol,
@@ -65,18 +271,19 @@ impl FactorBacktester {
                 );
             }
             loop_cnt += 1;
+            last_ts = cur_ts;
 
             // 设置模拟时钟，LocalMarketDataManager 会根据这个时间过滤数据
             self.clock.set_cur_ts(cur_ts);
 
             // 获取因子值和因子行情时间戳
-            let factor_result = calculator
-                .calculate(&self.market_mgr, &market_type, symbol)
+            let factor_result = self
+                .calculate_factor(calculator, &market_type, symbol, cur_ts)
                 .await;
 
             // 获取价格和价格行情时间戳
             let price_result = price_provider
-                .get_price(&self.market_mgr, &market_type, symbol)
+                .get_price(self.market_mgr.as_ref(), &market_type, symbol)
                 .await;
 
             // 只有当因子和价格都成功获取时才记录
@@ -94,7 +301,6 @@ impl FactorBacktester {
                         cur_ts,
                         symbol
                     );
-                    cur_ts += step_ms;
                     continue;
                 };
 
@@ -109,7 +315,6 @@ impl FactorBacktester {
                         cur_ts,
                         symbol
                     );
-                    cur_ts += step_ms;
                     continue;
                 };
 
@@ -123,7 +328,6 @@ impl FactorBacktester {
                         cur_ts,
                         factor_ts
                     );
-                    cur_ts += step_ms;
                     continue;
                 }
 
@@ -136,13 +340,13 @@ impl FactorBacktester {
                         cur_ts,
                         price_ts
                     );
-                    cur_ts += step_ms;
                     continue;
                 }
 
                 // 数据有效性检查
                 if !factor_value.is_nan() && price > 0.0 {
                     records.push(FactorRecord {
+                        symbol: symbol.to_string(),
                         timestamp: cur_ts,
                         factor_value,
                         factor_timestamp: factor_ts,
@@ -171,8 +375,9 @@ impl FactorBacktester {
                     );
                 }
             }
-
-            cur_ts += step_ms;
+        }
+        if let Some((_, callback)) = &self.progress {
+            callback(last_ts, loop_cnt, total_estimate);
         }
 
         // 2. 计算 Forward Return (未来收益率)
@@ -182,15 +387,25 @@ impl FactorBacktester {
         let target_duration = step_ms * forward_steps as u64;
 
         for record in records.iter_mut() {
-            let target_ts = record.timestamp + target_duration;
+            // 事件模式下取之后第 forward_steps 个事件的时间
+            let target_ts = match &events {
+                None => record.timestamp + target_duration,
+                Some(events) => match events
+                    .binary_search(&record.timestamp)
+                    .ok()
+                    .and_then(|i| events.get(i + forward_steps))
+                {
+                    Some(ts) => *ts,
+                    None => continue,
+                },
+            };
 
             // 只有当严格对应的未来时间点有价格时，才计算收益
             // 如果未来那个时间点数据缺失，那么这个样本的 forward_return 就是 None，
             // 在计算 IC 时会被自动剔除。这是最严谨的做法。
             if let Some(future_price) = price_map.get(&target_ts) {
                 if record.price > 0.0 {
-                    let ret = (future_price - record.price) / record.price;
-                    record.forward_return = Some(ret);
+                    record.forward_return = Some(Self::forward_return(record.price, *future_price));
                 }
             }
         }
@@ -198,6 +413,223 @@ impl FactorBacktester {
         Ok(records)
     }
 
+    /// 多个 symbol 并发回测，最多 max_parallel 个任务同时运行，返回 symbol -> 回测记录
+    /// 每个任务使用独立的 Clock 和共享 db 的数据管理器副本，互不影响对方的时间推进；
+    /// 设置了进度回调时，按 (end_ts, 已完成 symbol 数, symbol 总数) 回调
+    #[allow(clippy::too_many_arguments)]
+    pub async fn run_universe(
+        &self,
+        calculator_factory: &CalculatorFactory,
+        price_provider_factory: &PriceProviderFactory,
+        market_type: MarketType,
+        symbols: &[String],
+        start_ts: u64,
+        end_ts: u64,
+        step_ms: u64,
+        forward_steps: usize,
+        max_parallel: usize,
+    ) -> Result<HashMap<String, Vec<FactorRecord>>> {
+        let tasks = symbols.iter().map(|symbol| {
+            let clock = Arc::new(Clock::new(start_ts));
+            let backtester = FactorBacktester {
+                market_mgr: Arc::new(self.market_mgr.with_clock(clock.clone())),
+                clock,
+                progress: None,
+                cancel_token: self.cancel_token.clone(),
+                factor_cache: self.factor_cache.clone(),
+                step_mode: self.step_mode.clone(),
+            };
+            let calculator = calculator_factory(symbol);
+            let price_provider = price_provider_factory(symbol);
+            let (market_type, symbol) = (market_type.clone(), symbol.clone());
+            // buffer_unordered 按需拉取，spawn 在有空闲并发额度时才发生
+            async move {
+                tokio::spawn(async move {
+                    let records = backtester
+                        .run_test(
+                            calculator.as_ref(),
+                            price_provider.as_ref(),
+                            market_type,
+                            &symbol,
+                            start_ts,
+                            end_ts,
+                            step_ms,
+                            forward_steps,
+                        )
+                        .await;
+                    (symbol, records)
+                })
+                .await
+            }
+        });
+        let mut tasks = stream::iter(tasks).buffer_unordered(max_parallel.max(1));
+
+        let mut results = HashMap::new();
+        while let Some(joined) = tasks.next().await {
+            let (symbol, records) = joined.map_err(|e| PlatformError::PlatformError {
+                message: format!("backtest task join err: {}", e),
+            })?;
+            results.insert(symbol, records?);
+            if let Some((_, callback)) = &self.progress {
+                callback(end_ts, results.len() as u64, symbols.len() as u64);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Walk-forward 评估：在 [from_ts, to_ts] 上按 step_ms 滚动切分连续的训练/测试窗口，
+    /// 分别计算各窗口的 Rank IC 并汇总 test_ic 的均值与标准差，用于衡量因子在不同时段的稳定性
+    /// - train_ms / test_ms: 训练、测试窗口长度
+    /// - step_ms: 窗口滚动步长
+    /// - sample_ms: 采样步进，同 run_test 的 step_ms
+    /// - forward_steps: 同 run_test
+    #[allow(clippy::too_many_arguments)]
+    pub async fn walk_forward(
+        &self,
+        calculator: &dyn FactorCalculator,
+        price_provider: &dyn PriceProvider,
+        market_type: MarketType,
+        symbol: &str,
+        from_ts: u64,
+        to_ts: u64,
+        train_ms: u64,
+        test_ms: u64,
+        step_ms: u64,
+        sample_ms: u64,
+        forward_steps: usize,
+    ) -> Result<WalkForwardReport> {
+        if sample_ms == 0 {
+            return Err(PlatformError::FactorError {
+                message: "walk forward sample_ms must be positive".to_string(),
+            });
+        }
+        let records = self
+            .run_test(
+                calculator,
+                price_provider,
+                market_type,
+                symbol,
+                from_ts,
+                to_ts,
+                sample_ms,
+                forward_steps,
+            )
+            .await?;
+        self.evaluate_walk_forward(
+            &records,
+            from_ts,
+            to_ts,
+            train_ms,
+            test_ms,
+            step_ms,
+            sample_ms * forward_steps as u64,
+        )
+    }
+
+    /// 对 run_test 的结果做 walk-forward 切分，records 需按 timestamp 升序
+    /// horizon_ms 为 forward_return 的跨度：窗口内只保留收益区间不越过窗口右端的样本，
+    /// 避免训练窗口的标签使用到测试窗口的价格
+    #[allow(clippy::too_many_arguments)]
+    pub fn evaluate_walk_forward(
+        &self,
+        records: &[FactorRecord],
+        from_ts: u64,
+        to_ts: u64,
+        train_ms: u64,
+        test_ms: u64,
+        step_ms: u64,
+        horizon_ms: u64,
+    ) -> Result<WalkForwardReport> {
+        if train_ms == 0 || test_ms == 0 || step_ms == 0 {
+            return Err(PlatformError::FactorError {
+                message: format!(
+                    "walk forward windows must be positive, train_ms: {}, test_ms: {}, step_ms: {}",
+                    train_ms, test_ms, step_ms
+                ),
+            });
+        }
+
+        let window_records = |start: u64, end: u64| -> Vec<FactorRecord> {
+            let lo = records.partition_point(|r| r.timestamp < start);
+            let hi = records.partition_point(|r| r.timestamp < end);
+            records[lo..hi]
+                .iter()
+                .filter(|r| {
+                    r.forward_return.is_some()
+                        && !r.factor_value.is_nan()
+                        && r.timestamp + horizon_ms <= end
+                })
+                .cloned()
+                .collect()
+        };
+
+        let mut windows = Vec::new();
+        let mut train_start = from_ts;
+        while train_start + train_ms + test_ms <= to_ts {
+            let train_end = train_start + train_ms;
+            let test_end = train_end + test_ms;
+            let train_records = window_records(train_start, train_end);
+            let test_records = window_records(train_end, test_end);
+            let window = WalkForwardWindow {
+                train_start,
+                train_end,
+                test_start: train_end,
+                test_end,
+                train_ic: self.calculate_ic(&train_records),
+                test_ic: self.calculate_ic(&test_records),
+                train_samples: train_records.len(),
+                test_samples: test_records.len(),
+            };
+            log::info!(
+                "walk forward window train [{}, {}) IC: {:.6}, test [{}, {}) IC: {:.6}, samples: {}/{}",
+                window.train_start,
+                window.train_end,
+                window.train_ic,
+                window.test_start,
+                window.test_end,
+                window.test_ic,
+                window.train_samples,
+                window.test_samples
+            );
+            windows.push(window);
+            train_start += step_ms;
+        }
+
+        if windows.is_empty() {
+            return Err(PlatformError::FactorError {
+                message: format!(
+                    "time range [{}, {}] is shorter than one walk forward window ({} + {} ms)",
+                    from_ts, to_ts, train_ms, test_ms
+                ),
+            });
+        }
+
+        let n = windows.len() as f64;
+        let ic_mean = windows.iter().map(|w| w.test_ic).sum::<f64>() / n;
+        let ic_std = (windows
+            .iter()
+            .map(|w| (w.test_ic - ic_mean).powi(2))
+            .sum::<f64>()
+            / n)
+            .sqrt();
+        let ic_ir = if ic_std != 0.0 { ic_mean / ic_std } else { 0.0 };
+        let positive_ratio = windows.iter().filter(|w| w.test_ic > 0.0).count() as f64 / n;
+        let sign_consistency = windows
+            .iter()
+            .filter(|w| w.train_ic * w.test_ic > 0.0)
+            .count() as f64
+            / n;
+
+        Ok(WalkForwardReport {
+            windows,
+            ic_mean,
+            ic_std,
+            ic_ir,
+            positive_ratio,
+            sign_consistency,
+        })
+    }
+
     /// 计算 IC (Information Coefficient)
     /// 计算 Rank IC (Spearman Correlation)
     pub fn calculate_ic(&self, records: &[FactorRecord]) -> f64 {
@@ -222,6 +654,92 @@ impl FactorBacktester {
         Self::calculate_pearson_correlation(&factor_ranks, &return_ranks)
     }
 
+    /// 计算因子两两之间的相关系数，用于组合前识别冗余因子
+    /// 各因子的记录按 (symbol, timestamp) 对齐，只使用所有因子都有有效值的样本
+    pub fn factor_correlation(
+        &self,
+        records_by_factor: &HashMap<String, Vec<FactorRecord>>,
+        method: CorrelationMethod,
+    ) -> Result<FactorCorrelation> {
+        let mut factors = records_by_factor.keys().cloned().collect::<Vec<_>>();
+        factors.sort();
+        if factors.is_empty() {
+            return Err(PlatformError::FactorError {
+                message: "factor correlation requires at least one factor".to_string(),
+            });
+        }
+
+        let values_by_factor = factors
+            .iter()
+            .map(|factor| {
+                records_by_factor[factor]
+                    .iter()
+                    .filter(|r| !r.factor_value.is_nan())
+                    .map(|r| ((r.symbol.as_str(), r.timestamp), r.factor_value))
+                    .collect::<HashMap<_, _>>()
+            })
+            .collect::<Vec<_>>();
+        // 取所有因子时间点的交集，排序保证结果与 HashMap 遍历顺序无关
+        let mut keys = values_by_factor[0]
+            .keys()
+            .filter(|key| values_by_factor[1..].iter().all(|v| v.contains_key(*key)))
+            .copied()
+            .collect::<Vec<_>>();
+        keys.sort();
+        if keys.len() < 2 {
+            return Err(PlatformError::FactorError {
+                message: format!(
+                    "not enough aligned samples ({}) for factor correlation",
+                    keys.len()
+                ),
+            });
+        }
+
+        let series = values_by_factor
+            .iter()
+            .map(|values| {
+                let series = keys.iter().map(|key| values[key]).collect::<Vec<_>>();
+                match method {
+                    CorrelationMethod::Pearson => series,
+                    CorrelationMethod::Rank => Self::get_ranks(&series),
+                }
+            })
+            .collect::<Vec<_>>();
+        let n = factors.len();
+        let mut matrix = vec![vec![1.0; n]; n];
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let corr = Self::calculate_pearson_correlation(&series[i], &series[j]);
+                matrix[i][j] = corr;
+                matrix[j][i] = corr;
+            }
+        }
+
+        Ok(FactorCorrelation {
+            factors,
+            matrix,
+            samples: keys.len(),
+        })
+    }
+
+    /// 价格由 Decimal 解析而来，还原为 Decimal 后计算收益率再转回 f64，
+    /// 避免低价币种（如 0.00001234）的相近价格在 f64 下相减丢失有效位
+    fn forward_return(price: f64, future_price: f64) -> f64 {
+        let to_decimal = |value: f64| Decimal::from_str(&value.to_string()).ok();
+        match (to_decimal(price), to_decimal(future_price)) {
+            (Some(price), Some(future_price)) => (future_price - price)
+                .checked_div(price)
+                .and_then(|ret| ret.to_f64())
+                .unwrap_or(f64::NAN),
+            _ => (future_price - price) / price,
+        }
+    }
+
+    /// 按相对误差判断并列，固定阈值会把量级很小的因子值（如低价币种的 vwap）全部判为并列
+    fn is_tie(a: f64, b: f64) -> bool {
+        (a - b).abs() <= RANK_TIE_RELATIVE_EPSILON * a.abs().max(b.abs())
+    }
+
     fn get_ranks(values: &[f64]) -> Vec<f64> {
         let n = values.len();
         let mut indices: Vec<usize> = (0..n).collect();
@@ -237,7 +755,7 @@ impl FactorBacktester {
         while i < n {
             let mut j = i + 1;
             // Check for ties
-            while j < n && (values[indices[j]] - values[indices[i]]).abs() < 1e-9 {
+            while j < n && Self::is_tie(values[indices[j]], values[indices[i]]) {
                 j += 1;
             }
 
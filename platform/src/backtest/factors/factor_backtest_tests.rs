@@ -0,0 +1,828 @@
+use crate::{
+    backtest::{
+        factors::{
+            factor_backtest::{
+                CorrelationMethod, FactorBacktester, FactorRecord, StepMode, WalkForwardReport,
+            },
+            factor_cache::FactorCache,
+            factor_calculators::{
+                KlineFactorCalculators, KlineFactorType, TradeFactorCalculators, TradeFactorType,
+            },
+            price_providers::{KlineClosePriceProvider, TradePriceProvider},
+            traits::{FactorCalculator, PriceMethod, PriceProvider},
+        },
+        test_utils::{kline_market_mgr, test_market_mgr},
+    },
+    data_manager::{db::update_trade_data, local_data_manager::Clock, MarketDataManager},
+    errors::Result,
+    models::{KlineData, KlineInterval, MarketType, Trade},
+};
+use async_trait::async_trait;
+use db::sqlite::SQLiteDB;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tempfile::NamedTempFile;
+use tokio_util::sync::CancellationToken;
+
+const STEP_MS: u64 = 60_000;
+
+/// 因子值与行情时间戳均取当前时钟，调用 cancel_after 次后取消回测
+struct ClockFactor {
+    clock: Arc<Clock>,
+    calls: AtomicU64,
+    cancel_after: Option<(u64, CancellationToken)>,
+}
+
+#[async_trait]
+impl FactorCalculator for ClockFactor {
+    async fn calculate(
+        &self,
+        _manager: &dyn MarketDataManager,
+        _market_type: &MarketType,
+        _symbol: &str,
+    ) -> Result<(f64, u64)> {
+        let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some((cancel_after, cancel_token)) = &self.cancel_after {
+            if calls == *cancel_after {
+                cancel_token.cancel();
+            }
+        }
+        let cur_ts = self.clock.cur_ts();
+        Ok((cur_ts as f64, cur_ts))
+    }
+}
+
+/// 价格随步数线性增长
+struct ClockPrice {
+    clock: Arc<Clock>,
+}
+
+#[async_trait]
+impl PriceProvider for ClockPrice {
+    fn method(&self) -> PriceMethod {
+        PriceMethod::KlineClose
+    }
+
+    async fn get_price(
+        &self,
+        _manager: &dyn MarketDataManager,
+        _market_type: &MarketType,
+        _symbol: &str,
+    ) -> Result<(f64, u64)> {
+        let cur_ts = self.clock.cur_ts();
+        Ok((100.0 + (cur_ts / STEP_MS) as f64, cur_ts))
+    }
+}
+
+#[tokio::test]
+async fn test_cancel_returns_partial_records() {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = test_market_mgr(&db_file, clock.clone());
+    let cancel_token = CancellationToken::new();
+    let calculator = ClockFactor {
+        clock: clock.clone(),
+        calls: AtomicU64::new(0),
+        cancel_after: Some((50, cancel_token.clone())),
+    };
+    let price_provider = ClockPrice {
+        clock: clock.clone(),
+    };
+
+    let backtester =
+        FactorBacktester::new(market_mgr, clock.clone()).with_cancel_token(cancel_token);
+    // 时间轴共 100 万步，取消后应立即停止
+    let records = backtester
+        .run_test(
+            &calculator,
+            &price_provider,
+            MarketType::BinanceSpot,
+            "BTCUSDT",
+            0,
+            1_000_000 * STEP_MS,
+            STEP_MS,
+            1,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(calculator.calls.load(Ordering::SeqCst), 50);
+    assert_eq!(records.len(), 50);
+    for (i, record) in records.iter().enumerate() {
+        assert_eq!(record.timestamp, i as u64 * STEP_MS);
+    }
+    // 部分记录的 forward_return 照常计算，最后一条没有未来价格
+    assert_eq!(records[0].forward_return, Some(1.0 / 100.0));
+    assert!(records[48].forward_return.is_some());
+    assert_eq!(records[49].forward_return, None);
+}
+
+#[tokio::test]
+async fn test_progress_callback() {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = test_market_mgr(&db_file, clock.clone());
+    let calculator = ClockFactor {
+        clock: clock.clone(),
+        calls: AtomicU64::new(0),
+        cancel_after: None,
+    };
+    let price_provider = ClockPrice {
+        clock: clock.clone(),
+    };
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let progress_clone = progress.clone();
+    let backtester = FactorBacktester::new(market_mgr, clock.clone()).with_progress(
+        10,
+        Box::new(move |cur_ts, processed, total| {
+            progress_clone
+                .lock()
+                .unwrap()
+                .push((cur_ts, processed, total));
+        }),
+    );
+    let records = backtester
+        .run_test(
+            &calculator,
+            &price_provider,
+            MarketType::BinanceSpot,
+            "BTCUSDT",
+            0,
+            100 * STEP_MS,
+            STEP_MS,
+            1,
+        )
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 101);
+
+    // 每 10 步回调一次，结束时再回调一次
+    let progress = progress.lock().unwrap();
+    assert_eq!(progress.len(), 11);
+    for (i, (cur_ts, processed, total)) in progress.iter().take(10).enumerate() {
+        let processed_expected = (i as u64 + 1) * 10;
+        assert_eq!(*processed, processed_expected);
+        assert_eq!(*cur_ts, processed_expected * STEP_MS);
+        assert_eq!(*total, 101);
+    }
+    assert_eq!(progress[10], (100 * STEP_MS, 101, 101));
+}
+
+const REGIME_STEPS: u64 = 400;
+const REGIME_SWITCH_STEP: u64 = 200;
+
+/// 伪随机因子值，任意 101 个连续步内互不相同
+fn regime_factor(step: u64) -> f64 {
+    ((step * 7919) % 101) as f64
+}
+
+/// 下一步收益 = sign * (factor - 50) / 50 * 0.1%，sign 为各步所处 regime 的方向
+fn regime_prices(signs: impl Fn(u64) -> f64) -> Vec<f64> {
+    let mut prices = vec![100.0];
+    for step in 0..REGIME_STEPS {
+        let ret = signs(step) * (regime_factor(step) - 50.0) / 50.0 * 0.001;
+        prices.push(prices[step as usize] * (1.0 + ret));
+    }
+    prices
+}
+
+struct RegimeFactor {
+    clock: Arc<Clock>,
+}
+
+#[async_trait]
+impl FactorCalculator for RegimeFactor {
+    async fn calculate(
+        &self,
+        _manager: &dyn MarketDataManager,
+        _market_type: &MarketType,
+        _symbol: &str,
+    ) -> Result<(f64, u64)> {
+        let cur_ts = self.clock.cur_ts();
+        Ok((regime_factor(cur_ts / STEP_MS), cur_ts))
+    }
+}
+
+struct RegimePrice {
+    clock: Arc<Clock>,
+    prices: Vec<f64>,
+}
+
+#[async_trait]
+impl PriceProvider for RegimePrice {
+    fn method(&self) -> PriceMethod {
+        PriceMethod::KlineClose
+    }
+
+    async fn get_price(
+        &self,
+        _manager: &dyn MarketDataManager,
+        _market_type: &MarketType,
+        _symbol: &str,
+    ) -> Result<(f64, u64)> {
+        let cur_ts = self.clock.cur_ts();
+        Ok((self.prices[(cur_ts / STEP_MS) as usize], cur_ts))
+    }
+}
+
+async fn run_regime_walk_forward(prices: Vec<f64>) -> WalkForwardReport {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = test_market_mgr(&db_file, clock.clone());
+    let calculator = RegimeFactor {
+        clock: clock.clone(),
+    };
+    let price_provider = RegimePrice {
+        clock: clock.clone(),
+        prices,
+    };
+    let backtester = FactorBacktester::new(market_mgr, clock.clone());
+    // 训练 100 步、测试 50 步、每次滚动 50 步
+    backtester
+        .walk_forward(
+            &calculator,
+            &price_provider,
+            MarketType::BinanceSpot,
+            "BTCUSDT",
+            0,
+            REGIME_STEPS * STEP_MS,
+            100 * STEP_MS,
+            50 * STEP_MS,
+            50 * STEP_MS,
+            STEP_MS,
+            1,
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_walk_forward_regime_change() {
+    // 前 200 步因子与未来收益正相关，之后反转
+    let report = run_regime_walk_forward(regime_prices(|step| {
+        if step < REGIME_SWITCH_STEP {
+            1.0
+        } else {
+            -1.0
+        }
+    }))
+    .await;
+
+    let test_starts: Vec<u64> = report.windows.iter().map(|w| w.test_start).collect();
+    assert_eq!(
+        test_starts,
+        [100, 150, 200, 250, 300, 350]
+            .iter()
+            .map(|s| s * STEP_MS)
+            .collect::<Vec<_>>()
+    );
+    for window in &report.windows {
+        assert_eq!(window.test_end - window.test_start, 50 * STEP_MS);
+        assert_eq!(window.test_samples, 50);
+        // 训练窗口只保留收益区间落在窗口内的样本
+        assert_eq!(window.train_samples, 100);
+        let expected = if window.test_end <= REGIME_SWITCH_STEP * STEP_MS {
+            1.0
+        } else {
+            -1.0
+        };
+        assert!((window.test_ic - expected).abs() < 1e-9);
+    }
+    // 训练窗口横跨切换点时 IC 被两段行情抵消
+    let straddle = &report.windows[2];
+    assert_eq!(straddle.train_start, 100 * STEP_MS);
+    assert!(straddle.train_ic > 0.99);
+    assert!(report.windows[3].train_ic.abs() < 0.2);
+
+    // 2 个窗口 IC=1，4 个窗口 IC=-1
+    assert!((report.ic_mean - (-1.0 / 3.0)).abs() < 1e-9);
+    assert!((report.ic_std - (8.0f64 / 9.0).sqrt()).abs() < 1e-9);
+    assert!(report.ic_ir.abs() < 0.5);
+    assert!((report.positive_ratio - 2.0 / 6.0).abs() < 1e-9);
+    // 切换后第一个测试窗口的方向与训练窗口相反
+    assert!(report.sign_consistency < 1.0);
+}
+
+#[tokio::test]
+async fn test_walk_forward_stable_factor() {
+    let report = run_regime_walk_forward(regime_prices(|_| 1.0)).await;
+    assert_eq!(report.windows.len(), 6);
+    assert!(report
+        .windows
+        .iter()
+        .all(|w| (w.test_ic - 1.0).abs() < 1e-9));
+    assert!((report.ic_mean - 1.0).abs() < 1e-9);
+    assert!(report.ic_std < 1e-9);
+    assert_eq!(report.positive_ratio, 1.0);
+    assert_eq!(report.sign_consistency, 1.0);
+}
+
+#[test]
+fn test_walk_forward_invalid_windows() {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let backtester = FactorBacktester::new(test_market_mgr(&db_file, clock.clone()), clock);
+    assert!(backtester
+        .evaluate_walk_forward(&[], 0, 1000, 100, 0, 100, 0)
+        .is_err());
+    // 时间范围不足一个训练+测试窗口
+    assert!(backtester
+        .evaluate_walk_forward(&[], 0, 1000, 800, 300, 100, 0)
+        .is_err());
+}
+
+const UNIVERSE: [&str; 4] = ["BNBUSDT", "BTCUSDT", "ETHUSDT", "SOLUSDT"];
+const UNIVERSE_STEPS: u64 = 300;
+
+/// 各 symbol 的价格走势相位与周期不同
+fn universe_kline(symbol_idx: usize, i: u64) -> KlineData {
+    let (x, phase) = (i as f64, symbol_idx as f64);
+    let period = 0.2 + phase * 0.07;
+    let open = 100.0 + (x * period + phase).sin() * 5.0;
+    let close = 100.0 + ((x + 1.0) * period + phase).sin() * 5.0;
+    let decimal = |v: f64| Decimal::from_f64(v).unwrap().round_dp(8);
+    KlineData {
+        symbol: UNIVERSE[symbol_idx].to_string(),
+        interval: KlineInterval::OneMinute,
+        open_time: i * STEP_MS,
+        close_time: i * STEP_MS + STEP_MS - 1,
+        open: decimal(open),
+        high: decimal(open.max(close) + 0.5),
+        low: decimal(open.min(close) - 0.5),
+        close: decimal(close),
+        volume: decimal(10.0 + (x * 1.3 + phase).cos().abs() * 90.0),
+        quote_volume: Decimal::ZERO,
+        taker_buy_volume: Decimal::ZERO,
+        taker_buy_quote_volume: Decimal::ZERO,
+        is_closed: 1,
+    }
+}
+
+fn record_fields(records: &[FactorRecord]) -> Vec<(u64, f64, u64, f64, u64, Option<f64>)> {
+    records
+        .iter()
+        .map(|r| {
+            (
+                r.timestamp,
+                r.factor_value,
+                r.factor_timestamp,
+                r.price,
+                r.price_timestamp,
+                r.forward_return,
+            )
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_run_universe_matches_serial() {
+    let klines = (0..UNIVERSE.len())
+        .flat_map(|idx| (0..UNIVERSE_STEPS).map(move |i| universe_kline(idx, i)))
+        .collect::<Vec<_>>();
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = kline_market_mgr(&db_file, clock.clone(), &klines);
+    let symbols = UNIVERSE.map(|s| s.to_string());
+    let calculator = |_: &str| {
+        Box::new(KlineFactorCalculators::new(
+            KlineFactorType::PriceVolatility,
+            KlineInterval::OneMinute,
+            20,
+        )) as Box<dyn FactorCalculator>
+    };
+    let price_provider = |_: &str| {
+        Box::new(KlineClosePriceProvider::new(KlineInterval::OneMinute)) as Box<dyn PriceProvider>
+    };
+    let (start_ts, end_ts) = (30 * STEP_MS, (UNIVERSE_STEPS - 1) * STEP_MS);
+
+    // 串行：逐个 symbol 在同一个回测器上运行，每个 symbol 使用新的计算器
+    let serial_backtester = FactorBacktester::new(market_mgr.clone(), clock.clone());
+    let mut serial = HashMap::new();
+    for symbol in symbols.iter() {
+        let records = serial_backtester
+            .run_test(
+                calculator(symbol).as_ref(),
+                price_provider(symbol).as_ref(),
+                MarketType::BinanceSpot,
+                symbol,
+                start_ts,
+                end_ts,
+                STEP_MS,
+                5,
+            )
+            .await
+            .unwrap();
+        serial.insert(symbol.clone(), records);
+    }
+
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let progress_clone = progress.clone();
+    let clock = Arc::new(Clock::new(0));
+    let backtester = FactorBacktester::new(market_mgr, clock.clone()).with_progress(
+        1,
+        Box::new(move |cur_ts, finished, total| {
+            progress_clone
+                .lock()
+                .unwrap()
+                .push((cur_ts, finished, total));
+        }),
+    );
+    let parallel = backtester
+        .run_universe(
+            &calculator,
+            &price_provider,
+            MarketType::BinanceSpot,
+            &symbols,
+            start_ts,
+            end_ts,
+            STEP_MS,
+            5,
+            2,
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(parallel.len(), UNIVERSE.len());
+    for symbol in symbols.iter() {
+        let serial_records = &serial[symbol];
+        // 覆盖完整时间轴，最后 5 条没有未来价格
+        assert_eq!(serial_records.len(), (UNIVERSE_STEPS - 30) as usize);
+        assert_eq!(
+            serial_records
+                .iter()
+                .filter(|r| r.forward_return.is_some())
+                .count(),
+            serial_records.len() - 5
+        );
+        assert_eq!(
+            record_fields(&parallel[symbol]),
+            record_fields(serial_records),
+            "{} records differ",
+            symbol
+        );
+    }
+    // 各 symbol 的因子值互不相同，确认没有串用其他 symbol 的数据
+    assert_ne!(
+        record_fields(&parallel["BTCUSDT"]),
+        record_fields(&parallel["ETHUSDT"])
+    );
+    // 任务使用各自的时钟，不推进回测器自身的时钟
+    assert_eq!(clock.cur_ts(), 0);
+    assert_eq!(
+        *progress.lock().unwrap(),
+        (1..=UNIVERSE.len() as u64)
+            .map(|finished| (end_ts, finished, UNIVERSE.len() as u64))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_factor_cache_shared_across_horizons() {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = test_market_mgr(&db_file, clock.clone());
+    let calculator = ClockFactor {
+        clock: clock.clone(),
+        calls: AtomicU64::new(0),
+        cancel_after: None,
+    };
+    let price_provider = ClockPrice {
+        clock: clock.clone(),
+    };
+    let cache = Arc::new(FactorCache::new(1000));
+    let backtester = FactorBacktester::new(market_mgr.clone(), clock.clone())
+        .with_factor_cache(cache.clone(), "clock_factor");
+
+    let run = |forward_steps: usize, end_step: u64| {
+        backtester.run_test(
+            &calculator,
+            &price_provider,
+            MarketType::BinanceSpot,
+            "BTCUSDT",
+            0,
+            end_step * STEP_MS,
+            STEP_MS,
+            forward_steps,
+        )
+    };
+    let first = run(1, 100).await.unwrap();
+    assert_eq!(calculator.calls.load(Ordering::SeqCst), 101);
+    assert_eq!(cache.len(), 101);
+
+    // 同一因子换 horizon 重跑，全部命中缓存，不再调用 calculator
+    let second = run(5, 100).await.unwrap();
+    assert_eq!(calculator.calls.load(Ordering::SeqCst), 101);
+    assert_eq!(second.len(), first.len());
+    for (a, b) in first.iter().zip(second.iter()) {
+        assert_eq!(
+            (a.timestamp, a.factor_value, a.factor_timestamp),
+            (b.timestamp, b.factor_value, b.factor_timestamp)
+        );
+    }
+    assert_eq!(second[0].forward_return, Some(5.0 / 100.0));
+    assert_eq!(cache.stats().hits, 101);
+
+    // 延长时间范围只计算新增的时间点
+    run(1, 120).await.unwrap();
+    assert_eq!(calculator.calls.load(Ordering::SeqCst), 121);
+
+    // 行情变化后失效的时间点重新计算
+    cache.invalidate_symbol(&MarketType::BinanceSpot, "BTCUSDT", 110 * STEP_MS);
+    run(1, 120).await.unwrap();
+    assert_eq!(calculator.calls.load(Ordering::SeqCst), 132);
+
+    // 其他 factor_id 不共享缓存
+    let other = FactorBacktester::new(market_mgr, clock.clone())
+        .with_factor_cache(cache.clone(), "other_factor");
+    other
+        .run_test(
+            &calculator,
+            &price_provider,
+            MarketType::BinanceSpot,
+            "BTCUSDT",
+            0,
+            10 * STEP_MS,
+            STEP_MS,
+            1,
+        )
+        .await
+        .unwrap();
+    assert_eq!(calculator.calls.load(Ordering::SeqCst), 143);
+}
+
+/// 以基准价格作为因子值，便于核对每个评估点读到的行情
+struct PriceFactor<P: PriceProvider>(P);
+
+#[async_trait]
+impl<P: PriceProvider> FactorCalculator for PriceFactor<P> {
+    async fn calculate(
+        &self,
+        manager: &dyn MarketDataManager,
+        market_type: &MarketType,
+        symbol: &str,
+    ) -> Result<(f64, u64)> {
+        self.0.get_price(manager, market_type, symbol).await
+    }
+}
+
+#[tokio::test]
+async fn test_event_step_kline_close() {
+    // 前 20 分钟每分钟一根 kline，之后只有 60/120/180 分钟三根
+    let minutes = (0..20).chain([60, 120, 180]).collect::<Vec<u64>>();
+    let klines = minutes
+        .iter()
+        .map(|i| universe_kline(1, *i))
+        .collect::<Vec<_>>();
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = kline_market_mgr(&db_file, clock.clone(), &klines);
+    let calculator = PriceFactor(KlineClosePriceProvider::new(KlineInterval::OneMinute));
+    let price_provider = KlineClosePriceProvider::new(KlineInterval::OneMinute);
+    let (calculator, price_provider) = (&calculator, &price_provider);
+    let step_ms = 5 * STEP_MS;
+    let run = |backtester: FactorBacktester| async move {
+        backtester
+            .run_test(
+                calculator,
+                price_provider,
+                MarketType::BinanceSpot,
+                "BTCUSDT",
+                0,
+                200 * STEP_MS,
+                step_ms,
+                1,
+            )
+            .await
+            .unwrap()
+    };
+
+    // 固定步长：密集段每 5 分钟只取一个点，稀疏段只在 kline 后 step_ms 内有记录
+    let fixed = run(FactorBacktester::new(market_mgr.clone(), clock.clone())).await;
+    assert_eq!(
+        fixed
+            .iter()
+            .map(|r| r.timestamp / STEP_MS)
+            .collect::<Vec<_>>(),
+        [5, 10, 15, 20, 65, 125, 185]
+    );
+
+    // 事件步进：每根 kline 收盘恰好一条记录
+    let events = run(FactorBacktester::new(market_mgr, clock.clone())
+        .with_step_mode(StepMode::KlineClose(KlineInterval::OneMinute)))
+    .await;
+    assert_eq!(events.len(), klines.len());
+    for (i, (record, kline)) in events.iter().zip(klines.iter()).enumerate() {
+        let close = kline.close.to_string().parse::<f64>().unwrap();
+        assert_eq!(record.timestamp, kline.close_time);
+        assert_eq!(record.price_timestamp, kline.open_time);
+        assert_eq!(record.price, close);
+        // forward_return 取下一个事件的价格，跨越稀疏段也不缺失
+        match klines.get(i + 1) {
+            Some(next) => {
+                let next_close = next.close.to_string().parse::<f64>().unwrap();
+                let expected = (next_close - close) / close;
+                assert!((record.forward_return.unwrap() - expected).abs() < 1e-12);
+            }
+            None => assert_eq!(record.forward_return, None),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_event_step_trade() {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = kline_market_mgr(&db_file, clock.clone(), &[universe_kline(1, 0)]);
+    // 1s 有两笔成交，之后 2s、3s、120s、300s、301s 各一笔
+    let trade_secs = [1, 1, 2, 3, 120, 300, 301];
+    let trades = trade_secs
+        .iter()
+        .enumerate()
+        .map(|(i, sec)| Trade {
+            symbol: "BTCUSDT".to_string(),
+            trade_id: (i + 1).to_string(),
+            price: Decimal::from(100 + i as i64),
+            quantity: Decimal::ONE,
+            timestamp: sec * 1000,
+            is_buyer_maker: 0,
+            seq_id: i as u64 + 1,
+        })
+        .collect::<Vec<_>>();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    update_trade_data(db, &MarketType::BinanceSpot, &trades).unwrap();
+
+    let calculator = PriceFactor(TradePriceProvider::new());
+    let price_provider = TradePriceProvider::new();
+    let (calculator, price_provider) = (&calculator, &price_provider);
+    let run = |backtester: FactorBacktester| async move {
+        backtester
+            .run_test(
+                calculator,
+                price_provider,
+                MarketType::BinanceSpot,
+                "BTCUSDT",
+                0,
+                360_000,
+                STEP_MS,
+                1,
+            )
+            .await
+            .unwrap()
+    };
+
+    // 固定步长错过开头的密集成交，安静期按延迟阈值重复评估同一笔成交
+    let fixed = run(FactorBacktester::new(market_mgr.clone(), clock.clone())).await;
+    assert_eq!(
+        fixed.iter().map(|r| r.timestamp / 1000).collect::<Vec<_>>(),
+        [60, 120, 180, 300, 360]
+    );
+
+    let events =
+        run(FactorBacktester::new(market_mgr, clock.clone()).with_step_mode(StepMode::Trade)).await;
+    // 同一时间戳的成交只评估一次，价格为该时间戳最后一笔
+    assert_eq!(
+        events
+            .iter()
+            .map(|r| r.timestamp / 1000)
+            .collect::<Vec<_>>(),
+        [1, 2, 3, 120, 300, 301]
+    );
+    assert_eq!(
+        events.iter().map(|r| r.price).collect::<Vec<_>>(),
+        [101.0, 102.0, 103.0, 104.0, 105.0, 106.0]
+    );
+    assert_eq!(events[0].forward_return, Some(1.0 / 101.0));
+    assert_eq!(events[5].forward_return, None);
+}
+
+fn factor_records(points: &[(u64, f64)]) -> Vec<FactorRecord> {
+    points
+        .iter()
+        .map(|(step, factor_value)| FactorRecord {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: step * STEP_MS,
+            factor_value: *factor_value,
+            factor_timestamp: step * STEP_MS,
+            price: 100.0,
+            price_timestamp: step * STEP_MS,
+            forward_return: None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_factor_correlation() {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let backtester = FactorBacktester::new(test_market_mgr(&db_file, clock.clone()), clock);
+
+    // 对齐后的样本为 step 0..5，x = -2..=2
+    let xs = [(0u64, -2.0), (1, -1.0), (2, 0.0), (3, 1.0), (4, 2.0)];
+    let mut records_by_factor = HashMap::new();
+    // base 多出的时间点和 linear 的 NaN 样本在对齐时被剔除
+    let mut base = xs.to_vec();
+    base.push((10, 5.0));
+    records_by_factor.insert("base".to_string(), factor_records(&base));
+    let mut linear = xs
+        .iter()
+        .map(|(s, x)| (*s, 2.0 * x + 1.0))
+        .collect::<Vec<_>>();
+    linear.push((11, f64::NAN));
+    records_by_factor.insert("linear".to_string(), factor_records(&linear));
+    // x^2 与 x 的 Pearson 和 Rank 相关系数均为 0
+    let square = xs.iter().map(|(s, x)| (*s, x * x)).collect::<Vec<_>>();
+    records_by_factor.insert("square".to_string(), factor_records(&square));
+    // x^3 单调但非线性
+    let cube = xs
+        .iter()
+        .rev()
+        .map(|(s, x)| (*s, x * x * x))
+        .collect::<Vec<_>>();
+    records_by_factor.insert("cube".to_string(), factor_records(&cube));
+
+    let pearson = backtester
+        .factor_correlation(&records_by_factor, CorrelationMethod::Pearson)
+        .unwrap();
+    assert_eq!(pearson.factors, ["base", "cube", "linear", "square"]);
+    assert_eq!(pearson.samples, 5);
+    let close = |v: Option<f64>, expected: f64| (v.unwrap() - expected).abs() < 1e-12;
+    assert!(close(pearson.get("base", "linear"), 1.0));
+    assert!(close(pearson.get("linear", "base"), 1.0));
+    assert!(close(pearson.get("base", "square"), 0.0));
+    assert!(close(pearson.get("linear", "square"), 0.0));
+    assert!(close(pearson.get("square", "square"), 1.0));
+    // corr(x, x^3) = sum(x^4) / sqrt(sum(x^2) * sum(x^6)) = 34 / sqrt(10 * 130)
+    assert!(close(pearson.get("base", "cube"), 34.0 / 1300f64.sqrt()));
+    assert_eq!(pearson.get("base", "missing"), None);
+
+    let rank = backtester
+        .factor_correlation(&records_by_factor, CorrelationMethod::Rank)
+        .unwrap();
+    assert!(close(rank.get("base", "cube"), 1.0));
+    assert!(close(rank.get("base", "linear"), 1.0));
+    assert!(close(rank.get("base", "square"), 0.0));
+    for i in 0..4 {
+        for j in 0..4 {
+            assert_eq!(rank.matrix[i][j], rank.matrix[j][i]);
+        }
+    }
+
+    // 没有共同时间点时报错
+    let mut disjoint = HashMap::new();
+    disjoint.insert("a".to_string(), factor_records(&[(0, 1.0), (1, 2.0)]));
+    disjoint.insert("b".to_string(), factor_records(&[(2, 1.0), (3, 2.0)]));
+    assert!(backtester
+        .factor_correlation(&disjoint, CorrelationMethod::Pearson)
+        .is_err());
+}
+
+/// 以 base + tick * k 报价的成交序列上回测成交 VWAP 因子的 Rank IC
+async fn trade_vwap_ic(base: Decimal, tick: Decimal) -> f64 {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = kline_market_mgr(&db_file, clock.clone(), &[universe_kline(1, 0)]);
+    let trades = (0..300u64)
+        .map(|i| Trade {
+            symbol: "BTCUSDT".to_string(),
+            trade_id: (i + 1).to_string(),
+            price: base + tick * Decimal::from((i * i * 7 + i * 3) % 11),
+            quantity: Decimal::from(100 + (i * 37) % 50),
+            timestamp: (i + 1) * 1000,
+            is_buyer_maker: i % 2,
+            seq_id: i + 1,
+        })
+        .collect::<Vec<_>>();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    update_trade_data(db, &MarketType::BinanceSpot, &trades).unwrap();
+
+    let backtester = FactorBacktester::new(market_mgr, clock).with_step_mode(StepMode::Trade);
+    let records = backtester
+        .run_test(
+            &TradeFactorCalculators::new(TradeFactorType::Vwap, 20),
+            &TradePriceProvider::new(),
+            MarketType::BinanceSpot,
+            "BTCUSDT",
+            0,
+            300_000,
+            STEP_MS,
+            1,
+        )
+        .await
+        .unwrap();
+    assert!(records.len() > 200);
+    backtester.calculate_ic(&records)
+}
+
+#[tokio::test]
+async fn test_low_price_ic_matches_scaled_prices() {
+    // 0.00001234 附近的 vwap 相差约 1e-10，固定 1e-9 的并列阈值会把所有样本判为并列，IC 退化为 0
+    let low = trade_vwap_ic(Decimal::new(1234, 8), Decimal::new(1, 8)).await;
+    let high = trade_vwap_ic(Decimal::from(1234), Decimal::ONE).await;
+    assert!(low.abs() > 0.01);
+    assert!((low - high).abs() < 1e-12, "{} != {}", low, high);
+}
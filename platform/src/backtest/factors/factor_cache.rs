@@ -0,0 +1,121 @@
+use crate::models::MarketType;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Mutex,
+};
+
+/// 缓存键：(factor_id, 市场, symbol, 回测时钟时间)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FactorCacheKey {
+    pub factor_id: String,
+    pub market_type: MarketType,
+    pub symbol: String,
+    pub ts: u64,
+}
+
+/// 缓存命中统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FactorCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+struct CacheInner {
+    entries: HashMap<FactorCacheKey, ((f64, u64), u64)>, // key -> ((因子值, 行情时间戳), 最近访问序号)
+    lru: BTreeMap<u64, FactorCacheKey>,                  // 最近访问序号 -> key，最小的最久未使用
+    next_tick: u64,
+    stats: FactorCacheStats,
+}
+
+/// 因子值的 LRU 缓存，多个回测（不同 forward_steps、不同策略）共享同一因子时避免重复计算
+/// 超过 capacity 时淘汰最久未访问的条目；行情数据变化后需调用 invalidate_* 清理受影响的值
+pub struct FactorCache {
+    capacity: usize,
+    inner: Mutex<CacheInner>,
+}
+
+impl FactorCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(CacheInner {
+                entries: HashMap::new(),
+                lru: BTreeMap::new(),
+                next_tick: 0,
+                stats: FactorCacheStats::default(),
+            }),
+        }
+    }
+
+    pub fn get(&self, key: &FactorCacheKey) -> Option<(f64, u64)> {
+        let mut inner = self.inner.lock().unwrap();
+        let tick = inner.next_tick;
+        let Some((value, last_tick)) = inner.entries.get_mut(key) else {
+            inner.stats.misses += 1;
+            return None;
+        };
+        let (value, prev_tick) = (*value, std::mem::replace(last_tick, tick));
+        inner.lru.remove(&prev_tick);
+        inner.lru.insert(tick, key.clone());
+        inner.next_tick += 1;
+        inner.stats.hits += 1;
+        Some(value)
+    }
+
+    pub fn insert(&self, key: FactorCacheKey, value: (f64, u64)) {
+        let mut inner = self.inner.lock().unwrap();
+        let tick = inner.next_tick;
+        inner.next_tick += 1;
+        if let Some((_, prev_tick)) = inner.entries.insert(key.clone(), (value, tick)) {
+            inner.lru.remove(&prev_tick);
+        }
+        inner.lru.insert(tick, key);
+        while inner.entries.len() > self.capacity {
+            let Some((_, oldest)) = inner.lru.pop_first() else {
+                break;
+            };
+            inner.entries.remove(&oldest);
+            inner.stats.evictions += 1;
+        }
+    }
+
+    /// symbol 在 since_ts 及之后的行情发生变化（补数据、修正）时调用，清理所有因子在此之后的缓存值
+    pub fn invalidate_symbol(&self, market_type: &MarketType, symbol: &str, since_ts: u64) {
+        self.retain(|key| {
+            !(key.market_type == *market_type && key.symbol == symbol && key.ts >= since_ts)
+        });
+    }
+
+    /// 因子实现或参数变化时清理该因子的全部缓存值
+    pub fn invalidate_factor(&self, factor_id: &str) {
+        self.retain(|key| key.factor_id != factor_id);
+    }
+
+    pub fn clear(&self) {
+        self.retain(|_| false);
+    }
+
+    fn retain(&self, keep: impl Fn(&FactorCacheKey) -> bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let inner = &mut *inner;
+        inner.entries.retain(|key, _| keep(key));
+        inner.lru.retain(|_, key| keep(key));
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn stats(&self) -> FactorCacheStats {
+        self.inner.lock().unwrap().stats.clone()
+    }
+}
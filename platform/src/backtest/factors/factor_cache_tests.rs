@@ -0,0 +1,89 @@
+use crate::{
+    backtest::factors::factor_cache::{FactorCache, FactorCacheKey, FactorCacheStats},
+    models::MarketType,
+};
+
+fn key(factor_id: &str, symbol: &str, ts: u64) -> FactorCacheKey {
+    FactorCacheKey {
+        factor_id: factor_id.to_string(),
+        market_type: MarketType::BinanceSpot,
+        symbol: symbol.to_string(),
+        ts,
+    }
+}
+
+#[test]
+fn test_lru_eviction_bounds_size() {
+    let cache = FactorCache::new(3);
+    for ts in 0..3 {
+        cache.insert(key("f", "BTCUSDT", ts), (ts as f64, ts));
+    }
+    // 访问 ts=0 后，最久未使用的是 ts=1
+    assert_eq!(cache.get(&key("f", "BTCUSDT", 0)), Some((0.0, 0)));
+    cache.insert(key("f", "BTCUSDT", 3), (3.0, 3));
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.get(&key("f", "BTCUSDT", 1)), None);
+    assert_eq!(cache.get(&key("f", "BTCUSDT", 0)), Some((0.0, 0)));
+    assert_eq!(cache.get(&key("f", "BTCUSDT", 2)), Some((2.0, 2)));
+    assert_eq!(cache.get(&key("f", "BTCUSDT", 3)), Some((3.0, 3)));
+
+    // 覆盖已有 key 不增加条目
+    cache.insert(key("f", "BTCUSDT", 3), (30.0, 3));
+    assert_eq!(cache.len(), 3);
+    assert_eq!(cache.get(&key("f", "BTCUSDT", 3)), Some((30.0, 3)));
+
+    // 大量写入后条目数始终不超过容量
+    for ts in 100..10_000 {
+        cache.insert(key("f", "ETHUSDT", ts), (ts as f64, ts));
+        assert!(cache.len() <= cache.capacity());
+    }
+    assert_eq!(
+        cache.get(&key("f", "ETHUSDT", 9_999)),
+        Some((9_999.0, 9_999))
+    );
+    assert_eq!(cache.get(&key("f", "ETHUSDT", 100)), None);
+    assert_eq!(
+        cache.stats(),
+        FactorCacheStats {
+            hits: 6,
+            misses: 2,
+            evictions: 1 + 9_900,
+        }
+    );
+}
+
+#[test]
+fn test_invalidate() {
+    let cache = FactorCache::new(100);
+    for ts in 0..10 {
+        cache.insert(key("f1", "BTCUSDT", ts), (1.0, ts));
+        cache.insert(key("f2", "BTCUSDT", ts), (2.0, ts));
+        cache.insert(key("f1", "ETHUSDT", ts), (3.0, ts));
+    }
+    assert_eq!(cache.len(), 30);
+
+    // BTCUSDT 从 ts=5 起的数据变化，所有因子在此之后的值失效
+    cache.invalidate_symbol(&MarketType::BinanceSpot, "BTCUSDT", 5);
+    assert_eq!(cache.len(), 20);
+    assert!(cache.get(&key("f1", "BTCUSDT", 4)).is_some());
+    assert!(cache.get(&key("f1", "BTCUSDT", 5)).is_none());
+    assert!(cache.get(&key("f2", "BTCUSDT", 9)).is_none());
+    assert!(cache.get(&key("f1", "ETHUSDT", 9)).is_some());
+
+    cache.invalidate_factor("f1");
+    assert_eq!(cache.len(), 5);
+    assert!(cache.get(&key("f2", "BTCUSDT", 0)).is_some());
+
+    // 失效后的 key 不再占用 LRU 位置，重新写入后正常淘汰
+    let small = FactorCache::new(2);
+    small.insert(key("f", "BTCUSDT", 0), (0.0, 0));
+    small.insert(key("f", "BTCUSDT", 1), (1.0, 1));
+    small.invalidate_factor("f");
+    small.insert(key("g", "BTCUSDT", 0), (0.0, 0));
+    small.insert(key("g", "BTCUSDT", 1), (1.0, 1));
+    assert_eq!(small.len(), 2);
+    assert_eq!(small.stats().evictions, 0);
+
+    cache.clear();
+    assert!(cache.is_empty());
+}
@@ -1,11 +1,12 @@
 use crate::{
     backtest::factors::traits::FactorCalculator,
-    data_manager::{local_data_manager::LocalMarketDataManager, MarketDataManager},
+    data_manager::MarketDataManager,
     errors::Result,
-    factors::{calc_kline_factors, calc_trade_factors},
+    factors::{calc_trade_factors, KlineFactors, RollingKlineFactors},
     models::{KlineData, KlineInterval, MarketType, Trade},
 };
 use async_trait::async_trait;
+use std::sync::Mutex;
 
 pub enum KlineFactorType {
     PriceReturn,
@@ -46,6 +47,7 @@ pub struct KlineFactorCalculators {
     pub factor_type: KlineFactorType,
     pub interval: KlineInterval,
     pub window_size: usize,
+    rolling: Mutex<RollingKlineFactors>, // 增量维护的窗口统计，回测按时间前进时每步只处理新增kline
 }
 
 impl KlineFactorCalculators {
@@ -54,18 +56,64 @@ impl KlineFactorCalculators {
             factor_type,
             interval,
             window_size,
+            rolling: Mutex::new(RollingKlineFactors::new(window_size)),
         }
     }
-}
 
-#[async_trait]
-impl FactorCalculator for KlineFactorCalculators {
-    async fn calculate(
+    // 上次窗口之后新增的kline，无法增量更新（首次、时间回退、缺失、已有kline被修改）时返回None
+    async fn fetch_new_klines(
         &self,
-        manager: &LocalMarketDataManager,
+        manager: &dyn MarketDataManager,
         market_type: &MarketType,
         symbol: &str,
-    ) -> Result<(f64, u64)> {
+    ) -> Result<Option<Vec<KlineData>>> {
+        let last = match self.rolling.lock().unwrap().last_kline() {
+            Some(last) => last.clone(),
+            None => return Ok(None),
+        };
+        let symbol = symbol.to_string();
+        let latest = manager
+            .get_klines(market_type, &symbol, &self.interval, Some(1))
+            .await?;
+        let latest_open_time = match latest.last() {
+            Some(kline) if kline.open_time >= last.open_time => kline.open_time,
+            _ => return Ok(None),
+        };
+        // 按bar边界逐根推进计数，月线各月长度不同
+        let mut new_count = 0;
+        let mut open_time = last.open_time;
+        while open_time < latest_open_time {
+            if new_count == self.window_size {
+                return Ok(None);
+            }
+            open_time = self.interval.next_open_time(open_time);
+            new_count += 1;
+        }
+
+        // 多取一根用于校验与上次窗口的衔接
+        let klines = manager
+            .get_klines(market_type, &symbol, &self.interval, Some(new_count + 1))
+            .await?;
+        let continuous = klines.len() == new_count + 1
+            && klines[0].open_time == last.open_time
+            && klines[0].close == last.close
+            && klines[0].volume == last.volume
+            && klines
+                .windows(2)
+                .all(|w| w[1].open_time == self.interval.next_open_time(w[0].open_time));
+        if !continuous {
+            return Ok(None);
+        }
+        Ok(Some(klines[1..].to_vec()))
+    }
+
+    // 全量获取窗口并校验，重建增量状态
+    async fn reload_window(
+        &self,
+        manager: &dyn MarketDataManager,
+        market_type: &MarketType,
+        symbol: &str,
+    ) -> Result<()> {
         let klines: Vec<KlineData> = manager
             .get_klines(
                 market_type,
@@ -90,9 +138,9 @@ impl FactorCalculator for KlineFactorCalculators {
         }
         let start_open_time = klines.first().unwrap().open_time;
         let end_open_time = klines.last().unwrap().open_time;
-        let close_time = klines.last().unwrap().close_time;
-        if (end_open_time - start_open_time) / self.interval.to_millis()
-            != self.window_size as u64 - 1
+        if !klines
+            .windows(2)
+            .all(|w| w[1].open_time == self.interval.next_open_time(w[0].open_time))
         {
             log::warn!(
                 "Klines are not continuous for factor calculation: start {}, end {}, expected window size {}",
@@ -110,7 +158,44 @@ impl FactorCalculator for KlineFactorCalculators {
             });
         }
 
-        let factors = calc_kline_factors(&klines)?;
+        let mut rolling = self.rolling.lock().unwrap();
+        rolling.clear();
+        for kline in klines.iter() {
+            rolling.push(kline);
+        }
+        Ok(())
+    }
+
+    async fn rolling_factors(
+        &self,
+        manager: &dyn MarketDataManager,
+        market_type: &MarketType,
+        symbol: &str,
+    ) -> Result<(KlineFactors, u64)> {
+        match self.fetch_new_klines(manager, market_type, symbol).await? {
+            Some(klines) => {
+                let mut rolling = self.rolling.lock().unwrap();
+                for kline in klines.iter() {
+                    rolling.push(kline);
+                }
+            }
+            None => self.reload_window(manager, market_type, symbol).await?,
+        }
+        let rolling = self.rolling.lock().unwrap();
+        let close_time = rolling.last_kline().map(|k| k.close_time).unwrap_or(0);
+        Ok((rolling.factors()?, close_time))
+    }
+}
+
+#[async_trait]
+impl FactorCalculator for KlineFactorCalculators {
+    async fn calculate(
+        &self,
+        manager: &dyn MarketDataManager,
+        market_type: &MarketType,
+        symbol: &str,
+    ) -> Result<(f64, u64)> {
+        let (factors, close_time) = self.rolling_factors(manager, market_type, symbol).await?;
         let factor_value = match self.factor_type {
             KlineFactorType::PriceReturn => factors.price_return,
             KlineFactorType::TrendStrength => factors.trend_strength,
@@ -212,7 +297,7 @@ impl TradeFactorCalculators {
 impl FactorCalculator for TradeFactorCalculators {
     async fn calculate(
         &self,
-        manager: &LocalMarketDataManager,
+        manager: &dyn MarketDataManager,
         market_type: &MarketType,
         symbol: &str,
     ) -> Result<(f64, u64)> {
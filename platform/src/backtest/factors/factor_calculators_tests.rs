@@ -0,0 +1,180 @@
+use crate::{
+    backtest::factors::{
+        factor_calculators::{KlineFactorCalculators, KlineFactorType},
+        traits::FactorCalculator,
+    },
+    data_manager::{
+        local_data_manager::Clock, memory_data_manager::InMemoryMarketDataManager,
+        MarketDataManager,
+    },
+    factors::calc_kline_factors,
+    models::{KlineData, KlineInterval, MarketType},
+};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use std::sync::Arc;
+
+const STEP_MS: u64 = 60_000;
+
+fn kline(i: u64) -> KlineData {
+    let x = i as f64;
+    let open = 100.0 + (x * 0.37).sin() * 5.0;
+    let close = 100.0 + ((x + 1.0) * 0.37).sin() * 5.0;
+    let decimal = |v: f64| Decimal::from_f64(v).unwrap().round_dp(8);
+    KlineData {
+        symbol: "BTCUSDT".to_string(),
+        interval: KlineInterval::OneMinute,
+        open_time: i * STEP_MS,
+        close_time: i * STEP_MS + STEP_MS - 1,
+        open: decimal(open),
+        high: decimal(open.max(close) + 0.5),
+        low: decimal(open.min(close) - 0.5),
+        close: decimal(close),
+        volume: decimal(10.0 + (x * 1.3).cos().abs() * 90.0),
+        quote_volume: Decimal::ZERO,
+        taker_buy_volume: Decimal::ZERO,
+        taker_buy_quote_volume: Decimal::ZERO,
+        is_closed: 1,
+    }
+}
+
+#[tokio::test]
+async fn test_kline_calculator_incremental_matches_naive() {
+    // 第250根缺失，跨越缺失的窗口应返回错误
+    let klines: Vec<KlineData> = (0..400).filter(|i| *i != 250).map(kline).collect();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = Arc::new(InMemoryMarketDataManager::new(clock.clone()));
+    let market_type = MarketType::BinanceSpot;
+    market_mgr.add_klines(&market_type, &klines);
+    let symbol = "BTCUSDT".to_string();
+    let window_size = 30;
+    let calculators = [
+        KlineFactorType::PriceVolatility,
+        KlineFactorType::PricePosition,
+        KlineFactorType::VolumeTrend,
+        KlineFactorType::OBV,
+        KlineFactorType::PriceVolumeCorrelation,
+        KlineFactorType::AvgIntradayRange,
+    ]
+    .map(|factor_type| {
+        KlineFactorCalculators::new(factor_type, KlineInterval::OneMinute, window_size)
+    });
+
+    let (mut ok_count, mut err_count) = (0, 0);
+    let mut cur_ts = 0;
+    for step in 0..300u64 {
+        // 时钟交替前进1根和3根kline
+        cur_ts += if step % 2 == 0 { STEP_MS } else { 3 * STEP_MS };
+        if cur_ts > 400 * STEP_MS {
+            break;
+        }
+        clock.set_cur_ts(cur_ts);
+
+        let window = market_mgr
+            .get_klines(
+                &market_type,
+                &symbol,
+                &KlineInterval::OneMinute,
+                Some(window_size),
+            )
+            .await
+            .unwrap();
+        let continuous = window.len() == window_size
+            && window.last().unwrap().open_time - window[0].open_time
+                == (window_size as u64 - 1) * STEP_MS;
+        for calculator in calculators.iter() {
+            let result = calculator
+                .calculate(market_mgr.as_ref(), &market_type, &symbol)
+                .await;
+            if !continuous {
+                assert!(result.is_err(), "expect err at {}", cur_ts);
+                continue;
+            }
+            let expected = calc_kline_factors(&window).unwrap();
+            let expected = match calculator.factor_type {
+                KlineFactorType::PriceVolatility => expected.price_volatility,
+                KlineFactorType::PricePosition => expected.price_position,
+                KlineFactorType::VolumeTrend => expected.volume_trend,
+                KlineFactorType::OBV => expected.obv,
+                KlineFactorType::PriceVolumeCorrelation => expected.price_volume_correlation,
+                KlineFactorType::AvgIntradayRange => expected.avg_intraday_range,
+                _ => unreachable!(),
+            };
+            let (value, ts) = result.unwrap();
+            assert!(
+                (value - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+                "at {}: incremental {} naive {}",
+                cur_ts,
+                value,
+                expected
+            );
+            assert_eq!(ts, window.last().unwrap().close_time);
+        }
+        if continuous {
+            ok_count += 1;
+        } else {
+            err_count += 1;
+        }
+    }
+    assert!(ok_count > 100);
+    assert!(err_count > 0);
+}
+
+#[tokio::test]
+async fn test_kline_calculator_monthly_window() {
+    // 月线按自然月推进，窗口跨越多年时按30天估算会误判为不连续；第115根缺失
+    let month = KlineInterval::OneMonth;
+    let mut open_times = vec![0];
+    for _ in 1..130 {
+        open_times.push(month.next_open_time(*open_times.last().unwrap()));
+    }
+    let klines: Vec<KlineData> = open_times
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != 115)
+        .map(|(i, &open_time)| KlineData {
+            interval: KlineInterval::OneMonth,
+            open_time,
+            close_time: month.next_open_time(open_time) - 1,
+            ..kline(i as u64)
+        })
+        .collect();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = Arc::new(InMemoryMarketDataManager::new(clock.clone()));
+    let market_type = MarketType::BinanceSpot;
+    market_mgr.add_klines(&market_type, &klines);
+    let symbol = "BTCUSDT".to_string();
+    let window_size = 100;
+    let calculator =
+        KlineFactorCalculators::new(KlineFactorType::PriceVolatility, month.clone(), window_size);
+
+    for i in window_size - 1..open_times.len() {
+        clock.set_cur_ts(month.next_open_time(open_times[i]) - 1);
+        let result = calculator
+            .calculate(market_mgr.as_ref(), &market_type, &symbol)
+            .await;
+        // 之后的窗口都包含缺失的第115根
+        if i > 115 {
+            assert!(result.is_err(), "expect err at month {}", i);
+            continue;
+        }
+        let window = market_mgr
+            .get_klines(
+                &market_type,
+                &symbol,
+                &KlineInterval::OneMonth,
+                Some(window_size),
+            )
+            .await
+            .unwrap();
+        let expected = calc_kline_factors(&window).unwrap().price_volatility;
+        let (value, ts) = result.unwrap();
+        assert!(
+            (value - expected).abs() <= 1e-9 * expected.abs().max(1.0),
+            "at month {}: incremental {} naive {}",
+            i,
+            value,
+            expected
+        );
+        assert_eq!(ts, window.last().unwrap().close_time);
+    }
+}
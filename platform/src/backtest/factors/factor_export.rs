@@ -0,0 +1,136 @@
+use crate::{
+    backtest::factors::factor_backtest::FactorRecord,
+    data_manager::dataset::{parse_csv_row, write_csv_row},
+    errors::{PlatformError, Result},
+};
+use std::io::{BufRead, Read, Write};
+
+pub const FACTOR_RECORD_CSV_HEADER: &[&str] = &[
+    "symbol",
+    "timestamp",
+    "factor_value",
+    "factor_timestamp",
+    "price",
+    "price_timestamp",
+    "forward_return",
+];
+
+// NaN、Infinity 与缺失的 forward_return 均写为空字段，便于 pandas 读为 NaN
+fn csv_f64(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v.is_finite() => v.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn parse_f64(field: &str, line_no: usize) -> Result<Option<f64>> {
+    if field.is_empty() {
+        return Ok(None);
+    }
+    field
+        .parse::<f64>()
+        .map(Some)
+        .map_err(|e| PlatformError::StorageError {
+            message: format!("invalid number {:?} at line {}: {}", field, line_no, e),
+        })
+}
+
+fn parse_u64(field: &str, line_no: usize) -> Result<u64> {
+    field
+        .parse::<u64>()
+        .map_err(|e| PlatformError::StorageError {
+            message: format!("invalid integer {:?} at line {}: {}", field, line_no, e),
+        })
+}
+
+/// 写出带表头的 csv，返回记录数
+pub fn write_factor_records_csv<W: Write>(
+    writer: &mut W,
+    records: &[FactorRecord],
+) -> Result<usize> {
+    let header = FACTOR_RECORD_CSV_HEADER
+        .iter()
+        .map(|h| h.to_string())
+        .collect::<Vec<_>>();
+    write_csv_row(writer, &header)?;
+    for record in records {
+        write_csv_row(
+            writer,
+            &[
+                record.symbol.clone(),
+                record.timestamp.to_string(),
+                csv_f64(Some(record.factor_value)),
+                record.factor_timestamp.to_string(),
+                csv_f64(Some(record.price)),
+                record.price_timestamp.to_string(),
+                csv_f64(record.forward_return),
+            ],
+        )?;
+    }
+    Ok(records.len())
+}
+
+/// 读取 write_factor_records_csv 的输出，空字段读为 NaN（forward_return 为 None）
+pub fn read_factor_records_csv<R: BufRead>(reader: R) -> Result<Vec<FactorRecord>> {
+    let mut lines = reader.lines().enumerate();
+    let header = match lines.next() {
+        Some((_, line)) => parse_csv_row(&line.map_err(|e| PlatformError::StorageError {
+            message: format!("read csv header err: {}", e),
+        })?),
+        None => return Ok(Vec::new()),
+    };
+    if header != FACTOR_RECORD_CSV_HEADER {
+        return Err(PlatformError::StorageError {
+            message: format!("unexpected factor record csv header: {:?}", header),
+        });
+    }
+
+    let mut records = Vec::new();
+    for (idx, line) in lines {
+        let line_no = idx + 1;
+        let line = line.map_err(|e| PlatformError::StorageError {
+            message: format!("read csv line {} err: {}", line_no, e),
+        })?;
+        if line.is_empty() {
+            continue;
+        }
+        let fields = parse_csv_row(&line);
+        if fields.len() != FACTOR_RECORD_CSV_HEADER.len() {
+            return Err(PlatformError::StorageError {
+                message: format!(
+                    "expected {} fields at line {}, got {}",
+                    FACTOR_RECORD_CSV_HEADER.len(),
+                    line_no,
+                    fields.len()
+                ),
+            });
+        }
+        records.push(FactorRecord {
+            symbol: fields[0].clone(),
+            timestamp: parse_u64(&fields[1], line_no)?,
+            factor_value: parse_f64(&fields[2], line_no)?.unwrap_or(f64::NAN),
+            factor_timestamp: parse_u64(&fields[3], line_no)?,
+            price: parse_f64(&fields[4], line_no)?.unwrap_or(f64::NAN),
+            price_timestamp: parse_u64(&fields[5], line_no)?,
+            forward_return: parse_f64(&fields[6], line_no)?,
+        });
+    }
+    Ok(records)
+}
+
+/// 写出 json 数组，非有限值写为 null，返回记录数
+pub fn write_factor_records_json<W: Write>(
+    writer: &mut W,
+    records: &[FactorRecord],
+) -> Result<usize> {
+    serde_json::to_writer(writer, records).map_err(|e| PlatformError::StorageError {
+        message: format!("write factor records json err: {}", e),
+    })?;
+    Ok(records.len())
+}
+
+pub fn read_factor_records_json<R: Read>(reader: R) -> Result<Vec<FactorRecord>> {
+    serde_json::from_reader(reader).map_err(|e| PlatformError::StorageError {
+        message: format!("read factor records json err: {}", e),
+    })
+}
@@ -0,0 +1,104 @@
+use crate::backtest::factors::{
+    factor_backtest::FactorRecord,
+    factor_export::{
+        read_factor_records_csv, read_factor_records_json, write_factor_records_csv,
+        write_factor_records_json,
+    },
+};
+use std::io::Cursor;
+
+fn records() -> Vec<FactorRecord> {
+    vec![
+        FactorRecord {
+            symbol: "BTCUSDT".to_string(),
+            timestamp: 1_700_000_000_000,
+            factor_value: 0.1 + 0.2,
+            factor_timestamp: 1_699_999_999_999,
+            price: 43_210.123_456_78,
+            price_timestamp: 1_699_999_940_000,
+            forward_return: Some(-1.234_567_890_123e-5),
+        },
+        FactorRecord {
+            symbol: "ETHUSDT".to_string(),
+            timestamp: 1_700_000_060_000,
+            factor_value: f64::NAN,
+            factor_timestamp: 1_700_000_059_999,
+            price: 2_000.5,
+            price_timestamp: 1_700_000_000_000,
+            forward_return: None,
+        },
+        // 含分隔符和引号的 symbol、极小值与无穷值
+        FactorRecord {
+            symbol: "A,\"B\"".to_string(),
+            timestamp: 0,
+            factor_value: f64::INFINITY,
+            factor_timestamp: 0,
+            price: 1e-300,
+            price_timestamp: u64::MAX,
+            forward_return: Some(f64::MIN_POSITIVE),
+        },
+    ]
+}
+
+// NaN 与 Infinity 均读回为 NaN，其余字段逐位一致
+fn assert_round_trip(decoded: &[FactorRecord]) {
+    let expected = records();
+    assert_eq!(decoded.len(), expected.len());
+    for (a, b) in decoded.iter().zip(expected.iter()) {
+        assert_eq!(a.symbol, b.symbol);
+        assert_eq!(a.timestamp, b.timestamp);
+        assert_eq!(a.factor_timestamp, b.factor_timestamp);
+        assert_eq!(a.price_timestamp, b.price_timestamp);
+        assert_eq!(
+            a.forward_return.map(f64::to_bits),
+            b.forward_return.map(f64::to_bits)
+        );
+        for (x, y) in [(a.factor_value, b.factor_value), (a.price, b.price)] {
+            if y.is_finite() {
+                assert_eq!(x.to_bits(), y.to_bits());
+            } else {
+                assert!(x.is_nan());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_factor_records_csv_round_trip() {
+    let mut buf = Vec::new();
+    assert_eq!(write_factor_records_csv(&mut buf, &records()).unwrap(), 3);
+    let text = String::from_utf8(buf.clone()).unwrap();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines[0],
+        "symbol,timestamp,factor_value,factor_timestamp,price,price_timestamp,forward_return"
+    );
+    // 非有限值与缺失的 forward_return 为空字段
+    assert_eq!(
+        lines[2],
+        "ETHUSDT,1700000060000,,1700000059999,2000.5,1700000000000,"
+    );
+    assert!(lines[3].starts_with("\"A,\"\"B\"\"\",0,,0,"));
+
+    assert_round_trip(&read_factor_records_csv(Cursor::new(buf)).unwrap());
+
+    // 表头不匹配或字段数不对时报错
+    assert!(read_factor_records_csv(Cursor::new("a,b\n1,2\n")).is_err());
+    let bad_row = format!("{}\nBTCUSDT,1,2\n", lines[0]);
+    assert!(read_factor_records_csv(Cursor::new(bad_row)).is_err());
+    assert!(read_factor_records_csv(Cursor::new("")).unwrap().is_empty());
+}
+
+#[test]
+fn test_factor_records_json_round_trip() {
+    let mut buf = Vec::new();
+    assert_eq!(write_factor_records_json(&mut buf, &records()).unwrap(), 3);
+    let value: serde_json::Value = serde_json::from_slice(&buf).unwrap();
+    assert_eq!(value[1]["factor_value"], serde_json::Value::Null);
+    assert_eq!(value[1]["forward_return"], serde_json::Value::Null);
+    assert_eq!(value[2]["factor_value"], serde_json::Value::Null);
+    assert_eq!(value[0]["symbol"], "BTCUSDT");
+    assert_eq!(value[0]["timestamp"], 1_700_000_000_000u64);
+
+    assert_round_trip(&read_factor_records_json(Cursor::new(buf)).unwrap());
+}
@@ -1,4 +1,20 @@
+pub mod composite_factor;
 pub mod factor_backtest;
+pub mod factor_cache;
 pub mod factor_calculators;
+pub mod factor_export;
 pub mod price_providers;
 pub mod traits;
+
+#[cfg(test)]
+mod composite_factor_tests;
+#[cfg(test)]
+mod factor_backtest_tests;
+#[cfg(test)]
+mod factor_cache_tests;
+#[cfg(test)]
+mod factor_calculators_tests;
+#[cfg(test)]
+mod factor_export_tests;
+#[cfg(test)]
+mod price_providers_tests;
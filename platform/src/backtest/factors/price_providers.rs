@@ -1,10 +1,11 @@
 use crate::{
-    backtest::factors::traits::PriceProvider,
-    data_manager::{local_data_manager::LocalMarketDataManager, MarketDataManager},
+    backtest::factors::traits::{PriceMethod, PriceProvider},
+    data_manager::MarketDataManager,
     errors::Result,
     models::{KlineData, KlineInterval, MarketType, Trade},
 };
 use async_trait::async_trait;
+use rust_decimal::Decimal;
 
 pub struct KlineClosePriceProvider {
     interval: KlineInterval,
@@ -18,9 +19,13 @@ impl KlineClosePriceProvider {
 
 #[async_trait]
 impl PriceProvider for KlineClosePriceProvider {
+    fn method(&self) -> PriceMethod {
+        PriceMethod::KlineClose
+    }
+
     async fn get_price(
         &self,
-        manager: &LocalMarketDataManager,
+        manager: &dyn MarketDataManager,
         market_type: &MarketType,
         symbol: &str,
     ) -> Result<(f64, u64)> {
@@ -28,11 +33,9 @@ impl PriceProvider for KlineClosePriceProvider {
             .get_klines(market_type, &symbol.to_string(), &self.interval, Some(1))
             .await?;
         if klines.is_empty() {
-            return Err(crate::errors::PlatformError::PlatformError {
-                message: format!(
-                    "No klines found for symbol {} at interval {:?}",
-                    symbol, self.interval
-                ),
+            return Err(crate::errors::PlatformError::DataNotFound {
+                symbol: symbol.to_string(),
+                data: format!("{:?} klines", self.interval),
             });
         }
         let last_kline = klines.last().unwrap();
@@ -47,6 +50,7 @@ impl PriceProvider for KlineClosePriceProvider {
     }
 }
 
+#[derive(Default)]
 pub struct TradePriceProvider;
 
 impl TradePriceProvider {
@@ -57,9 +61,13 @@ impl TradePriceProvider {
 
 #[async_trait]
 impl PriceProvider for TradePriceProvider {
+    fn method(&self) -> PriceMethod {
+        PriceMethod::LastTrade
+    }
+
     async fn get_price(
         &self,
-        manager: &LocalMarketDataManager,
+        manager: &dyn MarketDataManager,
         market_type: &MarketType,
         symbol: &str,
     ) -> Result<(f64, u64)> {
@@ -71,8 +79,9 @@ impl PriceProvider for TradePriceProvider {
                 "No trades found for symbol {} when getting trade price",
                 symbol
             );
-            return Err(crate::errors::PlatformError::PlatformError {
-                message: format!("No trades found for symbol {}", symbol),
+            return Err(crate::errors::PlatformError::DataNotFound {
+                symbol: symbol.to_string(),
+                data: "trades".to_string(),
             });
         }
         let last_trade = trades.last().unwrap();
@@ -86,3 +95,99 @@ impl PriceProvider for TradePriceProvider {
         Ok((price, market_timestamp))
     }
 }
+
+/// 盘口中间价 (best_bid + best_ask) / 2，行情时间戳取depth时间戳
+#[derive(Default)]
+pub struct MidPriceProvider;
+
+impl MidPriceProvider {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait]
+impl PriceProvider for MidPriceProvider {
+    fn method(&self) -> PriceMethod {
+        PriceMethod::Mid
+    }
+
+    async fn get_price(
+        &self,
+        manager: &dyn MarketDataManager,
+        market_type: &MarketType,
+        symbol: &str,
+    ) -> Result<(f64, u64)> {
+        let depth = manager.get_depth(market_type, &symbol.to_string()).await?;
+        let depth = match depth {
+            None => {
+                return Err(crate::errors::PlatformError::DataNotFound {
+                    symbol: symbol.to_string(),
+                    data: "depth".to_string(),
+                });
+            }
+            Some(depth) => depth,
+        };
+        let best_bid = depth.bids.iter().map(|level| level.price).max();
+        let best_ask = depth.asks.iter().map(|level| level.price).min();
+        let (best_bid, best_ask) = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) => (bid, ask),
+            _ => {
+                return Err(crate::errors::PlatformError::DataNotFound {
+                    symbol: symbol.to_string(),
+                    data: "depth bids/asks".to_string(),
+                });
+            }
+        };
+        let price = ((best_bid + best_ask) / Decimal::TWO)
+            .to_string()
+            .parse::<f64>()
+            .unwrap_or(f64::NAN);
+
+        Ok((price, depth.timestamp))
+    }
+}
+
+/// 最近 window_size 笔成交的成交量加权均价，行情时间戳取最后一笔成交时间
+pub struct VwapPriceProvider {
+    window_size: usize,
+}
+
+impl VwapPriceProvider {
+    pub fn new(window_size: usize) -> Self {
+        Self { window_size }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for VwapPriceProvider {
+    fn method(&self) -> PriceMethod {
+        PriceMethod::Vwap
+    }
+
+    async fn get_price(
+        &self,
+        manager: &dyn MarketDataManager,
+        market_type: &MarketType,
+        symbol: &str,
+    ) -> Result<(f64, u64)> {
+        let trades: Vec<Trade> = manager
+            .get_trades(market_type, &symbol.to_string(), Some(self.window_size))
+            .await?;
+        let volume: Decimal = trades.iter().map(|t| t.quantity).sum();
+        if trades.is_empty() || volume.is_zero() {
+            return Err(crate::errors::PlatformError::DataNotFound {
+                symbol: symbol.to_string(),
+                data: "trades".to_string(),
+            });
+        }
+        let amount: Decimal = trades.iter().map(|t| t.price * t.quantity).sum();
+        let price = (amount / volume)
+            .to_string()
+            .parse::<f64>()
+            .unwrap_or(f64::NAN);
+        let market_timestamp = trades.last().unwrap().timestamp;
+
+        Ok((price, market_timestamp))
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    backtest::factors::{
+        price_providers::{
+            KlineClosePriceProvider, MidPriceProvider, TradePriceProvider, VwapPriceProvider,
+        },
+        traits::{PriceMethod, PriceProvider},
+    },
+    data_manager::MarketDataManager,
+    errors::{PlatformError, Result},
+    models::{
+        DepthData, KlineData, KlineInterval, MarketType, PriceLevel, SymbolInfo, Ticker24hr, Trade,
+    },
+};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+// 预置kline/成交/盘口的行情数据，成交按时间升序
+#[derive(Default)]
+struct MockMarketDataManager {
+    klines: Vec<KlineData>,
+    trades: Vec<Trade>,
+    depth: Option<DepthData>,
+}
+
+#[async_trait]
+impl MarketDataManager for MockMarketDataManager {
+    async fn init(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_klines(
+        &self,
+        _market_type: &MarketType,
+        _symbol: &String,
+        _interval: &KlineInterval,
+        limit: Option<usize>,
+    ) -> Result<Vec<KlineData>> {
+        let limit = limit.unwrap_or(self.klines.len()).min(self.klines.len());
+        Ok(self.klines[self.klines.len() - limit..].to_vec())
+    }
+
+    async fn get_trades(
+        &self,
+        _market_type: &MarketType,
+        _symbol: &String,
+        limit: Option<usize>,
+    ) -> Result<Vec<Trade>> {
+        let limit = limit.unwrap_or(self.trades.len()).min(self.trades.len());
+        Ok(self.trades[self.trades.len() - limit..].to_vec())
+    }
+
+    async fn get_depth(
+        &self,
+        _market_type: &MarketType,
+        _symbol: &String,
+    ) -> Result<Option<DepthData>> {
+        Ok(self.depth.clone())
+    }
+
+    async fn get_ticker(
+        &self,
+        _market_type: &MarketType,
+        _symbol: &String,
+    ) -> Result<Option<Ticker24hr>> {
+        Ok(None)
+    }
+
+    async fn get_symbol_info(
+        &self,
+        _market_type: &MarketType,
+        _symbol: &String,
+    ) -> Result<Option<SymbolInfo>> {
+        Ok(None)
+    }
+
+    async fn get_symbol(
+        &self,
+        _market_type: &MarketType,
+        _base_asset: &String,
+        _quote_asset: &String,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+fn kline(open_time: u64, close: Decimal) -> KlineData {
+    KlineData {
+        symbol: "BTCUSDT".to_string(),
+        interval: KlineInterval::OneMinute,
+        open_time,
+        close_time: open_time + 59_999,
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: Decimal::ONE,
+        quote_volume: close,
+        taker_buy_volume: Decimal::ZERO,
+        taker_buy_quote_volume: Decimal::ZERO,
+        is_closed: 1,
+    }
+}
+
+fn trade(id: u64, price: Decimal, quantity: Decimal) -> Trade {
+    Trade {
+        symbol: "BTCUSDT".to_string(),
+        trade_id: id.to_string(),
+        price,
+        quantity,
+        timestamp: id * 1000,
+        is_buyer_maker: 0,
+        seq_id: id,
+    }
+}
+
+fn levels(prices: &[Decimal]) -> Vec<PriceLevel> {
+    prices
+        .iter()
+        .map(|price| PriceLevel {
+            price: *price,
+            quantity: Decimal::ONE,
+        })
+        .collect()
+}
+
+fn manager() -> MockMarketDataManager {
+    MockMarketDataManager {
+        klines: vec![kline(0, dec!(99)), kline(60_000, dec!(100))],
+        trades: vec![
+            trade(1, dec!(90), dec!(10)),
+            trade(2, dec!(100), dec!(1)),
+            trade(3, dec!(102), dec!(3)),
+        ],
+        depth: Some(DepthData {
+            symbol: "BTCUSDT".to_string(),
+            // 档位未排序，需取最优价
+            bids: levels(&[dec!(100.5), dec!(101), dec!(99)]),
+            asks: levels(&[dec!(102), dec!(101.5)]),
+            timestamp: 5000,
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_reference_price_by_method() {
+    let manager = manager();
+    let market_type = MarketType::BinanceSpot;
+
+    let providers: Vec<(Box<dyn PriceProvider>, PriceMethod, f64, u64)> = vec![
+        (
+            Box::new(KlineClosePriceProvider::new(KlineInterval::OneMinute)),
+            PriceMethod::KlineClose,
+            100.0,
+            60_000,
+        ),
+        (
+            Box::new(TradePriceProvider::new()),
+            PriceMethod::LastTrade,
+            102.0,
+            3000,
+        ),
+        (
+            Box::new(MidPriceProvider::new()),
+            PriceMethod::Mid,
+            101.25,
+            5000,
+        ),
+        // (100 * 1 + 102 * 3) / 4
+        (
+            Box::new(VwapPriceProvider::new(2)),
+            PriceMethod::Vwap,
+            101.5,
+            3000,
+        ),
+    ];
+    for (provider, method, expected_price, expected_ts) in providers {
+        assert_eq!(provider.method(), method);
+        let (price, ts) = provider
+            .get_price(&manager, &market_type, "BTCUSDT")
+            .await
+            .unwrap();
+        assert!(
+            (price - expected_price).abs() < 1e-9,
+            "{}: {} != {}",
+            method.as_str(),
+            price,
+            expected_price
+        );
+        assert_eq!(ts, expected_ts, "{}", method.as_str());
+    }
+
+    // 窗口覆盖全部成交：(900 + 100 + 306) / 14
+    let (price, _) = VwapPriceProvider::new(10)
+        .get_price(&manager, &market_type, "BTCUSDT")
+        .await
+        .unwrap();
+    assert!((price - 1306.0 / 14.0).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn test_missing_data_returns_not_found() {
+    let market_type = MarketType::BinanceSpot;
+
+    let mut one_sided = manager();
+    one_sided.depth.as_mut().unwrap().asks.clear();
+    let err = MidPriceProvider::new()
+        .get_price(&one_sided, &market_type, "BTCUSDT")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PlatformError::DataNotFound { .. }));
+
+    let empty = MockMarketDataManager::default();
+    let err = MidPriceProvider::new()
+        .get_price(&empty, &market_type, "BTCUSDT")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PlatformError::DataNotFound { .. }));
+    let err = VwapPriceProvider::new(5)
+        .get_price(&empty, &market_type, "BTCUSDT")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, PlatformError::DataNotFound { .. }));
+}
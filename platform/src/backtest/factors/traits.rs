@@ -1,27 +1,48 @@
-use crate::{
-    data_manager::local_data_manager::LocalMarketDataManager, errors::Result, models::MarketType,
-};
+use crate::{data_manager::MarketDataManager, errors::Result, models::MarketType};
 use async_trait::async_trait;
 
 /// 具体的因子实现这个 trait，可以基于 kline、trade、depth 等任意数据计算
 /// 返回 (因子值, 行情时间戳)
 #[async_trait]
-pub trait FactorCalculator {
+pub trait FactorCalculator: Send + Sync {
     async fn calculate(
         &self,
-        manager: &LocalMarketDataManager,
+        manager: &dyn MarketDataManager,
         market_type: &MarketType,
         symbol: &str,
     ) -> Result<(f64, u64)>;
 }
 
-/// 价格提供者 trait，允许用户自定义价格获取逻辑
+/// 基准价格的取价方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriceMethod {
+    KlineClose, // kline收盘价
+    LastTrade,  // 最新成交价
+    Mid,        // 盘口中间价
+    Vwap,       // 成交量加权均价
+}
+
+impl PriceMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriceMethod::KlineClose => "kline_close",
+            PriceMethod::LastTrade => "last_trade",
+            PriceMethod::Mid => "mid",
+            PriceMethod::Vwap => "vwap",
+        }
+    }
+}
+
+/// 价格提供者 trait，允许用户自定义价格获取逻辑，只依赖 MarketDataManager 接口
 /// 返回 (价格, 行情时间戳)
 #[async_trait]
-pub trait PriceProvider {
+pub trait PriceProvider: Send + Sync {
+    // 取价方式，回测结果中据此说明 forward_return 的计算基准
+    fn method(&self) -> PriceMethod;
+
     async fn get_price(
         &self,
-        manager: &LocalMarketDataManager,
+        manager: &dyn MarketDataManager,
         market_type: &MarketType,
         symbol: &str,
     ) -> Result<(f64, u64)>;
@@ -1,2 +1,5 @@
 pub mod factors;
 pub mod strategies;
+
+#[cfg(test)]
+pub(crate) mod test_utils;
@@ -0,0 +1,5 @@
+pub mod strategy_engine;
+pub mod traits;
+
+#[cfg(test)]
+mod strategy_engine_tests;
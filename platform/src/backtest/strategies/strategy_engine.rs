@@ -0,0 +1,184 @@
+use crate::{
+    backtest::strategies::traits::Strategy,
+    data_manager::local_data_manager::{Clock, LocalMarketDataManager},
+    errors::{PlatformError, Result},
+    models::MarketType,
+};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::{
+    select,
+    sync::{broadcast, mpsc},
+    time::{sleep_until, Instant},
+};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone)]
+pub struct StrategyConfig {
+    pub start_ts: u64,
+    pub end_ts: u64,
+    pub step_ms: u64,
+    pub warmup_steps: usize,             // 预热步数，期间不输出信号
+    pub min_reevaluate_interval_ms: u64, // 事件驱动模式下同一symbol两次评估的最小间隔
+}
+
+/// 因子事件，事件驱动模式的输入
+#[derive(Debug, Clone, PartialEq)]
+pub struct FactorEvent {
+    pub timestamp: u64,
+    pub market_type: MarketType,
+    pub symbol: String,
+    pub name: String,
+    pub value: f64,
+}
+
+/// 目标仓位信号
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionTargetSignal {
+    pub timestamp: u64,
+    pub market_type: MarketType,
+    pub symbol: String,
+    pub target_position: f64,
+}
+
+pub struct StrategyEngine {
+    market_mgr: Arc<LocalMarketDataManager>,
+    clock: Arc<Clock>,
+    config: StrategyConfig,
+}
+
+impl StrategyEngine {
+    pub fn new(
+        market_mgr: Arc<LocalMarketDataManager>,
+        clock: Arc<Clock>,
+        config: StrategyConfig,
+    ) -> Result<Self> {
+        if config.step_ms == 0 || config.start_ts > config.end_ts {
+            return Err(PlatformError::StrategyError {
+                message: format!("invalid strategy config: {:?}", config),
+            });
+        }
+        Ok(Self {
+            market_mgr,
+            clock,
+            config,
+        })
+    }
+
+    /// 按 step_ms 推进时钟运行策略，返回预热结束后产生的信号
+    pub async fn run(
+        &self,
+        strategy: &mut dyn Strategy,
+        market_type: MarketType,
+        symbol: &str,
+    ) -> Result<Vec<PositionTargetSignal>> {
+        let mut signals = Vec::new();
+        let mut cur_ts = self.config.start_ts;
+        let mut step = 0;
+
+        while cur_ts <= self.config.end_ts {
+            self.clock.advance_to(cur_ts).await?;
+
+            if step == self.config.warmup_steps {
+                strategy.on_start(cur_ts).await?;
+            }
+
+            // 预热期内仍调用 on_step 让策略积累状态，但丢弃信号
+            let target = strategy
+                .on_step(&self.market_mgr, &market_type, symbol, cur_ts)
+                .await?;
+            if let Some(target_position) = target.filter(|_| step >= self.config.warmup_steps) {
+                signals.push(PositionTargetSignal {
+                    timestamp: cur_ts,
+                    market_type: market_type.clone(),
+                    symbol: symbol.to_string(),
+                    target_position,
+                });
+            }
+
+            step += 1;
+            cur_ts += self.config.step_ms;
+        }
+
+        // 只有 on_start 被调用过才对应调用 on_stop
+        if step > self.config.warmup_steps {
+            strategy.on_stop(self.clock.cur_ts()).await?;
+        }
+        Ok(signals)
+    }
+
+    /// 事件驱动运行：消费因子事件，按 symbol 去抖后评估策略，信号发送给 ExecutionEngine::run_signals 执行
+    /// 间隔内到达的事件只保留最新一条，到期后再评估
+    pub async fn run_event_driven(
+        &self,
+        strategy: &mut dyn Strategy,
+        mut events: broadcast::Receiver<FactorEvent>,
+        signal_sender: mpsc::UnboundedSender<PositionTargetSignal>,
+        shutdown_token: CancellationToken,
+    ) -> Result<()> {
+        let min_interval = Duration::from_millis(self.config.min_reevaluate_interval_ms);
+        let mut last_evaluated: HashMap<(MarketType, String), Instant> = HashMap::new();
+        let mut pending: HashMap<(MarketType, String), FactorEvent> = HashMap::new();
+        let mut step = 0;
+        let mut last_ts = 0;
+
+        loop {
+            let next_deadline = pending
+                .keys()
+                .filter_map(|key| last_evaluated.get(key).map(|t| *t + min_interval))
+                .min();
+            select! {
+                _ = shutdown_token.cancelled() => break,
+                _ = sleep_until(next_deadline.unwrap_or_else(Instant::now)), if next_deadline.is_some() => {}
+                event = events.recv() => match event {
+                    Ok(event) => {
+                        pending.insert((event.market_type.clone(), event.symbol.clone()), event);
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        log::warn!("factor event receiver lagged, skipped {} events", n);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            }
+
+            let now = Instant::now();
+            let due_keys = pending
+                .keys()
+                .filter(|key| {
+                    last_evaluated
+                        .get(*key)
+                        .is_none_or(|t| now >= *t + min_interval)
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+            for key in due_keys {
+                let event = pending.remove(&key).unwrap();
+                last_evaluated.insert(key, now);
+                last_ts = event.timestamp;
+
+                if step == self.config.warmup_steps {
+                    strategy.on_start(event.timestamp).await?;
+                }
+                let target = strategy.on_factor_event(&self.market_mgr, &event).await?;
+                if let Some(target_position) = target.filter(|_| step >= self.config.warmup_steps) {
+                    signal_sender
+                        .send(PositionTargetSignal {
+                            timestamp: event.timestamp,
+                            market_type: event.market_type,
+                            symbol: event.symbol,
+                            target_position,
+                        })
+                        .map_err(|e| PlatformError::StrategyError {
+                            message: format!("send position target signal err: {}", e),
+                        })?;
+                }
+                step += 1;
+            }
+        }
+
+        if step > self.config.warmup_steps {
+            strategy.on_stop(last_ts).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use crate::{
+    backtest::strategies::{
+        strategy_engine::{FactorEvent, PositionTargetSignal, StrategyConfig, StrategyEngine},
+        traits::Strategy,
+    },
+    backtest::test_utils::test_market_mgr,
+    data_manager::local_data_manager::{Clock, LocalMarketDataManager},
+    errors::Result,
+    models::MarketType,
+};
+use async_trait::async_trait;
+use std::{sync::Arc, time::Duration};
+use tempfile::NamedTempFile;
+use tokio::{
+    sync::{broadcast, mpsc},
+    time::sleep,
+};
+use tokio_util::sync::CancellationToken;
+
+/// 每个 step 都输出信号，记录生命周期调用
+#[derive(Default)]
+struct MockStrategy {
+    steps: Vec<u64>,
+    started_at: Option<u64>,
+    stopped_at: Option<u64>,
+}
+
+#[async_trait]
+impl Strategy for MockStrategy {
+    async fn on_start(&mut self, ts: u64) -> Result<()> {
+        assert!(self.started_at.is_none());
+        self.started_at = Some(ts);
+        Ok(())
+    }
+
+    async fn on_stop(&mut self, ts: u64) -> Result<()> {
+        self.stopped_at = Some(ts);
+        Ok(())
+    }
+
+    async fn on_step(
+        &mut self,
+        _manager: &LocalMarketDataManager,
+        _market_type: &MarketType,
+        _symbol: &str,
+        ts: u64,
+    ) -> Result<Option<f64>> {
+        self.steps.push(ts);
+        Ok(Some(self.steps.len() as f64))
+    }
+
+    async fn on_factor_event(
+        &mut self,
+        _manager: &LocalMarketDataManager,
+        event: &FactorEvent,
+    ) -> Result<Option<f64>> {
+        self.steps.push(event.timestamp);
+        Ok(Some(event.value))
+    }
+}
+
+#[tokio::test]
+async fn test_warmup_suppresses_signals() {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = test_market_mgr(&db_file, clock.clone());
+    let engine = StrategyEngine::new(
+        market_mgr,
+        clock,
+        StrategyConfig {
+            start_ts: 1000,
+            end_ts: 10000,
+            step_ms: 1000,
+            warmup_steps: 3,
+            min_reevaluate_interval_ms: 0,
+        },
+    )
+    .unwrap();
+
+    let mut strategy = MockStrategy::default();
+    let signals = engine
+        .run(&mut strategy, MarketType::BinanceSpot, "BTCUSDT")
+        .await
+        .unwrap();
+
+    // 预热期内 on_step 仍被调用
+    assert_eq!(strategy.steps.len(), 10);
+    assert_eq!(strategy.started_at, Some(4000));
+    assert_eq!(strategy.stopped_at, Some(10000));
+    assert_eq!(signals.len(), 7);
+    assert_eq!(
+        signals[0],
+        PositionTargetSignal {
+            timestamp: 4000,
+            market_type: MarketType::BinanceSpot,
+            symbol: "BTCUSDT".to_string(),
+            target_position: 4.0,
+        }
+    );
+    assert!(signals.iter().all(|s| s.timestamp >= 4000));
+}
+
+#[tokio::test]
+async fn test_warmup_longer_than_run() {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = test_market_mgr(&db_file, clock.clone());
+    let engine = StrategyEngine::new(
+        market_mgr,
+        clock,
+        StrategyConfig {
+            start_ts: 1000,
+            end_ts: 3000,
+            step_ms: 1000,
+            warmup_steps: 5,
+            min_reevaluate_interval_ms: 0,
+        },
+    )
+    .unwrap();
+
+    let mut strategy = MockStrategy::default();
+    let signals = engine
+        .run(&mut strategy, MarketType::BinanceSpot, "BTCUSDT")
+        .await
+        .unwrap();
+    assert!(signals.is_empty());
+    assert_eq!(strategy.steps.len(), 3);
+    assert!(strategy.started_at.is_none());
+    assert!(strategy.stopped_at.is_none());
+}
+
+fn factor_event(symbol: &str, timestamp: u64, value: f64) -> FactorEvent {
+    FactorEvent {
+        timestamp,
+        market_type: MarketType::BinanceSpot,
+        symbol: symbol.to_string(),
+        name: "price_return".to_string(),
+        value,
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_event_driven_debounce() {
+    let db_file = NamedTempFile::new().unwrap();
+    let clock = Arc::new(Clock::new(0));
+    let market_mgr = test_market_mgr(&db_file, clock.clone());
+    let engine = StrategyEngine::new(
+        market_mgr,
+        clock,
+        StrategyConfig {
+            start_ts: 0,
+            end_ts: 0,
+            step_ms: 1,
+            warmup_steps: 1,
+            min_reevaluate_interval_ms: 100,
+        },
+    )
+    .unwrap();
+
+    let (event_sender, event_receiver) = broadcast::channel(100);
+    let (signal_sender, mut signal_receiver) = mpsc::unbounded_channel();
+    let shutdown_token = CancellationToken::new();
+    let mut strategy = MockStrategy::default();
+
+    let producer = async {
+        // 预热事件，不产生信号
+        event_sender.send(factor_event("ETHUSDT", 1, 0.5)).unwrap();
+        sleep(Duration::from_millis(10)).await;
+        // 一批 BTCUSDT 事件：首条立即评估，其余去抖后只评估最新一条
+        for i in 0..10 {
+            event_sender
+                .send(factor_event("BTCUSDT", 100 + i, i as f64))
+                .unwrap();
+        }
+        sleep(Duration::from_millis(50)).await;
+        event_sender
+            .send(factor_event("BTCUSDT", 200, 20.0))
+            .unwrap();
+        sleep(Duration::from_millis(500)).await;
+        // 距上次评估已超过间隔，立即评估
+        event_sender
+            .send(factor_event("BTCUSDT", 300, 30.0))
+            .unwrap();
+        sleep(Duration::from_millis(10)).await;
+        shutdown_token.cancel();
+    };
+    let (result, _) = tokio::join!(
+        engine.run_event_driven(
+            &mut strategy,
+            event_receiver,
+            signal_sender,
+            shutdown_token.clone()
+        ),
+        producer
+    );
+    result.unwrap();
+
+    let mut signals = vec![];
+    while let Ok(signal) = signal_receiver.try_recv() {
+        signals.push((signal.symbol, signal.timestamp, signal.target_position));
+    }
+    assert_eq!(
+        signals,
+        vec![
+            ("BTCUSDT".to_string(), 100, 0.0),
+            ("BTCUSDT".to_string(), 200, 20.0),
+            ("BTCUSDT".to_string(), 300, 30.0),
+        ]
+    );
+    assert_eq!(strategy.steps, vec![1, 100, 200, 300]);
+    assert_eq!(strategy.started_at, Some(100));
+    assert_eq!(strategy.stopped_at, Some(300));
+}
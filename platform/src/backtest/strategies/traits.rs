@@ -0,0 +1,38 @@
+use crate::{
+    backtest::strategies::strategy_engine::FactorEvent,
+    data_manager::local_data_manager::LocalMarketDataManager, errors::Result, models::MarketType,
+};
+use async_trait::async_trait;
+
+/// 策略 trait，每个 step 基于行情/因子给出目标仓位
+/// 生命周期：预热期内只调用 on_step/on_factor_event（信号被丢弃）-> on_start -> ... -> on_stop
+#[async_trait]
+pub trait Strategy: Send {
+    /// 预热结束后、处理第一个可生效的 step 之前调用
+    async fn on_start(&mut self, _ts: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// 运行结束时调用
+    async fn on_stop(&mut self, _ts: u64) -> Result<()> {
+        Ok(())
+    }
+
+    /// 事件驱动模式下处理因子事件，返回目标仓位，None 表示不调整
+    async fn on_factor_event(
+        &mut self,
+        _manager: &LocalMarketDataManager,
+        _event: &FactorEvent,
+    ) -> Result<Option<f64>> {
+        Ok(None)
+    }
+
+    /// 返回目标仓位，None 表示不调整
+    async fn on_step(
+        &mut self,
+        manager: &LocalMarketDataManager,
+        market_type: &MarketType,
+        symbol: &str,
+        ts: u64,
+    ) -> Result<Option<f64>>;
+}
@@ -0,0 +1,135 @@
+use crate::{
+    config::{Config, PlatformConfig},
+    data_manager::{
+        db::*,
+        local_data_manager::{Clock, LocalMarketDataManager},
+    },
+    models::{KlineData, MarketType, SymbolInfo, SymbolStatus},
+};
+use db::sqlite::SQLiteDB;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+use tempfile::NamedTempFile;
+
+/// 空db上的 LocalMarketDataManager，供不依赖行情数据的测试使用
+pub fn test_market_mgr(db_file: &NamedTempFile, clock: Arc<Clock>) -> Arc<LocalMarketDataManager> {
+    let db_path = db_file.path().to_str().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_path).unwrap());
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    market_mgr_with_config(db, db_path, "[]", "[]", clock)
+}
+
+/// 预置 symbol_info 与 1m kline 的 LocalMarketDataManager，订阅 klines 中出现的 symbol
+pub fn kline_market_mgr(
+    db_file: &NamedTempFile,
+    clock: Arc<Clock>,
+    klines: &[KlineData],
+) -> Arc<LocalMarketDataManager> {
+    let db_path = db_file.path().to_str().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_path).unwrap());
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    let market_type = MarketType::BinanceSpot;
+    let mut symbols = klines.iter().map(|k| k.symbol.clone()).collect::<Vec<_>>();
+    symbols.sort();
+    symbols.dedup();
+    let symbol_infos = symbols
+        .iter()
+        .map(|symbol| symbol_info(symbol, symbol.trim_end_matches("USDT"), "USDT"))
+        .collect::<Vec<_>>();
+    update_symbol_info(db.clone(), &market_type, &symbol_infos).unwrap();
+    for chunk in klines.chunks(500) {
+        update_kline_data(db.clone(), &market_type, chunk).unwrap();
+    }
+    let symbols = format!(
+        "[{}]",
+        symbols
+            .iter()
+            .map(|s| format!("\"{}\"", s))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    market_mgr_with_config(db, db_path, &symbols, "[\"1m\"]", clock)
+}
+
+fn market_mgr_with_config(
+    db: Arc<SQLiteDB>,
+    db_path: &str,
+    symbols: &str,
+    intervals: &str,
+    clock: Arc<Clock>,
+) -> Arc<LocalMarketDataManager> {
+    let config = test_config(db_path, symbols, intervals);
+    Arc::new(LocalMarketDataManager::new(config, clock, db, 1000).unwrap())
+}
+
+/// 仅启用 binance_spot 的测试配置，symbols 与 intervals 为 JSON 数组
+pub fn test_config(db_path: &str, symbols: &str, intervals: &str) -> Arc<PlatformConfig> {
+    let config_content = r#"
+    {
+        "markets": ["binance_spot"],
+        "db_path": "{placeholder}",
+        "binance_spot": {
+            "cache_capacity": 1000,
+            "api_base_url": "",
+            "stream_base_url": "",
+            "stream_api_base_url": "",
+            "api_key": "",
+            "secret_key": "",
+            "subscribed_symbols": {symbols},
+            "subscribed_kline_intervals": {intervals}
+        }
+    }
+    "#
+    .replace("{placeholder}", db_path)
+    .replace("{symbols}", symbols)
+    .replace("{intervals}", intervals);
+    let mut config_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config_file, config_content.as_bytes()).unwrap();
+    let config = Config::from_json(config_file.path().to_str().unwrap()).unwrap();
+    Arc::new(PlatformConfig::from_config(config).unwrap())
+}
+
+/// 交易中的现货 SymbolInfo，过滤条件取 BTCUSDT 的常见值
+pub fn symbol_info(symbol: &str, base_asset: &str, quote_asset: &str) -> SymbolInfo {
+    SymbolInfo {
+        symbol: symbol.to_string(),
+        status: SymbolStatus::Trading,
+        base_asset: base_asset.to_string(),
+        quote_asset: quote_asset.to_string(),
+        base_asset_precision: Some(8),
+        quote_asset_precision: Some(8),
+        min_price: Some(Decimal::new(1, 2)),
+        max_price: Some(Decimal::from(1_000_000)),
+        price_tick_size: Some(Decimal::new(1, 2)),
+        min_market_quantity: Some(Decimal::ZERO),
+        max_market_quantity: Some(Decimal::from(100)),
+        market_quantity_step_size: Some(Decimal::new(1, 5)),
+        min_quantity: Some(Decimal::new(1, 5)),
+        max_quantity: Some(Decimal::from(9000)),
+        quantity_step_size: Some(Decimal::new(1, 5)),
+        min_notional: Some(Decimal::from(5)),
+    }
+}
+
+/// 不带任何过滤条件的 SymbolInfo
+pub fn unfiltered_symbol_info(symbol: &str, base_asset: &str, quote_asset: &str) -> SymbolInfo {
+    SymbolInfo {
+        base_asset_precision: None,
+        quote_asset_precision: None,
+        min_price: None,
+        max_price: None,
+        price_tick_size: None,
+        min_market_quantity: None,
+        max_market_quantity: None,
+        market_quantity_step_size: None,
+        min_quantity: None,
+        max_quantity: None,
+        quantity_step_size: None,
+        min_notional: None,
+        ..symbol_info(symbol, base_asset, quote_asset)
+    }
+}
@@ -1,6 +1,8 @@
-use log::info;
+use log::{error, info};
 use serde::Deserialize;
+use std::time::Duration;
 use thiserror::Error;
+use tokio_util::sync::CancellationToken;
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -8,6 +10,82 @@ pub enum ConfigError {
     FileError { message: String },
     #[error("Configuration parse error: {message}")]
     ParseError { message: String },
+    #[error("Configuration key missing: {key}")]
+    MissingKey { key: String },
+    #[error("Configuration type mismatch for {key}: {message}")]
+    TypeMismatch { key: String, message: String },
+    #[error("Configuration validation error: {message}")]
+    ValidationError { message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigValueType {
+    Bool,
+    Integer,
+    Float, // 兼容整数
+    String,
+    Array,
+    Table,
+}
+
+impl ConfigValueType {
+    fn matches(&self, kind: &config::ValueKind) -> bool {
+        use config::ValueKind;
+        match self {
+            ConfigValueType::Bool => matches!(kind, ValueKind::Boolean(_)),
+            ConfigValueType::Integer => matches!(
+                kind,
+                ValueKind::I64(_) | ValueKind::I128(_) | ValueKind::U64(_) | ValueKind::U128(_)
+            ),
+            ConfigValueType::Float => matches!(
+                kind,
+                ValueKind::Float(_)
+                    | ValueKind::I64(_)
+                    | ValueKind::I128(_)
+                    | ValueKind::U64(_)
+                    | ValueKind::U128(_)
+            ),
+            ConfigValueType::String => matches!(kind, ValueKind::String(_)),
+            ConfigValueType::Array => matches!(kind, ValueKind::Array(_)),
+            ConfigValueType::Table => matches!(kind, ValueKind::Table(_)),
+        }
+    }
+}
+
+struct ConfigField {
+    key: String,
+    value_type: ConfigValueType,
+    required: bool,
+}
+
+// 配置校验规则：必填key及各key的类型
+#[derive(Default)]
+pub struct ConfigSchema {
+    fields: Vec<ConfigField>,
+}
+
+impl ConfigSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn required(mut self, key: &str, value_type: ConfigValueType) -> Self {
+        self.fields.push(ConfigField {
+            key: key.to_string(),
+            value_type,
+            required: true,
+        });
+        self
+    }
+
+    pub fn optional(mut self, key: &str, value_type: ConfigValueType) -> Self {
+        self.fields.push(ConfigField {
+            key: key.to_string(),
+            value_type,
+            required: false,
+        });
+        self
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -62,6 +140,103 @@ impl Config {
                 message: e.to_string(),
             })
     }
+
+    // key不存在时返回None，类型不匹配返回TypeMismatch
+    fn get_opt<'de, T: Deserialize<'de>>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        match self.settings.get::<T>(key) {
+            Ok(v) => Ok(Some(v)),
+            Err(config::ConfigError::NotFound(_)) => Ok(None),
+            Err(e) => Err(ConfigError::TypeMismatch {
+                key: key.to_string(),
+                message: e.to_string(),
+            }),
+        }
+    }
+
+    pub fn get_or<'de, T: Deserialize<'de>>(
+        &self,
+        key: &str,
+        default: T,
+    ) -> Result<T, ConfigError> {
+        Ok(self.get_opt(key)?.unwrap_or(default))
+    }
+
+    pub fn require<'de, T: Deserialize<'de>>(&self, key: &str) -> Result<T, ConfigError> {
+        self.get_opt(key)?.ok_or(ConfigError::MissingKey {
+            key: key.to_string(),
+        })
+    }
+
+    // 启动时统一校验，汇总所有缺失/类型错误
+    pub fn validate(&self, schema: &ConfigSchema) -> Result<(), ConfigError> {
+        let mut errors = vec![];
+        for field in &schema.fields {
+            match self.settings.get::<config::Value>(&field.key) {
+                Ok(value) => {
+                    if !field.value_type.matches(&value.kind) {
+                        errors.push(format!(
+                            "{} expect {:?}, got {}",
+                            field.key, field.value_type, value
+                        ));
+                    }
+                }
+                Err(config::ConfigError::NotFound(_)) => {
+                    if field.required {
+                        errors.push(format!("{} is required", field.key));
+                    }
+                }
+                Err(e) => errors.push(format!("{}: {}", field.key, e)),
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::ValidationError {
+                message: errors.join("; "),
+            })
+        }
+    }
+
+    // 轮询toml配置文件，内容变化且解析成功后回调新配置；解析失败保留旧配置不回调
+    pub fn watch<F>(filepath: &str, poll_interval: Duration, on_change: F) -> CancellationToken
+    where
+        F: Fn(Config) + Send + Sync + 'static,
+    {
+        let shutdown_token = CancellationToken::new();
+        let token = shutdown_token.clone();
+        let filepath = filepath.to_string();
+        let mut last_content = std::fs::read_to_string(&filepath).ok();
+        tokio::spawn(async move {
+            let mut interval_tick = tokio::time::interval(poll_interval);
+            loop {
+                tokio::select! {
+                    _ = token.cancelled() => {
+                        break;
+                    },
+                    _ = interval_tick.tick() => {
+                        let content = match tokio::fs::read_to_string(&filepath).await {
+                            Ok(content) => content,
+                            Err(e) => {
+                                error!("read config file {} failed: {}", filepath, e);
+                                continue;
+                            }
+                        };
+                        if last_content.as_deref() == Some(content.as_str()) {
+                            continue;
+                        }
+                        last_content = Some(content);
+                        match Config::from_toml(&filepath) {
+                            Ok(config) => on_change(config),
+                            Err(e) => {
+                                error!("reload config file {} failed, keep old config: {}", filepath, e);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+        shutdown_token
+    }
 }
 
 #[cfg(test)]
@@ -91,6 +266,58 @@ mod tests {
         assert_eq!(api_key, "my_api_key");
     }
 
+    #[test]
+    fn test_get_or_and_require() {
+        let mut tempfile = NamedTempFile::new().unwrap();
+        writeln!(tempfile, "[api]\nkey = \"my_api_key\"\ntimeout = 100").unwrap();
+        let config = Config::from_toml(tempfile.path().to_str().unwrap()).unwrap();
+
+        let timeout: u64 = config.get_or("api.timeout", 30).unwrap();
+        assert_eq!(timeout, 100);
+        let retry: u64 = config.get_or("api.retry", 3).unwrap();
+        assert_eq!(retry, 3);
+
+        let key: String = config.require("api.key").unwrap();
+        assert_eq!(key, "my_api_key");
+        assert!(matches!(
+            config.require::<String>("api.secret"),
+            Err(ConfigError::MissingKey { key }) if key == "api.secret"
+        ));
+        assert!(matches!(
+            config.get_or::<u64>("api.key", 0),
+            Err(ConfigError::TypeMismatch { key, .. }) if key == "api.key"
+        ));
+    }
+
+    #[test]
+    fn test_validate_schema() {
+        let mut tempfile = NamedTempFile::new().unwrap();
+        writeln!(
+            tempfile,
+            "markets = [\"binance_spot\"]\n[api]\nkey = \"my_api_key\"\ntimeout = 100"
+        )
+        .unwrap();
+        let config = Config::from_toml(tempfile.path().to_str().unwrap()).unwrap();
+
+        let schema = ConfigSchema::new()
+            .required("markets", ConfigValueType::Array)
+            .required("api.key", ConfigValueType::String)
+            .required("api.timeout", ConfigValueType::Integer)
+            .optional("api.ratio", ConfigValueType::Float);
+        assert!(config.validate(&schema).is_ok());
+
+        let schema = ConfigSchema::new()
+            .required("api.secret", ConfigValueType::String)
+            .required("api.timeout", ConfigValueType::String);
+        match config.validate(&schema) {
+            Err(ConfigError::ValidationError { message }) => {
+                assert!(message.contains("api.secret is required"));
+                assert!(message.contains("api.timeout expect String"));
+            }
+            _ => panic!("expect validation error"),
+        }
+    }
+
     #[test]
     fn test_load_toml_config() {
         let mut tempfile = NamedTempFile::new().unwrap();
@@ -1,4 +1,7 @@
-use crate::models::{enums::*, market::*, market_reqs::*, trade::*, trade_reqs::*};
+use crate::{
+    errors::PlatformError,
+    models::{enums::*, market::*, market_reqs::*, trade::*, trade_reqs::*},
+};
 use exchange::binance::spot::{models as ex_models, requests as ex_requests};
 
 // ============================================================================
@@ -81,6 +84,20 @@ impl From<ex_models::AggTrade> for Trade {
     }
 }
 
+impl From<ex_models::MarketTrade> for Trade {
+    fn from(value: ex_models::MarketTrade) -> Self {
+        Trade {
+            symbol: value.symbol,
+            trade_id: value.trade_id.to_string(),
+            price: value.price,
+            quantity: value.quantity,
+            timestamp: value.timestamp,
+            is_buyer_maker: if value.is_buyer_maker { 1 } else { 0 },
+            seq_id: value.trade_id,
+        }
+    }
+}
+
 impl From<ex_models::Symbol> for SymbolInfo {
     fn from(value: ex_models::Symbol) -> Self {
         // Extract filter values
@@ -179,6 +196,7 @@ impl From<ex_models::OrderType> for OrderType {
             ex_models::OrderType::TakeProfit => OrderType::TakeProfit,
             ex_models::OrderType::TakeProfitLimit => OrderType::TakeProfitLimit,
             ex_models::OrderType::LimitMaker => OrderType::LimitMaker,
+            ex_models::OrderType::Unknown(s) => OrderType::Unknown(s),
         }
     }
 }
@@ -195,6 +213,7 @@ impl From<ex_models::OrderStatus> for OrderStatus {
             ex_models::OrderStatus::Rejected => OrderStatus::Rejected,
             ex_models::OrderStatus::Expired => OrderStatus::Expired,
             ex_models::OrderStatus::ExpiredInMatch => OrderStatus::ExpiredInMatch,
+            ex_models::OrderStatus::Unknown(s) => OrderStatus::Unknown(s),
         }
     }
 }
@@ -205,6 +224,7 @@ impl From<ex_models::TimeInForce> for TimeInForce {
             ex_models::TimeInForce::Gtc => TimeInForce::Gtc,
             ex_models::TimeInForce::Ioc => TimeInForce::Ioc,
             ex_models::TimeInForce::Fok => TimeInForce::Fok,
+            ex_models::TimeInForce::Unknown(s) => TimeInForce::Unknown(s),
         }
     }
 }
@@ -248,6 +268,7 @@ impl From<ex_models::Order> for Order {
             time_in_force: value.time_in_force.into(),
             stop_price: value.stop_price,
             iceberg_qty: value.iceberg_qty,
+            quote_order_qty: value.quote_order_qty,
             create_time: value.create_time,
             update_time: value.update_time,
         }
@@ -322,6 +343,7 @@ impl From<OrderType> for ex_models::OrderType {
             OrderType::TakeProfit => ex_models::OrderType::TakeProfit,
             OrderType::TakeProfitLimit => ex_models::OrderType::TakeProfitLimit,
             OrderType::LimitMaker => ex_models::OrderType::LimitMaker,
+            OrderType::Unknown(s) => ex_models::OrderType::Unknown(s),
         }
     }
 }
@@ -332,6 +354,7 @@ impl From<TimeInForce> for ex_models::TimeInForce {
             TimeInForce::Gtc => ex_models::TimeInForce::Gtc,
             TimeInForce::Ioc => ex_models::TimeInForce::Ioc,
             TimeInForce::Fok => ex_models::TimeInForce::Fok,
+            TimeInForce::Unknown(s) => ex_models::TimeInForce::Unknown(s),
         }
     }
 }
@@ -385,6 +408,17 @@ impl From<GetTradesRequest> for ex_requests::GetAggTradesRequest {
     }
 }
 
+// historicalTrades不支持按时间查询，start_time/end_time由调用方处理
+impl From<GetTradesRequest> for ex_requests::GetHistoricalTradesRequest {
+    fn from(value: GetTradesRequest) -> Self {
+        ex_requests::GetHistoricalTradesRequest {
+            symbol: value.symbol,
+            from_id: value.from_id.and_then(|id| id.parse().ok()),
+            limit: value.limit,
+        }
+    }
+}
+
 impl From<GetDepthRequest> for ex_requests::GetDepthRequest {
     fn from(value: GetDepthRequest) -> Self {
         ex_requests::GetDepthRequest {
@@ -426,6 +460,7 @@ impl From<PlaceOrderRequest> for ex_requests::PlaceOrderRequest {
             new_client_order_id: Some(value.client_order_id),
             stop_price: value.stop_price,
             iceberg_qty: value.iceberg_qty,
+            quote_order_qty: value.quote_order_qty,
         }
     }
 }
@@ -441,6 +476,28 @@ impl From<CancelOrderRequest> for ex_requests::CancelOrderRequest {
     }
 }
 
+// 撤单的order_id无法解析时报错，避免静默退化为仅按client_order_id撤单
+impl TryFrom<CancelReplaceOrderRequest> for ex_requests::CancelReplaceOrderRequest {
+    type Error = PlatformError;
+
+    fn try_from(value: CancelReplaceOrderRequest) -> Result<Self, Self::Error> {
+        let cancel_order_id = value
+            .cancel_order_id
+            .map(|id| {
+                id.parse::<u64>()
+                    .map_err(|e| PlatformError::ValidationError {
+                        message: format!("invalid binance cancel_order_id {}: {}", id, e),
+                    })
+            })
+            .transpose()?;
+        Ok(ex_requests::CancelReplaceOrderRequest {
+            cancel_order_id,
+            cancel_orig_client_order_id: Some(value.cancel_client_order_id),
+            new_order: value.new_order.into(),
+        })
+    }
+}
+
 impl From<GetOrderRequest> for ex_requests::GetOrderRequest {
     fn from(value: GetOrderRequest) -> Self {
         ex_requests::GetOrderRequest {
@@ -484,6 +541,101 @@ impl From<GetUserTradesRequest> for ex_requests::GetTradesRequest {
     }
 }
 
+// ============================================================================
+// Model Conversions: platform -> exchange
+// ============================================================================
+// 平台模型不保存的字段（成交笔数、归集成交的首末成交id等）填0；
+// 0/1表示的布尔字段非0视为true，再转回平台模型时为1
+
+impl From<KlineData> for ex_models::KlineData {
+    fn from(value: KlineData) -> Self {
+        ex_models::KlineData {
+            symbol: value.symbol,
+            interval: value.interval.into(),
+            open_time: value.open_time,
+            close_time: value.close_time,
+            open: value.open,
+            high: value.high,
+            low: value.low,
+            close: value.close,
+            volume: value.volume,
+            quote_volume: value.quote_volume,
+            trade_count: 0,
+            taker_buy_volume: value.taker_buy_volume,
+            taker_buy_quote_volume: value.taker_buy_quote_volume,
+            first_trade_id: 0,
+            last_trade_id: 0,
+            is_closed: value.is_closed != 0,
+        }
+    }
+}
+
+// agg_trade_id取seq_id，转回平台模型时trade_id为seq_id的字符串形式
+impl From<Trade> for ex_models::AggTrade {
+    fn from(value: Trade) -> Self {
+        ex_models::AggTrade {
+            symbol: value.symbol,
+            agg_trade_id: value.seq_id,
+            price: value.price,
+            quantity: value.quantity,
+            first_trade_id: 0,
+            last_trade_id: 0,
+            timestamp: value.timestamp,
+            is_buyer_maker: value.is_buyer_maker != 0,
+        }
+    }
+}
+
+impl From<OrderStatus> for ex_models::OrderStatus {
+    fn from(value: OrderStatus) -> Self {
+        match value {
+            OrderStatus::New => ex_models::OrderStatus::New,
+            OrderStatus::PendingNew => ex_models::OrderStatus::PendingNew,
+            OrderStatus::PartiallyFilled => ex_models::OrderStatus::PartiallyFilled,
+            OrderStatus::Filled => ex_models::OrderStatus::Filled,
+            OrderStatus::Canceled => ex_models::OrderStatus::Canceled,
+            OrderStatus::PendingCancel => ex_models::OrderStatus::PendingCancel,
+            OrderStatus::Rejected => ex_models::OrderStatus::Rejected,
+            OrderStatus::Expired => ex_models::OrderStatus::Expired,
+            OrderStatus::ExpiredInMatch => ex_models::OrderStatus::ExpiredInMatch,
+            OrderStatus::Unknown(s) => ex_models::OrderStatus::Unknown(s),
+        }
+    }
+}
+
+// 交易所order_id为数字，本地模拟等来源的非数字id无法转换
+impl TryFrom<Order> for ex_models::Order {
+    type Error = PlatformError;
+
+    fn try_from(value: Order) -> Result<Self, Self::Error> {
+        let order_id =
+            value
+                .order_id
+                .parse::<u64>()
+                .map_err(|e| PlatformError::ValidationError {
+                    message: format!("invalid binance order_id {}: {}", value.order_id, e),
+                })?;
+        Ok(ex_models::Order {
+            order_id,
+            client_order_id: value.client_order_id,
+            symbol: value.symbol,
+            order_side: value.order_side.into(),
+            order_type: value.order_type.into(),
+            order_quantity: value.order_quantity,
+            order_price: value.order_price,
+            executed_qty: value.executed_qty,
+            cummulative_quote_qty: value.cummulative_quote_qty,
+            order_status: value.order_status.into(),
+            time_in_force: value.time_in_force.into(),
+            stop_price: value.stop_price,
+            iceberg_qty: value.iceberg_qty,
+            quote_order_qty: value.quote_order_qty,
+            create_time: value.create_time,
+            update_time: value.update_time,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -527,6 +679,28 @@ mod tests {
         assert!(matches!(ex_side, ex_models::Side::Sell));
     }
 
+    #[test]
+    fn test_unknown_order_status_conversion() {
+        let ex_status: ex_models::OrderStatus =
+            serde_json::from_str(r#""PENDING_REVIEW""#).unwrap();
+        let status: OrderStatus = ex_status.into();
+        assert_eq!(status, OrderStatus::Unknown("PENDING_REVIEW".to_string()));
+        assert_eq!(status.as_str(), "PENDING_REVIEW");
+        assert!(!status.is_terminal());
+
+        // 写入db后按原始值读回
+        let stored = serde_json::to_string(&status).unwrap();
+        assert_eq!(stored, r#""PENDING_REVIEW""#);
+        assert_eq!(
+            serde_json::from_str::<OrderStatus>(&stored).unwrap(),
+            status
+        );
+        assert_eq!(
+            serde_json::from_str::<OrderStatus>(r#""FILLED""#).unwrap(),
+            OrderStatus::Filled
+        );
+    }
+
     #[test]
     fn test_trade_conversion() {
         let ex_agg_trade = ex_models::AggTrade {
@@ -546,6 +720,37 @@ mod tests {
         assert_eq!(platform_trade.price, dec!(50000.0));
     }
 
+    #[test]
+    fn test_raw_trade_conversion() {
+        let ex_trade = ex_models::MarketTrade {
+            symbol: "BTCUSDT".to_string(),
+            trade_id: 67890,
+            price: dec!(50000.0),
+            quantity: dec!(0.01),
+            timestamp: 1000000,
+            is_buyer_maker: false,
+        };
+
+        let platform_trade: Trade = ex_trade.into();
+        assert_eq!(platform_trade.symbol, "BTCUSDT");
+        assert_eq!(platform_trade.trade_id, "67890");
+        assert_eq!(platform_trade.seq_id, 67890);
+        assert_eq!(platform_trade.quantity, dec!(0.01));
+        assert_eq!(platform_trade.timestamp, 1000000);
+        assert_eq!(platform_trade.is_buyer_maker, 0);
+
+        let req: ex_requests::GetHistoricalTradesRequest = GetTradesRequest {
+            symbol: "BTCUSDT".to_string(),
+            from_id: Some("67890".to_string()),
+            start_time: None,
+            end_time: None,
+            limit: Some(1000),
+        }
+        .into();
+        assert_eq!(req.from_id, Some(67890));
+        assert_eq!(req.limit, Some(1000));
+    }
+
     #[test]
     fn test_user_trade_conversion() {
         let ex_trade = ex_models::Trade {
@@ -582,4 +787,147 @@ mod tests {
         assert_eq!(ex_req.start_time, Some(1000));
         assert_eq!(ex_req.limit, Some(100));
     }
+
+    // platform -> exchange -> platform 的往返转换
+    mod round_trip {
+        use super::*;
+        use proptest::{prelude::*, sample::select};
+        use rust_decimal::Decimal;
+
+        // 不同精度（含负数与尾随0）的Decimal，比较序列化结果以同时校验scale
+        fn decimal() -> impl Strategy<Value = Decimal> {
+            (any::<i64>(), 0u32..=18).prop_map(|(mantissa, scale)| Decimal::new(mantissa, scale))
+        }
+
+        fn assert_same<T: serde::Serialize>(actual: &T, expected: &T) {
+            assert_eq!(
+                serde_json::to_value(actual).unwrap(),
+                serde_json::to_value(expected).unwrap()
+            );
+        }
+
+        prop_compose! {
+            fn kline()(
+                symbol in "[A-Z]{2,10}",
+                interval in select(vec![
+                    KlineInterval::OneSecond, KlineInterval::OneMinute, KlineInterval::ThreeMinutes,
+                    KlineInterval::FiveMinutes, KlineInterval::FifteenMinutes,
+                    KlineInterval::ThirtyMinutes, KlineInterval::OneHour, KlineInterval::TwoHours,
+                    KlineInterval::FourHours, KlineInterval::SixHours, KlineInterval::EightHours,
+                    KlineInterval::TwelveHours, KlineInterval::OneDay, KlineInterval::ThreeDays,
+                    KlineInterval::OneWeek, KlineInterval::OneMonth,
+                ]),
+                (open_time, close_time) in (any::<u64>(), any::<u64>()),
+                (open, high, low, close) in (decimal(), decimal(), decimal(), decimal()),
+                (volume, quote_volume) in (decimal(), decimal()),
+                (taker_buy_volume, taker_buy_quote_volume) in (decimal(), decimal()),
+                is_closed in 0u64..=1,
+            ) -> KlineData {
+                KlineData {
+                    symbol, interval, open_time, close_time, open, high, low, close, volume,
+                    quote_volume, taker_buy_volume, taker_buy_quote_volume, is_closed,
+                }
+            }
+        }
+
+        prop_compose! {
+            fn trade()(
+                symbol in "[A-Z]{2,10}",
+                seq_id in any::<u64>(),
+                (price, quantity) in (decimal(), decimal()),
+                timestamp in any::<u64>(),
+                is_buyer_maker in 0u64..=1,
+            ) -> Trade {
+                Trade {
+                    symbol, trade_id: seq_id.to_string(), price, quantity, timestamp,
+                    is_buyer_maker, seq_id,
+                }
+            }
+        }
+
+        prop_compose! {
+            fn order()(
+                symbol in "[A-Z]{2,10}",
+                order_id in any::<u64>(),
+                client_order_id in "[a-zA-Z0-9_-]{1,36}",
+                order_side in select(vec![OrderSide::Buy, OrderSide::Sell]),
+                order_type in select(vec![
+                    OrderType::Limit, OrderType::Market, OrderType::StopLoss,
+                    OrderType::StopLossLimit, OrderType::TakeProfit, OrderType::TakeProfitLimit,
+                    OrderType::LimitMaker, OrderType::Unknown("OTO".to_string()),
+                ]),
+                order_status in select(vec![
+                    OrderStatus::New, OrderStatus::PendingNew, OrderStatus::PartiallyFilled,
+                    OrderStatus::Filled, OrderStatus::Canceled, OrderStatus::PendingCancel,
+                    OrderStatus::Rejected, OrderStatus::Expired, OrderStatus::ExpiredInMatch,
+                    OrderStatus::Unknown("PENDING_REVIEW".to_string()),
+                ]),
+                time_in_force in select(vec![
+                    TimeInForce::Gtc, TimeInForce::Ioc, TimeInForce::Fok,
+                    TimeInForce::Unknown("GTD".to_string()),
+                ]),
+                (order_price, order_quantity, executed_qty) in (decimal(), decimal(), decimal()),
+                (cummulative_quote_qty, stop_price) in (decimal(), decimal()),
+                (iceberg_qty, quote_order_qty) in (decimal(), decimal()),
+                (create_time, update_time) in (any::<u64>(), any::<u64>()),
+            ) -> Order {
+                Order {
+                    symbol, order_id: order_id.to_string(), client_order_id, order_side,
+                    order_type, order_status, order_price, order_quantity, executed_qty,
+                    cummulative_quote_qty, time_in_force, stop_price, iceberg_qty,
+                    quote_order_qty, create_time, update_time,
+                }
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn kline_round_trip(kline in kline()) {
+                let ex_kline: ex_models::KlineData = kline.clone().into();
+                assert_same(&KlineData::from(ex_kline), &kline);
+            }
+
+            #[test]
+            fn trade_round_trip(trade in trade()) {
+                let ex_trade: ex_models::AggTrade = trade.clone().into();
+                assert_same(&Trade::from(ex_trade), &trade);
+            }
+
+            #[test]
+            fn order_round_trip(order in order()) {
+                let ex_order = ex_models::Order::try_from(order.clone()).unwrap();
+                prop_assert_eq!(Order::from(ex_order), order);
+            }
+
+            // 非0/1的布尔字段转回后为1，trade_id统一为seq_id
+            #[test]
+            fn round_trip_normalization(
+                kline in kline(),
+                trade in trade(),
+                flag in 2u64..,
+                trade_id in "[a-z0-9-]{1,20}",
+            ) {
+                let normalized = KlineData::from(ex_models::KlineData::from(KlineData {
+                    is_closed: flag,
+                    ..kline.clone()
+                }));
+                assert_same(&normalized, &KlineData { is_closed: 1, ..kline });
+
+                let normalized = Trade::from(ex_models::AggTrade::from(Trade {
+                    trade_id,
+                    is_buyer_maker: flag,
+                    ..trade.clone()
+                }));
+                assert_same(&normalized, &Trade { is_buyer_maker: 1, ..trade });
+            }
+
+            // 交易所order_id为数字，非数字id无法转换
+            #[test]
+            fn non_numeric_order_id_rejected(order in order(), order_id in "[a-z][a-z0-9-]{0,20}") {
+                let result = ex_models::Order::try_from(Order { order_id, ..order });
+                let rejected = matches!(result, Err(PlatformError::ValidationError { .. }));
+                prop_assert!(rejected);
+            }
+        }
+    }
 }
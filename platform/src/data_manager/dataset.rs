@@ -0,0 +1,499 @@
+use crate::{
+    data_manager::{db::*, migration::migrate},
+    errors::{PlatformError, Result},
+    models::{KlineData, KlineInterval, MarketType, SymbolInfo, Trade},
+};
+use db::sqlite::SQLiteDB;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    sync::Arc,
+};
+
+// 导出时每次从db读取的行数，导入时每批写入的行数
+const DATASET_CHUNK_SIZE: u64 = 500;
+
+// 数据集文件为jsonl格式，每行一条记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum DatasetRecord {
+    SymbolInfo(SymbolInfo),
+    Kline(KlineData),
+    Trade(Trade),
+}
+
+#[derive(Debug, Clone)]
+pub struct DatasetFilter {
+    pub market_type: MarketType,
+    pub symbol: String,
+    pub intervals: Vec<KlineInterval>,
+    pub start_time: u64, // 毫秒时间戳，kline按open_time，trade按timestamp
+    pub end_time: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DatasetStats {
+    pub symbol_infos: usize,
+    pub klines: usize,
+    pub trades: usize,
+}
+
+fn write_record<W: Write>(writer: &mut W, record: &DatasetRecord) -> Result<()> {
+    let line = serde_json::to_string(record).map_err(|e| PlatformError::StorageError {
+        message: format!("serialize dataset record err: {}", e),
+    })?;
+    writeln!(writer, "{}", line).map_err(|e| PlatformError::StorageError {
+        message: format!("write dataset record err: {}", e),
+    })
+}
+
+// 按open_time升序分块读取[start_time, end_time]内的kline，返回总行数
+fn scan_klines<F>(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    interval: &KlineInterval,
+    start_time: u64,
+    end_time: u64,
+    mut on_chunk: F,
+) -> Result<usize>
+where
+    F: FnMut(&[KlineData]) -> Result<()>,
+{
+    let mut total = 0;
+    // start_time为0时db按倒序返回，这里从1开始保证升序分页
+    let mut start_time = start_time.max(1);
+    loop {
+        let klines = get_klines(
+            db.clone(),
+            market_type,
+            symbol,
+            interval,
+            Some(start_time),
+            Some(end_time),
+            Some(DATASET_CHUNK_SIZE),
+        )?;
+        on_chunk(&klines)?;
+        total += klines.len();
+        if (klines.len() as u64) < DATASET_CHUNK_SIZE {
+            return Ok(total);
+        }
+        start_time = klines.last().unwrap().open_time + 1;
+    }
+}
+
+// 按seq_id升序分块读取[start_time, end_time]内的trade，返回总行数
+fn scan_trades<F>(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    start_time: u64,
+    end_time: u64,
+    mut on_chunk: F,
+) -> Result<usize>
+where
+    F: FnMut(&[Trade]) -> Result<()>,
+{
+    let mut total = 0;
+    // 首批按时间定位，之后按seq_id分页，避免相同timestamp的trade被跳过
+    let mut trades = get_trades(
+        db.clone(),
+        market_type,
+        symbol,
+        Some(start_time.max(1)),
+        Some(end_time),
+        None,
+        Some(DATASET_CHUNK_SIZE),
+    )?;
+    loop {
+        let batch_len = trades.len() as u64;
+        let last_seq_id = trades.last().map(|t| t.seq_id);
+        let in_range = trades
+            .iter()
+            .take_while(|t| t.timestamp <= end_time)
+            .count();
+        on_chunk(&trades[..in_range])?;
+        total += in_range;
+        if (in_range as u64) < batch_len || batch_len < DATASET_CHUNK_SIZE {
+            return Ok(total);
+        }
+        trades = get_trades(
+            db.clone(),
+            market_type,
+            symbol,
+            None,
+            None,
+            Some(last_seq_id.unwrap() + 1),
+            Some(DATASET_CHUNK_SIZE),
+        )?;
+    }
+}
+
+pub fn export_dataset(
+    db: Arc<SQLiteDB>,
+    filter: &DatasetFilter,
+    path: &str,
+) -> Result<DatasetStats> {
+    let file = File::create(path).map_err(|e| PlatformError::StorageError {
+        message: format!("create dataset file {} err: {}", path, e),
+    })?;
+    let mut writer = BufWriter::new(file);
+    let mut stats = DatasetStats::default();
+    let market_type = &filter.market_type;
+    let symbol = &filter.symbol;
+
+    if let Some(symbol_info) = get_symbol_info(db.clone(), market_type, symbol)? {
+        write_record(&mut writer, &DatasetRecord::SymbolInfo(symbol_info))?;
+        stats.symbol_infos += 1;
+    }
+
+    for interval in filter.intervals.iter() {
+        stats.klines += scan_klines(
+            db.clone(),
+            market_type,
+            symbol,
+            interval,
+            filter.start_time,
+            filter.end_time,
+            |klines| {
+                for kline in klines.iter() {
+                    write_record(&mut writer, &DatasetRecord::Kline(kline.clone()))?;
+                }
+                Ok(())
+            },
+        )?;
+    }
+
+    stats.trades += scan_trades(
+        db.clone(),
+        market_type,
+        symbol,
+        filter.start_time,
+        filter.end_time,
+        |trades| {
+            for trade in trades.iter() {
+                write_record(&mut writer, &DatasetRecord::Trade(trade.clone()))?;
+            }
+            Ok(())
+        },
+    )?;
+
+    writer.flush().map_err(|e| PlatformError::StorageError {
+        message: format!("flush dataset file {} err: {}", path, e),
+    })?;
+    log::info!("export dataset to {}: {:?}", path, stats);
+    Ok(stats)
+}
+
+fn flush_records(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol_infos: &mut Vec<SymbolInfo>,
+    klines: &mut Vec<KlineData>,
+    trades: &mut Vec<Trade>,
+) -> Result<()> {
+    if !symbol_infos.is_empty() {
+        update_symbol_info(db.clone(), market_type, symbol_infos)?;
+        symbol_infos.clear();
+    }
+    if !klines.is_empty() {
+        update_kline_data(db.clone(), market_type, klines)?;
+        klines.clear();
+    }
+    if !trades.is_empty() {
+        update_trade_data(db.clone(), market_type, trades)?;
+        trades.clear();
+    }
+    Ok(())
+}
+
+pub fn import_dataset(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    path: &str,
+) -> Result<DatasetStats> {
+    let file = File::open(path).map_err(|e| PlatformError::StorageError {
+        message: format!("open dataset file {} err: {}", path, e),
+    })?;
+    migrate(db.clone())?;
+
+    let mut stats = DatasetStats::default();
+    let mut symbol_infos = vec![];
+    let mut klines = vec![];
+    let mut trades = vec![];
+    for (line_no, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| PlatformError::StorageError {
+            message: format!("read dataset file {} err: {}", path, e),
+        })?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: DatasetRecord =
+            serde_json::from_str(&line).map_err(|e| PlatformError::StorageError {
+                message: format!("parse dataset line {} err: {}", line_no + 1, e),
+            })?;
+        match record {
+            DatasetRecord::SymbolInfo(symbol_info) => {
+                symbol_infos.push(symbol_info);
+                stats.symbol_infos += 1;
+            }
+            DatasetRecord::Kline(kline) => {
+                klines.push(kline);
+                stats.klines += 1;
+            }
+            DatasetRecord::Trade(trade) => {
+                trades.push(trade);
+                stats.trades += 1;
+            }
+        }
+        if symbol_infos.len() + klines.len() + trades.len() >= DATASET_CHUNK_SIZE as usize {
+            flush_records(
+                db.clone(),
+                market_type,
+                &mut symbol_infos,
+                &mut klines,
+                &mut trades,
+            )?;
+        }
+    }
+    flush_records(
+        db.clone(),
+        market_type,
+        &mut symbol_infos,
+        &mut klines,
+        &mut trades,
+    )?;
+
+    log::info!("import dataset from {}: {:?}", path, stats);
+    Ok(stats)
+}
+
+// csv字段含分隔符、引号或换行时加引号并转义
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub(crate) fn write_csv_row<W: Write>(writer: &mut W, fields: &[String]) -> Result<()> {
+    let line = fields
+        .iter()
+        .map(|f| csv_field(f))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(writer, "{}", line).map_err(|e| PlatformError::StorageError {
+        message: format!("write csv row err: {}", e),
+    })
+}
+
+// 解析write_csv_row写出的单行，引号内的分隔符与转义引号按字段内容处理
+pub(crate) fn parse_csv_row(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+pub const KLINE_CSV_HEADER: &[&str] = &[
+    "symbol",
+    "interval",
+    "open_time",
+    "close_time",
+    "open",
+    "high",
+    "low",
+    "close",
+    "volume",
+    "quote_volume",
+    "taker_buy_volume",
+    "taker_buy_quote_volume",
+    "is_closed",
+];
+
+pub const TRADE_CSV_HEADER: &[&str] = &[
+    "symbol",
+    "trade_id",
+    "price",
+    "quantity",
+    "timestamp",
+    "is_buyer_maker",
+    "seq_id",
+];
+
+// 导出kline为csv，无数据时只输出表头，返回数据行数
+pub fn export_klines_csv<W: Write>(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    interval: &KlineInterval,
+    start_time: u64,
+    end_time: u64,
+    writer: &mut W,
+) -> Result<usize> {
+    let header = KLINE_CSV_HEADER
+        .iter()
+        .map(|h| h.to_string())
+        .collect::<Vec<_>>();
+    write_csv_row(writer, &header)?;
+    scan_klines(
+        db,
+        market_type,
+        symbol,
+        interval,
+        start_time,
+        end_time,
+        |klines| {
+            for kline in klines.iter() {
+                write_csv_row(
+                    writer,
+                    &[
+                        kline.symbol.clone(),
+                        kline.interval.as_str().to_string(),
+                        kline.open_time.to_string(),
+                        kline.close_time.to_string(),
+                        kline.open.normalize().to_string(),
+                        kline.high.normalize().to_string(),
+                        kline.low.normalize().to_string(),
+                        kline.close.normalize().to_string(),
+                        kline.volume.normalize().to_string(),
+                        kline.quote_volume.normalize().to_string(),
+                        kline.taker_buy_volume.normalize().to_string(),
+                        kline.taker_buy_quote_volume.normalize().to_string(),
+                        kline.is_closed.to_string(),
+                    ],
+                )?;
+            }
+            Ok(())
+        },
+    )
+}
+
+// 导出trade为csv，无数据时只输出表头，返回数据行数
+pub fn export_trades_csv<W: Write>(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    start_time: u64,
+    end_time: u64,
+    writer: &mut W,
+) -> Result<usize> {
+    let header = TRADE_CSV_HEADER
+        .iter()
+        .map(|h| h.to_string())
+        .collect::<Vec<_>>();
+    write_csv_row(writer, &header)?;
+    scan_trades(db, market_type, symbol, start_time, end_time, |trades| {
+        for trade in trades.iter() {
+            write_csv_row(
+                writer,
+                &[
+                    trade.symbol.clone(),
+                    trade.trade_id.clone(),
+                    trade.price.normalize().to_string(),
+                    trade.quantity.normalize().to_string(),
+                    trade.timestamp.to_string(),
+                    trade.is_buyer_maker.to_string(),
+                    trade.seq_id.to_string(),
+                ],
+            )?;
+        }
+        Ok(())
+    })
+}
+
+// 导出trade为parquet，decimal按字符串存储，每个chunk写一个batch，按row_group_size落盘
+#[cfg(feature = "parquet")]
+pub fn export_trades_parquet<W: Write + Send>(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    start_time: u64,
+    end_time: u64,
+    row_group_size: usize,
+    writer: W,
+) -> Result<usize> {
+    use arrow_array::{ArrayRef, BooleanArray, Int64Array, RecordBatch, StringArray, UInt64Array};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::{arrow::ArrowWriter, file::properties::WriterProperties};
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("symbol", DataType::Utf8, false),
+        Field::new("trade_id", DataType::Utf8, false),
+        Field::new("price", DataType::Utf8, false),
+        Field::new("quantity", DataType::Utf8, false),
+        Field::new("timestamp", DataType::Int64, false),
+        Field::new("is_buyer_maker", DataType::Boolean, false),
+        Field::new("seq_id", DataType::UInt64, false),
+    ]));
+    let props = WriterProperties::builder()
+        .set_max_row_group_size(row_group_size)
+        .build();
+    let mut parquet_writer =
+        ArrowWriter::try_new(writer, schema.clone(), Some(props)).map_err(|e| {
+            PlatformError::StorageError {
+                message: format!("create parquet writer err: {}", e),
+            }
+        })?;
+
+    let rows = scan_trades(db, market_type, symbol, start_time, end_time, |trades| {
+        if trades.is_empty() {
+            return Ok(());
+        }
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                trades.iter().map(|t| t.symbol.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                trades.iter().map(|t| t.trade_id.as_str()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                trades.iter().map(|t| t.price.normalize().to_string()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                trades.iter().map(|t| t.quantity.normalize().to_string()),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                trades.iter().map(|t| t.timestamp as i64),
+            )),
+            Arc::new(BooleanArray::from_iter(
+                trades.iter().map(|t| Some(t.is_buyer_maker != 0)),
+            )),
+            Arc::new(UInt64Array::from_iter_values(
+                trades.iter().map(|t| t.seq_id),
+            )),
+        ];
+        let batch = RecordBatch::try_new(schema.clone(), columns).map_err(|e| {
+            PlatformError::StorageError {
+                message: format!("build parquet batch err: {}", e),
+            }
+        })?;
+        parquet_writer
+            .write(&batch)
+            .map_err(|e| PlatformError::StorageError {
+                message: format!("write parquet batch err: {}", e),
+            })
+    })?;
+
+    parquet_writer
+        .close()
+        .map_err(|e| PlatformError::StorageError {
+            message: format!("close parquet writer err: {}", e),
+        })?;
+    Ok(rows)
+}
@@ -0,0 +1,313 @@
+use crate::{
+    backtest::test_utils::symbol_info,
+    data_manager::{
+        dataset::{
+            export_dataset, export_klines_csv, export_trades_csv, import_dataset, DatasetFilter,
+            DatasetStats,
+        },
+        db::*,
+    },
+    models::{KlineData, KlineInterval, MarketType, Trade},
+};
+use db::sqlite::SQLiteDB;
+use rust_decimal::Decimal;
+use std::{str::FromStr, sync::Arc};
+use tempfile::NamedTempFile;
+
+fn new_db(file: &NamedTempFile) -> Arc<SQLiteDB> {
+    let db = Arc::new(SQLiteDB::new(file.path().to_str().unwrap()).unwrap());
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    db
+}
+
+fn kline(symbol: &str, open_time: u64) -> KlineData {
+    KlineData {
+        symbol: symbol.to_string(),
+        interval: KlineInterval::OneMinute,
+        open_time,
+        close_time: open_time + 59_999,
+        open: Decimal::from(100),
+        high: Decimal::from_str("101.5").unwrap(),
+        low: Decimal::from(99),
+        close: Decimal::from_str("100.25").unwrap(),
+        volume: Decimal::from_str("1.5").unwrap(),
+        quote_volume: Decimal::from(150),
+        taker_buy_volume: Decimal::ONE,
+        taker_buy_quote_volume: Decimal::from(100),
+        is_closed: 1,
+    }
+}
+
+fn trade(symbol: &str, seq_id: u64, timestamp: u64) -> Trade {
+    Trade {
+        symbol: symbol.to_string(),
+        trade_id: seq_id.to_string(),
+        price: Decimal::from_str("100.1").unwrap(),
+        quantity: Decimal::from_str("0.25").unwrap(),
+        timestamp,
+        is_buyer_maker: (seq_id % 2) as u64,
+        seq_id,
+    }
+}
+
+#[test]
+fn test_export_import_dataset_round_trip() {
+    let market_type = MarketType::BinanceSpot;
+    let src_file = NamedTempFile::new().unwrap();
+    let src_db = new_db(&src_file);
+    update_symbol_info(
+        src_db.clone(),
+        &market_type,
+        &[
+            symbol_info("BTCUSDT", "BTC", "USDT"),
+            symbol_info("ETHUSDT", "ETH", "USDT"),
+        ],
+    )
+    .unwrap();
+    // 超过一个分块，覆盖分页逻辑
+    let klines = (0..1200)
+        .map(|i| kline("BTCUSDT", i * 60_000))
+        .collect::<Vec<_>>();
+    for chunk in klines.chunks(500) {
+        update_kline_data(src_db.clone(), &market_type, chunk).unwrap();
+    }
+    update_kline_data(src_db.clone(), &market_type, &[kline("ETHUSDT", 60_000)]).unwrap();
+    // 每个timestamp两笔trade
+    let trades = (1..=1200)
+        .map(|i| trade("BTCUSDT", i, (i + 1) / 2 * 60_000))
+        .collect::<Vec<_>>();
+    for chunk in trades.chunks(500) {
+        update_trade_data(src_db.clone(), &market_type, chunk).unwrap();
+    }
+
+    // 导出 [100m, 1100m] 的数据，trade从seq_id 199开始
+    let filter = DatasetFilter {
+        market_type: market_type.clone(),
+        symbol: "BTCUSDT".to_string(),
+        intervals: vec![KlineInterval::OneMinute],
+        start_time: 100 * 60_000,
+        end_time: 1100 * 60_000,
+    };
+    let dataset_file = NamedTempFile::new().unwrap();
+    let dataset_path = dataset_file.path().to_str().unwrap();
+    let export_stats = export_dataset(src_db.clone(), &filter, dataset_path).unwrap();
+    assert_eq!(
+        export_stats,
+        DatasetStats {
+            symbol_infos: 1,
+            klines: 1001,
+            trades: 1200 - 198,
+        }
+    );
+
+    let dst_file = NamedTempFile::new().unwrap();
+    let dst_db = Arc::new(SQLiteDB::new(dst_file.path().to_str().unwrap()).unwrap());
+    let import_stats = import_dataset(dst_db.clone(), &market_type, dataset_path).unwrap();
+    assert_eq!(import_stats, export_stats);
+
+    let count = |table: &str| {
+        let result = dst_db
+            .execute_query(&format!("SELECT COUNT(*) AS cnt FROM {};", table), &[])
+            .unwrap();
+        #[derive(serde::Deserialize)]
+        struct Count {
+            cnt: u64,
+        }
+        result.into_struct::<Count>().unwrap()[0].cnt
+    };
+    assert_eq!(count("symbol_info"), 1);
+    assert_eq!(count("kline"), 1001);
+    assert_eq!(count("trade"), 1002);
+
+    let imported_info = get_symbol_info(dst_db.clone(), &market_type, "BTCUSDT")
+        .unwrap()
+        .unwrap();
+    assert_eq!(imported_info.min_notional, Some(Decimal::from(5)));
+    assert!(get_symbol_info(dst_db.clone(), &market_type, "ETHUSDT")
+        .unwrap()
+        .is_none());
+
+    let imported_klines = get_klines(
+        dst_db.clone(),
+        &market_type,
+        "BTCUSDT",
+        &KlineInterval::OneMinute,
+        Some(500 * 60_000),
+        Some(500 * 60_000),
+        None,
+    )
+    .unwrap();
+    assert_eq!(imported_klines.len(), 1);
+    assert_eq!(imported_klines[0].close_time, klines[500].close_time);
+    assert_eq!(imported_klines[0].high, klines[500].high);
+    assert_eq!(imported_klines[0].close, klines[500].close);
+    assert_eq!(imported_klines[0].volume, klines[500].volume);
+
+    let imported_trades = get_trades(
+        dst_db.clone(),
+        &market_type,
+        "BTCUSDT",
+        None,
+        None,
+        Some(199),
+        Some(1),
+    )
+    .unwrap();
+    assert_eq!(imported_trades.len(), 1);
+    assert_eq!(imported_trades[0].seq_id, 199);
+    assert_eq!(imported_trades[0].timestamp, 100 * 60_000);
+    assert_eq!(imported_trades[0].price, trades[198].price);
+    assert_eq!(
+        imported_trades[0].is_buyer_maker,
+        trades[198].is_buyer_maker
+    );
+}
+
+#[test]
+fn test_export_csv() {
+    let market_type = MarketType::BinanceSpot;
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    let symbol = "BTC,USDT";
+    update_kline_data(
+        db.clone(),
+        &market_type,
+        &[
+            kline(symbol, 0),
+            kline(symbol, 60_000),
+            kline(symbol, 120_000),
+        ],
+    )
+    .unwrap();
+    update_trade_data(
+        db.clone(),
+        &market_type,
+        &[
+            trade(symbol, 1, 1000),
+            Trade {
+                price: Decimal::from_str("100.10000").unwrap(),
+                ..trade(symbol, 2, 2000)
+            },
+            trade(symbol, 3, 3000),
+        ],
+    )
+    .unwrap();
+
+    let mut output = vec![];
+    let rows = export_klines_csv(
+        db.clone(),
+        &market_type,
+        symbol,
+        &KlineInterval::OneMinute,
+        60_000,
+        120_000,
+        &mut output,
+    )
+    .unwrap();
+    assert_eq!(rows, 2);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "symbol,interval,open_time,close_time,open,high,low,close,volume,quote_volume,taker_buy_volume,taker_buy_quote_volume,is_closed\n\
+         \"BTC,USDT\",1m,60000,119999,100,101.5,99,100.25,1.5,150,1,100,1\n\
+         \"BTC,USDT\",1m,120000,179999,100,101.5,99,100.25,1.5,150,1,100,1\n"
+    );
+
+    let mut output = vec![];
+    let rows = export_trades_csv(db.clone(), &market_type, symbol, 1, 2000, &mut output).unwrap();
+    assert_eq!(rows, 2);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "symbol,trade_id,price,quantity,timestamp,is_buyer_maker,seq_id\n\
+         \"BTC,USDT\",1,100.1,0.25,1000,1,1\n\
+         \"BTC,USDT\",2,100.1,0.25,2000,0,2\n"
+    );
+
+    // 无数据时只有表头
+    let mut output = vec![];
+    let rows =
+        export_trades_csv(db.clone(), &market_type, "ETHUSDT", 1, 2000, &mut output).unwrap();
+    assert_eq!(rows, 0);
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "symbol,trade_id,price,quantity,timestamp,is_buyer_maker,seq_id\n"
+    );
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_export_trades_parquet() {
+    use crate::data_manager::dataset::export_trades_parquet;
+    use arrow_array::{Array, BooleanArray, Int64Array, StringArray, UInt64Array};
+    use arrow_schema::DataType;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let market_type = MarketType::BinanceSpot;
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    let trades = (1..=3000)
+        .map(|i| trade("BTCUSDT", i, i * 1000))
+        .collect::<Vec<_>>();
+    for chunk in trades.chunks(500) {
+        update_trade_data(db.clone(), &market_type, chunk).unwrap();
+    }
+
+    let output = NamedTempFile::new().unwrap();
+    let rows = export_trades_parquet(
+        db.clone(),
+        &market_type,
+        "BTCUSDT",
+        1,
+        u64::MAX / 2,
+        1024,
+        output.reopen().unwrap(),
+    )
+    .unwrap();
+    assert_eq!(rows, 3000);
+
+    let builder = ParquetRecordBatchReaderBuilder::try_new(output.reopen().unwrap()).unwrap();
+    assert_eq!(builder.metadata().num_row_groups(), 3);
+    let schema = builder.schema().clone();
+    assert_eq!(
+        schema.field_with_name("price").unwrap().data_type(),
+        &DataType::Utf8
+    );
+    assert_eq!(
+        schema.field_with_name("timestamp").unwrap().data_type(),
+        &DataType::Int64
+    );
+    assert_eq!(
+        schema
+            .field_with_name("is_buyer_maker")
+            .unwrap()
+            .data_type(),
+        &DataType::Boolean
+    );
+
+    let mut seq_ids = vec![];
+    for batch in builder.build().unwrap() {
+        let batch = batch.unwrap();
+        let column = |name: &str| batch.column(schema.index_of(name).unwrap()).clone();
+        let price = column("price");
+        let price = price.as_any().downcast_ref::<StringArray>().unwrap();
+        let timestamp = column("timestamp");
+        let timestamp = timestamp.as_any().downcast_ref::<Int64Array>().unwrap();
+        let is_buyer_maker = column("is_buyer_maker");
+        let is_buyer_maker = is_buyer_maker
+            .as_any()
+            .downcast_ref::<BooleanArray>()
+            .unwrap();
+        let seq_id = column("seq_id");
+        let seq_id = seq_id.as_any().downcast_ref::<UInt64Array>().unwrap();
+        for i in 0..batch.num_rows() {
+            let expected = &trades[seq_id.value(i) as usize - 1];
+            assert_eq!(price.value(i), expected.price.to_string());
+            assert_eq!(timestamp.value(i), expected.timestamp as i64);
+            assert_eq!(is_buyer_maker.value(i), expected.is_buyer_maker != 0);
+            seq_ids.push(seq_id.value(i));
+        }
+        assert_eq!(price.len(), batch.num_rows());
+    }
+    assert_eq!(seq_ids, (1..=3000).collect::<Vec<_>>());
+}
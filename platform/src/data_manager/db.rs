@@ -1,8 +1,8 @@
 use crate::{
     errors::{PlatformError, Result},
     models::{
-        Account, AccountUpdate, Balance, KlineData, KlineInterval, MarketType, Order, SymbolInfo,
-        Trade, UserTrade,
+        Account, AccountUpdate, Balance, BalanceSnapshot, DepthDiff, DepthSnapshot, KlineData,
+        KlineInterval, MarketType, Order, OrderStatus, PriceLevel, SymbolInfo, Trade, UserTrade,
     },
 };
 use db::{common::Row, sqlite::SQLiteDB};
@@ -36,8 +36,9 @@ pub fn create_symbol_info_table(db: Arc<SQLiteDB>) -> Result<()> {
     );
     "#;
     db.execute_update(sql, &[])
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to create symbol_info table: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to create symbol_info table".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -126,8 +127,9 @@ pub fn update_symbol_info(
         .collect::<Vec<_>>();
     let params = values.iter().map(|v| v as &dyn ToSql).collect::<Vec<_>>();
     db.execute_update(&sql, &params)
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to update symbol_info data: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to update symbol_info data".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -150,8 +152,9 @@ pub fn get_symbol_info(
     let params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
     let result = db
         .execute_query(&sql, &params)
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to get symbol_info: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to get symbol_info".to_string(),
+            source: e,
         })?;
 
     if result.is_empty() {
@@ -161,8 +164,9 @@ pub fn get_symbol_info(
     let symbol_infos: Vec<SymbolInfo> =
         result
             .into_struct::<SymbolInfo>()
-            .map_err(|e| PlatformError::PlatformError {
-                message: format!("Fail to into symbol_info: {}", e),
+            .map_err(|e| PlatformError::DbError {
+                context: "Fail to into symbol_info".to_string(),
+                source: e,
             })?;
 
     Ok(symbol_infos.into_iter().next())
@@ -183,14 +187,16 @@ pub fn get_all_symbol_info(db: Arc<SQLiteDB>, market_type: &MarketType) -> Resul
     let params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
     let result = db
         .execute_query(&sql, &params)
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to get all symbol_info: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to get all symbol_info".to_string(),
+            source: e,
         })?;
 
     result
         .into_struct::<SymbolInfo>()
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to into symbol_info list: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to into symbol_info list".to_string(),
+            source: e,
         })
 }
 
@@ -216,8 +222,9 @@ pub fn create_kline_table(db: Arc<SQLiteDB>) -> Result<()> {
     );
     "#;
     db.execute_update(sql, &[])
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to create kline table: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to create kline table".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -238,16 +245,18 @@ pub fn create_trade_table(db: Arc<SQLiteDB>) -> Result<()> {
     );
     "#;
     db.execute_update(sql, &[])
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to create trade table: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to create trade table".to_string(),
+            source: e,
         })?;
     let index = r#"
     CREATE INDEX IF NOT EXISTS idx_trade_symbol_timestamp_seq_id
     ON trade (market_type, symbol, timestamp, seq_id);
     "#;
     db.execute_update(index, &[])
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to create trade index: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to create trade index".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -303,8 +312,9 @@ pub fn update_kline_data(
         .collect::<Vec<_>>();
     let params = values.iter().map(|v| v as &dyn ToSql).collect::<Vec<_>>();
     db.execute_update(&sql, &params)
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to update kline data: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to update kline data".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -344,8 +354,9 @@ pub fn update_trade_data(
         .collect::<Vec<_>>();
     let params = values.iter().map(|v| v as &dyn ToSql).collect::<Vec<_>>();
     db.execute_update(&sql, &params)
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to update trade data: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to update trade data".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -383,13 +394,15 @@ pub fn get_klines(
     let params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
     let result = db
         .execute_query(&sql, &params)
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to get klines: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to get klines".to_string(),
+            source: e,
         })?;
     result
         .into_struct::<KlineData>()
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to into klines: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to into klines".to_string(),
+            source: e,
         })
         .map(|mut v| {
             v.sort_by(|a, b| a.open_time.cmp(&b.open_time));
@@ -428,7 +441,7 @@ pub fn get_trades(
             SELECT symbol, trade_id, price, quantity, timestamp, is_buyer_maker, seq_id
             FROM trade
             WHERE market_type = ? AND symbol = ? AND timestamp >= {} AND timestamp <= {}
-            ORDER BY timestamp ASC
+            ORDER BY timestamp ASC, seq_id ASC
             LIMIT {};
             "#,
                 start_time, end_time, limit
@@ -439,7 +452,7 @@ pub fn get_trades(
             SELECT symbol, trade_id, price, quantity, timestamp, is_buyer_maker, seq_id
             FROM trade
             WHERE market_type = ? AND symbol = ? AND timestamp >= {} AND timestamp <= {}
-            ORDER BY timestamp DESC
+            ORDER BY timestamp DESC, seq_id DESC
             LIMIT {};
             "#,
                 start_time, end_time, limit
@@ -451,13 +464,15 @@ pub fn get_trades(
     let params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
     let result = db
         .execute_query(&sql, &params)
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to get trades: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to get trades".to_string(),
+            source: e,
         })?;
     result
         .into_struct::<Trade>()
-        .map_err(|e| PlatformError::PlatformError {
-            message: format!("Fail to into trades: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to into trades".to_string(),
+            source: e,
         })
         .map(|mut v| {
             v.sort_by(|a, b| a.seq_id.cmp(&b.seq_id));
@@ -465,16 +480,327 @@ pub fn get_trades(
         })
 }
 
+// [start_time, end_time]内的kline收盘时间，升序，用于按事件推进回测时钟
+pub fn get_kline_close_times(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    interval: &KlineInterval,
+    start_time: u64,
+    end_time: u64,
+) -> Result<Vec<u64>> {
+    let sql = r#"
+    SELECT close_time
+    FROM kline
+    WHERE market_type = ? AND symbol = ? AND interval = ? AND close_time >= ? AND close_time <= ?
+    ORDER BY close_time ASC;
+    "#;
+    let values: Vec<String> = vec![
+        market_type.as_str().to_string(),
+        symbol.to_string(),
+        interval.as_str().to_string(),
+        start_time.to_string(),
+        end_time.to_string(),
+    ];
+    let params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
+    let result = db
+        .execute_query(sql, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to get kline close times".to_string(),
+            source: e,
+        })?;
+    Ok(result
+        .rows
+        .iter()
+        .filter_map(|row| row.get_i64("close_time"))
+        .map(|ts| ts as u64)
+        .collect())
+}
+
+// [start_time, end_time]内去重后的成交时间，升序
+pub fn get_trade_timestamps(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    start_time: u64,
+    end_time: u64,
+) -> Result<Vec<u64>> {
+    let sql = r#"
+    SELECT DISTINCT timestamp
+    FROM trade
+    WHERE market_type = ? AND symbol = ? AND timestamp >= ? AND timestamp <= ?
+    ORDER BY timestamp ASC;
+    "#;
+    let values: Vec<String> = vec![
+        market_type.as_str().to_string(),
+        symbol.to_string(),
+        start_time.to_string(),
+        end_time.to_string(),
+    ];
+    let params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
+    let result = db
+        .execute_query(sql, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to get trade timestamps".to_string(),
+            source: e,
+        })?;
+    Ok(result
+        .rows
+        .iter()
+        .filter_map(|row| row.get_i64("timestamp"))
+        .map(|ts| ts as u64)
+        .collect())
+}
+
+// 盘口快照与增量，档位以json存储
+pub fn create_depth_tables(db: Arc<SQLiteDB>) -> Result<()> {
+    let snapshot_sql = r#"
+    CREATE TABLE IF NOT EXISTS depth_snapshot (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        market_type TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        last_update_id INTEGER NOT NULL,
+        bids TEXT NOT NULL,
+        asks TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        UNIQUE(market_type, symbol, last_update_id)
+    );
+    "#;
+    db.execute_update(snapshot_sql, &[])
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to create depth snapshot table".to_string(),
+            source: e,
+        })?;
+    let snapshot_index = r#"
+    CREATE INDEX IF NOT EXISTS idx_depth_snapshot_symbol_timestamp
+    ON depth_snapshot (market_type, symbol, timestamp);
+    "#;
+    db.execute_update(snapshot_index, &[])
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to create depth snapshot index".to_string(),
+            source: e,
+        })?;
+    let diff_sql = r#"
+    CREATE TABLE IF NOT EXISTS depth_diff (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        market_type TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        first_update_id INTEGER NOT NULL,
+        last_update_id INTEGER NOT NULL,
+        bids TEXT NOT NULL,
+        asks TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        UNIQUE(market_type, symbol, last_update_id)
+    );
+    "#;
+    db.execute_update(diff_sql, &[])
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to create depth diff table".to_string(),
+            source: e,
+        })?;
+    Ok(())
+}
+
+fn levels_to_json(levels: &[PriceLevel]) -> Result<String> {
+    serde_json::to_string(levels).map_err(|e| PlatformError::DataManagerError {
+        message: format!("serialize price levels err: {}", e),
+    })
+}
+
+fn levels_from_json(levels: &str) -> Result<Vec<PriceLevel>> {
+    serde_json::from_str(levels).map_err(|e| PlatformError::DataManagerError {
+        message: format!("parse price levels err: {}", e),
+    })
+}
+
+#[derive(serde::Deserialize)]
+struct DepthRow {
+    symbol: String,
+    #[serde(default)]
+    first_update_id: u64,
+    last_update_id: u64,
+    bids: String,
+    asks: String,
+    timestamp: u64,
+}
+
+pub fn update_depth_snapshot(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    snapshot: &DepthSnapshot,
+) -> Result<()> {
+    let sql = r#"
+    INSERT INTO depth_snapshot (market_type, symbol, last_update_id, bids, asks, timestamp)
+    VALUES (?, ?, ?, ?, ?, ?)
+    ON CONFLICT(market_type, symbol, last_update_id) DO NOTHING;
+    "#;
+    let values = [
+        market_type.as_str().to_string(),
+        snapshot.symbol.clone(),
+        snapshot.last_update_id.to_string(),
+        levels_to_json(&snapshot.bids)?,
+        levels_to_json(&snapshot.asks)?,
+        snapshot.timestamp.to_string(),
+    ];
+    let params = values.iter().map(|v| v as &dyn ToSql).collect::<Vec<_>>();
+    db.execute_update(sql, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to update depth snapshot".to_string(),
+            source: e,
+        })?;
+    Ok(())
+}
+
+pub fn update_depth_diffs(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    diffs: &[DepthDiff],
+) -> Result<()> {
+    if diffs.is_empty() {
+        return Ok(());
+    }
+    let placeholder = diffs
+        .iter()
+        .map(|_| "(?, ?, ?, ?, ?, ?, ?)")
+        .collect::<Vec<_>>()
+        .join(", ");
+    let sql = format!(
+        r#"
+    INSERT INTO depth_diff (market_type, symbol, first_update_id, last_update_id, bids, asks, timestamp)
+    VALUES {}
+    ON CONFLICT(market_type, symbol, last_update_id) DO NOTHING;
+    "#,
+        placeholder
+    );
+    let mut values = Vec::with_capacity(diffs.len() * 7);
+    for diff in diffs {
+        values.push(market_type.as_str().to_string());
+        values.push(diff.symbol.clone());
+        values.push(diff.first_update_id.to_string());
+        values.push(diff.last_update_id.to_string());
+        values.push(levels_to_json(&diff.bids)?);
+        values.push(levels_to_json(&diff.asks)?);
+        values.push(diff.timestamp.to_string());
+    }
+    let params = values.iter().map(|v| v as &dyn ToSql).collect::<Vec<_>>();
+    db.execute_update(&sql, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to update depth diffs".to_string(),
+            source: e,
+        })?;
+    Ok(())
+}
+
+// timestamp <= end_time的最近一个快照
+pub fn get_depth_snapshot_before(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    end_time: u64,
+) -> Result<Option<DepthSnapshot>> {
+    let sql = r#"
+    SELECT symbol, last_update_id, bids, asks, timestamp
+    FROM depth_snapshot
+    WHERE market_type = ? AND symbol = ? AND timestamp <= ?
+    ORDER BY timestamp DESC, last_update_id DESC
+    LIMIT 1;
+    "#;
+    let values: Vec<String> = vec![
+        market_type.as_str().to_string(),
+        symbol.to_string(),
+        end_time.to_string(),
+    ];
+    let params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
+    let result = db
+        .execute_query(sql, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to get depth snapshot".to_string(),
+            source: e,
+        })?;
+    let rows = result
+        .into_struct::<DepthRow>()
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to into depth snapshot".to_string(),
+            source: e,
+        })?;
+    match rows.into_iter().next() {
+        None => Ok(None),
+        Some(row) => Ok(Some(DepthSnapshot {
+            symbol: row.symbol,
+            last_update_id: row.last_update_id,
+            bids: levels_from_json(&row.bids)?,
+            asks: levels_from_json(&row.asks)?,
+            timestamp: row.timestamp,
+        })),
+    }
+}
+
+// last_update_id > after_update_id且timestamp <= end_time的增量，按update id升序
+pub fn get_depth_diffs(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    after_update_id: u64,
+    end_time: u64,
+    limit: Option<u64>,
+) -> Result<Vec<DepthDiff>> {
+    let limit = limit.unwrap_or(1000);
+    let sql = format!(
+        r#"
+    SELECT symbol, first_update_id, last_update_id, bids, asks, timestamp
+    FROM depth_diff
+    WHERE market_type = ? AND symbol = ? AND last_update_id > ? AND timestamp <= ?
+    ORDER BY last_update_id ASC
+    LIMIT {};
+    "#,
+        limit
+    );
+    let values: Vec<String> = vec![
+        market_type.as_str().to_string(),
+        symbol.to_string(),
+        after_update_id.to_string(),
+        end_time.to_string(),
+    ];
+    let params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
+    let result = db
+        .execute_query(&sql, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to get depth diffs".to_string(),
+            source: e,
+        })?;
+    let rows = result
+        .into_struct::<DepthRow>()
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to into depth diffs".to_string(),
+            source: e,
+        })?;
+    rows.into_iter()
+        .map(|row| {
+            Ok(DepthDiff {
+                symbol: row.symbol,
+                first_update_id: row.first_update_id,
+                last_update_id: row.last_update_id,
+                bids: levels_from_json(&row.bids)?,
+                asks: levels_from_json(&row.asks)?,
+                timestamp: row.timestamp,
+            })
+        })
+        .collect()
+}
+
 pub fn create_api_sync_ts_table(db: Arc<SQLiteDB>) -> Result<()> {
     let query = r#"
         CREATE TABLE IF NOT EXISTS api_sync_ts (
-            market_type TEXT NOT NULL PRIMARY KEY,
-            last_sync_ts INTEGER NOT NULL
+            market_type TEXT NOT NULL,
+            account_id TEXT NOT NULL DEFAULT 'default',
+            last_sync_ts INTEGER NOT NULL,
+            PRIMARY KEY(market_type, account_id)
         )
     "#;
     db.execute_update(query, &[])
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("create api_sync_ts table failed: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "create api_sync_ts table failed".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -484,16 +810,18 @@ pub fn create_account_balance_table(db: Arc<SQLiteDB>) -> Result<()> {
         CREATE TABLE IF NOT EXISTS account_balance (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             market_type TEXT NOT NULL,
+            account_id TEXT NOT NULL DEFAULT 'default',
             asset TEXT NOT NULL,
             free TEXT NOT NULL,
             locked TEXT NOT NULL,
             updated_at INTEGER NOT NULL,
-            UNIQUE(market_type, asset)
+            UNIQUE(market_type, account_id, asset)
         )
     "#;
     db.execute_update(query, &[])
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("create account_balance table failed: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "create account_balance table failed".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -503,6 +831,7 @@ pub fn create_orders_table(db: Arc<SQLiteDB>) -> Result<()> {
         CREATE TABLE IF NOT EXISTS orders (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             market_type TEXT NOT NULL,
+            account_id TEXT NOT NULL DEFAULT 'default',
             symbol TEXT NOT NULL,
             order_id TEXT NOT NULL,
             client_order_id TEXT NOT NULL,
@@ -516,50 +845,47 @@ pub fn create_orders_table(db: Arc<SQLiteDB>) -> Result<()> {
             time_in_force TEXT NOT NULL,
             stop_price TEXT NOT NULL,
             iceberg_qty TEXT NOT NULL,
+            quote_order_qty TEXT NOT NULL DEFAULT '0',
             create_time INTEGER NOT NULL,
             update_time INTEGER NOT NULL,
-            UNIQUE(market_type, symbol, client_order_id)
+            UNIQUE(market_type, account_id, symbol, client_order_id)
         )
     "#;
     db.execute_update(query, &[])
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("create orders table failed: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "create orders table failed".to_string(),
+            source: e,
         })?;
 
     let index = r#"
         CREATE INDEX IF NOT EXISTS idx_orders_market_type_symbol_update_time 
-        ON orders(market_type, symbol, update_time DESC)
+        ON orders(market_type, account_id, symbol, update_time DESC)
     "#;
     db.execute_update(index, &[])
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!(
-                "create orders (market_type, symbol, update_time) index failed: {}",
-                e
-            ),
+        .map_err(|e| PlatformError::DbError {
+            context: "create orders (market_type, symbol, update_time) index failed".to_string(),
+            source: e,
         })?;
 
     let index = r#"
         CREATE INDEX IF NOT EXISTS idx_orders_market_type_status_update_time 
-        ON orders(market_type, order_status, update_time DESC)
+        ON orders(market_type, account_id, order_status, update_time DESC)
     "#;
     db.execute_update(index, &[])
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!(
-                "create orders (market_type, order_status, update_time) index failed: {}",
-                e
-            ),
+        .map_err(|e| PlatformError::DbError {
+            context: "create orders (market_type, order_status, update_time) index failed"
+                .to_string(),
+            source: e,
         })?;
 
     let index = r#"
         CREATE INDEX IF NOT EXISTS idx_orders_market_type_order_id 
-        ON orders(market_type, symbol, order_id)
+        ON orders(market_type, account_id, symbol, order_id)
     "#;
     db.execute_update(index, &[])
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!(
-                "create orders (market_type, symbol, order_id) index failed: {}",
-                e
-            ),
+        .map_err(|e| PlatformError::DbError {
+            context: "create orders (market_type, symbol, order_id) index failed".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -569,6 +895,7 @@ pub fn create_user_trades_table(db: Arc<SQLiteDB>) -> Result<()> {
         CREATE TABLE IF NOT EXISTS user_trades (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             market_type TEXT NOT NULL,
+            account_id TEXT NOT NULL DEFAULT 'default',
             trade_id TEXT NOT NULL,
             order_id TEXT NOT NULL,
             symbol TEXT NOT NULL,
@@ -579,54 +906,48 @@ pub fn create_user_trades_table(db: Arc<SQLiteDB>) -> Result<()> {
             commission_asset TEXT NOT NULL,
             is_maker INTEGER NOT NULL,
             timestamp INTEGER NOT NULL,
-            UNIQUE(market_type, symbol, trade_id)
+            UNIQUE(market_type, account_id, symbol, trade_id)
         )
     "#;
     db.execute_update(query, &[])
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("create user_trades table failed: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "create user_trades table failed".to_string(),
+            source: e,
         })?;
 
-    let index = r#"
-        CREATE INDEX IF NOT EXISTS idx_user_trades_market_type_order_id 
-        ON user_trades(market_type, symbol, order_id)
-    "#;
-    db.execute_update(index, &[])
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!(
-                "create user_trades (market_type, symbol, order_id) index failed: {}",
-                e
-            ),
-        })?;
+    create_user_trades_order_index(db.clone())?;
 
     let index = r#"
         CREATE INDEX IF NOT EXISTS idx_user_trades_market_type_symbol_timestamp 
-        ON user_trades(market_type, symbol, timestamp DESC)
+        ON user_trades(market_type, account_id, symbol, timestamp DESC)
     "#;
     db.execute_update(index, &[])
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!(
-                "create user_trades (market_type, symbol, timestamp) index failed: {}",
-                e
-            ),
+        .map_err(|e| PlatformError::DbError {
+            context: "create user_trades (market_type, symbol, timestamp) index failed".to_string(),
+            source: e,
         })?;
 
     Ok(())
 }
 
-pub fn get_last_sync_ts(db: Arc<SQLiteDB>, market_type: &MarketType) -> Result<Option<u64>> {
+pub fn get_last_sync_ts(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    account_id: &str,
+) -> Result<Option<u64>> {
     let query = r#"
         SELECT last_sync_ts
         FROM api_sync_ts
-        WHERE market_type = ?1
+        WHERE market_type = ?1 AND account_id = ?2
     "#;
     let market_type_str = market_type.as_str().to_string();
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str];
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &account_id];
 
     let result = db
         .execute_query(query, &params)
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("get last sync ts err: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "get last sync ts err".to_string(),
+            source: e,
         })?;
 
     if result.is_empty() {
@@ -646,22 +967,46 @@ pub fn get_last_sync_ts(db: Arc<SQLiteDB>, market_type: &MarketType) -> Result<O
 pub fn update_last_sync_ts(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     last_sync_ts: u64,
 ) -> Result<()> {
     let query = r#"
-        INSERT INTO api_sync_ts (market_type, last_sync_ts)
-        VALUES (?1, ?2)
-        ON CONFLICT(market_type) DO UPDATE SET
+        INSERT INTO api_sync_ts (market_type, account_id, last_sync_ts)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT(market_type, account_id) DO UPDATE SET
             last_sync_ts = excluded.last_sync_ts
         WHERE excluded.last_sync_ts >= api_sync_ts.last_sync_ts
     "#;
     let market_type_str = market_type.as_str().to_string();
     let last_sync_ts_i64 = last_sync_ts as i64;
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &last_sync_ts_i64];
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &account_id, &last_sync_ts_i64];
 
     db.execute_update(query, &params)
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("update last sync ts err: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "update last sync ts err".to_string(),
+            source: e,
+        })?;
+    Ok(())
+}
+
+// 余额变化流水，只追加；同一资产同一时间戳重复写入（如定期同步拿到相同快照）时保留首条
+pub fn create_balance_history_table(db: Arc<SQLiteDB>) -> Result<()> {
+    let query = r#"
+        CREATE TABLE IF NOT EXISTS balance_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            market_type TEXT NOT NULL,
+            account_id TEXT NOT NULL DEFAULT 'default',
+            asset TEXT NOT NULL,
+            free TEXT NOT NULL,
+            locked TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            UNIQUE(market_type, account_id, asset, timestamp)
+        )
+    "#;
+    db.execute_update(query, &[])
+        .map_err(|e| PlatformError::DbError {
+            context: "create balance_history table failed".to_string(),
+            source: e,
         })?;
     Ok(())
 }
@@ -669,19 +1014,30 @@ pub fn update_last_sync_ts(
 pub fn update_account_balance(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     balances: &Vec<Balance>,
     timestamp: u64,
 ) -> Result<()> {
+    if balances.is_empty() {
+        return Ok(());
+    }
     let placeholders = balances
         .iter()
-        .map(|_| "(?, ?, ?, ?, ?)")
+        .map(|_| "(?, ?, ?, ?, ?, ?)")
         .collect::<Vec<_>>()
         .join(", ");
+    let history_query = format!(
+        r#"
+        INSERT OR IGNORE INTO balance_history (market_type, account_id, asset, free, locked, timestamp)
+        VALUES {}
+    "#,
+        placeholders
+    );
     let query = format!(
         r#"
-        INSERT INTO account_balance (market_type, asset, free, locked, updated_at)
+        INSERT INTO account_balance (market_type, account_id, asset, free, locked, updated_at)
         VALUES {}
-        ON CONFLICT(market_type, asset) DO UPDATE SET
+        ON CONFLICT(market_type, account_id, asset) DO UPDATE SET
             free = excluded.free,
             locked = excluded.locked,
             updated_at = excluded.updated_at
@@ -692,6 +1048,7 @@ pub fn update_account_balance(
     let mut params: Vec<String> = Vec::new();
     for balance in balances {
         params.push(market_type.as_str().to_string());
+        params.push(account_id.to_string());
         params.push(balance.asset.clone());
         params.push(balance.free.to_string());
         params.push(balance.locked.to_string());
@@ -700,21 +1057,91 @@ pub fn update_account_balance(
     let params_refs: Vec<&dyn rusqlite::ToSql> =
         params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
 
-    db.execute_update(&query, &params_refs)
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("update account balance err: {}", e),
+    // 流水按到达顺序全部记录，最新快照只接受不早于当前的更新；两者同一事务写入
+    db.with_transaction(|| {
+        db.execute_update(&history_query, &params_refs)
+            .map_err(|e| PlatformError::DbError {
+                context: "insert balance history err".to_string(),
+                source: e,
+            })?;
+        db.execute_update(&query, &params_refs)
+            .map_err(|e| PlatformError::DbError {
+                context: "update account balance err".to_string(),
+                source: e,
+            })?;
+        Ok(())
+    })
+}
+
+// [start, end]内某资产的余额流水，按时间升序
+pub fn get_balance_history(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    account_id: &str,
+    asset: &str,
+    start: u64,
+    end: u64,
+) -> Result<Vec<BalanceSnapshot>> {
+    let query = r#"
+        SELECT asset, free, locked, timestamp
+        FROM balance_history
+        WHERE market_type = ? AND account_id = ? AND asset = ? AND timestamp >= ? AND timestamp <= ?
+        ORDER BY timestamp ASC
+    "#;
+    let values: Vec<String> = vec![
+        market_type.as_str().to_string(),
+        account_id.to_string(),
+        asset.to_string(),
+        start.to_string(),
+        end.to_string(),
+    ];
+    let params: Vec<&dyn ToSql> = values.iter().map(|v| v as &dyn ToSql).collect();
+    let result = db
+        .execute_query(query, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "get balance history err".to_string(),
+            source: e,
         })?;
-    Ok(())
+    result
+        .rows
+        .iter()
+        .map(|row| {
+            let decimal = |col: &str| -> Result<Decimal> {
+                let s = row.get_string(col).ok_or(PlatformError::DataManagerError {
+                    message: format!("column {} not found", col),
+                })?;
+                Decimal::from_str(&s).map_err(|e| PlatformError::DataManagerError {
+                    message: format!("parse decimal error for column {}: {}", col, e),
+                })
+            };
+            Ok(BalanceSnapshot {
+                asset: row
+                    .get_string("asset")
+                    .ok_or(PlatformError::DataManagerError {
+                        message: "column asset not found".to_string(),
+                    })?,
+                free: decimal("free")?,
+                locked: decimal("locked")?,
+                timestamp: row
+                    .get_i64("timestamp")
+                    .ok_or(PlatformError::DataManagerError {
+                        message: "column timestamp not found".to_string(),
+                    })? as u64,
+            })
+        })
+        .collect()
 }
 
 pub fn update_account_update(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     account_update: &AccountUpdate,
 ) -> Result<()> {
     update_account_balance(
         db,
         market_type,
+        account_id,
         &account_update.balances,
         account_update.timestamp,
     )
@@ -723,21 +1150,63 @@ pub fn update_account_update(
 pub fn update_account(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     account: &Account,
 ) -> Result<()> {
-    update_account_balance(db, market_type, &account.balances, account.timestamp)
+    update_account_balance(
+        db,
+        market_type,
+        account_id,
+        &account.balances,
+        account.timestamp,
+    )
 }
 
-pub fn update_order(db: Arc<SQLiteDB>, market_type: &MarketType, order: &Order) -> Result<()> {
-    let query = r#"
+// 单条upsert语句的最大行数，避免超出sqlite参数个数上限
+const UPSERT_CHUNK_ROWS: usize = 500;
+
+fn values_placeholders(rows: usize, columns: usize) -> String {
+    let row = format!("({})", vec!["?"; columns].join(", "));
+    vec![row; rows].join(", ")
+}
+
+pub fn update_order(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    account_id: &str,
+    order: &Order,
+) -> Result<()> {
+    update_orders(db, market_type, account_id, std::slice::from_ref(order))
+}
+
+// 终态列表由OrderStatus::is_terminal生成，如'FILLED', 'CANCELED'
+fn terminal_order_status_list() -> String {
+    OrderStatus::KNOWN
+        .iter()
+        .filter(|status| status.is_terminal())
+        .map(|status| format!("'{}'", status.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// 批量upsert，每条语句最多UPSERT_CHUNK_ROWS行；冲突时逐行判断，较新的update_time胜出，终态不会被非终态覆盖
+pub fn update_orders(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    account_id: &str,
+    orders: &[Order],
+) -> Result<()> {
+    for chunk in orders.chunks(UPSERT_CHUNK_ROWS) {
+        let query = format!(
+            r#"
         INSERT INTO orders (
-            market_type, symbol, order_id, client_order_id, order_side, 
+            market_type, account_id, symbol, order_id, client_order_id, order_side, 
             order_type, order_status, order_price, order_quantity, 
             executed_qty, cummulative_quote_qty, time_in_force, 
-            stop_price, iceberg_qty, create_time, update_time
+            stop_price, iceberg_qty, quote_order_qty, create_time, update_time
         )
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)
-        ON CONFLICT(market_type, symbol, client_order_id) DO UPDATE SET
+        VALUES {}
+        ON CONFLICT(market_type, account_id, symbol, client_order_id) DO UPDATE SET
             order_id = excluded.order_id,
             order_side = excluded.order_side,
             order_type = excluded.order_type,
@@ -749,52 +1218,80 @@ pub fn update_order(db: Arc<SQLiteDB>, market_type: &MarketType, order: &Order)
             time_in_force = excluded.time_in_force,
             stop_price = excluded.stop_price,
             iceberg_qty = excluded.iceberg_qty,
+            quote_order_qty = excluded.quote_order_qty,
             create_time = excluded.create_time,
             update_time = excluded.update_time
         WHERE excluded.update_time >= orders.update_time
-    "#;
+            AND (
+                orders.order_status NOT IN ({terminal})
+                OR excluded.order_status IN ({terminal})
+            )
+    "#,
+            values_placeholders(chunk.len(), 18),
+            terminal = terminal_order_status_list()
+        );
 
-    let params: Vec<String> = vec![
-        market_type.as_str().to_string(),
-        order.symbol.clone(),
-        order.order_id.clone(),
-        order.client_order_id.clone(),
-        order.order_side.as_str().to_string(),
-        order.order_type.as_str().to_string(),
-        order.order_status.as_str().to_string(),
-        order.order_price.to_string(),
-        order.order_quantity.to_string(),
-        order.executed_qty.to_string(),
-        order.cummulative_quote_qty.to_string(),
-        order.time_in_force.as_str().to_string(),
-        order.stop_price.to_string(),
-        order.iceberg_qty.to_string(),
-        order.create_time.to_string(),
-        order.update_time.to_string(),
-    ];
-    let params_refs: Vec<&dyn rusqlite::ToSql> =
-        params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
+        let mut params: Vec<String> = Vec::with_capacity(chunk.len() * 18);
+        for order in chunk {
+            params.extend([
+                market_type.as_str().to_string(),
+                account_id.to_string(),
+                order.symbol.clone(),
+                order.order_id.clone(),
+                order.client_order_id.clone(),
+                order.order_side.as_str().to_string(),
+                order.order_type.as_str().to_string(),
+                order.order_status.as_str().to_string(),
+                order.order_price.to_string(),
+                order.order_quantity.to_string(),
+                order.executed_qty.to_string(),
+                order.cummulative_quote_qty.to_string(),
+                order.time_in_force.as_str().to_string(),
+                order.stop_price.to_string(),
+                order.iceberg_qty.to_string(),
+                order.quote_order_qty.to_string(),
+                order.create_time.to_string(),
+                order.update_time.to_string(),
+            ]);
+        }
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
 
-    db.execute_update(query, &params_refs)
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("update order err: {}", e),
-        })?;
+        db.execute_update(&query, &params_refs)
+            .map_err(|e| PlatformError::DbError {
+                context: "update orders err".to_string(),
+                source: e,
+            })?;
+    }
     Ok(())
 }
 
 pub fn update_user_trade(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     trade: &UserTrade,
 ) -> Result<()> {
-    let query = r#"
+    update_user_trades(db, market_type, account_id, std::slice::from_ref(trade))
+}
+
+// 批量upsert，每条语句最多UPSERT_CHUNK_ROWS行
+pub fn update_user_trades(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    account_id: &str,
+    trades: &[UserTrade],
+) -> Result<()> {
+    for chunk in trades.chunks(UPSERT_CHUNK_ROWS) {
+        let query = format!(
+            r#"
         INSERT INTO user_trades (
-            market_type, trade_id, order_id, symbol, order_side,
+            market_type, account_id, trade_id, order_id, symbol, order_side,
             trade_price, trade_quantity, commission, commission_asset,
             is_maker, timestamp
         )
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
-        ON CONFLICT(market_type, symbol, trade_id) DO UPDATE SET
+        VALUES {}
+        ON CONFLICT(market_type, account_id, symbol, trade_id) DO UPDATE SET
             order_id = excluded.order_id,
             order_side = excluded.order_side,
             trade_price = excluded.trade_price,
@@ -803,32 +1300,60 @@ pub fn update_user_trade(
             commission_asset = excluded.commission_asset,
             is_maker = excluded.is_maker,
             timestamp = excluded.timestamp
-    "#;
+    "#,
+            values_placeholders(chunk.len(), 12)
+        );
 
-    let params: Vec<String> = vec![
-        market_type.as_str().to_string(),
-        trade.trade_id.clone(),
-        trade.order_id.clone(),
-        trade.symbol.clone(),
-        trade.order_side.as_str().to_string(),
-        trade.trade_price.to_string(),
-        trade.trade_quantity.to_string(),
-        trade.commission.to_string(),
-        trade.commission_asset.clone(),
-        trade.is_maker.to_string(),
-        trade.timestamp.to_string(),
-    ];
-    let params_refs: Vec<&dyn rusqlite::ToSql> =
-        params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
+        let mut params: Vec<String> = Vec::with_capacity(chunk.len() * 12);
+        for trade in chunk {
+            params.extend([
+                market_type.as_str().to_string(),
+                account_id.to_string(),
+                trade.trade_id.clone(),
+                trade.order_id.clone(),
+                trade.symbol.clone(),
+                trade.order_side.as_str().to_string(),
+                trade.trade_price.to_string(),
+                trade.trade_quantity.to_string(),
+                trade.commission.to_string(),
+                trade.commission_asset.clone(),
+                trade.is_maker.to_string(),
+                trade.timestamp.to_string(),
+            ]);
+        }
+        let params_refs: Vec<&dyn rusqlite::ToSql> =
+            params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
 
-    db.execute_update(query, &params_refs)
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("update user trade err: {}", e),
-        })?;
+        db.execute_update(&query, &params_refs)
+            .map_err(|e| PlatformError::DbError {
+                context: "update user trades err".to_string(),
+                source: e,
+            })?;
+    }
     Ok(())
 }
 
-pub fn get_account(db: Arc<SQLiteDB>, market_type: &MarketType) -> Result<Option<Account>> {
+// 同一事务内写入orders/trades并推进last_sync_ts，任一失败整体回滚，保证last_sync_ts不越过未落库数据
+pub fn sync_orders_and_trades(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    account_id: &str,
+    orders: &[Order],
+    trades: &[UserTrade],
+    last_sync_ts: u64,
+) -> Result<()> {
+    db.with_transaction(|| {
+        update_orders(db.clone(), market_type, account_id, orders)?;
+        update_user_trades(db.clone(), market_type, account_id, trades)?;
+        update_last_sync_ts(db.clone(), market_type, account_id, last_sync_ts)
+    })
+}
+
+pub fn get_account(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    account_id: &str,
+) -> Result<Option<Account>> {
     let get_row_column_string = |row: &Row, col: &str| -> Result<String> {
         row.get_string(col).ok_or(PlatformError::DataManagerError {
             message: format!("column {} not found", col),
@@ -851,15 +1376,16 @@ pub fn get_account(db: Arc<SQLiteDB>, market_type: &MarketType) -> Result<Option
     let query = r#"
         SELECT asset, free, locked, updated_at
         FROM account_balance
-        WHERE market_type = ?1
+        WHERE market_type = ?1 AND account_id = ?2
     "#;
     let market_type_str = market_type.as_str().to_string();
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str];
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &account_id];
 
     let result = db
         .execute_query(query, &params)
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("get account balance err: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "get account balance err".to_string(),
+            source: e,
         })?;
 
     if result.is_empty() {
@@ -892,6 +1418,7 @@ pub fn get_account(db: Arc<SQLiteDB>, market_type: &MarketType) -> Result<Option
 pub fn get_orders(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     symbol: &str,
     start_time: Option<u64>,
     end_time: Option<u64>,
@@ -905,28 +1432,30 @@ pub fn get_orders(
         r#"
         SELECT symbol, order_id, client_order_id, order_side, order_type,
                order_status, order_price, order_quantity, executed_qty,
-               cummulative_quote_qty, time_in_force, stop_price, iceberg_qty,
+               cummulative_quote_qty, time_in_force, stop_price, iceberg_qty, quote_order_qty,
                create_time, update_time
         FROM orders
-        WHERE market_type = ?1 AND symbol = ?2 and update_time >= {} AND update_time <= {}
+        WHERE market_type = ?1 AND account_id = ?2 AND symbol = ?3 and update_time >= {} AND update_time <= {}
         ORDER BY update_time {}
         LIMIT {}
     "#,
         start_time, end_time, order_direction, limit
     );
     let market_type_str = market_type.as_str().to_string();
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &symbol];
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &account_id, &symbol];
 
-    let result =
-        db.execute_query(&query, &params)
-            .map_err(|e| PlatformError::DataManagerError {
-                message: format!("get orders err: {}", e),
-            })?;
+    let result = db
+        .execute_query(&query, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "get orders err".to_string(),
+            source: e,
+        })?;
 
     result
         .into_struct()
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("get_orders into err: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "get_orders into err".to_string(),
+            source: e,
         })
         .map(|mut v| {
             v.sort_by(|a: &Order, b: &Order| a.update_time.cmp(&b.update_time));
@@ -937,31 +1466,33 @@ pub fn get_orders(
 pub fn get_order_by_client_id(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     symbol: &str,
     client_order_id: &str,
 ) -> Result<Option<Order>> {
     let query = r#"
         SELECT symbol, order_id, client_order_id, order_side, order_type,
                order_status, order_price, order_quantity, executed_qty,
-               cummulative_quote_qty, time_in_force, stop_price, iceberg_qty,
+               cummulative_quote_qty, time_in_force, stop_price, iceberg_qty, quote_order_qty,
                create_time, update_time
         FROM orders
-        WHERE market_type = ?1 AND symbol = ?2 AND client_order_id = ?3
+        WHERE market_type = ?1 AND account_id = ?2 AND symbol = ?3 AND client_order_id = ?4
     "#;
     let market_type_str = market_type.as_str().to_string();
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &symbol, &client_order_id];
+    let params: Vec<&dyn rusqlite::ToSql> =
+        vec![&market_type_str, &account_id, &symbol, &client_order_id];
 
-    let result =
-        db.execute_query(&query, &params)
-            .map_err(|e| PlatformError::DataManagerError {
-                message: format!("get orders by client id err: {}", e),
-            })?;
-
-    let orders: Vec<Order> = result
-        .into_struct()
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("get_order_by_client_id into err: {}", e),
+    let result = db
+        .execute_query(&query, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "get orders by client id err".to_string(),
+            source: e,
         })?;
+
+    let orders: Vec<Order> = result.into_struct().map_err(|e| PlatformError::DbError {
+        context: "get_order_by_client_id into err".to_string(),
+        source: e,
+    })?;
     if orders.is_empty() {
         return Ok(None);
     }
@@ -971,60 +1502,67 @@ pub fn get_order_by_client_id(
 pub fn get_order_by_id(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     symbol: &str,
     order_id: &str,
 ) -> Result<Option<Order>> {
     let query = r#"
         SELECT symbol, order_id, client_order_id, order_side, order_type,
                order_status, order_price, order_quantity, executed_qty,
-               cummulative_quote_qty, time_in_force, stop_price, iceberg_qty,
+               cummulative_quote_qty, time_in_force, stop_price, iceberg_qty, quote_order_qty,
                create_time, update_time
         FROM orders
-        WHERE market_type = ?1 AND symbol = ?2 AND order_id = ?3
+        WHERE market_type = ?1 AND account_id = ?2 AND symbol = ?3 AND order_id = ?4
     "#;
     let market_type_str = market_type.as_str().to_string();
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &symbol, &order_id];
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &account_id, &symbol, &order_id];
 
-    let result =
-        db.execute_query(&query, &params)
-            .map_err(|e| PlatformError::DataManagerError {
-                message: format!("get orders by id err: {}", e),
-            })?;
-
-    let orders: Vec<Order> = result
-        .into_struct()
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("get_order_by_id into err: {}", e),
+    let result = db
+        .execute_query(&query, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "get orders by id err".to_string(),
+            source: e,
         })?;
+
+    let orders: Vec<Order> = result.into_struct().map_err(|e| PlatformError::DbError {
+        context: "get_order_by_id into err".to_string(),
+        source: e,
+    })?;
     if orders.is_empty() {
         return Ok(None);
     }
     Ok(Some(orders[0].clone()))
 }
 
-pub fn get_open_orders(db: Arc<SQLiteDB>, market_type: &MarketType) -> Result<Vec<Order>> {
+pub fn get_open_orders(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    account_id: &str,
+) -> Result<Vec<Order>> {
     let query = r#"
         SELECT symbol, order_id, client_order_id, order_side, order_type,
                order_status, order_price, order_quantity, executed_qty,
-               cummulative_quote_qty, time_in_force, stop_price, iceberg_qty,
+               cummulative_quote_qty, time_in_force, stop_price, iceberg_qty, quote_order_qty,
                create_time, update_time
         FROM orders
-        WHERE market_type = ?1 AND order_status IN ('NEW', 'PENDING_NEW', 'PARTIALLY_FILLED')
+        WHERE market_type = ?1 AND account_id = ?2 AND order_status IN ('NEW', 'PENDING_NEW', 'PARTIALLY_FILLED')
         ORDER BY update_time DESC
     "#;
     let market_type_str = market_type.as_str().to_string();
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str];
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &account_id];
 
     let result = db
         .execute_query(query, &params)
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("get open orders err: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "get open orders err".to_string(),
+            source: e,
         })?;
 
     result
         .into_struct()
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("get_open_orders into err: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "get_open_orders into err".to_string(),
+            source: e,
         })
         .map(|mut v| {
             v.sort_by(|a: &Order, b: &Order| a.update_time.cmp(&b.update_time));
@@ -1035,6 +1573,7 @@ pub fn get_open_orders(db: Arc<SQLiteDB>, market_type: &MarketType) -> Result<Ve
 pub fn get_user_trades(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     symbol: &str,
     start_time: Option<u64>,
     end_time: Option<u64>,
@@ -1049,25 +1588,27 @@ pub fn get_user_trades(
         SELECT trade_id, order_id, symbol, order_side, trade_price,
                trade_quantity, commission, commission_asset, is_maker, timestamp
         FROM user_trades
-        WHERE market_type = ?1 AND symbol = ?2 and timestamp >= {} AND timestamp <= {}
+        WHERE market_type = ?1 AND account_id = ?2 AND symbol = ?3 and timestamp >= {} AND timestamp <= {}
         ORDER BY timestamp {}
         LIMIT {}
     "#,
         start_time, end_time, order_direction, limit
     );
     let market_type_str = market_type.as_str().to_string();
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &symbol];
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &account_id, &symbol];
 
-    let result =
-        db.execute_query(&query, &params)
-            .map_err(|e| PlatformError::DataManagerError {
-                message: format!("get user trades err: {}", e),
-            })?;
+    let result = db
+        .execute_query(&query, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "get user trades err".to_string(),
+            source: e,
+        })?;
 
     result
         .into_struct()
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("get_user_trades into err: {}", e),
+        .map_err(|e| PlatformError::DbError {
+            context: "get_user_trades into err".to_string(),
+            source: e,
         })
         .map(|mut v| {
             v.sort_by(|a: &UserTrade, b: &UserTrade| a.timestamp.cmp(&b.timestamp));
@@ -1075,9 +1616,34 @@ pub fn get_user_trades(
         })
 }
 
+// 按订单查成交的索引，包含timestamp使排序也由索引完成；
+// 旧索引不含timestamp，查询计划会改用(symbol, timestamp)索引扫描该symbol的全部成交
+pub fn create_user_trades_order_index(db: Arc<SQLiteDB>) -> Result<()> {
+    db.execute_update(
+        "DROP INDEX IF EXISTS idx_user_trades_market_type_order_id",
+        &[],
+    )
+    .map_err(|e| PlatformError::DbError {
+        context: "drop user_trades order_id index failed".to_string(),
+        source: e,
+    })?;
+    let index = r#"
+        CREATE INDEX IF NOT EXISTS idx_user_trades_symbol_order_id_timestamp
+        ON user_trades(market_type, account_id, symbol, order_id, timestamp)
+    "#;
+    db.execute_update(index, &[])
+        .map_err(|e| PlatformError::DbError {
+            context: "create user_trades (market_type, symbol, order_id, timestamp) index failed"
+                .to_string(),
+            source: e,
+        })?;
+    Ok(())
+}
+
 pub fn get_user_trades_by_order(
     db: Arc<SQLiteDB>,
     market_type: &MarketType,
+    account_id: &str,
     symbol: &str,
     order_id: &str,
 ) -> Result<Vec<UserTrade>> {
@@ -1085,43 +1651,44 @@ pub fn get_user_trades_by_order(
         SELECT trade_id, order_id, symbol, order_side, trade_price,
                trade_quantity, commission, commission_asset, is_maker, timestamp
         FROM user_trades
-        WHERE market_type = ?1 AND symbol = ?2 AND order_id = ?3
-        ORDER BY timestamp DESC
+        WHERE market_type = ?1 AND account_id = ?2 AND symbol = ?3 AND order_id = ?4
+        ORDER BY timestamp ASC
     "#;
     let market_type_str = market_type.as_str().to_string();
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &symbol, &order_id];
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &account_id, &symbol, &order_id];
 
-    let result =
-        db.execute_query(&query, &params)
-            .map_err(|e| PlatformError::DataManagerError {
-                message: format!("get user trades by order_id err: {}", e),
-            })?;
+    let result = db
+        .execute_query(&query, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "get user trades by order_id err".to_string(),
+            source: e,
+        })?;
 
-    result
-        .into_struct()
-        .map_err(|e| PlatformError::DataManagerError {
-            message: format!("get_user_trades_by_order_ids into err: {}", e),
-        })
-        .map(|mut v| {
-            v.sort_by(|a: &UserTrade, b: &UserTrade| a.timestamp.cmp(&b.timestamp));
-            v
-        })
+    result.into_struct().map_err(|e| PlatformError::DbError {
+        context: "get_user_trades_by_order_ids into err".to_string(),
+        source: e,
+    })
 }
 
-pub fn get_all_symbol(db: Arc<SQLiteDB>, market_type: &MarketType) -> Result<Vec<String>> {
+pub fn get_all_symbol(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    account_id: &str,
+) -> Result<Vec<String>> {
     let query = r#"
         SELECT DISTINCT symbol
         FROM orders
-        WHERE market_type = ?1
+        WHERE market_type = ?1 AND account_id = ?2
     "#;
     let market_type_str = market_type.as_str().to_string();
-    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str];
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&market_type_str, &account_id];
 
-    let result =
-        db.execute_query(&query, &params)
-            .map_err(|e| PlatformError::DataManagerError {
-                message: format!("get all symbols err: {}", e),
-            })?;
+    let result = db
+        .execute_query(&query, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "get all symbols err".to_string(),
+            source: e,
+        })?;
 
     let mut symbols = vec![];
     for row in result.rows {
@@ -1134,3 +1701,69 @@ pub fn get_all_symbol(db: Arc<SQLiteDB>, market_type: &MarketType) -> Result<Vec
     }
     Ok(symbols)
 }
+
+pub fn create_factor_state_table(db: Arc<SQLiteDB>) -> Result<()> {
+    let query = r#"
+        CREATE TABLE IF NOT EXISTS factor_state (
+            state_key TEXT NOT NULL PRIMARY KEY,
+            state TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        )
+    "#;
+    db.execute_update(query, &[])
+        .map_err(|e| PlatformError::DbError {
+            context: "create factor_state table failed".to_string(),
+            source: e,
+        })?;
+    Ok(())
+}
+
+// state为Codec编码后的字节，旧版本以文本写入的json同样可读
+pub fn get_factor_state(db: Arc<SQLiteDB>, state_key: &str) -> Result<Option<Vec<u8>>> {
+    let query = r#"
+        SELECT state
+        FROM factor_state
+        WHERE state_key = ?1
+    "#;
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&state_key];
+
+    let result = db
+        .execute_query(query, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "get factor state err".to_string(),
+            source: e,
+        })?;
+
+    match result.rows.first() {
+        None => Ok(None),
+        Some(row) => Ok(Some(row.get_bytes("state").ok_or(
+            PlatformError::DataManagerError {
+                message: "column state not found".to_string(),
+            },
+        )?)),
+    }
+}
+
+pub fn update_factor_state(
+    db: Arc<SQLiteDB>,
+    state_key: &str,
+    state: &[u8],
+    updated_at: u64,
+) -> Result<()> {
+    let query = r#"
+        INSERT INTO factor_state (state_key, state, updated_at)
+        VALUES (?1, ?2, ?3)
+        ON CONFLICT(state_key) DO UPDATE SET
+            state = excluded.state,
+            updated_at = excluded.updated_at
+    "#;
+    let updated_at_i64 = updated_at as i64;
+    let params: Vec<&dyn rusqlite::ToSql> = vec![&state_key, &state, &updated_at_i64];
+
+    db.execute_update(query, &params)
+        .map_err(|e| PlatformError::DbError {
+            context: "update factor state err".to_string(),
+            source: e,
+        })?;
+    Ok(())
+}
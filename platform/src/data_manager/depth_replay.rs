@@ -0,0 +1,135 @@
+use crate::{
+    data_manager::db::{get_depth_diffs, get_depth_snapshot_before},
+    errors::{PlatformError, Result},
+    models::{DepthData, DepthDiff, DepthSnapshot, MarketType, PriceLevel, ReplayedDepth},
+};
+use db::sqlite::SQLiteDB;
+use rust_decimal::Decimal;
+use std::{collections::BTreeMap, sync::Arc};
+
+// 每次从db加载的增量条数
+const DEPTH_DIFF_PAGE_LIMIT: u64 = 1000;
+
+struct DepthBook {
+    symbol: String,
+    last_update_id: u64,
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    timestamp: u64,
+}
+
+impl DepthBook {
+    fn from_snapshot(snapshot: &DepthSnapshot) -> Self {
+        Self {
+            symbol: snapshot.symbol.clone(),
+            last_update_id: snapshot.last_update_id,
+            bids: snapshot
+                .bids
+                .iter()
+                .map(|l| (l.price, l.quantity))
+                .collect(),
+            asks: snapshot
+                .asks
+                .iter()
+                .map(|l| (l.price, l.quantity))
+                .collect(),
+            timestamp: snapshot.timestamp,
+        }
+    }
+
+    fn apply_levels(side: &mut BTreeMap<Decimal, Decimal>, levels: &[PriceLevel]) {
+        for level in levels {
+            if level.quantity.is_zero() {
+                side.remove(&level.price);
+            } else {
+                side.insert(level.price, level.quantity);
+            }
+        }
+    }
+
+    // 增量与当前盘口不连续时返回false
+    fn apply(&mut self, diff: &DepthDiff) -> bool {
+        if diff.last_update_id <= self.last_update_id {
+            return true;
+        }
+        if diff.first_update_id > self.last_update_id + 1 {
+            return false;
+        }
+        Self::apply_levels(&mut self.bids, &diff.bids);
+        Self::apply_levels(&mut self.asks, &diff.asks);
+        self.last_update_id = diff.last_update_id;
+        self.timestamp = diff.timestamp;
+        true
+    }
+
+    fn depth(&self) -> DepthData {
+        let level = |(price, quantity): (&Decimal, &Decimal)| PriceLevel {
+            price: *price,
+            quantity: *quantity,
+        };
+        DepthData {
+            symbol: self.symbol.clone(),
+            bids: self.bids.iter().rev().map(level).collect(),
+            asks: self.asks.iter().map(level).collect(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+// 重建cur_ts时刻的盘口：加载不晚于cur_ts的最近快照，并回放其后timestamp <= cur_ts的增量
+// 增量出现缺口时无法得到准确盘口，返回快照本身并标记stale
+pub fn replay_depth_at(
+    db: Arc<SQLiteDB>,
+    market_type: &MarketType,
+    symbol: &str,
+    cur_ts: u64,
+) -> Result<ReplayedDepth> {
+    let snapshot = get_depth_snapshot_before(db.clone(), market_type, symbol, cur_ts)?.ok_or(
+        PlatformError::DataManagerError {
+            message: format!(
+                "no depth snapshot for {} {} before {}",
+                market_type.as_str(),
+                symbol,
+                cur_ts
+            ),
+        },
+    )?;
+
+    let mut book = DepthBook::from_snapshot(&snapshot);
+    loop {
+        let diffs = get_depth_diffs(
+            db.clone(),
+            market_type,
+            symbol,
+            book.last_update_id,
+            cur_ts,
+            Some(DEPTH_DIFF_PAGE_LIMIT),
+        )?;
+        for diff in diffs.iter() {
+            if !book.apply(diff) {
+                log::warn!(
+                    "depth diff gap for {} {} at update id {}, next diff first id {}",
+                    market_type.as_str(),
+                    symbol,
+                    book.last_update_id,
+                    diff.first_update_id
+                );
+                let snapshot_book = DepthBook::from_snapshot(&snapshot);
+                return Ok(ReplayedDepth {
+                    depth: snapshot_book.depth(),
+                    last_update_id: snapshot.last_update_id,
+                    stale: true,
+                });
+            }
+        }
+        if (diffs.len() as u64) < DEPTH_DIFF_PAGE_LIMIT {
+            break;
+        }
+    }
+
+    Ok(ReplayedDepth {
+        depth: book.depth(),
+        last_update_id: book.last_update_id,
+        stale: false,
+    })
+}
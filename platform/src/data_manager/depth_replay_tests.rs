@@ -0,0 +1,190 @@
+use crate::{
+    data_manager::{db::*, depth_replay::replay_depth_at},
+    models::{DepthDiff, DepthSnapshot, MarketType, PriceLevel},
+};
+use db::sqlite::SQLiteDB;
+use rust_decimal::Decimal;
+use std::{collections::HashMap, sync::Arc};
+use tempfile::NamedTempFile;
+
+const SYMBOL: &str = "BTCUSDT";
+
+fn new_db(file: &NamedTempFile) -> Arc<SQLiteDB> {
+    let db = Arc::new(SQLiteDB::new(file.path().to_str().unwrap()).unwrap());
+    create_depth_tables(db.clone()).unwrap();
+    db
+}
+
+fn level(price: u64, quantity: u64) -> PriceLevel {
+    PriceLevel {
+        price: Decimal::from(price),
+        quantity: Decimal::from(quantity),
+    }
+}
+
+// 逐条应用增量并记录每一时刻的盘口，作为回放结果的对照
+struct RecordedBook {
+    bids: HashMap<Decimal, Decimal>,
+    asks: HashMap<Decimal, Decimal>,
+    last_update_id: u64,
+    timestamp: u64,
+}
+
+impl RecordedBook {
+    fn apply(&mut self, diff: &DepthDiff) {
+        for (side, levels) in [(&mut self.bids, &diff.bids), (&mut self.asks, &diff.asks)] {
+            for l in levels {
+                if l.quantity.is_zero() {
+                    side.remove(&l.price);
+                } else {
+                    side.insert(l.price, l.quantity);
+                }
+            }
+        }
+        self.last_update_id = diff.last_update_id;
+        self.timestamp = diff.timestamp;
+    }
+
+    fn snapshot(&self) -> DepthSnapshot {
+        let mut bids: Vec<PriceLevel> = self
+            .bids
+            .iter()
+            .map(|(p, q)| PriceLevel {
+                price: *p,
+                quantity: *q,
+            })
+            .collect();
+        bids.sort_by(|a, b| b.price.cmp(&a.price));
+        let mut asks: Vec<PriceLevel> = self
+            .asks
+            .iter()
+            .map(|(p, q)| PriceLevel {
+                price: *p,
+                quantity: *q,
+            })
+            .collect();
+        asks.sort_by(|a, b| a.price.cmp(&b.price));
+        DepthSnapshot {
+            symbol: SYMBOL.to_string(),
+            last_update_id: self.last_update_id,
+            bids,
+            asks,
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+// 第i条增量覆盖update id [10i+1, 10i+10]，timestamp为1000 + 100i
+fn diff(i: u64) -> DepthDiff {
+    DepthDiff {
+        symbol: SYMBOL.to_string(),
+        first_update_id: 10 * i + 1,
+        last_update_id: 10 * i + 10,
+        bids: vec![level(100 - i % 5, i + 1), level(90 + i % 3, i % 2)],
+        asks: vec![level(101 + i % 5, i + 2), level(110 - i % 3, i % 2)],
+        timestamp: 1000 + 100 * i,
+    }
+}
+
+fn assert_same_book(actual: &crate::models::DepthData, expected: &DepthSnapshot) {
+    let prices = |levels: &[PriceLevel]| {
+        levels
+            .iter()
+            .map(|l| (l.price, l.quantity))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(prices(&actual.bids), prices(&expected.bids));
+    assert_eq!(prices(&actual.asks), prices(&expected.asks));
+    assert_eq!(actual.timestamp, expected.timestamp);
+}
+
+#[test]
+fn test_replay_matches_recorded_book() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    let market_type = MarketType::BinanceSpot;
+
+    let mut book = RecordedBook {
+        bids: HashMap::from([(Decimal::from(99), Decimal::ONE)]),
+        asks: HashMap::from([(Decimal::from(102), Decimal::ONE)]),
+        last_update_id: 0,
+        timestamp: 900,
+    };
+    // 每条增量应用后记录的真实盘口
+    let mut ground_truth = vec![book.snapshot()];
+    update_depth_snapshot(db.clone(), &market_type, &book.snapshot()).unwrap();
+    let diffs: Vec<DepthDiff> = (0..40).map(diff).collect();
+    for d in diffs.iter() {
+        book.apply(d);
+        ground_truth.push(book.snapshot());
+        // 每10条增量保存一次快照
+        if d.last_update_id % 100 == 0 {
+            update_depth_snapshot(db.clone(), &market_type, &book.snapshot()).unwrap();
+        }
+    }
+    update_depth_diffs(db.clone(), &market_type, &diffs).unwrap();
+
+    for cur_ts in [900, 950, 1000, 1450, 1900, 2000, 2050, 3333, 4900, 9999] {
+        let replayed = replay_depth_at(db.clone(), &market_type, SYMBOL, cur_ts).unwrap();
+        let expected = ground_truth
+            .iter()
+            .rev()
+            .find(|s| s.timestamp <= cur_ts)
+            .unwrap();
+        assert!(!replayed.stale, "cur_ts {}", cur_ts);
+        assert_eq!(replayed.last_update_id, expected.last_update_id);
+        assert_same_book(&replayed.depth, expected);
+    }
+
+    // 早于所有快照
+    assert!(replay_depth_at(db.clone(), &market_type, SYMBOL, 899).is_err());
+    assert!(replay_depth_at(db.clone(), &market_type, "ETHUSDT", 9999).is_err());
+}
+
+#[test]
+fn test_replay_with_missing_diffs_returns_stale_snapshot() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    let market_type = MarketType::BinanceSpot;
+
+    let mut book = RecordedBook {
+        bids: HashMap::from([(Decimal::from(99), Decimal::ONE)]),
+        asks: HashMap::from([(Decimal::from(102), Decimal::ONE)]),
+        last_update_id: 0,
+        timestamp: 900,
+    };
+    let snapshot = book.snapshot();
+    update_depth_snapshot(db.clone(), &market_type, &snapshot).unwrap();
+    // 缺失第3条增量
+    let diffs: Vec<DepthDiff> = (0..6).filter(|i| *i != 3).map(diff).collect();
+    update_depth_diffs(db.clone(), &market_type, &diffs).unwrap();
+
+    // 缺口之前仍可准确回放
+    for d in diffs.iter().take(3) {
+        book.apply(d);
+    }
+    let replayed = replay_depth_at(db.clone(), &market_type, SYMBOL, 1200).unwrap();
+    assert!(!replayed.stale);
+    assert_same_book(&replayed.depth, &book.snapshot());
+
+    // 跨过缺口返回快照并标记stale
+    let replayed = replay_depth_at(db.clone(), &market_type, SYMBOL, 1500).unwrap();
+    assert!(replayed.stale);
+    assert_eq!(replayed.last_update_id, snapshot.last_update_id);
+    assert_same_book(&replayed.depth, &snapshot);
+
+    // 缺口之后的新快照可恢复准确回放
+    let mut later = RecordedBook {
+        bids: HashMap::from([(Decimal::from(95), Decimal::TWO)]),
+        asks: HashMap::from([(Decimal::from(105), Decimal::TWO)]),
+        last_update_id: 40,
+        timestamp: 1350,
+    };
+    update_depth_snapshot(db.clone(), &market_type, &later.snapshot()).unwrap();
+    later.apply(&diff(4));
+    later.apply(&diff(5));
+    let replayed = replay_depth_at(db.clone(), &market_type, SYMBOL, 1500).unwrap();
+    assert!(!replayed.stale);
+    assert_eq!(replayed.last_update_id, 60);
+    assert_same_book(&replayed.depth, &later.snapshot());
+}
@@ -0,0 +1,42 @@
+use crate::{errors::PlatformError, models::MarketType};
+
+#[derive(Debug, Clone)]
+pub struct SymbolInitFailure {
+    pub market_type: MarketType,
+    pub symbol: String,
+    pub error: String,
+}
+
+// 非严格模式下初始化失败的symbol，其余symbol正常初始化
+#[derive(Debug, Clone, Default)]
+pub struct SymbolInitReport {
+    pub failures: Vec<SymbolInitFailure>,
+}
+
+impl SymbolInitReport {
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn add(&mut self, market_type: &MarketType, symbol: &str, error: &PlatformError) {
+        log::error!(
+            "skip symbol {:?} {} due to init failure: {}",
+            market_type,
+            symbol,
+            error
+        );
+        self.failures.push(SymbolInitFailure {
+            market_type: market_type.clone(),
+            symbol: symbol.to_string(),
+            error: error.to_string(),
+        });
+    }
+
+    pub fn failed_symbols(&self, market_type: &MarketType) -> Vec<String> {
+        self.failures
+            .iter()
+            .filter(|f| &f.market_type == market_type)
+            .map(|f| f.symbol.clone())
+            .collect()
+    }
+}
@@ -1,45 +1,113 @@
 use crate::{
-    config::PlatformConfig,
-    data_manager::{db::*, MarketDataManager, TradeDataManager},
+    config::{BacktestConfig, FeeSchedule, PlatformConfig},
+    data_manager::{
+        db::*, position_manager::PositionManager, MarketDataManager, SymbolInitReport,
+        TradeDataManager,
+    },
     errors::{PlatformError, Result},
     models::{
-        Account, Balance, CancelOrderRequest, DepthData, KlineData, KlineInterval, MarketType,
-        Order, OrderSide, OrderStatus, OrderType, PlaceOrderRequest, SymbolInfo, Ticker24hr, Trade,
-        UserTrade,
+        Account, AmendOrderRequest, Balance, CancelOrderRequest, DepthData, KlineData,
+        KlineInterval, MarketType, Order, OrderSide, OrderStatus, OrderType, PlaceOrderRequest,
+        SymbolInfo, SymbolStatus, Ticker24hr, TimeInForce, Trade, UserTrade,
     },
 };
 use async_trait::async_trait;
 use db::sqlite::SQLiteDB;
+use futures_util::{stream, StreamExt, TryStreamExt};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use rust_decimal::{prelude::FromPrimitive, Decimal};
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc,
+        Arc, Mutex, Weak,
     },
 };
-use tokio::sync::RwLock;
+use time::{noop_metrics, Metrics};
+use tokio::sync::{RwLock, RwLockWriteGuard};
+
+// 时钟推进回调，例如推进后触发模拟撮合
+#[async_trait]
+pub trait ClockHook: Send + Sync {
+    async fn on_clock_advance(&self, cur_ts: u64) -> Result<()>;
+}
 
+// 回测时手动推进；live模式下跟随系统时间，用于实盘复用本地数据管理器
 pub struct Clock {
-    cur_ts: AtomicU64, // 毫秒时间戳
+    cur_ts: AtomicU64, // 毫秒时间戳，live模式下为最近一次返回的时间
+    live: bool,
+    hooks: Mutex<Vec<Weak<dyn ClockHook>>>, // 弱引用，避免hook持有clock时循环引用
 }
 
 impl Clock {
     pub fn new(cur_ts: u64) -> Self {
         Self {
             cur_ts: AtomicU64::new(cur_ts),
+            live: false,
+            hooks: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn new_live() -> Self {
+        Self {
+            cur_ts: AtomicU64::new(time::get_current_milli_timestamp()),
+            live: true,
+            hooks: Mutex::new(Vec::new()),
         }
     }
 
+    pub fn is_live(&self) -> bool {
+        self.live
+    }
+
+    // live模式下忽略
     pub fn set_cur_ts(&self, cur_ts: u64) {
+        if self.live {
+            log::warn!("set_cur_ts({}) ignored on live clock", cur_ts);
+            return;
+        }
         self.cur_ts.store(cur_ts, Ordering::Release);
     }
 
+    // 按注册顺序在 advance_to 时调用
+    pub fn register_hook(&self, hook: Arc<dyn ClockHook>) {
+        self.hooks.lock().unwrap().push(Arc::downgrade(&hook));
+    }
+
+    // 推进时钟并依次调用hook，任一hook失败即返回错误
+    pub async fn advance_to(&self, cur_ts: u64) -> Result<()> {
+        self.set_cur_ts(cur_ts);
+        let hooks = {
+            let mut hooks = self.hooks.lock().unwrap();
+            hooks.retain(|hook| hook.strong_count() > 0);
+            hooks
+                .iter()
+                .filter_map(|hook| hook.upgrade())
+                .collect::<Vec<_>>()
+        };
+        let cur_ts = self.cur_ts();
+        for hook in hooks {
+            hook.on_clock_advance(cur_ts).await?;
+        }
+        Ok(())
+    }
+
     pub fn cur_ts(&self) -> u64 {
+        if self.live {
+            // 系统时间回拨时保持单调不减
+            let now = time::get_current_milli_timestamp();
+            let prev = self.cur_ts.fetch_max(now, Ordering::AcqRel);
+            return prev.max(now);
+        }
         self.cur_ts.load(Ordering::Acquire)
     }
 }
 
+// init时并发加载缓存的上限
+pub(crate) const INIT_LOAD_CONCURRENCY: usize = 8;
+// 首次加载trade时cur_ts之前预热的成交笔数（按seq_id计）
+const TRADE_WARMUP_SIZE: u64 = 1000;
+
 pub struct LocalMarketDataManager {
     clock: Arc<Clock>,
 
@@ -48,10 +116,14 @@ pub struct LocalMarketDataManager {
 
     symbol_infos: Arc<HashMap<MarketType, HashMap<String, SymbolInfo>>>,
     base_quote_symbols: Arc<HashMap<MarketType, HashMap<(String, String), String>>>,
+    active_symbols: Arc<HashMap<MarketType, Vec<String>>>, // 可交易状态的symbol，按名称排序
 
     cache_capacities: Arc<HashMap<MarketType, usize>>,
+    kline_cache_capacities: Arc<HashMap<(MarketType, String, KlineInterval), usize>>, // 配置覆盖的kline缓存容量
     klines: Arc<HashMap<(MarketType, String, KlineInterval), Arc<RwLock<VecDeque<KlineData>>>>>,
     trades: Arc<HashMap<(MarketType, String), Arc<RwLock<VecDeque<Trade>>>>>,
+
+    init_report: SymbolInitReport,
 }
 
 impl LocalMarketDataManager {
@@ -62,31 +134,32 @@ impl LocalMarketDataManager {
         max_cache_size: usize,
     ) -> Result<Self> {
         let mut cache_capacities = HashMap::new();
+        let mut kline_cache_capacities = HashMap::new();
         let mut klines = HashMap::new();
         let mut trades = HashMap::new();
         let mut symbol_infos = HashMap::new();
         let mut base_quote_symbols = HashMap::new();
+        let mut active_symbols = HashMap::new();
+        let mut init_report = SymbolInitReport::default();
         for market_type in config.markets.iter() {
             let market_config = config.configs.get(market_type).unwrap();
             cache_capacities.insert(market_type.clone(), market_config.cache_capacity);
             for symbol in market_config.subscribed_symbols.iter() {
-                for interval in market_config.subscribed_kline_intervals.iter() {
-                    klines.insert(
-                        (market_type.clone(), symbol.clone(), interval.clone()),
-                        Arc::new(RwLock::new(VecDeque::with_capacity(max_cache_size))),
-                    );
-                }
-                trades.insert(
-                    (market_type.clone(), symbol.clone()),
-                    Arc::new(RwLock::new(VecDeque::with_capacity(max_cache_size))),
-                );
-                let symbol_info = match get_symbol_info(db.clone(), market_type, symbol)? {
-                    None => {
-                        return Err(PlatformError::PlatformError {
-                            message: format!("symbol info: {} not found in db", symbol),
-                        });
+                // 非严格模式下跳过缺少symbol_info的symbol
+                let symbol_info =
+                    get_symbol_info(db.clone(), market_type, symbol).and_then(|info| {
+                        info.ok_or_else(|| PlatformError::SymbolNotFound {
+                            market_type: market_type.clone(),
+                            symbol: symbol.clone(),
+                        })
+                    });
+                let symbol_info = match symbol_info {
+                    Ok(symbol_info) => symbol_info,
+                    Err(e) if market_config.strict_symbol_init => return Err(e),
+                    Err(e) => {
+                        init_report.add(market_type, symbol, &e);
+                        continue;
                     }
-                    Some(symbol_info) => symbol_info,
                 };
                 symbol_infos
                     .entry(market_type.clone())
@@ -102,19 +175,146 @@ impl LocalMarketDataManager {
                         ),
                         symbol.clone(),
                     );
+                // 非可交易状态的symbol保留symbol_info供下单校验，不创建行情缓存
+                if !market_config.is_active_symbol_status(&symbol_info.status) {
+                    log::info!(
+                        "skip loading {:?} {} with status {:?}",
+                        market_type,
+                        symbol,
+                        symbol_info.status
+                    );
+                    continue;
+                }
+                active_symbols
+                    .entry(market_type.clone())
+                    .or_insert_with(Vec::new)
+                    .push(symbol.clone());
+                for interval in market_config.subscribed_kline_intervals.iter() {
+                    let key = (market_type.clone(), symbol.clone(), interval.clone());
+                    let capacity = match market_config.kline_cache_capacity(symbol, interval) {
+                        Some(capacity) => {
+                            kline_cache_capacities.insert(key.clone(), capacity);
+                            capacity
+                        }
+                        None => max_cache_size,
+                    };
+                    klines.insert(
+                        key,
+                        Arc::new(RwLock::new(VecDeque::with_capacity(capacity))),
+                    );
+                }
+                trades.insert(
+                    (market_type.clone(), symbol.clone()),
+                    Arc::new(RwLock::new(VecDeque::with_capacity(max_cache_size))),
+                );
             }
         }
+        for symbols in active_symbols.values_mut() {
+            symbols.sort();
+        }
 
         Ok(Self {
             clock,
             db,
             max_cache_size,
             cache_capacities: Arc::new(cache_capacities),
+            kline_cache_capacities: Arc::new(kline_cache_capacities),
             klines: Arc::new(klines),
             trades: Arc::new(trades),
             symbol_infos: Arc::new(symbol_infos),
             base_quote_symbols: Arc::new(base_quote_symbols),
+            active_symbols: Arc::new(active_symbols),
+            init_report,
+        })
+    }
+
+    // 共享db与symbol配置、使用独立时钟和空缓存的副本，供多个回测任务各自推进时间
+    // 缓存按时间单向加载，不同时钟的任务不能共用
+    pub fn with_clock(&self, clock: Arc<Clock>) -> Self {
+        let klines = self
+            .klines
+            .keys()
+            .map(|key| {
+                let capacity = self
+                    .kline_cache_capacities
+                    .get(key)
+                    .copied()
+                    .unwrap_or(self.max_cache_size);
+                (
+                    key.clone(),
+                    Arc::new(RwLock::new(VecDeque::with_capacity(capacity))),
+                )
+            })
+            .collect();
+        let trades = self
+            .trades
+            .keys()
+            .map(|key| {
+                (
+                    key.clone(),
+                    Arc::new(RwLock::new(VecDeque::with_capacity(self.max_cache_size))),
+                )
+            })
+            .collect();
+        Self {
+            clock,
+            db: self.db.clone(),
+            max_cache_size: self.max_cache_size,
+            symbol_infos: self.symbol_infos.clone(),
+            base_quote_symbols: self.base_quote_symbols.clone(),
+            active_symbols: self.active_symbols.clone(),
+            cache_capacities: self.cache_capacities.clone(),
+            kline_cache_capacities: self.kline_cache_capacities.clone(),
+            klines: Arc::new(klines),
+            trades: Arc::new(trades),
+            init_report: self.init_report.clone(),
+        }
+    }
+
+    // 直接查询db，不受clock限制，供回测预先生成事件时间轴
+    pub async fn kline_close_times(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+        interval: &KlineInterval,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<u64>> {
+        let (db, mt, sym, itv) = (
+            self.db.clone(),
+            market_type.clone(),
+            symbol.to_string(),
+            interval.clone(),
+        );
+        tokio::task::spawn_blocking(move || {
+            get_kline_close_times(db, &mt, &sym, &itv, start_time, end_time)
         })
+        .await
+        .map_err(|e| PlatformError::PlatformError {
+            message: format!("get kline close times join err: {}", e),
+        })?
+    }
+
+    pub async fn trade_timestamps(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<u64>> {
+        let (db, mt, sym) = (self.db.clone(), market_type.clone(), symbol.to_string());
+        tokio::task::spawn_blocking(move || {
+            get_trade_timestamps(db, &mt, &sym, start_time, end_time)
+        })
+        .await
+        .map_err(|e| PlatformError::PlatformError {
+            message: format!("get trade timestamps join err: {}", e),
+        })?
+    }
+
+    // 非严格模式下跳过的symbol及原因
+    pub fn init_report(&self) -> &SymbolInitReport {
+        &self.init_report
     }
 
     async fn load_klines(
@@ -130,16 +330,19 @@ impl LocalMarketDataManager {
             .get(&(market_type.clone(), symbol.clone(), interval.clone()))
         {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!(
-                        "kline cache not found for {:?}, {}, {:?}",
-                        market_type, symbol, interval
-                    ),
+                return Err(PlatformError::CacheMiss {
+                    cache: "kline".to_string(),
+                    key: format!("{:?}, {}, {:?}", market_type, symbol, interval),
                 });
             }
             Some(cache) => cache,
         };
 
+        let max_cache_size = self
+            .kline_cache_capacities
+            .get(&(market_type.clone(), symbol.clone(), interval.clone()))
+            .copied()
+            .unwrap_or(self.max_cache_size);
         let mut klines = cache.write().await;
 
         loop {
@@ -151,30 +354,33 @@ impl LocalMarketDataManager {
             } else {
                 None
             };
-            let end_time = if start_time.is_none() {
-                Some(cur_ts)
+            // 首次加载按缓存容量取最近的数据
+            let (end_time, limit) = if start_time.is_none() {
+                (Some(cur_ts), Some(max_cache_size as u64))
             } else {
-                None
+                (None, None)
             };
-            let db_klines = get_klines(
+            // db为同步查询，放到blocking线程避免阻塞其他加载任务
+            let (db, mt, sym, itv) = (
                 self.db.clone(),
-                market_type,
-                symbol,
-                interval,
-                start_time,
-                end_time,
-                None,
-            )
+                market_type.clone(),
+                symbol.clone(),
+                interval.clone(),
+            );
+            let db_klines = tokio::task::spawn_blocking(move || {
+                get_klines(db, &mt, &sym, &itv, start_time, end_time, limit)
+            })
+            .await
             .map_err(|e| PlatformError::PlatformError {
-                message: format!("get klines db err: {}", e),
-            })?;
+                message: format!("get klines join err: {}", e),
+            })??;
 
             if db_klines.is_empty() {
                 return Ok(());
             }
 
             for kline in db_klines.iter() {
-                if klines.len() > self.max_cache_size {
+                if klines.len() > max_cache_size {
                     klines.pop_front();
                 }
                 klines.push_back(kline.clone());
@@ -182,12 +388,25 @@ impl LocalMarketDataManager {
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn kline_cache(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+        interval: &KlineInterval,
+    ) -> Option<Arc<RwLock<VecDeque<KlineData>>>> {
+        self.klines
+            .get(&(market_type.clone(), symbol.to_string(), interval.clone()))
+            .cloned()
+    }
+
     async fn load_trades(&self, market_type: &MarketType, symbol: &String) -> Result<()> {
         let cur_ts = self.clock.cur_ts();
         let cache = match self.trades.get(&(market_type.clone(), symbol.clone())) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("trade cache not found for {:?}, {}", market_type, symbol),
+                return Err(PlatformError::CacheMiss {
+                    cache: "trade".to_string(),
+                    key: format!("{:?}, {}", market_type, symbol),
                 });
             }
             Some(cache) => cache,
@@ -195,32 +414,38 @@ impl LocalMarketDataManager {
 
         let mut trades = cache.write().await;
 
+        // 首次加载时按时间定位cur_ts前最后一笔成交，之后统一按seq_id游标加载
+        // 避免按时间窗口截断时同一时间戳的多笔成交被部分遗漏
+        let mut next_seq_id = match trades.back() {
+            Some(trade) => trade.seq_id + 1,
+            None => {
+                let (db, mt, sym) = (self.db.clone(), market_type.clone(), symbol.clone());
+                let last_trades = tokio::task::spawn_blocking(move || {
+                    get_trades(db, &mt, &sym, None, Some(cur_ts), None, Some(1))
+                })
+                .await
+                .map_err(|e| PlatformError::PlatformError {
+                    message: format!("get trades join err: {}", e),
+                })??;
+                match last_trades.last() {
+                    None => return Ok(()),
+                    Some(trade) => (trade.seq_id + 1).saturating_sub(TRADE_WARMUP_SIZE),
+                }
+            }
+        };
+
         loop {
             if !trades.is_empty() && trades.back().unwrap().timestamp > cur_ts {
                 return Ok(());
             }
-            let from_seq_id = if !trades.is_empty() {
-                Some(trades.back().unwrap().seq_id + 1)
-            } else {
-                None
-            };
-            let end_time = if from_seq_id.is_none() {
-                Some(cur_ts)
-            } else {
-                None
-            };
-            let db_trades = get_trades(
-                self.db.clone(),
-                market_type,
-                symbol,
-                None,
-                end_time,
-                from_seq_id,
-                None,
-            )
+            let (db, mt, sym) = (self.db.clone(), market_type.clone(), symbol.clone());
+            let db_trades = tokio::task::spawn_blocking(move || {
+                get_trades(db, &mt, &sym, None, None, Some(next_seq_id), None)
+            })
+            .await
             .map_err(|e| PlatformError::PlatformError {
-                message: format!("get trades db err: {}", e),
-            })?;
+                message: format!("get trades join err: {}", e),
+            })??;
 
             if db_trades.is_empty() {
                 return Ok(());
@@ -232,6 +457,7 @@ impl LocalMarketDataManager {
                 }
                 trades.push_back(trade.clone());
             }
+            next_seq_id = trades.back().unwrap().seq_id + 1;
             log::info!(
                 "Loaded {} trades for {:?} {}, last trade id: {}, cache size: {}",
                 db_trades.len(),
@@ -247,13 +473,26 @@ impl LocalMarketDataManager {
 #[async_trait]
 impl MarketDataManager for LocalMarketDataManager {
     async fn init(&self) -> Result<()> {
-        for (market_type, symbol, interval) in self.klines.keys() {
-            self.load_klines(market_type, symbol, interval).await?;
-        }
+        // 每个缓存只在自己的加载任务中持有写锁，任一任务失败即返回错误
+        let kline_tasks = self
+            .klines
+            .keys()
+            .map(|(market_type, symbol, interval)| self.load_klines(market_type, symbol, interval))
+            .collect::<Vec<_>>();
+        stream::iter(kline_tasks)
+            .buffer_unordered(INIT_LOAD_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
 
-        for (market_type, symbol) in self.trades.keys() {
-            self.load_trades(market_type, symbol).await?;
-        }
+        let trade_tasks = self
+            .trades
+            .keys()
+            .map(|(market_type, symbol)| self.load_trades(market_type, symbol))
+            .collect::<Vec<_>>();
+        stream::iter(trade_tasks)
+            .buffer_unordered(INIT_LOAD_CONCURRENCY)
+            .try_collect::<Vec<_>>()
+            .await?;
 
         Ok(())
     }
@@ -268,10 +507,15 @@ impl MarketDataManager for LocalMarketDataManager {
         // 获取数据
         self.load_klines(market_type, symbol, interval).await?;
 
-        let cache_capacity = match self.cache_capacities.get(market_type) {
+        let cache_capacity = match self
+            .kline_cache_capacities
+            .get(&(market_type.clone(), symbol.clone(), interval.clone()))
+            .or_else(|| self.cache_capacities.get(market_type))
+        {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("cache capacity not found for {:?}", market_type),
+                return Err(PlatformError::CacheMiss {
+                    cache: "cache capacity".to_string(),
+                    key: format!("{:?}", market_type),
                 });
             }
             Some(capacity) => *capacity,
@@ -284,11 +528,9 @@ impl MarketDataManager for LocalMarketDataManager {
             .get(&(market_type.clone(), symbol.clone(), interval.clone()))
         {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!(
-                        "kline cache not found for {:?}, {}, {:?}",
-                        market_type, symbol, interval
-                    ),
+                return Err(PlatformError::CacheMiss {
+                    cache: "kline".to_string(),
+                    key: format!("{:?}, {}, {:?}", market_type, symbol, interval),
                 });
             }
             Some(cache) => cache,
@@ -300,7 +542,7 @@ impl MarketDataManager for LocalMarketDataManager {
             .iter()
             .filter(|kline| kline.close_time <= cur_ts)
             .rev()
-            .take(limit.unwrap_or(cache_capacity))
+            .take(limit.unwrap_or(cache_capacity).min(cache_capacity))
             .cloned()
             .collect::<Vec<_>>();
         result.reverse();
@@ -308,7 +550,7 @@ impl MarketDataManager for LocalMarketDataManager {
         // 检测result按时间顺序排列
         for i in 1..result.len() {
             if result[i].open_time <= result[i - 1].open_time {
-                return Err(PlatformError::PlatformError {
+                return Err(PlatformError::ValidationError {
                     message: format!(
                         "klines not in order for {:?}, {}, {:?}",
                         market_type, symbol, interval
@@ -320,6 +562,45 @@ impl MarketDataManager for LocalMarketDataManager {
         Ok(result)
     }
 
+    // 直接查询db，不影响缓存；结果不晚于当前clock（close_time <= cur_ts）
+    async fn get_klines_range(
+        &self,
+        market_type: &MarketType,
+        symbol: &String,
+        interval: &KlineInterval,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<KlineData>> {
+        let cur_ts = self.clock.cur_ts();
+        let end_time = end_time.min(cur_ts);
+        let batch_size = 1000;
+
+        let mut result: Vec<KlineData> = vec![];
+        // start_time为0时db按倒序返回，这里从1开始保证升序分页
+        let mut cur_start_time = start_time.max(1);
+        while cur_start_time <= end_time {
+            let batch = get_klines(
+                self.db.clone(),
+                market_type,
+                symbol,
+                interval,
+                Some(cur_start_time),
+                Some(end_time),
+                Some(batch_size),
+            )?;
+            let batch_len = batch.len() as u64;
+            if let Some(last) = batch.last() {
+                cur_start_time = last.open_time + 1;
+            }
+            result.extend(batch.into_iter().filter(|k| k.close_time <= cur_ts));
+            if batch_len < batch_size {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
     async fn get_trades(
         &self,
         market_type: &MarketType,
@@ -331,8 +612,9 @@ impl MarketDataManager for LocalMarketDataManager {
 
         let cache_capacity = match self.cache_capacities.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("cache capacity not found for {:?}", market_type),
+                return Err(PlatformError::CacheMiss {
+                    cache: "cache capacity".to_string(),
+                    key: format!("{:?}", market_type),
                 });
             }
             Some(capacity) => *capacity,
@@ -342,8 +624,9 @@ impl MarketDataManager for LocalMarketDataManager {
 
         let cache = match self.trades.get(&(market_type.clone(), symbol.clone())) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("trade cache not found for {:?}, {}", market_type, symbol),
+                return Err(PlatformError::CacheMiss {
+                    cache: "trade".to_string(),
+                    key: format!("{:?}, {}", market_type, symbol),
                 });
             }
             Some(cache) => cache,
@@ -354,7 +637,7 @@ impl MarketDataManager for LocalMarketDataManager {
             .iter()
             .filter(|trade| trade.timestamp <= cur_ts)
             .rev()
-            .take(limit.unwrap_or(cache_capacity))
+            .take(limit.unwrap_or(cache_capacity).min(cache_capacity))
             .cloned()
             .collect::<Vec<_>>();
         result.reverse();
@@ -362,7 +645,7 @@ impl MarketDataManager for LocalMarketDataManager {
         // 检测result按seq id顺序排列
         for i in 1..result.len() {
             if result[i].seq_id <= result[i - 1].seq_id {
-                return Err(PlatformError::PlatformError {
+                return Err(PlatformError::ValidationError {
                     message: format!("trades not in order for {:?}, {}", market_type, symbol),
                 });
             }
@@ -371,13 +654,17 @@ impl MarketDataManager for LocalMarketDataManager {
         Ok(result)
     }
 
-    #[allow(unused_variables)]
     async fn get_depth(
         &self,
         market_type: &MarketType,
         symbol: &String,
     ) -> Result<Option<DepthData>> {
-        unimplemented!("local data manager get depth not implemented");
+        Err(PlatformError::DataManagerError {
+            message: format!(
+                "local data manager does not store depth, {:?} {}",
+                market_type, symbol
+            ),
+        })
     }
 
     #[allow(unused_variables)]
@@ -396,8 +683,9 @@ impl MarketDataManager for LocalMarketDataManager {
     ) -> Result<Option<SymbolInfo>> {
         let symbol_infos = match self.symbol_infos.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} symbol infos not found", market_type),
+                return Err(PlatformError::CacheMiss {
+                    cache: "symbol info".to_string(),
+                    key: format!("{:?}", market_type),
                 });
             }
             Some(infos) => infos,
@@ -405,11 +693,9 @@ impl MarketDataManager for LocalMarketDataManager {
         if symbol_infos.contains_key(symbol) {
             Ok(Some(symbol_infos.get(symbol).unwrap().clone()))
         } else {
-            return Err(PlatformError::PlatformError {
-                message: format!(
-                    "market type: {:?} symbol: {} symbol info not found",
-                    market_type, symbol
-                ),
+            return Err(PlatformError::SymbolNotFound {
+                market_type: market_type.clone(),
+                symbol: symbol.clone(),
             });
         }
     }
@@ -422,11 +708,9 @@ impl MarketDataManager for LocalMarketDataManager {
     ) -> Result<Option<String>> {
         let base_quote_symbols = match self.base_quote_symbols.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!(
-                        "market type: {:?} base-quote symbols not found",
-                        market_type
-                    ),
+                return Err(PlatformError::CacheMiss {
+                    cache: "base-quote symbol".to_string(),
+                    key: format!("{:?}", market_type),
                 });
             }
             Some(symbols) => symbols,
@@ -439,24 +723,156 @@ impl MarketDataManager for LocalMarketDataManager {
                     .clone(),
             ))
         } else {
-            Err(PlatformError::PlatformError {
-                message: format!(
-                    "market type: {:?} base: {}, quote: {}, symbol not found",
-                    market_type, base_asset, quote_asset
-                ),
+            Err(PlatformError::SymbolNotFound {
+                market_type: market_type.clone(),
+                symbol: format!("{}/{}", base_asset, quote_asset),
             })
         }
     }
+
+    async fn get_active_symbols(&self, market_type: &MarketType) -> Result<Vec<String>> {
+        Ok(self
+            .active_symbols
+            .get(market_type)
+            .cloned()
+            .unwrap_or_default())
+    }
+}
+
+// 模拟撮合滑点：配置种子时在 [0, max_bps] 内随机取滑点，未配置种子时按原价成交
+// 同一种子、同样的撮合顺序下结果可复现
+struct SlippageModel {
+    max_bps: u64,
+    rng: Option<Mutex<StdRng>>,
 }
 
+impl SlippageModel {
+    fn from_config(config: &BacktestConfig) -> Self {
+        Self {
+            max_bps: config.max_slippage_bps,
+            rng: config
+                .rng_seed
+                .map(|seed| Mutex::new(StdRng::seed_from_u64(seed))),
+        }
+    }
+
+    // 买单价格上浮、卖单价格下浮
+    fn apply(&self, side: &OrderSide, price: Decimal) -> Decimal {
+        let rng = match &self.rng {
+            Some(rng) if self.max_bps > 0 => rng,
+            _ => return price,
+        };
+        let bps = rng.lock().unwrap().random_range(0..=self.max_bps);
+        let slippage = price * Decimal::from(bps) / Decimal::from(10000);
+        match side {
+            OrderSide::Buy => price + slippage,
+            OrderSide::Sell => price - slippage,
+        }
+    }
+}
+
+// 模拟成交记录：按symbol保存按(timestamp, 写入序号)排序的成交，并以(symbol, order_id)建立二级索引，
+// 按订单与按时间范围的查询都只访问命中的成交
+#[derive(Default)]
+struct UserTradeStore {
+    trades: HashMap<String, BTreeMap<(u64, u64), UserTrade>>,
+    by_order: HashMap<(String, String), Vec<(u64, u64)>>, // (symbol, order_id) -> trades的key
+    next_seq: u64,
+}
+
+impl UserTradeStore {
+    fn insert(&mut self, trade: UserTrade) {
+        let key = (trade.timestamp, self.next_seq);
+        self.next_seq += 1;
+        self.by_order
+            .entry((trade.symbol.clone(), trade.order_id.clone()))
+            .or_default()
+            .push(key);
+        self.trades
+            .entry(trade.symbol.clone())
+            .or_default()
+            .insert(key, trade);
+    }
+
+    // 按成交写入顺序返回
+    fn by_order(&self, symbol: &str, order_id: &str) -> Vec<UserTrade> {
+        let (Some(keys), Some(trades)) = (
+            self.by_order
+                .get(&(symbol.to_string(), order_id.to_string())),
+            self.trades.get(symbol),
+        ) else {
+            return vec![];
+        };
+        keys.iter()
+            .filter_map(|key| trades.get(key))
+            .cloned()
+            .collect()
+    }
+
+    // [start_time, end_time]内最近的limit笔，按timestamp升序
+    fn range(
+        &self,
+        symbol: &str,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        limit: usize,
+    ) -> Vec<UserTrade> {
+        let Some(trades) = self.trades.get(symbol) else {
+            return vec![];
+        };
+        let start = (start_time.unwrap_or(0), 0);
+        let end = (end_time.unwrap_or(u64::MAX), u64::MAX);
+        if start > end {
+            return vec![];
+        }
+        let mut result: Vec<UserTrade> = trades
+            .range(start..=end)
+            .rev()
+            .take(limit)
+            .map(|(_, trade)| trade.clone())
+            .collect();
+        result.reverse();
+        result
+    }
+}
+
+// 账户余额与订单冻结记录由同一把锁保护，快照时二者始终一致
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountState {
+    pub account: Account,
+    pub order_freezes: HashMap<String, Decimal>, // client_id -> frozen amount
+}
+
+// 暂存的账户变更：持有账户写锁，commit前其他读者看不到任何中间状态，drop即放弃
+struct StagedAccountState<'a> {
+    guard: RwLockWriteGuard<'a, AccountState>,
+    staged: AccountState,
+}
+
+impl StagedAccountState<'_> {
+    fn commit(mut self) {
+        *self.guard = self.staged;
+    }
+}
+
+// 按比例解冻金额保留的小数位数
+const FREEZE_AMOUNT_DP: u32 = 16;
+
+// 锁获取顺序固定为 open_orders -> closed_orders -> user_trades -> accounts，
+// 只能按此顺序嵌套获取；持有上述任一把锁期间不await市场数据管理器等外部组件，
+// 所需的symbol信息、成交等在加锁前预取
 pub struct LocalTradeDataManager {
     clock: Arc<Clock>,
-    accounts: Arc<HashMap<MarketType, Arc<RwLock<Account>>>>,
-    order_freezes: Arc<RwLock<HashMap<MarketType, HashMap<String, Decimal>>>>, // asset -> frozen amount
+    accounts: Arc<HashMap<MarketType, Arc<RwLock<AccountState>>>>,
     open_orders: Arc<HashMap<MarketType, Arc<RwLock<HashMap<String, Order>>>>>, // client_id
     closed_orders: Arc<HashMap<MarketType, Arc<RwLock<HashMap<String, Order>>>>>, // client_id
-    user_trades: Arc<HashMap<MarketType, Arc<RwLock<HashMap<String, Vec<UserTrade>>>>>>, // order_id
+    user_trades: Arc<HashMap<MarketType, Arc<RwLock<UserTradeStore>>>>,
+    slippages: Arc<HashMap<MarketType, SlippageModel>>,
+    fee_schedules: Arc<HashMap<MarketType, FeeSchedule>>,
+    active_symbol_statuses: Arc<HashMap<MarketType, Vec<SymbolStatus>>>, // 允许下单的symbol状态
     market_mgr: Arc<dyn MarketDataManager>,
+    metrics: Arc<dyn Metrics>,
+    position_mgrs: HashMap<MarketType, Arc<PositionManager>>, // 撮合成交同步到仓位
 }
 
 impl LocalTradeDataManager {
@@ -470,17 +886,23 @@ impl LocalTradeDataManager {
         let mut open_orders = HashMap::new();
         let mut closed_orders = HashMap::new();
         let mut user_trades = HashMap::new();
+        let mut slippages = HashMap::new();
+        let mut fee_schedules = HashMap::new();
+        let mut active_symbol_statuses = HashMap::new();
 
         for market_type in config.markets.iter() {
             if !init_accounts.contains_key(market_type) {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} not found in init accounts", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "init account".to_string(),
                 });
             }
             accounts.insert(
                 market_type.clone(),
-                Arc::new(RwLock::new(init_accounts.get(market_type).unwrap().clone()))
-                    as Arc<RwLock<Account>>,
+                Arc::new(RwLock::new(AccountState {
+                    account: init_accounts.get(market_type).unwrap().clone(),
+                    order_freezes: HashMap::new(),
+                })),
             );
             open_orders.insert(
                 market_type.clone(),
@@ -492,22 +914,99 @@ impl LocalTradeDataManager {
             );
             user_trades.insert(
                 market_type.clone(),
-                Arc::new(RwLock::new(HashMap::new()))
-                    as Arc<RwLock<HashMap<String, Vec<UserTrade>>>>,
+                Arc::new(RwLock::new(UserTradeStore::default())),
+            );
+            let market_config =
+                config
+                    .configs
+                    .get(market_type)
+                    .ok_or_else(|| PlatformError::MarketNotFound {
+                        market_type: market_type.clone(),
+                        resource: "config".to_string(),
+                    })?;
+            slippages.insert(
+                market_type.clone(),
+                SlippageModel::from_config(&market_config.backtest),
+            );
+            fee_schedules.insert(market_type.clone(), market_config.fee_schedule.clone());
+            active_symbol_statuses.insert(
+                market_type.clone(),
+                market_config.active_symbol_statuses.clone(),
             );
         }
 
         Ok(Self {
             clock: clock,
             accounts: Arc::new(accounts),
-            order_freezes: Arc::new(RwLock::new(HashMap::new())),
             open_orders: Arc::new(open_orders),
             closed_orders: Arc::new(closed_orders),
             user_trades: Arc::new(user_trades),
+            slippages: Arc::new(slippages),
+            fee_schedules: Arc::new(fee_schedules),
+            active_symbol_statuses: Arc::new(active_symbol_statuses),
             market_mgr: market_mgr.clone(),
+            metrics: noop_metrics(),
+            position_mgrs: HashMap::new(),
         })
     }
 
+    // 上报 trade.order_placed/order_canceled/order_amended/fill 计数及 trade.open_orders
+    pub fn set_metrics(&mut self, metrics: Arc<dyn Metrics>) {
+        self.metrics = metrics;
+    }
+
+    // 撮合产生的成交计入position_mgr所在market_type的仓位
+    pub fn set_position_manager(&mut self, position_mgr: Arc<PositionManager>) {
+        self.position_mgrs
+            .insert(position_mgr.market_type().clone(), position_mgr);
+    }
+
+    // maker费率可为负（返佣），佣金为负时成交记入而非扣除
+    fn fee_rate(&self, market_type: &MarketType, symbol: &str, is_maker: bool) -> Result<Decimal> {
+        self.fee_schedules
+            .get(market_type)
+            .map(|schedule| schedule.rate(symbol, is_maker))
+            .ok_or_else(|| PlatformError::MarketNotFound {
+                market_type: market_type.clone(),
+                resource: "fee schedule".to_string(),
+            })
+    }
+
+    // 冻结按taker费率计算，不依赖订单最终以何种方式成交
+    fn taker_fee_rate(&self, market_type: &MarketType, symbol: &str) -> Result<Decimal> {
+        self.fee_rate(market_type, symbol, false)
+    }
+
+    // 挂单判定：GTC限价单下单时未与此前最近一笔成交价交叉，视为挂在盘口等待成交（maker）；
+    // 市价单、IOC/FOK及下单即可成交的限价单视为taker。下单前无成交记录时视为maker
+    fn is_resting_order(order: &Order, trades: &[Trade]) -> bool {
+        if order.order_type != OrderType::Limit || order.time_in_force != TimeInForce::Gtc {
+            return false;
+        }
+        match trades
+            .iter()
+            .rev()
+            .find(|t| t.timestamp <= order.create_time)
+        {
+            None => true,
+            Some(last) => match order.order_side {
+                OrderSide::Buy => order.order_price < last.price,
+                OrderSide::Sell => order.order_price > last.price,
+            },
+        }
+    }
+
+    // 账户余额与订单冻结记录的一致快照
+    pub async fn get_account_state(&self, market_type: &MarketType) -> Result<AccountState> {
+        match self.accounts.get(market_type) {
+            None => Err(PlatformError::MarketNotFound {
+                market_type: market_type.clone(),
+                resource: "account".to_string(),
+            }),
+            Some(account_lock) => Ok(account_lock.read().await.clone()),
+        }
+    }
+
     async fn get_symbol_info(
         &self,
         market_type: &MarketType,
@@ -516,11 +1015,9 @@ impl LocalTradeDataManager {
         let symbol_info: SymbolInfo =
             match self.market_mgr.get_symbol_info(market_type, symbol).await? {
                 None => {
-                    return Err(PlatformError::PlatformError {
-                        message: format!(
-                            "market type: {:?} symbol: {} not found",
-                            market_type, symbol
-                        ),
+                    return Err(PlatformError::SymbolNotFound {
+                        market_type: market_type.clone(),
+                        symbol: symbol.clone(),
                     })
                 }
                 Some(info) => info,
@@ -534,20 +1031,25 @@ impl LocalTradeDataManager {
             .get_trades(market_type, symbol, Some(1))
             .await?;
         if trades.is_empty() {
-            return Err(PlatformError::PlatformError {
-                message: format!(
-                    "market type: {:?} symbol: {} no trades found",
-                    market_type, symbol
-                ),
+            return Err(PlatformError::DataNotFound {
+                symbol: symbol.clone(),
+                data: "trades".to_string(),
             });
         }
         Ok(trades[0].clone())
     }
 
+    fn is_release_status(status: &OrderStatus) -> bool {
+        matches!(
+            status,
+            OrderStatus::Canceled | OrderStatus::Rejected | OrderStatus::Expired
+        )
+    }
+
     // 订单状态流转时,账户的余额和冻结金额都需要变更
-    // 下买订单：Market订单：冻结最新trade价格 * 1.2 * 数量，Limit订单：冻结订单价格 * 1.001 * 数量
+    // 下买订单：Market订单：冻结最新trade价格 * 1.2 * 数量，Limit订单：冻结订单价格 * (1 + taker费率) * 数量
     // 买订单（部分）成交：按比例接触冻结金额（本次成交数量/原订单剩余数量 * 该订单剩余冻结金额），可用余额增加；同时扣除可用金额中本次成交对应的金额（成交价格 * 数量 + 佣金率）
-    // 买订单取消：释放冻结金额到可用余额
+    // 买订单取消/拒绝/过期：释放冻结金额到可用余额
     // 账户余额不足，返回失败
     // 卖订单同理，只是冻结和解冻的是基础资产数量
     async fn update_account_on_order_status_change(
@@ -558,36 +1060,104 @@ impl LocalTradeDataManager {
         base_asset: &str,
         quote_asset: &str,
     ) -> Result<()> {
+        self.stage_order_status_change(market_type, order, trade, base_asset, quote_asset, None)
+            .await?
+            .commit();
+        Ok(())
+    }
+
+    // 在副本上计算变更并校验，返回持锁的暂存结果，由调用方在其余步骤成功后提交
+    // latest_price: 新Market买单冻结所用的最新成交价，需在加锁前获取
+    async fn stage_order_status_change(
+        &self,
+        market_type: &MarketType,
+        order: &Order,
+        trade: Option<&UserTrade>,
+        base_asset: &str,
+        quote_asset: &str,
+        latest_price: Option<Decimal>,
+    ) -> Result<StagedAccountState<'_>> {
         // 获取账户锁
         let account_lock = match self.accounts.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} account not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "account".to_string(),
                 });
             }
             Some(lock) => lock,
         };
 
-        let mut account = account_lock.write().await;
+        let guard = account_lock.write().await;
 
-        // 获取订单冻结记录锁
-        let mut order_freezes = self.order_freezes.write().await;
-        let market_freezes = order_freezes
-            .entry(market_type.clone())
-            .or_insert_with(|| HashMap::new());
+        // 在副本上变更，校验通过后整体提交，失败时账户与冻结记录保持不变
+        let mut staged = guard.clone();
+        self.apply_order_status_change(
+            market_type,
+            &mut staged.account,
+            &mut staged.order_freezes,
+            order,
+            trade,
+            base_asset,
+            quote_asset,
+            latest_price,
+        )?;
+        Self::check_non_negative_balances(&staged.account)?;
+
+        Ok(StagedAccountState { guard, staged })
+    }
 
+    fn check_non_negative_balances(account: &Account) -> Result<()> {
+        for balance in account.balances.iter() {
+            if balance.free < Decimal::ZERO {
+                return Err(PlatformError::InsufficientBalance {
+                    asset: balance.asset.clone(),
+                    free: balance.free,
+                    required: Decimal::ZERO,
+                });
+            }
+            if balance.locked < Decimal::ZERO {
+                return Err(PlatformError::InsufficientLockedBalance {
+                    asset: balance.asset.clone(),
+                    locked: balance.locked,
+                    required: Decimal::ZERO,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn apply_order_status_change(
+        &self,
+        market_type: &MarketType,
+        account: &mut Account,
+        market_freezes: &mut HashMap<String, Decimal>,
+        order: &Order,
+        trade: Option<&UserTrade>,
+        base_asset: &str,
+        quote_asset: &str,
+        latest_price: Option<Decimal>,
+    ) -> Result<()> {
         // 处理买单 - 新订单状态
         if order.order_side == OrderSide::Buy && order.order_status == OrderStatus::New {
             // 买单新订单：需要冻结quote资产
-            let freeze_amount = if order.order_type == OrderType::Market {
+            let freeze_amount = if order.quote_order_qty > Decimal::ZERO {
+                // 按quote金额下的Market订单：直接冻结该金额（含手续费）
+                order.quote_order_qty
+            } else if order.order_type == OrderType::Market {
                 // Market订单：冻结最新trade价格 * 1.2 * 数量
-                let trade = self.get_latest_trade(market_type, &order.symbol).await?;
-                trade.price * order.order_quantity * Decimal::from_f64(1.2).unwrap()
+                let price = latest_price.ok_or_else(|| PlatformError::DataNotFound {
+                    symbol: order.symbol.clone(),
+                    data: "trades".to_string(),
+                })?;
+                price * order.order_quantity * Decimal::from_f64(1.2).unwrap()
             } else if order.order_type == OrderType::Limit {
-                // Limit订单：冻结订单价格 * 1.001 * 数量
-                order.order_price * order.order_quantity * Decimal::from_f64(1.001).unwrap()
+                // Limit订单：冻结订单价格 * (1 + taker费率) * 数量
+                let fee_rate = self.taker_fee_rate(market_type, &order.symbol)?;
+                order.order_price * order.order_quantity * (Decimal::ONE + fee_rate)
             } else {
-                return Err(PlatformError::PlatformError {
+                return Err(PlatformError::ValidationError {
                     message: format!("unsupported order type: {:?}", order.order_type),
                 });
             };
@@ -597,17 +1167,16 @@ impl LocalTradeDataManager {
 
             match quote_balance {
                 None => {
-                    return Err(PlatformError::PlatformError {
-                        message: format!("quote asset {} not found in account", quote_asset),
+                    return Err(PlatformError::AssetNotFound {
+                        asset: quote_asset.to_string(),
                     });
                 }
                 Some(balance) => {
                     if balance.free < freeze_amount {
-                        return Err(PlatformError::PlatformError {
-                            message: format!(
-                                "insufficient balance for quote asset {}: free={}, required={}",
-                                quote_asset, balance.free, freeze_amount
-                            ),
+                        return Err(PlatformError::InsufficientBalance {
+                            asset: quote_asset.to_string(),
+                            free: balance.free,
+                            required: freeze_amount,
                         });
                     }
 
@@ -630,7 +1199,7 @@ impl LocalTradeDataManager {
         {
             let user_trade = match trade {
                 None => {
-                    return Err(PlatformError::PlatformError {
+                    return Err(PlatformError::MatchingError {
                         message: "trade required for filled/partially filled status".to_string(),
                     });
                 }
@@ -640,7 +1209,7 @@ impl LocalTradeDataManager {
             // 获取该订单之前冻结的金额
             let frozen_amount = match market_freezes.get(&order.client_order_id) {
                 None => {
-                    return Err(PlatformError::PlatformError {
+                    return Err(PlatformError::MatchingError {
                         message: format!(
                             "frozen amount not found for order {}",
                             order.client_order_id
@@ -650,44 +1219,50 @@ impl LocalTradeDataManager {
                 Some(amount) => *amount,
             };
 
-            // 计算本次成交应该释放的冻结金额
-            // 按比例：本次成交数量 / 订单总数量 * 总冻结金额
-            let unfreeze_amount = user_trade.trade_quantity
-                / (order.order_quantity - order.executed_qty + user_trade.trade_quantity)
-                * frozen_amount;
-
-            // 计算本次成交实际花费（成交价格 * 数量 + 手续费）
+            // 计算本次成交实际花费（成交价格 * 数量 + 手续费，返佣时手续费为负）
             let actual_cost =
                 user_trade.trade_price * user_trade.trade_quantity + user_trade.commission;
 
+            // 计算本次成交应该释放的冻结金额
+            // 完全成交释放全部剩余冻结；按quote金额下单的部分成交释放实际花费；
+            // 其余部分成交按比例（本次成交数量 / 成交前剩余数量 * 剩余冻结金额），
+            // 并截断到固定精度，避免多订单累加后locked与冻结记录之间出现舍入偏差
+            let unfreeze_amount = if order.order_status == OrderStatus::Filled {
+                frozen_amount
+            } else if order.quote_order_qty > Decimal::ZERO {
+                actual_cost.min(frozen_amount)
+            } else {
+                (frozen_amount * user_trade.trade_quantity
+                    / (order.order_quantity - order.executed_qty + user_trade.trade_quantity))
+                    .round_dp(FREEZE_AMOUNT_DP)
+            };
+
             // 找到quote资产的余额
             let quote_balance = account.balances.iter_mut().find(|b| b.asset == quote_asset);
 
             match quote_balance {
                 None => {
-                    return Err(PlatformError::PlatformError {
-                        message: format!("quote asset {} not found in account", quote_asset),
+                    return Err(PlatformError::AssetNotFound {
+                        asset: quote_asset.to_string(),
                     });
                 }
                 Some(balance) => {
                     // 释放冻结金额
                     if balance.locked < unfreeze_amount {
-                        return Err(PlatformError::PlatformError {
-                            message: format!(
-                                "insufficient locked balance for quote asset {}: locked={}, required={}",
-                                quote_asset, balance.locked, unfreeze_amount
-                            ),
+                        return Err(PlatformError::InsufficientLockedBalance {
+                            asset: quote_asset.to_string(),
+                            locked: balance.locked,
+                            required: unfreeze_amount,
                         });
                     }
                     balance.locked -= unfreeze_amount;
 
                     // 释放的金额转到可用余额，但要扣除实际花费
                     if balance.free + unfreeze_amount < actual_cost {
-                        return Err(PlatformError::PlatformError {
-                            message: format!(
-                                "insufficient free balance for quote asset {} after unfreeze: free+unfreeze={}, actual_cost={}",
-                                quote_asset, balance.free + unfreeze_amount, actual_cost
-                            ),
+                        return Err(PlatformError::InsufficientBalance {
+                            asset: quote_asset.to_string(),
+                            free: balance.free + unfreeze_amount,
+                            required: actual_cost,
                         });
                     }
                     balance.free += unfreeze_amount - actual_cost;
@@ -722,8 +1297,8 @@ impl LocalTradeDataManager {
             return Ok(());
         }
 
-        // 处理买单 - 取消
-        if order.order_side == OrderSide::Buy && order.order_status == OrderStatus::Canceled {
+        // 处理买单 - 取消/拒绝/过期，均释放剩余冻结
+        if order.order_side == OrderSide::Buy && Self::is_release_status(&order.order_status) {
             // 获取该订单冻结的金额
             let frozen_amount = match market_freezes.get(&order.client_order_id) {
                 None => {
@@ -739,18 +1314,17 @@ impl LocalTradeDataManager {
 
                 match quote_balance {
                     None => {
-                        return Err(PlatformError::PlatformError {
-                            message: format!("quote asset {} not found in account", quote_asset),
+                        return Err(PlatformError::AssetNotFound {
+                            asset: quote_asset.to_string(),
                         });
                     }
                     Some(balance) => {
                         // 释放所有冻结金额到可用余额
                         if balance.locked < frozen_amount {
-                            return Err(PlatformError::PlatformError {
-                                message: format!(
-                                    "insufficient locked balance for quote asset {}: locked={}, required={}",
-                                    quote_asset, balance.locked, frozen_amount
-                                ),
+                            return Err(PlatformError::InsufficientLockedBalance {
+                                asset: quote_asset.to_string(),
+                                locked: balance.locked,
+                                required: frozen_amount,
                             });
                         }
                         balance.locked -= frozen_amount;
@@ -775,17 +1349,16 @@ impl LocalTradeDataManager {
 
             match base_balance {
                 None => {
-                    return Err(PlatformError::PlatformError {
-                        message: format!("base asset {} not found in account", base_asset),
+                    return Err(PlatformError::AssetNotFound {
+                        asset: base_asset.to_string(),
                     });
                 }
                 Some(balance) => {
                     if balance.free < freeze_amount {
-                        return Err(PlatformError::PlatformError {
-                            message: format!(
-                                "insufficient balance for base asset {}: free={}, required={}",
-                                base_asset, balance.free, freeze_amount
-                            ),
+                        return Err(PlatformError::InsufficientBalance {
+                            asset: base_asset.to_string(),
+                            free: balance.free,
+                            required: freeze_amount,
                         });
                     }
 
@@ -808,7 +1381,7 @@ impl LocalTradeDataManager {
         {
             let user_trade = match trade {
                 None => {
-                    return Err(PlatformError::PlatformError {
+                    return Err(PlatformError::MatchingError {
                         message: "trade required for filled/partially filled status".to_string(),
                     });
                 }
@@ -818,7 +1391,7 @@ impl LocalTradeDataManager {
             // 获取该订单之前冻结的数量
             let frozen_amount = match market_freezes.get(&order.client_order_id) {
                 None => {
-                    return Err(PlatformError::PlatformError {
+                    return Err(PlatformError::MatchingError {
                         message: format!(
                             "frozen amount not found for order {}",
                             order.client_order_id
@@ -835,18 +1408,17 @@ impl LocalTradeDataManager {
 
             match base_balance {
                 None => {
-                    return Err(PlatformError::PlatformError {
-                        message: format!("base asset {} not found in account", base_asset),
+                    return Err(PlatformError::AssetNotFound {
+                        asset: base_asset.to_string(),
                     });
                 }
                 Some(balance) => {
                     // 释放冻结的base资产（卖出的币从冻结中扣除）
                     if balance.locked < unfreeze_amount {
-                        return Err(PlatformError::PlatformError {
-                            message: format!(
-                                "insufficient locked balance for base asset {}: locked={}, required={}",
-                                base_asset, balance.locked, unfreeze_amount
-                            ),
+                        return Err(PlatformError::InsufficientLockedBalance {
+                            asset: base_asset.to_string(),
+                            locked: balance.locked,
+                            required: unfreeze_amount,
                         });
                     }
                     balance.locked -= unfreeze_amount;
@@ -861,7 +1433,7 @@ impl LocalTradeDataManager {
                 }
             }
 
-            // 卖单成交后，增加quote资产（卖出得到的钱），扣除手续费
+            // 卖单成交后，增加quote资产（卖出得到的钱），扣除手续费（返佣时手续费为负，实际增加）
             let actual_receive =
                 user_trade.trade_price * user_trade.trade_quantity - user_trade.commission;
 
@@ -884,8 +1456,8 @@ impl LocalTradeDataManager {
             return Ok(());
         }
 
-        // 处理卖单 - 取消
-        if order.order_side == OrderSide::Sell && order.order_status == OrderStatus::Canceled {
+        // 处理卖单 - 取消/拒绝/过期，均释放剩余冻结
+        if order.order_side == OrderSide::Sell && Self::is_release_status(&order.order_status) {
             // 获取该订单冻结的数量
             let frozen_amount = match market_freezes.get(&order.client_order_id) {
                 None => {
@@ -901,18 +1473,17 @@ impl LocalTradeDataManager {
 
                 match base_balance {
                     None => {
-                        return Err(PlatformError::PlatformError {
-                            message: format!("base asset {} not found in account", base_asset),
+                        return Err(PlatformError::AssetNotFound {
+                            asset: base_asset.to_string(),
                         });
                     }
                     Some(balance) => {
                         // 释放所有冻结数量到可用余额
                         if balance.locked < frozen_amount {
-                            return Err(PlatformError::PlatformError {
-                                message: format!(
-                                    "insufficient locked balance for base asset {}: locked={}, required={}",
-                                    base_asset, balance.locked, frozen_amount
-                                ),
+                            return Err(PlatformError::InsufficientLockedBalance {
+                                asset: base_asset.to_string(),
+                                locked: balance.locked,
+                                required: frozen_amount,
                             });
                         }
                         balance.locked -= frozen_amount;
@@ -928,7 +1499,7 @@ impl LocalTradeDataManager {
         }
 
         // 如果以上都不匹配，返回错误
-        Err(PlatformError::PlatformError {
+        Err(PlatformError::MatchingError {
             message: format!(
                 "unhandled order status change: side={:?}, status={:?}",
                 order.order_side, order.order_status
@@ -937,96 +1508,171 @@ impl LocalTradeDataManager {
     }
 
     // 测试环境调整clock时，需要check一次订单是否有匹配的成交产生
+    // IOC/FOK订单在下单后的首轮撮合即完成：FOK不能全部成交则拒绝，IOC未成交部分撤销
     pub async fn matching_order(&self, mgr: Arc<dyn MarketDataManager>) -> Result<()> {
-        for (market_type, open_orders) in self.open_orders.iter() {
-            let mut open_orders = open_orders.write().await;
+        let mut fills = vec![];
+        for (market_type, open_orders_lock) in self.open_orders.iter() {
+            // 加锁前按symbol预取symbol信息与成交，撮合期间不再await市场数据管理器
+            let symbols = open_orders_lock
+                .read()
+                .await
+                .values()
+                .map(|order| order.symbol.clone())
+                .collect::<HashSet<_>>();
+            let mut market_data = HashMap::new();
+            for symbol in symbols {
+                let symbol_info: SymbolInfo =
+                    match mgr.get_symbol_info(market_type, &symbol).await? {
+                        None => {
+                            return Err(PlatformError::SymbolNotFound {
+                                market_type: market_type.clone(),
+                                symbol,
+                            });
+                        }
+                        Some(info) => info,
+                    };
+                let trades: Vec<Trade> =
+                    mgr.get_trades(market_type, &symbol, None)
+                        .await
+                        .map_err(|e| PlatformError::MatchingError {
+                            message: format!(
+                            "matching order get trades err for market_type: {:?}, symbol: {}: {}",
+                            market_type, symbol, e
+                        ),
+                        })?;
+                market_data.insert(symbol, (symbol_info, trades));
+            }
+
+            let mut open_orders = open_orders_lock.write().await;
             let mut closed_orders = match self.closed_orders.get(market_type) {
                 None => {
-                    return Err(PlatformError::PlatformError {
-                        message: format!("market type: {:?} closed orders not found", market_type),
+                    return Err(PlatformError::MarketNotFound {
+                        market_type: market_type.clone(),
+                        resource: "closed orders".to_string(),
                     })
                 }
                 Some(orders_lock) => orders_lock.write().await,
             };
             let mut user_trades = match self.user_trades.get(market_type) {
                 None => {
-                    return Err(PlatformError::PlatformError {
-                        message: format!("market type: {:?} user trades not found", market_type),
+                    return Err(PlatformError::MarketNotFound {
+                        market_type: market_type.clone(),
+                        resource: "user trades".to_string(),
                     })
                 }
                 Some(user_trades_lock) => user_trades_lock.write().await,
             };
-            let open_order_ids = open_orders.keys().map(|e| e.clone()).collect::<Vec<_>>();
+            let slippage =
+                self.slippages
+                    .get(market_type)
+                    .ok_or_else(|| PlatformError::MarketNotFound {
+                        market_type: market_type.clone(),
+                        resource: "slippage".to_string(),
+                    })?;
+            // 按下单时间排序，保证撮合顺序（及随机数消耗顺序）确定
+            let mut open_order_ids = open_orders.keys().map(|e| e.clone()).collect::<Vec<_>>();
+            open_order_ids.sort_by(|a, b| {
+                let (a, b) = (&open_orders[a], &open_orders[b]);
+                (a.create_time, &a.client_order_id).cmp(&(b.create_time, &b.client_order_id))
+            });
 
             for open_order_id in open_order_ids.iter() {
                 let mut order = open_orders.get(open_order_id).unwrap().clone();
-                let symbol_info: SymbolInfo = match mgr
-                    .get_symbol_info(market_type, &order.symbol)
-                    .await?
-                {
-                    None => {
-                        return Err(PlatformError::PlatformError {
-                                message: format!(
-                                    "matching order get symbol info err for market_type: {:?}, symbol: {}",
-                                    market_type, order.symbol
-                                ),
-                            });
-                    }
-                    Some(info) => info,
+                // 预取之后新下的订单没有未撮合的成交，留待下一轮
+                let Some((symbol_info, trades)) = market_data.get(&order.symbol) else {
+                    continue;
                 };
 
-                let trades: Vec<Trade> = mgr
-                    .get_trades(market_type, &order.symbol, None)
-                    .await
-                    .map_err(|e| PlatformError::PlatformError {
-                        message: format!(
-                            "matching order get trades err for market_type: {:?}, symbol: {}, order_id: {}: {}",
-                            market_type, order.symbol, order.order_id, e
-                        ),
-                    })?;
+                let is_maker = Self::is_resting_order(&order, trades);
+                let fee_rate = self.fee_rate(market_type, &order.symbol, is_maker)?;
                 let trades = trades
                     .iter()
                     .filter(|e| e.timestamp > order.update_time)
                     .collect::<Vec<_>>();
 
-                for trade in trades.iter() {
-                    let can_match = if order.order_type == OrderType::Market {
-                        true
-                    } else if order.order_type == OrderType::Limit {
-                        if order.order_side == OrderSide::Buy {
-                            trade.price <= order.order_price
-                        } else {
-                            trade.price >= order.order_price
+                // FOK：本轮可成交量不足以全部成交则整单拒绝，释放冻结
+                if order.time_in_force == TimeInForce::Fok {
+                    let mut matchable_quantity = Decimal::ZERO;
+                    for trade in trades.iter() {
+                        if Self::can_match(&order, trade)? {
+                            matchable_quantity += trade.quantity;
                         }
-                    } else {
-                        return Err(PlatformError::PlatformError {
-                            message: format!(
-                                "match fail due to unsuppoted order type: {:?}",
-                                order.order_type
-                            ),
-                        });
-                    };
-                    if !can_match {
+                    }
+                    if matchable_quantity < order.order_quantity - order.executed_qty {
+                        order.order_status = OrderStatus::Rejected;
+                        order.update_time = self.clock.cur_ts();
+                        self.update_account_on_order_status_change(
+                            market_type,
+                            &order,
+                            None,
+                            &symbol_info.base_asset,
+                            &symbol_info.quote_asset,
+                        )
+                        .await?;
+                        open_orders.remove(open_order_id);
+                        closed_orders.insert(open_order_id.clone(), order);
                         continue;
                     }
+                }
 
-                    let remaining_quatity = order.order_quantity - order.executed_qty;
+                for trade in trades.iter() {
+                    if !Self::can_match(&order, trade)? {
+                        continue;
+                    }
+
+                    // 限价单滑点后的成交价不劣于委托价
+                    let mut fill_price = slippage.apply(&order.order_side, trade.price);
+                    if order.order_type == OrderType::Limit {
+                        fill_price = match order.order_side {
+                            OrderSide::Buy => fill_price.min(order.order_price),
+                            OrderSide::Sell => fill_price.max(order.order_price),
+                        };
+                    }
+
+                    // 按quote金额下单：剩余金额（含手续费）按成交价折算可成交数量
+                    let remaining_quatity = if order.quote_order_qty > Decimal::ZERO {
+                        let remaining_quote = order.quote_order_qty
+                            - order.cummulative_quote_qty * (Decimal::ONE + fee_rate);
+                        symbol_info.round_market_quantity(
+                            remaining_quote / (fill_price * (Decimal::ONE + fee_rate)),
+                        )
+                    } else {
+                        order.order_quantity - order.executed_qty
+                    };
+                    // 剩余金额不足最小数量步长，未成交部分过期，释放剩余冻结
+                    if remaining_quatity <= Decimal::ZERO {
+                        order.order_status = OrderStatus::Expired;
+                        order.update_time = self.clock.cur_ts();
+                        self.update_account_on_order_status_change(
+                            market_type,
+                            &order,
+                            None,
+                            &symbol_info.base_asset,
+                            &symbol_info.quote_asset,
+                        )
+                        .await?;
+                        open_orders.remove(open_order_id);
+                        closed_orders.insert(open_order_id.clone(), order.clone());
+                        break;
+                    }
                     let trade_quantity = if trade.quantity >= remaining_quatity {
                         remaining_quatity
                     } else {
                         trade.quantity
                     };
-                    let order_status =
-                        if order.executed_qty + trade_quantity >= order.order_quantity {
-                            OrderStatus::Filled
-                        } else {
-                            OrderStatus::PartiallyFilled
-                        };
+                    let order_status = if trade_quantity >= remaining_quatity {
+                        OrderStatus::Filled
+                    } else {
+                        OrderStatus::PartiallyFilled
+                    };
 
                     order.order_status = order_status;
                     order.executed_qty += trade_quantity;
-                    order.cummulative_quote_qty += trade_quantity * trade.price;
+                    order.cummulative_quote_qty += trade_quantity * fill_price;
                     order.update_time = self.clock.cur_ts();
+                    if order.quote_order_qty > Decimal::ZERO {
+                        order.order_quantity = order.executed_qty;
+                    }
 
                     let user_trade = UserTrade {
                         trade_id: format!(
@@ -1036,13 +1682,11 @@ impl LocalTradeDataManager {
                         order_id: order.order_id.clone(),
                         symbol: order.symbol.clone(),
                         order_side: order.order_side.clone(),
-                        trade_price: trade.price,
-                        trade_quantity: trade.quantity,
-                        commission: Decimal::from_f64(0.001).unwrap()
-                            * trade.quantity
-                            * trade.price, // 千分之一手续费
+                        trade_price: fill_price,
+                        trade_quantity,
+                        commission: fee_rate * trade_quantity * fill_price,
                         commission_asset: symbol_info.quote_asset.clone(),
-                        is_maker: 0, // 模拟撮合都看作taker单
+                        is_maker: u64::from(is_maker),
                         timestamp: trade.timestamp,
                     };
 
@@ -1063,19 +1707,64 @@ impl LocalTradeDataManager {
                         open_orders.insert(open_order_id.clone(), order.clone());
                     }
 
-                    user_trades
-                        .entry(order.order_id.clone())
-                        .or_insert_with(Vec::new)
-                        .push(user_trade);
+                    if self.position_mgrs.contains_key(market_type) {
+                        fills.push((market_type, user_trade.clone()));
+                    }
+                    user_trades.insert(user_trade);
+                    self.metrics.incr("trade.fill", 1);
 
                     if order.order_status == OrderStatus::Filled {
                         break;
                     }
                 }
+
+                // IOC：本轮未成交的剩余部分直接撤销，释放剩余冻结
+                if order.time_in_force == TimeInForce::Ioc
+                    && order.order_status != OrderStatus::Filled
+                {
+                    order.order_status = OrderStatus::Canceled;
+                    order.update_time = self.clock.cur_ts();
+                    self.update_account_on_order_status_change(
+                        market_type,
+                        &order,
+                        None,
+                        &symbol_info.base_asset,
+                        &symbol_info.quote_asset,
+                    )
+                    .await?;
+                    open_orders.remove(open_order_id);
+                    closed_orders.insert(open_order_id.clone(), order);
+                }
             }
         }
+
+        // 释放订单锁后再更新仓位
+        for (market_type, user_trade) in fills {
+            self.position_mgrs[market_type]
+                .apply_fill(&user_trade)
+                .await?;
+        }
         Ok(())
     }
+
+    fn can_match(order: &Order, trade: &Trade) -> Result<bool> {
+        if order.order_type == OrderType::Market {
+            Ok(true)
+        } else if order.order_type == OrderType::Limit {
+            if order.order_side == OrderSide::Buy {
+                Ok(trade.price <= order.order_price)
+            } else {
+                Ok(trade.price >= order.order_price)
+            }
+        } else {
+            Err(PlatformError::MatchingError {
+                message: format!(
+                    "match fail due to unsuppoted order type: {:?}",
+                    order.order_type
+                ),
+            })
+        }
+    }
 }
 
 #[async_trait]
@@ -1086,20 +1775,22 @@ impl TradeDataManager for LocalTradeDataManager {
 
     async fn get_account(&self, market_type: &MarketType) -> Result<Option<Account>> {
         match self.accounts.get(market_type) {
-            None => Err(PlatformError::PlatformError {
-                message: format!("market type: {:?} account not found", market_type),
+            None => Err(PlatformError::MarketNotFound {
+                market_type: market_type.clone(),
+                resource: "account".to_string(),
             }),
             Some(account_lock) => {
-                let account = account_lock.read().await;
-                Ok(Some(account.clone()))
+                let state = account_lock.read().await;
+                Ok(Some(state.account.clone()))
             }
         }
     }
 
     async fn get_open_orders(&self, market_type: &MarketType) -> Result<Vec<Order>> {
         match self.open_orders.get(market_type) {
-            None => Err(PlatformError::PlatformError {
-                message: format!("market type: {:?} open orders not found", market_type),
+            None => Err(PlatformError::MarketNotFound {
+                market_type: market_type.clone(),
+                resource: "open orders".to_string(),
             }),
             Some(orders_lock) => {
                 let orders = orders_lock.read().await;
@@ -1116,23 +1807,17 @@ impl TradeDataManager for LocalTradeDataManager {
     ) -> Result<Vec<UserTrade>> {
         let user_trades = match self.user_trades.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} user trades not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "user trades".to_string(),
                 })
             }
             Some(user_trades_lock) => user_trades_lock.read().await,
         };
-        if user_trades.contains_key(order_id) {
-            Ok(user_trades
-                .get(order_id)
-                .unwrap()
-                .iter()
-                .filter(|e| e.symbol == symbol)
-                .map(|e| e.clone())
-                .collect())
-        } else {
-            Ok(vec![])
-        }
+        let trades = user_trades.by_order(symbol, order_id);
+        self.metrics
+            .incr("trade.user_trades_scanned", trades.len() as u64);
+        Ok(trades)
     }
 
     async fn get_orders(
@@ -1146,16 +1831,18 @@ impl TradeDataManager for LocalTradeDataManager {
         let limit = limit.unwrap_or(1000);
         let open_orders = match self.open_orders.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} open orders not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "open orders".to_string(),
                 })
             }
             Some(orders_lock) => orders_lock.read().await,
         };
         let closed_orders = match self.closed_orders.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} closed orders not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "closed orders".to_string(),
                 })
             }
             Some(orders_lock) => orders_lock.read().await,
@@ -1215,36 +1902,16 @@ impl TradeDataManager for LocalTradeDataManager {
         let limit = limit.unwrap_or(1000);
         let user_trades = match self.user_trades.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} user trades not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "user trades".to_string(),
                 })
             }
             Some(user_trades_lock) => user_trades_lock.read().await,
         };
-        let mut trades = user_trades
-            .iter()
-            .flat_map(|e| {
-                e.1.iter()
-                    .filter(|ut| {
-                        if start_time.is_some() && ut.timestamp < start_time.unwrap() {
-                            return false;
-                        }
-                        if end_time.is_some() && ut.timestamp > end_time.unwrap() {
-                            return false;
-                        }
-                        if symbol != ut.symbol {
-                            return false;
-                        }
-                        return true;
-                    })
-                    .cloned()
-                    .collect::<Vec<_>>()
-            })
-            .collect::<Vec<_>>();
-        trades.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-        if trades.len() > limit {
-            trades = trades[trades.len() - limit..].to_vec();
-        }
+        let trades = user_trades.range(symbol, start_time, end_time, limit);
+        self.metrics
+            .incr("trade.user_trades_scanned", trades.len() as u64);
         Ok(trades)
     }
 
@@ -1256,16 +1923,18 @@ impl TradeDataManager for LocalTradeDataManager {
     ) -> Result<Option<Order>> {
         let open_orders = match self.open_orders.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} open orders not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "open orders".to_string(),
                 })
             }
             Some(orders_lock) => orders_lock.read().await,
         };
         let closed_orders = match self.closed_orders.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} closed orders not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "closed orders".to_string(),
                 })
             }
             Some(orders_lock) => orders_lock.read().await,
@@ -1297,16 +1966,18 @@ impl TradeDataManager for LocalTradeDataManager {
     ) -> Result<Option<Order>> {
         let open_orders = match self.open_orders.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} open orders not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "open orders".to_string(),
                 })
             }
             Some(orders_lock) => orders_lock.read().await,
         };
         let closed_orders = match self.closed_orders.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} closed orders not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "closed orders".to_string(),
                 })
             }
             Some(orders_lock) => orders_lock.read().await,
@@ -1347,28 +2018,66 @@ impl TradeDataManager for LocalTradeDataManager {
 
     async fn place_order(&self, market_type: &MarketType, req: PlaceOrderRequest) -> Result<Order> {
         if req.r#type != OrderType::Limit && req.r#type != OrderType::Market {
-            return Err(PlatformError::PlatformError {
+            return Err(PlatformError::ValidationError {
                 message: format!(
                     "only support Limit/Market order in test, got {:?}",
                     req.r#type
                 ),
             });
         }
+        if let Some(quote_order_qty) = req.quote_order_qty {
+            if req.r#type != OrderType::Market || req.side != OrderSide::Buy {
+                return Err(PlatformError::ValidationError {
+                    message: "quote_order_qty is only supported for Market buy order in test"
+                        .to_string(),
+                });
+            }
+            if req.quantity.is_some() || quote_order_qty <= Decimal::ZERO {
+                return Err(PlatformError::ValidationError {
+                    message: format!(
+                        "quote_order_qty must be positive and exclusive with quantity, got {}",
+                        quote_order_qty
+                    ),
+                });
+            }
+        }
+
+        // 获取symbol信息，非可交易状态拒绝下单；加锁前完成，见锁顺序说明
+        let symbol_info = self.get_symbol_info(market_type, &req.symbol).await?;
+        let active = self
+            .active_symbol_statuses
+            .get(market_type)
+            .is_some_and(|statuses| statuses.contains(&symbol_info.status));
+        if !active {
+            return Err(PlatformError::SymbolNotTrading {
+                market_type: market_type.clone(),
+                symbol: req.symbol.clone(),
+                status: symbol_info.status,
+            });
+        }
+        let base_asset = symbol_info.base_asset.clone();
+        let quote_asset = symbol_info.quote_asset.clone();
+        let latest_price = if req.r#type == OrderType::Market
+            && req.side == OrderSide::Buy
+            && req.quote_order_qty.is_none()
+        {
+            Some(self.get_latest_trade(market_type, &req.symbol).await?.price)
+        } else {
+            None
+        };
 
         let mut open_orders = match self.open_orders.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} open orders not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "open orders".to_string(),
                 })
             }
             Some(orders_lock) => orders_lock.write().await,
         };
         if open_orders.contains_key(&req.client_order_id) {
-            return Err(PlatformError::PlatformError {
-                message: format!(
-                    "order with client_order_id: {} already exists",
-                    req.client_order_id
-                ),
+            return Err(PlatformError::DuplicateOrder {
+                client_order_id: req.client_order_id.clone(),
             });
         }
 
@@ -1378,48 +2087,49 @@ impl TradeDataManager for LocalTradeDataManager {
         order.create_time = now;
         order.update_time = now;
 
-        // 获取symbol信息
-        let symbol_info = self.get_symbol_info(market_type, &order.symbol).await?;
-        let base_asset = symbol_info.base_asset.clone();
-        let quote_asset = symbol_info.quote_asset.clone();
-
-        // 更新账户状态（冻结资金）
-        self.update_account_on_order_status_change(
-            market_type,
-            &order,
-            None,
-            &base_asset,
-            &quote_asset,
-        )
-        .await?;
-
+        // 暂存冻结资金，与挂单登记在同时持有两把锁时一并提交，
+        // 任一步失败则余额、冻结记录与挂单均保持不变
+        let staged = self
+            .stage_order_status_change(
+                market_type,
+                &order,
+                None,
+                &base_asset,
+                &quote_asset,
+                latest_price,
+            )
+            .await?;
         open_orders.insert(req.client_order_id.clone(), order.clone());
+        staged.commit();
+        self.metrics.incr("trade.order_placed", 1);
+        self.metrics
+            .gauge("trade.open_orders", open_orders.len() as f64);
 
         Ok(order)
     }
 
     async fn cancel_order(&self, market_type: &MarketType, req: CancelOrderRequest) -> Result<()> {
+        // 加锁前获取symbol信息，见锁顺序说明
+        let symbol_info = self.get_symbol_info(market_type, &req.symbol).await?;
         let mut open_orders = match self.open_orders.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} open orders not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "open orders".to_string(),
                 })
             }
             Some(orders_lock) => orders_lock.write().await,
         };
 
         if !open_orders.contains_key(&req.client_order_id) {
-            return Err(PlatformError::PlatformError {
-                message: format!(
-                    "order with client_order_id: {} not found",
-                    req.client_order_id
-                ),
+            return Err(PlatformError::OrderNotFound {
+                client_order_id: req.client_order_id.clone(),
             });
         }
         if req.order_id.is_some() {
             let order = open_orders.get(&req.client_order_id).unwrap();
             if order.order_id != req.order_id.clone().unwrap() {
-                return Err(PlatformError::PlatformError {
+                return Err(PlatformError::ValidationError {
                     message: format!(
                         "order_id mismatch for client_order_id: {}",
                         req.client_order_id
@@ -1428,11 +2138,17 @@ impl TradeDataManager for LocalTradeDataManager {
             }
         }
         let mut order = open_orders.get(&req.client_order_id).unwrap().clone();
+        if order.symbol != req.symbol {
+            return Err(PlatformError::ValidationError {
+                message: format!(
+                    "symbol mismatch for client_order_id: {}",
+                    req.client_order_id
+                ),
+            });
+        }
         order.order_status = OrderStatus::Canceled;
         order.update_time = self.clock.cur_ts();
 
-        // 获取symbol信息
-        let symbol_info = self.get_symbol_info(market_type, &order.symbol).await?;
         let base_asset = symbol_info.base_asset.clone();
         let quote_asset = symbol_info.quote_asset.clone();
 
@@ -1448,15 +2164,185 @@ impl TradeDataManager for LocalTradeDataManager {
 
         let mut closed_orders = match self.closed_orders.get(market_type) {
             None => {
-                return Err(PlatformError::PlatformError {
-                    message: format!("market type: {:?} closed orders not found", market_type),
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "closed orders".to_string(),
                 })
             }
             Some(orders_lock) => orders_lock.write().await,
         };
         open_orders.remove(&req.client_order_id);
         closed_orders.insert(req.client_order_id.clone(), order);
+        self.metrics.incr("trade.order_canceled", 1);
+        self.metrics
+            .gauge("trade.open_orders", open_orders.len() as f64);
 
         Ok(())
     }
+
+    // 本地直接原地修改订单价格/数量，并按差额调整冻结
+    async fn amend_order(&self, market_type: &MarketType, req: AmendOrderRequest) -> Result<Order> {
+        // 加锁前获取symbol信息，见锁顺序说明
+        let symbol_info = self.get_symbol_info(market_type, &req.symbol).await?;
+        let mut open_orders = match self.open_orders.get(market_type) {
+            None => {
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "open orders".to_string(),
+                })
+            }
+            Some(orders_lock) => orders_lock.write().await,
+        };
+
+        let mut order = match open_orders.get(&req.client_order_id) {
+            Some(order) => order.clone(),
+            None => {
+                let is_closed = match self.closed_orders.get(market_type) {
+                    None => false,
+                    Some(orders_lock) => {
+                        orders_lock.read().await.contains_key(&req.client_order_id)
+                    }
+                };
+                if is_closed {
+                    return Err(PlatformError::ValidationError {
+                        message: format!(
+                            "order with client_order_id: {} is in terminal status, can not amend",
+                            req.client_order_id
+                        ),
+                    });
+                }
+                return Err(PlatformError::OrderNotFound {
+                    client_order_id: req.client_order_id.clone(),
+                });
+            }
+        };
+        if order.symbol != req.symbol {
+            return Err(PlatformError::ValidationError {
+                message: format!(
+                    "symbol mismatch for client_order_id: {}",
+                    req.client_order_id
+                ),
+            });
+        }
+        if req
+            .order_id
+            .as_ref()
+            .is_some_and(|order_id| order.order_id != *order_id)
+        {
+            return Err(PlatformError::ValidationError {
+                message: format!(
+                    "order_id mismatch for client_order_id: {}",
+                    req.client_order_id
+                ),
+            });
+        }
+        if order.order_type != OrderType::Limit {
+            return Err(PlatformError::ValidationError {
+                message: format!(
+                    "only support amend Limit order in test, got {:?}",
+                    order.order_type
+                ),
+            });
+        }
+        if req.new_client_order_id != req.client_order_id
+            && open_orders.contains_key(&req.new_client_order_id)
+        {
+            return Err(PlatformError::DuplicateOrder {
+                client_order_id: req.new_client_order_id.clone(),
+            });
+        }
+
+        if let Some(price) = req.price {
+            order.order_price = price;
+        }
+        if let Some(quantity) = req.quantity {
+            if quantity <= order.executed_qty {
+                return Err(PlatformError::ValidationError {
+                    message: format!(
+                        "amend quantity {} must be greater than executed quantity {}",
+                        quantity, order.executed_qty
+                    ),
+                });
+            }
+            order.order_quantity = quantity;
+        }
+        order.client_order_id = req.new_client_order_id.clone();
+        order.update_time = self.clock.cur_ts();
+
+        // 剩余未成交部分的冻结，与下单时的冻结规则一致
+        let remaining_quantity = order.order_quantity - order.executed_qty;
+        let (freeze_asset, new_freeze) = match order.order_side {
+            OrderSide::Buy => (
+                symbol_info.quote_asset.clone(),
+                order.order_price
+                    * remaining_quantity
+                    * (Decimal::ONE + self.taker_fee_rate(market_type, &order.symbol)?),
+            ),
+            OrderSide::Sell => (symbol_info.base_asset.clone(), remaining_quantity),
+        };
+
+        // 获取账户锁，余额与订单冻结记录一并变更
+        let account_lock = match self.accounts.get(market_type) {
+            None => {
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "account".to_string(),
+                });
+            }
+            Some(lock) => lock,
+        };
+        let mut state_guard = account_lock.write().await;
+        let state = &mut *state_guard;
+        let market_freezes = &mut state.order_freezes;
+
+        let old_freeze = market_freezes
+            .get(&req.client_order_id)
+            .cloned()
+            .unwrap_or(Decimal::ZERO);
+        let delta = new_freeze - old_freeze;
+        let balance = match state
+            .account
+            .balances
+            .iter_mut()
+            .find(|b| b.asset == freeze_asset)
+        {
+            None => {
+                return Err(PlatformError::AssetNotFound {
+                    asset: freeze_asset,
+                });
+            }
+            Some(balance) => balance,
+        };
+        if balance.free < delta {
+            return Err(PlatformError::InsufficientBalance {
+                asset: freeze_asset,
+                free: balance.free,
+                required: delta,
+            });
+        }
+        if balance.locked + delta < Decimal::ZERO {
+            return Err(PlatformError::InsufficientLockedBalance {
+                asset: freeze_asset,
+                locked: balance.locked,
+                required: -delta,
+            });
+        }
+        balance.free -= delta;
+        balance.locked += delta;
+
+        market_freezes.remove(&req.client_order_id);
+        market_freezes.insert(order.client_order_id.clone(), new_freeze);
+        open_orders.remove(&req.client_order_id);
+        open_orders.insert(order.client_order_id.clone(), order.clone());
+        self.metrics.incr("trade.order_amended", 1);
+
+        Ok(order)
+    }
+}
+
+#[async_trait]
+impl ClockHook for LocalTradeDataManager {
+    async fn on_clock_advance(&self, _cur_ts: u64) -> Result<()> {
+        self.matching_order(self.market_mgr.clone()).await
+    }
 }
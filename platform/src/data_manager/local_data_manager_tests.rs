@@ -0,0 +1,1809 @@
+use crate::{
+    backtest::test_utils::symbol_info,
+    config::{Config, PlatformConfig},
+    data_manager::{
+        db::*,
+        local_data_manager::{
+            AccountState, Clock, LocalMarketDataManager, LocalTradeDataManager,
+            INIT_LOAD_CONCURRENCY,
+        },
+        MarketDataManager, TradeDataManager,
+    },
+    errors::PlatformError,
+    models::{
+        Account, AmendOrderRequest, Balance, CancelOrderRequest, KlineData, KlineInterval,
+        MarketType, OrderSide, OrderStatus, OrderType, PlaceOrderRequest, SymbolInfo, SymbolStatus,
+        TimeInForce, Trade, UserTrade,
+    },
+};
+use db::sqlite::SQLiteDB;
+use rust_decimal::Decimal;
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tempfile::NamedTempFile;
+use time::{noop_metrics, InMemoryMetrics, Metrics};
+
+struct TestEnv {
+    _db_file: NamedTempFile,
+    db: Arc<SQLiteDB>,
+    clock: Arc<Clock>,
+    market_mgr: Arc<LocalMarketDataManager>,
+    trade_mgr: Arc<LocalTradeDataManager>,
+}
+
+fn test_config_with_symbols(db_path: &str, symbols: &[String]) -> Arc<PlatformConfig> {
+    test_config_with(db_path, symbols, &["1m"], "")
+}
+
+// extra_fields 为追加到 binance_spot 配置中的字段，需以逗号结尾
+fn test_config_with(
+    db_path: &str,
+    symbols: &[String],
+    intervals: &[&str],
+    extra_fields: &str,
+) -> Arc<PlatformConfig> {
+    let config_content = r#"
+    {
+        "markets": ["binance_spot"],
+        "db_path": "{placeholder}",
+        "binance_spot": {
+            {extra_fields}
+            "cache_capacity": 1000,
+            "api_base_url": "",
+            "stream_base_url": "",
+            "stream_api_base_url": "",
+            "api_key": "",
+            "secret_key": "",
+            "subscribed_symbols": {symbols},
+            "subscribed_kline_intervals": {intervals}
+        }
+    }
+    "#;
+    let config_content = config_content
+        .replace("{placeholder}", db_path)
+        .replace("{symbols}", &serde_json::to_string(symbols).unwrap())
+        .replace("{intervals}", &serde_json::to_string(intervals).unwrap())
+        .replace("{extra_fields}", extra_fields);
+    let mut config_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config_file, config_content.as_bytes()).unwrap();
+    let config = Config::from_json(config_file.path().to_str().unwrap()).unwrap();
+    Arc::new(PlatformConfig::from_config(config).unwrap())
+}
+
+fn test_trade(seq_id: u64, timestamp: u64, price: &str, quantity: &str) -> Trade {
+    Trade {
+        symbol: "BTCUSDT".to_string(),
+        trade_id: seq_id.to_string(),
+        price: Decimal::from_str(price).unwrap(),
+        quantity: Decimal::from_str(quantity).unwrap(),
+        timestamp,
+        is_buyer_maker: 0,
+        seq_id,
+    }
+}
+
+fn test_kline(open_time: u64) -> KlineData {
+    KlineData {
+        symbol: "BTCUSDT".to_string(),
+        interval: KlineInterval::OneMinute,
+        open_time,
+        close_time: open_time + 59_999,
+        open: Decimal::from(100),
+        high: Decimal::from(101),
+        low: Decimal::from(99),
+        close: Decimal::from(100),
+        volume: Decimal::ONE,
+        quote_volume: Decimal::from(100),
+        taker_buy_volume: Decimal::ZERO,
+        taker_buy_quote_volume: Decimal::ZERO,
+        is_closed: 1,
+    }
+}
+
+fn test_balances(usdt: i64, btc: Option<i64>) -> Vec<Balance> {
+    let mut balances = vec![Balance {
+        asset: "USDT".to_string(),
+        free: Decimal::from(usdt),
+        locked: Decimal::ZERO,
+    }];
+    if let Some(btc) = btc {
+        balances.push(Balance {
+            asset: "BTC".to_string(),
+            free: Decimal::from(btc),
+            locked: Decimal::ZERO,
+        });
+    }
+    balances
+}
+
+async fn setup(trades: Vec<Trade>, cur_ts: u64, balances: Vec<Balance>) -> TestEnv {
+    setup_with_config_fields(trades, cur_ts, balances, "").await
+}
+
+async fn setup_with_config_fields(
+    trades: Vec<Trade>,
+    cur_ts: u64,
+    balances: Vec<Balance>,
+    extra_fields: &str,
+) -> TestEnv {
+    setup_with_metrics(trades, cur_ts, balances, extra_fields, noop_metrics()).await
+}
+
+async fn setup_with_metrics(
+    trades: Vec<Trade>,
+    cur_ts: u64,
+    balances: Vec<Balance>,
+    extra_fields: &str,
+    metrics: Arc<dyn Metrics>,
+) -> TestEnv {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    let market_type = MarketType::BinanceSpot;
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    update_symbol_info(
+        db.clone(),
+        &market_type,
+        &[symbol_info("BTCUSDT", "BTC", "USDT")],
+    )
+    .unwrap();
+    update_trade_data(db.clone(), &market_type, &trades).unwrap();
+
+    let config = test_config_with(
+        db_file.path().to_str().unwrap(),
+        &["BTCUSDT".to_string()],
+        &["1m"],
+        extra_fields,
+    );
+    let clock = Arc::new(Clock::new(cur_ts));
+    let market_mgr = Arc::new(
+        LocalMarketDataManager::new(config.clone(), clock.clone(), db.clone(), 10000).unwrap(),
+    );
+    market_mgr.init().await.unwrap();
+
+    let mut init_accounts = HashMap::new();
+    init_accounts.insert(
+        market_type,
+        Account {
+            balances,
+            timestamp: cur_ts,
+        },
+    );
+    let mut trade_mgr =
+        LocalTradeDataManager::new(clock.clone(), config, init_accounts, market_mgr.clone())
+            .unwrap();
+    trade_mgr.set_metrics(metrics);
+    let trade_mgr = Arc::new(trade_mgr);
+
+    TestEnv {
+        _db_file: db_file,
+        db,
+        clock,
+        market_mgr,
+        trade_mgr,
+    }
+}
+
+fn limit_buy(client_order_id: &str, time_in_force: TimeInForce) -> PlaceOrderRequest {
+    PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: OrderSide::Buy,
+        r#type: OrderType::Limit,
+        time_in_force: Some(time_in_force),
+        quantity: Some(Decimal::ONE),
+        price: Some(Decimal::from(100)),
+        client_order_id: client_order_id.to_string(),
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: None,
+    }
+}
+
+fn balance(account: &Account, asset: &str) -> Balance {
+    account
+        .balances
+        .iter()
+        .find(|b| b.asset == asset)
+        .unwrap()
+        .clone()
+}
+
+#[tokio::test]
+async fn test_fok_rejected_on_insufficient_liquidity() {
+    let env = setup(
+        vec![
+            test_trade(1, 500, "100", "1"),
+            test_trade(2, 1500, "99", "0.4"),
+            test_trade(3, 1600, "101", "0.3"),
+        ],
+        1000,
+        test_balances(10000, Some(0)),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+
+    let order = env
+        .trade_mgr
+        .place_order(&market_type, limit_buy("fok_1", TimeInForce::Fok))
+        .await
+        .unwrap();
+    assert_eq!(order.order_status, OrderStatus::New);
+
+    env.clock.set_cur_ts(2000);
+    env.trade_mgr
+        .matching_order(env.market_mgr.clone())
+        .await
+        .unwrap();
+
+    let order = env
+        .trade_mgr
+        .get_order_by_client_id(&market_type, "BTCUSDT", "fok_1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(order.order_status, OrderStatus::Rejected);
+    assert_eq!(order.executed_qty, Decimal::ZERO);
+    assert!(env
+        .trade_mgr
+        .get_open_orders(&market_type)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let account = env
+        .trade_mgr
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap();
+    let usdt = balance(&account, "USDT");
+    assert_eq!(usdt.free, Decimal::from(10000));
+    assert_eq!(usdt.locked, Decimal::ZERO);
+}
+
+#[tokio::test]
+async fn test_ioc_partial_fill_cancels_remainder() {
+    let env = setup(
+        vec![
+            test_trade(1, 500, "100", "1"),
+            test_trade(2, 1500, "99", "0.4"),
+            test_trade(3, 1600, "101", "0.3"),
+        ],
+        1000,
+        test_balances(10000, Some(0)),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+
+    env.trade_mgr
+        .place_order(&market_type, limit_buy("ioc_1", TimeInForce::Ioc))
+        .await
+        .unwrap();
+
+    env.clock.set_cur_ts(2000);
+    env.trade_mgr
+        .matching_order(env.market_mgr.clone())
+        .await
+        .unwrap();
+
+    let order = env
+        .trade_mgr
+        .get_order_by_client_id(&market_type, "BTCUSDT", "ioc_1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(order.order_status, OrderStatus::Canceled);
+    assert_eq!(order.executed_qty, Decimal::from_str("0.4").unwrap());
+    assert!(env
+        .trade_mgr
+        .get_open_orders(&market_type)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // 成交0.4 @ 99，千分之一手续费，剩余冻结全部释放
+    let account = env
+        .trade_mgr
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap();
+    let usdt = balance(&account, "USDT");
+    assert_eq!(usdt.locked, Decimal::ZERO);
+    assert_eq!(usdt.free, Decimal::from_str("9960.3604").unwrap());
+    assert_eq!(
+        balance(&account, "BTC").free,
+        Decimal::from_str("0.4").unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_structured_balance_errors() {
+    let env = setup(
+        vec![test_trade(1, 500, "100", "1")],
+        1000,
+        test_balances(50, None),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+
+    // 冻结100.1 USDT，可用仅50
+    match env
+        .trade_mgr
+        .place_order(&market_type, limit_buy("buy_1", TimeInForce::Gtc))
+        .await
+    {
+        Err(PlatformError::InsufficientBalance {
+            asset,
+            free,
+            required,
+        }) => {
+            assert_eq!(asset, "USDT");
+            assert_eq!(free, Decimal::from(50));
+            assert_eq!(required, Decimal::from_str("100.1").unwrap());
+        }
+        other => panic!("expect InsufficientBalance, got {:?}", other),
+    }
+
+    // 账户中没有BTC
+    let mut sell = limit_buy("sell_1", TimeInForce::Gtc);
+    sell.side = OrderSide::Sell;
+    match env.trade_mgr.place_order(&market_type, sell).await {
+        Err(PlatformError::AssetNotFound { asset }) => assert_eq!(asset, "BTC"),
+        other => panic!("expect AssetNotFound, got {:?}", other),
+    }
+
+    // 失败操作不改变账户
+    let account = env
+        .trade_mgr
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap();
+    let usdt = balance(&account, "USDT");
+    assert_eq!(usdt.free, Decimal::from(50));
+    assert_eq!(usdt.locked, Decimal::ZERO);
+    assert!(env
+        .trade_mgr
+        .get_open_orders(&market_type)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_negative_balance_returns_error() {
+    // 手续费为成交额的2倍，卖出所得为负
+    let env = setup_with_config_fields(
+        vec![
+            test_trade(1, 500, "100", "1"),
+            test_trade(2, 1500, "100", "1"),
+        ],
+        1000,
+        test_balances(0, Some(1)),
+        r#""fee_schedule": {"maker_bps": 20000, "taker_bps": 20000},"#,
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+    let mut sell = limit_buy("sell_1", TimeInForce::Gtc);
+    sell.side = OrderSide::Sell;
+    env.trade_mgr.place_order(&market_type, sell).await.unwrap();
+    let before = env.trade_mgr.get_account_state(&market_type).await.unwrap();
+
+    env.clock.set_cur_ts(2000);
+    match env.trade_mgr.matching_order(env.market_mgr.clone()).await {
+        Err(PlatformError::InsufficientBalance { asset, free, .. }) => {
+            assert_eq!(asset, "USDT");
+            assert_eq!(free, Decimal::from(-100));
+        }
+        other => panic!("expect InsufficientBalance, got {:?}", other),
+    }
+    let after = env.trade_mgr.get_account_state(&market_type).await.unwrap();
+    assert_eq!(after, before);
+}
+
+#[tokio::test]
+async fn test_place_order_freeze_failure_keeps_state() {
+    // 成交在2000ms，当前时间1000ms，市价单取不到最新成交价，冻结步骤失败
+    let env = setup(
+        vec![test_trade(1, 2000, "100", "1")],
+        1000,
+        test_balances(1000, Some(1)),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+    env.trade_mgr
+        .place_order(&market_type, limit_buy("buy_1", TimeInForce::Gtc))
+        .await
+        .unwrap();
+    let before = env.trade_mgr.get_account_state(&market_type).await.unwrap();
+    assert_eq!(before.order_freezes.len(), 1);
+
+    let mut market_buy = limit_buy("buy_2", TimeInForce::Gtc);
+    market_buy.r#type = OrderType::Market;
+    market_buy.time_in_force = None;
+    market_buy.price = None;
+    match env.trade_mgr.place_order(&market_type, market_buy).await {
+        Err(PlatformError::DataNotFound { data, .. }) => assert_eq!(data, "trades"),
+        other => panic!("expect DataNotFound, got {:?}", other),
+    }
+
+    // 冻结金额超出可用余额
+    let mut large_buy = limit_buy("buy_3", TimeInForce::Gtc);
+    large_buy.quantity = Some(Decimal::from(20));
+    assert!(matches!(
+        env.trade_mgr.place_order(&market_type, large_buy).await,
+        Err(PlatformError::InsufficientBalance { .. })
+    ));
+
+    // 余额、冻结记录与挂单均保持下单前状态
+    let after = env.trade_mgr.get_account_state(&market_type).await.unwrap();
+    assert_eq!(after, before);
+    let open_orders = env.trade_mgr.get_open_orders(&market_type).await.unwrap();
+    assert_eq!(open_orders.len(), 1);
+    assert_eq!(open_orders[0].client_order_id, "buy_1");
+}
+
+#[tokio::test]
+async fn test_get_klines_range() {
+    let env = setup(
+        vec![test_trade(1, 500, "100", "1")],
+        10 * 60_000,
+        test_balances(10000, Some(0)),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    let klines = (0..20).map(|i| test_kline(i * 60_000)).collect::<Vec<_>>();
+    update_kline_data(env.db.clone(), &market_type, &klines).unwrap();
+
+    // 子区间 [2m, 5m]
+    let result = env
+        .market_mgr
+        .get_klines_range(
+            &market_type,
+            &symbol,
+            &KlineInterval::OneMinute,
+            2 * 60_000,
+            5 * 60_000,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result.iter().map(|k| k.open_time).collect::<Vec<_>>(),
+        vec![2 * 60_000, 3 * 60_000, 4 * 60_000, 5 * 60_000]
+    );
+
+    // 区间超过cur_ts(10m)时，只返回已收盘的kline，clock不变
+    let result = env
+        .market_mgr
+        .get_klines_range(
+            &market_type,
+            &symbol,
+            &KlineInterval::OneMinute,
+            8 * 60_000,
+            15 * 60_000,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        result.iter().map(|k| k.open_time).collect::<Vec<_>>(),
+        vec![8 * 60_000, 9 * 60_000]
+    );
+    assert_eq!(env.clock.cur_ts(), 10 * 60_000);
+}
+
+#[tokio::test]
+async fn test_init_loads_all_symbol_caches() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    let market_type = MarketType::BinanceSpot;
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+
+    let symbols = (0..32).map(|i| format!("SYM{}USDT", i)).collect::<Vec<_>>();
+    let symbol_infos = (0..32)
+        .map(|i| symbol_info(&symbols[i], &format!("SYM{}", i), "USDT"))
+        .collect::<Vec<_>>();
+    update_symbol_info(db.clone(), &market_type, &symbol_infos).unwrap();
+    for (i, symbol) in symbols.iter().enumerate() {
+        let klines = (0..=i as u64)
+            .map(|j| KlineData {
+                symbol: symbol.clone(),
+                ..test_kline(j * 60_000)
+            })
+            .collect::<Vec<_>>();
+        update_kline_data(db.clone(), &market_type, &klines).unwrap();
+        let trades = (1..=i as u64 + 1)
+            .map(|j| Trade {
+                symbol: symbol.clone(),
+                ..test_trade(j, j * 1000, "100", "1")
+            })
+            .collect::<Vec<_>>();
+        update_trade_data(db.clone(), &market_type, &trades).unwrap();
+    }
+
+    let config = test_config_with_symbols(db_file.path().to_str().unwrap(), &symbols);
+    let clock = Arc::new(Clock::new(3_600_000));
+    let market_mgr = LocalMarketDataManager::new(config, clock, db.clone(), 10000).unwrap();
+    market_mgr.init().await.unwrap();
+
+    // 清空db后仍能读到，说明数据来自init加载的缓存
+    db.execute_update("DELETE FROM kline", &[]).unwrap();
+    db.execute_update("DELETE FROM trade", &[]).unwrap();
+    for (i, symbol) in symbols.iter().enumerate() {
+        let klines = market_mgr
+            .get_klines(&market_type, symbol, &KlineInterval::OneMinute, None)
+            .await
+            .unwrap();
+        assert_eq!(klines.len(), i + 1, "klines of {}", symbol);
+        let trades = market_mgr
+            .get_trades(&market_type, symbol, None)
+            .await
+            .unwrap();
+        assert_eq!(trades.len(), i + 1, "trades of {}", symbol);
+    }
+
+    // 任一加载任务失败时init返回错误
+    db.execute_update("DROP TABLE trade", &[]).unwrap();
+    let config = test_config_with_symbols(db_file.path().to_str().unwrap(), &symbols);
+    let market_mgr =
+        LocalMarketDataManager::new(config, Arc::new(Clock::new(3_600_000)), db, 10000).unwrap();
+    assert!(market_mgr.init().await.is_err());
+}
+
+// 测试持有每个kline缓存的读锁，加载任务的写锁排队后try_read失败
+// 所有缓存同时有写锁排队，说明init并发加载而非逐个加载
+#[tokio::test]
+async fn test_init_loads_caches_concurrently() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    let market_type = MarketType::BinanceSpot;
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+
+    let symbols = (0..INIT_LOAD_CONCURRENCY)
+        .map(|i| format!("SYM{}USDT", i))
+        .collect::<Vec<_>>();
+    let symbol_infos = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, symbol)| symbol_info(symbol, &format!("SYM{}", i), "USDT"))
+        .collect::<Vec<_>>();
+    update_symbol_info(db.clone(), &market_type, &symbol_infos).unwrap();
+    for symbol in symbols.iter() {
+        let kline = KlineData {
+            symbol: symbol.clone(),
+            ..test_kline(0)
+        };
+        update_kline_data(db.clone(), &market_type, &[kline]).unwrap();
+    }
+
+    let config = test_config_with_symbols(db_file.path().to_str().unwrap(), &symbols);
+    let market_mgr = Arc::new(
+        LocalMarketDataManager::new(config, Arc::new(Clock::new(3_600_000)), db, 10000).unwrap(),
+    );
+    let caches = symbols
+        .iter()
+        .map(|symbol| {
+            market_mgr
+                .kline_cache(&market_type, symbol, &KlineInterval::OneMinute)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let mut guards = vec![];
+    for cache in caches.iter() {
+        guards.push(cache.clone().read_owned().await);
+    }
+
+    let init = tokio::spawn({
+        let market_mgr = market_mgr.clone();
+        async move { market_mgr.init().await }
+    });
+    let mut all_queued = false;
+    for _ in 0..500 {
+        if caches.iter().all(|cache| cache.try_read().is_err()) {
+            all_queued = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(all_queued);
+
+    drop(guards);
+    init.await.unwrap().unwrap();
+    for cache in caches.iter() {
+        assert_eq!(cache.read().await.len(), 1);
+    }
+}
+
+// 同一组订单和行情跑一次撮合，返回序列化后的成交记录
+async fn run_matching_with_seed(seed: Option<u64>) -> Vec<u8> {
+    let extra_fields = match seed {
+        Some(seed) => format!(
+            r#""backtest": {{ "rng_seed": {}, "max_slippage_bps": 50 }},"#,
+            seed
+        ),
+        None => r#""backtest": { "max_slippage_bps": 50 },"#.to_string(),
+    };
+    let env = setup_with_config_fields(
+        vec![
+            test_trade(1, 500, "100", "1"),
+            test_trade(2, 1500, "100", "0.5"),
+            test_trade(3, 1600, "99", "0.7"),
+            test_trade(4, 1700, "101.9", "2"),
+        ],
+        1000,
+        test_balances(10000, Some(0)),
+        &extra_fields,
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+
+    let client_ids = ["order_a", "order_b", "order_c"];
+    for client_id in client_ids {
+        env.trade_mgr
+            .place_order(
+                &market_type,
+                PlaceOrderRequest {
+                    price: Some(Decimal::from(102)),
+                    ..limit_buy(client_id, TimeInForce::Gtc)
+                },
+            )
+            .await
+            .unwrap();
+    }
+    env.clock.set_cur_ts(2000);
+    env.trade_mgr
+        .matching_order(env.market_mgr.clone())
+        .await
+        .unwrap();
+
+    let mut user_trades = vec![];
+    for client_id in client_ids {
+        let order = env
+            .trade_mgr
+            .get_order_by_client_id(&market_type, "BTCUSDT", client_id)
+            .await
+            .unwrap()
+            .unwrap();
+        user_trades.extend(
+            env.trade_mgr
+                .get_user_trades_by_order(&market_type, "BTCUSDT", &order.order_id)
+                .await
+                .unwrap(),
+        );
+    }
+    assert!(!user_trades.is_empty());
+    // 限价单滑点后成交价不超过委托价
+    assert!(user_trades
+        .iter()
+        .all(|t| t.trade_price <= Decimal::from(102)));
+    serde_json::to_vec(&user_trades).unwrap()
+}
+
+#[tokio::test]
+async fn test_seeded_slippage_is_reproducible() {
+    let first = run_matching_with_seed(Some(7)).await;
+    let second = run_matching_with_seed(Some(7)).await;
+    assert_eq!(first, second);
+
+    // 未配置种子时按原价成交，结果同样确定
+    let unseeded = run_matching_with_seed(None).await;
+    assert_eq!(unseeded, run_matching_with_seed(None).await);
+    assert_ne!(first, unseeded);
+    let unseeded: Vec<UserTrade> = serde_json::from_slice(&unseeded).unwrap();
+    assert!(unseeded.iter().all(|t| [
+        Decimal::from(100),
+        Decimal::from(99),
+        Decimal::from_str("101.9").unwrap()
+    ]
+    .contains(&t.trade_price)));
+}
+
+// 以100限价买入1BTC并全部成交，返回下单后冻结的USDT、成交记录与成交后的USDT余额
+async fn fill_with_fee_schedule(fee_schedule: &str) -> (Decimal, UserTrade, Balance) {
+    let env = setup_with_config_fields(
+        vec![
+            test_trade(1, 500, "100", "1"),
+            test_trade(2, 1500, "100", "1"),
+        ],
+        1000,
+        test_balances(10000, Some(0)),
+        &format!(r#""fee_schedule": {},"#, fee_schedule),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+
+    let order = env
+        .trade_mgr
+        .place_order(&market_type, limit_buy("fee_1", TimeInForce::Gtc))
+        .await
+        .unwrap();
+    let account = env
+        .trade_mgr
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap();
+    let locked = balance(&account, "USDT").locked;
+
+    env.clock.set_cur_ts(2000);
+    env.trade_mgr
+        .matching_order(env.market_mgr.clone())
+        .await
+        .unwrap();
+    let user_trades = env
+        .trade_mgr
+        .get_user_trades_by_order(&market_type, "BTCUSDT", &order.order_id)
+        .await
+        .unwrap();
+    assert_eq!(user_trades.len(), 1);
+    let account = env
+        .trade_mgr
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap();
+    (locked, user_trades[0].clone(), balance(&account, "USDT"))
+}
+
+#[tokio::test]
+async fn test_fee_schedule_applied_to_fills() {
+    let d = |s: &str| Decimal::from_str(s).unwrap();
+
+    // BTCUSDT按覆盖的2bps收取
+    let (locked, trade, usdt) = fill_with_fee_schedule(
+        r#"{"taker_bps": 5, "symbol_overrides": {"BTCUSDT": {"maker_bps": 0, "taker_bps": 2}}}"#,
+    )
+    .await;
+    assert_eq!(locked, d("100.02"));
+    assert_eq!(trade.commission, d("0.02"));
+    assert_eq!(trade.commission_asset, "USDT");
+    assert_eq!(usdt.free, d("9899.98"));
+    assert_eq!(usdt.locked, Decimal::ZERO);
+
+    // 覆盖其他symbol时BTCUSDT按默认5bps收取
+    let (locked, trade, usdt) = fill_with_fee_schedule(
+        r#"{"taker_bps": 5, "symbol_overrides": {"ETHUSDT": {"maker_bps": 0, "taker_bps": 2}}}"#,
+    )
+    .await;
+    assert_eq!(locked, d("100.05"));
+    assert_eq!(trade.commission, d("0.05"));
+    assert_eq!(usdt.free, d("9899.95"));
+
+    // BNB抵扣：默认10bps打75折
+    let (_, trade, _) = fill_with_fee_schedule(r#"{"bnb_discount": true}"#).await;
+    assert_eq!(trade.commission, d("0.075"));
+}
+
+// 以100限价挂单（下单前最近成交价在对手侧，不会立即成交）并被后续成交全部撮合，返回成交记录与成交后的账户
+async fn maker_fill_with_fee_schedule(side: OrderSide, fee_schedule: &str) -> (UserTrade, Account) {
+    let last_price = match side {
+        OrderSide::Buy => "101",
+        OrderSide::Sell => "99",
+    };
+    let env = setup_with_config_fields(
+        vec![
+            test_trade(1, 500, last_price, "1"),
+            test_trade(2, 1500, "100", "1"),
+        ],
+        1000,
+        test_balances(10000, Some(1)),
+        &format!(r#""fee_schedule": {},"#, fee_schedule),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+
+    let mut req = limit_buy("maker_1", TimeInForce::Gtc);
+    req.side = side;
+    let order = env.trade_mgr.place_order(&market_type, req).await.unwrap();
+    env.clock.set_cur_ts(2000);
+    env.trade_mgr
+        .matching_order(env.market_mgr.clone())
+        .await
+        .unwrap();
+    let user_trades = env
+        .trade_mgr
+        .get_user_trades_by_order(&market_type, "BTCUSDT", &order.order_id)
+        .await
+        .unwrap();
+    assert_eq!(user_trades.len(), 1);
+    let account = env
+        .trade_mgr
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap();
+    (user_trades[0].clone(), account)
+}
+
+#[tokio::test]
+async fn test_negative_maker_fee_rebate() {
+    let d = |s: &str| Decimal::from_str(s).unwrap();
+    let zero_fee = r#"{"maker_bps": 0, "taker_bps": 5}"#;
+    let rebate = r#"{"maker_bps": -2, "taker_bps": 5}"#;
+
+    // 买单：返佣以USDT记入，成交花费低于零费率
+    let (trade, zero_account) = maker_fill_with_fee_schedule(OrderSide::Buy, zero_fee).await;
+    assert_eq!(trade.is_maker, 1);
+    assert_eq!(trade.commission, Decimal::ZERO);
+    let (trade, account) = maker_fill_with_fee_schedule(OrderSide::Buy, rebate).await;
+    assert_eq!(trade.is_maker, 1);
+    assert_eq!(trade.commission, d("-0.02"));
+    let usdt = balance(&account, "USDT");
+    assert_eq!(usdt.free, d("9900.02"));
+    assert_eq!(usdt.locked, Decimal::ZERO);
+    assert!(usdt.free > balance(&zero_account, "USDT").free);
+    assert_eq!(balance(&account, "BTC").free, d("2"));
+
+    // 卖单：到账USDT高于零费率
+    let (_, zero_account) = maker_fill_with_fee_schedule(OrderSide::Sell, zero_fee).await;
+    let (trade, account) = maker_fill_with_fee_schedule(OrderSide::Sell, rebate).await;
+    assert_eq!(trade.commission, d("-0.02"));
+    assert_eq!(balance(&account, "USDT").free, d("10100.02"));
+    assert!(balance(&account, "USDT").free > balance(&zero_account, "USDT").free);
+    assert_eq!(balance(&account, "BTC").free, Decimal::ZERO);
+    assert_eq!(balance(&account, "BTC").locked, Decimal::ZERO);
+}
+
+fn quote_market_buy(client_order_id: &str, quote_order_qty: &str) -> PlaceOrderRequest {
+    PlaceOrderRequest {
+        r#type: OrderType::Market,
+        time_in_force: None,
+        quantity: None,
+        price: None,
+        quote_order_qty: Some(Decimal::from_str(quote_order_qty).unwrap()),
+        ..limit_buy(client_order_id, TimeInForce::Gtc)
+    }
+}
+
+#[tokio::test]
+async fn test_quote_order_qty_market_buy() {
+    let d = |s: &str| Decimal::from_str(s).unwrap();
+    let env = setup(
+        vec![
+            test_trade(1, 500, "100", "1"),
+            test_trade(2, 1500, "100", "0.4"),
+            test_trade(3, 1600, "110", "5"),
+        ],
+        1000,
+        test_balances(10000, Some(0)),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+
+    // 不支持的组合
+    let mut limit = limit_buy("bad_1", TimeInForce::Gtc);
+    limit.quote_order_qty = Some(d("100"));
+    assert!(matches!(
+        env.trade_mgr.place_order(&market_type, limit).await,
+        Err(PlatformError::ValidationError { .. })
+    ));
+    let mut both = quote_market_buy("bad_2", "100");
+    both.quantity = Some(Decimal::ONE);
+    assert!(matches!(
+        env.trade_mgr.place_order(&market_type, both).await,
+        Err(PlatformError::ValidationError { .. })
+    ));
+
+    // 直接冻结quote金额
+    let order = env
+        .trade_mgr
+        .place_order(&market_type, quote_market_buy("quote_1", "100.1"))
+        .await
+        .unwrap();
+    let state = env.trade_mgr.get_account_state(&market_type).await.unwrap();
+    assert_eq!(balance(&state.account, "USDT").locked, d("100.1"));
+    assert_eq!(state.order_freezes["quote_1"], d("100.1"));
+
+    // 100成交0.4（花费40.04），剩余60.06按110折算 60.06 / (110 * 1.001) 向下取整到0.00001
+    env.clock.set_cur_ts(2000);
+    env.trade_mgr
+        .matching_order(env.market_mgr.clone())
+        .await
+        .unwrap();
+    let trades = env
+        .trade_mgr
+        .get_user_trades_by_order(&market_type, "BTCUSDT", &order.order_id)
+        .await
+        .unwrap();
+    assert_eq!(
+        trades.iter().map(|t| t.trade_quantity).collect::<Vec<_>>(),
+        vec![d("0.4"), d("0.54545")]
+    );
+    let order = env
+        .trade_mgr
+        .get_order_by_client_id(&market_type, "BTCUSDT", "quote_1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(order.order_status, OrderStatus::Filled);
+    assert_eq!(order.executed_qty, d("0.94545"));
+    assert_eq!(order.order_quantity, d("0.94545"));
+    assert_eq!(order.cummulative_quote_qty, d("99.9995"));
+
+    // 实际花费（含手续费）不超过下单金额，差额不足一个数量步长，退回可用余额
+    let commission: Decimal = trades.iter().map(|t| t.commission).sum();
+    let spent = order.cummulative_quote_qty + commission;
+    assert_eq!(spent, d("100.0994995"));
+    assert!(d("100.1") - spent < d("0.00001") * d("110") * d("1.001"));
+    let state = env.trade_mgr.get_account_state(&market_type).await.unwrap();
+    let usdt = balance(&state.account, "USDT");
+    assert_eq!(usdt.free, d("10000") - spent);
+    assert_eq!(usdt.locked, Decimal::ZERO);
+    assert!(state.order_freezes.is_empty());
+    assert_eq!(balance(&state.account, "BTC").free, d("0.94545"));
+}
+
+#[tokio::test]
+async fn test_live_and_manual_clock() {
+    let manual = Clock::new(1000);
+    assert!(!manual.is_live());
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    assert_eq!(manual.cur_ts(), 1000);
+    manual.set_cur_ts(2000);
+    assert_eq!(manual.cur_ts(), 2000);
+
+    let live = Clock::new_live();
+    assert!(live.is_live());
+    let t1 = live.cur_ts();
+    assert!(t1 >= time::get_current_milli_timestamp() - 1000);
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    let t2 = live.cur_ts();
+    assert!(t2 > t1);
+    // live模式下手动设置不生效，时间仍单调不减
+    live.set_cur_ts(1000);
+    let t3 = live.cur_ts();
+    assert!(t3 >= t2);
+}
+
+#[tokio::test]
+async fn test_advance_to_triggers_matching() {
+    let env = setup(
+        vec![
+            test_trade(1, 500, "100", "1"),
+            test_trade(2, 1500, "101", "1"),
+            test_trade(3, 2500, "99", "1"),
+        ],
+        1000,
+        test_balances(10000, Some(0)),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+    env.clock.register_hook(env.trade_mgr.clone());
+
+    env.trade_mgr
+        .place_order(
+            &market_type,
+            PlaceOrderRequest {
+                price: Some(Decimal::from_str("99.5").unwrap()),
+                ..limit_buy("hook_1", TimeInForce::Gtc)
+            },
+        )
+        .await
+        .unwrap();
+
+    // 2000 之前的成交价都高于限价，订单保持挂单
+    env.clock.advance_to(2000).await.unwrap();
+    let order = env
+        .trade_mgr
+        .get_order_by_client_id(&market_type, "BTCUSDT", "hook_1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(order.order_status, OrderStatus::New);
+
+    // 推进到 3000 时穿过限价，无需显式调用 matching_order
+    env.clock.advance_to(3000).await.unwrap();
+    let order = env
+        .trade_mgr
+        .get_order_by_client_id(&market_type, "BTCUSDT", "hook_1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(order.order_status, OrderStatus::Filled);
+    assert_eq!(env.clock.cur_ts(), 3000);
+}
+
+#[tokio::test]
+async fn test_kline_cache_capacity_per_interval() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    let market_type = MarketType::BinanceSpot;
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    update_symbol_info(
+        db.clone(),
+        &market_type,
+        &[symbol_info("BTCUSDT", "BTC", "USDT")],
+    )
+    .unwrap();
+
+    // 3天跨度：1m 4320根，1d 3根
+    let day = 24 * 60 * 60_000;
+    let minute_klines = (0..3 * 24 * 60)
+        .map(|i| test_kline(i * 60_000))
+        .collect::<Vec<_>>();
+    for chunk in minute_klines.chunks(500) {
+        update_kline_data(db.clone(), &market_type, chunk).unwrap();
+    }
+    let day_klines = (0..3)
+        .map(|i| KlineData {
+            interval: KlineInterval::OneDay,
+            close_time: i * day + day - 1,
+            ..test_kline(i * day)
+        })
+        .collect::<Vec<_>>();
+    update_kline_data(db.clone(), &market_type, &day_klines).unwrap();
+
+    let config = test_config_with(
+        db_file.path().to_str().unwrap(),
+        &["BTCUSDT".to_string()],
+        &["1m", "1d"],
+        r#""kline_cache_capacities": {"1m": 3000, "1d": 100, "BTCUSDT:1d": 2},"#,
+    );
+    let market_config = config.configs.get(&market_type).unwrap();
+    assert_eq!(
+        market_config.kline_cache_capacity("BTCUSDT", &KlineInterval::OneDay),
+        Some(2)
+    );
+    assert_eq!(
+        market_config.kline_cache_capacity("ETHUSDT", &KlineInterval::OneDay),
+        Some(100)
+    );
+    assert_eq!(
+        market_config.kline_cache_capacity("BTCUSDT", &KlineInterval::OneHour),
+        None
+    );
+
+    let clock = Arc::new(Clock::new(3 * day));
+    let market_mgr = LocalMarketDataManager::new(config, clock, db, 10000).unwrap();
+    market_mgr.init().await.unwrap();
+
+    let symbol = "BTCUSDT".to_string();
+    let minute = market_mgr
+        .get_klines(&market_type, &symbol, &KlineInterval::OneMinute, None)
+        .await
+        .unwrap();
+    let daily = market_mgr
+        .get_klines(&market_type, &symbol, &KlineInterval::OneDay, None)
+        .await
+        .unwrap();
+    // 默认limit和缓存大小都按覆盖后的容量，超过市场默认的1000
+    assert_eq!(minute.len(), 3000);
+    assert_eq!(minute.last().unwrap().open_time, (3 * 24 * 60 - 1) * 60_000);
+    assert_eq!(daily.len(), 2);
+    assert_eq!(daily.last().unwrap().open_time, 2 * day);
+    let all_minute = market_mgr
+        .get_klines(&market_type, &symbol, &KlineInterval::OneMinute, Some(5000))
+        .await
+        .unwrap();
+    assert_eq!(all_minute.len(), 3000);
+}
+
+fn amend(client_order_id: &str, new_client_order_id: &str, quantity: &str) -> AmendOrderRequest {
+    AmendOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        order_id: None,
+        client_order_id: client_order_id.to_string(),
+        new_client_order_id: new_client_order_id.to_string(),
+        price: None,
+        quantity: Some(Decimal::from_str(quantity).unwrap()),
+    }
+}
+
+#[tokio::test]
+async fn test_amend_order_adjusts_freeze_by_delta() {
+    let env = setup(
+        vec![test_trade(1, 500, "100", "1")],
+        1000,
+        test_balances(10000, Some(10)),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+    let usdt = |account: &Account| balance(account, "USDT");
+
+    env.trade_mgr
+        .place_order(&market_type, limit_buy("buy_1", TimeInForce::Gtc))
+        .await
+        .unwrap();
+    let before = usdt(
+        &env.trade_mgr
+            .get_account(&market_type)
+            .await
+            .unwrap()
+            .unwrap(),
+    );
+    assert_eq!(before.locked, Decimal::from_str("100.1").unwrap());
+
+    // 数量调大：冻结增加 100 * 2 * 1.001
+    let order = env
+        .trade_mgr
+        .amend_order(&market_type, amend("buy_1", "buy_2", "3"))
+        .await
+        .unwrap();
+    assert_eq!(order.client_order_id, "buy_2");
+    assert_eq!(order.order_quantity, Decimal::from(3));
+    let after_up = usdt(
+        &env.trade_mgr
+            .get_account(&market_type)
+            .await
+            .unwrap()
+            .unwrap(),
+    );
+    let delta = Decimal::from_str("200.2").unwrap();
+    assert_eq!(after_up.locked - before.locked, delta);
+    assert_eq!(before.free - after_up.free, delta);
+
+    // 数量调小：冻结减少 100 * 1.5 * 1.001
+    env.trade_mgr
+        .amend_order(&market_type, amend("buy_2", "buy_3", "1.5"))
+        .await
+        .unwrap();
+    let after_down = usdt(
+        &env.trade_mgr
+            .get_account(&market_type)
+            .await
+            .unwrap()
+            .unwrap(),
+    );
+    let delta = Decimal::from_str("150.15").unwrap();
+    assert_eq!(after_up.locked - after_down.locked, delta);
+    assert_eq!(after_down.free - after_up.free, delta);
+
+    let open_orders = env.trade_mgr.get_open_orders(&market_type).await.unwrap();
+    assert_eq!(open_orders.len(), 1);
+    assert_eq!(open_orders[0].client_order_id, "buy_3");
+    assert_eq!(
+        open_orders[0].order_quantity,
+        Decimal::from_str("1.5").unwrap()
+    );
+
+    // 卖单冻结base资产，按数量差额调整
+    let mut sell = limit_buy("sell_1", TimeInForce::Gtc);
+    sell.side = OrderSide::Sell;
+    env.trade_mgr.place_order(&market_type, sell).await.unwrap();
+    env.trade_mgr
+        .amend_order(&market_type, amend("sell_1", "sell_1", "4"))
+        .await
+        .unwrap();
+    let btc = balance(
+        &env.trade_mgr
+            .get_account(&market_type)
+            .await
+            .unwrap()
+            .unwrap(),
+        "BTC",
+    );
+    assert_eq!(btc.locked, Decimal::from(4));
+    assert_eq!(btc.free, Decimal::from(6));
+
+    // 余额不足时修改失败，账户与订单不变
+    match env
+        .trade_mgr
+        .amend_order(&market_type, amend("buy_3", "buy_4", "200"))
+        .await
+    {
+        Err(PlatformError::InsufficientBalance { asset, .. }) => assert_eq!(asset, "USDT"),
+        other => panic!("expect InsufficientBalance, got {:?}", other),
+    }
+    let account = env
+        .trade_mgr
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(usdt(&account), after_down);
+
+    // 终态订单不可修改，取消后释放全部冻结
+    env.trade_mgr
+        .cancel_order(
+            &market_type,
+            CancelOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                order_id: None,
+                client_order_id: "buy_3".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    let err = env
+        .trade_mgr
+        .amend_order(&market_type, amend("buy_3", "buy_5", "2"))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("terminal status"));
+    let account = env
+        .trade_mgr
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(usdt(&account).locked, Decimal::ZERO);
+    assert_eq!(usdt(&account).free, Decimal::from(10000));
+}
+
+#[tokio::test]
+async fn test_structured_order_errors() {
+    let env = setup(
+        vec![test_trade(1, 500, "100", "1")],
+        1000,
+        test_balances(10000, Some(10)),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+
+    env.trade_mgr
+        .place_order(&market_type, limit_buy("buy_1", TimeInForce::Gtc))
+        .await
+        .unwrap();
+    let err = env
+        .trade_mgr
+        .place_order(&market_type, limit_buy("buy_1", TimeInForce::Gtc))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code_name(), "DUPLICATE_ORDER");
+    match err {
+        PlatformError::DuplicateOrder { client_order_id } => assert_eq!(client_order_id, "buy_1"),
+        other => panic!("expect DuplicateOrder, got {:?}", other),
+    }
+
+    let cancel = CancelOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        order_id: None,
+        client_order_id: "buy_x".to_string(),
+    };
+    match env.trade_mgr.cancel_order(&market_type, cancel).await {
+        Err(PlatformError::OrderNotFound { client_order_id }) => {
+            assert_eq!(client_order_id, "buy_x")
+        }
+        other => panic!("expect OrderNotFound, got {:?}", other),
+    }
+    match env
+        .trade_mgr
+        .amend_order(&market_type, amend("buy_x", "buy_y", "2"))
+        .await
+    {
+        Err(PlatformError::OrderNotFound { client_order_id }) => {
+            assert_eq!(client_order_id, "buy_x")
+        }
+        other => panic!("expect OrderNotFound, got {:?}", other),
+    }
+
+    let mut unknown = limit_buy("eth_1", TimeInForce::Gtc);
+    unknown.symbol = "ETHUSDT".to_string();
+    match env.trade_mgr.place_order(&market_type, unknown).await {
+        Err(PlatformError::SymbolNotFound {
+            market_type: err_market_type,
+            symbol,
+        }) => {
+            assert_eq!(err_market_type, market_type);
+            assert_eq!(symbol, "ETHUSDT");
+        }
+        other => panic!("expect SymbolNotFound, got {:?}", other),
+    }
+
+    // 未订阅的symbol没有缓存
+    match env
+        .market_mgr
+        .get_klines(
+            &market_type,
+            &"ETHUSDT".to_string(),
+            &KlineInterval::OneMinute,
+            Some(1),
+        )
+        .await
+    {
+        Err(PlatformError::CacheMiss { cache, key }) => {
+            assert_eq!(cache, "kline");
+            assert!(key.contains("ETHUSDT"));
+        }
+        other => panic!("expect CacheMiss, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_trade_metrics() {
+    let metrics = Arc::new(InMemoryMetrics::new());
+    let env = setup_with_metrics(
+        vec![
+            test_trade(1, 500, "100", "1"),
+            test_trade(2, 1500, "99", "1"),
+        ],
+        1000,
+        test_balances(10000, Some(0)),
+        "",
+        metrics.clone(),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+    env.clock.register_hook(env.trade_mgr.clone());
+
+    for client_order_id in ["buy_1", "buy_2"] {
+        env.trade_mgr
+            .place_order(
+                &market_type,
+                PlaceOrderRequest {
+                    price: Some(Decimal::from_str("99.5").unwrap()),
+                    ..limit_buy(client_order_id, TimeInForce::Gtc)
+                },
+            )
+            .await
+            .unwrap();
+    }
+    assert_eq!(metrics.counter("trade.order_placed"), 2);
+    assert_eq!(metrics.gauge_value("trade.open_orders"), Some(2.0));
+
+    // 失败的下单不计数
+    assert!(env
+        .trade_mgr
+        .place_order(&market_type, limit_buy("buy_1", TimeInForce::Gtc))
+        .await
+        .is_err());
+    assert_eq!(metrics.counter("trade.order_placed"), 2);
+
+    env.trade_mgr
+        .cancel_order(
+            &market_type,
+            CancelOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                order_id: None,
+                client_order_id: "buy_2".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(metrics.counter("trade.order_canceled"), 1);
+    assert_eq!(metrics.gauge_value("trade.open_orders"), Some(1.0));
+
+    env.clock.advance_to(2000).await.unwrap();
+    assert_eq!(metrics.counter("trade.fill"), 1);
+}
+
+#[tokio::test]
+async fn test_non_strict_symbol_init_skips_broken_symbol() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db_path = db_file.path().to_str().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_path).unwrap());
+    let market_type = MarketType::BinanceSpot;
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    // ETHUSDT 缺少 symbol_info
+    update_symbol_info(
+        db.clone(),
+        &market_type,
+        &[symbol_info("BTCUSDT", "BTC", "USDT")],
+    )
+    .unwrap();
+    update_kline_data(db.clone(), &market_type, &[test_kline(0)]).unwrap();
+    let symbols = ["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+    let clock = Arc::new(Clock::new(60_000));
+
+    let config = test_config_with(db_path, &symbols, &["1m"], "");
+    match LocalMarketDataManager::new(config, clock.clone(), db.clone(), 100) {
+        Err(PlatformError::SymbolNotFound { symbol, .. }) => assert_eq!(symbol, "ETHUSDT"),
+        Err(e) => panic!("expect SymbolNotFound, got {:?}", e),
+        Ok(_) => panic!("expect strict init to fail"),
+    }
+
+    let config = test_config_with(
+        db_path,
+        &symbols,
+        &["1m"],
+        r#""strict_symbol_init": false,"#,
+    );
+    let market_mgr = LocalMarketDataManager::new(config, clock, db.clone(), 100).unwrap();
+    market_mgr.init().await.unwrap();
+
+    let report = market_mgr.init_report();
+    assert!(!report.is_ok());
+    assert_eq!(report.failed_symbols(&market_type), vec!["ETHUSDT"]);
+    assert!(report.failures[0].error.contains("ETHUSDT"));
+
+    let klines = market_mgr
+        .get_klines(
+            &market_type,
+            &"BTCUSDT".to_string(),
+            &KlineInterval::OneMinute,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(klines.len(), 1);
+    assert!(market_mgr
+        .get_symbol_info(&market_type, &"BTCUSDT".to_string())
+        .await
+        .unwrap()
+        .is_some());
+    assert!(market_mgr
+        .get_klines(
+            &market_type,
+            &"ETHUSDT".to_string(),
+            &KlineInterval::OneMinute,
+            None,
+        )
+        .await
+        .is_err());
+}
+
+fn assert_freezes_consistent(state: &AccountState) {
+    let locked: Decimal = state.account.balances.iter().map(|b| b.locked).sum();
+    let frozen: Decimal = state.order_freezes.values().sum();
+    assert_eq!(locked, frozen, "inconsistent account state: {:?}", state);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_account_state_consistent_under_concurrency() {
+    let trades = (0..20)
+        .map(|i| test_trade(i + 1, 1500 + i * 100, "99", "0.5"))
+        .collect();
+    let env = setup(trades, 1000, test_balances(100000, Some(0))).await;
+    let market_type = MarketType::BinanceSpot;
+    env.clock.register_hook(env.trade_mgr.clone());
+
+    let running = Arc::new(AtomicBool::new(true));
+    let mut readers = Vec::new();
+    for _ in 0..3 {
+        let trade_mgr = env.trade_mgr.clone();
+        let running = running.clone();
+        let market_type = market_type.clone();
+        readers.push(tokio::spawn(async move {
+            // 至少读取一次，避免reader调度晚于写入结束
+            let mut snapshots = 0;
+            loop {
+                let state = trade_mgr.get_account_state(&market_type).await.unwrap();
+                assert_freezes_consistent(&state);
+                snapshots += 1;
+                if !running.load(Ordering::SeqCst) {
+                    break;
+                }
+                tokio::task::yield_now().await;
+            }
+            snapshots
+        }));
+    }
+
+    // 下单、撤单、推进时钟撮合成交交替进行
+    for i in 0..20u64 {
+        let client_order_id = format!("buy_{}", i);
+        env.trade_mgr
+            .place_order(&market_type, limit_buy(&client_order_id, TimeInForce::Gtc))
+            .await
+            .unwrap();
+        if i % 3 == 0 {
+            env.trade_mgr
+                .cancel_order(
+                    &market_type,
+                    CancelOrderRequest {
+                        symbol: "BTCUSDT".to_string(),
+                        order_id: None,
+                        client_order_id,
+                    },
+                )
+                .await
+                .unwrap();
+        }
+        env.clock.advance_to(1500 + i * 100).await.unwrap();
+    }
+    running.store(false, Ordering::SeqCst);
+
+    for reader in readers {
+        assert!(reader.await.unwrap() > 0);
+    }
+    let state = env.trade_mgr.get_account_state(&market_type).await.unwrap();
+    assert_freezes_consistent(&state);
+    assert!(balance(&state.account, "BTC").free > Decimal::ZERO);
+    assert!(!state.order_freezes.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_concurrent_place_cancel_match_no_deadlock() {
+    // 初始时刻已有成交，市价单可取到最新价
+    let trades = (0..200)
+        .map(|i| test_trade(i + 1, 1000 + i * 10, "99", "0.2"))
+        .collect();
+    let env = setup(trades, 1000, test_balances(1_000_000, Some(0))).await;
+    let market_type = MarketType::BinanceSpot;
+    env.clock.register_hook(env.trade_mgr.clone());
+
+    let mut tasks = Vec::new();
+    // 多个任务并发下单（限价/市价）并撤单，订单可能已被撮合成交，撤单失败可忽略
+    for worker in 0..4 {
+        let trade_mgr = env.trade_mgr.clone();
+        let market_type = market_type.clone();
+        tasks.push(tokio::spawn(async move {
+            for i in 0..50 {
+                let client_order_id = format!("w{}_{}", worker, i);
+                let mut req = limit_buy(&client_order_id, TimeInForce::Gtc);
+                if i % 5 == 0 {
+                    req.r#type = OrderType::Market;
+                    req.time_in_force = None;
+                    req.price = None;
+                }
+                trade_mgr.place_order(&market_type, req).await.unwrap();
+                if i % 2 == 0 {
+                    let _ = trade_mgr
+                        .cancel_order(
+                            &market_type,
+                            CancelOrderRequest {
+                                symbol: "BTCUSDT".to_string(),
+                                order_id: None,
+                                client_order_id,
+                            },
+                        )
+                        .await;
+                }
+                tokio::task::yield_now().await;
+            }
+        }));
+    }
+    // 推进时钟触发撮合，同时直接调用撮合
+    {
+        let clock = env.clock.clone();
+        tasks.push(tokio::spawn(async move {
+            for i in 0..200 {
+                clock.advance_to(1100 + i * 10).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        }));
+        let trade_mgr = env.trade_mgr.clone();
+        let market_mgr = env.market_mgr.clone();
+        tasks.push(tokio::spawn(async move {
+            for _ in 0..200 {
+                trade_mgr.matching_order(market_mgr.clone()).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        }));
+    }
+
+    let all = futures_util::future::join_all(tasks);
+    let results = tokio::time::timeout(std::time::Duration::from_secs(30), all)
+        .await
+        .expect("place/cancel/match deadlocked");
+    for result in results {
+        result.unwrap();
+    }
+
+    env.clock.advance_to(3100).await.unwrap();
+    let state = env.trade_mgr.get_account_state(&market_type).await.unwrap();
+    assert_freezes_consistent(&state);
+    let open_orders = env.trade_mgr.get_open_orders(&market_type).await.unwrap();
+    assert_eq!(open_orders.len(), state.order_freezes.len());
+    assert!(open_orders
+        .iter()
+        .all(|o| state.order_freezes.contains_key(&o.client_order_id)));
+}
+
+#[tokio::test]
+async fn test_inactive_symbols_excluded() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db_path = db_file.path().to_str().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_path).unwrap());
+    let market_type = MarketType::BinanceSpot;
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    update_symbol_info(
+        db.clone(),
+        &market_type,
+        &[
+            symbol_info("BTCUSDT", "BTC", "USDT"),
+            SymbolInfo {
+                status: SymbolStatus::Halted,
+                ..symbol_info("ETHUSDT", "ETH", "USDT")
+            },
+        ],
+    )
+    .unwrap();
+    update_kline_data(db.clone(), &market_type, &[test_kline(0)]).unwrap();
+    let symbols = ["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+    let clock = Arc::new(Clock::new(60_000));
+
+    let config = test_config_with_symbols(db_path, &symbols);
+    let market_mgr = Arc::new(
+        LocalMarketDataManager::new(config.clone(), clock.clone(), db.clone(), 100).unwrap(),
+    );
+    market_mgr.init().await.unwrap();
+    assert_eq!(
+        market_mgr.get_active_symbols(&market_type).await.unwrap(),
+        vec!["BTCUSDT".to_string()]
+    );
+    // 停牌的symbol不加载行情，symbol_info仍可查询
+    assert!(market_mgr
+        .get_klines(
+            &market_type,
+            &"ETHUSDT".to_string(),
+            &KlineInterval::OneMinute,
+            None,
+        )
+        .await
+        .is_err());
+    assert_eq!(
+        market_mgr
+            .get_symbol_info(&market_type, &"ETHUSDT".to_string())
+            .await
+            .unwrap()
+            .unwrap()
+            .status,
+        SymbolStatus::Halted
+    );
+
+    let mut init_accounts = HashMap::new();
+    init_accounts.insert(
+        market_type.clone(),
+        Account {
+            balances: test_balances(10000, Some(0)),
+            timestamp: 60_000,
+        },
+    );
+    let trade_mgr =
+        LocalTradeDataManager::new(clock.clone(), config, init_accounts, market_mgr).unwrap();
+    match trade_mgr
+        .place_order(
+            &market_type,
+            PlaceOrderRequest {
+                symbol: "ETHUSDT".to_string(),
+                ..limit_buy("eth_1", TimeInForce::Gtc)
+            },
+        )
+        .await
+    {
+        Err(PlatformError::SymbolNotTrading { symbol, status, .. }) => {
+            assert_eq!(symbol, "ETHUSDT");
+            assert_eq!(status, SymbolStatus::Halted);
+        }
+        other => panic!("expect SymbolNotTrading, got {:?}", other),
+    }
+    trade_mgr
+        .place_order(&market_type, limit_buy("btc_1", TimeInForce::Gtc))
+        .await
+        .unwrap();
+    let account = trade_mgr.get_account(&market_type).await.unwrap().unwrap();
+    assert_eq!(
+        balance(&account, "USDT").locked,
+        Decimal::from_str("100.1").unwrap()
+    );
+
+    // 配置HALTED为可交易状态后两者都加载
+    let config = test_config_with(
+        db_path,
+        &symbols,
+        &["1m"],
+        r#""active_symbol_statuses": ["TRADING", "HALTED"],"#,
+    );
+    let market_mgr = LocalMarketDataManager::new(config, clock, db.clone(), 100).unwrap();
+    market_mgr.init().await.unwrap();
+    assert_eq!(
+        market_mgr.get_active_symbols(&market_type).await.unwrap(),
+        symbols.to_vec()
+    );
+}
+
+#[tokio::test]
+async fn test_trade_warmup_contiguous_with_shared_timestamps() {
+    // 每100笔成交共用一个时间戳
+    let trade_ts = |seq_id: u64| 1000 + (seq_id / 100) * 10;
+    let trades = (1..=2500)
+        .map(|seq_id| test_trade(seq_id, trade_ts(seq_id), "100", "1"))
+        .collect::<Vec<_>>();
+    // cur_ts落在seq_id 1700~1799共用的时间戳上
+    let env = setup(trades, trade_ts(1750), test_balances(10000, Some(0))).await;
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+
+    let assert_contiguous = |trades: &[Trade], first: u64, last: u64| {
+        assert_eq!(
+            trades.iter().map(|t| t.seq_id).collect::<Vec<_>>(),
+            (first..=last).collect::<Vec<_>>()
+        );
+    };
+    let loaded = env
+        .market_mgr
+        .get_trades(&market_type, &symbol, None)
+        .await
+        .unwrap();
+    assert_contiguous(&loaded, 800, 1799);
+
+    // 推进时间后继续按seq_id加载，不重复不遗漏，返回最近cache_capacity笔
+    env.clock.set_cur_ts(trade_ts(2450));
+    let loaded = env
+        .market_mgr
+        .get_trades(&market_type, &symbol, None)
+        .await
+        .unwrap();
+    assert_contiguous(&loaded, 1500, 2499);
+}
+
+#[tokio::test]
+async fn test_user_trades_lookup_touches_only_matching() {
+    let metrics = Arc::new(InMemoryMetrics::new());
+    let order_count = 20u64;
+    // 每个订单由两笔各0.5的成交完全成交
+    let trades = (1..=order_count * 2)
+        .map(|i| test_trade(i, 1000 + i * 10, "100", "0.5"))
+        .collect();
+    let env = setup_with_metrics(
+        trades,
+        1000,
+        test_balances(10000, Some(0)),
+        "",
+        metrics.clone(),
+    )
+    .await;
+    let market_type = MarketType::BinanceSpot;
+    env.clock.register_hook(env.trade_mgr.clone());
+
+    let mut order_ids = vec![];
+    for i in 0..order_count {
+        let order = env
+            .trade_mgr
+            .place_order(
+                &market_type,
+                limit_buy(&format!("buy_{}", i), TimeInForce::Gtc),
+            )
+            .await
+            .unwrap();
+        order_ids.push(order.order_id);
+        env.clock.advance_to(1000 + (i + 1) * 20).await.unwrap();
+    }
+
+    let target = 7;
+    let user_trades = env
+        .trade_mgr
+        .get_user_trades_by_order(&market_type, "BTCUSDT", &order_ids[target])
+        .await
+        .unwrap();
+    assert_eq!(user_trades.len(), 2);
+    assert!(user_trades.iter().all(|t| t.order_id == order_ids[target]));
+    assert!(user_trades[0].timestamp < user_trades[1].timestamp);
+    assert_eq!(metrics.counter("trade.user_trades_scanned"), 2);
+
+    // 其他symbol或不存在的订单不访问任何成交
+    assert!(env
+        .trade_mgr
+        .get_user_trades_by_order(&market_type, "ETHUSDT", &order_ids[target])
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(env
+        .trade_mgr
+        .get_user_trades_by_order(&market_type, "BTCUSDT", "missing")
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(metrics.counter("trade.user_trades_scanned"), 2);
+
+    // 按时间范围查询只访问区间内的成交
+    let user_trades = env
+        .trade_mgr
+        .get_user_trades(&market_type, "BTCUSDT", Some(1101), Some(1140), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        user_trades.iter().map(|t| t.timestamp).collect::<Vec<_>>(),
+        vec![1110, 1120, 1130, 1140]
+    );
+    assert_eq!(metrics.counter("trade.user_trades_scanned"), 6);
+    let user_trades = env
+        .trade_mgr
+        .get_user_trades(&market_type, "BTCUSDT", None, None, Some(3))
+        .await
+        .unwrap();
+    assert_eq!(
+        user_trades.iter().map(|t| t.timestamp).collect::<Vec<_>>(),
+        vec![1380, 1390, 1400]
+    );
+    assert_eq!(metrics.counter("trade.user_trades_scanned"), 9);
+}
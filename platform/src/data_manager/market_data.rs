@@ -1,11 +1,11 @@
-use super::MarketDataManager;
+use super::{trade_reorder_buffer::TradeReorderBuffer, MarketDataManager, SymbolInitReport};
 use crate::{
     config::PlatformConfig,
     errors::{PlatformError, Result},
-    market_provider::MarketProvider,
+    market_provider::{EventRecvError, MarketProvider},
     models::{
         DepthData, ExchangeInfo, GetExchangeInfoRequest, KlineData, KlineInterval, MarketType,
-        SymbolInfo, Ticker24hr, Trade,
+        SymbolInfo, SymbolStatus, Ticker24hr, Trade,
     },
 };
 use async_trait::async_trait;
@@ -18,6 +18,11 @@ use std::{
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 
+// 单次kline请求的最大条数（binance上限1000），超过容量时分页拉取
+const KLINES_PAGE_LIMIT: usize = 1000;
+// 单次aggTrades请求的最大条数
+const TRADES_PAGE_LIMIT: u64 = 1000;
+
 struct Cache<T: Clone> {
     capacity: usize,
     data: BTreeMap<u64, T>,
@@ -35,46 +40,83 @@ impl<T: Clone> Cache<T> {
         self.capacity
     }
 
+    fn get_by_key(&self, key: u64) -> Option<&T> {
+        self.data.get(&key)
+    }
+
+    // cache已满时，早于最旧数据的key不写入
+    fn can_add(&self, key: u64) -> bool {
+        self.data.contains_key(&key)
+            || self.data.len() < self.capacity
+            || self
+                .data
+                .first_key_value()
+                .is_some_and(|(oldest, _)| *oldest < key)
+    }
+
+    // 已有key原地替换；新key在cache已满时淘汰最旧的一条，返回被淘汰的数据
     fn add(&mut self, key: u64, value: T) -> Option<T> {
-        if self.data.contains_key(&key) {
-            self.data.insert(key, value);
+        if let Some(existing) = self.data.get_mut(&key) {
+            *existing = value;
             return None;
         }
 
-        if self.data.len() >= self.capacity
-            && self.data.first_key_value().map(|(k, _)| k.clone()).unwrap() > key
-        {
+        if !self.can_add(key) {
             return None;
         }
 
+        // can_add保证此时最旧的key严格早于新key
         let mut ret = None;
         if self.data.len() >= self.capacity {
             ret = self.data.pop_first().map(|(_, value)| value);
         }
 
         self.data.insert(key, value);
-        return ret;
+        ret
     }
 
+    // 返回最近的limit条，limit为None或超过容量时按容量截断
     fn get(&self, limit: Option<usize>) -> Vec<T> {
+        if self.data.is_empty() {
+            return vec![];
+        }
+        // 先截断到已有数量，避免 len - limit 下溢
+        let limit = limit
+            .unwrap_or(self.capacity)
+            .min(self.capacity)
+            .min(self.data.len());
         self.data
             .values()
-            .skip(self.data.len() - limit.unwrap_or(self.data.len()))
+            .skip(self.data.len() - limit)
             .cloned()
             .collect()
     }
 }
 
+// kline写入cache的结果，stream会对未收盘的bar重复推送
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KlineAddResult {
+    New,              // 新的bar
+    ReplaceClosed,    // 替换已收盘的bar
+    UpdateInProgress, // 更新未收盘的bar
+    Closed,           // 未收盘的bar收盘
+    Ignored, // 未写入：open_time不在bar边界上、早于cache中最旧的bar，或已收盘的bar收到未收盘的更新
+}
+
 pub struct MarketData {
     market_types: Arc<Vec<MarketType>>,
     market_providers: Arc<HashMap<MarketType, Arc<dyn MarketProvider>>>,
     refresh_intervals: HashMap<MarketType, Duration>,
+    trade_reorders: HashMap<MarketType, (usize, u64)>, // (缓冲条数, 超时毫秒)
     klines: Arc<HashMap<(MarketType, String, KlineInterval), Arc<RwLock<Cache<KlineData>>>>>,
     trades: Arc<HashMap<(MarketType, String), Arc<RwLock<Cache<Trade>>>>>,
     depths: Arc<HashMap<(MarketType, String), Arc<RwLock<Option<DepthData>>>>>,
     tickers: Arc<HashMap<(MarketType, String), Arc<RwLock<Option<Ticker24hr>>>>>,
     symbol_infos: Arc<HashMap<(MarketType, String), Arc<RwLock<Option<SymbolInfo>>>>>,
     symbols: Arc<RwLock<HashMap<(MarketType, String, String), String>>>,
+    strict_symbol_inits: HashMap<MarketType, bool>,
+    active_symbol_statuses: HashMap<MarketType, Vec<SymbolStatus>>,
+    init_report: RwLock<SymbolInitReport>,
     shutdown_token: CancellationToken,
 }
 
@@ -91,9 +133,27 @@ impl MarketData {
         let mut tickers = HashMap::new();
         let mut symbol_infos = HashMap::new();
         let mut refresh_intervals = HashMap::new();
+        let mut trade_reorders = HashMap::new();
+        let mut strict_symbol_inits = HashMap::new();
+        let mut active_symbol_statuses = HashMap::new();
         for market_type in market_types.iter() {
+            strict_symbol_inits.insert(
+                market_type.clone(),
+                config.configs[market_type].strict_symbol_init,
+            );
+            active_symbol_statuses.insert(
+                market_type.clone(),
+                config.configs[market_type].active_symbol_statuses.clone(),
+            );
             let refresh_interval: u64 = config.configs[market_type].market_refresh_interval_secs;
             refresh_intervals.insert(market_type.clone(), Duration::from_secs(refresh_interval));
+            trade_reorders.insert(
+                market_type.clone(),
+                (
+                    config.configs[market_type].trade_reorder_window,
+                    config.configs[market_type].trade_reorder_timeout_milli_secs,
+                ),
+            );
 
             let cache_capacity: usize = config.configs[market_type].cache_capacity;
             let symbols: Vec<String> = config.configs[market_type].subscribed_symbols.clone();
@@ -132,16 +192,25 @@ impl MarketData {
             market_types,
             market_providers,
             refresh_intervals,
+            trade_reorders,
             klines: Arc::new(klines),
             trades: Arc::new(trades),
             depths: Arc::new(depths),
             tickers: Arc::new(tickers),
             symbol_infos: Arc::new(symbol_infos),
             symbols: Arc::new(RwLock::new(HashMap::new())),
+            strict_symbol_inits,
+            active_symbol_statuses,
+            init_report: RwLock::new(SymbolInitReport::default()),
             shutdown_token: CancellationToken::new(),
         })
     }
 
+    // 非严格模式下init跳过的symbol及原因
+    pub async fn init_report(&self) -> SymbolInitReport {
+        self.init_report.read().await.clone()
+    }
+
     async fn init(&self) -> Result<()> {
         for market_type in self.market_types.iter() {
             let market_provider =
@@ -174,8 +243,12 @@ impl MarketData {
                                         log::error!("Failed to add kline data for market type: {:?}, symbol: {}, kline_interval: {:?}", market_type_clone, symbol, kline_interval);
                                     }
                                 }
-                                Err(_e) => {
-                                    log::error!("Kline subscription error for market type: {:?}", market_type_clone);
+                                Err(EventRecvError::Lagged(n)) => {
+                                    log::error!("Kline subscription lagged for market type: {:?}, dropped {} events", market_type_clone, n);
+                                }
+                                Err(EventRecvError::Closed) => {
+                                    log::error!("Kline subscription closed for market type: {:?}", market_type_clone);
+                                    break;
                                 }
                             }
                         }
@@ -188,22 +261,45 @@ impl MarketData {
             let trades = self.trades.clone();
             let market_type_clone = market_type.clone();
             let mut trade_sub = market_provider.subscribe_trade();
+            let (reorder_window, reorder_timeout_ms) = self
+                .trade_reorders
+                .get(market_type)
+                .cloned()
+                .unwrap_or((0, 0));
             tokio::spawn(async move {
+                // 按seq_id顺序写入cache，减少乱序推送造成的短暂缺口
+                let mut reorder = TradeReorderBuffer::new(reorder_window, reorder_timeout_ms);
                 loop {
+                    let flush_delay = reorder.next_deadline().map(|deadline| {
+                        Duration::from_millis(
+                            deadline.saturating_sub(time::get_current_milli_timestamp()),
+                        )
+                    });
                     tokio::select! {
                         _ = shutdown_token.cancelled() => {
                             break;
                         }
+                        _ = async {
+                            match flush_delay {
+                                Some(delay) => tokio::time::sleep(delay).await,
+                                None => std::future::pending().await,
+                            }
+                        } => {
+                            let expired = reorder.flush_expired(time::get_current_milli_timestamp());
+                            Self::add_trades_logged(trades.clone(), &market_type_clone, expired).await;
+                        }
                         trade = trade_sub.recv() => {
                             match trade {
                                 Ok(trade) => {
-                                    let symbol = trade.symbol.clone();
-                                    if Self::add_trade_inner(trades.clone(), &market_type_clone, trade).await.is_err() {
-                                        log::error!("Failed to add trade data for market type: {:?}, symbol: {}", market_type_clone, symbol);
-                                    }
+                                    let released = reorder.push(trade, time::get_current_milli_timestamp());
+                                    Self::add_trades_logged(trades.clone(), &market_type_clone, released).await;
+                                }
+                                Err(EventRecvError::Lagged(n)) => {
+                                    log::error!("Trade subscription lagged for market type: {:?}, dropped {} events", market_type_clone, n);
                                 }
-                                Err(_e) => {
-                                    log::error!("Trade subscription error for market type: {:?}", market_type_clone);
+                                Err(EventRecvError::Closed) => {
+                                    log::error!("Trade subscription closed for market type: {:?}", market_type_clone);
+                                    break;
                                 }
                             }
                         }
@@ -230,8 +326,12 @@ impl MarketData {
                                         log::error!("Failed to add depth data for market type: {:?}, symbol: {:?}", market_type_clone, symbol);
                                     }
                                 }
-                                Err(_e) => {
-                                    log::error!("Depth subscription error for market type: {:?}", market_type_clone);
+                                Err(EventRecvError::Lagged(n)) => {
+                                    log::error!("Depth subscription lagged for market type: {:?}, dropped {} events", market_type_clone, n);
+                                }
+                                Err(EventRecvError::Closed) => {
+                                    log::error!("Depth subscription closed for market type: {:?}", market_type_clone);
+                                    break;
                                 }
                             }
                         }
@@ -258,8 +358,12 @@ impl MarketData {
                                         log::error!("Failed to add ticker data for market type: {:?}, symbol: {}", market_type_clone, symbol);
                                     }
                                 }
-                                Err(_e) => {
-                                    log::error!("Ticker subscription error for market type: {:?}", market_type_clone);
+                                Err(EventRecvError::Lagged(n)) => {
+                                    log::error!("Ticker subscription lagged for market type: {:?}, dropped {} events", market_type_clone, n);
+                                }
+                                Err(EventRecvError::Closed) => {
+                                    log::error!("Ticker subscription closed for market type: {:?}", market_type_clone);
+                                    break;
                                 }
                             }
                         }
@@ -373,97 +477,226 @@ impl MarketData {
                 .into_iter()
                 .collect();
 
+            let strict = self.strict_symbol_inits[market_type];
+            let active_statuses = &self.active_symbol_statuses[market_type];
             for symbol in symbols {
-                // 初始化kline数据
-                let intervals: Vec<KlineInterval> = self
-                    .klines
-                    .keys()
-                    .filter(|(mt, s, _)| mt == market_type && s == &symbol)
-                    .map(|(_, _, interval)| interval.clone())
-                    .collect();
-
-                for interval in intervals {
-                    info!(
-                        "Initializing kline data for {:?} {} {:?}",
-                        market_type, symbol, interval
-                    );
-                    if let Some(cache) =
-                        self.klines
-                            .get(&(market_type.clone(), symbol.clone(), interval.clone()))
-                    {
-                        let capacity = cache.read().await.get_capacity();
-
-                        let klines: Vec<KlineData> = market_provider
-                            .get_klines(crate::models::GetKlinesRequest {
-                                symbol: symbol.clone(),
-                                interval: interval.clone(),
-                                start_time: None,
-                                end_time: None,
-                                limit: Some(capacity as u32),
-                            })
+                let symbol_info = match self
+                    .symbol_infos
+                    .get(&(market_type.clone(), symbol.clone()))
+                {
+                    Some(cache) => cache.read().await.clone(),
+                    None => None,
+                };
+                // 非可交易状态的symbol不加载行情
+                match &symbol_info {
+                    Some(info) if !active_statuses.contains(&info.status) => {
+                        info!(
+                            "Skip initializing {:?} {} with status {:?}",
+                            market_type, symbol, info.status
+                        );
+                        continue;
+                    }
+                    _ => {}
+                }
+                // 非严格模式下跳过symbol_info缺失或初始化失败的symbol
+                let result = match symbol_info {
+                    None if !strict => Err(PlatformError::SymbolNotFound {
+                        market_type: market_type.clone(),
+                        symbol: symbol.clone(),
+                    }),
+                    _ => {
+                        self.init_symbol_from_api(market_provider, market_type, &symbol)
                             .await
-                            .map_err(|e| PlatformError::DataManagerError {
-                                message: format!("init data from api, fetch klines err: {}", e),
-                            })?;
-                        for kline in klines {
-                            if Self::add_kline_inner(self.klines.clone(), market_type, kline)
-                                .await
-                                .is_err()
-                            {
-                                log::error!(
-                                    "Failed to add kline data for market type: {:?}, symbol: {}, kline_interval: {:?}",
-                                    market_type,
-                                    symbol,
-                                    interval
-                                );
-                            }
-                        }
                     }
+                };
+                match result {
+                    Ok(()) => {}
+                    Err(e) if strict => return Err(e),
+                    Err(e) => self.init_report.write().await.add(market_type, &symbol, &e),
                 }
+            }
+        }
 
-                // 初始化trade数据
-                info!("Initializing trade data for {:?} {}", market_type, symbol);
-                if let Some(cache) = self.trades.get(&(market_type.clone(), symbol.clone())) {
-                    let capacity = cache.read().await.get_capacity();
-
-                    let trades: Vec<Trade> = market_provider
-                        .get_trades(crate::models::GetTradesRequest {
-                            symbol: symbol.clone(),
-                            from_id: None,
-                            start_time: None,
-                            end_time: None,
-                            limit: Some(capacity as u32),
-                        })
-                        .await
-                        .map_err(|e| PlatformError::DataManagerError {
-                            message: format!("init data from api, fetch trades err: {}", e),
-                        })?;
+        Ok(())
+    }
 
-                    for trade in trades {
-                        if Self::add_trade_inner(self.trades.clone(), market_type, trade)
-                            .await
-                            .is_err()
-                        {
-                            log::error!(
-                                "Failed to add trade data for market type: {:?}, symbol: {}",
+    // 从最新的kline开始向前分页，每页不超过KLINES_PAGE_LIMIT，每页请求各自占用限流权重
+    // 返回按open_time升序合并后的最多capacity条kline
+    async fn fetch_klines_paged(
+        market_provider: &Arc<dyn MarketProvider>,
+        symbol: &str,
+        interval: &KlineInterval,
+        capacity: usize,
+    ) -> Result<Vec<KlineData>> {
+        let mut pages: Vec<Vec<KlineData>> = Vec::new();
+        let mut remaining = capacity;
+        let mut end_time: Option<u64> = None;
+        while remaining > 0 {
+            let limit = remaining.min(KLINES_PAGE_LIMIT);
+            let mut page: Vec<KlineData> = market_provider
+                .get_klines(crate::models::GetKlinesRequest {
+                    symbol: symbol.to_string(),
+                    interval: interval.clone(),
+                    start_time: None,
+                    end_time,
+                    limit: Some(limit as u32),
+                })
+                .await
+                .map_err(|e| PlatformError::DataManagerError {
+                    message: format!("init data from api, fetch klines err: {}", e),
+                })?;
+            page.sort_by_key(|k| k.open_time);
+            // 防止交易所忽略end_time导致重复数据
+            if let Some(end_time) = end_time {
+                page.retain(|k| k.open_time <= end_time);
+            }
+            let fetched = page.len();
+            let earliest = page.first().map(|k| k.open_time);
+            pages.push(page);
+            remaining -= fetched.min(remaining);
+            match earliest {
+                // 不足一页说明已无更早的数据
+                Some(open_time) if fetched == limit && open_time > 0 => {
+                    end_time = Some(open_time - 1);
+                }
+                _ => break,
+            }
+        }
+
+        Ok(pages.into_iter().rev().flatten().collect())
+    }
+
+    // 先取最新一笔成交作为锚点，再从 锚点id - capacity + 1 开始按from_id向后分页
+    // 按id游标加载保证seq_id连续，不会因同一时间戳的多笔成交跨越时间窗口而重复或遗漏
+    async fn fetch_trades_by_id(
+        market_provider: &Arc<dyn MarketProvider>,
+        symbol: &str,
+        capacity: usize,
+    ) -> Result<Vec<Trade>> {
+        let get_trades = |from_id: Option<u64>, limit: u64| {
+            market_provider.get_trades(crate::models::GetTradesRequest {
+                symbol: symbol.to_string(),
+                from_id: from_id.map(|id| id.to_string()),
+                start_time: None,
+                end_time: None,
+                limit: Some(limit as u32),
+            })
+        };
+        let map_err = |e: PlatformError| PlatformError::DataManagerError {
+            message: format!("init data from api, fetch trades err: {}", e),
+        };
+
+        let last_id = match get_trades(None, 1).await.map_err(map_err)?.last() {
+            None => return Ok(vec![]),
+            Some(trade) => trade.seq_id,
+        };
+        let mut next_id = (last_id + 1).saturating_sub(capacity as u64);
+        let mut result: Vec<Trade> = Vec::with_capacity(capacity);
+        while next_id <= last_id {
+            let limit = (last_id - next_id + 1).min(TRADES_PAGE_LIMIT);
+            let mut page = get_trades(Some(next_id), limit).await.map_err(map_err)?;
+            page.sort_by_key(|t| t.seq_id);
+            let before = result.len();
+            for trade in page {
+                // 丢弃重复及锚点之后的成交
+                if trade.seq_id < next_id || trade.seq_id > last_id {
+                    continue;
+                }
+                if trade.seq_id != next_id {
+                    log::warn!(
+                        "trades not contiguous for {}, expected id: {}, got: {}",
+                        symbol,
+                        next_id,
+                        trade.seq_id
+                    );
+                }
+                next_id = trade.seq_id + 1;
+                result.push(trade);
+            }
+            if result.len() == before {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    async fn init_symbol_from_api(
+        &self,
+        market_provider: &Arc<dyn MarketProvider>,
+        market_type: &MarketType,
+        symbol: &String,
+    ) -> Result<()> {
+        // 初始化kline数据
+        let intervals: Vec<KlineInterval> = self
+            .klines
+            .keys()
+            .filter(|(mt, s, _)| mt == market_type && s == symbol)
+            .map(|(_, _, interval)| interval.clone())
+            .collect();
+
+        for interval in intervals {
+            info!(
+                "Initializing kline data for {:?} {} {:?}",
+                market_type, symbol, interval
+            );
+            if let Some(cache) =
+                self.klines
+                    .get(&(market_type.clone(), symbol.clone(), interval.clone()))
+            {
+                let capacity = cache.read().await.get_capacity();
+
+                let klines =
+                    Self::fetch_klines_paged(market_provider, symbol, &interval, capacity).await?;
+                for kline in klines {
+                    if Self::add_kline_inner(self.klines.clone(), market_type, kline)
+                        .await
+                        .is_err()
+                    {
+                        log::error!(
+                                "Failed to add kline data for market type: {:?}, symbol: {}, kline_interval: {:?}",
                                 market_type,
-                                symbol
+                                symbol,
+                                interval
                             );
-                        }
                     }
                 }
+            }
+        }
 
-                // 初始化depth数据（获取最新的）
-                info!("Initializing depth data for {:?} {}", market_type, symbol);
-                let depth = market_provider
-                    .get_depth(crate::models::GetDepthRequest {
-                        symbol: symbol.clone(),
-                        limit: None,
-                    })
+        // 初始化trade数据
+        info!("Initializing trade data for {:?} {}", market_type, symbol);
+        if let Some(cache) = self.trades.get(&(market_type.clone(), symbol.clone())) {
+            let capacity = cache.read().await.get_capacity();
+
+            let trades = Self::fetch_trades_by_id(market_provider, symbol, capacity).await?;
+            for trade in trades {
+                if Self::add_trade_inner(self.trades.clone(), market_type, trade)
                     .await
-                    .map_err(|e| PlatformError::DataManagerError {
-                        message: format!("init data from api, fetch depth err: {}", e),
-                    })?;
+                    .is_err()
+                {
+                    log::error!(
+                        "Failed to add trade data for market type: {:?}, symbol: {}",
+                        market_type,
+                        symbol
+                    );
+                }
+            }
+        }
+
+        // 初始化depth数据（获取最新的）
+        info!("Initializing depth data for {:?} {}", market_type, symbol);
+        let depth = market_provider
+            .get_depth(crate::models::GetDepthRequest {
+                symbol: symbol.clone(),
+                limit: None,
+            })
+            .await
+            .map_err(|e| PlatformError::DataManagerError {
+                message: format!("init data from api, fetch depth err: {}", e),
+            })?;
+        match depth {
+            Some(depth) => {
                 if Self::add_depth_inner(self.depths.clone(), market_type, depth)
                     .await
                     .is_err()
@@ -474,27 +707,20 @@ impl MarketData {
                         symbol
                     );
                 }
+            }
+            // 尚无盘口，等待stream推送
+            None => info!("No depth data yet for {:?} {}", market_type, symbol),
+        }
 
-                // 初始化ticker数据（获取最新的）
-                info!("Initializing ticker data for {:?} {}", market_type, symbol);
-                let tickers: Vec<Ticker24hr> = market_provider
-                    .get_ticker_24hr(crate::models::GetTicker24hrRequest {
-                        symbol: Some(symbol.clone()),
-                        symbols: None,
-                    })
-                    .await
-                    .map_err(|e| PlatformError::DataManagerError {
-                        message: format!("init data from api, fetch ticker err: {}", e),
-                    })?;
-                if tickers.is_empty() {
-                    return Err(PlatformError::DataManagerError {
-                        message: format!(
-                            "init data from api, fetch ticker got empty for {:?} {}",
-                            market_type, symbol
-                        ),
-                    });
-                }
-                let ticker = tickers.first().unwrap().clone();
+        // 初始化ticker数据（获取最新的）
+        info!("Initializing ticker data for {:?} {}", market_type, symbol);
+        let ticker = market_provider.get_ticker(symbol).await.map_err(|e| {
+            PlatformError::DataManagerError {
+                message: format!("init data from api, fetch ticker err: {}", e),
+            }
+        })?;
+        match ticker {
+            Some(ticker) => {
                 if Self::add_ticker_inner(self.tickers.clone(), market_type, ticker)
                     .await
                     .is_err()
@@ -506,23 +732,55 @@ impl MarketData {
                     );
                 }
             }
+            None => info!("No ticker data yet for {:?} {}", market_type, symbol),
         }
 
         Ok(())
     }
 
+    pub async fn add_kline(
+        &self,
+        market_type: &MarketType,
+        kline: KlineData,
+    ) -> Result<KlineAddResult> {
+        Self::add_kline_inner(self.klines.clone(), market_type, kline).await
+    }
+
     async fn add_kline_inner(
         klines: Arc<HashMap<(MarketType, String, KlineInterval), Arc<RwLock<Cache<KlineData>>>>>,
         market_type: &MarketType,
         kline: KlineData,
-    ) -> Result<Option<KlineData>> {
+    ) -> Result<KlineAddResult> {
         if let Some(cache) = klines.get(&(
             market_type.clone(),
             kline.symbol.clone(),
             kline.interval.clone(),
         )) {
+            // open_time不在bar边界上的kline无法与其他bar衔接，直接忽略
+            if !kline.interval.is_aligned(kline.open_time) {
+                log::warn!("Ignore misaligned kline: {:?}", kline);
+                return Ok(KlineAddResult::Ignored);
+            }
+            // 判断与写入在同一写锁内完成
             let mut cache = cache.write().await;
-            return Ok(cache.add(kline.open_time, kline));
+            let result = match cache.get_by_key(kline.open_time) {
+                None if !cache.can_add(kline.open_time) => KlineAddResult::Ignored,
+                None => KlineAddResult::New,
+                Some(existing) if existing.is_closed == 0 => {
+                    if kline.is_closed == 0 {
+                        KlineAddResult::UpdateInProgress
+                    } else {
+                        KlineAddResult::Closed
+                    }
+                }
+                // 延迟到达的未收盘更新不能覆盖已收盘的bar
+                Some(_) if kline.is_closed == 0 => KlineAddResult::Ignored,
+                Some(_) => KlineAddResult::ReplaceClosed,
+            };
+            if result != KlineAddResult::Ignored {
+                cache.add(kline.open_time, kline);
+            }
+            Ok(result)
         } else {
             Err(PlatformError::DataManagerError {
                 message: format!("Kline cache not found for: {:?}", kline),
@@ -545,6 +803,27 @@ impl MarketData {
         }
     }
 
+    // 依次写入cache，失败只记录日志
+    async fn add_trades_logged(
+        trades: Arc<HashMap<(MarketType, String), Arc<RwLock<Cache<Trade>>>>>,
+        market_type: &MarketType,
+        batch: Vec<Trade>,
+    ) {
+        for trade in batch {
+            let symbol = trade.symbol.clone();
+            if Self::add_trade_inner(trades.clone(), market_type, trade)
+                .await
+                .is_err()
+            {
+                log::error!(
+                    "Failed to add trade data for market type: {:?}, symbol: {}",
+                    market_type,
+                    symbol
+                );
+            }
+        }
+    }
+
     async fn add_depth_inner(
         depths: Arc<HashMap<(MarketType, String), Arc<RwLock<Option<DepthData>>>>>,
         market_type: &MarketType,
@@ -700,6 +979,35 @@ impl MarketDataManager for MarketData {
             ),
         })
     }
+
+    async fn get_active_symbols(&self, market_type: &MarketType) -> Result<Vec<String>> {
+        let active_statuses = match self.active_symbol_statuses.get(market_type) {
+            None => {
+                return Err(PlatformError::MarketNotFound {
+                    market_type: market_type.clone(),
+                    resource: "symbol info".to_string(),
+                })
+            }
+            Some(statuses) => statuses,
+        };
+        let mut symbols = Vec::new();
+        for ((mt, symbol), cache) in self.symbol_infos.iter() {
+            if mt != market_type {
+                continue;
+            }
+            // symbol_info未获取到时状态未知，不视为可交易
+            if cache
+                .read()
+                .await
+                .as_ref()
+                .is_some_and(|info| active_statuses.contains(&info.status))
+            {
+                symbols.push(symbol.clone());
+            }
+        }
+        symbols.sort();
+        Ok(symbols)
+    }
 }
 
 impl Drop for MarketData {
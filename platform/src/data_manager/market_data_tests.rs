@@ -1,12 +1,26 @@
 use crate::{
-    config::{Config, PlatformConfig},
-    data_manager::{market_data::MarketData, MarketDataManager},
-    market_provider::{binance_spot_market_provider::BinanceSpotMarketProvider, MarketProvider},
-    models::{DepthData, KlineData, KlineInterval, MarketType, Ticker24hr, Trade},
+    backtest::test_utils::unfiltered_symbol_info,
+    config::{ChannelOverflowPolicy, Config, PlatformConfig},
+    data_manager::{
+        market_data::{KlineAddResult, MarketData},
+        MarketDataManager,
+    },
+    errors::{PlatformError, Result},
+    market_provider::{
+        binance_spot_market_provider::BinanceSpotMarketProvider, EventReceiver, EventSender,
+        MarketProvider,
+    },
+    models::{
+        DepthData, ExchangeInfo, GapPolicy, GetDepthRequest, GetExchangeInfoRequest,
+        GetKlinesRequest, GetTicker24hrRequest, GetTradesRequest, KlineData, KlineInterval,
+        MarketType, SymbolStatus, Ticker24hr, Trade,
+    },
 };
+use async_trait::async_trait;
 use env_logger::Env;
 use json::dump;
 use log::info;
+use rust_decimal::Decimal;
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tempfile::NamedTempFile;
 use tokio::time::sleep;
@@ -290,3 +304,987 @@ async fn test_market_data_initialization() {
             == "ETHUSDT"
     );
 }
+
+fn local_market_data(cache_capacity: usize) -> MarketData {
+    let config_content = r#"
+    {
+        "markets": ["binance_spot"],
+        "db_path": "test_db_path",
+        "binance_spot": {
+            "cache_capacity": {cache_capacity},
+            "api_base_url": "",
+            "stream_base_url": "",
+            "stream_api_base_url": "",
+            "api_key": "",
+            "secret_key": "",
+            "subscribed_symbols": ["BTCUSDT"],
+            "subscribed_kline_intervals": ["1m", "1w", "1M"]
+        }
+    }
+    "#
+    .replace("{cache_capacity}", &cache_capacity.to_string());
+    let mut config_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config_file, config_content.as_bytes()).unwrap();
+    let config = Config::from_json(config_file.path().to_str().unwrap()).unwrap();
+    let platform_config = Arc::new(PlatformConfig::from_config(config).unwrap());
+    MarketData::new(platform_config, Arc::new(HashMap::new())).unwrap()
+}
+
+fn kline(open_time: u64, close: i64, is_closed: u64) -> KlineData {
+    KlineData {
+        symbol: "BTCUSDT".to_string(),
+        interval: KlineInterval::OneMinute,
+        open_time,
+        close_time: open_time + 59_999,
+        open: Decimal::from(100),
+        high: Decimal::from(close.max(100)),
+        low: Decimal::from(close.min(100)),
+        close: Decimal::from(close),
+        volume: Decimal::ONE,
+        quote_volume: Decimal::from(close),
+        taker_buy_volume: Decimal::ZERO,
+        taker_buy_quote_volume: Decimal::ZERO,
+        is_closed,
+    }
+}
+
+#[tokio::test]
+async fn test_add_kline_in_progress_updates() {
+    let market_data = local_market_data(2);
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    let interval = KlineInterval::OneMinute;
+
+    let results = [
+        kline(60_000, 100, 1),
+        kline(120_000, 101, 0),
+        kline(120_000, 102, 0),
+        kline(120_000, 103, 0),
+        kline(120_000, 104, 1),
+        // 收盘后延迟到达的未收盘更新
+        kline(120_000, 105, 0),
+        kline(120_000, 106, 1),
+        // cache已满，早于最旧bar的数据不写入
+        kline(0, 99, 1),
+    ];
+    let mut actual = vec![];
+    for k in results {
+        actual.push(market_data.add_kline(&market_type, k).await.unwrap());
+    }
+    assert_eq!(
+        actual,
+        vec![
+            KlineAddResult::New,
+            KlineAddResult::New,
+            KlineAddResult::UpdateInProgress,
+            KlineAddResult::UpdateInProgress,
+            KlineAddResult::Closed,
+            KlineAddResult::Ignored,
+            KlineAddResult::ReplaceClosed,
+            KlineAddResult::Ignored,
+        ]
+    );
+
+    let klines = market_data
+        .get_klines(&market_type, &symbol, &interval, None)
+        .await
+        .unwrap();
+    assert_eq!(klines.len(), 2);
+    assert_eq!(klines[0].open_time, 60_000);
+    assert_eq!(klines[1].open_time, 120_000);
+    assert_eq!(klines[1].close, Decimal::from(106));
+    assert_eq!(klines[1].is_closed, 1);
+
+    // 新bar挤出最旧的bar
+    assert_eq!(
+        market_data
+            .add_kline(&market_type, kline(180_000, 107, 0))
+            .await
+            .unwrap(),
+        KlineAddResult::New
+    );
+    let klines = market_data
+        .get_klines(&market_type, &symbol, &interval, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        klines.iter().map(|k| k.open_time).collect::<Vec<_>>(),
+        vec![120_000, 180_000]
+    );
+}
+
+#[tokio::test]
+async fn test_get_klines_limit_clamped_to_capacity() {
+    let market_data = local_market_data(3);
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    let interval = KlineInterval::OneMinute;
+
+    // 未满时limit超过已有数量
+    market_data
+        .add_kline(&market_type, kline(60_000, 100, 1))
+        .await
+        .unwrap();
+    let klines = market_data
+        .get_klines(&market_type, &symbol, &interval, Some(10))
+        .await
+        .unwrap();
+    assert_eq!(klines.len(), 1);
+
+    // 中间缺失的bar不占位
+    for open_time in [180_000, 240_000, 420_000] {
+        market_data
+            .add_kline(&market_type, kline(open_time, 100, 1))
+            .await
+            .unwrap();
+    }
+    let open_times =
+        |klines: Vec<KlineData>| klines.iter().map(|k| k.open_time).collect::<Vec<_>>();
+    for limit in [None, Some(3), Some(100)] {
+        let klines = market_data
+            .get_klines(&market_type, &symbol, &interval, limit)
+            .await
+            .unwrap();
+        assert_eq!(open_times(klines), vec![180_000, 240_000, 420_000]);
+    }
+    let klines = market_data
+        .get_klines(&market_type, &symbol, &interval, Some(2))
+        .await
+        .unwrap();
+    assert_eq!(open_times(klines), vec![240_000, 420_000]);
+}
+
+#[tokio::test]
+async fn test_add_kline_at_capacity_boundary() {
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    let interval = KlineInterval::OneMinute;
+    let open_times =
+        |klines: Vec<KlineData>| klines.iter().map(|k| k.open_time).collect::<Vec<_>>();
+    let full_market_data = || async {
+        let market_data = local_market_data(3);
+        for open_time in [120_000, 180_000, 240_000] {
+            market_data
+                .add_kline(&market_type, kline(open_time, 100, 1))
+                .await
+                .unwrap();
+        }
+        market_data
+    };
+
+    // 与最旧bar相同的key原地替换，不淘汰
+    let market_data = full_market_data().await;
+    assert_eq!(
+        market_data
+            .add_kline(&market_type, kline(120_000, 101, 1))
+            .await
+            .unwrap(),
+        KlineAddResult::ReplaceClosed
+    );
+    let klines = market_data
+        .get_klines(&market_type, &symbol, &interval, None)
+        .await
+        .unwrap();
+    assert_eq!(klines[0].close, Decimal::from(101));
+    assert_eq!(open_times(klines), vec![120_000, 180_000, 240_000]);
+
+    // 早于最旧bar的key不写入
+    let market_data = full_market_data().await;
+    assert_eq!(
+        market_data
+            .add_kline(&market_type, kline(60_000, 101, 1))
+            .await
+            .unwrap(),
+        KlineAddResult::Ignored
+    );
+    let klines = market_data
+        .get_klines(&market_type, &symbol, &interval, None)
+        .await
+        .unwrap();
+    assert_eq!(open_times(klines), vec![120_000, 180_000, 240_000]);
+
+    // 新key淘汰最旧的bar
+    let market_data = full_market_data().await;
+    assert_eq!(
+        market_data
+            .add_kline(&market_type, kline(300_000, 101, 1))
+            .await
+            .unwrap(),
+        KlineAddResult::New
+    );
+    let klines = market_data
+        .get_klines(&market_type, &symbol, &interval, None)
+        .await
+        .unwrap();
+    assert_eq!(open_times(klines), vec![180_000, 240_000, 300_000]);
+
+    // 容量为0时不写入且不panic
+    let market_data = local_market_data(0);
+    assert_eq!(
+        market_data
+            .add_kline(&market_type, kline(60_000, 100, 1))
+            .await
+            .unwrap(),
+        KlineAddResult::Ignored
+    );
+    assert!(market_data
+        .get_klines(&market_type, &symbol, &interval, None)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_get_on_empty_cache_and_oversized_limit() {
+    let market_data = local_market_data(5);
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    let interval = KlineInterval::OneMinute;
+
+    // 空cache
+    for limit in [None, Some(0), Some(1), Some(5), Some(usize::MAX)] {
+        assert!(market_data
+            .get_klines(&market_type, &symbol, &interval, limit)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(market_data
+            .get_trades(&market_type, &symbol, limit)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    // limit超过已有数量及容量时返回全部已有数据
+    for open_time in [60_000, 120_000] {
+        market_data
+            .add_kline(&market_type, kline(open_time, 100, 1))
+            .await
+            .unwrap();
+    }
+    for limit in [None, Some(3), Some(5), Some(6), Some(usize::MAX)] {
+        let klines = market_data
+            .get_klines(&market_type, &symbol, &interval, limit)
+            .await
+            .unwrap();
+        assert_eq!(
+            klines.iter().map(|k| k.open_time).collect::<Vec<_>>(),
+            vec![60_000, 120_000]
+        );
+    }
+    assert!(market_data
+        .get_klines(&market_type, &symbol, &interval, Some(0))
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_get_klines_with_gap_policy() {
+    let market_data = local_market_data(10);
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    let interval = KlineInterval::OneMinute;
+    // 缺失120_000、300_000、360_000
+    for (open_time, close) in [
+        (60_000, 100),
+        (180_000, 101),
+        (240_000, 102),
+        (420_000, 103),
+    ] {
+        market_data
+            .add_kline(&market_type, kline(open_time, close, 1))
+            .await
+            .unwrap();
+    }
+    let get = |gap_policy: GapPolicy| {
+        market_data.get_klines_with_gap_policy(&market_type, &symbol, &interval, None, gap_policy)
+    };
+
+    let sparse = get(GapPolicy::Sparse).await.unwrap();
+    assert_eq!(
+        sparse
+            .iter()
+            .map(|k| k.as_ref().map(|k| k.kline.open_time))
+            .collect::<Vec<_>>(),
+        vec![
+            Some(60_000),
+            None,
+            Some(180_000),
+            Some(240_000),
+            None,
+            None,
+            Some(420_000)
+        ]
+    );
+    assert!(sparse.iter().flatten().all(|k| !k.synthetic));
+
+    let dense = get(GapPolicy::Dense)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|k| k.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        dense.iter().map(|k| k.kline.open_time).collect::<Vec<_>>(),
+        (1..=7).map(|i| i * 60_000).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        dense.iter().map(|k| k.synthetic).collect::<Vec<_>>(),
+        vec![false, true, false, false, true, true, false]
+    );
+    // 填充的bar复制前一根bar的数据
+    assert_eq!(dense[1].kline.close, Decimal::from(100));
+    assert_eq!(dense[1].kline.close_time, 179_999);
+    assert_eq!(dense[4].kline.close, Decimal::from(102));
+    assert_eq!(dense[5].kline.close, Decimal::from(102));
+    assert_eq!(dense[6].kline.close, Decimal::from(103));
+
+    let skip = get(GapPolicy::Skip).await.unwrap();
+    assert_eq!(
+        skip.iter()
+            .map(|k| k.as_ref().unwrap().kline.open_time)
+            .collect::<Vec<_>>(),
+        vec![60_000, 180_000, 240_000, 420_000]
+    );
+    assert!(skip.iter().flatten().all(|k| !k.synthetic));
+}
+
+#[tokio::test]
+async fn test_add_kline_checks_calendar_alignment() {
+    let market_data = local_market_data(10);
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    let day = 24 * 60 * 60 * 1000;
+    // 2024-01-01 00:00 UTC，周一
+    let monday = 1_704_067_200_000;
+    let weekly = |open_time: u64| KlineData {
+        interval: KlineInterval::OneWeek,
+        close_time: open_time + 7 * day - 1,
+        ..kline(open_time, 100, 1)
+    };
+
+    assert_eq!(
+        market_data
+            .add_kline(&market_type, weekly(monday))
+            .await
+            .unwrap(),
+        KlineAddResult::New
+    );
+    // 按epoch整除周长得到的周四起点不是交易所的bar边界
+    let epoch_aligned = monday - 4 * day;
+    assert_eq!(epoch_aligned % KlineInterval::OneWeek.to_millis(), 0);
+    assert_eq!(
+        market_data
+            .add_kline(&market_type, weekly(epoch_aligned))
+            .await
+            .unwrap(),
+        KlineAddResult::Ignored
+    );
+    assert_eq!(
+        market_data
+            .add_kline(&market_type, kline(90_000, 100, 1))
+            .await
+            .unwrap(),
+        KlineAddResult::Ignored
+    );
+    let klines = market_data
+        .get_klines(&market_type, &symbol, &KlineInterval::OneWeek, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        klines.iter().map(|k| k.open_time).collect::<Vec<_>>(),
+        vec![monday]
+    );
+}
+
+#[tokio::test]
+async fn test_monthly_gap_filled_by_calendar_month() {
+    let market_data = local_market_data(10);
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    let day = 24 * 60 * 60 * 1000;
+    let jan_2024 = 1_704_067_200_000;
+    let (feb, mar, apr) = (
+        jan_2024 + 31 * day,
+        jan_2024 + 60 * day,
+        jan_2024 + 91 * day,
+    );
+    // 缺失2月与3月
+    for (open_time, close_time) in [(jan_2024, feb - 1), (apr, apr + 30 * day - 1)] {
+        let monthly = KlineData {
+            interval: KlineInterval::OneMonth,
+            close_time,
+            ..kline(open_time, 100, 1)
+        };
+        market_data.add_kline(&market_type, monthly).await.unwrap();
+    }
+
+    let dense = market_data
+        .get_klines_with_gap_policy(
+            &market_type,
+            &symbol,
+            &KlineInterval::OneMonth,
+            None,
+            GapPolicy::Dense,
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|k| k.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        dense
+            .iter()
+            .map(|k| (k.kline.open_time, k.kline.close_time, k.synthetic))
+            .collect::<Vec<_>>(),
+        vec![
+            (jan_2024, feb - 1, false),
+            (feb, mar - 1, true),
+            (mar, apr - 1, true),
+            (apr, apr + 30 * day - 1, false),
+        ]
+    );
+}
+
+// 模拟行情provider，ETHUSDT的kline拉取失败，halted_symbols在exchange info中为停牌状态
+// BTCUSDT有kline_history根1m kline（open_time从60_000开始）及trade_history笔成交（seq_id从1开始），
+// 并记录kline/trade请求；missing_data_symbols无盘口与ticker，failing_depth_symbols的盘口请求失败
+struct BrokenSymbolMarketProvider {
+    halted_symbols: Vec<String>,
+    missing_data_symbols: Vec<String>,
+    failing_depth_symbols: Vec<String>,
+    kline_history: u64,
+    kline_requests: std::sync::Mutex<Vec<GetKlinesRequest>>,
+    trade_history: u64,
+    trade_requests: std::sync::Mutex<Vec<GetTradesRequest>>,
+    kline_sender: EventSender<KlineData>,
+    trade_sender: EventSender<Trade>,
+    depth_sender: EventSender<DepthData>,
+    ticker_sender: EventSender<Ticker24hr>,
+}
+
+impl BrokenSymbolMarketProvider {
+    fn new(halted_symbols: &[&str]) -> Self {
+        Self {
+            halted_symbols: halted_symbols.iter().map(|s| s.to_string()).collect(),
+            missing_data_symbols: vec![],
+            failing_depth_symbols: vec![],
+            kline_history: 1,
+            kline_requests: std::sync::Mutex::new(vec![]),
+            trade_history: 0,
+            trade_requests: std::sync::Mutex::new(vec![]),
+            kline_sender: EventSender::new(16, ChannelOverflowPolicy::DropOldest),
+            trade_sender: EventSender::new(16, ChannelOverflowPolicy::DropOldest),
+            depth_sender: EventSender::new(16, ChannelOverflowPolicy::DropOldest),
+            ticker_sender: EventSender::new(16, ChannelOverflowPolicy::DropOldest),
+        }
+    }
+}
+
+#[async_trait]
+impl MarketProvider for BrokenSymbolMarketProvider {
+    async fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_klines(&self, req: GetKlinesRequest) -> Result<Vec<KlineData>> {
+        if req.symbol == "ETHUSDT" {
+            return Err(PlatformError::MarketProviderError {
+                message: "kline api unavailable".to_string(),
+            });
+        }
+        self.kline_requests.lock().unwrap().push(req.clone());
+        let end_time = req.end_time.unwrap_or(u64::MAX);
+        let klines: Vec<KlineData> = (1..=self.kline_history)
+            .map(|i| kline(i * 60_000, 101, 1))
+            .filter(|k| k.open_time <= end_time)
+            .collect();
+        let limit = (req.limit.unwrap_or(500) as usize).min(klines.len());
+        Ok(klines[klines.len() - limit..].to_vec())
+    }
+
+    async fn get_trades(&self, req: GetTradesRequest) -> Result<Vec<Trade>> {
+        self.trade_requests.lock().unwrap().push(req.clone());
+        // 每200笔成交共用一个时间戳
+        let trades: Vec<Trade> = (1..=self.trade_history)
+            .map(|seq_id| Trade {
+                symbol: req.symbol.clone(),
+                trade_id: seq_id.to_string(),
+                price: Decimal::from(100),
+                quantity: Decimal::ONE,
+                timestamp: 1000 + seq_id / 200,
+                is_buyer_maker: 0,
+                seq_id,
+            })
+            .collect();
+        let limit = req.limit.unwrap_or(500) as usize;
+        // 有from_id时从该id向后取，否则取最新的limit笔
+        Ok(match req.from_id {
+            Some(from_id) => {
+                let from_id: u64 = from_id.parse().unwrap();
+                trades
+                    .into_iter()
+                    .filter(|t| t.seq_id >= from_id)
+                    .take(limit)
+                    .collect()
+            }
+            None => {
+                let limit = limit.min(trades.len());
+                trades[trades.len() - limit..].to_vec()
+            }
+        })
+    }
+
+    async fn get_depth(&self, req: GetDepthRequest) -> Result<Option<DepthData>> {
+        if self.failing_depth_symbols.contains(&req.symbol) {
+            return Err(PlatformError::MarketProviderError {
+                message: "depth api unavailable".to_string(),
+            });
+        }
+        if self.missing_data_symbols.contains(&req.symbol) {
+            return Ok(None);
+        }
+        Ok(Some(DepthData {
+            symbol: req.symbol,
+            bids: vec![],
+            asks: vec![],
+            timestamp: 0,
+        }))
+    }
+
+    async fn get_ticker_24hr(&self, req: GetTicker24hrRequest) -> Result<Vec<Ticker24hr>> {
+        let symbol = req.symbol.unwrap_or_default();
+        if self.missing_data_symbols.contains(&symbol) {
+            return Ok(vec![]);
+        }
+        Ok(vec![Ticker24hr {
+            symbol,
+            last_price: Decimal::from(101),
+            last_qty: Decimal::ONE,
+            bid_price: Decimal::from(100),
+            bid_qty: Decimal::ONE,
+            ask_price: Decimal::from(102),
+            ask_qty: Decimal::ONE,
+            open_price: Decimal::from(100),
+            high_price: Decimal::from(102),
+            low_price: Decimal::from(99),
+            volume: Decimal::ONE,
+            quote_volume: Decimal::from(101),
+            open_time: 0,
+            close_time: 86_399_999,
+            count: 1,
+        }])
+    }
+
+    async fn get_exchange_info(&self, _req: GetExchangeInfoRequest) -> Result<ExchangeInfo> {
+        let mut symbols = vec![
+            unfiltered_symbol_info("BTCUSDT", "BTC", "USDT"),
+            unfiltered_symbol_info("ETHUSDT", "ETH", "USDT"),
+        ];
+        for info in symbols.iter_mut() {
+            if self.halted_symbols.contains(&info.symbol) {
+                info.status = SymbolStatus::Halted;
+            }
+        }
+        Ok(ExchangeInfo { symbols })
+    }
+
+    fn subscribe_kline(&self) -> EventReceiver<KlineData> {
+        self.kline_sender.subscribe()
+    }
+
+    fn subscribe_trade(&self) -> EventReceiver<Trade> {
+        self.trade_sender.subscribe()
+    }
+
+    fn subscribe_depth(&self) -> EventReceiver<DepthData> {
+        self.depth_sender.subscribe()
+    }
+
+    fn subscribe_ticker(&self) -> EventReceiver<Ticker24hr> {
+        self.ticker_sender.subscribe()
+    }
+}
+
+#[tokio::test]
+async fn test_subscribe_for_symbol_filters_events() {
+    let provider = BrokenSymbolMarketProvider::new(&[]);
+    let mut btc_trades = provider.subscribe_trade_for("BTCUSDT");
+    let mut btc_1m = provider.subscribe_kline_for("BTCUSDT", &KlineInterval::OneMinute);
+    let mut all_trades = provider.subscribe_trade();
+
+    let trade = |symbol: &str, seq_id: u64| Trade {
+        symbol: symbol.to_string(),
+        trade_id: seq_id.to_string(),
+        price: Decimal::from(100),
+        quantity: Decimal::ONE,
+        timestamp: seq_id,
+        is_buyer_maker: 0,
+        seq_id,
+    };
+    for (symbol, seq_id) in [
+        ("ETHUSDT", 1),
+        ("BTCUSDT", 2),
+        ("ETHUSDT", 3),
+        ("BTCUSDT", 4),
+    ] {
+        provider.trade_sender.send(trade(symbol, seq_id)).await;
+    }
+    let eth_1m = KlineData {
+        symbol: "ETHUSDT".to_string(),
+        ..kline(0, 101, 1)
+    };
+    let btc_5m = KlineData {
+        interval: KlineInterval::FiveMinutes,
+        ..kline(0, 102, 1)
+    };
+    for k in [eth_1m, btc_5m, kline(60_000, 103, 1)] {
+        provider.kline_sender.send(k).await;
+    }
+
+    assert_eq!(btc_trades.recv().await.unwrap().seq_id, 2);
+    assert_eq!(btc_trades.recv().await.unwrap().seq_id, 4);
+    let k = btc_1m.recv().await.unwrap();
+    assert_eq!(
+        (k.symbol.as_str(), k.interval, k.open_time),
+        ("BTCUSDT", KlineInterval::OneMinute, 60_000)
+    );
+    // 其他symbol/周期的事件不会返回给按symbol订阅的接收端
+    let wait = Duration::from_millis(50);
+    assert!(tokio::time::timeout(wait, btc_trades.recv()).await.is_err());
+    assert!(tokio::time::timeout(wait, btc_1m.recv()).await.is_err());
+
+    // 不带过滤的订阅仍收到全部事件
+    for seq_id in 1..=4 {
+        assert_eq!(all_trades.recv().await.unwrap().seq_id, seq_id);
+    }
+}
+
+fn broken_symbol_market_data(strict_symbol_init: bool, halted_symbols: &[&str]) -> MarketData {
+    market_data_with_provider(
+        strict_symbol_init,
+        100,
+        Arc::new(BrokenSymbolMarketProvider::new(halted_symbols)),
+    )
+}
+
+fn market_data_with_provider(
+    strict_symbol_init: bool,
+    cache_capacity: usize,
+    provider: Arc<dyn MarketProvider>,
+) -> MarketData {
+    market_data_with_extra_config(strict_symbol_init, cache_capacity, "", provider)
+}
+
+// extra_config为追加到market配置中的字段，如 "\"trade_reorder_window\": 10,"
+fn market_data_with_extra_config(
+    strict_symbol_init: bool,
+    cache_capacity: usize,
+    extra_config: &str,
+    provider: Arc<dyn MarketProvider>,
+) -> MarketData {
+    let config_content = r#"
+    {
+        "markets": ["binance_spot"],
+        "db_path": "test_db_path",
+        "binance_spot": {
+            "api_base_url": "",
+            "stream_base_url": "",
+            "stream_api_base_url": "",
+            "api_key": "",
+            "secret_key": "",
+            "cache_capacity": {cache_capacity},
+            "strict_symbol_init": {strict_symbol_init},
+            {extra_config}
+            "subscribed_symbols": ["BTCUSDT", "ETHUSDT"],
+            "subscribed_kline_intervals": ["1m"]
+        }
+    }
+    "#
+    .replace("{strict_symbol_init}", &strict_symbol_init.to_string())
+    .replace("{cache_capacity}", &cache_capacity.to_string())
+    .replace("{extra_config}", extra_config);
+    let mut config_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config_file, config_content.as_bytes()).unwrap();
+    let config = Config::from_json(config_file.path().to_str().unwrap()).unwrap();
+    let platform_config = Arc::new(PlatformConfig::from_config(config).unwrap());
+    let mut market_providers: HashMap<MarketType, Arc<dyn MarketProvider>> = HashMap::new();
+    market_providers.insert(MarketType::BinanceSpot, provider);
+    MarketData::new(platform_config, Arc::new(market_providers)).unwrap()
+}
+
+#[tokio::test]
+async fn test_non_strict_symbol_init_skips_broken_symbol() {
+    let market_data = broken_symbol_market_data(false, &[]);
+    MarketDataManager::init(&market_data).await.unwrap();
+
+    let report = market_data.init_report().await;
+    assert!(!report.is_ok());
+    assert_eq!(
+        report.failed_symbols(&MarketType::BinanceSpot),
+        vec!["ETHUSDT".to_string()]
+    );
+    assert!(report.failures[0].error.contains("kline api unavailable"));
+
+    // 正常symbol的数据不受影响
+    let klines = market_data
+        .get_klines(
+            &MarketType::BinanceSpot,
+            &"BTCUSDT".to_string(),
+            &KlineInterval::OneMinute,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(klines.len(), 1);
+}
+
+#[tokio::test]
+async fn test_strict_symbol_init_fails_on_broken_symbol() {
+    let market_data = broken_symbol_market_data(true, &[]);
+    let err = MarketDataManager::init(&market_data).await.unwrap_err();
+    assert!(err.to_string().contains("kline api unavailable"));
+    assert!(market_data.init_report().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_halted_symbol_skipped_at_init() {
+    // ETHUSDT停牌，不拉取其行情，严格模式下也能初始化成功
+    let market_data = broken_symbol_market_data(true, &["ETHUSDT"]);
+    MarketDataManager::init(&market_data).await.unwrap();
+    assert!(market_data.init_report().await.is_ok());
+    assert_eq!(
+        market_data
+            .get_active_symbols(&MarketType::BinanceSpot)
+            .await
+            .unwrap(),
+        vec!["BTCUSDT".to_string()]
+    );
+
+    let eth_klines = market_data
+        .get_klines(
+            &MarketType::BinanceSpot,
+            &"ETHUSDT".to_string(),
+            &KlineInterval::OneMinute,
+            None,
+        )
+        .await
+        .unwrap();
+    assert!(eth_klines.is_empty());
+    let btc_klines = market_data
+        .get_klines(
+            &MarketType::BinanceSpot,
+            &"BTCUSDT".to_string(),
+            &KlineInterval::OneMinute,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(btc_klines.len(), 1);
+}
+
+#[tokio::test]
+async fn test_init_klines_paginated_by_page_limit() {
+    let mut provider = BrokenSymbolMarketProvider::new(&["ETHUSDT"]);
+    provider.kline_history = 3500;
+    let provider = Arc::new(provider);
+    let market_data = market_data_with_provider(true, 3000, provider.clone());
+    MarketDataManager::init(&market_data).await.unwrap();
+
+    // 3000条分三页，每页1000条，从最新向前翻页
+    let requests = provider.kline_requests.lock().unwrap().clone();
+    assert_eq!(requests.len(), 3);
+    assert!(requests.iter().all(|r| r.limit == Some(1000)));
+    assert_eq!(
+        requests.iter().map(|r| r.end_time).collect::<Vec<_>>(),
+        vec![None, Some(2501 * 60_000 - 1), Some(1501 * 60_000 - 1)]
+    );
+
+    let klines = market_data
+        .get_klines(
+            &MarketType::BinanceSpot,
+            &"BTCUSDT".to_string(),
+            &KlineInterval::OneMinute,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        klines.iter().map(|k| k.open_time).collect::<Vec<_>>(),
+        (501..=3500).map(|i| i * 60_000).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_init_klines_stops_when_history_exhausted() {
+    let mut provider = BrokenSymbolMarketProvider::new(&["ETHUSDT"]);
+    provider.kline_history = 1200;
+    let provider = Arc::new(provider);
+    let market_data = market_data_with_provider(true, 3000, provider.clone());
+    MarketDataManager::init(&market_data).await.unwrap();
+
+    // 第二页不足1000条，不再继续请求
+    assert_eq!(provider.kline_requests.lock().unwrap().len(), 2);
+    let klines = market_data
+        .get_klines(
+            &MarketType::BinanceSpot,
+            &"BTCUSDT".to_string(),
+            &KlineInterval::OneMinute,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(klines.len(), 1200);
+    assert_eq!(klines.first().unwrap().open_time, 60_000);
+    assert_eq!(klines.last().unwrap().open_time, 1200 * 60_000);
+}
+
+#[tokio::test]
+async fn test_init_trades_paginated_by_from_id() {
+    let mut provider = BrokenSymbolMarketProvider::new(&["ETHUSDT"]);
+    provider.trade_history = 3000;
+    let provider = Arc::new(provider);
+    let market_data = market_data_with_provider(true, 2500, provider.clone());
+    MarketDataManager::init(&market_data).await.unwrap();
+
+    // 先取最新一笔作为锚点，再从3000 - 2500 + 1开始按from_id分页
+    let requests = provider.trade_requests.lock().unwrap().clone();
+    assert_eq!(
+        requests
+            .iter()
+            .map(|r| (r.from_id.clone(), r.limit))
+            .collect::<Vec<_>>(),
+        vec![
+            (None, Some(1)),
+            (Some("501".to_string()), Some(1000)),
+            (Some("1501".to_string()), Some(1000)),
+            (Some("2501".to_string()), Some(500)),
+        ]
+    );
+
+    let trades = market_data
+        .get_trades(&MarketType::BinanceSpot, &"BTCUSDT".to_string(), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        trades.iter().map(|t| t.seq_id).collect::<Vec<_>>(),
+        (501..=3000).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_stream_trades_reordered_before_caching() {
+    let provider = Arc::new(BrokenSymbolMarketProvider::new(&["ETHUSDT"]));
+    let market_data = market_data_with_extra_config(
+        true,
+        100,
+        r#""trade_reorder_window": 10, "trade_reorder_timeout_milli_secs": 200,"#,
+        provider.clone(),
+    );
+    MarketDataManager::init(&market_data).await.unwrap();
+
+    let trade = |seq_id: u64| Trade {
+        symbol: "BTCUSDT".to_string(),
+        trade_id: seq_id.to_string(),
+        price: Decimal::from(100),
+        quantity: Decimal::ONE,
+        timestamp: seq_id,
+        is_buyer_maker: 0,
+        seq_id,
+    };
+    let cached_seq_ids = || async {
+        market_data
+            .get_trades(&MarketType::BinanceSpot, &"BTCUSDT".to_string(), None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|t| t.seq_id)
+            .collect::<Vec<_>>()
+    };
+    let wait_for = |expected: Vec<u64>| async move {
+        for _ in 0..100 {
+            if cached_seq_ids().await == expected {
+                break;
+            }
+            sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(cached_seq_ids().await, expected);
+    };
+
+    // 缺少2时3、4暂存在缓冲中
+    for seq_id in [1, 3, 4] {
+        provider.trade_sender.send(trade(seq_id)).await;
+    }
+    wait_for(vec![1]).await;
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(cached_seq_ids().await, vec![1]);
+
+    provider.trade_sender.send(trade(2)).await;
+    wait_for(vec![1, 2, 3, 4]).await;
+
+    // 5一直缺失，超时后跳过缺口
+    for seq_id in [6, 7] {
+        provider.trade_sender.send(trade(seq_id)).await;
+    }
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(cached_seq_ids().await, vec![1, 2, 3, 4]);
+    wait_for(vec![1, 2, 3, 4, 6, 7]).await;
+}
+
+#[tokio::test]
+async fn test_init_tolerates_missing_depth_and_ticker() {
+    let mut provider = BrokenSymbolMarketProvider::new(&["ETHUSDT"]);
+    provider.missing_data_symbols = vec!["BTCUSDT".to_string()];
+    let provider = Arc::new(provider);
+    let btc = "BTCUSDT".to_string();
+
+    // 无数据不是错误
+    assert!(provider
+        .get_depth(GetDepthRequest {
+            symbol: btc.clone(),
+            limit: None,
+        })
+        .await
+        .unwrap()
+        .is_none());
+    assert!(provider.get_ticker(&btc).await.unwrap().is_none());
+
+    let market_data = market_data_with_provider(true, 100, provider.clone());
+    MarketDataManager::init(&market_data).await.unwrap();
+    assert!(market_data.init_report().await.is_ok());
+    assert!(market_data
+        .get_depth(&MarketType::BinanceSpot, &btc)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(market_data
+        .get_ticker(&MarketType::BinanceSpot, &btc)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_init_fails_on_depth_request_error() {
+    for strict_symbol_init in [true, false] {
+        let mut provider = BrokenSymbolMarketProvider::new(&["ETHUSDT"]);
+        provider.failing_depth_symbols = vec!["BTCUSDT".to_string()];
+        let market_data = market_data_with_provider(strict_symbol_init, 100, Arc::new(provider));
+        let result = MarketDataManager::init(&market_data).await;
+        if strict_symbol_init {
+            assert!(result
+                .unwrap_err()
+                .to_string()
+                .contains("depth api unavailable"));
+        } else {
+            result.unwrap();
+            let report = market_data.init_report().await;
+            assert_eq!(
+                report.failed_symbols(&MarketType::BinanceSpot),
+                vec!["BTCUSDT".to_string()]
+            );
+            assert!(report.failures[0].error.contains("depth api unavailable"));
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{
+    data_manager::{local_data_manager::Clock, MarketDataManager},
+    errors::{PlatformError, Result},
+    models::{
+        DepthData, KlineData, KlineInterval, MarketType, SymbolInfo, SymbolStatus, Ticker24hr,
+        Trade,
+    },
+};
+use async_trait::async_trait;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, RwLock},
+};
+
+type SymbolKey = (MarketType, String);
+
+#[derive(Default)]
+struct MemoryData {
+    symbol_infos: HashMap<SymbolKey, SymbolInfo>,
+    // 以下按open_time/seq_id/timestamp/close_time排序
+    klines: HashMap<(MarketType, String, KlineInterval), BTreeMap<u64, KlineData>>,
+    trades: HashMap<SymbolKey, BTreeMap<u64, Trade>>,
+    depths: HashMap<SymbolKey, BTreeMap<u64, DepthData>>,
+    tickers: HashMap<SymbolKey, BTreeMap<u64, Ticker24hr>>,
+}
+
+// 纯内存的行情数据，供测试直接预置数据，不依赖db
+// 与LocalMarketDataManager一致按clock过滤：kline按close_time，trade/depth按timestamp，ticker按close_time
+pub struct InMemoryMarketDataManager {
+    clock: Arc<Clock>,
+    data: RwLock<MemoryData>,
+}
+
+impl InMemoryMarketDataManager {
+    pub fn new(clock: Arc<Clock>) -> Self {
+        Self {
+            clock,
+            data: RwLock::new(MemoryData::default()),
+        }
+    }
+
+    pub fn add_symbol_info(&self, market_type: &MarketType, symbol_info: SymbolInfo) {
+        let mut data = self.data.write().unwrap();
+        data.symbol_infos.insert(
+            (market_type.clone(), symbol_info.symbol.clone()),
+            symbol_info,
+        );
+    }
+
+    // 相同open_time的kline会被覆盖
+    pub fn add_klines(&self, market_type: &MarketType, klines: &[KlineData]) {
+        let mut data = self.data.write().unwrap();
+        for kline in klines {
+            data.klines
+                .entry((
+                    market_type.clone(),
+                    kline.symbol.clone(),
+                    kline.interval.clone(),
+                ))
+                .or_default()
+                .insert(kline.open_time, kline.clone());
+        }
+    }
+
+    // 相同seq_id的成交会被覆盖
+    pub fn add_trades(&self, market_type: &MarketType, trades: &[Trade]) {
+        let mut data = self.data.write().unwrap();
+        for trade in trades {
+            data.trades
+                .entry((market_type.clone(), trade.symbol.clone()))
+                .or_default()
+                .insert(trade.seq_id, trade.clone());
+        }
+    }
+
+    // 按timestamp保存盘口快照，get_depth返回不晚于cur_ts的最近一个
+    pub fn add_depth(&self, market_type: &MarketType, depth: DepthData) {
+        let mut data = self.data.write().unwrap();
+        data.depths
+            .entry((market_type.clone(), depth.symbol.clone()))
+            .or_default()
+            .insert(depth.timestamp, depth);
+    }
+
+    pub fn add_ticker(&self, market_type: &MarketType, ticker: Ticker24hr) {
+        let mut data = self.data.write().unwrap();
+        data.tickers
+            .entry((market_type.clone(), ticker.symbol.clone()))
+            .or_default()
+            .insert(ticker.close_time, ticker);
+    }
+}
+
+#[async_trait]
+impl MarketDataManager for InMemoryMarketDataManager {
+    async fn init(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_klines(
+        &self,
+        market_type: &MarketType,
+        symbol: &String,
+        interval: &KlineInterval,
+        limit: Option<usize>,
+    ) -> Result<Vec<KlineData>> {
+        let cur_ts = self.clock.cur_ts();
+        let data = self.data.read().unwrap();
+        let klines = match data
+            .klines
+            .get(&(market_type.clone(), symbol.clone(), interval.clone()))
+        {
+            None => return Ok(vec![]),
+            Some(klines) => klines,
+        };
+        let mut result: Vec<KlineData> = klines
+            .values()
+            .filter(|kline| kline.close_time <= cur_ts)
+            .rev()
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        result.reverse();
+        Ok(result)
+    }
+
+    async fn get_klines_range(
+        &self,
+        market_type: &MarketType,
+        symbol: &String,
+        interval: &KlineInterval,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<KlineData>> {
+        let cur_ts = self.clock.cur_ts();
+        let data = self.data.read().unwrap();
+        let klines = match data
+            .klines
+            .get(&(market_type.clone(), symbol.clone(), interval.clone()))
+        {
+            None => return Ok(vec![]),
+            Some(klines) => klines,
+        };
+        if start_time > end_time {
+            return Ok(vec![]);
+        }
+        Ok(klines
+            .range(start_time..=end_time)
+            .map(|(_, kline)| kline)
+            .filter(|kline| kline.close_time <= cur_ts)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_trades(
+        &self,
+        market_type: &MarketType,
+        symbol: &String,
+        limit: Option<usize>,
+    ) -> Result<Vec<Trade>> {
+        let cur_ts = self.clock.cur_ts();
+        let data = self.data.read().unwrap();
+        let trades = match data.trades.get(&(market_type.clone(), symbol.clone())) {
+            None => return Ok(vec![]),
+            Some(trades) => trades,
+        };
+        let mut result: Vec<Trade> = trades
+            .values()
+            .filter(|trade| trade.timestamp <= cur_ts)
+            .rev()
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        result.reverse();
+        Ok(result)
+    }
+
+    async fn get_depth(
+        &self,
+        market_type: &MarketType,
+        symbol: &String,
+    ) -> Result<Option<DepthData>> {
+        let cur_ts = self.clock.cur_ts();
+        let data = self.data.read().unwrap();
+        Ok(data
+            .depths
+            .get(&(market_type.clone(), symbol.clone()))
+            .and_then(|depths| depths.range(..=cur_ts).next_back())
+            .map(|(_, depth)| depth.clone()))
+    }
+
+    async fn get_ticker(
+        &self,
+        market_type: &MarketType,
+        symbol: &String,
+    ) -> Result<Option<Ticker24hr>> {
+        let cur_ts = self.clock.cur_ts();
+        let data = self.data.read().unwrap();
+        Ok(data
+            .tickers
+            .get(&(market_type.clone(), symbol.clone()))
+            .and_then(|tickers| tickers.range(..=cur_ts).next_back())
+            .map(|(_, ticker)| ticker.clone()))
+    }
+
+    async fn get_symbol_info(
+        &self,
+        market_type: &MarketType,
+        symbol: &String,
+    ) -> Result<Option<SymbolInfo>> {
+        let data = self.data.read().unwrap();
+        match data
+            .symbol_infos
+            .get(&(market_type.clone(), symbol.clone()))
+        {
+            Some(info) => Ok(Some(info.clone())),
+            None => Err(PlatformError::SymbolNotFound {
+                market_type: market_type.clone(),
+                symbol: symbol.clone(),
+            }),
+        }
+    }
+
+    async fn get_symbol(
+        &self,
+        market_type: &MarketType,
+        base_asset: &String,
+        quote_asset: &String,
+    ) -> Result<Option<String>> {
+        let data = self.data.read().unwrap();
+        data.symbol_infos
+            .iter()
+            .find(|((mt, _), info)| {
+                mt == market_type
+                    && info.base_asset == *base_asset
+                    && info.quote_asset == *quote_asset
+            })
+            .map(|((_, symbol), _)| Some(symbol.clone()))
+            .ok_or(PlatformError::SymbolNotFound {
+                market_type: market_type.clone(),
+                symbol: format!("{}/{}", base_asset, quote_asset),
+            })
+    }
+
+    // 状态为Trading的symbol，按名称排序
+    async fn get_active_symbols(&self, market_type: &MarketType) -> Result<Vec<String>> {
+        let data = self.data.read().unwrap();
+        let mut symbols: Vec<String> = data
+            .symbol_infos
+            .iter()
+            .filter(|((mt, _), info)| mt == market_type && info.status == SymbolStatus::Trading)
+            .map(|((_, symbol), _)| symbol.clone())
+            .collect();
+        symbols.sort();
+        Ok(symbols)
+    }
+}
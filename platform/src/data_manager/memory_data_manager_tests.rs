@@ -0,0 +1,236 @@
+use crate::{
+    backtest::test_utils::unfiltered_symbol_info,
+    data_manager::{
+        local_data_manager::Clock, memory_data_manager::InMemoryMarketDataManager,
+        MarketDataManager,
+    },
+    models::{
+        DepthData, KlineData, KlineInterval, MarketType, PriceLevel, SymbolInfo, SymbolStatus,
+        Trade,
+    },
+};
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+const STEP_MS: u64 = 60_000;
+
+fn kline(i: u64) -> KlineData {
+    KlineData {
+        symbol: "BTCUSDT".to_string(),
+        interval: KlineInterval::OneMinute,
+        open_time: i * STEP_MS,
+        close_time: i * STEP_MS + STEP_MS - 1,
+        open: Decimal::from(100 + i),
+        high: Decimal::from(101 + i),
+        low: Decimal::from(99 + i),
+        close: Decimal::from(100 + i),
+        volume: Decimal::ONE,
+        quote_volume: Decimal::ZERO,
+        taker_buy_volume: Decimal::ZERO,
+        taker_buy_quote_volume: Decimal::ZERO,
+        is_closed: 1,
+    }
+}
+
+fn trade(seq_id: u64, timestamp: u64) -> Trade {
+    Trade {
+        symbol: "BTCUSDT".to_string(),
+        trade_id: seq_id.to_string(),
+        price: Decimal::from(100),
+        quantity: Decimal::ONE,
+        timestamp,
+        is_buyer_maker: 0,
+        seq_id,
+    }
+}
+
+fn depth(timestamp: u64, best_bid: u64) -> DepthData {
+    DepthData {
+        symbol: "BTCUSDT".to_string(),
+        bids: vec![PriceLevel {
+            price: Decimal::from(best_bid),
+            quantity: Decimal::ONE,
+        }],
+        asks: vec![PriceLevel {
+            price: Decimal::from(best_bid + 1),
+            quantity: Decimal::ONE,
+        }],
+        timestamp,
+    }
+}
+
+#[tokio::test]
+async fn test_klines_respect_cur_ts() {
+    let clock = Arc::new(Clock::new(0));
+    let mgr = InMemoryMarketDataManager::new(clock.clone());
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    let interval = KlineInterval::OneMinute;
+    // 乱序写入
+    let klines: Vec<KlineData> = (0..10).rev().map(kline).collect();
+    mgr.add_klines(&market_type, &klines);
+
+    // 第一根尚未收盘
+    clock.set_cur_ts(STEP_MS - 2);
+    assert!(mgr
+        .get_klines(&market_type, &symbol, &interval, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    clock.set_cur_ts(5 * STEP_MS - 1);
+    let result = mgr
+        .get_klines(&market_type, &symbol, &interval, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        result
+            .iter()
+            .map(|k| k.open_time / STEP_MS)
+            .collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+    let result = mgr
+        .get_klines(&market_type, &symbol, &interval, Some(2))
+        .await
+        .unwrap();
+    assert_eq!(
+        result
+            .iter()
+            .map(|k| k.open_time / STEP_MS)
+            .collect::<Vec<_>>(),
+        vec![3, 4]
+    );
+
+    let result = mgr
+        .get_klines_range(&market_type, &symbol, &interval, 2 * STEP_MS, 8 * STEP_MS)
+        .await
+        .unwrap();
+    assert_eq!(
+        result
+            .iter()
+            .map(|k| k.open_time / STEP_MS)
+            .collect::<Vec<_>>(),
+        vec![2, 3, 4]
+    );
+
+    // 未预置的数据返回空
+    assert!(mgr
+        .get_klines(&market_type, &symbol, &KlineInterval::OneHour, None)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_trades_respect_cur_ts() {
+    let clock = Arc::new(Clock::new(0));
+    let mgr = InMemoryMarketDataManager::new(clock.clone());
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    // 相同时间戳的多笔成交
+    mgr.add_trades(
+        &market_type,
+        &[trade(3, 200), trade(1, 100), trade(2, 200), trade(4, 300)],
+    );
+
+    clock.set_cur_ts(99);
+    assert!(mgr
+        .get_trades(&market_type, &symbol, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    clock.set_cur_ts(200);
+    let result = mgr.get_trades(&market_type, &symbol, None).await.unwrap();
+    assert_eq!(
+        result.iter().map(|t| t.seq_id).collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+    let result = mgr
+        .get_trades(&market_type, &symbol, Some(1))
+        .await
+        .unwrap();
+    assert_eq!(result.iter().map(|t| t.seq_id).collect::<Vec<_>>(), vec![3]);
+
+    clock.set_cur_ts(1000);
+    let result = mgr.get_trades(&market_type, &symbol, None).await.unwrap();
+    assert_eq!(result.len(), 4);
+}
+
+#[tokio::test]
+async fn test_depth_respect_cur_ts() {
+    let clock = Arc::new(Clock::new(0));
+    let mgr = InMemoryMarketDataManager::new(clock.clone());
+    let market_type = MarketType::BinanceSpot;
+    let symbol = "BTCUSDT".to_string();
+    mgr.add_depth(&market_type, depth(100, 50));
+    mgr.add_depth(&market_type, depth(300, 60));
+
+    clock.set_cur_ts(99);
+    assert!(mgr
+        .get_depth(&market_type, &symbol)
+        .await
+        .unwrap()
+        .is_none());
+
+    clock.set_cur_ts(299);
+    let result = mgr.get_depth(&market_type, &symbol).await.unwrap().unwrap();
+    assert_eq!(result.timestamp, 100);
+    assert_eq!(result.bids[0].price, Decimal::from(50));
+
+    clock.set_cur_ts(300);
+    let result = mgr.get_depth(&market_type, &symbol).await.unwrap().unwrap();
+    assert_eq!(result.timestamp, 300);
+    assert_eq!(result.bids[0].price, Decimal::from(60));
+
+    assert!(mgr
+        .get_depth(&market_type, &"ETHUSDT".to_string())
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_symbol_infos() {
+    let mgr = InMemoryMarketDataManager::new(Arc::new(Clock::new(0)));
+    let market_type = MarketType::BinanceSpot;
+    mgr.add_symbol_info(
+        &market_type,
+        unfiltered_symbol_info("ETHUSDT", "ETH", "USDT"),
+    );
+    mgr.add_symbol_info(
+        &market_type,
+        unfiltered_symbol_info("BTCUSDT", "BTC", "USDT"),
+    );
+    mgr.add_symbol_info(
+        &market_type,
+        SymbolInfo {
+            status: SymbolStatus::Halted,
+            ..unfiltered_symbol_info("LUNAUSDT", "LUNA", "USDT")
+        },
+    );
+
+    assert_eq!(
+        mgr.get_active_symbols(&market_type).await.unwrap(),
+        vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]
+    );
+    assert_eq!(
+        mgr.get_symbol(&market_type, &"ETH".to_string(), &"USDT".to_string())
+            .await
+            .unwrap(),
+        Some("ETHUSDT".to_string())
+    );
+    assert!(mgr
+        .get_symbol_info(&market_type, &"XRPUSDT".to_string())
+        .await
+        .is_err());
+    assert_eq!(
+        mgr.get_symbol_info(&market_type, &"LUNAUSDT".to_string())
+            .await
+            .unwrap()
+            .unwrap()
+            .status,
+        SymbolStatus::Halted
+    );
+}
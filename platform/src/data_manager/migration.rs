@@ -0,0 +1,503 @@
+use crate::{
+    config::DEFAULT_ACCOUNT_ID,
+    data_manager::db::{
+        create_balance_history_table, create_depth_tables, create_factor_state_table,
+        create_user_trades_order_index,
+    },
+    errors::{PlatformError, Result},
+};
+use db::sqlite::SQLiteDB;
+use std::sync::Arc;
+
+// 一个schema版本的变更，apply在事务内执行，成功后记录到schema_version
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    pub apply: SchemaStep,
+}
+
+pub type SchemaStep = fn(Arc<SQLiteDB>) -> Result<()>;
+
+// 引入迁移前的schema原样保留，之后的变更只能通过新的迁移步骤完成
+pub(crate) const BASELINE_SCHEMA: &[&str] = &[
+    r#"
+    CREATE TABLE IF NOT EXISTS symbol_info (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        market_type TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        status TEXT NOT NULL,
+        base_asset TEXT NOT NULL,
+        quote_asset TEXT NOT NULL,
+        base_asset_precision INTEGER,
+        quote_asset_precision INTEGER,
+        min_price TEXT,
+        max_price TEXT,
+        price_tick_size TEXT,
+        min_market_quantity TEXT,
+        max_market_quantity TEXT,
+        market_quantity_step_size TEXT,
+        min_quantity TEXT,
+        max_quantity TEXT,
+        quantity_step_size TEXT,
+        min_notional TEXT,
+        UNIQUE(market_type, symbol)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS kline (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        market_type TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        interval TEXT NOT NULL,
+        open_time INTEGER NOT NULL,
+        close_time INTEGER NOT NULL,
+        open TEXT NOT NULL,
+        high TEXT NOT NULL,
+        low TEXT NOT NULL,
+        close TEXT NOT NULL,
+        volume TEXT NOT NULL,
+        quote_volume TEXT NOT NULL,
+        taker_buy_volume TEXT NOT NULL,
+        taker_buy_quote_volume TEXT NOT NULL,
+        is_closed INTEGER NOT NULL,
+        UNIQUE(market_type, symbol, interval, open_time)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS trade (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        market_type TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        trade_id TEXT NOT NULL,
+        price TEXT NOT NULL,
+        quantity TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        is_buyer_maker INTEGER NOT NULL,
+        seq_id INTEGER NOT NULL,
+        UNIQUE(market_type, symbol, seq_id)
+    )
+    "#,
+    r#"
+    CREATE INDEX IF NOT EXISTS idx_trade_symbol_timestamp_seq_id
+    ON trade (market_type, symbol, timestamp, seq_id)
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS api_sync_ts (
+        market_type TEXT NOT NULL PRIMARY KEY,
+        last_sync_ts INTEGER NOT NULL
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS account_balance (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        market_type TEXT NOT NULL,
+        asset TEXT NOT NULL,
+        free TEXT NOT NULL,
+        locked TEXT NOT NULL,
+        updated_at INTEGER NOT NULL,
+        UNIQUE(market_type, asset)
+    )
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS orders (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        market_type TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        order_id TEXT NOT NULL,
+        client_order_id TEXT NOT NULL,
+        order_side TEXT NOT NULL,
+        order_type TEXT NOT NULL,
+        order_status TEXT NOT NULL,
+        order_price TEXT NOT NULL,
+        order_quantity TEXT NOT NULL,
+        executed_qty TEXT NOT NULL,
+        cummulative_quote_qty TEXT NOT NULL,
+        time_in_force TEXT NOT NULL,
+        stop_price TEXT NOT NULL,
+        iceberg_qty TEXT NOT NULL,
+        create_time INTEGER NOT NULL,
+        update_time INTEGER NOT NULL,
+        UNIQUE(market_type, symbol, client_order_id)
+    )
+    "#,
+    r#"
+    CREATE INDEX IF NOT EXISTS idx_orders_market_type_symbol_update_time
+    ON orders(market_type, symbol, update_time DESC)
+    "#,
+    r#"
+    CREATE INDEX IF NOT EXISTS idx_orders_market_type_status_update_time
+    ON orders(market_type, order_status, update_time DESC)
+    "#,
+    r#"
+    CREATE INDEX IF NOT EXISTS idx_orders_market_type_order_id
+    ON orders(market_type, symbol, order_id)
+    "#,
+    r#"
+    CREATE TABLE IF NOT EXISTS user_trades (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        market_type TEXT NOT NULL,
+        trade_id TEXT NOT NULL,
+        order_id TEXT NOT NULL,
+        symbol TEXT NOT NULL,
+        order_side TEXT NOT NULL,
+        trade_price TEXT NOT NULL,
+        trade_quantity TEXT NOT NULL,
+        commission TEXT NOT NULL,
+        commission_asset TEXT NOT NULL,
+        is_maker INTEGER NOT NULL,
+        timestamp INTEGER NOT NULL,
+        UNIQUE(market_type, symbol, trade_id)
+    )
+    "#,
+    r#"
+    CREATE INDEX IF NOT EXISTS idx_user_trades_market_type_order_id
+    ON user_trades(market_type, symbol, order_id)
+    "#,
+    r#"
+    CREATE INDEX IF NOT EXISTS idx_user_trades_market_type_symbol_timestamp
+    ON user_trades(market_type, symbol, timestamp DESC)
+    "#,
+];
+
+// 版本3引入account_id后的表定义原样保留，之后的列/索引变更由后续迁移完成
+const ACCOUNT_ID_SCHEMA: &[(&str, &[&str])] = &[
+    (
+        "api_sync_ts",
+        &[r#"
+        CREATE TABLE IF NOT EXISTS api_sync_ts (
+            market_type TEXT NOT NULL,
+            account_id TEXT NOT NULL DEFAULT 'default',
+            last_sync_ts INTEGER NOT NULL,
+            PRIMARY KEY(market_type, account_id)
+        )
+        "#],
+    ),
+    (
+        "account_balance",
+        &[r#"
+        CREATE TABLE IF NOT EXISTS account_balance (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            market_type TEXT NOT NULL,
+            account_id TEXT NOT NULL DEFAULT 'default',
+            asset TEXT NOT NULL,
+            free TEXT NOT NULL,
+            locked TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            UNIQUE(market_type, account_id, asset)
+        )
+        "#],
+    ),
+    (
+        "orders",
+        &[
+            r#"
+            CREATE TABLE IF NOT EXISTS orders (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                market_type TEXT NOT NULL,
+                account_id TEXT NOT NULL DEFAULT 'default',
+                symbol TEXT NOT NULL,
+                order_id TEXT NOT NULL,
+                client_order_id TEXT NOT NULL,
+                order_side TEXT NOT NULL,
+                order_type TEXT NOT NULL,
+                order_status TEXT NOT NULL,
+                order_price TEXT NOT NULL,
+                order_quantity TEXT NOT NULL,
+                executed_qty TEXT NOT NULL,
+                cummulative_quote_qty TEXT NOT NULL,
+                time_in_force TEXT NOT NULL,
+                stop_price TEXT NOT NULL,
+                iceberg_qty TEXT NOT NULL,
+                create_time INTEGER NOT NULL,
+                update_time INTEGER NOT NULL,
+                UNIQUE(market_type, account_id, symbol, client_order_id)
+            )
+            "#,
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_orders_market_type_symbol_update_time
+            ON orders(market_type, account_id, symbol, update_time DESC)
+            "#,
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_orders_market_type_status_update_time
+            ON orders(market_type, account_id, order_status, update_time DESC)
+            "#,
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_orders_market_type_order_id
+            ON orders(market_type, account_id, symbol, order_id)
+            "#,
+        ],
+    ),
+    (
+        "user_trades",
+        &[
+            r#"
+            CREATE TABLE IF NOT EXISTS user_trades (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                market_type TEXT NOT NULL,
+                account_id TEXT NOT NULL DEFAULT 'default',
+                trade_id TEXT NOT NULL,
+                order_id TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                order_side TEXT NOT NULL,
+                trade_price TEXT NOT NULL,
+                trade_quantity TEXT NOT NULL,
+                commission TEXT NOT NULL,
+                commission_asset TEXT NOT NULL,
+                is_maker INTEGER NOT NULL,
+                timestamp INTEGER NOT NULL,
+                UNIQUE(market_type, account_id, symbol, trade_id)
+            )
+            "#,
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_trades_market_type_order_id
+            ON user_trades(market_type, account_id, symbol, order_id)
+            "#,
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_user_trades_market_type_symbol_timestamp
+            ON user_trades(market_type, account_id, symbol, timestamp DESC)
+            "#,
+        ],
+    ),
+];
+
+// 按version递增排列，只能追加，已发布的步骤不可修改
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        apply: initial_schema,
+    },
+    Migration {
+        version: 2,
+        description: "factor_state table for persisted factor windows",
+        apply: create_factor_state_table,
+    },
+    Migration {
+        version: 3,
+        description: "account_id on account tables",
+        apply: add_account_id,
+    },
+    Migration {
+        version: 4,
+        description: "depth snapshot and diff tables",
+        apply: create_depth_tables,
+    },
+    Migration {
+        version: 5,
+        description: "user_trades order lookup index with timestamp",
+        apply: create_user_trades_order_index,
+    },
+    Migration {
+        version: 6,
+        description: "append-only balance_history table",
+        apply: create_balance_history_table,
+    },
+    Migration {
+        version: 7,
+        description: "orders.quote_order_qty for quote-denominated market orders",
+        apply: add_orders_quote_order_qty,
+    },
+];
+
+// 均为IF NOT EXISTS，对迁移前已建表的库执行为空操作
+fn initial_schema(db: Arc<SQLiteDB>) -> Result<()> {
+    for statement in BASELINE_SCHEMA {
+        db.execute_update(statement, &[])
+            .map_err(|e| PlatformError::DbError {
+                context: "Fail to create initial schema".to_string(),
+                source: e,
+            })?;
+    }
+    Ok(())
+}
+
+// 账户相关表按(market_type, account_id, ...)唯一，旧库的行归入默认账户
+fn add_account_id(db: Arc<SQLiteDB>) -> Result<()> {
+    for (table, create) in ACCOUNT_ID_SCHEMA {
+        if column_exists(db.clone(), table, "account_id")? {
+            continue;
+        }
+        add_column_if_missing(
+            db.clone(),
+            table,
+            "account_id",
+            &format!("TEXT NOT NULL DEFAULT '{}'", DEFAULT_ACCOUNT_ID),
+        )?;
+        rebuild_table(db.clone(), table, create)?;
+    }
+    Ok(())
+}
+
+fn add_orders_quote_order_qty(db: Arc<SQLiteDB>) -> Result<()> {
+    add_column_if_missing(db, "orders", "quote_order_qty", "TEXT NOT NULL DEFAULT '0'")
+}
+
+// 启动时调用，将库升级到最新版本，返回升级后的版本
+pub fn migrate(db: Arc<SQLiteDB>) -> Result<u32> {
+    run_migrations(db, MIGRATIONS)
+}
+
+pub fn run_migrations(db: Arc<SQLiteDB>, migrations: &[Migration]) -> Result<u32> {
+    if migrations.windows(2).any(|w| w[0].version >= w[1].version) {
+        return Err(PlatformError::DataManagerError {
+            message: "migrations must be sorted by strictly increasing version".to_string(),
+        });
+    }
+    create_schema_version_table(db.clone())?;
+
+    let current = schema_version(db.clone())?;
+    let mut version = current;
+    for migration in migrations.iter().filter(|m| m.version > current) {
+        db.with_transaction(|| apply_migration(db.clone(), migration))?;
+        log::info!(
+            "applied schema migration {}: {}",
+            migration.version,
+            migration.description
+        );
+        version = migration.version;
+    }
+    Ok(version)
+}
+
+fn apply_migration(db: Arc<SQLiteDB>, migration: &Migration) -> Result<()> {
+    // 并发启动时其他连接可能已完成该步骤
+    if schema_version(db.clone())? >= migration.version {
+        return Ok(());
+    }
+    (migration.apply)(db.clone()).map_err(|e| PlatformError::DataManagerError {
+        message: format!(
+            "apply migration {} ({}) failed: {}",
+            migration.version, migration.description, e
+        ),
+    })?;
+    db.execute_update(
+        "INSERT INTO schema_version (version, description, applied_at) VALUES (?, ?, ?)",
+        &[
+            &migration.version,
+            &migration.description,
+            &(time::get_current_milli_timestamp() as i64),
+        ],
+    )
+    .map_err(|e| PlatformError::DbError {
+        context: format!("Fail to record migration {}", migration.version),
+        source: e,
+    })?;
+    Ok(())
+}
+
+fn create_schema_version_table(db: Arc<SQLiteDB>) -> Result<()> {
+    let query = r#"
+        CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER NOT NULL PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )
+    "#;
+    db.execute_update(query, &[])
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to create schema_version table".to_string(),
+            source: e,
+        })?;
+    Ok(())
+}
+
+// 已应用的最大版本，未执行过迁移时为0
+pub fn schema_version(db: Arc<SQLiteDB>) -> Result<u32> {
+    let result = db
+        .execute_query(
+            "SELECT COALESCE(MAX(version), 0) AS version FROM schema_version",
+            &[],
+        )
+        .map_err(|e| PlatformError::DbError {
+            context: "Fail to query schema_version".to_string(),
+            source: e,
+        })?;
+    Ok(result
+        .first()
+        .and_then(|row| row.get_i64("version"))
+        .unwrap_or(0) as u32)
+}
+
+pub fn column_exists(db: Arc<SQLiteDB>, table: &str, column: &str) -> Result<bool> {
+    Ok(table_columns(db, table)?.iter().any(|name| name == column))
+}
+
+// ALTER TABLE ADD COLUMN，列已存在时跳过，便于迁移步骤对新建库重复执行
+pub fn add_column_if_missing(
+    db: Arc<SQLiteDB>,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<()> {
+    if column_exists(db.clone(), table, column)? {
+        return Ok(());
+    }
+    db.execute_update(
+        &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+        &[],
+    )
+    .map_err(|e| PlatformError::DbError {
+        context: format!("Fail to add column {}.{}", table, column),
+        source: e,
+    })?;
+    Ok(())
+}
+
+// SQLite无法修改唯一键/主键：旧表改名后按create给出的建表/建索引语句重建，拷贝两边共有的列
+pub fn rebuild_table(db: Arc<SQLiteDB>, table: &str, create: &[&str]) -> Result<()> {
+    let map_err = |context: String| move |e| PlatformError::DbError { context, source: e };
+    // 索引随表改名保留原名，先删除以便create重建到新表上
+    let indexes = db
+        .execute_query(
+            "SELECT name FROM sqlite_master WHERE type = 'index' AND tbl_name = ?1 AND sql IS NOT NULL",
+            &[&table],
+        )
+        .map_err(map_err(format!("Fail to list indexes of {}", table)))?;
+    for index in indexes.rows.iter().filter_map(|row| row.get_string("name")) {
+        db.execute_update(&format!("DROP INDEX {}", index), &[])
+            .map_err(map_err(format!("Fail to drop index {}", index)))?;
+    }
+
+    let old_table = format!("{}_old", table);
+    db.execute_update(
+        &format!("ALTER TABLE {} RENAME TO {}", table, old_table),
+        &[],
+    )
+    .map_err(map_err(format!("Fail to rename table {}", table)))?;
+    for statement in create {
+        db.execute_update(statement, &[])
+            .map_err(map_err(format!("Fail to recreate table {}", table)))?;
+    }
+
+    let old_columns = table_columns(db.clone(), &old_table)?;
+    let columns = table_columns(db.clone(), table)?
+        .into_iter()
+        .filter(|column| old_columns.contains(column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    db.execute_update(
+        &format!(
+            "INSERT INTO {} ({}) SELECT {} FROM {}",
+            table, columns, columns, old_table
+        ),
+        &[],
+    )
+    .map_err(map_err(format!("Fail to copy rows into {}", table)))?;
+    db.execute_update(&format!("DROP TABLE {}", old_table), &[])
+        .map_err(map_err(format!("Fail to drop table {}", old_table)))?;
+    Ok(())
+}
+
+fn table_columns(db: Arc<SQLiteDB>, table: &str) -> Result<Vec<String>> {
+    let result = db
+        .execute_query(&format!("PRAGMA table_info({})", table), &[])
+        .map_err(|e| PlatformError::DbError {
+            context: format!("Fail to query table_info of {}", table),
+            source: e,
+        })?;
+    Ok(result
+        .rows
+        .iter()
+        .filter_map(|row| row.get_string("name"))
+        .collect())
+}
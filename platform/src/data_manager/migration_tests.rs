@@ -0,0 +1,440 @@
+use crate::{
+    config::DEFAULT_ACCOUNT_ID,
+    data_manager::{
+        db::{
+            create_user_trades_table, get_account, get_last_sync_ts, get_orders, get_user_trades,
+            update_last_sync_ts, update_orders,
+        },
+        migration::{
+            add_column_if_missing, column_exists, migrate, run_migrations, schema_version,
+            Migration, BASELINE_SCHEMA, MIGRATIONS,
+        },
+    },
+    errors::{PlatformError, Result},
+    models::{MarketType, Order, OrderSide, OrderType, PlaceOrderRequest, TimeInForce},
+};
+use db::sqlite::SQLiteDB;
+use rust_decimal::Decimal;
+use std::{str::FromStr, sync::Arc};
+use tempfile::NamedTempFile;
+
+fn new_db(file: &NamedTempFile) -> Arc<SQLiteDB> {
+    Arc::new(SQLiteDB::new(file.path().to_str().unwrap()).unwrap())
+}
+
+fn create_widget(db: Arc<SQLiteDB>) -> Result<()> {
+    db.execute_update(
+        "CREATE TABLE IF NOT EXISTS widget (id INTEGER PRIMARY KEY, name TEXT NOT NULL)",
+        &[],
+    )
+    .unwrap();
+    Ok(())
+}
+
+fn add_widget_color(db: Arc<SQLiteDB>) -> Result<()> {
+    add_column_if_missing(db, "widget", "color", "TEXT NOT NULL DEFAULT 'red'")
+}
+
+fn add_widget_size_then_fail(db: Arc<SQLiteDB>) -> Result<()> {
+    add_column_if_missing(db, "widget", "size", "INTEGER")?;
+    Err(PlatformError::DataManagerError {
+        message: "boom".to_string(),
+    })
+}
+
+const WIDGET_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create widget",
+        apply: create_widget,
+    },
+    Migration {
+        version: 2,
+        description: "add widget color",
+        apply: add_widget_color,
+    },
+];
+
+#[test]
+fn test_migrate_fresh_db() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    let latest = MIGRATIONS.last().unwrap().version;
+
+    assert_eq!(migrate(db.clone()).unwrap(), latest);
+    for table in [
+        "symbol_info",
+        "kline",
+        "trade",
+        "orders",
+        "user_trades",
+        "factor_state",
+    ] {
+        assert!(db.table_exists(table).unwrap(), "table: {}", table);
+    }
+    // 重复执行为空操作
+    assert_eq!(migrate(db.clone()).unwrap(), latest);
+    let rows = db
+        .execute_query("SELECT COUNT(*) AS cnt FROM schema_version", &[])
+        .unwrap();
+    assert_eq!(
+        rows.first().unwrap().get_i64("cnt").unwrap(),
+        MIGRATIONS.len() as i64
+    );
+}
+
+#[test]
+fn test_migrate_appends_new_steps() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    // 表已存在，没有schema_version
+    create_widget(db.clone()).unwrap();
+    db.execute_update("INSERT INTO widget (id, name) VALUES (1, 'a')", &[])
+        .unwrap();
+    assert!(!column_exists(db.clone(), "widget", "color").unwrap());
+
+    assert_eq!(
+        run_migrations(db.clone(), &WIDGET_MIGRATIONS[..1]).unwrap(),
+        1
+    );
+    assert_eq!(schema_version(db.clone()).unwrap(), 1);
+    assert!(!column_exists(db.clone(), "widget", "color").unwrap());
+
+    // 重新打开库后执行新增的迁移步骤
+    drop(db);
+    let db = new_db(&file);
+    assert_eq!(run_migrations(db.clone(), WIDGET_MIGRATIONS).unwrap(), 2);
+    assert_eq!(schema_version(db.clone()).unwrap(), 2);
+    assert!(column_exists(db.clone(), "widget", "color").unwrap());
+    let rows = db
+        .execute_query("SELECT color FROM widget WHERE id = 1", &[])
+        .unwrap();
+    assert_eq!(
+        rows.first().unwrap().get_string("color"),
+        Some("red".to_string())
+    );
+    db.execute_update("INSERT INTO widget (id, name) VALUES (2, 'b')", &[])
+        .unwrap();
+}
+
+// 未引入迁移前创建的库：按基线schema建表并写入数据，没有schema_version
+fn seed_baseline_db(db: Arc<SQLiteDB>) {
+    for statement in BASELINE_SCHEMA {
+        db.execute_update(statement, &[]).unwrap();
+    }
+    db.execute_update(
+        r#"
+        INSERT INTO orders (
+            market_type, symbol, order_id, client_order_id, order_side, order_type,
+            order_status, order_price, order_quantity, executed_qty, cummulative_quote_qty,
+            time_in_force, stop_price, iceberg_qty, create_time, update_time
+        ) VALUES (
+            'binance_spot', 'BTCUSDT', '1', 'order_1', 'BUY', 'LIMIT',
+            'NEW', '100', '1', '0', '0', 'GTC', '0', '0', 1000, 1000
+        )
+        "#,
+        &[],
+    )
+    .unwrap();
+    db.execute_update(
+        r#"
+        INSERT INTO user_trades (
+            market_type, trade_id, order_id, symbol, order_side, trade_price,
+            trade_quantity, commission, commission_asset, is_maker, timestamp
+        ) VALUES ('binance_spot', '7', '1', 'BTCUSDT', 'BUY', '100', '0.5', '0', 'BTC', 1, 1500)
+        "#,
+        &[],
+    )
+    .unwrap();
+    db.execute_update(
+        r#"
+        INSERT INTO account_balance (market_type, asset, free, locked, updated_at)
+        VALUES ('binance_spot', 'USDT', '900', '100', 1500)
+        "#,
+        &[],
+    )
+    .unwrap();
+    db.execute_update(
+        "INSERT INTO api_sync_ts (market_type, last_sync_ts) VALUES ('binance_spot', 1500)",
+        &[],
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_migrate_old_schema_db() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    seed_baseline_db(db.clone());
+    for table in ["orders", "user_trades", "account_balance", "api_sync_ts"] {
+        assert!(!column_exists(db.clone(), table, "account_id").unwrap());
+    }
+
+    assert_eq!(
+        migrate(db.clone()).unwrap(),
+        MIGRATIONS.last().unwrap().version
+    );
+    for table in ["orders", "user_trades", "account_balance", "api_sync_ts"] {
+        assert!(
+            column_exists(db.clone(), table, "account_id").unwrap(),
+            "table: {}",
+            table
+        );
+    }
+
+    // 旧数据归入默认账户，可通过正常的读取接口读出
+    let market_type = MarketType::BinanceSpot;
+    let orders = get_orders(
+        db.clone(),
+        &market_type,
+        DEFAULT_ACCOUNT_ID,
+        "BTCUSDT",
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].client_order_id, "order_1");
+    assert_eq!(orders[0].quote_order_qty, Decimal::ZERO);
+    let trades = get_user_trades(
+        db.clone(),
+        &market_type,
+        DEFAULT_ACCOUNT_ID,
+        "BTCUSDT",
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(trades.len(), 1);
+    assert_eq!(trades[0].trade_id, "7");
+    assert_eq!(trades[0].trade_quantity, Decimal::from_str("0.5").unwrap());
+    let account = get_account(db.clone(), &market_type, DEFAULT_ACCOUNT_ID)
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.balances.len(), 1);
+    assert_eq!(account.balances[0].free, Decimal::from(900));
+    assert_eq!(account.balances[0].locked, Decimal::from(100));
+    assert_eq!(
+        get_last_sync_ts(db.clone(), &market_type, DEFAULT_ACCOUNT_ID).unwrap(),
+        Some(1500)
+    );
+}
+
+#[test]
+fn test_failed_migration_rolls_back() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    let migrations = [
+        Migration {
+            version: 1,
+            description: "create widget",
+            apply: create_widget,
+        },
+        Migration {
+            version: 2,
+            description: "add widget size",
+            apply: add_widget_size_then_fail,
+        },
+    ];
+
+    assert!(run_migrations(db.clone(), &migrations).is_err());
+    // 版本1已提交，版本2的变更整体回滚
+    assert_eq!(schema_version(db.clone()).unwrap(), 1);
+    assert!(db.table_exists("widget").unwrap());
+    assert!(!column_exists(db.clone(), "widget", "size").unwrap());
+}
+
+#[test]
+fn test_unsorted_migrations_rejected() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    let migrations = [
+        Migration {
+            version: 2,
+            description: "add widget color",
+            apply: add_widget_color,
+        },
+        Migration {
+            version: 1,
+            description: "create widget",
+            apply: create_widget,
+        },
+    ];
+    assert!(run_migrations(db.clone(), &migrations).is_err());
+    assert!(!db.table_exists("widget").unwrap());
+}
+
+#[test]
+fn test_user_trades_order_index_used() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    // 旧schema：按订单查询的索引不含timestamp
+    create_user_trades_table(db.clone()).unwrap();
+    db.execute_update("DROP INDEX idx_user_trades_symbol_order_id_timestamp", &[])
+        .unwrap();
+    db.execute_update(
+        "CREATE INDEX idx_user_trades_market_type_order_id ON user_trades(market_type, account_id, symbol, order_id)",
+        &[],
+    )
+    .unwrap();
+    // 旧索引下查询计划选择(symbol, timestamp)索引，扫描该symbol的全部成交
+    let plan = query_plan_of_order_lookup(db.clone());
+    assert!(
+        plan.contains("idx_user_trades_market_type_symbol_timestamp"),
+        "plan: {}",
+        plan
+    );
+
+    assert_eq!(
+        migrate(db.clone()).unwrap(),
+        MIGRATIONS.last().unwrap().version
+    );
+    let plan = query_plan_of_order_lookup(db.clone());
+    assert!(
+        plan.contains("idx_user_trades_symbol_order_id_timestamp") && plan.contains("order_id=?"),
+        "plan: {}",
+        plan
+    );
+    assert!(!plan.contains("TEMP B-TREE"), "plan: {}", plan);
+}
+
+#[test]
+fn test_balance_history_migration() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    // 升级前的库停留在版本5，只有最新快照表
+    assert_eq!(run_migrations(db.clone(), &MIGRATIONS[..5]).unwrap(), 5);
+    assert!(!db.table_exists("balance_history").unwrap());
+
+    assert_eq!(
+        migrate(db.clone()).unwrap(),
+        MIGRATIONS.last().unwrap().version
+    );
+    assert!(db.table_exists("balance_history").unwrap());
+    assert!(db.table_exists("account_balance").unwrap());
+}
+
+#[test]
+fn test_orders_quote_order_qty_migration() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    let market_type = MarketType::BinanceSpot;
+    // 版本3建出的orders表没有quote_order_qty列，升级前写入的订单也没有该值
+    assert_eq!(run_migrations(db.clone(), &MIGRATIONS[..6]).unwrap(), 6);
+    assert!(column_exists(db.clone(), "orders", "account_id").unwrap());
+    assert!(!column_exists(db.clone(), "orders", "quote_order_qty").unwrap());
+    db.execute_update(
+        r#"
+        INSERT INTO orders (
+            market_type, account_id, symbol, order_id, client_order_id, order_side, order_type,
+            order_status, order_price, order_quantity, executed_qty, cummulative_quote_qty,
+            time_in_force, stop_price, iceberg_qty, create_time, update_time
+        ) VALUES (
+            'binance_spot', 'default', 'BTCUSDT', '1', 'order_1', 'BUY', 'LIMIT',
+            'NEW', '100', '1', '0', '0', 'GTC', '0', '0', 1000, 1000
+        )
+        "#,
+        &[],
+    )
+    .unwrap();
+
+    assert_eq!(
+        migrate(db.clone()).unwrap(),
+        MIGRATIONS.last().unwrap().version
+    );
+    assert!(column_exists(db.clone(), "orders", "quote_order_qty").unwrap());
+    let orders = get_orders(
+        db.clone(),
+        &market_type,
+        DEFAULT_ACCOUNT_ID,
+        "BTCUSDT",
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    assert_eq!(orders.len(), 1);
+    assert_eq!(orders[0].quote_order_qty, Decimal::ZERO);
+}
+
+#[test]
+fn test_account_id_migration_rebuilds_unique_keys() {
+    let file = NamedTempFile::new().unwrap();
+    let db = new_db(&file);
+    // 多账户之前的orders/api_sync_ts：唯一键不含account_id
+    seed_baseline_db(db.clone());
+
+    migrate(db.clone()).unwrap();
+    assert!(column_exists(db.clone(), "orders", "account_id").unwrap());
+    assert!(!db.table_exists("orders_old").unwrap());
+
+    // 同一client_order_id可分属不同账户
+    let market_type = MarketType::BinanceSpot;
+    let mut order = Order::new_order_from_place_order_req(&PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: OrderSide::Buy,
+        r#type: OrderType::Limit,
+        time_in_force: Some(TimeInForce::Gtc),
+        quantity: Some(Decimal::ONE),
+        price: Some(Decimal::from(100)),
+        client_order_id: "order_1".to_string(),
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: None,
+    });
+    order.order_id = "1".to_string();
+    update_orders(
+        db.clone(),
+        &market_type,
+        DEFAULT_ACCOUNT_ID,
+        &[order.clone()],
+    )
+    .unwrap();
+    update_orders(db.clone(), &market_type, "sub", &[order]).unwrap();
+    for account_id in [DEFAULT_ACCOUNT_ID, "sub"] {
+        let orders = get_orders(
+            db.clone(),
+            &market_type,
+            account_id,
+            "BTCUSDT",
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(orders.len(), 1, "account: {}", account_id);
+    }
+
+    // 旧的同步时间归入默认账户，其他账户独立记录
+    update_last_sync_ts(db.clone(), &market_type, "sub", 900).unwrap();
+    assert_eq!(
+        get_last_sync_ts(db.clone(), &market_type, DEFAULT_ACCOUNT_ID).unwrap(),
+        Some(1500)
+    );
+    assert_eq!(
+        get_last_sync_ts(db.clone(), &market_type, "sub").unwrap(),
+        Some(900)
+    );
+}
+
+fn query_plan_of_order_lookup(db: Arc<SQLiteDB>) -> String {
+    let result = db
+        .execute_query(
+            r#"
+            EXPLAIN QUERY PLAN
+            SELECT trade_id FROM user_trades
+            WHERE market_type = ?1 AND account_id = ?2 AND symbol = ?3 AND order_id = ?4
+            ORDER BY timestamp ASC
+            "#,
+            &[&"BINANCE_SPOT", &"default", &"BTCUSDT", &"1"],
+        )
+        .unwrap();
+    result
+        .rows
+        .iter()
+        .filter_map(|row| row.get_string("detail"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,12 +1,34 @@
+pub mod dataset;
 pub mod db;
+pub mod depth_replay;
+pub mod init_report;
 pub mod market_data;
+pub mod memory_data_manager;
+pub mod migration;
+pub mod position_manager;
 pub mod trade_data;
+pub mod trade_reorder_buffer;
 pub mod traits;
+pub use init_report::{SymbolInitFailure, SymbolInitReport};
 pub use traits::{MarketDataManager, TradeDataManager};
 
+#[cfg(test)]
+mod dataset_tests;
+#[cfg(test)]
+mod depth_replay_tests;
 #[cfg(test)]
 mod market_data_tests;
 #[cfg(test)]
+mod memory_data_manager_tests;
+#[cfg(test)]
+mod migration_tests;
+#[cfg(test)]
+mod position_manager_tests;
+#[cfg(test)]
 mod trade_data_tests;
+#[cfg(test)]
+mod trade_reorder_buffer_tests;
 
 pub mod local_data_manager;
+#[cfg(test)]
+mod local_data_manager_tests;
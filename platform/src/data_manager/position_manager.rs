@@ -0,0 +1,227 @@
+use crate::{
+    data_manager::MarketDataManager,
+    errors::{PlatformError, Result},
+    models::{Account, MarketType, Position, UserTrade},
+};
+use rust_decimal::Decimal;
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::RwLock;
+
+// 按成交累计单个market_type下各symbol的仓位，并按标记价格计算盯市盈亏
+pub struct PositionManager {
+    market_type: MarketType,
+    market_mgr: Arc<dyn MarketDataManager>,
+    positions: RwLock<HashMap<String, Position>>, // symbol -> position
+}
+
+impl PositionManager {
+    pub fn new(market_type: MarketType, market_mgr: Arc<dyn MarketDataManager>) -> Self {
+        Self {
+            market_type,
+            market_mgr,
+            positions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn market_type(&self) -> &MarketType {
+        &self.market_type
+    }
+
+    pub async fn apply_fill(&self, trade: &UserTrade) -> Result<()> {
+        // 新symbol先在写锁外查询symbol info，避免持写锁等待market_mgr
+        let new_position = if self.positions.read().await.contains_key(&trade.symbol) {
+            None
+        } else {
+            let symbol_info = self
+                .market_mgr
+                .get_symbol_info(&self.market_type, &trade.symbol)
+                .await?
+                .ok_or(PlatformError::DataManagerError {
+                    message: format!(
+                        "symbol info not found for market_type {:?}, symbol {}",
+                        self.market_type, trade.symbol
+                    ),
+                })?;
+            Some(Position::new(
+                &symbol_info.symbol,
+                &symbol_info.base_asset,
+                &symbol_info.quote_asset,
+            ))
+        };
+        let mut positions = self.positions.write().await;
+        let position = match new_position {
+            Some(position) => positions.entry(trade.symbol.clone()).or_insert(position),
+            None => positions.get_mut(&trade.symbol).unwrap(),
+        };
+        position.apply_fill(trade);
+        Ok(())
+    }
+
+    // 以给定仓位替换当前全部仓位，用于按交易所余额对账重建
+    pub async fn reset_positions(&self, positions: Vec<Position>) {
+        *self.positions.write().await = positions
+            .into_iter()
+            .map(|position| (position.symbol.clone(), position))
+            .collect();
+    }
+
+    pub async fn get_position(&self, symbol: &str) -> Option<Position> {
+        self.positions.read().await.get(symbol).cloned()
+    }
+
+    pub async fn get_positions(&self) -> Vec<Position> {
+        self.positions.read().await.values().cloned().collect()
+    }
+
+    // 标记价格：优先取最新成交价，无成交时取盘口中间价
+    pub async fn mark_price(&self, symbol: &str) -> Result<Option<Decimal>> {
+        let symbol = symbol.to_string();
+        let trades = self
+            .market_mgr
+            .get_trades(&self.market_type, &symbol, Some(1))
+            .await?;
+        if let Some(trade) = trades.last() {
+            return Ok(Some(trade.price));
+        }
+        let depth = self
+            .market_mgr
+            .get_depth(&self.market_type, &symbol)
+            .await?;
+        Ok(
+            depth.and_then(|depth| match (depth.bids.first(), depth.asks.first()) {
+                (Some(bid), Some(ask)) => Some((bid.price + ask.price) / Decimal::TWO),
+                _ => None,
+            }),
+        )
+    }
+
+    // 获取所有非空仓位的标记价格，无价格的symbol不在结果中
+    pub async fn mark_prices(&self) -> Result<HashMap<String, Decimal>> {
+        let symbols: Vec<String> = self
+            .positions
+            .read()
+            .await
+            .values()
+            .filter(|position| !position.quantity.is_zero())
+            .map(|position| position.symbol.clone())
+            .collect();
+        let mut mark_prices = HashMap::new();
+        for symbol in symbols {
+            if let Some(price) = self.mark_price(&symbol).await? {
+                mark_prices.insert(symbol, price);
+            }
+        }
+        Ok(mark_prices)
+    }
+
+    // 未实现盈亏（quote资产计价），mark_prices为None时从market_mgr获取
+    // 无标记价格的仓位按0计入
+    pub async fn unrealized_pnl(
+        &self,
+        mark_prices: Option<&HashMap<String, Decimal>>,
+    ) -> Result<Decimal> {
+        let fetched;
+        let mark_prices = match mark_prices {
+            Some(mark_prices) => mark_prices,
+            None => {
+                fetched = self.mark_prices().await?;
+                &fetched
+            }
+        };
+        let positions = self.positions.read().await;
+        let mut pnl = Decimal::ZERO;
+        for position in positions.values().filter(|p| !p.quantity.is_zero()) {
+            match mark_prices.get(&position.symbol) {
+                Some(price) => pnl += (*price - position.avg_entry_price) * position.quantity,
+                None => log::warn!(
+                    "mark price not found for {:?} {}, unrealized pnl counted as 0",
+                    self.market_type,
+                    position.symbol
+                ),
+            }
+        }
+        Ok(pnl)
+    }
+
+    // 各仓位已实现盈亏之和（quote资产计价）
+    pub async fn realized_pnl(&self) -> Decimal {
+        self.positions
+            .read()
+            .await
+            .values()
+            .map(|position| position.realized_pnl)
+            .sum()
+    }
+
+    // 仓位名义价值之和：|quantity| * 标记价格，mark_prices为None时从market_mgr获取
+    // 无标记价格的仓位按0计入
+    pub async fn position_notional(
+        &self,
+        mark_prices: Option<&HashMap<String, Decimal>>,
+    ) -> Result<Decimal> {
+        let fetched;
+        let mark_prices = match mark_prices {
+            Some(mark_prices) => mark_prices,
+            None => {
+                fetched = self.mark_prices().await?;
+                &fetched
+            }
+        };
+        let positions = self.positions.read().await;
+        Ok(positions
+            .values()
+            .filter_map(|position| {
+                mark_prices
+                    .get(&position.symbol)
+                    .map(|price| *price * position.quantity.abs())
+            })
+            .sum())
+    }
+
+    // 总权益：账户中cash_asset余额（含冻结）加上以cash_asset计价的仓位市值
+    // 非cash_asset计价或无标记价格的仓位按0计入
+    pub async fn total_equity(
+        &self,
+        account: &Account,
+        cash_asset: &str,
+        mark_prices: Option<&HashMap<String, Decimal>>,
+    ) -> Result<Decimal> {
+        let fetched;
+        let mark_prices = match mark_prices {
+            Some(mark_prices) => mark_prices,
+            None => {
+                fetched = self.mark_prices().await?;
+                &fetched
+            }
+        };
+        let mut equity: Decimal = account
+            .balances
+            .iter()
+            .filter(|balance| balance.asset == cash_asset)
+            .map(|balance| balance.free + balance.locked)
+            .sum();
+
+        let positions = self.positions.read().await;
+        for position in positions.values().filter(|p| !p.quantity.is_zero()) {
+            if position.quote_asset != cash_asset {
+                log::warn!(
+                    "position {:?} {} quoted in {}, not {}, market value counted as 0",
+                    self.market_type,
+                    position.symbol,
+                    position.quote_asset,
+                    cash_asset
+                );
+                continue;
+            }
+            match mark_prices.get(&position.symbol) {
+                Some(price) => equity += *price * position.quantity,
+                None => log::warn!(
+                    "mark price not found for {:?} {}, market value counted as 0",
+                    self.market_type,
+                    position.symbol
+                ),
+            }
+        }
+        Ok(equity)
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{
+    backtest::test_utils::unfiltered_symbol_info,
+    data_manager::{position_manager::PositionManager, MarketDataManager},
+    errors::Result,
+    models::{
+        Account, Balance, DepthData, KlineData, KlineInterval, MarketType, OrderSide, PriceLevel,
+        SymbolInfo, Ticker24hr, Trade, UserTrade,
+    },
+};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::Notify;
+
+// 仅返回预置symbol_info/最新成交/盘口的行情数据
+#[derive(Default)]
+struct MockMarketDataManager {
+    symbol_infos: HashMap<String, SymbolInfo>,
+    last_prices: HashMap<String, Decimal>,
+    depths: HashMap<String, (Decimal, Decimal)>,
+    symbol_info_gate: Option<Arc<Notify>>, // 设置时get_symbol_info等待通知后返回
+}
+
+#[async_trait]
+impl MarketDataManager for MockMarketDataManager {
+    async fn init(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_klines(
+        &self,
+        _market_type: &MarketType,
+        _symbol: &String,
+        _interval: &KlineInterval,
+        _limit: Option<usize>,
+    ) -> Result<Vec<KlineData>> {
+        Ok(vec![])
+    }
+
+    async fn get_trades(
+        &self,
+        _market_type: &MarketType,
+        symbol: &String,
+        _limit: Option<usize>,
+    ) -> Result<Vec<Trade>> {
+        Ok(self
+            .last_prices
+            .get(symbol)
+            .map(|price| Trade {
+                symbol: symbol.clone(),
+                trade_id: "1".to_string(),
+                price: *price,
+                quantity: Decimal::ONE,
+                timestamp: 1,
+                is_buyer_maker: 0,
+                seq_id: 1,
+            })
+            .into_iter()
+            .collect())
+    }
+
+    async fn get_depth(
+        &self,
+        _market_type: &MarketType,
+        symbol: &String,
+    ) -> Result<Option<DepthData>> {
+        Ok(self.depths.get(symbol).map(|(bid, ask)| DepthData {
+            symbol: symbol.clone(),
+            bids: vec![PriceLevel {
+                price: *bid,
+                quantity: Decimal::ONE,
+            }],
+            asks: vec![PriceLevel {
+                price: *ask,
+                quantity: Decimal::ONE,
+            }],
+            timestamp: 1,
+        }))
+    }
+
+    async fn get_ticker(
+        &self,
+        _market_type: &MarketType,
+        _symbol: &String,
+    ) -> Result<Option<Ticker24hr>> {
+        Ok(None)
+    }
+
+    async fn get_symbol_info(
+        &self,
+        _market_type: &MarketType,
+        symbol: &String,
+    ) -> Result<Option<SymbolInfo>> {
+        if let Some(gate) = &self.symbol_info_gate {
+            gate.notified().await;
+        }
+        Ok(self.symbol_infos.get(symbol).cloned())
+    }
+
+    async fn get_symbol(
+        &self,
+        _market_type: &MarketType,
+        _base_asset: &String,
+        _quote_asset: &String,
+    ) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+fn buy(symbol: &str, price: Decimal, quantity: Decimal) -> UserTrade {
+    UserTrade {
+        trade_id: "1".to_string(),
+        order_id: "1".to_string(),
+        symbol: symbol.to_string(),
+        order_side: OrderSide::Buy,
+        trade_price: price,
+        trade_quantity: quantity,
+        commission: Decimal::ZERO,
+        commission_asset: "USDT".to_string(),
+        is_maker: 0,
+        timestamp: 1,
+    }
+}
+
+#[tokio::test]
+async fn test_unrealized_pnl_and_total_equity() {
+    let market_mgr = MockMarketDataManager {
+        symbol_infos: [
+            unfiltered_symbol_info("BTCUSDT", "BTC", "USDT"),
+            unfiltered_symbol_info("ETHUSDT", "ETH", "USDT"),
+            unfiltered_symbol_info("SOLUSDT", "SOL", "USDT"),
+            unfiltered_symbol_info("ETHBTC", "ETH", "BTC"),
+        ]
+        .into_iter()
+        .map(|info| (info.symbol.clone(), info))
+        .collect(),
+        // BTCUSDT取最新成交价，ETHUSDT无成交取盘口中间价，SOLUSDT无价格
+        last_prices: HashMap::from([
+            ("BTCUSDT".to_string(), dec!(110)),
+            ("ETHBTC".to_string(), dec!(0.06)),
+        ]),
+        depths: HashMap::from([("ETHUSDT".to_string(), (dec!(19), dec!(21)))]),
+        ..Default::default()
+    };
+    let position_mgr = PositionManager::new(MarketType::BinanceSpot, Arc::new(market_mgr));
+
+    position_mgr
+        .apply_fill(&buy("BTCUSDT", dec!(100), dec!(2)))
+        .await
+        .unwrap();
+    position_mgr
+        .apply_fill(&buy("ETHUSDT", dec!(25), dec!(10)))
+        .await
+        .unwrap();
+    position_mgr
+        .apply_fill(&buy("SOLUSDT", dec!(10), dec!(5)))
+        .await
+        .unwrap();
+    assert!(position_mgr
+        .apply_fill(&buy("XRPUSDT", dec!(1), dec!(1)))
+        .await
+        .is_err());
+    assert_eq!(position_mgr.get_positions().await.len(), 3);
+    assert_eq!(
+        position_mgr.get_position("BTCUSDT").await.unwrap().quantity,
+        dec!(2)
+    );
+
+    // 指定标记价格：2 * 20 + 10 * 5，SOLUSDT无价格按0计入
+    let mark_prices = HashMap::from([
+        ("BTCUSDT".to_string(), dec!(120)),
+        ("ETHUSDT".to_string(), dec!(30)),
+    ]);
+    assert_eq!(
+        position_mgr
+            .unrealized_pnl(Some(&mark_prices))
+            .await
+            .unwrap(),
+        dec!(90)
+    );
+
+    // 从行情获取：2 * 10 + 10 * (20 - 25)
+    let fetched = position_mgr.mark_prices().await.unwrap();
+    assert_eq!(fetched.len(), 2);
+    assert_eq!(fetched["ETHUSDT"], dec!(20));
+    assert_eq!(position_mgr.unrealized_pnl(None).await.unwrap(), dec!(-30));
+
+    // 非USDT计价的仓位不计入USDT权益
+    position_mgr
+        .apply_fill(&buy("ETHBTC", dec!(0.05), dec!(1)))
+        .await
+        .unwrap();
+    let account = Account {
+        balances: vec![
+            Balance {
+                asset: "USDT".to_string(),
+                free: dec!(1000),
+                locked: dec!(50),
+            },
+            Balance {
+                asset: "BTC".to_string(),
+                free: dec!(1),
+                locked: Decimal::ZERO,
+            },
+        ],
+        timestamp: 1,
+    };
+    // 1050 + 2 * 110 + 10 * 20
+    assert_eq!(
+        position_mgr
+            .total_equity(&account, "USDT", None)
+            .await
+            .unwrap(),
+        dec!(1470)
+    );
+    assert_eq!(
+        position_mgr
+            .total_equity(&account, "USDT", Some(&mark_prices))
+            .await
+            .unwrap(),
+        dec!(1590)
+    );
+}
+
+#[tokio::test]
+async fn test_apply_fill_resolves_symbol_info_without_lock() {
+    let gate = Arc::new(Notify::new());
+    let market_mgr = MockMarketDataManager {
+        symbol_infos: [unfiltered_symbol_info("BTCUSDT", "BTC", "USDT")]
+            .into_iter()
+            .map(|info| (info.symbol.clone(), info))
+            .collect(),
+        symbol_info_gate: Some(gate.clone()),
+        ..Default::default()
+    };
+    let position_mgr = Arc::new(PositionManager::new(
+        MarketType::BinanceSpot,
+        Arc::new(market_mgr),
+    ));
+
+    let fill = tokio::spawn({
+        let position_mgr = position_mgr.clone();
+        async move {
+            position_mgr
+                .apply_fill(&buy("BTCUSDT", dec!(100), dec!(1)))
+                .await
+        }
+    });
+    tokio::task::yield_now().await;
+    // 查询symbol info期间仓位仍可读
+    let positions = tokio::time::timeout(Duration::from_secs(1), position_mgr.get_positions())
+        .await
+        .unwrap();
+    assert!(positions.is_empty());
+
+    gate.notify_one();
+    fill.await.unwrap().unwrap();
+    let position = position_mgr.get_position("BTCUSDT").await.unwrap();
+    assert_eq!(position.quantity, dec!(1));
+}
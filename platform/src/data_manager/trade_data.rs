@@ -1,85 +1,251 @@
 use super::TradeDataManager;
 use crate::{
-    config::PlatformConfig,
-    data_manager::db::*,
+    config::{PlatformConfig, DEFAULT_ACCOUNT_ID},
+    data_manager::{db::*, migration::migrate, position_manager::PositionManager},
     errors::{PlatformError, Result},
     models::{
-        Account, AccountUpdate, CancelOrderRequest, GetAllOrdersRequest, GetOpenOrdersRequest,
-        GetUserTradesRequest, MarketType, Order, OrderStatus, PlaceOrderRequest, UserTrade,
+        Account, AccountUpdate, AmendOrderRequest, BalanceSnapshot, CancelOrderRequest,
+        CancelReplaceOrderRequest, GetAllOrdersRequest, GetOpenOrdersRequest, GetUserTradesRequest,
+        MarketType, Order, OrderStatus, OrderType, PlaceOrderRequest, UserTrade,
     },
     trade_provider::TradeProvider,
 };
 use async_trait::async_trait;
 use db::sqlite::SQLiteDB;
-use std::{collections::HashMap, sync::Arc, time::Duration};
-use tokio::sync::RwLock;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use time::{noop_metrics, Metrics};
+use tokio::sync::{Notify, RwLock};
 use tokio_util::sync::CancellationToken;
 
+// 终态订单id缓存上限，超出后淘汰最早记录
+const MAX_TERMINAL_ORDER_IDS: usize = 100000;
+
+// (市场, account_id)
+pub type AccountKey = (MarketType, String);
+
 struct OpenOrderTradeStat {
     orders: HashMap<String, Order>, // client_id -> order
+    // 已进入终态的client_id，防止延迟到达的REST/stream旧状态使订单回退为在途
+    terminal_order_ids: HashSet<String>,
+    terminal_order_queue: VecDeque<String>,
+}
+
+impl OpenOrderTradeStat {
+    fn mark_terminal(&mut self, client_order_id: &str) {
+        if self.terminal_order_ids.insert(client_order_id.to_string()) {
+            self.terminal_order_queue
+                .push_back(client_order_id.to_string());
+            while self.terminal_order_queue.len() > MAX_TERMINAL_ORDER_IDS {
+                if let Some(id) = self.terminal_order_queue.pop_front() {
+                    self.terminal_order_ids.remove(&id);
+                }
+            }
+        }
+    }
 }
 
+#[derive(Clone)]
+struct SyncRetryPolicy {
+    retry_times: u32,
+    backoff: Duration, // 第n次重试等待 backoff * 2^n
+}
+
+// 单次重试等待上限
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+// 定期同步的时间窗口，单位毫秒
+#[derive(Clone)]
+pub(crate) struct SyncWindowPolicy {
+    pub initial_lookback: u64,
+    pub max_gap: u64,
+    pub safety_margin: u64,
+}
+
+impl SyncWindowPolicy {
+    // 本次需同步的[start, end]分段，跨度超过max_gap时依次切分，相邻分段首尾相接
+    pub fn windows(&self, last_sync_ts: Option<u64>, now: u64) -> Vec<(u64, u64)> {
+        let end_ts = now.saturating_sub(self.safety_margin);
+        // 从未同步过时回溯initial_lookback
+        let mut start_ts =
+            last_sync_ts.unwrap_or_else(|| end_ts.saturating_sub(self.initial_lookback) + 1);
+        let mut windows = vec![];
+        while start_ts < end_ts {
+            let window_end = end_ts.min(start_ts + self.max_gap - 1);
+            windows.push((start_ts, window_end));
+            start_ts = window_end + 1;
+        }
+        windows
+    }
+}
+
+// 每个(市场, 账户)独立的账户/在途订单缓存与同步任务，TradeDataManager接口作用于默认账户
 pub struct TradeData {
-    market_types: Arc<Vec<MarketType>>,
+    account_keys: Arc<Vec<AccountKey>>,
     refresh_intervals: Arc<HashMap<MarketType, Duration>>,
+    retry_policies: Arc<HashMap<MarketType, SyncRetryPolicy>>,
+    window_policies: Arc<HashMap<MarketType, SyncWindowPolicy>>,
     shutdown_token: CancellationToken,
-    trade_providers: Arc<HashMap<MarketType, Arc<dyn TradeProvider>>>,
+    trade_providers: Arc<HashMap<AccountKey, Arc<dyn TradeProvider>>>,
 
     // 账户缓存
-    accounts: Arc<HashMap<MarketType, Arc<RwLock<Option<Account>>>>>,
+    accounts: Arc<HashMap<AccountKey, Arc<RwLock<Option<Account>>>>>,
     // 在途订单缓存
-    open_order_stats: Arc<HashMap<MarketType, Arc<RwLock<OpenOrderTradeStat>>>>,
+    open_order_stats: Arc<HashMap<AccountKey, Arc<RwLock<OpenOrderTradeStat>>>>,
+
+    // 定期同步重试耗尽次数
+    sync_retry_exhausted: Arc<AtomicU64>,
+    // 每轮定期同步结束（落库与缓存刷新完成）后通知
+    sync_completed: Arc<Notify>,
+    metrics: Arc<dyn Metrics>,
 
     db: Arc<SQLiteDB>,
+
+    // 成交推送同步到对应账户的仓位
+    position_mgrs: HashMap<AccountKey, Arc<PositionManager>>,
+
+    default_account: TradeAccount,
+}
+
+// 单个账户的交易数据视图，数据由所属TradeData统一初始化与同步
+#[derive(Clone)]
+pub struct TradeAccount {
+    account_id: String,
+    trade_providers: Arc<HashMap<AccountKey, Arc<dyn TradeProvider>>>,
+    accounts: Arc<HashMap<AccountKey, Arc<RwLock<Option<Account>>>>>,
+    open_order_stats: Arc<HashMap<AccountKey, Arc<RwLock<OpenOrderTradeStat>>>>,
+    db: Arc<SQLiteDB>,
+    metrics: Arc<dyn Metrics>,
 }
 
 impl TradeData {
+    // trade_providers按(市场, account_id)提供，需覆盖配置中每个市场的全部账户
     pub fn new(
         config: Arc<PlatformConfig>,
-        trade_providers: Arc<HashMap<MarketType, Arc<dyn TradeProvider>>>,
+        trade_providers: Arc<HashMap<AccountKey, Arc<dyn TradeProvider>>>,
     ) -> Result<Self> {
-        let market_types: Arc<Vec<MarketType>> = Arc::new(config.markets.clone());
         let db_path: String = config.db_path.clone();
 
+        let mut account_keys = Vec::new();
         let mut accounts = HashMap::new();
         let mut stats = HashMap::new();
         let mut refresh_intervals = HashMap::new();
-        for market_type in market_types.iter() {
-            accounts.insert(market_type.clone(), Arc::new(RwLock::new(None)));
-            stats.insert(
+        let mut retry_policies = HashMap::new();
+        let mut window_policies = HashMap::new();
+        for market_type in config.markets.iter() {
+            let market_config =
+                config
+                    .configs
+                    .get(market_type)
+                    .ok_or_else(|| PlatformError::ConfigError {
+                        message: format!(
+                            "market config not found for market_type {:?}",
+                            market_type
+                        ),
+                    })?;
+            for account_id in market_config.account_ids() {
+                let key = (market_type.clone(), account_id);
+                accounts.insert(key.clone(), Arc::new(RwLock::new(None)));
+                stats.insert(
+                    key.clone(),
+                    Arc::new(RwLock::new(OpenOrderTradeStat {
+                        orders: HashMap::new(),
+                        terminal_order_ids: HashSet::new(),
+                        terminal_order_queue: VecDeque::new(),
+                    })),
+                );
+                account_keys.push(key);
+            }
+            refresh_intervals.insert(
                 market_type.clone(),
-                Arc::new(RwLock::new(OpenOrderTradeStat {
-                    orders: HashMap::new(),
-                })),
+                Duration::from_secs(market_config.trade_refresh_interval_secs),
             );
-            refresh_intervals.insert(
+            retry_policies.insert(
                 market_type.clone(),
-                Duration::from_secs(
-                    config.configs[&MarketType::BinanceSpot].trade_refresh_interval_secs,
-                ),
+                SyncRetryPolicy {
+                    retry_times: market_config.trade_sync_retry_times,
+                    backoff: Duration::from_millis(
+                        market_config.trade_sync_retry_backoff_milli_secs,
+                    ),
+                },
+            );
+            window_policies.insert(
+                market_type.clone(),
+                SyncWindowPolicy {
+                    initial_lookback: market_config.trade_sync_initial_lookback_milli_secs,
+                    max_gap: market_config.trade_sync_max_gap_milli_secs,
+                    safety_margin: market_config.trade_sync_safety_margin_milli_secs,
+                },
             );
         }
 
-        let db =
-            Arc::new(
-                SQLiteDB::new(&db_path).map_err(|e| PlatformError::DataManagerError {
-                    message: format!("connect db failed: {}", e),
-                })?,
-            );
+        let db = Arc::new(SQLiteDB::new(&db_path).map_err(|e| PlatformError::DbError {
+            context: "connect db failed".to_string(),
+            source: e,
+        })?);
+
+        let accounts = Arc::new(accounts);
+        let open_order_stats = Arc::new(stats);
+        let default_account = TradeAccount {
+            account_id: DEFAULT_ACCOUNT_ID.to_string(),
+            trade_providers: trade_providers.clone(),
+            accounts: accounts.clone(),
+            open_order_stats: open_order_stats.clone(),
+            db: db.clone(),
+            metrics: noop_metrics(),
+        };
 
         Ok(Self {
-            market_types,
+            account_keys: Arc::new(account_keys),
             trade_providers,
             refresh_intervals: Arc::new(refresh_intervals),
+            retry_policies: Arc::new(retry_policies),
+            window_policies: Arc::new(window_policies),
             shutdown_token: CancellationToken::new(),
-            accounts: Arc::new(accounts),
-            open_order_stats: Arc::new(stats),
+            accounts,
+            open_order_stats,
+            sync_retry_exhausted: Arc::new(AtomicU64::new(0)),
+            sync_completed: Arc::new(Notify::new()),
+            metrics: noop_metrics(),
             db,
+            position_mgrs: HashMap::new(),
+            default_account,
         })
     }
 
+    // 需在init之前设置，上报 trade.order_placed/order_canceled/order_amended 计数及
+    // 定期同步全部分段落库成功的 trade.sync、有分段失败的 trade.sync_failed 计数
+    pub fn set_metrics(&mut self, metrics: Arc<dyn Metrics>) {
+        self.default_account.metrics = metrics.clone();
+        self.metrics = metrics;
+    }
+
+    // 需在init之前设置，account_id账户的成交推送计入position_mgr所在market_type的仓位
+    pub fn set_position_manager(&mut self, account_id: &str, position_mgr: Arc<PositionManager>) {
+        self.position_mgrs.insert(
+            (position_mgr.market_type().clone(), account_id.to_string()),
+            position_mgr,
+        );
+    }
+
+    // 获取指定账户的视图，账户不存在时接口调用返回错误
+    pub fn account(&self, account_id: &str) -> TradeAccount {
+        TradeAccount {
+            account_id: account_id.to_string(),
+            ..self.default_account.clone()
+        }
+    }
+
     async fn _fetch_api_data(
         market_type: &MarketType,
+        account_id: &str,
         trade_provider: Arc<dyn TradeProvider>,
     ) -> Result<(Account, Vec<Order>)> {
         let account = match trade_provider.get_account().await {
@@ -87,8 +253,8 @@ impl TradeData {
             Err(e) => {
                 return Err(PlatformError::DataManagerError {
                     message: format!(
-                        "get account from trade provider failed for market_type {:?}: {}",
-                        market_type, e
+                        "get account from trade provider failed for market_type {:?}, account {}: {}",
+                        market_type, account_id, e
                     ),
                 });
             }
@@ -102,8 +268,8 @@ impl TradeData {
             Err(e) => {
                 return Err(PlatformError::DataManagerError {
                     message: format!(
-                        "get open orders from trade provider failed for market_type {:?}: {}",
-                        market_type, e
+                        "get open orders from trade provider failed for market_type {:?}, account {}: {}",
+                        market_type, account_id, e
                     ),
                 });
             }
@@ -112,6 +278,92 @@ impl TradeData {
         Ok((account, orders))
     }
 
+    async fn _retry_with_backoff<T, F, Fut>(
+        policy: &SyncRetryPolicy,
+        exhausted_counter: &AtomicU64,
+        op_name: &str,
+        mut op: F,
+    ) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Ok(v) => return Ok(v),
+                Err(e) => {
+                    if attempt >= policy.retry_times {
+                        exhausted_counter.fetch_add(1, Ordering::Relaxed);
+                        log::error!("{} failed after {} retries: {}", op_name, attempt, e);
+                        return Err(e);
+                    }
+                    let backoff = policy
+                        .backoff
+                        .saturating_mul(2u32.saturating_pow(attempt))
+                        .min(MAX_RETRY_BACKOFF);
+                    log::warn!(
+                        "{} failed (attempt {}): {}, retry after {:?}",
+                        op_name,
+                        attempt + 1,
+                        e,
+                        backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    // 拉取各symbol在[start_time, end_time]内的全部订单与成交
+    async fn _fetch_orders_and_trades(
+        retry_policy: &SyncRetryPolicy,
+        sync_retry_exhausted: &AtomicU64,
+        trade_provider: Arc<dyn TradeProvider>,
+        symbols: &[String],
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<(Vec<Order>, Vec<UserTrade>)> {
+        let mut orders = Vec::new();
+        let mut trades = Vec::new();
+        for symbol in symbols {
+            orders.extend(
+                Self::_retry_with_backoff(
+                    retry_policy,
+                    sync_retry_exhausted,
+                    "fetch all orders",
+                    || {
+                        Self::_fetch_all_orders(
+                            trade_provider.clone(),
+                            symbol.clone(),
+                            start_time,
+                            end_time,
+                        )
+                    },
+                )
+                .await?,
+            );
+            trades.extend(
+                Self::_retry_with_backoff(
+                    retry_policy,
+                    sync_retry_exhausted,
+                    "fetch all trades",
+                    || {
+                        Self::_fetch_all_trades(
+                            trade_provider.clone(),
+                            symbol.clone(),
+                            start_time,
+                            end_time,
+                        )
+                    },
+                )
+                .await?,
+            );
+        }
+        Ok((orders, trades))
+    }
+
     async fn _fetch_all_orders(
         trade_provider: Arc<dyn TradeProvider>,
         symbol: String,
@@ -183,47 +435,45 @@ impl TradeData {
     }
 
     pub async fn init(&self) -> Result<()> {
-        // 初始化数据库
-        create_api_sync_ts_table(self.db.clone())?;
-        create_account_balance_table(self.db.clone())?;
-        create_orders_table(self.db.clone())?;
-        create_user_trades_table(self.db.clone())?;
-
-        for market_type in self.market_types.iter() {
-            let trade_provider =
-                self.trade_providers
-                    .get(market_type)
-                    .ok_or(PlatformError::DataManagerError {
-                        message: format!(
-                            "Trade provider not found for market type: {:?}",
-                            market_type
-                        ),
-                    })?;
+        // 初始化数据库，升级到最新schema
+        migrate(self.db.clone())?;
+
+        for (market_type, account_id) in self.account_keys.iter() {
+            let trade_provider = self
+                .trade_providers
+                .get(&(market_type.clone(), account_id.clone()))
+                .ok_or(PlatformError::DataManagerError {
+                    message: format!(
+                        "Trade provider not found for market type: {:?}, account: {}",
+                        market_type, account_id
+                    ),
+                })?;
 
             let (api_account, api_orders) =
-                Self::_fetch_api_data(market_type, trade_provider.clone()).await?;
+                Self::_fetch_api_data(market_type, account_id, trade_provider.clone()).await?;
             Self::update_account_inner(
                 self.accounts.clone(),
                 self.db.clone(),
                 market_type,
+                account_id,
                 api_account,
             )
             .await?;
-            for order in api_orders {
-                Self::update_order_inner(
-                    self.open_order_stats.clone(),
-                    self.db.clone(),
-                    market_type,
-                    order,
-                )
-                .await?;
-            }
+            Self::update_orders_inner(
+                self.open_order_stats.clone(),
+                self.db.clone(),
+                market_type,
+                account_id,
+                api_orders,
+            )
+            .await?;
 
             // 订阅/定期更新
             let shutdown_token = self.shutdown_token.clone();
             let db = self.db.clone();
             let open_order_stats = self.open_order_stats.clone();
             let market_type_clone = market_type.clone();
+            let account_id_clone = account_id.clone();
             let mut order_sub = trade_provider.subscribe_order();
             tokio::spawn(async move {
                 loop {
@@ -238,13 +488,14 @@ impl TradeData {
                                         open_order_stats.clone(),
                                         db.clone(),
                                         &market_type_clone,
+                                        &account_id_clone,
                                         order,
                                     ).await.is_err() {
-                                        log::error!("update order failed for market_type {:?}", market_type_clone);
+                                        log::error!("update order failed for market_type {:?}, account {}", market_type_clone, account_id_clone);
                                     }
                                 },
                                 Err(e) => {
-                                    log::error!("order subscription error for market_type {:?}: {}", market_type_clone, e);
+                                    log::error!("order subscription error for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
                                 }
                             }
                         }
@@ -256,6 +507,11 @@ impl TradeData {
             let db = self.db.clone();
             let open_order_stats = self.open_order_stats.clone();
             let market_type_clone = market_type.clone();
+            let account_id_clone = account_id.clone();
+            let position_mgr = self
+                .position_mgrs
+                .get(&(market_type.clone(), account_id.clone()))
+                .cloned();
             let mut trade_sub = trade_provider.subscribe_user_trade();
             tokio::spawn(async move {
                 loop {
@@ -266,17 +522,25 @@ impl TradeData {
                         trade = trade_sub.recv() => {
                             match trade {
                                 Ok(trade) => {
+                                    let applied = match position_mgr.as_ref() {
+                                        Some(position_mgr) => position_mgr.apply_fill(&trade).await,
+                                        None => Ok(()),
+                                    };
+                                    if let Err(e) = applied {
+                                        log::error!("apply fill to position failed for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
+                                    }
                                     if Self::update_user_trade_inner(
                                         open_order_stats.clone(),
                                         db.clone(),
                                         &market_type_clone,
+                                        &account_id_clone,
                                         trade,
                                     ).await.is_err() {
-                                        log::error!("update user trade failed for market_type {:?}", market_type_clone);
+                                        log::error!("update user trade failed for market_type {:?}, account {}", market_type_clone, account_id_clone);
                                     }
                                 },
                                 Err(e) => {
-                                    log::error!("user trade subscription error for market_type {:?}: {}", market_type_clone, e);
+                                    log::error!("user trade subscription error for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
                                 }
                             }
                         }
@@ -288,6 +552,7 @@ impl TradeData {
             let db = self.db.clone();
             let accounts = self.accounts.clone();
             let market_type_clone = market_type.clone();
+            let account_id_clone = account_id.clone();
             let mut account_update_sub = trade_provider.subscribe_account_update();
             tokio::spawn(async move {
                 loop {
@@ -302,13 +567,14 @@ impl TradeData {
                                         accounts.clone(),
                                         db.clone(),
                                         &market_type_clone,
+                                        &account_id_clone,
                                         account_update,
                                     ).await.is_err() {
-                                        log::error!("update account update failed for market_type {:?}", market_type_clone);
+                                        log::error!("update account update failed for market_type {:?}, account {}", market_type_clone, account_id_clone);
                                     }
                                 },
                                 Err(e) => {
-                                    log::error!("account update subscription error for market_type {:?}: {}", market_type_clone, e);
+                                    log::error!("account update subscription error for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
                                 }
                             }
                         }
@@ -320,11 +586,21 @@ impl TradeData {
             let accounts = self.accounts.clone();
             let open_order_stats = self.open_order_stats.clone();
             let market_type_clone = market_type.clone();
+            let account_id_clone = account_id.clone();
             let refresh_interval = self
                 .refresh_intervals
                 .get(&market_type_clone)
                 .unwrap()
                 .clone();
+            let retry_policy = self.retry_policies.get(&market_type_clone).unwrap().clone();
+            let window_policy = self
+                .window_policies
+                .get(&market_type_clone)
+                .unwrap()
+                .clone();
+            let sync_retry_exhausted = self.sync_retry_exhausted.clone();
+            let sync_completed = self.sync_completed.clone();
+            let metrics = self.metrics.clone();
             let trade_provider_clone = trade_provider.clone();
             let db = self.db.clone();
             tokio::spawn(async move {
@@ -336,10 +612,12 @@ impl TradeData {
                         },
                         _ = interval_tick.tick() => {
                             let (account , api_orders) =
-                                match Self::_fetch_api_data(&market_type_clone, trade_provider_clone.clone()).await {
+                                match Self::_retry_with_backoff(&retry_policy, &sync_retry_exhausted, "fetch api data", || {
+                                    Self::_fetch_api_data(&market_type_clone, &account_id_clone, trade_provider_clone.clone())
+                                }).await {
                                     Ok(data) => data,
                                     Err(e) => {
-                                        log::error!("periodic fetch api data failed for market_type {:?}: {}", market_type_clone, e);
+                                        log::error!("periodic fetch api data failed for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
                                         continue;
                                     }
                                 };
@@ -347,123 +625,88 @@ impl TradeData {
                                 accounts.clone(),
                                 db.clone(),
                                 &market_type_clone,
+                                &account_id_clone,
                                 account,
                             ).await.is_err() {
-                                log::error!("update account failed for market_type {:?}", market_type_clone);
+                                log::error!("update account failed for market_type {:?}, account {}", market_type_clone, account_id_clone);
                                 continue;
                             }
-                            let mut update_open_order_err = false;
-                            for order in api_orders {
-                                if Self::update_order_inner(
-                                    open_order_stats.clone(),
-                                    db.clone(),
-                                    &market_type_clone,
-                                    order,
-                                ).await.is_err() {
-                                    update_open_order_err = true;
-                                    log::error!("update order failed for market_type {:?}", market_type_clone);
-                                }
-                            }
-                            if update_open_order_err {
+                            if let Err(e) = Self::update_orders_inner(
+                                open_order_stats.clone(),
+                                db.clone(),
+                                &market_type_clone,
+                                &account_id_clone,
+                                api_orders,
+                            ).await {
+                                log::error!("update orders failed for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
                                 continue;
                             }
 
                             // 从上一次更新的时间位置，获取全量的order/trade并更新
-                            let symbols = match get_all_symbol(db.clone(), &market_type_clone) {
+                            let symbols = match get_all_symbol(db.clone(), &market_type_clone, &account_id_clone) {
                                 Ok(symbols) => symbols,
                                 Err(e) => {
-                                    log::error!("get all symbols failed for market_type {:?}: {}", market_type_clone, e);
+                                    log::error!("get all symbols failed for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
                                     continue;
                                 }
                             };
 
-                            let current_ts = time::get_current_milli_timestamp() - 5 * 1000;
-                            let mut last_sync_ts = match get_last_sync_ts(db.clone(), &market_type_clone) {
-                                Ok(ts) => ts.unwrap_or(current_ts - 24 * 60 * 60 * 1000 + 1), // 从未同步过，获取最近一天
+                            let last_sync_ts = match get_last_sync_ts(db.clone(), &market_type_clone, &account_id_clone) {
+                                Ok(ts) => ts,
                                 Err(e) => {
-                                    log::error!("get last sync ts failed for market_type {:?}: {}", market_type_clone, e);
+                                    log::error!("get last sync ts failed for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
                                     continue;
                                 }
                             };
-
-                            if current_ts - last_sync_ts >= 24 * 60 * 60 * 1000 {
-                                log::warn!("large time gap detected for market_type {:?}, last_sync_ts {}, current_ts {}. Limiting to 1 day interval", market_type_clone, last_sync_ts, current_ts);
-                                last_sync_ts = current_ts - 24 * 60 * 60 * 1000 + 1;
+                            let windows = window_policy.windows(last_sync_ts, time::get_current_milli_timestamp());
+                            if windows.len() > 1 {
+                                log::warn!("large time gap detected for market_type {:?}, account {}, last_sync_ts {:?}. Syncing in {} chunks", market_type_clone, account_id_clone, last_sync_ts, windows.len());
                             }
 
-                            let mut orders = Vec::new();
-                            let mut trades = Vec::new();
-                            let mut get_order_trade_succ = true;
-                            for symbol in symbols.iter() {
-                                orders.extend(
-                                    match Self::_fetch_all_orders(
-                                        trade_provider_clone.clone(),
-                                        symbol.clone(),
-                                        last_sync_ts,
-                                        current_ts,
-                                    ).await {
-                                        Ok(ords) => ords,
-                                        Err(e) => {
-                                            log::error!("fetch all orders failed for market_type {:?}, symbol {}: {}", market_type_clone, symbol, e);
-                                            get_order_trade_succ = false;
-                                            break;
-                                        }
-                                    }
-                                );
-                                trades.extend(
-                                    match Self::_fetch_all_trades(
-                                        trade_provider_clone.clone(),
-                                        symbol.clone(),
-                                        last_sync_ts,
-                                        current_ts,
-                                    ).await {
-                                        Ok(trds) => trds,
-                                        Err(e) => {
-                                            log::error!("fetch all trades failed for market_type {:?}, symbol {}: {}", market_type_clone, symbol, e);
-                                            get_order_trade_succ = false;
-                                            break;
-                                        }
+                            // 按分段依次拉取并落库，失败时保留已完成分段的进度，下次从中断处继续
+                            let mut synced = true;
+                            for (start_ts, end_ts) in windows {
+                                let (orders, trades) = match Self::_fetch_orders_and_trades(
+                                    &retry_policy,
+                                    &sync_retry_exhausted,
+                                    trade_provider_clone.clone(),
+                                    &symbols,
+                                    start_ts,
+                                    end_ts,
+                                ).await {
+                                    Ok(data) => data,
+                                    Err(e) => {
+                                        log::error!("fetch orders and trades failed for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
+                                        synced = false;
+                                        break;
                                     }
-                                );
-                            }
-                            if !get_order_trade_succ {
-                                continue;
-                            }
-                            let mut update_order_trade_err = false;
-                            for order in orders {
-                                if Self::update_order_inner(
-                                    open_order_stats.clone(),
+                                };
+                                // orders/trades/last_sync_ts同一事务落库，成功后再刷新在途订单缓存
+                                if let Err(e) = sync_orders_and_trades(
                                     db.clone(),
                                     &market_type_clone,
-                                    order,
-                                ).await.is_err() {
-                                    update_order_trade_err = true;
-                                    log::error!("update order failed for market_type {:?}", market_type_clone);
+                                    &account_id_clone,
+                                    &orders,
+                                    &trades,
+                                    end_ts,
+                                ) {
+                                    log::error!("sync orders and trades failed for market_type {:?}, account {}: {}", market_type_clone, account_id_clone, e);
+                                    synced = false;
+                                    break;
                                 }
-                            }
-                            for trade in trades {
-                                if Self::update_user_trade_inner(
-                                    open_order_stats.clone(),
-                                    db.clone(),
-                                    &market_type_clone,
-                                    trade,
-                                ).await.is_err() {
-                                    update_order_trade_err = true;
-                                    log::error!("update user trade failed for market_type {:?}", market_type_clone);
+                                for order in orders {
+                                    if Self::update_order_cache(
+                                        open_order_stats.clone(),
+                                        &market_type_clone,
+                                        &account_id_clone,
+                                        order,
+                                    ).await.is_err() {
+                                        log::error!("update order cache failed for market_type {:?}, account {}", market_type_clone, account_id_clone);
+                                    }
                                 }
                             }
-                            if update_order_trade_err {
-                                continue;
-                            }
-
-                            // 更新last_sync_ts
-                            if update_last_sync_ts(
-                                db.clone(),
-                                &market_type_clone,
-                                current_ts,
-                            ).is_err() {
-                                log::error!("update last sync ts failed for market_type {:?}", market_type_clone);
-                            };
+                            metrics.incr(if synced { "trade.sync" } else { "trade.sync_failed" }, 1);
+                            sync_completed.notify_waiters();
                         }
                     }
                 }
@@ -473,24 +716,65 @@ impl TradeData {
     }
 
     async fn update_order_inner(
-        open_order_stats: Arc<HashMap<MarketType, Arc<RwLock<OpenOrderTradeStat>>>>,
+        open_order_stats: Arc<HashMap<AccountKey, Arc<RwLock<OpenOrderTradeStat>>>>,
         db: Arc<SQLiteDB>,
         market_type: &MarketType,
+        account_id: &str,
         order: Order,
     ) -> Result<()> {
-        update_order(db.clone(), market_type, &order)?;
+        update_order(db.clone(), market_type, account_id, &order)?;
+        Self::update_order_cache(open_order_stats, market_type, account_id, order).await
+    }
 
-        let stat_lock =
-            open_order_stats
-                .get(market_type)
-                .ok_or(PlatformError::DataManagerError {
-                    message: format!(
-                        "open_order_stats lock not found for market_type {:?}",
-                        market_type
-                    ),
-                })?;
+    // 一次批量落库后逐个刷新缓存
+    async fn update_orders_inner(
+        open_order_stats: Arc<HashMap<AccountKey, Arc<RwLock<OpenOrderTradeStat>>>>,
+        db: Arc<SQLiteDB>,
+        market_type: &MarketType,
+        account_id: &str,
+        orders: Vec<Order>,
+    ) -> Result<()> {
+        update_orders(db.clone(), market_type, account_id, &orders)?;
+        for order in orders {
+            Self::update_order_cache(open_order_stats.clone(), market_type, account_id, order)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn update_order_cache(
+        open_order_stats: Arc<HashMap<AccountKey, Arc<RwLock<OpenOrderTradeStat>>>>,
+        market_type: &MarketType,
+        account_id: &str,
+        order: Order,
+    ) -> Result<()> {
+        let stat_lock = open_order_stats
+            .get(&(market_type.clone(), account_id.to_string()))
+            .ok_or(PlatformError::DataManagerError {
+                message: format!(
+                    "open_order_stats lock not found for market_type {:?}, account {}",
+                    market_type, account_id
+                ),
+            })?;
         let mut stat_guard = stat_lock.write().await;
 
+        // 终态具有粘性：REST与stream时钟可能不一致，终态后到达的非终态更新直接忽略
+        if order.order_status.is_terminal() {
+            stat_guard.orders.remove(&order.client_order_id);
+            stat_guard.mark_terminal(&order.client_order_id);
+            return Ok(());
+        }
+        if stat_guard
+            .terminal_order_ids
+            .contains(&order.client_order_id)
+        {
+            log::debug!(
+                "ignore stale non-terminal order update for terminal order {}",
+                order.client_order_id
+            );
+            return Ok(());
+        }
+
         if !stat_guard.orders.contains_key(&order.client_order_id)
             || stat_guard.orders[&order.client_order_id].update_time <= order.update_time
         {
@@ -511,26 +795,31 @@ impl TradeData {
 
     // 当前缓存状态不考虑trade，仅做透传
     async fn update_user_trade_inner(
-        _open_order_stats: Arc<HashMap<MarketType, Arc<RwLock<OpenOrderTradeStat>>>>,
+        _open_order_stats: Arc<HashMap<AccountKey, Arc<RwLock<OpenOrderTradeStat>>>>,
         db: Arc<SQLiteDB>,
         market_type: &MarketType,
+        account_id: &str,
         trade: UserTrade,
     ) -> Result<()> {
-        update_user_trade(db.clone(), market_type, &trade)
+        update_user_trade(db.clone(), market_type, account_id, &trade)
     }
 
     async fn update_account_inner(
-        accounts: Arc<HashMap<MarketType, Arc<RwLock<Option<Account>>>>>,
+        accounts: Arc<HashMap<AccountKey, Arc<RwLock<Option<Account>>>>>,
         db: Arc<SQLiteDB>,
         market_type: &MarketType,
+        account_id: &str,
         account: Account,
     ) -> Result<()> {
-        update_account(db.clone(), market_type, &account)?;
+        update_account(db.clone(), market_type, account_id, &account)?;
 
         let account_lock = accounts
-            .get(market_type)
+            .get(&(market_type.clone(), account_id.to_string()))
             .ok_or(PlatformError::DataManagerError {
-                message: format!("account lock not found for market_type {:?}", market_type),
+                message: format!(
+                    "account lock not found for market_type {:?}, account {}",
+                    market_type, account_id
+                ),
             })?;
         let mut account_guard = account_lock.write().await;
 
@@ -543,17 +832,21 @@ impl TradeData {
     }
 
     async fn update_account_update_inner(
-        accounts: Arc<HashMap<MarketType, Arc<RwLock<Option<Account>>>>>,
+        accounts: Arc<HashMap<AccountKey, Arc<RwLock<Option<Account>>>>>,
         db: Arc<SQLiteDB>,
         market_type: &MarketType,
+        account_id: &str,
         account_update: AccountUpdate,
     ) -> Result<()> {
-        update_account_update(db.clone(), market_type, &account_update)?;
+        update_account_update(db.clone(), market_type, account_id, &account_update)?;
 
         let account_lock = accounts
-            .get(market_type)
+            .get(&(market_type.clone(), account_id.to_string()))
             .ok_or(PlatformError::DataManagerError {
-                message: format!("account lock not found for market_type {:?}", market_type),
+                message: format!(
+                    "account lock not found for market_type {:?}, account {}",
+                    market_type, account_id
+                ),
             })?;
         let mut account_guard = account_lock.write().await;
 
@@ -579,33 +872,80 @@ impl TradeData {
             }
         } else {
             Err(PlatformError::DataManagerError {
-                message: format!("account not initialized for market_type {:?}", market_type),
+                message: format!(
+                    "account not initialized for market_type {:?}, account {}",
+                    market_type, account_id
+                ),
             })
         }
     }
 
+    // 定期同步重试耗尽的累计次数
+    pub fn sync_retry_exhausted_count(&self) -> u64 {
+        self.sync_retry_exhausted.load(Ordering::Relaxed)
+    }
+
+    // 需在触发同步前调用notified()注册等待，再等待其完成
+    pub fn sync_completed(&self) -> Arc<Notify> {
+        self.sync_completed.clone()
+    }
+
     // 暴露db获取接口，仅供测试使用
-    pub fn get_account_from_db(&self, market_type: &MarketType) -> Result<Option<Account>> {
-        get_account(self.db.clone(), market_type)
+    pub fn get_account_from_db(
+        &self,
+        market_type: &MarketType,
+        account_id: &str,
+    ) -> Result<Option<Account>> {
+        get_account(self.db.clone(), market_type, account_id)
     }
 
-    pub fn get_open_orders_from_db(&self, market_type: &MarketType) -> Result<Vec<Order>> {
-        get_open_orders(self.db.clone(), market_type)
+    // 余额变化流水，按时间升序，用于绘制权益曲线
+    pub fn get_balance_history(
+        &self,
+        market_type: &MarketType,
+        account_id: &str,
+        asset: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<BalanceSnapshot>> {
+        get_balance_history(self.db.clone(), market_type, account_id, asset, start, end)
+    }
+
+    pub fn get_open_orders_from_db(
+        &self,
+        market_type: &MarketType,
+        account_id: &str,
+    ) -> Result<Vec<Order>> {
+        get_open_orders(self.db.clone(), market_type, account_id)
+    }
+}
+
+impl TradeAccount {
+    pub fn account_id(&self) -> &str {
+        &self.account_id
+    }
+
+    fn key(&self, market_type: &MarketType) -> AccountKey {
+        (market_type.clone(), self.account_id.clone())
     }
 }
 
 #[async_trait]
-impl TradeDataManager for TradeData {
+impl TradeDataManager for TradeAccount {
+    // 由所属TradeData统一初始化
     async fn init(&self) -> Result<()> {
-        self.init().await
+        Ok(())
     }
 
     async fn get_account(&self, market_type: &MarketType) -> Result<Option<Account>> {
         let account_lock =
             self.accounts
-                .get(market_type)
+                .get(&self.key(market_type))
                 .ok_or(PlatformError::DataManagerError {
-                    message: format!("account lock not found for market_type {:?}", market_type),
+                    message: format!(
+                        "account lock not found for market_type {:?}, account {}",
+                        market_type, self.account_id
+                    ),
                 })?;
 
         let account_guard = account_lock.read().await;
@@ -613,15 +953,14 @@ impl TradeDataManager for TradeData {
     }
 
     async fn get_open_orders(&self, market_type: &MarketType) -> Result<Vec<Order>> {
-        let stat_lock =
-            self.open_order_stats
-                .get(market_type)
-                .ok_or(PlatformError::DataManagerError {
-                    message: format!(
-                        "open_order_stats lock not found for market_type {:?}",
-                        market_type
-                    ),
-                })?;
+        let stat_lock = self.open_order_stats.get(&self.key(market_type)).ok_or(
+            PlatformError::DataManagerError {
+                message: format!(
+                    "open_order_stats lock not found for market_type {:?}, account {}",
+                    market_type, self.account_id
+                ),
+            },
+        )?;
 
         let stat_guard = stat_lock.read().await;
         let orders: Vec<Order> = stat_guard.orders.values().cloned().collect();
@@ -634,7 +973,13 @@ impl TradeDataManager for TradeData {
         symbol: &str,
         order_id: &str,
     ) -> Result<Vec<UserTrade>> {
-        get_user_trades_by_order(self.db.clone(), market_type, symbol, order_id)
+        get_user_trades_by_order(
+            self.db.clone(),
+            market_type,
+            &self.account_id,
+            symbol,
+            order_id,
+        )
     }
 
     async fn get_orders(
@@ -648,6 +993,7 @@ impl TradeDataManager for TradeData {
         get_orders(
             self.db.clone(),
             market_type,
+            &self.account_id,
             symbol,
             start_time,
             end_time,
@@ -666,6 +1012,7 @@ impl TradeDataManager for TradeData {
         get_user_trades(
             self.db.clone(),
             market_type,
+            &self.account_id,
             symbol,
             start_time,
             end_time,
@@ -679,7 +1026,13 @@ impl TradeDataManager for TradeData {
         symbol: &str,
         client_order_id: &str,
     ) -> Result<Option<Order>> {
-        get_order_by_client_id(self.db.clone(), market_type, symbol, client_order_id)
+        get_order_by_client_id(
+            self.db.clone(),
+            market_type,
+            &self.account_id,
+            symbol,
+            client_order_id,
+        )
     }
 
     async fn get_order_by_id(
@@ -688,40 +1041,50 @@ impl TradeDataManager for TradeData {
         symbol: &str,
         order_id: &str,
     ) -> Result<Option<Order>> {
-        get_order_by_id(self.db.clone(), market_type, symbol, order_id)
+        get_order_by_id(
+            self.db.clone(),
+            market_type,
+            &self.account_id,
+            symbol,
+            order_id,
+        )
     }
 
     async fn get_last_sync_ts(&self, market_type: &MarketType) -> Result<Option<u64>> {
-        get_last_sync_ts(self.db.clone(), market_type)
+        get_last_sync_ts(self.db.clone(), market_type, &self.account_id)
     }
 
     async fn place_order(&self, market_type: &MarketType, req: PlaceOrderRequest) -> Result<Order> {
         let mut order = Order::new_order_from_place_order_req(&req);
-        Self::update_order_inner(
+        TradeData::update_order_inner(
             self.open_order_stats.clone(),
             self.db.clone(),
             market_type,
+            &self.account_id,
             order.clone(),
         )
         .await?;
 
-        let trade_provider =
-            self.trade_providers
-                .get(market_type)
-                .ok_or(PlatformError::DataManagerError {
-                    message: format!(
-                        "Trade provider not found for market type: {:?}",
-                        market_type
-                    ),
-                })?;
+        let trade_provider = self.trade_providers.get(&self.key(market_type)).ok_or(
+            PlatformError::DataManagerError {
+                message: format!(
+                    "Trade provider not found for market type: {:?}, account: {}",
+                    market_type, self.account_id
+                ),
+            },
+        )?;
         match trade_provider.place_order(req).await {
-            Ok(order) => Ok(order),
+            Ok(order) => {
+                self.metrics.incr("trade.order_placed", 1);
+                Ok(order)
+            }
             Err(e) => {
                 order.order_status = OrderStatus::Rejected;
-                Self::update_order_inner(
+                TradeData::update_order_inner(
                     self.open_order_stats.clone(),
                     self.db.clone(),
                     market_type,
+                    &self.account_id,
                     order,
                 )
                 .await?;
@@ -731,16 +1094,253 @@ impl TradeDataManager for TradeData {
     }
 
     async fn cancel_order(&self, market_type: &MarketType, req: CancelOrderRequest) -> Result<()> {
-        let trade_provider =
-            self.trade_providers
-                .get(market_type)
-                .ok_or(PlatformError::DataManagerError {
+        let trade_provider = self.trade_providers.get(&self.key(market_type)).ok_or(
+            PlatformError::DataManagerError {
+                message: format!(
+                    "Trade provider not found for market type: {:?}, account: {}",
+                    market_type, self.account_id
+                ),
+            },
+        )?;
+        trade_provider.cancel_order(req).await?;
+        self.metrics.incr("trade.order_canceled", 1);
+        Ok(())
+    }
+
+    async fn amend_order(&self, market_type: &MarketType, req: AmendOrderRequest) -> Result<Order> {
+        let stat_lock = self.open_order_stats.get(&self.key(market_type)).ok_or(
+            PlatformError::DataManagerError {
+                message: format!(
+                    "open_order_stats lock not found for market_type {:?}, account {}",
+                    market_type, self.account_id
+                ),
+            },
+        )?;
+        // 仅在途订单可修改，终态订单已从缓存移除
+        let order = {
+            let stat_guard = stat_lock.read().await;
+            if stat_guard.terminal_order_ids.contains(&req.client_order_id) {
+                return Err(PlatformError::DataManagerError {
                     message: format!(
-                        "Trade provider not found for market type: {:?}",
-                        market_type
+                        "order {} is in terminal status, can not amend",
+                        req.client_order_id
                     ),
-                })?;
-        trade_provider.cancel_order(req).await
+                });
+            }
+            stat_guard.orders.get(&req.client_order_id).cloned().ok_or(
+                PlatformError::DataManagerError {
+                    message: format!("open order {} not found", req.client_order_id),
+                },
+            )?
+        };
+        if req
+            .order_id
+            .as_ref()
+            .is_some_and(|order_id| !order.order_id.is_empty() && order.order_id != *order_id)
+        {
+            return Err(PlatformError::DataManagerError {
+                message: format!(
+                    "order_id mismatch for client_order_id {}: expect {}, got {}",
+                    req.client_order_id,
+                    order.order_id,
+                    req.order_id.unwrap_or_default()
+                ),
+            });
+        }
+        if order.order_type != OrderType::Limit {
+            return Err(PlatformError::DataManagerError {
+                message: format!(
+                    "only limit order can be amended, got {:?}",
+                    order.order_type
+                ),
+            });
+        }
+        // 撤单重下后新订单与原订单并存于缓存/db，client_order_id不可复用
+        if req.new_client_order_id == order.client_order_id {
+            return Err(PlatformError::DataManagerError {
+                message: format!(
+                    "new_client_order_id must differ from client_order_id {}",
+                    order.client_order_id
+                ),
+            });
+        }
+        let quantity = req.quantity.unwrap_or(order.order_quantity);
+        if quantity <= order.executed_qty {
+            return Err(PlatformError::DataManagerError {
+                message: format!(
+                    "amend quantity {} must be greater than executed quantity {}",
+                    quantity, order.executed_qty
+                ),
+            });
+        }
+
+        // 新订单仅包含未成交部分
+        let new_order_req = PlaceOrderRequest {
+            symbol: order.symbol.clone(),
+            side: order.order_side.clone(),
+            r#type: order.order_type.clone(),
+            time_in_force: Some(order.time_in_force.clone()),
+            quantity: Some(quantity - order.executed_qty),
+            price: Some(req.price.unwrap_or(order.order_price)),
+            client_order_id: req.new_client_order_id.clone(),
+            stop_price: None,
+            iceberg_qty: if order.iceberg_qty.is_zero() {
+                None
+            } else {
+                Some(order.iceberg_qty)
+            },
+            quote_order_qty: None,
+        };
+        let mut new_order = Order::new_order_from_place_order_req(&new_order_req);
+        TradeData::update_order_inner(
+            self.open_order_stats.clone(),
+            self.db.clone(),
+            market_type,
+            &self.account_id,
+            new_order.clone(),
+        )
+        .await?;
+
+        let trade_provider = self.trade_providers.get(&self.key(market_type)).ok_or(
+            PlatformError::DataManagerError {
+                message: format!(
+                    "Trade provider not found for market type: {:?}, account: {}",
+                    market_type, self.account_id
+                ),
+            },
+        )?;
+        let cancel_replace_req = CancelReplaceOrderRequest {
+            cancel_order_id: if order.order_id.is_empty() {
+                None
+            } else {
+                Some(order.order_id.clone())
+            },
+            cancel_client_order_id: order.client_order_id.clone(),
+            new_order: new_order_req,
+        };
+        match trade_provider
+            .cancel_replace_order(cancel_replace_req)
+            .await
+        {
+            Ok(replaced) => {
+                // 原订单已撤销，先在本地标记终态，避免等待推送期间仍计入在途订单
+                let mut canceled = order;
+                canceled.order_status = OrderStatus::Canceled;
+                canceled.update_time = canceled.update_time.max(replaced.update_time);
+                TradeData::update_order_inner(
+                    self.open_order_stats.clone(),
+                    self.db.clone(),
+                    market_type,
+                    &self.account_id,
+                    canceled,
+                )
+                .await?;
+                self.metrics.incr("trade.order_amended", 1);
+                Ok(replaced)
+            }
+            Err(e) => {
+                new_order.order_status = OrderStatus::Rejected;
+                TradeData::update_order_inner(
+                    self.open_order_stats.clone(),
+                    self.db.clone(),
+                    market_type,
+                    &self.account_id,
+                    new_order,
+                )
+                .await?;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TradeDataManager for TradeData {
+    async fn init(&self) -> Result<()> {
+        self.init().await
+    }
+
+    async fn get_account(&self, market_type: &MarketType) -> Result<Option<Account>> {
+        self.default_account.get_account(market_type).await
+    }
+
+    async fn get_open_orders(&self, market_type: &MarketType) -> Result<Vec<Order>> {
+        self.default_account.get_open_orders(market_type).await
+    }
+
+    async fn get_user_trades_by_order(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<Vec<UserTrade>> {
+        self.default_account
+            .get_user_trades_by_order(market_type, symbol, order_id)
+            .await
+    }
+
+    async fn get_orders(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Order>> {
+        self.default_account
+            .get_orders(market_type, symbol, start_time, end_time, limit)
+            .await
+    }
+
+    async fn get_user_trades(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<Vec<UserTrade>> {
+        self.default_account
+            .get_user_trades(market_type, symbol, start_time, end_time, limit)
+            .await
+    }
+
+    async fn get_order_by_client_id(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+        client_order_id: &str,
+    ) -> Result<Option<Order>> {
+        self.default_account
+            .get_order_by_client_id(market_type, symbol, client_order_id)
+            .await
+    }
+
+    async fn get_order_by_id(
+        &self,
+        market_type: &MarketType,
+        symbol: &str,
+        order_id: &str,
+    ) -> Result<Option<Order>> {
+        self.default_account
+            .get_order_by_id(market_type, symbol, order_id)
+            .await
+    }
+
+    async fn get_last_sync_ts(&self, market_type: &MarketType) -> Result<Option<u64>> {
+        self.default_account.get_last_sync_ts(market_type).await
+    }
+
+    async fn place_order(&self, market_type: &MarketType, req: PlaceOrderRequest) -> Result<Order> {
+        self.default_account.place_order(market_type, req).await
+    }
+
+    async fn cancel_order(&self, market_type: &MarketType, req: CancelOrderRequest) -> Result<()> {
+        self.default_account.cancel_order(market_type, req).await
+    }
+
+    async fn amend_order(&self, market_type: &MarketType, req: AmendOrderRequest) -> Result<Order> {
+        self.default_account.amend_order(market_type, req).await
     }
 }
 
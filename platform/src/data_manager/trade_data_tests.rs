@@ -1,19 +1,39 @@
+use crate::models::{AccountUpdate, OrderStatus};
 use crate::{
-    config::{Config, PlatformConfig},
-    data_manager::{trade_data::TradeData, TradeDataManager},
+    backtest::test_utils::symbol_info,
+    config::{Config, PlatformConfig, DEFAULT_ACCOUNT_ID},
+    data_manager::{
+        db::*,
+        local_data_manager::Clock,
+        memory_data_manager::InMemoryMarketDataManager,
+        position_manager::PositionManager,
+        trade_data::{SyncWindowPolicy, TradeData},
+        TradeDataManager,
+    },
+    errors::PlatformError,
     models::{
-        Account, Balance, CancelOrderRequest, GetAllOrdersRequest, GetOpenOrdersRequest,
-        GetUserTradesRequest, MarketType, Order, OrderSide, OrderType, PlaceOrderRequest,
-        TimeInForce, UserTrade,
+        Account, AmendOrderRequest, Balance, BalanceSnapshot, CancelOrderRequest,
+        GetAllOrdersRequest, GetOpenOrdersRequest, GetUserTradesRequest, MarketType, Order,
+        OrderSide, OrderType, PlaceOrderRequest, TimeInForce, UserTrade,
+    },
+    trade_provider::{
+        binance_spot_trade_provider::BinanceSpotTradeProvider,
+        mock_trade_provider::MockTradeProvider, TradeProvider,
     },
-    trade_provider::{binance_spot_trade_provider::BinanceSpotTradeProvider, TradeProvider},
 };
+use db::sqlite::SQLiteDB;
 use env_logger::Env;
 use json::dump;
 use log::info;
 use rust_decimal::Decimal;
-use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
 use tempfile::NamedTempFile;
+use time::InMemoryMetrics;
 
 #[tokio::test]
 async fn test_trade_data_with_binance_operations_and_persistence() {
@@ -72,8 +92,11 @@ async fn test_trade_data_with_binance_operations_and_persistence() {
     .unwrap();
     provider.init().await.unwrap();
     let provider = Arc::new(provider);
-    let mut trade_providers: HashMap<MarketType, Arc<dyn TradeProvider>> = HashMap::new();
-    trade_providers.insert(MarketType::BinanceSpot, provider.clone());
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (MarketType::BinanceSpot, DEFAULT_ACCOUNT_ID.to_string()),
+        provider.clone(),
+    );
 
     let trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
     let trade_data_ptr = Arc::new(trade_data);
@@ -96,6 +119,7 @@ async fn test_trade_data_with_binance_operations_and_persistence() {
         client_order_id: format!("test_buy_limit_{}", time::get_current_milli_timestamp()),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
     let order_1: Order = trade_data
         .place_order(&MarketType::BinanceSpot, buy_limit_order.clone())
@@ -114,6 +138,7 @@ async fn test_trade_data_with_binance_operations_and_persistence() {
         client_order_id: format!("test_cancel_{}", time::get_current_milli_timestamp()),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
     let order_2: Order = trade_data
         .place_order(&MarketType::BinanceSpot, buy_order_to_cancel.clone())
@@ -145,6 +170,7 @@ async fn test_trade_data_with_binance_operations_and_persistence() {
         client_order_id: format!("test_sell_{}", time::get_current_milli_timestamp()),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
     let order_3: Order = trade_data
         .place_order(&MarketType::BinanceSpot, sell_market_order.clone())
@@ -163,6 +189,7 @@ async fn test_trade_data_with_binance_operations_and_persistence() {
         client_order_id: format!("test_{}", time::get_current_milli_timestamp()),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
     let order_4: Order = trade_data
         .place_order(&MarketType::BinanceSpot, sell_order_to_cancel.clone())
@@ -181,7 +208,7 @@ async fn test_trade_data_with_binance_operations_and_persistence() {
         .unwrap()
         .unwrap();
     let account2 = trade_data_ptr
-        .get_account_from_db(&MarketType::BinanceSpot)
+        .get_account_from_db(&MarketType::BinanceSpot, DEFAULT_ACCOUNT_ID)
         .unwrap()
         .unwrap();
     let account3 = provider.get_account().await.unwrap();
@@ -195,7 +222,7 @@ async fn test_trade_data_with_binance_operations_and_persistence() {
         .await
         .unwrap();
     let open_orders2 = trade_data_ptr
-        .get_open_orders_from_db(&MarketType::BinanceSpot)
+        .get_open_orders_from_db(&MarketType::BinanceSpot, DEFAULT_ACCOUNT_ID)
         .unwrap();
     let open_orders3 = provider
         .get_open_orders(GetOpenOrdersRequest { symbol: None })
@@ -271,6 +298,1188 @@ async fn test_trade_data_with_binance_operations_and_persistence() {
     dump(&trades1, "trade_data_user_trades.json").unwrap();
 }
 
+#[test]
+fn test_sync_orders_and_trades_rollback_on_trade_failure() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    create_api_sync_ts_table(db.clone()).unwrap();
+    create_orders_table(db.clone()).unwrap();
+    create_user_trades_table(db.clone()).unwrap();
+
+    let market_type = MarketType::BinanceSpot;
+    let mut order = Order::new_order_from_place_order_req(&PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: OrderSide::Buy,
+        r#type: OrderType::Limit,
+        time_in_force: Some(TimeInForce::Gtc),
+        quantity: Some(Decimal::from_str("0.001").unwrap()),
+        price: Some(Decimal::from_str("100000").unwrap()),
+        client_order_id: "sync_order_1".to_string(),
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: None,
+    });
+    order.order_id = "1".to_string();
+    order.update_time = 1000;
+    let trade = UserTrade {
+        trade_id: "1".to_string(),
+        order_id: "1".to_string(),
+        symbol: "BTCUSDT".to_string(),
+        order_side: OrderSide::Buy,
+        trade_price: Decimal::from_str("100000").unwrap(),
+        trade_quantity: Decimal::from_str("0.001").unwrap(),
+        commission: Decimal::ZERO,
+        commission_asset: "BTC".to_string(),
+        is_maker: 1,
+        timestamp: 1000,
+    };
+
+    // 正常批次：数据与last_sync_ts一并提交
+    sync_orders_and_trades(
+        db.clone(),
+        &market_type,
+        DEFAULT_ACCOUNT_ID,
+        &[],
+        &[trade.clone()],
+        500,
+    )
+    .unwrap();
+    assert_eq!(
+        get_last_sync_ts(db.clone(), &market_type, DEFAULT_ACCOUNT_ID).unwrap(),
+        Some(500)
+    );
+
+    // 注入trade写入失败：整批回滚，last_sync_ts不推进，order也不落库
+    db.execute_update("DROP TABLE user_trades", &[]).unwrap();
+    let result = sync_orders_and_trades(
+        db.clone(),
+        &market_type,
+        DEFAULT_ACCOUNT_ID,
+        &[order.clone()],
+        &[trade.clone()],
+        2000,
+    );
+    assert!(result.is_err());
+    assert_eq!(
+        get_last_sync_ts(db.clone(), &market_type, DEFAULT_ACCOUNT_ID).unwrap(),
+        Some(500)
+    );
+    assert!(
+        get_open_orders(db.clone(), &market_type, DEFAULT_ACCOUNT_ID)
+            .unwrap()
+            .is_empty()
+    );
+
+    // 回滚后连接可继续使用
+    create_user_trades_table(db.clone()).unwrap();
+    sync_orders_and_trades(
+        db.clone(),
+        &market_type,
+        DEFAULT_ACCOUNT_ID,
+        &[order],
+        &[trade],
+        2000,
+    )
+    .unwrap();
+    assert_eq!(
+        get_last_sync_ts(db.clone(), &market_type, DEFAULT_ACCOUNT_ID).unwrap(),
+        Some(2000)
+    );
+    assert_eq!(
+        get_open_orders(db.clone(), &market_type, DEFAULT_ACCOUNT_ID)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_sync_orders_and_trades_concurrent_with_stream_writes() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    create_api_sync_ts_table(db.clone()).unwrap();
+    create_orders_table(db.clone()).unwrap();
+    create_user_trades_table(db.clone()).unwrap();
+
+    let market_type = MarketType::BinanceSpot;
+    let make_order = |order_id: String| {
+        let mut order = Order::new_order_from_place_order_req(&PlaceOrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            r#type: OrderType::Limit,
+            time_in_force: Some(TimeInForce::Gtc),
+            quantity: Some(Decimal::from_str("0.001").unwrap()),
+            price: Some(Decimal::from_str("100000").unwrap()),
+            client_order_id: format!("client_{}", order_id),
+            stop_price: None,
+            iceberg_qty: None,
+            quote_order_qty: None,
+        });
+        order.order_id = order_id;
+        order.update_time = 1000;
+        order
+    };
+
+    // 同步事务与推送写入并发：事务不会嵌套报错，推送写入也不会被卷入其他事务
+    let mut handles = vec![];
+    for i in 0..4 {
+        let sync_db = db.clone();
+        let sync_orders: Vec<Order> = (0..20)
+            .map(|j| make_order(format!("sync_{}_{}", i, j)))
+            .collect();
+        handles.push(std::thread::spawn(move || {
+            for (j, order) in sync_orders.iter().enumerate() {
+                sync_orders_and_trades(
+                    sync_db.clone(),
+                    &MarketType::BinanceSpot,
+                    DEFAULT_ACCOUNT_ID,
+                    std::slice::from_ref(order),
+                    &[],
+                    (i * 100 + j) as u64,
+                )
+                .unwrap();
+            }
+        }));
+        let stream_db = db.clone();
+        let stream_orders: Vec<Order> = (0..20)
+            .map(|j| make_order(format!("stream_{}_{}", i, j)))
+            .collect();
+        handles.push(std::thread::spawn(move || {
+            for order in stream_orders.iter() {
+                update_order(
+                    stream_db.clone(),
+                    &MarketType::BinanceSpot,
+                    DEFAULT_ACCOUNT_ID,
+                    order,
+                )
+                .unwrap();
+            }
+        }));
+    }
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(
+        get_open_orders(db.clone(), &market_type, DEFAULT_ACCOUNT_ID)
+            .unwrap()
+            .len(),
+        160
+    );
+}
+
+#[test]
+fn test_balance_history_concurrent_writes() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    create_account_balance_table(db.clone()).unwrap();
+    create_balance_history_table(db.clone()).unwrap();
+
+    // 多个资产的余额推送并发写入：流水与快照的事务互不嵌套，流水一条不丢
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let db = db.clone();
+            std::thread::spawn(move || {
+                for ts in 1..=100u64 {
+                    update_account_balance(
+                        db.clone(),
+                        &MarketType::BinanceSpot,
+                        DEFAULT_ACCOUNT_ID,
+                        &vec![Balance {
+                            asset: format!("ASSET{}", i),
+                            free: Decimal::from(ts),
+                            locked: Decimal::ZERO,
+                        }],
+                        ts,
+                    )
+                    .unwrap();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    for i in 0..8 {
+        let history = get_balance_history(
+            db.clone(),
+            &MarketType::BinanceSpot,
+            DEFAULT_ACCOUNT_ID,
+            &format!("ASSET{}", i),
+            0,
+            u64::MAX,
+        )
+        .unwrap();
+        assert_eq!(history.len(), 100);
+    }
+    let account = get_account(db.clone(), &MarketType::BinanceSpot, DEFAULT_ACCOUNT_ID)
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.balances.len(), 8);
+    assert!(account
+        .balances
+        .iter()
+        .all(|balance| balance.free == Decimal::from(100)));
+}
+
+fn mock_platform_config(db_path: &str, trade_refresh_interval_secs: u64) -> Arc<PlatformConfig> {
+    mock_platform_config_with_sub_accounts(db_path, trade_refresh_interval_secs, &[])
+}
+
+fn mock_platform_config_with_sub_accounts(
+    db_path: &str,
+    trade_refresh_interval_secs: u64,
+    sub_account_ids: &[&str],
+) -> Arc<PlatformConfig> {
+    mock_platform_config_with(db_path, trade_refresh_interval_secs, sub_account_ids, "")
+}
+
+// extra_fields为追加到市场配置中的字段，需以逗号结尾
+fn mock_platform_config_with(
+    db_path: &str,
+    trade_refresh_interval_secs: u64,
+    sub_account_ids: &[&str],
+    extra_fields: &str,
+) -> Arc<PlatformConfig> {
+    let sub_accounts = sub_account_ids
+        .iter()
+        .map(|id| format!(r#""{}": {{"api_key": "", "secret_key": ""}}"#, id))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let config_content = r#"
+    {
+        "markets": ["binance_spot"],
+        "db_path": "{placeholder}",
+        "binance_spot": {
+            "trade_refresh_interval_secs": {refresh_interval},
+            "trade_sync_retry_times": 3,
+            "trade_sync_retry_backoff_milli_secs": 10,
+            "api_base_url": "",
+            "stream_base_url": "",
+            "stream_api_base_url": "",
+            "api_key": "",
+            "secret_key": "",
+            "subscribed_symbols": ["BTCUSDT"],
+            "subscribed_kline_intervals": ["1m"],
+            {extra_fields}
+            "sub_accounts": {{sub_accounts}}
+        }
+    }
+    "#;
+    let config_content = config_content
+        .replace("{placeholder}", db_path)
+        .replace(
+            "{refresh_interval}",
+            &trade_refresh_interval_secs.to_string(),
+        )
+        .replace("{sub_accounts}", &sub_accounts)
+        .replace("{extra_fields}", extra_fields);
+    let mut config_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config_file, config_content.as_bytes()).unwrap();
+    let config = Config::from_json(config_file.path().to_str().unwrap()).unwrap();
+    Arc::new(PlatformConfig::from_config(config).unwrap())
+}
+
+fn mock_order(client_order_id: &str, status: OrderStatus, update_time: u64) -> Order {
+    let mut order = Order::new_order_from_place_order_req(&PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: OrderSide::Buy,
+        r#type: OrderType::Limit,
+        time_in_force: Some(TimeInForce::Gtc),
+        quantity: Some(Decimal::from_str("0.001").unwrap()),
+        price: Some(Decimal::from_str("100000").unwrap()),
+        client_order_id: client_order_id.to_string(),
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: None,
+    });
+    order.order_id = client_order_id.to_string();
+    order.order_status = status;
+    order.create_time = update_time;
+    order.update_time = update_time;
+    order
+}
+
+#[tokio::test]
+async fn test_periodic_sync_retries_until_success() {
+    let db_file = NamedTempFile::new().unwrap();
+    let platform_config = mock_platform_config(db_file.path().to_str().unwrap(), 60);
+
+    let now = time::get_current_milli_timestamp();
+    let provider = Arc::new(MockTradeProvider::new(2));
+    provider
+        .open_orders
+        .lock()
+        .unwrap()
+        .push(mock_order("open_1", OrderStatus::New, now - 60_000));
+    provider.all_orders.lock().unwrap().push(mock_order(
+        "filled_1",
+        OrderStatus::Filled,
+        now - 30_000,
+    ));
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (MarketType::BinanceSpot, DEFAULT_ACCOUNT_ID.to_string()),
+        provider.clone(),
+    );
+
+    let trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
+    trade_data.init().await.unwrap();
+
+    // 首次tick立即触发，两次失败后重试成功，远早于下一个刷新周期(60s)
+    let mut synced = false;
+    for _ in 0..100 {
+        if trade_data
+            .get_last_sync_ts(&MarketType::BinanceSpot)
+            .await
+            .unwrap()
+            .is_some()
+        {
+            synced = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(synced);
+    assert_eq!(provider.all_orders_calls.load(Ordering::SeqCst), 3);
+    assert_eq!(trade_data.sync_retry_exhausted_count(), 0);
+    let order = trade_data
+        .get_order_by_client_id(&MarketType::BinanceSpot, "BTCUSDT", "filled_1")
+        .await
+        .unwrap();
+    assert_eq!(order.unwrap().order_status, OrderStatus::Filled);
+}
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+
+#[test]
+fn test_sync_window_policy() {
+    let now = 100 * HOUR_MS;
+    // 默认值：首次同步回溯一天，截止到当前时间前5s
+    let config = mock_platform_config("", 60);
+    let market_config = &config.configs[&MarketType::BinanceSpot];
+    let policy = SyncWindowPolicy {
+        initial_lookback: market_config.trade_sync_initial_lookback_milli_secs,
+        max_gap: market_config.trade_sync_max_gap_milli_secs,
+        safety_margin: market_config.trade_sync_safety_margin_milli_secs,
+    };
+    let end_ts = now - 5000;
+    assert_eq!(
+        policy.windows(None, now),
+        vec![(end_ts - 24 * HOUR_MS + 1, end_ts)]
+    );
+    assert_eq!(
+        policy.windows(Some(end_ts - HOUR_MS), now),
+        vec![(end_ts - HOUR_MS, end_ts)]
+    );
+    // 已同步到最新时无需拉取
+    assert!(policy.windows(Some(end_ts), now).is_empty());
+
+    // 停机两天多，按一天分段补齐而不是丢弃更早的数据
+    let windows = policy.windows(Some(end_ts - 50 * HOUR_MS), now);
+    assert_eq!(windows.len(), 3);
+    assert_eq!(windows[0].0, end_ts - 50 * HOUR_MS);
+    assert_eq!(windows.last().unwrap().1, end_ts);
+    for w in windows.windows(2) {
+        assert_eq!(w[0].1 + 1, w[1].0);
+    }
+    assert!(windows.iter().all(|(s, e)| e - s < 24 * HOUR_MS));
+
+    let policy = SyncWindowPolicy {
+        initial_lookback: 3 * HOUR_MS,
+        max_gap: HOUR_MS,
+        safety_margin: 0,
+    };
+    let windows = policy.windows(None, now);
+    assert_eq!(
+        windows,
+        vec![
+            (now - 3 * HOUR_MS + 1, now - 2 * HOUR_MS),
+            (now - 2 * HOUR_MS + 1, now - HOUR_MS),
+            (now - HOUR_MS + 1, now),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_periodic_sync_chunks_long_gap() {
+    let db_file = NamedTempFile::new().unwrap();
+    let platform_config = mock_platform_config_with(
+        db_file.path().to_str().unwrap(),
+        60,
+        &[],
+        &format!(
+            r#""trade_sync_initial_lookback_milli_secs": {}, "trade_sync_max_gap_milli_secs": {}, "trade_sync_safety_margin_milli_secs": 1000,"#,
+            10 * HOUR_MS,
+            4 * HOUR_MS
+        ),
+    );
+
+    let now = time::get_current_milli_timestamp();
+    let provider = Arc::new(MockTradeProvider::new(0));
+    provider
+        .open_orders
+        .lock()
+        .unwrap()
+        .push(mock_order("open_1", OrderStatus::New, now - 60_000));
+    provider.all_orders.lock().unwrap().push(mock_order(
+        "filled_1",
+        OrderStatus::Filled,
+        now - 9 * HOUR_MS,
+    ));
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (MarketType::BinanceSpot, DEFAULT_ACCOUNT_ID.to_string()),
+        provider.clone(),
+    );
+
+    let trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
+    trade_data.init().await.unwrap();
+
+    let mut last_sync_ts = None;
+    for _ in 0..100 {
+        last_sync_ts = trade_data
+            .get_last_sync_ts(&MarketType::BinanceSpot)
+            .await
+            .unwrap();
+        if provider.all_orders_calls.load(Ordering::SeqCst) >= 3 && last_sync_ts.is_some() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // 回溯10小时，每段不超过4小时：分3次拉取，首尾相接
+    let windows = provider.all_orders_windows.lock().unwrap().clone();
+    assert_eq!(windows.len(), 3);
+    let first_start = windows[0].0;
+    assert!(first_start + 10 * HOUR_MS + 1000 > now && first_start + 10 * HOUR_MS <= now + 5000);
+    for w in windows.windows(2) {
+        assert_eq!(w[0].1 + 1, w[1].0);
+    }
+    assert!(windows.iter().all(|(s, e)| e - s < 4 * HOUR_MS));
+    assert_eq!(last_sync_ts, Some(windows[2].1));
+    assert!(windows[2].1 <= time::get_current_milli_timestamp() - 1000);
+    let order = trade_data
+        .get_order_by_client_id(&MarketType::BinanceSpot, "BTCUSDT", "filled_1")
+        .await
+        .unwrap();
+    assert_eq!(order.unwrap().order_status, OrderStatus::Filled);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_periodic_sync_uses_market_refresh_interval() {
+    let db_file = NamedTempFile::new().unwrap();
+    // 非默认的市场刷新间隔
+    let refresh_interval = Duration::from_secs(7);
+    let platform_config =
+        mock_platform_config(db_file.path().to_str().unwrap(), refresh_interval.as_secs());
+
+    let now = time::get_current_milli_timestamp();
+    let provider = Arc::new(MockTradeProvider::new(0));
+    provider
+        .open_orders
+        .lock()
+        .unwrap()
+        .push(mock_order("open_1", OrderStatus::New, now - 60_000));
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (MarketType::BinanceSpot, DEFAULT_ACCOUNT_ID.to_string()),
+        provider.clone(),
+    );
+
+    let trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
+    let sync_completed = trade_data.sync_completed();
+    // 首次tick立即触发
+    let notified = sync_completed.notified();
+    trade_data.init().await.unwrap();
+    notified.await;
+    let calls = provider.open_orders_calls.load(Ordering::SeqCst);
+
+    // 之后每经过一个市场配置的间隔同步一轮，每轮拉取一次在途订单
+    let start = tokio::time::Instant::now();
+    for round in 1..=3u32 {
+        let notified = sync_completed.notified();
+        tokio::time::advance(refresh_interval).await;
+        notified.await;
+        assert_eq!(start.elapsed(), refresh_interval * round);
+        assert_eq!(
+            provider.open_orders_calls.load(Ordering::SeqCst),
+            calls + round
+        );
+    }
+}
+
+#[test]
+fn test_missing_market_config_rejected() {
+    let db_file = NamedTempFile::new().unwrap();
+    let platform_config = PlatformConfig {
+        markets: vec![MarketType::BinanceSpot],
+        proxy: None,
+        db_path: db_file.path().to_str().unwrap().to_string(),
+        configs: HashMap::new(),
+    };
+    match TradeData::new(Arc::new(platform_config), Arc::new(HashMap::new())) {
+        Err(PlatformError::ConfigError { message }) => {
+            assert!(message.contains("BinanceSpot"), "{}", message)
+        }
+        _ => panic!("expect config error"),
+    }
+}
+
+#[test]
+fn test_batch_upsert_orders_and_trades() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    create_orders_table(db.clone()).unwrap();
+    create_user_trades_table(db.clone()).unwrap();
+    let market_type = MarketType::BinanceSpot;
+    let count = 1200; // 跨越多个语句分片
+
+    let mut orders: Vec<Order> = (0..count)
+        .map(|i| {
+            let status = if i % 4 == 3 {
+                OrderStatus::Filled
+            } else {
+                OrderStatus::New
+            };
+            mock_order(&format!("order_{}", i), status, 1000)
+        })
+        .collect();
+    update_orders(db.clone(), &market_type, DEFAULT_ACCOUNT_ID, &orders).unwrap();
+
+    // 逐行判断：0较新覆盖，1较旧忽略，2同一批次内先新后旧以新的为准，3终态不被非终态覆盖
+    for (i, order) in orders.iter_mut().enumerate() {
+        match i % 4 {
+            0 => {
+                order.order_status = OrderStatus::PartiallyFilled;
+                order.update_time = 2000;
+            }
+            1 => {
+                order.order_status = OrderStatus::Canceled;
+                order.update_time = 500;
+            }
+            2 => {
+                order.order_status = OrderStatus::PartiallyFilled;
+                order.update_time = 3000;
+            }
+            _ => {
+                order.order_status = OrderStatus::New;
+                order.update_time = 4000;
+            }
+        }
+    }
+    let stale: Vec<Order> = orders
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| i % 4 == 2)
+        .map(|(_, order)| {
+            let mut order = order.clone();
+            order.order_status = OrderStatus::Canceled;
+            order.update_time = 2500;
+            order
+        })
+        .collect();
+    orders.extend(stale);
+    update_orders(db.clone(), &market_type, DEFAULT_ACCOUNT_ID, &orders).unwrap();
+
+    let rows = db
+        .execute_query("SELECT COUNT(*) AS cnt FROM orders", &[])
+        .unwrap();
+    assert_eq!(rows.first().unwrap().get_i64("cnt"), Some(count as i64));
+    for i in 0..count {
+        let order = get_order_by_client_id(
+            db.clone(),
+            &market_type,
+            DEFAULT_ACCOUNT_ID,
+            "BTCUSDT",
+            &format!("order_{}", i),
+        )
+        .unwrap()
+        .unwrap();
+        let expected = match i % 4 {
+            0 => (OrderStatus::PartiallyFilled, 2000),
+            1 => (OrderStatus::New, 1000),
+            2 => (OrderStatus::PartiallyFilled, 3000),
+            _ => (OrderStatus::Filled, 1000),
+        };
+        assert_eq!(
+            (order.order_status, order.update_time),
+            expected,
+            "order_{}",
+            i
+        );
+    }
+
+    let mut trades: Vec<UserTrade> = (0..count)
+        .map(|i| UserTrade {
+            trade_id: i.to_string(),
+            order_id: format!("order_{}", i % 10),
+            symbol: "BTCUSDT".to_string(),
+            order_side: OrderSide::Buy,
+            trade_price: Decimal::from(100),
+            trade_quantity: Decimal::ONE,
+            commission: Decimal::ZERO,
+            commission_asset: "USDT".to_string(),
+            is_maker: 0,
+            timestamp: 1000 + i as u64,
+        })
+        .collect();
+    update_user_trades(db.clone(), &market_type, DEFAULT_ACCOUNT_ID, &trades).unwrap();
+    for trade in trades.iter_mut() {
+        trade.commission = Decimal::ONE;
+    }
+    update_user_trades(db.clone(), &market_type, DEFAULT_ACCOUNT_ID, &trades).unwrap();
+    let user_trades = get_user_trades_by_order(
+        db.clone(),
+        &market_type,
+        DEFAULT_ACCOUNT_ID,
+        "BTCUSDT",
+        "order_3",
+    )
+    .unwrap();
+    assert_eq!(user_trades.len(), count / 10);
+    assert!(user_trades.iter().all(|t| t.commission == Decimal::ONE));
+    assert!(user_trades
+        .windows(2)
+        .all(|w| w[0].timestamp < w[1].timestamp));
+    let rows = db
+        .execute_query("SELECT COUNT(*) AS cnt FROM user_trades", &[])
+        .unwrap();
+    assert_eq!(rows.first().unwrap().get_i64("cnt"), Some(count as i64));
+}
+
+// 暂停tokio时钟，由测试推进到下一次周期同步
+#[tokio::test(start_paused = true)]
+async fn test_terminal_order_status_is_sticky() {
+    let db_file = NamedTempFile::new().unwrap();
+    let platform_config = mock_platform_config(db_file.path().to_str().unwrap(), 1);
+
+    let now = time::get_current_milli_timestamp();
+    let provider = Arc::new(MockTradeProvider::new(0));
+    provider
+        .open_orders
+        .lock()
+        .unwrap()
+        .push(mock_order("order_1", OrderStatus::New, now));
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (MarketType::BinanceSpot, DEFAULT_ACCOUNT_ID.to_string()),
+        provider.clone(),
+    );
+
+    let trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
+    trade_data.init().await.unwrap();
+    assert_eq!(
+        trade_data
+            .get_open_orders(&MarketType::BinanceSpot)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    // stream推送成交
+    assert!(provider.push_order(mock_order("order_1", OrderStatus::Filled, now + 1000)) > 0);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(trade_data
+        .get_open_orders(&MarketType::BinanceSpot)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // REST返回陈旧的NEW，且因时钟偏差update_time更大
+    provider.open_orders.lock().unwrap()[0].update_time = now + 2000;
+    let sync_completed = trade_data.sync_completed();
+    // 可能有一轮同步在修改前已开始，再等待一轮完整的同步结束（含落库后的缓存刷新）
+    for _ in 0..2 {
+        let notified = sync_completed.notified();
+        tokio::time::advance(Duration::from_secs(1)).await;
+        notified.await;
+    }
+    assert!(trade_data
+        .get_open_orders(&MarketType::BinanceSpot)
+        .await
+        .unwrap()
+        .is_empty());
+    let order = trade_data
+        .get_order_by_client_id(&MarketType::BinanceSpot, "BTCUSDT", "order_1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(order.order_status, OrderStatus::Filled);
+    assert!(trade_data
+        .get_open_orders_from_db(&MarketType::BinanceSpot, DEFAULT_ACCOUNT_ID)
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_multiple_accounts_are_isolated() {
+    let db_file = NamedTempFile::new().unwrap();
+    let platform_config =
+        mock_platform_config_with_sub_accounts(db_file.path().to_str().unwrap(), 60, &["sub1"]);
+    let market_type = MarketType::BinanceSpot;
+
+    let now = time::get_current_milli_timestamp();
+    let main_provider = Arc::new(MockTradeProvider::new(0));
+    main_provider
+        .open_orders
+        .lock()
+        .unwrap()
+        .push(mock_order("main_open", OrderStatus::New, now));
+    let sub_provider = Arc::new(MockTradeProvider::new(0));
+    *sub_provider.account.lock().unwrap() = Account {
+        balances: vec![
+            Balance {
+                asset: "USDT".to_string(),
+                free: Decimal::from(200),
+                locked: Decimal::ZERO,
+            },
+            Balance {
+                asset: "BTC".to_string(),
+                free: Decimal::ONE,
+                locked: Decimal::ZERO,
+            },
+        ],
+        timestamp: 1,
+    };
+    sub_provider
+        .open_orders
+        .lock()
+        .unwrap()
+        .push(mock_order("sub_open", OrderStatus::New, now));
+
+    // 缺少子账户的provider时初始化失败
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (market_type.clone(), DEFAULT_ACCOUNT_ID.to_string()),
+        main_provider.clone(),
+    );
+    let trade_data =
+        TradeData::new(platform_config.clone(), Arc::new(trade_providers.clone())).unwrap();
+    assert!(trade_data.init().await.is_err());
+    drop(trade_data);
+
+    trade_providers.insert(
+        (market_type.clone(), "sub1".to_string()),
+        sub_provider.clone(),
+    );
+    let trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
+    trade_data.init().await.unwrap();
+    let main_account = trade_data.account(DEFAULT_ACCOUNT_ID);
+    let sub_account = trade_data.account("sub1");
+    assert_eq!(sub_account.account_id(), "sub1");
+
+    // 余额隔离，TradeData自身接口作用于默认账户
+    let main_balances = trade_data.get_account(&market_type).await.unwrap().unwrap();
+    assert_eq!(main_balances.balances.len(), 1);
+    assert_eq!(main_balances.balances[0].free, Decimal::from(1000));
+    let sub_balances = sub_account
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(sub_balances.balances.len(), 2);
+    assert!(account_equal(
+        &sub_balances,
+        &trade_data
+            .get_account_from_db(&market_type, "sub1")
+            .unwrap()
+            .unwrap()
+    ));
+    assert!(account_equal(
+        &main_balances,
+        &trade_data
+            .get_account_from_db(&market_type, DEFAULT_ACCOUNT_ID)
+            .unwrap()
+            .unwrap()
+    ));
+
+    // 在途订单隔离
+    let open_order_ids = |orders: Vec<Order>| {
+        let mut ids = orders
+            .into_iter()
+            .map(|o| o.client_order_id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    };
+    assert_eq!(
+        open_order_ids(main_account.get_open_orders(&market_type).await.unwrap()),
+        vec!["main_open"]
+    );
+    assert_eq!(
+        open_order_ids(sub_account.get_open_orders(&market_type).await.unwrap()),
+        vec!["sub_open"]
+    );
+
+    // 子账户下单与推送只影响子账户
+    sub_account
+        .place_order(
+            &market_type,
+            PlaceOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Sell,
+                r#type: OrderType::Limit,
+                time_in_force: Some(TimeInForce::Gtc),
+                quantity: Some(Decimal::from_str("0.5").unwrap()),
+                price: Some(Decimal::from(100000)),
+                client_order_id: "sub_placed".to_string(),
+                stop_price: None,
+                iceberg_qty: None,
+                quote_order_qty: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert!(
+        sub_provider.push_account_update(AccountUpdate {
+            balances: vec![Balance {
+                asset: "BTC".to_string(),
+                free: Decimal::from_str("0.5").unwrap(),
+                locked: Decimal::from_str("0.5").unwrap(),
+            }],
+            timestamp: 2,
+        }) > 0
+    );
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert_eq!(
+        open_order_ids(sub_account.get_open_orders(&market_type).await.unwrap()),
+        vec!["sub_open", "sub_placed"]
+    );
+    assert_eq!(
+        open_order_ids(trade_data.get_open_orders(&market_type).await.unwrap()),
+        vec!["main_open"]
+    );
+    assert!(trade_data
+        .get_order_by_client_id(&market_type, "BTCUSDT", "sub_placed")
+        .await
+        .unwrap()
+        .is_none());
+    let sub_btc = sub_account
+        .get_account(&market_type)
+        .await
+        .unwrap()
+        .unwrap()
+        .balances
+        .into_iter()
+        .find(|b| b.asset == "BTC")
+        .unwrap();
+    assert_eq!(sub_btc.locked, Decimal::from_str("0.5").unwrap());
+    assert!(account_equal(
+        &trade_data.get_account(&market_type).await.unwrap().unwrap(),
+        &main_balances
+    ));
+
+    // 未配置的账户返回错误
+    assert!(trade_data
+        .account("unknown")
+        .get_account(&market_type)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_scripted_order_ack_and_fill_persisted() {
+    let db_file = NamedTempFile::new().unwrap();
+    let platform_config = mock_platform_config(db_file.path().to_str().unwrap(), 60);
+    let market_type = MarketType::BinanceSpot;
+
+    let now = time::get_current_milli_timestamp();
+    let provider = Arc::new(MockTradeProvider::new(0));
+    provider.queue_account(Account {
+        balances: vec![Balance {
+            asset: "USDT".to_string(),
+            free: Decimal::from(500),
+            locked: Decimal::ZERO,
+        }],
+        timestamp: now,
+    });
+    let mut ack = mock_order("scripted_1", OrderStatus::New, now);
+    ack.order_id = "9001".to_string();
+    provider.queue_order_ack(Ok(ack.clone()));
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (market_type.clone(), DEFAULT_ACCOUNT_ID.to_string()),
+        provider.clone(),
+    );
+
+    let market_mgr = Arc::new(InMemoryMarketDataManager::new(Arc::new(Clock::new(now))));
+    market_mgr.add_symbol_info(&market_type, symbol_info("BTCUSDT", "BTC", "USDT"));
+    let position_mgr = Arc::new(PositionManager::new(market_type.clone(), market_mgr));
+
+    let mut trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
+    trade_data.set_position_manager(DEFAULT_ACCOUNT_ID, position_mgr.clone());
+    trade_data.init().await.unwrap();
+    // 等待首次定期同步完成，避免与后续推送交错
+    for _ in 0..100 {
+        if trade_data
+            .get_last_sync_ts(&market_type)
+            .await
+            .unwrap()
+            .is_some()
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let account = trade_data.get_account(&market_type).await.unwrap().unwrap();
+    assert_eq!(account.balances[0].free, Decimal::from(500));
+
+    let order = trade_data
+        .place_order(
+            &market_type,
+            PlaceOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                r#type: OrderType::Limit,
+                time_in_force: Some(TimeInForce::Gtc),
+                quantity: Some(Decimal::from_str("0.001").unwrap()),
+                price: Some(Decimal::from_str("100000").unwrap()),
+                client_order_id: "scripted_1".to_string(),
+                stop_price: None,
+                iceberg_qty: None,
+                quote_order_qty: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(order.order_id, "9001");
+    assert_eq!(provider.placed_orders().len(), 1);
+
+    // 推送成交
+    let mut filled = ack.clone();
+    filled.order_status = OrderStatus::Filled;
+    filled.executed_qty = filled.order_quantity;
+    filled.cummulative_quote_qty = filled.order_quantity * filled.order_price;
+    filled.update_time = now + 1000;
+    assert!(provider.push_order(filled) > 0);
+    assert!(
+        provider.push_user_trade(UserTrade {
+            trade_id: "7001".to_string(),
+            order_id: "9001".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            order_side: OrderSide::Buy,
+            trade_price: Decimal::from(100000),
+            trade_quantity: Decimal::from_str("0.001").unwrap(),
+            commission: Decimal::ZERO,
+            commission_asset: "BTC".to_string(),
+            is_maker: 1,
+            timestamp: now + 1000,
+        }) > 0
+    );
+    assert!(
+        provider.push_account_update(AccountUpdate {
+            balances: vec![
+                Balance {
+                    asset: "USDT".to_string(),
+                    free: Decimal::from(400),
+                    locked: Decimal::ZERO,
+                },
+                Balance {
+                    asset: "BTC".to_string(),
+                    free: Decimal::from_str("0.001").unwrap(),
+                    locked: Decimal::ZERO,
+                },
+            ],
+            timestamp: now + 1000,
+        }) > 0
+    );
+
+    let mut persisted = false;
+    for _ in 0..100 {
+        let order = trade_data
+            .get_order_by_client_id(&market_type, "BTCUSDT", "scripted_1")
+            .await
+            .unwrap();
+        let trades = trade_data
+            .get_user_trades_by_order(&market_type, "BTCUSDT", "9001")
+            .await
+            .unwrap();
+        let account = trade_data
+            .get_account_from_db(&market_type, DEFAULT_ACCOUNT_ID)
+            .unwrap()
+            .unwrap();
+        let btc = account.balances.iter().find(|b| b.asset == "BTC").cloned();
+        if order.is_some_and(|o| o.order_status == OrderStatus::Filled)
+            && trades.len() == 1
+            && btc.is_some_and(|b| b.free == Decimal::from_str("0.001").unwrap())
+        {
+            persisted = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(persisted);
+    assert!(trade_data
+        .get_open_orders_from_db(&market_type, DEFAULT_ACCOUNT_ID)
+        .unwrap()
+        .is_empty());
+    // 推送的成交计入仓位
+    let position = position_mgr.get_position("BTCUSDT").await.unwrap();
+    assert_eq!(position.quantity, Decimal::from_str("0.001").unwrap());
+    assert_eq!(position.avg_entry_price, Decimal::from(100000));
+}
+
+#[tokio::test]
+async fn test_amend_marks_replaced_order_canceled() {
+    let db_file = NamedTempFile::new().unwrap();
+    let platform_config = mock_platform_config(db_file.path().to_str().unwrap(), 60);
+    let market_type = MarketType::BinanceSpot;
+
+    let now = time::get_current_milli_timestamp();
+    let provider = Arc::new(MockTradeProvider::new(0));
+    provider.queue_account(Account {
+        balances: vec![Balance {
+            asset: "USDT".to_string(),
+            free: Decimal::from(500),
+            locked: Decimal::ZERO,
+        }],
+        timestamp: now,
+    });
+    let mut ack = mock_order("amend_1", OrderStatus::New, now);
+    ack.order_id = "9001".to_string();
+    provider.queue_order_ack(Ok(ack));
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (market_type.clone(), DEFAULT_ACCOUNT_ID.to_string()),
+        provider.clone(),
+    );
+    let trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
+    trade_data.init().await.unwrap();
+
+    trade_data
+        .place_order(
+            &market_type,
+            PlaceOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                r#type: OrderType::Limit,
+                time_in_force: Some(TimeInForce::Gtc),
+                quantity: Some(Decimal::from_str("0.001").unwrap()),
+                price: Some(Decimal::from(100000)),
+                client_order_id: "amend_1".to_string(),
+                stop_price: None,
+                iceberg_qty: None,
+                quote_order_qty: None,
+            },
+        )
+        .await
+        .unwrap();
+    let replaced = trade_data
+        .amend_order(
+            &market_type,
+            AmendOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                order_id: None,
+                client_order_id: "amend_1".to_string(),
+                new_client_order_id: "amend_2".to_string(),
+                price: Some(Decimal::from(99000)),
+                quantity: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(replaced.client_order_id, "amend_2");
+
+    // 原订单在本地标记为已撤销，不再计入在途订单
+    let old = trade_data
+        .get_order_by_client_id(&market_type, "BTCUSDT", "amend_1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(old.order_status, OrderStatus::Canceled);
+    let open_orders = trade_data.get_open_orders(&market_type).await.unwrap();
+    assert_eq!(open_orders.len(), 1);
+    assert_eq!(open_orders[0].client_order_id, "amend_2");
+}
+
+#[tokio::test]
+async fn test_trade_metrics() {
+    let db_file = NamedTempFile::new().unwrap();
+    let platform_config = mock_platform_config(db_file.path().to_str().unwrap(), 60);
+    let market_type = MarketType::BinanceSpot;
+
+    let provider = Arc::new(MockTradeProvider::new(0));
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (market_type.clone(), DEFAULT_ACCOUNT_ID.to_string()),
+        provider.clone(),
+    );
+    let mut trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
+    let metrics = Arc::new(InMemoryMetrics::new());
+    trade_data.set_metrics(metrics.clone());
+
+    // 首次tick立即触发一轮定期同步
+    let sync_completed = trade_data.sync_completed();
+    let notified = sync_completed.notified();
+    trade_data.init().await.unwrap();
+    notified.await;
+    assert_eq!(metrics.counter("trade.sync"), 1);
+    assert_eq!(metrics.counter("trade.sync_failed"), 0);
+
+    let limit_buy = |client_order_id: &str| PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: OrderSide::Buy,
+        r#type: OrderType::Limit,
+        time_in_force: Some(TimeInForce::Gtc),
+        quantity: Some(Decimal::from_str("0.001").unwrap()),
+        price: Some(Decimal::from(100000)),
+        client_order_id: client_order_id.to_string(),
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: None,
+    };
+    trade_data
+        .place_order(&market_type, limit_buy("metrics_1"))
+        .await
+        .unwrap();
+    assert_eq!(metrics.counter("trade.order_placed"), 1);
+
+    // 交易所拒绝的下单不计数
+    provider.queue_order_ack(Err(PlatformError::TradeProviderError {
+        message: "rejected".to_string(),
+    }));
+    assert!(trade_data
+        .place_order(&market_type, limit_buy("metrics_2"))
+        .await
+        .is_err());
+    assert_eq!(metrics.counter("trade.order_placed"), 1);
+
+    trade_data
+        .amend_order(
+            &market_type,
+            AmendOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                order_id: None,
+                client_order_id: "metrics_1".to_string(),
+                new_client_order_id: "metrics_3".to_string(),
+                price: Some(Decimal::from(99000)),
+                quantity: None,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(metrics.counter("trade.order_amended"), 1);
+
+    // account()取得的账户视图共用同一metrics
+    trade_data
+        .account(DEFAULT_ACCOUNT_ID)
+        .cancel_order(
+            &market_type,
+            CancelOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                order_id: None,
+                client_order_id: "metrics_3".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(metrics.counter("trade.order_canceled"), 1);
+}
+
 fn account_equal(a1: &Account, a2: &Account) -> bool {
     if a1.balances.len() != a2.balances.len() {
         return false;
@@ -351,3 +1560,162 @@ fn trades_equal(t1: Vec<UserTrade>, t2: Vec<UserTrade>) -> bool {
 
     return true;
 }
+
+#[test]
+fn test_account_balance_rollback_with_history() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    create_account_balance_table(db.clone()).unwrap();
+    create_balance_history_table(db.clone()).unwrap();
+    let market_type = MarketType::BinanceSpot;
+    let balances = vec![Balance {
+        asset: "USDT".to_string(),
+        free: Decimal::from(1000),
+        locked: Decimal::ZERO,
+    }];
+
+    // 注入快照写入失败：流水也不应落库
+    db.execute_update("DROP TABLE account_balance", &[])
+        .unwrap();
+    let result =
+        update_account_balance(db.clone(), &market_type, DEFAULT_ACCOUNT_ID, &balances, 10);
+    assert!(
+        matches!(result, Err(PlatformError::DbError { .. })),
+        "result: {:?}",
+        result
+    );
+    assert!(
+        get_balance_history(db.clone(), &market_type, DEFAULT_ACCOUNT_ID, "USDT", 0, 100)
+            .unwrap()
+            .is_empty()
+    );
+
+    create_account_balance_table(db.clone()).unwrap();
+    update_account_balance(db.clone(), &market_type, DEFAULT_ACCOUNT_ID, &balances, 10).unwrap();
+    assert_eq!(
+        get_balance_history(db.clone(), &market_type, DEFAULT_ACCOUNT_ID, "USDT", 0, 100)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_balance_history_accumulates() {
+    let db_file = NamedTempFile::new().unwrap();
+    let platform_config = mock_platform_config(db_file.path().to_str().unwrap(), 60);
+    let market_type = MarketType::BinanceSpot;
+
+    let provider = Arc::new(MockTradeProvider::new(0));
+    let mut trade_providers: HashMap<(MarketType, String), Arc<dyn TradeProvider>> = HashMap::new();
+    trade_providers.insert(
+        (market_type.clone(), DEFAULT_ACCOUNT_ID.to_string()),
+        provider.clone(),
+    );
+    let trade_data = TradeData::new(platform_config, Arc::new(trade_providers)).unwrap();
+    trade_data.init().await.unwrap();
+    for _ in 0..100 {
+        if trade_data
+            .get_last_sync_ts(&market_type)
+            .await
+            .unwrap()
+            .is_some()
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let usdt = |free: i64, locked: i64| Balance {
+        asset: "USDT".to_string(),
+        free: Decimal::from(free),
+        locked: Decimal::from(locked),
+    };
+    // 初始快照 timestamp=1，之后三次推送，最后一次早于上一次（乱序到达）
+    let updates = [
+        (vec![usdt(900, 100)], 10),
+        (
+            vec![
+                usdt(800, 0),
+                Balance {
+                    asset: "BTC".to_string(),
+                    free: Decimal::from_str("0.001").unwrap(),
+                    locked: Decimal::ZERO,
+                },
+            ],
+            30,
+        ),
+        (vec![usdt(850, 50)], 20),
+    ];
+    for (balances, timestamp) in updates {
+        assert!(
+            provider.push_account_update(AccountUpdate {
+                balances,
+                timestamp
+            }) > 0
+        );
+    }
+    let mut history = vec![];
+    for _ in 0..100 {
+        history = trade_data
+            .get_balance_history(&market_type, DEFAULT_ACCOUNT_ID, "USDT", 0, u64::MAX >> 1)
+            .unwrap();
+        if history.len() == 4 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let snapshot = |free: i64, locked: i64, timestamp: u64| BalanceSnapshot {
+        asset: "USDT".to_string(),
+        free: Decimal::from(free),
+        locked: Decimal::from(locked),
+        timestamp,
+    };
+    assert_eq!(
+        history,
+        vec![
+            snapshot(1000, 0, 1),
+            snapshot(900, 100, 10),
+            snapshot(850, 50, 20),
+            snapshot(800, 0, 30),
+        ]
+    );
+
+    // 范围查询包含两端
+    let ranged = trade_data
+        .get_balance_history(&market_type, DEFAULT_ACCOUNT_ID, "USDT", 10, 20)
+        .unwrap();
+    assert_eq!(ranged, vec![snapshot(900, 100, 10), snapshot(850, 50, 20)]);
+    let btc = trade_data
+        .get_balance_history(&market_type, DEFAULT_ACCOUNT_ID, "BTC", 0, 100)
+        .unwrap();
+    assert_eq!(btc.len(), 1);
+    assert_eq!(btc[0].timestamp, 30);
+
+    // 最新快照表仍只保留每个资产的最新值
+    let account = trade_data
+        .get_account_from_db(&market_type, DEFAULT_ACCOUNT_ID)
+        .unwrap()
+        .unwrap();
+    let usdt_balance = account.balances.iter().find(|b| b.asset == "USDT").unwrap();
+    assert_eq!(usdt_balance.free, Decimal::from(800));
+    assert_eq!(account.balances.len(), 2);
+
+    // 定期同步拿到相同时间戳的快照时不重复记录
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    update_account_balance(
+        db,
+        &market_type,
+        DEFAULT_ACCOUNT_ID,
+        &vec![usdt(800, 0)],
+        30,
+    )
+    .unwrap();
+    assert_eq!(
+        trade_data
+            .get_balance_history(&market_type, DEFAULT_ACCOUNT_ID, "USDT", 0, 100)
+            .unwrap()
+            .len(),
+        4
+    );
+}
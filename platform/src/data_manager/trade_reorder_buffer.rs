@@ -0,0 +1,115 @@
+use crate::models::Trade;
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Default)]
+struct SymbolBuffer {
+    next_seq_id: Option<u64>,      // 下一笔应放行的seq_id，首笔成交前为None
+    pending: BTreeMap<u64, Trade>, // 等待缺失seq_id的成交
+    waiting_since: Option<u64>,    // 开始等待当前缺口的时间（毫秒）
+}
+
+impl SymbolBuffer {
+    // 依次放行与next_seq_id连续的成交
+    fn drain_contiguous(&mut self, released: &mut Vec<Trade>) {
+        while let Some(next) = self.next_seq_id {
+            match self.pending.remove(&next) {
+                Some(trade) => {
+                    released.push(trade);
+                    self.next_seq_id = Some(next + 1);
+                }
+                None => break,
+            }
+        }
+    }
+
+    // 放弃等待缺口，按seq_id顺序放行全部缓冲
+    fn flush(&mut self, released: &mut Vec<Trade>) {
+        let pending = std::mem::take(&mut self.pending);
+        if let Some((last, _)) = pending.last_key_value() {
+            self.next_seq_id = Some(last + 1);
+        }
+        released.extend(pending.into_values());
+        self.waiting_since = None;
+    }
+}
+
+// 按symbol将stream推送的成交整理为seq_id顺序：出现缺口时暂存后续成交，缺口补齐后连续放行；
+// 缓冲超过window条或等待超过timeout_ms时放弃缺口，避免永久缺失的id阻塞后续成交
+// 早于已放行位置的成交（迟到或重复）直接放行，由cache按seq_id去重
+pub struct TradeReorderBuffer {
+    window: usize,
+    timeout_ms: u64,
+    symbols: HashMap<String, SymbolBuffer>,
+}
+
+impl TradeReorderBuffer {
+    // window为0时不缓冲
+    pub fn new(window: usize, timeout_ms: u64) -> Self {
+        Self {
+            window,
+            timeout_ms,
+            symbols: HashMap::new(),
+        }
+    }
+
+    // 返回可以写入cache的成交，按seq_id升序
+    pub fn push(&mut self, trade: Trade, now_ms: u64) -> Vec<Trade> {
+        if self.window == 0 {
+            return vec![trade];
+        }
+        let buffer = self.symbols.entry(trade.symbol.clone()).or_default();
+        let next = match buffer.next_seq_id {
+            Some(next) if trade.seq_id >= next => next,
+            Some(_) => return vec![trade],
+            None => {
+                buffer.next_seq_id = Some(trade.seq_id + 1);
+                return vec![trade];
+            }
+        };
+
+        let mut released = Vec::new();
+        buffer.pending.insert(trade.seq_id, trade);
+        buffer.drain_contiguous(&mut released);
+        if buffer.pending.is_empty() {
+            buffer.waiting_since = None;
+        } else if buffer.pending.len() > self.window {
+            log::warn!(
+                "trade reorder window exceeded, skip missing seq_id from {}",
+                next
+            );
+            buffer.flush(&mut released);
+        } else if buffer.waiting_since.is_none() || !released.is_empty() {
+            // 缺口前移后重新计时
+            buffer.waiting_since = Some(now_ms);
+        }
+        released
+    }
+
+    // 放行等待超时的缓冲
+    pub fn flush_expired(&mut self, now_ms: u64) -> Vec<Trade> {
+        let mut released = Vec::new();
+        for (symbol, buffer) in self.symbols.iter_mut() {
+            match buffer.waiting_since {
+                Some(since) if now_ms >= since + self.timeout_ms => {
+                    log::warn!(
+                        "trade reorder timeout for {}, skip missing seq_id {:?}",
+                        symbol,
+                        buffer.next_seq_id
+                    );
+                    buffer.flush(&mut released);
+                }
+                _ => {}
+            }
+        }
+        released
+    }
+
+    // 最近一次需要检查超时的时间，无缓冲时为None
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.symbols
+            .values()
+            .filter_map(|buffer| buffer.waiting_since)
+            .min()
+            .map(|since| since + self.timeout_ms)
+    }
+}
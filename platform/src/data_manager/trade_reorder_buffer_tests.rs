@@ -0,0 +1,101 @@
+use crate::{data_manager::trade_reorder_buffer::TradeReorderBuffer, models::Trade};
+use rust_decimal::Decimal;
+
+fn trade(symbol: &str, seq_id: u64) -> Trade {
+    Trade {
+        symbol: symbol.to_string(),
+        trade_id: seq_id.to_string(),
+        price: Decimal::from(100),
+        quantity: Decimal::ONE,
+        timestamp: seq_id,
+        is_buyer_maker: 0,
+        seq_id,
+    }
+}
+
+fn seq_ids(trades: Vec<Trade>) -> Vec<u64> {
+    trades.iter().map(|t| t.seq_id).collect()
+}
+
+#[test]
+fn test_in_order_trades_pass_through() {
+    let mut buffer = TradeReorderBuffer::new(10, 500);
+    for seq_id in 1..=5 {
+        assert_eq!(
+            seq_ids(buffer.push(trade("BTCUSDT", seq_id), 0)),
+            vec![seq_id]
+        );
+    }
+    assert_eq!(buffer.next_deadline(), None);
+}
+
+#[test]
+fn test_zero_window_disables_buffering() {
+    let mut buffer = TradeReorderBuffer::new(0, 500);
+    for seq_id in [1, 3, 2] {
+        assert_eq!(
+            seq_ids(buffer.push(trade("BTCUSDT", seq_id), 0)),
+            vec![seq_id]
+        );
+    }
+}
+
+#[test]
+fn test_gap_released_in_order_once_filled() {
+    let mut buffer = TradeReorderBuffer::new(10, 500);
+    assert_eq!(seq_ids(buffer.push(trade("BTCUSDT", 1), 0)), vec![1]);
+    assert!(buffer.push(trade("BTCUSDT", 4), 10).is_empty());
+    assert!(buffer.push(trade("BTCUSDT", 3), 20).is_empty());
+    assert_eq!(buffer.next_deadline(), Some(510));
+    // 其他symbol不受缺口影响
+    assert_eq!(seq_ids(buffer.push(trade("ETHUSDT", 7), 30)), vec![7]);
+
+    assert_eq!(seq_ids(buffer.push(trade("BTCUSDT", 2), 40)), vec![2, 3, 4]);
+    assert_eq!(buffer.next_deadline(), None);
+    assert_eq!(seq_ids(buffer.push(trade("BTCUSDT", 5), 50)), vec![5]);
+}
+
+#[test]
+fn test_partial_fill_restarts_timeout() {
+    let mut buffer = TradeReorderBuffer::new(10, 500);
+    buffer.push(trade("BTCUSDT", 1), 0);
+    buffer.push(trade("BTCUSDT", 3), 0);
+    buffer.push(trade("BTCUSDT", 5), 100);
+    assert_eq!(buffer.next_deadline(), Some(500));
+    // 2到达后缺口前移到4，重新计时
+    assert_eq!(seq_ids(buffer.push(trade("BTCUSDT", 2), 300)), vec![2, 3]);
+    assert_eq!(buffer.next_deadline(), Some(800));
+    assert!(buffer.flush_expired(500).is_empty());
+    assert_eq!(seq_ids(buffer.flush_expired(800)), vec![5]);
+}
+
+#[test]
+fn test_timeout_skips_missing_seq_id() {
+    let mut buffer = TradeReorderBuffer::new(10, 500);
+    buffer.push(trade("BTCUSDT", 1), 0);
+    buffer.push(trade("BTCUSDT", 3), 100);
+    buffer.push(trade("BTCUSDT", 4), 200);
+    assert!(buffer.flush_expired(599).is_empty());
+    assert_eq!(seq_ids(buffer.flush_expired(600)), vec![3, 4]);
+    assert_eq!(buffer.next_deadline(), None);
+
+    // 跳过缺口后继续按新位置放行，迟到的2直接放行
+    assert_eq!(seq_ids(buffer.push(trade("BTCUSDT", 5), 700)), vec![5]);
+    assert_eq!(seq_ids(buffer.push(trade("BTCUSDT", 2), 800)), vec![2]);
+    assert_eq!(seq_ids(buffer.push(trade("BTCUSDT", 6), 900)), vec![6]);
+}
+
+#[test]
+fn test_window_overflow_skips_missing_seq_id() {
+    let mut buffer = TradeReorderBuffer::new(3, 500);
+    buffer.push(trade("BTCUSDT", 1), 0);
+    for seq_id in 3..=5 {
+        assert!(buffer.push(trade("BTCUSDT", seq_id), 0).is_empty());
+    }
+    assert_eq!(
+        seq_ids(buffer.push(trade("BTCUSDT", 6), 0)),
+        vec![3, 4, 5, 6]
+    );
+    assert_eq!(buffer.next_deadline(), None);
+    assert_eq!(seq_ids(buffer.push(trade("BTCUSDT", 7), 0)), vec![7]);
+}
@@ -1,8 +1,9 @@
 use crate::{
-    errors::Result,
+    errors::{PlatformError, Result},
     models::{
-        Account, CancelOrderRequest, DepthData, KlineData, KlineInterval, MarketType, Order,
-        PlaceOrderRequest, SymbolInfo, Ticker24hr, Trade, UserTrade,
+        Account, AmendOrderRequest, CancelOrderRequest, DepthData, GapFilledKline, GapPolicy,
+        KlineData, KlineInterval, MarketType, Order, PlaceOrderRequest, SymbolInfo, Ticker24hr,
+        Trade, UserTrade,
     },
 };
 use async_trait::async_trait;
@@ -11,6 +12,8 @@ use async_trait::async_trait;
 pub trait MarketDataManager: Send + Sync {
     async fn init(&self) -> Result<()>;
 
+    // 返回最近的limit条（按open_time升序），limit为None或超过cache容量时按容量截断
+    // 缺失的bar不占位，结果只包含实际存在的kline
     async fn get_klines(
         &self,
         market_type: &MarketType,
@@ -19,6 +22,69 @@ pub trait MarketDataManager: Send + Sync {
         limit: Option<usize>,
     ) -> Result<Vec<KlineData>>;
 
+    // 在get_klines结果的基础上按gap_policy处理相邻bar之间缺失的位置，limit按实际存在的kline计数
+    // bar边界按KlineInterval::next_open_time计算，月线按自然月
+    async fn get_klines_with_gap_policy(
+        &self,
+        market_type: &MarketType,
+        symbol: &String,
+        interval: &KlineInterval,
+        limit: Option<usize>,
+        gap_policy: GapPolicy,
+    ) -> Result<Vec<Option<GapFilledKline>>> {
+        let klines = self
+            .get_klines(market_type, symbol, interval, limit)
+            .await?;
+        let detect_gap = gap_policy != GapPolicy::Skip;
+
+        let mut result = Vec::with_capacity(klines.len());
+        let mut prev: Option<KlineData> = None;
+        for kline in klines {
+            if let Some(prev) = prev.as_ref().filter(|_| detect_gap) {
+                let mut open_time = interval.next_open_time(prev.open_time);
+                while open_time < kline.open_time {
+                    let next_open_time = interval.next_open_time(open_time);
+                    result.push(match gap_policy {
+                        GapPolicy::Dense => Some(GapFilledKline {
+                            kline: KlineData {
+                                open_time,
+                                close_time: next_open_time - 1,
+                                ..prev.clone()
+                            },
+                            synthetic: true,
+                        }),
+                        _ => None,
+                    });
+                    open_time = next_open_time;
+                }
+            }
+            result.push(Some(GapFilledKline {
+                kline: kline.clone(),
+                synthetic: false,
+            }));
+            prev = Some(kline);
+        }
+        Ok(result)
+    }
+
+    // 按[start_time, end_time]获取kline（以open_time为准），不支持的实现返回错误
+    async fn get_klines_range(
+        &self,
+        market_type: &MarketType,
+        symbol: &String,
+        interval: &KlineInterval,
+        start_time: u64,
+        end_time: u64,
+    ) -> Result<Vec<KlineData>> {
+        Err(PlatformError::DataManagerError {
+            message: format!(
+                "get_klines_range not supported for {:?}, {}, {:?}, [{}, {}]",
+                market_type, symbol, interval, start_time, end_time
+            ),
+        })
+    }
+
+    // 返回最近的limit条（按seq_id升序），limit语义同get_klines
     async fn get_trades(
         &self,
         market_type: &MarketType,
@@ -50,6 +116,13 @@ pub trait MarketDataManager: Send + Sync {
         base_asset: &String,
         quote_asset: &String,
     ) -> Result<Option<String>>;
+
+    // 状态属于配置的active_symbol_statuses的订阅symbol（按名称排序），不支持的实现返回错误
+    async fn get_active_symbols(&self, market_type: &MarketType) -> Result<Vec<String>> {
+        Err(PlatformError::DataManagerError {
+            message: format!("get_active_symbols not supported for {:?}", market_type),
+        })
+    }
 }
 
 #[async_trait]
@@ -104,4 +177,7 @@ pub trait TradeDataManager: Send + Sync {
     async fn place_order(&self, market_type: &MarketType, req: PlaceOrderRequest) -> Result<Order>;
 
     async fn cancel_order(&self, market_type: &MarketType, req: CancelOrderRequest) -> Result<()>;
+
+    // 修改在途订单的价格/数量，终态订单不可修改
+    async fn amend_order(&self, market_type: &MarketType, req: AmendOrderRequest) -> Result<Order>;
 }
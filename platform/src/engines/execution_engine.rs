@@ -0,0 +1,743 @@
+use crate::{
+    backtest::strategies::strategy_engine::PositionTargetSignal,
+    data_manager::{
+        local_data_manager::{Clock, ClockHook},
+        position_manager::PositionManager,
+        MarketDataManager, TradeDataManager,
+    },
+    errors::{PlatformError, Result},
+    models::{
+        CancelOrderRequest, MarketType, Order, OrderSide, OrderStatus, OrderType,
+        PlaceOrderRequest, Position,
+    },
+};
+use async_trait::async_trait;
+use rand::{distr::Alphanumeric, Rng};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio_util::sync::CancellationToken;
+
+const DAY_MILLI_SECS: u64 = 86_400_000;
+
+/// TWAP执行参数：target_delta为正买入、为负卖出，在[start_ts, start_ts + duration_ms)内均分为slices个切片
+#[derive(Debug, Clone)]
+pub struct TwapConfig {
+    pub market_type: MarketType,
+    pub symbol: String,
+    pub target_delta: Decimal,
+    pub start_ts: u64,
+    pub duration_ms: u64,
+    pub slices: u32,
+}
+
+/// 目标仓位信号按TWAP执行的参数
+#[derive(Debug, Clone)]
+pub struct TargetExecutionConfig {
+    pub duration_ms: u64,
+    pub slices: u32,
+}
+
+/// TWAP下发的子订单
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChildOrder {
+    pub client_order_id: String,
+    pub slice: u32,
+    pub placed_ts: u64,
+    pub quantity: Decimal,
+    pub executed_qty: Decimal,
+    pub order_status: OrderStatus,
+}
+
+/// 按时间均匀切片下市价子订单；到达新切片时撤掉上一切片未成交部分，剩余数量均摊到剩余切片
+#[derive(Debug, Clone)]
+pub struct TwapExecution {
+    id: String,
+    config: TwapConfig,
+    side: OrderSide,
+    target_quantity: Decimal,
+    next_slice: u32,
+    child_orders: Vec<ChildOrder>,
+    stopped: bool,
+}
+
+impl TwapExecution {
+    pub fn new(id: String, config: TwapConfig) -> Result<Self> {
+        if config.slices == 0 || config.duration_ms == 0 || config.target_delta.is_zero() {
+            return Err(PlatformError::ExecutionError {
+                message: format!("invalid twap config: {:?}", config),
+            });
+        }
+        let side = if config.target_delta > Decimal::ZERO {
+            OrderSide::Buy
+        } else {
+            OrderSide::Sell
+        };
+        Ok(Self {
+            id,
+            target_quantity: config.target_delta.abs(),
+            config,
+            side,
+            next_slice: 0,
+            child_orders: Vec::new(),
+            stopped: false,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn config(&self) -> &TwapConfig {
+        &self.config
+    }
+
+    pub fn child_orders(&self) -> &[ChildOrder] {
+        &self.child_orders
+    }
+
+    // 第slice个切片的计划下单时间
+    pub fn slice_ts(&self, slice: u32) -> u64 {
+        self.config.start_ts + self.config.duration_ms * slice as u64 / self.config.slices as u64
+    }
+
+    pub fn filled_quantity(&self) -> Decimal {
+        self.child_orders.iter().map(|o| o.executed_qty).sum()
+    }
+
+    pub fn remaining_quantity(&self) -> Decimal {
+        (self.target_quantity - self.filled_quantity()).max(Decimal::ZERO)
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    // 所有切片已下发且子订单均已终结，或已停止
+    pub fn is_finished(&self) -> bool {
+        self.stopped
+            || (self.next_slice >= self.config.slices
+                && self
+                    .child_orders
+                    .iter()
+                    .all(|o| o.order_status.is_terminal()))
+    }
+
+    async fn sync_child_orders(&mut self, trade_mgr: &dyn TradeDataManager) -> Result<()> {
+        for child in self
+            .child_orders
+            .iter_mut()
+            .filter(|o| !o.order_status.is_terminal())
+        {
+            if let Some(order) = trade_mgr
+                .get_order_by_client_id(
+                    &self.config.market_type,
+                    &self.config.symbol,
+                    &child.client_order_id,
+                )
+                .await?
+            {
+                child.executed_qty = order.executed_qty;
+                child.order_status = order.order_status;
+            }
+        }
+        Ok(())
+    }
+
+    async fn cancel_open_child_orders(&mut self, trade_mgr: &dyn TradeDataManager) -> Result<()> {
+        for child in self
+            .child_orders
+            .iter()
+            .filter(|o| !o.order_status.is_terminal())
+        {
+            let req = CancelOrderRequest {
+                symbol: self.config.symbol.clone(),
+                order_id: None,
+                client_order_id: child.client_order_id.clone(),
+            };
+            match trade_mgr.cancel_order(&self.config.market_type, req).await {
+                // 撤单前已终结，以同步后的状态为准
+                Ok(()) | Err(PlatformError::OrderNotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        self.sync_child_orders(trade_mgr).await
+    }
+
+    /// 同步子订单成交，到达切片时间时下发子订单；时钟跨过多个切片时合并为一次下单
+    pub async fn on_tick(
+        &mut self,
+        trade_mgr: &dyn TradeDataManager,
+        market_mgr: &dyn MarketDataManager,
+        cur_ts: u64,
+    ) -> Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+        self.sync_child_orders(trade_mgr).await?;
+        if self.next_slice >= self.config.slices || cur_ts < self.slice_ts(self.next_slice) {
+            return Ok(());
+        }
+
+        let mut slice = self.next_slice;
+        while slice + 1 < self.config.slices && self.slice_ts(slice + 1) <= cur_ts {
+            slice += 1;
+        }
+        self.next_slice = slice + 1;
+
+        self.cancel_open_child_orders(trade_mgr).await?;
+        let remaining = self.remaining_quantity();
+        let slices_left = Decimal::from(self.config.slices - slice);
+        let symbol_info = market_mgr
+            .get_symbol_info(&self.config.market_type, &self.config.symbol)
+            .await?
+            .ok_or_else(|| PlatformError::SymbolNotFound {
+                market_type: self.config.market_type.clone(),
+                symbol: self.config.symbol.clone(),
+            })?;
+        let quantity = if self.next_slice >= self.config.slices {
+            symbol_info.round_quantity(remaining)
+        } else {
+            symbol_info.round_quantity(remaining / slices_left)
+        };
+        if quantity <= Decimal::ZERO {
+            return Ok(());
+        }
+
+        let client_order_id = format!("{}-{}", self.id, slice);
+        let req = PlaceOrderRequest {
+            symbol: self.config.symbol.clone(),
+            side: self.side.clone(),
+            r#type: OrderType::Market,
+            time_in_force: None,
+            quantity: Some(quantity),
+            price: None,
+            client_order_id: client_order_id.clone(),
+            stop_price: None,
+            iceberg_qty: None,
+            quote_order_qty: None,
+        };
+        let order = trade_mgr.place_order(&self.config.market_type, req).await?;
+        self.child_orders.push(ChildOrder {
+            client_order_id,
+            slice,
+            placed_ts: cur_ts,
+            quantity,
+            executed_qty: order.executed_qty,
+            order_status: order.order_status,
+        });
+        Ok(())
+    }
+
+    /// 停止执行并撤销未终结的子订单
+    pub async fn stop(&mut self, trade_mgr: &dyn TradeDataManager) -> Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+        self.cancel_open_child_orders(trade_mgr).await?;
+        self.stopped = true;
+        Ok(())
+    }
+}
+
+/// 对账时发现非本引擎下发的在途订单的处理方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnknownOrderPolicy {
+    Adopt,  // 接管为引擎已知订单，保留在途
+    Cancel, // 撤销
+}
+
+/// 对账参数：quote_asset之外的余额按base/quote_asset对应的symbol重建仓位
+#[derive(Debug, Clone)]
+pub struct ReconcileConfig {
+    pub quote_asset: String,
+    pub unknown_order_policy: UnknownOrderPolicy,
+}
+
+/// 对账结果
+#[derive(Debug, Clone, Default)]
+pub struct ReconcileReport {
+    pub positions: Vec<Position>,
+    pub adopted_orders: Vec<Order>,
+    pub canceled_orders: Vec<Order>,
+}
+
+/// 熔断参数：当日亏损（已实现+未实现盈亏相对当日起点的回落）或仓位名义价值超限时触发
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    pub max_daily_loss: Decimal,
+    pub max_position_notional: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CircuitBreakerReason {
+    DailyLoss { loss: Decimal, limit: Decimal },
+    PositionNotional { notional: Decimal, limit: Decimal },
+}
+
+/// 熔断触发时推送的告警
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitBreakerAlert {
+    pub timestamp: u64,
+    pub market_type: MarketType,
+    pub reason: CircuitBreakerReason,
+}
+
+struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    position_mgr: Arc<PositionManager>,
+    day: Option<u64>,
+    day_start_pnl: Decimal,
+    tripped: Option<CircuitBreakerAlert>,
+}
+
+impl CircuitBreaker {
+    async fn total_pnl(&self, mark_prices: &HashMap<String, Decimal>) -> Result<Decimal> {
+        Ok(self.position_mgr.realized_pnl().await
+            + self.position_mgr.unrealized_pnl(Some(mark_prices)).await?)
+    }
+
+    // 跨日时以当前盈亏作为新一日的起点，超限时返回触发原因
+    async fn check(&mut self, cur_ts: u64) -> Result<Option<CircuitBreakerReason>> {
+        let mark_prices = self.position_mgr.mark_prices().await?;
+        let pnl = self.total_pnl(&mark_prices).await?;
+        let day = cur_ts / DAY_MILLI_SECS;
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.day_start_pnl = pnl;
+        }
+
+        let loss = self.day_start_pnl - pnl;
+        if loss > self.config.max_daily_loss {
+            return Ok(Some(CircuitBreakerReason::DailyLoss {
+                loss,
+                limit: self.config.max_daily_loss,
+            }));
+        }
+        let notional = self
+            .position_mgr
+            .position_notional(Some(&mark_prices))
+            .await?;
+        if notional > self.config.max_position_notional {
+            return Ok(Some(CircuitBreakerReason::PositionNotional {
+                notional,
+                limit: self.config.max_position_notional,
+            }));
+        }
+        Ok(None)
+    }
+}
+
+/// 执行引擎：将目标仓位变化拆分为子订单执行，由clock推进驱动
+/// 回测时需在撮合之后注册为clock hook，保证每次推进先撮合再下发新的子订单
+pub struct ExecutionEngine {
+    trade_mgr: Arc<dyn TradeDataManager>,
+    market_mgr: Arc<dyn MarketDataManager>,
+    clock: Arc<Clock>,
+    twaps: Mutex<BTreeMap<String, TwapExecution>>,
+    next_id: AtomicU64,
+    run_id: String, // 区分不同进程下发的子订单，避免重启后client_order_id重复
+    position_mgrs: HashMap<MarketType, Arc<PositionManager>>,
+    adopted_orders: Mutex<HashMap<(MarketType, String), Order>>, // (market_type, client_order_id) -> order
+    circuit_breaker: Mutex<Option<CircuitBreaker>>,
+    alert_sender: broadcast::Sender<CircuitBreakerAlert>,
+}
+
+impl ExecutionEngine {
+    pub fn new(
+        trade_mgr: Arc<dyn TradeDataManager>,
+        market_mgr: Arc<dyn MarketDataManager>,
+        clock: Arc<Clock>,
+    ) -> Self {
+        Self {
+            trade_mgr,
+            market_mgr,
+            clock,
+            twaps: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(1),
+            run_id: rand::rng()
+                .sample_iter(&Alphanumeric)
+                .take(6)
+                .map(char::from)
+                .collect(),
+            position_mgrs: HashMap::new(),
+            adopted_orders: Mutex::new(HashMap::new()),
+            circuit_breaker: Mutex::new(None),
+            alert_sender: broadcast::channel(16).0,
+        }
+    }
+
+    // 对账重建的仓位写入position_mgr所在market_type的仓位管理器
+    pub fn set_position_manager(&mut self, position_mgr: Arc<PositionManager>) {
+        self.position_mgrs
+            .insert(position_mgr.market_type().clone(), position_mgr);
+    }
+
+    // 按position_mgr所在market_type的仓位盈亏熔断
+    pub fn set_circuit_breaker(
+        &mut self,
+        position_mgr: Arc<PositionManager>,
+        config: CircuitBreakerConfig,
+    ) {
+        self.circuit_breaker = Mutex::new(Some(CircuitBreaker {
+            config,
+            position_mgr,
+            day: None,
+            day_start_pnl: Decimal::ZERO,
+            tripped: None,
+        }));
+    }
+
+    pub fn subscribe_alert(&self) -> broadcast::Receiver<CircuitBreakerAlert> {
+        self.alert_sender.subscribe()
+    }
+
+    pub async fn is_halted(&self) -> bool {
+        self.circuit_breaker
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|breaker| breaker.tripped.is_some())
+    }
+
+    /// 检查熔断条件，触发后撤销所有在途订单、停止所有执行并推送告警，返回是否处于熔断状态
+    /// 熔断后需调用reset_circuit_breaker才能恢复下单
+    pub async fn check_circuit_breaker(&self, cur_ts: u64) -> Result<bool> {
+        let alert = {
+            let mut breaker = self.circuit_breaker.lock().await;
+            let breaker = match breaker.as_mut() {
+                None => return Ok(false),
+                Some(breaker) => breaker,
+            };
+            if breaker.tripped.is_some() {
+                return Ok(true);
+            }
+            let reason = match breaker.check(cur_ts).await? {
+                None => return Ok(false),
+                Some(reason) => reason,
+            };
+            let alert = CircuitBreakerAlert {
+                timestamp: cur_ts,
+                market_type: breaker.position_mgr.market_type().clone(),
+                reason,
+            };
+            breaker.tripped = Some(alert.clone());
+            alert
+        };
+
+        log::error!("circuit breaker tripped: {:?}", alert);
+        self.stop().await?;
+        for order in self.trade_mgr.get_open_orders(&alert.market_type).await? {
+            let req = CancelOrderRequest {
+                symbol: order.symbol.clone(),
+                order_id: Some(order.order_id.clone()),
+                client_order_id: order.client_order_id.clone(),
+            };
+            match self.trade_mgr.cancel_order(&alert.market_type, req).await {
+                Ok(()) | Err(PlatformError::OrderNotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let _ = self.alert_sender.send(alert);
+        Ok(true)
+    }
+
+    /// 解除熔断，以当前盈亏作为当日新的起点
+    pub async fn reset_circuit_breaker(&self) -> Result<()> {
+        let mut breaker = self.circuit_breaker.lock().await;
+        if let Some(breaker) = breaker.as_mut() {
+            let mark_prices = breaker.position_mgr.mark_prices().await?;
+            breaker.day_start_pnl = breaker.total_pnl(&mark_prices).await?;
+            breaker.tripped = None;
+        }
+        Ok(())
+    }
+
+    // 熔断期间拒绝下单
+    async fn ensure_not_halted(&self, cur_ts: u64) -> Result<()> {
+        if self.check_circuit_breaker(cur_ts).await? {
+            return Err(PlatformError::ExecutionError {
+                message: "circuit breaker tripped, new orders are refused".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// 经熔断检查后下单，熔断期间拒绝
+    pub async fn place_order(
+        &self,
+        market_type: &MarketType,
+        req: PlaceOrderRequest,
+    ) -> Result<Order> {
+        self.ensure_not_halted(self.clock.cur_ts()).await?;
+        self.trade_mgr.place_order(market_type, req).await
+    }
+
+    /// 提交TWAP执行，返回执行id；start_ts不晚于当前时间时立即下发首个切片，熔断期间拒绝提交
+    pub async fn submit_twap(&self, config: TwapConfig) -> Result<String> {
+        self.ensure_not_halted(self.clock.cur_ts()).await?;
+        let id = format!(
+            "twap-{}-{}-{}",
+            config.symbol,
+            self.run_id,
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+        let mut twap = TwapExecution::new(id.clone(), config)?;
+        twap.on_tick(
+            self.trade_mgr.as_ref(),
+            self.market_mgr.as_ref(),
+            self.clock.cur_ts(),
+        )
+        .await?;
+        self.twaps.lock().await.insert(id.clone(), twap);
+        Ok(id)
+    }
+
+    /// 将目标仓位转为TWAP执行：先停止该symbol未完成的执行，再按目标与当前仓位之差提交
+    /// 差额为0时不下单，返回None
+    pub async fn execute_target(
+        &self,
+        signal: &PositionTargetSignal,
+        config: &TargetExecutionConfig,
+    ) -> Result<Option<String>> {
+        let target = Decimal::from_f64(signal.target_position).ok_or_else(|| {
+            PlatformError::ExecutionError {
+                message: format!("invalid target position: {:?}", signal),
+            }
+        })?;
+        {
+            let mut twaps = self.twaps.lock().await;
+            for twap in twaps.values_mut().filter(|t| {
+                !t.is_finished()
+                    && t.config.market_type == signal.market_type
+                    && t.config.symbol == signal.symbol
+            }) {
+                twap.stop(self.trade_mgr.as_ref()).await?;
+            }
+        }
+
+        let current = self
+            .get_position(&signal.market_type, &signal.symbol)
+            .await
+            .map_or(Decimal::ZERO, |position| position.quantity);
+        let target_delta = target - current;
+        if target_delta.is_zero() {
+            return Ok(None);
+        }
+        let id = self
+            .submit_twap(TwapConfig {
+                market_type: signal.market_type.clone(),
+                symbol: signal.symbol.clone(),
+                target_delta,
+                start_ts: self.clock.cur_ts(),
+                duration_ms: config.duration_ms,
+                slices: config.slices,
+            })
+            .await?;
+        Ok(Some(id))
+    }
+
+    /// 消费策略输出的目标仓位信号并依次执行，单条信号执行失败只记录日志
+    pub async fn run_signals(
+        &self,
+        mut signals: mpsc::UnboundedReceiver<PositionTargetSignal>,
+        config: TargetExecutionConfig,
+        shutdown_token: CancellationToken,
+    ) {
+        loop {
+            let signal = tokio::select! {
+                _ = shutdown_token.cancelled() => break,
+                signal = signals.recv() => match signal {
+                    None => break,
+                    Some(signal) => signal,
+                },
+            };
+            if let Err(e) = self.execute_target(&signal, &config).await {
+                log::error!("execute target signal {:?} failed: {}", signal, e);
+            }
+        }
+    }
+
+    pub async fn get_twap(&self, id: &str) -> Option<TwapExecution> {
+        self.twaps.lock().await.get(id).cloned()
+    }
+
+    pub async fn stop_twap(&self, id: &str) -> Result<()> {
+        let mut twaps = self.twaps.lock().await;
+        let twap = twaps
+            .get_mut(id)
+            .ok_or_else(|| PlatformError::ExecutionError {
+                message: format!("twap {} not found", id),
+            })?;
+        twap.stop(self.trade_mgr.as_ref()).await
+    }
+
+    /// 停止所有执行并撤销未终结的子订单
+    pub async fn stop(&self) -> Result<()> {
+        let mut twaps = self.twaps.lock().await;
+        for twap in twaps.values_mut() {
+            twap.stop(self.trade_mgr.as_ref()).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_position(&self, market_type: &MarketType, symbol: &str) -> Option<Position> {
+        match self.position_mgrs.get(market_type) {
+            None => None,
+            Some(position_mgr) => position_mgr.get_position(symbol).await,
+        }
+    }
+
+    pub async fn get_adopted_orders(&self, market_type: &MarketType) -> Vec<Order> {
+        self.adopted_orders
+            .lock()
+            .await
+            .iter()
+            .filter(|((mt, _), _)| mt == market_type)
+            .map(|(_, order)| order.clone())
+            .collect()
+    }
+
+    /// 重启后与交易所对账：按账户余额重建仓位管理器中的仓位（持仓成本未知记为0），
+    /// 非TWAP子订单且未接管过的在途订单按策略接管或撤销，避免重复或遗留订单
+    pub async fn reconcile(
+        &self,
+        market_type: &MarketType,
+        config: &ReconcileConfig,
+    ) -> Result<ReconcileReport> {
+        let position_mgr =
+            self.position_mgrs
+                .get(market_type)
+                .ok_or_else(|| PlatformError::ExecutionError {
+                    message: format!("position manager not set for {:?}", market_type),
+                })?;
+        let account = self
+            .trade_mgr
+            .get_account(market_type)
+            .await?
+            .ok_or_else(|| PlatformError::ExecutionError {
+                message: format!("account not found for {:?}", market_type),
+            })?;
+        let open_orders = self.trade_mgr.get_open_orders(market_type).await?;
+        let mut report = ReconcileReport::default();
+
+        for balance in account.balances.iter() {
+            let quantity = balance.free + balance.locked;
+            if balance.asset == config.quote_asset || quantity.is_zero() {
+                continue;
+            }
+            let symbol = match self
+                .market_mgr
+                .get_symbol(market_type, &balance.asset, &config.quote_asset)
+                .await
+            {
+                Ok(Some(symbol)) => symbol,
+                Ok(None) | Err(PlatformError::SymbolNotFound { .. }) => {
+                    log::warn!(
+                        "reconcile skip balance {} {}: no {}/{} symbol in {:?}",
+                        balance.asset,
+                        quantity,
+                        balance.asset,
+                        config.quote_asset,
+                        market_type
+                    );
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            let mut position = Position::new(&symbol, &balance.asset, &config.quote_asset);
+            position.quantity = quantity;
+            report.positions.push(position);
+        }
+        report.positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        position_mgr.reset_positions(report.positions.clone()).await;
+
+        let known_client_ids: HashSet<String> = self
+            .twaps
+            .lock()
+            .await
+            .values()
+            .filter(|t| &t.config.market_type == market_type)
+            .flat_map(|t| t.child_orders.iter().map(|o| o.client_order_id.clone()))
+            .collect();
+        let mut adopted_orders = self.adopted_orders.lock().await;
+        for order in open_orders {
+            let key = (market_type.clone(), order.client_order_id.clone());
+            if known_client_ids.contains(&order.client_order_id)
+                || adopted_orders.contains_key(&key)
+            {
+                continue;
+            }
+            match config.unknown_order_policy {
+                UnknownOrderPolicy::Adopt => {
+                    log::info!(
+                        "reconcile adopt unknown open order {} {}",
+                        order.symbol,
+                        order.client_order_id
+                    );
+                    adopted_orders.insert(key, order.clone());
+                    report.adopted_orders.push(order);
+                }
+                UnknownOrderPolicy::Cancel => {
+                    log::info!(
+                        "reconcile cancel unknown open order {} {}",
+                        order.symbol,
+                        order.client_order_id
+                    );
+                    let req = CancelOrderRequest {
+                        symbol: order.symbol.clone(),
+                        order_id: Some(order.order_id.clone()),
+                        client_order_id: order.client_order_id.clone(),
+                    };
+                    match self.trade_mgr.cancel_order(market_type, req).await {
+                        Ok(()) => report.canceled_orders.push(order),
+                        // 对账期间已终结
+                        Err(PlatformError::OrderNotFound { .. }) => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    pub async fn on_tick(&self, cur_ts: u64) -> Result<()> {
+        // 没有执行时也需检查，熔断会撤销手动下的在途订单
+        if self.check_circuit_breaker(cur_ts).await? {
+            return Ok(());
+        }
+        let ids = self
+            .twaps
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, t)| !t.is_finished())
+            .map(|(id, _)| id.clone())
+            .collect::<Vec<_>>();
+        // 前一个执行的成交可能已触发熔断，下发每个切片前重新检查
+        // 熔断会停止所有执行，需在twaps锁外检查
+        for (i, id) in ids.iter().enumerate() {
+            if i > 0 && self.check_circuit_breaker(cur_ts).await? {
+                return Ok(());
+            }
+            if let Some(twap) = self.twaps.lock().await.get_mut(id) {
+                twap.on_tick(self.trade_mgr.as_ref(), self.market_mgr.as_ref(), cur_ts)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ClockHook for ExecutionEngine {
+    async fn on_clock_advance(&self, cur_ts: u64) -> Result<()> {
+        self.on_tick(cur_ts).await
+    }
+}
@@ -0,0 +1,760 @@
+use crate::{
+    backtest::strategies::strategy_engine::PositionTargetSignal,
+    backtest::test_utils::{symbol_info, test_config},
+    data_manager::{
+        db::*,
+        local_data_manager::{Clock, LocalMarketDataManager, LocalTradeDataManager},
+        position_manager::PositionManager,
+        MarketDataManager, TradeDataManager,
+    },
+    engines::execution_engine::{
+        CircuitBreakerConfig, CircuitBreakerReason, ExecutionEngine, ReconcileConfig,
+        TargetExecutionConfig, TwapConfig, UnknownOrderPolicy,
+    },
+    models::{
+        Account, Balance, MarketType, OrderSide, OrderStatus, OrderType, PlaceOrderRequest,
+        TimeInForce, Trade, UserTrade,
+    },
+};
+use db::sqlite::SQLiteDB;
+use rust_decimal::Decimal;
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use tempfile::NamedTempFile;
+
+struct TestEnv {
+    _db_file: NamedTempFile,
+    clock: Arc<Clock>,
+    market_mgr: Arc<LocalMarketDataManager>,
+    trade_mgr: Arc<LocalTradeDataManager>,
+    position_mgr: Arc<PositionManager>,
+    engine: Arc<ExecutionEngine>,
+}
+
+fn test_trade(seq_id: u64, timestamp: u64, quantity: &str) -> Trade {
+    test_trade_at(seq_id, timestamp, 100, quantity)
+}
+
+fn test_trade_at(seq_id: u64, timestamp: u64, price: i64, quantity: &str) -> Trade {
+    Trade {
+        symbol: "BTCUSDT".to_string(),
+        trade_id: seq_id.to_string(),
+        price: Decimal::from(price),
+        quantity: Decimal::from_str(quantity).unwrap(),
+        timestamp,
+        is_buyer_maker: 0,
+        seq_id,
+    }
+}
+
+async fn setup(trades: Vec<Trade>, cur_ts: u64) -> TestEnv {
+    setup_with_btc(trades, cur_ts, 0).await
+}
+
+async fn setup_with_btc(trades: Vec<Trade>, cur_ts: u64, btc: i64) -> TestEnv {
+    setup_with(trades, cur_ts, btc, None).await
+}
+
+// 先注册撮合再注册执行引擎，每次推进先撮合已下发的子订单
+async fn setup_with(
+    trades: Vec<Trade>,
+    cur_ts: u64,
+    btc: i64,
+    circuit_breaker: Option<CircuitBreakerConfig>,
+) -> TestEnv {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    let market_type = MarketType::BinanceSpot;
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    update_symbol_info(
+        db.clone(),
+        &market_type,
+        &[symbol_info("BTCUSDT", "BTC", "USDT")],
+    )
+    .unwrap();
+    update_trade_data(db.clone(), &market_type, &trades).unwrap();
+
+    let config = test_config(
+        db_file.path().to_str().unwrap(),
+        "[\"BTCUSDT\"]",
+        "[\"1m\"]",
+    );
+    let clock = Arc::new(Clock::new(cur_ts));
+    let market_mgr = Arc::new(
+        LocalMarketDataManager::new(config.clone(), clock.clone(), db.clone(), 10000).unwrap(),
+    );
+    market_mgr.init().await.unwrap();
+
+    let mut init_accounts = HashMap::new();
+    init_accounts.insert(
+        market_type.clone(),
+        Account {
+            balances: vec![
+                Balance {
+                    asset: "USDT".to_string(),
+                    free: Decimal::from(100000),
+                    locked: Decimal::ZERO,
+                },
+                Balance {
+                    asset: "BTC".to_string(),
+                    free: Decimal::from(btc),
+                    locked: Decimal::ZERO,
+                },
+            ],
+            timestamp: cur_ts,
+        },
+    );
+    let position_mgr = Arc::new(PositionManager::new(market_type, market_mgr.clone()));
+    let mut trade_mgr =
+        LocalTradeDataManager::new(clock.clone(), config, init_accounts, market_mgr.clone())
+            .unwrap();
+    trade_mgr.set_position_manager(position_mgr.clone());
+    let trade_mgr = Arc::new(trade_mgr);
+    let mut engine = ExecutionEngine::new(trade_mgr.clone(), market_mgr.clone(), clock.clone());
+    engine.set_position_manager(position_mgr.clone());
+    if let Some(config) = circuit_breaker {
+        engine.set_circuit_breaker(position_mgr.clone(), config);
+    }
+    let engine = Arc::new(engine);
+    clock.register_hook(trade_mgr.clone());
+    clock.register_hook(engine.clone());
+
+    TestEnv {
+        _db_file: db_file,
+        clock,
+        market_mgr,
+        trade_mgr,
+        position_mgr,
+        engine,
+    }
+}
+
+fn twap_config(target_delta: &str, start_ts: u64, duration_ms: u64, slices: u32) -> TwapConfig {
+    TwapConfig {
+        market_type: MarketType::BinanceSpot,
+        symbol: "BTCUSDT".to_string(),
+        target_delta: Decimal::from_str(target_delta).unwrap(),
+        start_ts,
+        duration_ms,
+        slices,
+    }
+}
+
+async fn advance(env: &TestEnv, start_ts: u64, end_ts: u64, step_ms: u64) {
+    let mut ts = start_ts;
+    while ts <= end_ts {
+        env.clock.advance_to(ts).await.unwrap();
+        ts += step_ms;
+    }
+}
+
+#[tokio::test]
+async fn test_twap_fills_target_on_schedule() {
+    // 每100ms一笔1BTC的成交，流动性充足
+    let trades = (0..100)
+        .map(|i| test_trade(i + 1, 900 + i * 100, "1"))
+        .collect();
+    let env = setup(trades, 900).await;
+
+    let id = env
+        .engine
+        .submit_twap(twap_config("2.5", 1000, 5000, 5))
+        .await
+        .unwrap();
+    // 未到开始时间不下单
+    assert!(env
+        .engine
+        .get_twap(&id)
+        .await
+        .unwrap()
+        .child_orders()
+        .is_empty());
+
+    advance(&env, 1000, 8000, 100).await;
+
+    let twap = env.engine.get_twap(&id).await.unwrap();
+    assert!(twap.is_finished());
+    assert_eq!(twap.filled_quantity(), Decimal::from_str("2.5").unwrap());
+    assert_eq!(
+        twap.child_orders()
+            .iter()
+            .map(|o| (o.slice, o.placed_ts))
+            .collect::<Vec<_>>(),
+        vec![(0, 1000), (1, 2000), (2, 3000), (3, 4000), (4, 5000)]
+    );
+    assert!(twap
+        .child_orders()
+        .iter()
+        .all(|o| o.quantity == Decimal::from_str("0.5").unwrap()
+            && o.order_status == OrderStatus::Filled));
+}
+
+#[tokio::test]
+async fn test_twap_redistributes_unfilled_quantity() {
+    // 前两个切片内每个切片只有0.2BTC可成交，之后流动性充足
+    let mut trades = vec![test_trade(1, 900, "1")];
+    trades.push(test_trade(2, 1500, "0.2"));
+    trades.push(test_trade(3, 2500, "0.2"));
+    for i in 0..50 {
+        trades.push(test_trade(4 + i, 3100 + i * 100, "1"));
+    }
+    let env = setup(trades, 1000).await;
+
+    let id = env
+        .engine
+        .submit_twap(twap_config("4", 1000, 4000, 4))
+        .await
+        .unwrap();
+    advance(&env, 1100, 8000, 100).await;
+
+    let twap = env.engine.get_twap(&id).await.unwrap();
+    let quantities = twap
+        .child_orders()
+        .iter()
+        .map(|o| {
+            (
+                o.placed_ts,
+                o.quantity,
+                o.executed_qty,
+                o.order_status.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    let d = |s: &str| Decimal::from_str(s).unwrap();
+    assert_eq!(
+        quantities,
+        vec![
+            (1000, d("1"), d("0.2"), OrderStatus::Canceled),
+            (2000, d("1.26666"), d("0.2"), OrderStatus::Canceled),
+            (3000, d("1.8"), d("1.8"), OrderStatus::Filled),
+            (4000, d("1.8"), d("1.8"), OrderStatus::Filled),
+        ]
+    );
+    assert_eq!(twap.filled_quantity(), d("4"));
+}
+
+#[tokio::test]
+async fn test_twap_ids_differ_across_restarts() {
+    let env = setup(vec![test_trade(1, 900, "1")], 1000).await;
+    let market_type = MarketType::BinanceSpot;
+
+    let id = env
+        .engine
+        .submit_twap(twap_config("1", 1000, 3000, 3))
+        .await
+        .unwrap();
+    // 重启后的引擎从头编号，子订单id仍不能与之前的重复
+    let restarted = ExecutionEngine::new(
+        env.trade_mgr.clone(),
+        env.market_mgr.clone(),
+        env.clock.clone(),
+    );
+    let restarted_id = restarted
+        .submit_twap(twap_config("1", 1000, 3000, 3))
+        .await
+        .unwrap();
+    assert_ne!(id, restarted_id);
+
+    let mut client_order_ids = env
+        .trade_mgr
+        .get_open_orders(&market_type)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|o| o.client_order_id)
+        .collect::<Vec<_>>();
+    client_order_ids.sort();
+    let mut expected = vec![
+        env.engine.get_twap(&id).await.unwrap().child_orders()[0]
+            .client_order_id
+            .clone(),
+        restarted
+            .get_twap(&restarted_id)
+            .await
+            .unwrap()
+            .child_orders()[0]
+            .client_order_id
+            .clone(),
+    ];
+    expected.sort();
+    assert_ne!(expected[0], expected[1]);
+    assert_eq!(client_order_ids, expected);
+}
+
+#[tokio::test]
+async fn test_twap_stop_cancels_open_child_orders() {
+    // 首个切片下单后没有成交
+    let env = setup(vec![test_trade(1, 900, "1")], 1000).await;
+    let market_type = MarketType::BinanceSpot;
+
+    let id = env
+        .engine
+        .submit_twap(twap_config("-1", 1000, 3000, 3))
+        .await;
+    // 无BTC余额，卖单冻结失败
+    assert!(id.is_err());
+
+    let id = env
+        .engine
+        .submit_twap(twap_config("3", 1000, 3000, 3))
+        .await
+        .unwrap();
+    advance(&env, 1100, 1500, 100).await;
+    assert_eq!(
+        env.trade_mgr
+            .get_open_orders(&market_type)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    env.engine.stop().await.unwrap();
+    assert!(env
+        .trade_mgr
+        .get_open_orders(&market_type)
+        .await
+        .unwrap()
+        .is_empty());
+    let twap = env.engine.get_twap(&id).await.unwrap();
+    assert!(twap.is_stopped());
+    assert_eq!(twap.child_orders()[0].order_status, OrderStatus::Canceled);
+
+    // 停止后不再下发后续切片
+    advance(&env, 2000, 4000, 1000).await;
+    let twap = env.engine.get_twap(&id).await.unwrap();
+    assert_eq!(twap.child_orders().len(), 1);
+    assert!(env
+        .trade_mgr
+        .get_open_orders(&market_type)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+fn limit_sell(client_order_id: &str, quantity: i64, price: i64) -> PlaceOrderRequest {
+    PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: OrderSide::Sell,
+        r#type: OrderType::Limit,
+        time_in_force: Some(TimeInForce::Gtc),
+        quantity: Some(Decimal::from(quantity)),
+        price: Some(Decimal::from(price)),
+        client_order_id: client_order_id.to_string(),
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: None,
+    }
+}
+
+fn reconcile_config(policy: UnknownOrderPolicy) -> ReconcileConfig {
+    ReconcileConfig {
+        quote_asset: "USDT".to_string(),
+        unknown_order_policy: policy,
+    }
+}
+
+// 引擎下发的TWAP子订单在途，另有一笔重启前遗留的卖单
+async fn setup_with_unknown_order() -> (TestEnv, String) {
+    let env = setup_with_btc(vec![test_trade(1, 900, "1")], 1000, 3).await;
+    let id = env
+        .engine
+        .submit_twap(twap_config("2", 1000, 2000, 2))
+        .await
+        .unwrap();
+    env.trade_mgr
+        .place_order(&MarketType::BinanceSpot, limit_sell("orphan_1", 1, 200))
+        .await
+        .unwrap();
+    (env, id)
+}
+
+#[tokio::test]
+async fn test_reconcile_cancels_unknown_open_order() {
+    let (env, id) = setup_with_unknown_order().await;
+    let market_type = MarketType::BinanceSpot;
+
+    let report = env
+        .engine
+        .reconcile(&market_type, &reconcile_config(UnknownOrderPolicy::Cancel))
+        .await
+        .unwrap();
+
+    // 仓位按余额（含卖单冻结）重建
+    assert_eq!(report.positions.len(), 1);
+    assert_eq!(report.positions[0].symbol, "BTCUSDT");
+    assert_eq!(report.positions[0].quantity, Decimal::from(3));
+    assert_eq!(
+        env.engine.get_position(&market_type, "BTCUSDT").await,
+        Some(report.positions[0].clone())
+    );
+    assert_eq!(
+        env.position_mgr.get_positions().await,
+        vec![report.positions[0].clone()]
+    );
+
+    assert!(report.adopted_orders.is_empty());
+    assert_eq!(
+        report
+            .canceled_orders
+            .iter()
+            .map(|o| o.client_order_id.clone())
+            .collect::<Vec<_>>(),
+        vec!["orphan_1".to_string()]
+    );
+    // 仅保留TWAP子订单
+    let twap = env.engine.get_twap(&id).await.unwrap();
+    let open_orders = env.trade_mgr.get_open_orders(&market_type).await.unwrap();
+    assert_eq!(open_orders.len(), 1);
+    assert_eq!(
+        open_orders[0].client_order_id,
+        twap.child_orders()[0].client_order_id
+    );
+}
+
+#[tokio::test]
+async fn test_reconcile_adopts_unknown_open_order() {
+    let (env, _) = setup_with_unknown_order().await;
+    let market_type = MarketType::BinanceSpot;
+
+    let report = env
+        .engine
+        .reconcile(&market_type, &reconcile_config(UnknownOrderPolicy::Adopt))
+        .await
+        .unwrap();
+    assert!(report.canceled_orders.is_empty());
+    assert_eq!(report.adopted_orders.len(), 1);
+    assert_eq!(report.adopted_orders[0].client_order_id, "orphan_1");
+    assert_eq!(
+        env.trade_mgr
+            .get_open_orders(&market_type)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+    assert_eq!(env.engine.get_adopted_orders(&market_type).await.len(), 1);
+
+    // 已接管的订单再次对账时视为已知，Cancel策略也不会撤销
+    let report = env
+        .engine
+        .reconcile(&market_type, &reconcile_config(UnknownOrderPolicy::Cancel))
+        .await
+        .unwrap();
+    assert!(report.adopted_orders.is_empty());
+    assert!(report.canceled_orders.is_empty());
+    assert_eq!(
+        env.trade_mgr
+            .get_open_orders(&market_type)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+}
+
+fn breaker_config(max_daily_loss: i64, max_position_notional: i64) -> CircuitBreakerConfig {
+    CircuitBreakerConfig {
+        max_daily_loss: Decimal::from(max_daily_loss),
+        max_position_notional: Decimal::from(max_position_notional),
+    }
+}
+
+fn fill(side: OrderSide, price: i64, quantity: i64) -> UserTrade {
+    UserTrade {
+        trade_id: "1".to_string(),
+        order_id: "1".to_string(),
+        symbol: "BTCUSDT".to_string(),
+        order_side: side,
+        trade_price: Decimal::from(price),
+        trade_quantity: Decimal::from(quantity),
+        commission: Decimal::ZERO,
+        commission_asset: "USDT".to_string(),
+        is_maker: 0,
+        timestamp: 0,
+    }
+}
+
+fn limit_buy(client_order_id: &str, price: i64) -> PlaceOrderRequest {
+    PlaceOrderRequest {
+        side: OrderSide::Buy,
+        ..limit_sell(client_order_id, 1, price)
+    }
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_halts_on_daily_loss() {
+    // 价格从100跌到60，2BTC多头亏损80
+    let trades = vec![
+        test_trade_at(1, 900, 100, "1"),
+        test_trade_at(2, 2000, 60, "0.1"),
+    ];
+    let env = setup_with(trades, 1000, 0, Some(breaker_config(50, 100000))).await;
+    let market_type = MarketType::BinanceSpot;
+    let mut alerts = env.engine.subscribe_alert();
+    env.position_mgr
+        .apply_fill(&fill(OrderSide::Buy, 100, 2))
+        .await
+        .unwrap();
+
+    env.trade_mgr
+        .place_order(&market_type, limit_buy("manual_1", 10))
+        .await
+        .unwrap();
+    let id = env
+        .engine
+        .submit_twap(twap_config("1", 1500, 2000, 2))
+        .await
+        .unwrap();
+    advance(&env, 1100, 1900, 100).await;
+    assert!(!env.engine.is_halted().await);
+    assert_eq!(
+        env.trade_mgr
+            .get_open_orders(&market_type)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // 2000时亏损超限，撤销所有在途订单并停止TWAP
+    // TWAP子订单按60成交0.1BTC计入仓位，另付手续费0.006
+    env.clock.advance_to(2000).await.unwrap();
+    assert!(env.engine.is_halted().await);
+    assert!(env
+        .trade_mgr
+        .get_open_orders(&market_type)
+        .await
+        .unwrap()
+        .is_empty());
+    let manual = env
+        .trade_mgr
+        .get_order_by_client_id(&market_type, "BTCUSDT", "manual_1")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(manual.order_status, OrderStatus::Canceled);
+    assert!(env.engine.get_twap(&id).await.unwrap().is_stopped());
+
+    let alert = alerts.try_recv().unwrap();
+    assert_eq!(alert.timestamp, 2000);
+    assert_eq!(alert.market_type, market_type);
+    assert_eq!(
+        alert.reason,
+        CircuitBreakerReason::DailyLoss {
+            loss: Decimal::from_str("80.006").unwrap(),
+            limit: Decimal::from(50),
+        }
+    );
+    // 只告警一次
+    advance(&env, 2100, 2500, 100).await;
+    assert!(alerts.try_recv().is_err());
+
+    // 熔断期间拒绝新的执行
+    assert!(env
+        .engine
+        .submit_twap(twap_config("1", 2500, 1000, 1))
+        .await
+        .is_err());
+
+    // 显式解除后以当前盈亏为起点恢复
+    env.engine.reset_circuit_breaker().await.unwrap();
+    assert!(!env.engine.is_halted().await);
+    env.engine
+        .submit_twap(twap_config("1", 2500, 1000, 1))
+        .await
+        .unwrap();
+    assert_eq!(
+        env.trade_mgr
+            .get_open_orders(&market_type)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_tracks_matched_fills() {
+    // 1100时以100买入2BTC，2000时价格跌到60
+    let trades = vec![
+        test_trade_at(1, 900, 100, "1"),
+        test_trade_at(2, 1100, 100, "2"),
+        test_trade_at(3, 2000, 60, "0.1"),
+    ];
+    let env = setup_with(trades, 1000, 0, Some(breaker_config(50, 100000))).await;
+    let market_type = MarketType::BinanceSpot;
+
+    let market_buy = PlaceOrderRequest {
+        r#type: OrderType::Market,
+        time_in_force: None,
+        quantity: Some(Decimal::from(2)),
+        price: None,
+        ..limit_buy("manual_1", 100)
+    };
+    env.engine
+        .place_order(&market_type, market_buy)
+        .await
+        .unwrap();
+    env.clock.advance_to(1100).await.unwrap();
+    // 撮合成交直接计入仓位
+    let position = env.position_mgr.get_position("BTCUSDT").await.unwrap();
+    assert_eq!(position.quantity, Decimal::from(2));
+    assert!(!env.engine.is_halted().await);
+
+    env.engine
+        .place_order(&market_type, limit_buy("manual_2", 10))
+        .await
+        .unwrap();
+    env.clock.advance_to(2000).await.unwrap();
+    assert!(env.engine.is_halted().await);
+    assert!(env
+        .trade_mgr
+        .get_open_orders(&market_type)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // 熔断期间经引擎的下单均被拒绝
+    assert!(env
+        .engine
+        .place_order(&market_type, limit_buy("manual_3", 10))
+        .await
+        .is_err());
+    assert!(env
+        .trade_mgr
+        .get_order_by_client_id(&market_type, "BTCUSDT", "manual_3")
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_halts_on_position_notional() {
+    let env = setup_with(
+        vec![test_trade_at(1, 900, 100, "1")],
+        1000,
+        0,
+        Some(breaker_config(1000, 150)),
+    )
+    .await;
+    let mut alerts = env.engine.subscribe_alert();
+    env.position_mgr
+        .apply_fill(&fill(OrderSide::Buy, 100, 1))
+        .await
+        .unwrap();
+    assert!(!env.engine.check_circuit_breaker(1000).await.unwrap());
+
+    // 仓位名义价值200超过150
+    env.position_mgr
+        .apply_fill(&fill(OrderSide::Buy, 100, 1))
+        .await
+        .unwrap();
+    assert!(env
+        .engine
+        .submit_twap(twap_config("1", 1000, 1000, 1))
+        .await
+        .is_err());
+    assert!(env
+        .trade_mgr
+        .get_open_orders(&MarketType::BinanceSpot)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        alerts.try_recv().unwrap().reason,
+        CircuitBreakerReason::PositionNotional {
+            notional: Decimal::from(200),
+            limit: Decimal::from(150),
+        }
+    );
+}
+
+fn target_signal(target_position: f64) -> PositionTargetSignal {
+    PositionTargetSignal {
+        timestamp: 0,
+        market_type: MarketType::BinanceSpot,
+        symbol: "BTCUSDT".to_string(),
+        target_position,
+    }
+}
+
+#[tokio::test]
+async fn test_execute_target_trades_position_delta() {
+    let trades = (0..50)
+        .map(|i| test_trade(i + 1, 900 + i * 100, "1"))
+        .collect();
+    let env = setup(trades, 1000).await;
+    let config = TargetExecutionConfig {
+        duration_ms: 1000,
+        slices: 2,
+    };
+
+    let id = env
+        .engine
+        .execute_target(&target_signal(2.0), &config)
+        .await
+        .unwrap()
+        .unwrap();
+    advance(&env, 1100, 2500, 100).await;
+    assert!(env.engine.get_twap(&id).await.unwrap().is_finished());
+    let position = env.position_mgr.get_position("BTCUSDT").await.unwrap();
+    assert_eq!(position.quantity, Decimal::from(2));
+
+    // 已达到目标仓位，不再下单
+    assert!(env
+        .engine
+        .execute_target(&target_signal(2.0), &config)
+        .await
+        .unwrap()
+        .is_none());
+
+    // 新目标到达时停止未完成的执行，按当前仓位重新计算差额
+    let id = env
+        .engine
+        .execute_target(&target_signal(5.0), &config)
+        .await
+        .unwrap()
+        .unwrap();
+    let reduce_id = env
+        .engine
+        .execute_target(&target_signal(0.5), &config)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(env.engine.get_twap(&id).await.unwrap().is_stopped());
+    let filled = env.engine.get_twap(&id).await.unwrap().filled_quantity();
+    let reduce = env.engine.get_twap(&reduce_id).await.unwrap();
+    assert_eq!(
+        reduce.config().target_delta,
+        Decimal::from_str("0.5").unwrap() - Decimal::from(2) - filled
+    );
+}
+
+#[tokio::test]
+async fn test_run_signals_executes_strategy_targets() {
+    let trades = (0..50)
+        .map(|i| test_trade(i + 1, 900 + i * 100, "1"))
+        .collect();
+    let env = setup(trades, 1000).await;
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    sender.send(target_signal(1.0)).unwrap();
+    sender.send(target_signal(f64::NAN)).unwrap();
+    drop(sender);
+
+    // 无效信号只记录日志，通道关闭后返回
+    env.engine
+        .run_signals(
+            receiver,
+            TargetExecutionConfig {
+                duration_ms: 1000,
+                slices: 1,
+            },
+            tokio_util::sync::CancellationToken::new(),
+        )
+        .await;
+    advance(&env, 1100, 1500, 100).await;
+    let position = env.position_mgr.get_position("BTCUSDT").await.unwrap();
+    assert_eq!(position.quantity, Decimal::ONE);
+}
@@ -1,2 +1,6 @@
 pub mod engine;
+pub mod execution_engine;
 pub mod single_side_engine;
+
+#[cfg(test)]
+mod execution_engine_tests;
@@ -1,4 +1,9 @@
+use crate::models::{MarketType, SymbolStatus};
+use db::errors::DBError;
+use exchange::binance::errors::BinanceError;
+use rust_decimal::Decimal;
 use thiserror::Error;
+use ws::WsError;
 
 #[derive(Debug, Error)]
 pub enum PlatformError {
@@ -11,9 +16,66 @@ pub enum PlatformError {
     #[error("Trade provider error: {message}")]
     TradeProviderError { message: String },
 
+    // 交易所返回的业务错误码，如 binance -2010 下单被拒
+    #[error("Exchange error: code: {code}, {message}")]
+    ExchangeError { code: i64, message: String },
+
+    // 交易所限频，status 429 为限频、418 为ip被封禁
+    #[error("Rate limited: status: {status}, code: {code}, {message}")]
+    RateLimited {
+        status: u16,
+        code: i64,
+        message: String,
+    },
+
+    // 其他crate的错误保留为 source，可沿 Error::source 链追溯
+    #[error("{context}")]
+    WsError {
+        context: String,
+        #[source]
+        source: WsError,
+    },
+
+    #[error("{context}")]
+    BinanceError {
+        context: String,
+        #[source]
+        source: BinanceError,
+    },
+
+    #[error("{context}")]
+    DbError {
+        context: String,
+        #[source]
+        source: DBError,
+    },
+
     #[error("Data manager error: {message}")]
     DataManagerError { message: String },
 
+    // 数据集、导出文件等本地文件读写失败；数据库错误使用DbError
+    #[error("Storage error: {message}")]
+    StorageError { message: String },
+
+    #[error("Cache miss: {cache} for {key}")]
+    CacheMiss { cache: String, key: String },
+
+    // 市场未初始化对应状态，如账户、挂单、配置等
+    #[error("Market {market_type:?} {resource} not found")]
+    MarketNotFound {
+        market_type: MarketType,
+        resource: String,
+    },
+
+    #[error("Symbol not found: {market_type:?} {symbol}")]
+    SymbolNotFound {
+        market_type: MarketType,
+        symbol: String,
+    },
+
+    #[error("No {data} found for symbol {symbol}")]
+    DataNotFound { symbol: String, data: String },
+
     #[error("Engine error: {message}")]
     EngineError { message: String },
 
@@ -23,6 +85,7 @@ pub enum PlatformError {
     #[error("Strategy error: {message}")]
     StrategyError { message: String },
 
+    // 兜底，仅用于无法归类的意外错误
     #[error("Platform error: {message}")]
     PlatformError { message: String },
 
@@ -31,6 +94,111 @@ pub enum PlatformError {
 
     #[error("Validation error: {message}")]
     ValidationError { message: String },
+
+    #[error("Insufficient balance for {asset}: free={free}, required={required}")]
+    InsufficientBalance {
+        asset: String,
+        free: Decimal,
+        required: Decimal,
+    },
+
+    #[error("Insufficient locked balance for {asset}: locked={locked}, required={required}")]
+    InsufficientLockedBalance {
+        asset: String,
+        locked: Decimal,
+        required: Decimal,
+    },
+
+    #[error("Asset not found: {asset}")]
+    AssetNotFound { asset: String },
+
+    #[error("Order not found: client_order_id: {client_order_id}")]
+    OrderNotFound { client_order_id: String },
+
+    #[error("Duplicate order: client_order_id: {client_order_id}")]
+    DuplicateOrder { client_order_id: String },
+
+    #[error("Matching error: {message}")]
+    MatchingError { message: String },
+
+    #[error("Symbol not trading: {market_type:?} {symbol}, status: {status:?}")]
+    SymbolNotTrading {
+        market_type: MarketType,
+        symbol: String,
+        status: SymbolStatus,
+    },
+}
+
+impl PlatformError {
+    // 稳定错误码，新增变体只追加，不修改已有取值
+    pub fn code(&self) -> u32 {
+        self.code_and_name().0
+    }
+
+    pub fn code_name(&self) -> &'static str {
+        self.code_and_name().1
+    }
+
+    fn code_and_name(&self) -> (u32, &'static str) {
+        match self {
+            PlatformError::ConfigError { .. } => (1001, "CONFIG_ERROR"),
+            PlatformError::MarketProviderError { .. } => (2001, "MARKET_PROVIDER_ERROR"),
+            PlatformError::TradeProviderError { .. } => (2002, "TRADE_PROVIDER_ERROR"),
+            PlatformError::ExchangeError { .. } => (2003, "EXCHANGE_ERROR"),
+            PlatformError::RateLimited { .. } => (2004, "RATE_LIMITED"),
+            PlatformError::WsError { .. } => (2005, "WS_ERROR"),
+            PlatformError::BinanceError { .. } => (2006, "BINANCE_ERROR"),
+            PlatformError::DataManagerError { .. } => (3001, "DATA_MANAGER_ERROR"),
+            PlatformError::StorageError { .. } => (3002, "STORAGE_ERROR"),
+            PlatformError::CacheMiss { .. } => (3003, "CACHE_MISS"),
+            PlatformError::MarketNotFound { .. } => (3004, "MARKET_NOT_FOUND"),
+            PlatformError::SymbolNotFound { .. } => (3005, "SYMBOL_NOT_FOUND"),
+            PlatformError::DataNotFound { .. } => (3006, "DATA_NOT_FOUND"),
+            PlatformError::DbError { .. } => (3007, "DB_ERROR"),
+            PlatformError::EngineError { .. } => (4001, "ENGINE_ERROR"),
+            PlatformError::FactorError { .. } => (4002, "FACTOR_ERROR"),
+            PlatformError::StrategyError { .. } => (4003, "STRATEGY_ERROR"),
+            PlatformError::ExecutionError { .. } => (5001, "EXECUTION_ERROR"),
+            PlatformError::ValidationError { .. } => (5002, "VALIDATION_ERROR"),
+            PlatformError::InsufficientBalance { .. } => (5003, "INSUFFICIENT_BALANCE"),
+            PlatformError::InsufficientLockedBalance { .. } => {
+                (5004, "INSUFFICIENT_LOCKED_BALANCE")
+            }
+            PlatformError::AssetNotFound { .. } => (5005, "ASSET_NOT_FOUND"),
+            PlatformError::OrderNotFound { .. } => (5006, "ORDER_NOT_FOUND"),
+            PlatformError::DuplicateOrder { .. } => (5007, "DUPLICATE_ORDER"),
+            PlatformError::MatchingError { .. } => (5008, "MATCHING_ERROR"),
+            PlatformError::SymbolNotTrading { .. } => (5009, "SYMBOL_NOT_TRADING"),
+            PlatformError::PlatformError { .. } => (9999, "PLATFORM_ERROR"),
+        }
+    }
+}
+
+impl From<WsError> for PlatformError {
+    fn from(source: WsError) -> Self {
+        PlatformError::WsError {
+            context: "ws error".to_string(),
+            source,
+        }
+    }
+}
+
+impl From<BinanceError> for PlatformError {
+    fn from(source: BinanceError) -> Self {
+        PlatformError::BinanceError {
+            context: "binance error".to_string(),
+            source,
+        }
+    }
+}
+
+impl From<DBError> for PlatformError {
+    fn from(source: DBError) -> Self {
+        PlatformError::DbError {
+            context: "db error".to_string(),
+            source,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, PlatformError>;
@@ -0,0 +1,118 @@
+use crate::{
+    errors::PlatformError,
+    models::{MarketType, SymbolStatus},
+};
+use db::errors::DBError;
+use rust_decimal::Decimal;
+use std::{collections::HashSet, error::Error};
+use ws::WsError;
+
+#[test]
+fn test_error_codes() {
+    let errors = vec![
+        PlatformError::ConfigError {
+            message: "".to_string(),
+        },
+        PlatformError::RateLimited {
+            status: 429,
+            code: -1003,
+            message: "".to_string(),
+        },
+        PlatformError::CacheMiss {
+            cache: "kline".to_string(),
+            key: "".to_string(),
+        },
+        PlatformError::MarketNotFound {
+            market_type: MarketType::BinanceSpot,
+            resource: "account".to_string(),
+        },
+        PlatformError::SymbolNotFound {
+            market_type: MarketType::BinanceSpot,
+            symbol: "BTCUSDT".to_string(),
+        },
+        PlatformError::InsufficientBalance {
+            asset: "USDT".to_string(),
+            free: Decimal::ZERO,
+            required: Decimal::ONE,
+        },
+        PlatformError::OrderNotFound {
+            client_order_id: "id".to_string(),
+        },
+        PlatformError::DuplicateOrder {
+            client_order_id: "id".to_string(),
+        },
+        PlatformError::MatchingError {
+            message: "".to_string(),
+        },
+        PlatformError::SymbolNotTrading {
+            market_type: MarketType::BinanceSpot,
+            symbol: "BTCUSDT".to_string(),
+            status: SymbolStatus::Halted,
+        },
+        PlatformError::PlatformError {
+            message: "".to_string(),
+        },
+    ];
+    let codes: Vec<(u32, &str)> = errors.iter().map(|e| (e.code(), e.code_name())).collect();
+    assert_eq!(
+        codes,
+        vec![
+            (1001, "CONFIG_ERROR"),
+            (2004, "RATE_LIMITED"),
+            (3003, "CACHE_MISS"),
+            (3004, "MARKET_NOT_FOUND"),
+            (3005, "SYMBOL_NOT_FOUND"),
+            (5003, "INSUFFICIENT_BALANCE"),
+            (5006, "ORDER_NOT_FOUND"),
+            (5007, "DUPLICATE_ORDER"),
+            (5008, "MATCHING_ERROR"),
+            (5009, "SYMBOL_NOT_TRADING"),
+            (9999, "PLATFORM_ERROR"),
+        ]
+    );
+    assert_eq!(
+        codes
+            .iter()
+            .map(|(code, _)| code)
+            .collect::<HashSet<_>>()
+            .len(),
+        codes.len()
+    );
+}
+
+#[test]
+fn test_error_display() {
+    let err = PlatformError::SymbolNotFound {
+        market_type: MarketType::BinanceSpot,
+        symbol: "ETHUSDT".to_string(),
+    };
+    assert_eq!(err.to_string(), "Symbol not found: BinanceSpot ETHUSDT");
+    let err = PlatformError::MarketNotFound {
+        market_type: MarketType::BinanceSpot,
+        resource: "open orders".to_string(),
+    };
+    assert_eq!(err.to_string(), "Market BinanceSpot open orders not found");
+}
+
+#[test]
+fn test_error_source_chain() {
+    let err: PlatformError = DBError::QueryError {
+        message: "no such table: kline".to_string(),
+    }
+    .into();
+    assert_eq!(err.code_name(), "DB_ERROR");
+    assert!(matches!(
+        err.source().unwrap().downcast_ref::<DBError>(),
+        Some(DBError::QueryError { .. })
+    ));
+
+    let err = PlatformError::WsError {
+        context: "Failed to connect".to_string(),
+        source: WsError::connection("refused"),
+    };
+    // Display只含上下文，底层错误通过source获取，避免reporter重复输出
+    assert_eq!(err.to_string(), "Failed to connect");
+    let source = err.source().unwrap();
+    assert!(source.downcast_ref::<WsError>().is_some());
+    assert_eq!(source.to_string(), "connection error: refused");
+}
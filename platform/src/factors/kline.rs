@@ -4,6 +4,7 @@ use crate::{
 };
 use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KlineFactors {
@@ -286,3 +287,399 @@ pub fn calc_kline_factors(klines: &[KlineData]) -> Result<KlineFactors> {
         avg_body_ratio: calc_avg_body_ratio(&data),
     })
 }
+
+// 滑动窗口内的最小/最大值，单调队列实现
+#[derive(Default)]
+struct RollingExtremes {
+    min: VecDeque<(u64, f64)>, // 单调递增
+    max: VecDeque<(u64, f64)>, // 单调递减
+}
+
+impl RollingExtremes {
+    fn push(&mut self, seq: u64, value: f64) {
+        while self.min.back().is_some_and(|&(_, v)| v >= value) {
+            self.min.pop_back();
+        }
+        self.min.push_back((seq, value));
+        while self.max.back().is_some_and(|&(_, v)| v <= value) {
+            self.max.pop_back();
+        }
+        self.max.push_back((seq, value));
+    }
+
+    // 移除序号小于front_seq的值
+    fn evict(&mut self, front_seq: u64) {
+        while self.min.front().is_some_and(|&(seq, _)| seq < front_seq) {
+            self.min.pop_front();
+        }
+        while self.max.front().is_some_and(|&(seq, _)| seq < front_seq) {
+            self.max.pop_front();
+        }
+    }
+
+    fn min(&self) -> f64 {
+        self.min.front().map(|&(_, v)| v).unwrap_or(0.0)
+    }
+
+    fn max(&self) -> f64 {
+        self.max.front().map(|&(_, v)| v).unwrap_or(0.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct RollingBar {
+    seq: u64,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: f64,
+}
+
+impl RollingBar {
+    fn body_ratio(&self) -> Option<f64> {
+        let range = (self.high - self.low).abs();
+        if range <= 0.0 {
+            return None;
+        }
+        Some((self.close - self.open).abs() / range)
+    }
+
+    // 窗口首根以自身open为前收盘价
+    fn intraday_range(&self, prev_close: f64) -> Option<f64> {
+        if prev_close.abs() < 1e-10 {
+            return None;
+        }
+        Some((self.high - self.low) / prev_close.abs())
+    }
+}
+
+// 固定窗口的滑动kline因子，每根新kline O(1) 更新
+// price_return/trend_strength/price_position 只依赖首尾、计数与极值，与 calc_kline_factors 逐位一致；
+// 其余因子基于增量维护的f64累计和，求和顺序与全量计算不同，无法逐位一致，相对误差在1e-9以内
+// 累计和以窗口内某根kline为基准做平移，减小方差计算的抵消误差；每滑动 window_size 次全量重算一次，避免误差累积
+pub struct RollingKlineFactors {
+    window_size: usize,
+    bars: VecDeque<RollingBar>,
+    last_kline: Option<KlineData>,
+    next_seq: u64,
+    slides: usize,
+
+    close_shift: f64,
+    volume_shift: f64,
+    close_sum: f64,  // Σ(c-close_shift)
+    close_sq: f64,   // Σ(c-close_shift)²
+    volume_sum: f64, // Σ(v-volume_shift)
+    volume_sq: f64,  // Σ(v-volume_shift)²
+    close_volume: f64,
+    first_half_volume: f64, // 前半段原始成交量之和
+
+    return_sum: f64,
+    return_sq: f64,
+    return_count: usize,
+    up: i64,
+    down: i64,
+    obv: f64,
+    intraday_sum: f64,
+    intraday_count: usize,
+    body_sum: f64,
+    body_count: usize,
+
+    closes: RollingExtremes,
+    volumes: RollingExtremes, // 用于识别常数序列，此时方差严格为0
+}
+
+impl RollingKlineFactors {
+    pub fn new(window_size: usize) -> Self {
+        Self {
+            window_size,
+            bars: VecDeque::with_capacity(window_size + 1),
+            last_kline: None,
+            next_seq: 0,
+            slides: 0,
+            close_shift: 0.0,
+            volume_shift: 0.0,
+            close_sum: 0.0,
+            close_sq: 0.0,
+            volume_sum: 0.0,
+            volume_sq: 0.0,
+            close_volume: 0.0,
+            first_half_volume: 0.0,
+            return_sum: 0.0,
+            return_sq: 0.0,
+            return_count: 0,
+            up: 0,
+            down: 0,
+            obv: 0.0,
+            intraday_sum: 0.0,
+            intraday_count: 0,
+            body_sum: 0.0,
+            body_count: 0,
+            closes: RollingExtremes::default(),
+            volumes: RollingExtremes::default(),
+        }
+    }
+
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.window_size > 0 && self.bars.len() == self.window_size
+    }
+
+    // 最近一次push的kline
+    pub fn last_kline(&self) -> Option<&KlineData> {
+        self.last_kline.as_ref()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new(self.window_size);
+    }
+
+    pub fn push(&mut self, kline: &KlineData) {
+        if self.window_size == 0 {
+            return;
+        }
+        let bar = RollingBar {
+            seq: self.next_seq,
+            open: kline.open.to_f64().unwrap_or(0.0),
+            high: kline.high.to_f64().unwrap_or(0.0),
+            low: kline.low.to_f64().unwrap_or(0.0),
+            close: kline.close.to_f64().unwrap_or(0.0),
+            volume: kline.volume.to_f64().unwrap_or(0.0),
+        };
+        self.next_seq += 1;
+        self.last_kline = Some(kline.clone());
+
+        self.closes.push(bar.seq, bar.close);
+        self.volumes.push(bar.seq, bar.volume);
+
+        if !self.is_full() || self.window_size < 2 {
+            self.bars.push_back(bar);
+            if self.bars.len() > self.window_size {
+                self.bars.pop_front();
+            }
+            self.evict_extremes();
+            if self.is_full() {
+                self.resync();
+            }
+            return;
+        }
+
+        self.slide(bar);
+        self.slides += 1;
+        if self.slides >= self.window_size {
+            self.resync();
+        }
+    }
+
+    fn evict_extremes(&mut self) {
+        let front_seq = match self.bars.front() {
+            None => return,
+            Some(bar) => bar.seq,
+        };
+        self.closes.evict(front_seq);
+        self.volumes.evict(front_seq);
+    }
+
+    fn add_pair(&mut self, prev: &RollingBar, cur: &RollingBar, sign: f64) {
+        if prev.close.abs() >= 1e-10 {
+            let ret = cur.close / prev.close - 1.0;
+            self.return_sum += sign * ret;
+            self.return_sq += sign * ret * ret;
+            if sign > 0.0 {
+                self.return_count += 1;
+            } else {
+                self.return_count -= 1;
+            }
+        }
+        let delta = sign as i64;
+        if cur.close > prev.close {
+            self.up += delta;
+            self.obv += sign * cur.volume;
+        } else if cur.close < prev.close {
+            self.down += delta;
+            self.obv -= sign * cur.volume;
+        }
+    }
+
+    fn add_intraday(&mut self, bar: &RollingBar, prev_close: f64, sign: f64) {
+        if let Some(range) = bar.intraday_range(prev_close) {
+            self.intraday_sum += sign * range;
+            if sign > 0.0 {
+                self.intraday_count += 1;
+            } else {
+                self.intraday_count -= 1;
+            }
+        }
+    }
+
+    fn add_bar(&mut self, bar: &RollingBar, sign: f64) {
+        let dc = bar.close - self.close_shift;
+        let dv = bar.volume - self.volume_shift;
+        self.close_sum += sign * dc;
+        self.close_sq += sign * dc * dc;
+        self.volume_sum += sign * dv;
+        self.volume_sq += sign * dv * dv;
+        self.close_volume += sign * dc * dv;
+        if let Some(ratio) = bar.body_ratio() {
+            self.body_sum += sign * ratio;
+            if sign > 0.0 {
+                self.body_count += 1;
+            } else {
+                self.body_count -= 1;
+            }
+        }
+    }
+
+    // 窗口已满，移出最旧的一根并加入新的一根
+    fn slide(&mut self, bar: RollingBar) {
+        let n = self.bars.len();
+        let front = self.bars[0];
+        let second = self.bars[1];
+        let middle = self.bars[n / 2];
+        let last = self.bars[n - 1];
+
+        self.add_bar(&front, -1.0);
+        self.add_pair(&front, &second, -1.0);
+        self.add_intraday(&front, front.open, -1.0);
+        self.add_intraday(&second, front.close, -1.0);
+        self.add_intraday(&second, second.open, 1.0);
+        self.first_half_volume += middle.volume - front.volume;
+
+        self.add_bar(&bar, 1.0);
+        self.add_pair(&last, &bar, 1.0);
+        self.add_intraday(&bar, last.close, 1.0);
+
+        self.bars.pop_front();
+        self.bars.push_back(bar);
+        self.evict_extremes();
+    }
+
+    // 以当前窗口全量重算累计值
+    fn resync(&mut self) {
+        self.slides = 0;
+        let bars: Vec<RollingBar> = self.bars.iter().copied().collect();
+        self.close_shift = bars.first().map(|b| b.close).unwrap_or(0.0);
+        self.volume_shift = bars.first().map(|b| b.volume).unwrap_or(0.0);
+        self.close_sum = 0.0;
+        self.close_sq = 0.0;
+        self.volume_sum = 0.0;
+        self.volume_sq = 0.0;
+        self.close_volume = 0.0;
+        self.return_sum = 0.0;
+        self.return_sq = 0.0;
+        self.return_count = 0;
+        self.up = 0;
+        self.down = 0;
+        self.obv = 0.0;
+        self.intraday_sum = 0.0;
+        self.intraday_count = 0;
+        self.body_sum = 0.0;
+        self.body_count = 0;
+        self.first_half_volume = bars[..bars.len() / 2].iter().map(|b| b.volume).sum();
+
+        for (i, bar) in bars.iter().enumerate() {
+            self.add_bar(bar, 1.0);
+            if i == 0 {
+                self.add_intraday(bar, bar.open, 1.0);
+            } else {
+                self.add_pair(&bars[i - 1], bar, 1.0);
+                self.add_intraday(bar, bars[i - 1].close, 1.0);
+            }
+        }
+    }
+
+    // 窗口未满或窗口小于5时返回错误，与 calc_kline_factors 一致
+    pub fn factors(&self) -> Result<KlineFactors> {
+        if !self.is_full() || self.window_size < 5 {
+            return Err(PlatformError::FactorError {
+                message: "Not enough klines to calculate factors".to_string(),
+            });
+        }
+        let n = self.bars.len();
+        let nf = n as f64;
+        let first = self.bars[0];
+        let last = self.bars[n - 1];
+
+        let close_mean = self.close_shift + self.close_sum / nf;
+        let volume_mean = self.volume_shift + self.volume_sum / nf;
+        let close_min = self.closes.min();
+        let close_max = self.closes.max();
+        // 离差平方和，常数序列直接取0，避免残余误差被相关系数分母中的1e-10放大
+        let close_const = close_min == close_max;
+        let volume_const = self.volumes.min() == self.volumes.max();
+        let close_var = if close_const {
+            0.0
+        } else {
+            (self.close_sq - self.close_sum * self.close_sum / nf).max(0.0)
+        };
+        let volume_var = if volume_const {
+            0.0
+        } else {
+            (self.volume_sq - self.volume_sum * self.volume_sum / nf).max(0.0)
+        };
+        let cov = if close_const || volume_const {
+            0.0
+        } else {
+            self.close_volume - self.close_sum * self.volume_sum / nf
+        };
+
+        let price_return = if first.close.abs() < 1e-10 {
+            0.0
+        } else {
+            last.close / first.close - 1.0
+        };
+        let price_volatility = if self.return_count == 0 {
+            0.0
+        } else {
+            let count = self.return_count as f64;
+            let mean = self.return_sum / count;
+            (self.return_sq / count - mean * mean).max(0.0).sqrt()
+        };
+        let price_range = close_max - close_min;
+        let price_position = if price_range > 0.0 {
+            (last.close - close_min) / price_range
+        } else {
+            0.5
+        };
+        let volume_trend = if n < 4 {
+            1.0
+        } else {
+            let half = n / 2;
+            let first_avg = self.first_half_volume / half as f64;
+            let volume_total = self.volume_shift * nf + self.volume_sum;
+            let second_avg = (volume_total - self.first_half_volume) / (n - half) as f64;
+            if first_avg > 0.0 {
+                second_avg / first_avg
+            } else {
+                1.0
+            }
+        };
+        let avg = |sum: f64, count: usize| {
+            if count == 0 {
+                0.0
+            } else {
+                sum / count as f64
+            }
+        };
+
+        Ok(KlineFactors {
+            price_return,
+            trend_strength: (self.up - self.down) as f64 / (nf - 1.0),
+            price_volatility,
+            price_range: price_range / (close_mean + 1e-10),
+            price_position,
+
+            avg_volume: volume_mean,
+            volume_volatility: (volume_var / nf).sqrt(),
+            volume_trend,
+            obv: self.obv,
+            price_volume_correlation: cov / ((close_var * volume_var).sqrt() + 1e-10),
+
+            avg_intraday_range: avg(self.intraday_sum, self.intraday_count),
+            avg_body_ratio: avg(self.body_sum, self.body_count),
+        })
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    factors::{calc_kline_factors, KlineFactors, RollingKlineFactors},
+    models::{KlineData, KlineInterval},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+
+fn random_klines(count: usize, seed: u64) -> Vec<KlineData> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut close: f64 = 100.0;
+    let mut klines = Vec::with_capacity(count);
+    for i in 0..count {
+        let open = close;
+        // 偶尔出现平盘与零成交量，覆盖相等比较的分支
+        close = if rng.random_bool(0.1) {
+            open
+        } else {
+            open * (1.0 + rng.random_range(-0.01..0.01))
+        };
+        let high = open.max(close) * (1.0 + rng.random_range(0.0..0.005));
+        let low = open.min(close) * (1.0 - rng.random_range(0.0..0.005));
+        let volume = if rng.random_bool(0.05) {
+            0.0
+        } else {
+            rng.random_range(0.0..1000.0)
+        };
+        let decimal = |v: f64| Decimal::from_f64(v).unwrap().round_dp(8);
+        let open_time = i as u64 * 60_000;
+        klines.push(KlineData {
+            symbol: "BTCUSDT".to_string(),
+            interval: KlineInterval::OneMinute,
+            open_time,
+            close_time: open_time + 59_999,
+            open: decimal(open),
+            high: decimal(high),
+            low: decimal(low),
+            close: decimal(close),
+            volume: decimal(volume),
+            quote_volume: decimal(volume * close),
+            taker_buy_volume: Decimal::ZERO,
+            taker_buy_quote_volume: Decimal::ZERO,
+            is_closed: 1,
+        });
+    }
+    klines
+}
+
+// 基于累计和的因子与全量计算的求和顺序不同，允许的相对误差
+const SUM_FACTOR_TOLERANCE: f64 = 1e-9;
+
+fn assert_close(name: &str, step: usize, actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= SUM_FACTOR_TOLERANCE * expected.abs().max(1.0),
+        "{} at step {}: rolling {} naive {}",
+        name,
+        step,
+        actual,
+        expected
+    );
+}
+
+fn assert_factors_close(step: usize, actual: &KlineFactors, expected: &KlineFactors) {
+    // 只依赖首尾、计数与极值的因子逐位一致
+    assert_eq!(actual.price_return, expected.price_return, "step {}", step);
+    assert_eq!(
+        actual.trend_strength, expected.trend_strength,
+        "step {}",
+        step
+    );
+    assert_eq!(
+        actual.price_position, expected.price_position,
+        "step {}",
+        step
+    );
+    assert_close(
+        "price_volatility",
+        step,
+        actual.price_volatility,
+        expected.price_volatility,
+    );
+    assert_close(
+        "price_range",
+        step,
+        actual.price_range,
+        expected.price_range,
+    );
+    assert_close("avg_volume", step, actual.avg_volume, expected.avg_volume);
+    assert_close(
+        "volume_volatility",
+        step,
+        actual.volume_volatility,
+        expected.volume_volatility,
+    );
+    assert_close(
+        "volume_trend",
+        step,
+        actual.volume_trend,
+        expected.volume_trend,
+    );
+    assert_close("obv", step, actual.obv, expected.obv);
+    assert_close(
+        "price_volume_correlation",
+        step,
+        actual.price_volume_correlation,
+        expected.price_volume_correlation,
+    );
+    assert_close(
+        "avg_intraday_range",
+        step,
+        actual.avg_intraday_range,
+        expected.avg_intraday_range,
+    );
+    assert_close(
+        "avg_body_ratio",
+        step,
+        actual.avg_body_ratio,
+        expected.avg_body_ratio,
+    );
+}
+
+#[test]
+fn test_rolling_kline_factors_match_naive() {
+    let klines = random_klines(5000, 7);
+    for window_size in [5, 7, 60, 501] {
+        let mut rolling = RollingKlineFactors::new(window_size);
+        for (i, kline) in klines.iter().enumerate() {
+            rolling.push(kline);
+            if i + 1 < window_size {
+                assert!(rolling.factors().is_err());
+                continue;
+            }
+            let expected = calc_kline_factors(&klines[i + 1 - window_size..=i]).unwrap();
+            assert_factors_close(i, &rolling.factors().unwrap(), &expected);
+        }
+    }
+}
+
+#[test]
+fn test_rolling_kline_factors_small_window() {
+    let klines = random_klines(10, 1);
+    let mut rolling = RollingKlineFactors::new(4);
+    for kline in klines.iter() {
+        rolling.push(kline);
+    }
+    assert!(rolling.is_full());
+    assert!(rolling.factors().is_err());
+    assert_eq!(rolling.last_kline().unwrap().open_time, klines[9].open_time);
+
+    rolling.clear();
+    assert!(!rolling.is_full());
+    assert!(rolling.last_kline().is_none());
+}
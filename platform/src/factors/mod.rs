@@ -1,8 +1,12 @@
 pub mod trade;
 pub use trade::*;
+#[cfg(test)]
+mod trade_tests;
 
 pub mod kline;
 pub use kline::*;
+#[cfg(test)]
+mod kline_tests;
 
 pub mod ticker;
 pub use ticker::*;
@@ -2,7 +2,7 @@ use crate::{
     errors::{PlatformError, Result},
     models::Trade,
 };
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,29 +46,31 @@ pub struct TradeFactors {
 }
 
 /// 共享的预计算数据结构，用于优化性能
+/// 价格与成交量保持Decimal，求和、均值、差值和比值均在Decimal下计算，最终结果再转为f64，
+/// 避免低价币种（如0.00001234）在f64下相减或加上固定的极小值时丢失有效位
 struct PrecomputedData {
-    prices: Vec<f64>,
-    volumes: Vec<f64>,
+    prices: Vec<Decimal>,
+    volumes: Vec<Decimal>,
     timestamps: Vec<u64>,
 
     // 价格统计
-    price_mean: f64,
-    price_min: f64,
-    price_max: f64,
-    price_first: f64,
-    price_last: f64,
+    price_mean: Decimal,
+    price_min: Decimal,
+    price_max: Decimal,
+    price_first: Decimal,
+    price_last: Decimal,
 
     // 成交量统计
-    vol_mean: f64,
+    vol_mean: Decimal,
 
     // 买卖统计
     buy_count: u64,
     sell_count: u64,
-    buy_vol: f64,
-    sell_vol: f64,
+    buy_vol: Decimal,
+    sell_vol: Decimal,
 
     // 价量统计
-    vwap: f64,
+    vwap: Decimal,
 
     // 时间统计
     time_span: f64, // 毫秒
@@ -80,27 +82,24 @@ impl PrecomputedData {
         let mut prices = Vec::with_capacity(n);
         let mut volumes = Vec::with_capacity(n);
         let mut timestamps = Vec::with_capacity(n);
-        let mut is_buyer_makers = Vec::with_capacity(n);
 
-        let mut price_sum = 0.0;
-        let mut price_min = f64::MAX;
-        let mut price_max = f64::MIN;
-        let mut vol_sum = 0.0;
+        let mut price_sum = Decimal::ZERO;
+        let mut price_min = Decimal::MAX;
+        let mut price_max = Decimal::MIN;
+        let mut vol_sum = Decimal::ZERO;
         let mut buy_count = 0u64;
         let mut sell_count = 0u64;
-        let mut buy_vol = 0.0;
-        let mut sell_vol = 0.0;
-        let mut weighted_price_sum = 0.0;
+        let mut buy_vol = Decimal::ZERO;
+        let mut sell_vol = Decimal::ZERO;
+        let mut weighted_price_sum = Decimal::ZERO;
 
         for trade in trades {
-            let price = trade.price.to_f64().unwrap_or(0.0);
-            let volume = trade.quantity.to_f64().unwrap_or(0.0);
-            let is_buyer_maker = trade.is_buyer_maker;
+            let price = trade.price;
+            let volume = trade.quantity;
 
             prices.push(price);
             volumes.push(volume);
             timestamps.push(trade.timestamp);
-            is_buyer_makers.push(is_buyer_maker);
 
             price_sum += price;
             vol_sum += volume;
@@ -109,7 +108,7 @@ impl PrecomputedData {
             weighted_price_sum += price * volume;
 
             // 买单做市提供流动性，说明吃单方向是卖单
-            if is_buyer_maker == 1 {
+            if trade.is_buyer_maker == 1 {
                 sell_count += 1;
                 sell_vol += volume;
             } else {
@@ -118,13 +117,11 @@ impl PrecomputedData {
             }
         }
 
-        let price_mean = price_sum / n as f64;
-        let vol_mean = vol_sum / n as f64;
-        let vwap = if vol_sum > 0.0 {
-            weighted_price_sum / vol_sum
-        } else {
-            0.0
-        };
+        let price_mean = price_sum / Decimal::from(n);
+        let vol_mean = vol_sum / Decimal::from(n);
+        let vwap = weighted_price_sum
+            .checked_div(vol_sum)
+            .unwrap_or(Decimal::ZERO);
 
         let price_first = prices[0];
         let price_last = prices[n - 1];
@@ -155,9 +152,18 @@ impl PrecomputedData {
     }
 }
 
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+/// Decimal下计算比值后转为f64，分母为0时返回None
+fn ratio(numerator: Decimal, denominator: Decimal) -> Option<f64> {
+    numerator.checked_div(denominator).map(to_f64)
+}
+
 /// 1. 价格变化率：最新价格/最早价格 - 1
 fn calc_price_return(data: &PrecomputedData) -> f64 {
-    (data.price_last / (data.price_first + 1e-10)) - 1.0
+    ratio(data.price_last - data.price_first, data.price_first).unwrap_or(0.0)
 }
 
 /// 2. 价格趋势强度：(上涨次数 - 下跌次数) / 总变化次数
@@ -166,10 +172,9 @@ fn calc_trend_strength(data: &PrecomputedData) -> f64 {
     let mut down_count = 0;
 
     for i in 1..data.prices.len() {
-        let diff = data.prices[i] - data.prices[i - 1];
-        if diff > 0.0 {
+        if data.prices[i] > data.prices[i - 1] {
             up_count += 1;
-        } else if diff < 0.0 {
+        } else if data.prices[i] < data.prices[i - 1] {
             down_count += 1;
         }
     }
@@ -185,7 +190,7 @@ fn calc_price_volatility(data: &PrecomputedData) -> f64 {
 
 /// 4. 价格区间幅度: (max_price - min_price) / mean_price
 fn calc_price_range(data: &PrecomputedData) -> f64 {
-    (data.price_max - data.price_min) / (data.price_mean + 1e-10)
+    ratio(data.price_max - data.price_min, data.price_mean).unwrap_or(0.0)
 }
 
 /// 5. 价格加速度：对价格变化率再求变化的平均值
@@ -193,7 +198,8 @@ fn calc_price_acceleration(data: &PrecomputedData) -> f64 {
     // 先计算一阶差分（速度）
     let mut first_diff = Vec::with_capacity(data.prices.len() - 1);
     for i in 1..data.prices.len() {
-        first_diff.push((data.prices[i] - data.prices[i - 1]) / (data.prices[i - 1] + 1e-10));
+        first_diff
+            .push(ratio(data.prices[i] - data.prices[i - 1], data.prices[i - 1]).unwrap_or(0.0));
     }
 
     // 再计算二阶差分（加速度）
@@ -207,17 +213,17 @@ fn calc_price_acceleration(data: &PrecomputedData) -> f64 {
 
 /// 6. 价格位置：(最新价格 - min_price) / (max_price - min_price)
 fn calc_price_position(data: &PrecomputedData) -> f64 {
-    let range = data.price_max - data.price_min;
-    if range > 0.0 {
-        (data.price_last - data.price_min) / range
-    } else {
-        0.5 // 如果没有价格变化，返回中间位置
-    }
+    // 如果没有价格变化，返回中间位置
+    ratio(
+        data.price_last - data.price_min,
+        data.price_max - data.price_min,
+    )
+    .unwrap_or(0.5)
 }
 
 /// 平均成交量（已在预计算中）
 fn calc_avg_vol(data: &PrecomputedData) -> f64 {
-    data.vol_mean
+    to_f64(data.vol_mean)
 }
 
 /// 成交量波动率：标准差
@@ -227,12 +233,11 @@ fn calc_vol_volatility(data: &PrecomputedData) -> f64 {
 
 /// 成交量偏度：衡量成交量分布的对称性
 fn calc_vol_skew(data: &PrecomputedData) -> f64 {
-    let mean = data.vol_mean;
     let std = calc_vol_volatility(data);
 
     let mut skew_sum = 0.0;
     for &vol in &data.volumes {
-        let diff = vol - mean;
+        let diff = to_f64(vol - data.vol_mean);
         skew_sum += diff.powi(3);
     }
 
@@ -242,7 +247,7 @@ fn calc_vol_skew(data: &PrecomputedData) -> f64 {
 
 /// 大单比例：成交量 > 1.5倍平均的交易数 / 总交易数
 fn calc_large_trade_ratio(data: &PrecomputedData) -> f64 {
-    let threshold = data.vol_mean * 1.5;
+    let threshold = data.vol_mean * Decimal::new(15, 1);
     let large_count = data.volumes.iter().filter(|&&v| v > threshold).count();
 
     large_count as f64 / data.volumes.len() as f64
@@ -251,14 +256,10 @@ fn calc_large_trade_ratio(data: &PrecomputedData) -> f64 {
 /// 成交量趋势：最近10条的平均成交量 / 10条前的平均成交量
 fn calc_vol_trend(data: &PrecomputedData) -> f64 {
     let n = data.volumes.len();
-    let first_half_avg: f64 = data.volumes[..n - 10].iter().sum::<f64>() / (n - 10) as f64;
-    let second_half_avg: f64 = data.volumes[n - 10..].iter().sum::<f64>() / 10.0;
+    let first_half_avg = data.volumes[..n - 10].iter().sum::<Decimal>() / Decimal::from(n - 10);
+    let second_half_avg = data.volumes[n - 10..].iter().sum::<Decimal>() / Decimal::from(10);
 
-    if first_half_avg > 0.0 {
-        second_half_avg / first_half_avg
-    } else {
-        1.0
-    }
+    ratio(second_half_avg, first_half_avg).unwrap_or(1.0)
 }
 
 /// 成交不平衡度: (buy_count - sell_count) / (buy_count + sell_count)
@@ -273,41 +274,28 @@ fn calc_trade_imbalance(data: &PrecomputedData) -> f64 {
 
 /// 成交量不平衡度: (buy_vol - sell_vol) / (buy_vol + sell_vol)
 fn calc_vol_imbalance(data: &PrecomputedData) -> f64 {
-    let total = data.buy_vol + data.sell_vol;
-    if total > 0.0 {
-        (data.buy_vol - data.sell_vol) / total
-    } else {
-        0.0
-    }
+    ratio(data.buy_vol - data.sell_vol, data.buy_vol + data.sell_vol).unwrap_or(0.0)
 }
 
 /// 净主动买入比例: buy_vol / (buy_vol + sell_vol)
 fn calc_net_buy_ratio(data: &PrecomputedData) -> f64 {
-    let total = data.buy_vol + data.sell_vol;
-    if total > 0.0 {
-        data.buy_vol / total
-    } else {
-        0.0
-    }
+    ratio(data.buy_vol, data.buy_vol + data.sell_vol).unwrap_or(0.0)
 }
 
 /// 买卖平均单量对比: (buy_vol/buy_count) / (sell_vol/sell_count)
 fn calc_avg_trade_size_ratio(data: &PrecomputedData) -> f64 {
-    let avg_buy_size = if data.buy_count > 0 {
-        data.buy_vol / data.buy_count as f64
-    } else {
-        0.0
-    };
-
-    let avg_sell_size = if data.sell_count > 0 {
-        data.sell_vol / data.sell_count as f64
-    } else {
-        0.0
-    };
-
-    if avg_sell_size > 0.0 {
-        avg_buy_size / avg_sell_size
-    } else if avg_buy_size > 0.0 {
+    let avg_buy_size = data
+        .buy_vol
+        .checked_div(Decimal::from(data.buy_count))
+        .unwrap_or(Decimal::ZERO);
+    let avg_sell_size = data
+        .sell_vol
+        .checked_div(Decimal::from(data.sell_count))
+        .unwrap_or(Decimal::ZERO);
+
+    if avg_sell_size > Decimal::ZERO {
+        ratio(avg_buy_size, avg_sell_size).unwrap_or(1.0)
+    } else if avg_buy_size > Decimal::ZERO {
         2.0 // 如果只有买单，返回一个大值
     } else {
         1.0
@@ -316,48 +304,27 @@ fn calc_avg_trade_size_ratio(data: &PrecomputedData) -> f64 {
 
 /// 最新价格与VWAP的偏离: (price_last - vwap) / vwap
 fn calc_price_vwap_deviation(data: &PrecomputedData) -> f64 {
-    if data.vwap > 0.0 {
-        (data.price_last - data.vwap) / data.vwap
-    } else {
-        0.0
+    ratio(data.price_last - data.vwap, data.vwap).unwrap_or(0.0)
+}
+
+/// [start, end)区间的VWAP，无成交量时为0
+fn calc_range_vwap(data: &PrecomputedData, start: usize, end: usize) -> Decimal {
+    let mut weighted_sum = Decimal::ZERO;
+    let mut vol_sum = Decimal::ZERO;
+    for i in start..end {
+        weighted_sum += data.prices[i] * data.volumes[i];
+        vol_sum += data.volumes[i];
     }
+    weighted_sum.checked_div(vol_sum).unwrap_or(Decimal::ZERO)
 }
 
 /// VWAP斜率：最近10条的VWAP与前20-10条的VWAP的变化率
 fn calc_vwap_slope(data: &PrecomputedData) -> f64 {
     let n = data.prices.len();
+    let first_vwap = calc_range_vwap(data, n - 20, n - 10);
+    let second_vwap = calc_range_vwap(data, n - 10, n);
 
-    // 计算前半段VWAP
-    let mut first_weighted_sum = 0.0;
-    let mut first_vol_sum = 0.0;
-    for i in n - 20..n - 10 {
-        first_weighted_sum += data.prices[i] * data.volumes[i];
-        first_vol_sum += data.volumes[i];
-    }
-    let first_vwap = if first_vol_sum > 0.0 {
-        first_weighted_sum / first_vol_sum
-    } else {
-        0.0
-    };
-
-    // 计算后半段VWAP
-    let mut second_weighted_sum = 0.0;
-    let mut second_vol_sum = 0.0;
-    for i in n - 10..n {
-        second_weighted_sum += data.prices[i] * data.volumes[i];
-        second_vol_sum += data.volumes[i];
-    }
-    let second_vwap = if second_vol_sum > 0.0 {
-        second_weighted_sum / second_vol_sum
-    } else {
-        0.0
-    };
-
-    if first_vwap > 0.0 {
-        (second_vwap - first_vwap) / first_vwap
-    } else {
-        0.0
-    }
+    ratio(second_vwap - first_vwap, first_vwap).unwrap_or(0.0)
 }
 
 /// OBV (On-Balance Volume)：价格上涨加成交量，下跌减成交量
@@ -366,7 +333,7 @@ fn calc_obv(data: &PrecomputedData) -> f64 {
         return 0.0;
     }
 
-    let mut obv = 0.0;
+    let mut obv = Decimal::ZERO;
     for i in 1..data.prices.len() {
         if data.prices[i] > data.prices[i - 1] {
             obv += data.volumes[i];
@@ -375,7 +342,7 @@ fn calc_obv(data: &PrecomputedData) -> f64 {
         }
     }
 
-    obv
+    to_f64(obv)
 }
 
 /// 价格与成交量的相关系数
@@ -384,23 +351,25 @@ fn calc_price_volume_correlation(data: &PrecomputedData) -> f64 {
         return 0.0;
     }
 
-    let price_mean = data.price_mean;
-    let vol_mean = data.vol_mean;
-
     let mut covariance = 0.0;
     let mut price_var = 0.0;
     let mut vol_var = 0.0;
 
+    // 离差在Decimal下计算，之后的平方和在f64下累加
     for i in 0..data.prices.len() {
-        let price_diff = data.prices[i] - price_mean;
-        let vol_diff = data.volumes[i] - vol_mean;
+        let price_diff = to_f64(data.prices[i] - data.price_mean);
+        let vol_diff = to_f64(data.volumes[i] - data.vol_mean);
         covariance += price_diff * vol_diff;
         price_var += price_diff * price_diff;
         vol_var += vol_diff * vol_diff;
     }
 
     let denominator = (price_var * vol_var).sqrt();
-    covariance / (denominator + 1e-10)
+    if denominator > 0.0 {
+        covariance / denominator
+    } else {
+        0.0
+    }
 }
 
 /// 交易频率：单位时间（秒）内的成交次数
@@ -428,23 +397,27 @@ fn calc_trade_interval_std(data: &PrecomputedData) -> f64 {
         return 0.0;
     }
 
-    let mut intervals = Vec::with_capacity(data.timestamps.len() - 1);
-    for i in 1..data.timestamps.len() {
-        intervals.push((data.timestamps[i] - data.timestamps[i - 1]) as f64);
-    }
+    let intervals: Vec<Decimal> = data
+        .timestamps
+        .windows(2)
+        .map(|w| Decimal::from(w[1] - w[0]))
+        .collect();
 
-    let mean = intervals.iter().sum::<f64>() / intervals.len() as f64;
+    let mean = intervals.iter().sum::<Decimal>() / Decimal::from(intervals.len());
     calc_std(&intervals, mean)
 }
 
-/// 计算标准差
-fn calc_std(values: &[f64], mean: f64) -> f64 {
+/// 计算标准差，离差在Decimal下计算，平方和在f64下累加
+fn calc_std(values: &[Decimal], mean: Decimal) -> f64 {
     if values.is_empty() {
         return 0.0;
     }
 
-    let variance: f64 =
-        values.iter().map(|&x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    let variance: f64 = values
+        .iter()
+        .map(|&x| to_f64(x - mean).powi(2))
+        .sum::<f64>()
+        / values.len() as f64;
 
     variance.sqrt()
 }
@@ -478,14 +451,14 @@ pub fn calc_trade_factors(trades: &[Trade]) -> Result<TradeFactors> {
         buy_count: data.buy_count,
         sell_count: data.sell_count,
         trade_imbalance: calc_trade_imbalance(&data),
-        buy_vol: data.buy_vol,
-        sell_vol: data.sell_vol,
+        buy_vol: to_f64(data.buy_vol),
+        sell_vol: to_f64(data.sell_vol),
         vol_imbalance: calc_vol_imbalance(&data),
         net_buy_ratio: calc_net_buy_ratio(&data),
         avg_trade_size_ratio: calc_avg_trade_size_ratio(&data),
 
         // 价量因子
-        vwap: data.vwap,
+        vwap: to_f64(data.vwap),
         price_vwap_deviation: calc_price_vwap_deviation(&data),
         vwap_slope: calc_vwap_slope(&data),
         obv: calc_obv(&data),
@@ -0,0 +1,83 @@
+use crate::{factors::calc_trade_factors, models::Trade};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use std::str::FromStr;
+
+// 价格在 base 附近按 tick 上下波动，成交量与买卖方向交替变化
+fn ticking_trades(base: Decimal, tick: Decimal, count: u64) -> Vec<Trade> {
+    (0..count)
+        .map(|i| Trade {
+            symbol: "PEPEUSDT".to_string(),
+            trade_id: i.to_string(),
+            price: base + tick * Decimal::from((i * 7 + i / 3) % 5),
+            quantity: Decimal::from(1000 + (i * 37) % 200),
+            timestamp: 1000 + i * 100,
+            is_buyer_maker: i % 3 % 2,
+            seq_id: i,
+        })
+        .collect()
+}
+
+fn assert_relative_eq(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() <= 1e-12 * expected.abs().max(1e-300),
+        "{} != {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn test_low_price_factors_match_scaled_prices() {
+    // 同一组成交分别以 0.00001234 和放大 1e8 后的 1234 报价，与价格量级无关的因子应一致
+    let low = ticking_trades(
+        Decimal::from_str("0.00001234").unwrap(),
+        Decimal::from_str("0.00000001").unwrap(),
+        50,
+    );
+    let high = ticking_trades(Decimal::from(1234), Decimal::ONE, 50);
+    let low_factors = calc_trade_factors(&low).unwrap();
+    let high_factors = calc_trade_factors(&high).unwrap();
+
+    assert!(low_factors.price_return != 0.0);
+    assert_relative_eq(low_factors.price_return, high_factors.price_return);
+    assert_relative_eq(low_factors.price_range, high_factors.price_range);
+    assert_relative_eq(
+        low_factors.price_acceleration,
+        high_factors.price_acceleration,
+    );
+    assert_relative_eq(low_factors.price_position, high_factors.price_position);
+    assert_relative_eq(
+        low_factors.price_vwap_deviation,
+        high_factors.price_vwap_deviation,
+    );
+    assert_relative_eq(low_factors.vwap_slope, high_factors.vwap_slope);
+    assert_relative_eq(
+        low_factors.price_volume_correlation,
+        high_factors.price_volume_correlation,
+    );
+    assert_relative_eq(low_factors.vwap, high_factors.vwap * 1e-8);
+    assert_relative_eq(
+        low_factors.price_volatility,
+        high_factors.price_volatility * 1e-8,
+    );
+    assert_eq!(low_factors.trend_strength, high_factors.trend_strength);
+    assert_eq!(low_factors.obv, high_factors.obv);
+}
+
+#[test]
+fn test_low_price_return_is_exact() {
+    let trades = ticking_trades(
+        Decimal::from_str("0.00001234").unwrap(),
+        Decimal::from_str("0.00000001").unwrap(),
+        50,
+    );
+    let (first, last) = (trades[0].price, trades[49].price);
+    let expected = ((last - first) / first).to_f64().unwrap();
+    let factors = calc_trade_factors(&trades).unwrap();
+    assert_relative_eq(factors.price_return, expected);
+
+    // 在 f64 下给分母加 1e-10 防除零，低价币种上的相对误差约为 1e-5
+    let (first, last) = (first.to_f64().unwrap(), last.to_f64().unwrap());
+    let naive = last / (first + 1e-10) - 1.0;
+    assert!((naive - expected).abs() > 1e-6 * expected.abs());
+}
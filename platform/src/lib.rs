@@ -4,6 +4,8 @@ pub mod conversions;
 pub mod data_manager;
 pub mod engines;
 pub mod errors;
+#[cfg(test)]
+mod errors_tests;
 pub mod factors;
 pub mod market_dump;
 pub mod market_provider;
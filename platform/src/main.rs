@@ -1,18 +1,29 @@
-use std::{collections::HashMap, fs::OpenOptions, io::Write, sync::Arc};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufWriter, Write},
+    sync::Arc,
+};
 
 use db::sqlite::SQLiteDB;
 use env_logger::Env;
 use platform::{
     backtest::factors::{
-        factor_backtest::FactorBacktester,
+        factor_backtest::{FactorBacktester, StepMode},
         factor_calculators::{
             KlineFactorCalculators, KlineFactorType, TradeFactorCalculators, TradeFactorType,
         },
-        price_providers::{KlineClosePriceProvider, TradePriceProvider},
+        factor_export::{write_factor_records_csv, write_factor_records_json},
+        price_providers::{
+            KlineClosePriceProvider, MidPriceProvider, TradePriceProvider, VwapPriceProvider,
+        },
         traits::{FactorCalculator, PriceProvider},
     },
     config::{Config, PlatformConfig},
     data_manager::{
+        dataset::{
+            export_dataset, export_klines_csv, export_trades_csv, import_dataset, DatasetFilter,
+        },
         local_data_manager::{Clock, LocalMarketDataManager},
         MarketDataManager,
     },
@@ -58,8 +69,9 @@ async fn db_migration_main(conf: &str, args: &HashMap<String, String>) {
     let platform_config = PlatformConfig::from_config(config).unwrap();
     let db = Arc::new(
         SQLiteDB::new(&platform_config.db_path)
-            .map_err(|e| PlatformError::PlatformError {
-                message: format!("Failed to open database: {}", e),
+            .map_err(|e| PlatformError::DbError {
+                context: "Failed to open database".to_string(),
+                source: e,
             })
             .expect("init db failed"),
     );
@@ -96,14 +108,116 @@ async fn db_migration_main(conf: &str, args: &HashMap<String, String>) {
     db.execute_update("VACUUM;", &[]).expect("vacuum failed");
 }
 
+async fn dataset_main(conf: &str, args: &HashMap<String, String>) {
+    let config = Config::from_toml(conf).unwrap();
+    let platform_config = PlatformConfig::from_config(config).unwrap();
+    let db = Arc::new(
+        SQLiteDB::new(&platform_config.db_path)
+            .map_err(|e| PlatformError::DbError {
+                context: "Failed to open database".to_string(),
+                source: e,
+            })
+            .expect("init db failed"),
+    );
+    let market_type = args
+        .get("market_type")
+        .and_then(|s| MarketType::from_str(s))
+        .expect("market_type not found");
+    let path = args.get("path").expect("path not found");
+
+    let stats = match args.get("action").map(String::as_str) {
+        Some("export") => {
+            let symbol = args.get("symbol").expect("symbol not found");
+            let intervals = args
+                .get("intervals")
+                .map(|s| {
+                    s.split(',')
+                        .map(|i| KlineInterval::from_str(i.trim()).expect("invalid kline interval"))
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            let start_time = args
+                .get("from_ts")
+                .and_then(|s| s.parse::<u64>().ok())
+                .expect("from_ts not found");
+            let end_time = args
+                .get("to_ts")
+                .and_then(|s| s.parse::<u64>().ok())
+                .expect("to_ts not found");
+            let filter = DatasetFilter {
+                market_type,
+                symbol: symbol.clone(),
+                intervals,
+                start_time,
+                end_time,
+            };
+            export_dataset(db, &filter, path).expect("export dataset failed")
+        }
+        Some("import") => import_dataset(db, &market_type, path).expect("import dataset failed"),
+        _ => panic!("unsupported dataset action"),
+    };
+    log::info!("dataset finished: {:?}", stats);
+}
+
+async fn csv_export_main(conf: &str, args: &HashMap<String, String>) {
+    let config = Config::from_toml(conf).unwrap();
+    let platform_config = PlatformConfig::from_config(config).unwrap();
+    let db = Arc::new(
+        SQLiteDB::new(&platform_config.db_path)
+            .map_err(|e| PlatformError::DbError {
+                context: "Failed to open database".to_string(),
+                source: e,
+            })
+            .expect("init db failed"),
+    );
+    let market_type = args
+        .get("market_type")
+        .and_then(|s| MarketType::from_str(s))
+        .expect("market_type not found");
+    let symbol = args.get("symbol").expect("symbol not found");
+    let from_ts = args
+        .get("from_ts")
+        .and_then(|s| s.parse::<u64>().ok())
+        .expect("from_ts not found");
+    let to_ts = args
+        .get("to_ts")
+        .and_then(|s| s.parse::<u64>().ok())
+        .expect("to_ts not found");
+    let output = args.get("output").expect("output not found");
+    let mut writer = BufWriter::new(File::create(output).expect("create output file failed"));
+
+    let rows = match args.get("data_type").map(String::as_str) {
+        Some("kline") => {
+            let interval = args.get("interval").expect("interval not found");
+            let interval = KlineInterval::from_str(interval).expect("invalid kline interval");
+            export_klines_csv(
+                db,
+                &market_type,
+                symbol,
+                &interval,
+                from_ts,
+                to_ts,
+                &mut writer,
+            )
+            .expect("export klines csv failed")
+        }
+        Some("trade") => export_trades_csv(db, &market_type, symbol, from_ts, to_ts, &mut writer)
+            .expect("export trades csv failed"),
+        _ => panic!("unsupported data_type"),
+    };
+    writer.flush().expect("flush output file failed");
+    log::info!("csv export finished, rows: {}, output: {}", rows, output);
+}
+
 async fn factor_backtest_main(conf: &str, args: &HashMap<String, String>) {
     let config = Config::from_toml(conf).unwrap();
     let platform_config = Arc::new(PlatformConfig::from_config(config).unwrap());
 
     let db = Arc::new(
         SQLiteDB::new(&platform_config.db_path)
-            .map_err(|e| PlatformError::PlatformError {
-                message: format!("Failed to open database: {}", e),
+            .map_err(|e| PlatformError::DbError {
+                context: "Failed to open database".to_string(),
+                source: e,
             })
             .expect("init db failed"),
     );
@@ -153,6 +267,11 @@ async fn factor_backtest_main(conf: &str, args: &HashMap<String, String>) {
         }
         _ => panic!("unsupported data_type"),
     };
+    // 未指定price_type时沿用data_type对应的默认价格
+    let price_provider = match args.get("price_type") {
+        Some(price_type) => build_price_provider(price_type, args),
+        None => price_provider,
+    };
 
     let clock = Arc::new(Clock::new(from_ts));
     let local_market_mgr = LocalMarketDataManager::new(platform_config, clock.clone(), db, 10000)
@@ -177,7 +296,18 @@ async fn factor_backtest_main(conf: &str, args: &HashMap<String, String>) {
         .and_then(|s| s.parse::<usize>().ok())
         .expect("forward_steps not found");
 
-    let factor_backtest = FactorBacktester::new(local_data_manager.clone(), clock.clone());
+    // step_mode: fixed（默认）/ kline（按interval的kline收盘）/ trade（按成交时间）
+    let step_mode = match args.get("step_mode").map(|s| s.as_str()) {
+        None | Some("fixed") => StepMode::Fixed,
+        Some("kline") => {
+            let interval = args.get("interval").expect("interval not found");
+            StepMode::KlineClose(KlineInterval::from_str(interval).expect("invalid kline interval"))
+        }
+        Some("trade") => StepMode::Trade,
+        Some(step_mode) => panic!("unsupported step_mode: {}", step_mode),
+    };
+    let factor_backtest =
+        FactorBacktester::new(local_data_manager.clone(), clock.clone()).with_step_mode(step_mode);
     let factor_records = factor_backtest
         .run_test(
             calculator.as_ref(),
@@ -194,12 +324,85 @@ async fn factor_backtest_main(conf: &str, args: &HashMap<String, String>) {
     let ic = factor_backtest.calculate_ic(&factor_records);
     let (ic_mean, ic_ir) = factor_backtest.calculate_ic_ir(&factor_records);
     log::info!(
-        "factor backtest finished, records: {}, IC: {:.6}, IC Mean: {:.6}, IC IR: {:.6}",
+        "factor backtest finished, price method: {}, records: {}, IC: {:.6}, IC Mean: {:.6}, IC IR: {:.6}",
+        price_provider.method().as_str(),
         factor_records.len(),
         ic,
         ic_mean,
         ic_ir
     );
+
+    // 指定records_output时导出回测记录，.json后缀写json，其余写csv
+    if let Some(output) = args.get("records_output") {
+        let mut writer = BufWriter::new(File::create(output).expect("create output file failed"));
+        let rows = if output.ends_with(".json") {
+            write_factor_records_json(&mut writer, &factor_records)
+        } else {
+            write_factor_records_csv(&mut writer, &factor_records)
+        }
+        .expect("write factor records failed");
+        writer.flush().expect("flush output file failed");
+        log::info!(
+            "factor records exported, rows: {}, output: {}",
+            rows,
+            output
+        );
+    }
+
+    // 指定train_ms时额外做walk-forward评估，test_ms缺省与train_ms相同，wf_step_ms缺省与test_ms相同
+    if let Some(train_ms) = args.get("train_ms").and_then(|s| s.parse::<u64>().ok()) {
+        let test_ms = args
+            .get("test_ms")
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(train_ms);
+        let wf_step_ms = args
+            .get("wf_step_ms")
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(test_ms);
+        let report = factor_backtest
+            .evaluate_walk_forward(
+                &factor_records,
+                from_ts,
+                to_ts,
+                train_ms,
+                test_ms,
+                wf_step_ms,
+                step_ms * forward_steps as u64,
+            )
+            .expect("walk forward failed");
+        log::info!(
+            "walk forward finished, windows: {}, IC Mean: {:.6}, IC Std: {:.6}, IC IR: {:.6}, positive ratio: {:.2}, sign consistency: {:.2}",
+            report.windows.len(),
+            report.ic_mean,
+            report.ic_std,
+            report.ic_ir,
+            report.positive_ratio,
+            report.sign_consistency
+        );
+    }
+}
+
+fn build_price_provider(
+    price_type: &str,
+    args: &HashMap<String, String>,
+) -> Arc<dyn PriceProvider> {
+    match price_type {
+        "close" => {
+            let interval = args.get("interval").expect("interval not found");
+            let interval = KlineInterval::from_str(interval).expect("invalid kline interval");
+            Arc::new(KlineClosePriceProvider::new(interval))
+        }
+        "trade" => Arc::new(TradePriceProvider::new()),
+        "mid" => Arc::new(MidPriceProvider::new()),
+        "vwap" => {
+            let vwap_window = args
+                .get("vwap_window")
+                .and_then(|s| s.parse::<usize>().ok())
+                .expect("vwap_window not found");
+            Arc::new(VwapPriceProvider::new(vwap_window))
+        }
+        _ => panic!("unsupported price_type"),
+    }
 }
 
 fn parse_args() -> HashMap<String, String> {
@@ -259,6 +462,22 @@ pub async fn main() {
                 .unwrap_or("conf/platform_conf.toml");
             db_migration_main(conf, &args).await;
         }
+        Some("dataset") => {
+            init_log("dataset");
+            let conf = args
+                .get("config")
+                .map(String::as_str)
+                .unwrap_or("conf/platform_conf.toml");
+            dataset_main(conf, &args).await;
+        }
+        Some("csv_export") => {
+            init_log("csv_export");
+            let conf = args
+                .get("config")
+                .map(String::as_str)
+                .unwrap_or("conf/platform_conf.toml");
+            csv_export_main(conf, &args).await;
+        }
         Some("factor_backtest") => {
             init_log("factor_backtest");
             let conf = args
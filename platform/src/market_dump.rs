@@ -1,5 +1,5 @@
 use crate::{
-    data_manager::db::*,
+    data_manager::{db::*, migration::migrate},
     errors::{PlatformError, Result},
     market_provider::MarketProvider,
     models::{GetExchangeInfoRequest, GetKlinesRequest, KlineInterval, MarketType},
@@ -324,16 +324,13 @@ pub async fn market_dump(
     market_providers: HashMap<MarketType, Arc<dyn MarketProvider>>,
     db_path: &str,
 ) -> Result<()> {
-    let db = Arc::new(
-        SQLiteDB::new(db_path).map_err(|e| PlatformError::PlatformError {
-            message: format!("Failed to open database: {}", e),
-        })?,
-    );
+    let db = Arc::new(SQLiteDB::new(db_path).map_err(|e| PlatformError::DbError {
+        context: "Failed to open database".to_string(),
+        source: e,
+    })?);
 
-    // 创建表
-    create_symbol_info_table(db.clone())?;
-    create_kline_table(db.clone())?;
-    create_trade_table(db.clone())?;
+    // 创建表，升级到最新schema
+    migrate(db.clone())?;
 
     log::info!("Starting to fetch symbol info for all markets...");
     for (market_type, provider) in market_providers.iter() {
@@ -372,7 +369,7 @@ pub async fn market_dump(
                         KlineInterval::OneMinute => now - 5 * 365 * 24 * 60 * 60 * 1000, // 最近5年
                         KlineInterval::OneSecond => now - 180 * 24 * 60 * 60 * 1000, // 最近6个月
                         _ => {
-                            return Err(PlatformError::PlatformError {
+                            return Err(PlatformError::ValidationError {
                                 message: format!(
                                     "Unsupported interval {:?} for market dump",
                                     interval
@@ -1,7 +1,7 @@
 use crate::{
-    config::{MarketConfig, Proxy},
+    config::{MarketConfig, Proxy, TradeStreamType},
     errors::{PlatformError, Result},
-    market_provider::MarketProvider,
+    market_provider::{EventReceiver, EventSender, MarketProvider},
     models::{
         DepthData, ExchangeInfo, GetDepthRequest, GetExchangeInfoRequest, GetKlinesRequest,
         GetTicker24hrRequest, GetTradesRequest, KlineData, PriceLevel, Ticker24hr, Trade,
@@ -10,7 +10,7 @@ use crate::{
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use exchange::binance::spot::{
-    market_api::MarketApi,
+    market_api::{MarketApi, RetryPolicy},
     market_stream::MarketStream,
     models::{self},
     requests::{self},
@@ -20,20 +20,42 @@ use rate_limiter::RateLimiter;
 use rust_decimal::Decimal;
 use std::{
     collections::{BTreeMap, HashMap},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::Duration,
 };
-use time::LatencyGuard;
+use time::{noop_metrics, LatencyGuard, Metrics};
 use tokio::sync::{broadcast, RwLock};
 use tokio_util::sync::CancellationToken;
 use ws::WsError;
 
-struct DepthState {
+// 计算checksum的盘口档数
+const DEPTH_CHECKSUM_LEVELS: usize = 25;
+// 深度处理任务异常退出后的重启间隔（毫秒）
+const DEPTH_TASK_RESTART_BACKOFF_MILLI_SECS: u64 = 1000;
+
+// CRC32 (IEEE)
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+pub(crate) struct DepthState {
     symbol: String,
     last_update_id: u64,
     bids: BTreeMap<Decimal, PriceLevel>,
     asks: BTreeMap<Decimal, PriceLevel>,
     timestamp: u64,
+    verify_checksum: bool,
 }
 
 impl DepthState {
@@ -78,9 +100,42 @@ impl DepthState {
             bids,
             asks,
             timestamp,
+            verify_checksum: false,
+        }
+    }
+
+    pub fn set_verify_checksum(&mut self, verify_checksum: bool) {
+        self.verify_checksum = verify_checksum;
+    }
+
+    // 最优买价 >= 最优卖价时返回(best_bid, best_ask)，通常由漏掉的删除档位导致
+    pub fn crossed_prices(&self) -> Option<(Decimal, Decimal)> {
+        match (self.bids.keys().next_back(), self.asks.keys().next()) {
+            (Some(bid), Some(ask)) if bid >= ask => Some((*bid, *ask)),
+            _ => None,
         }
     }
 
+    // 买卖盘前DEPTH_CHECKSUM_LEVELS档交替拼接为 "bid_price:bid_qty:ask_price:ask_qty:..."，
+    // 某一侧不足时跳过该侧，对拼接串计算CRC32并按有符号32位整数返回
+    pub fn checksum(&self) -> i32 {
+        let bids: Vec<&PriceLevel> = self
+            .bids
+            .values()
+            .rev()
+            .take(DEPTH_CHECKSUM_LEVELS)
+            .collect();
+        let asks: Vec<&PriceLevel> = self.asks.values().take(DEPTH_CHECKSUM_LEVELS).collect();
+        let mut fields = Vec::with_capacity(DEPTH_CHECKSUM_LEVELS * 4);
+        for i in 0..DEPTH_CHECKSUM_LEVELS {
+            for level in [bids.get(i), asks.get(i)].into_iter().flatten() {
+                fields.push(level.price.to_string());
+                fields.push(level.quantity.to_string());
+            }
+        }
+        crc32(fields.join(":").as_bytes()) as i32
+    }
+
     pub fn update_depth(&mut self, update: &models::DepthUpdate) -> Result<bool> {
         let _lg = LatencyGuard::new("BinanceSpotDepthState::update");
         if self.symbol != update.symbol {
@@ -116,6 +171,30 @@ impl DepthState {
             self.timestamp = update.timestamp;
             drop(_lg);
 
+            // 校验失败时返回错误，由调用方重新拉取快照，不发布异常盘口
+            if let Some((best_bid, best_ask)) = self.crossed_prices() {
+                return Err(PlatformError::MarketProviderError {
+                    message: format!(
+                        "Depth book crossed for {} at update id {}. Best bid: {}, best ask: {}",
+                        self.symbol, update.last_update_id, best_bid, best_ask
+                    ),
+                });
+            }
+            match update.checksum {
+                Some(expected) if self.verify_checksum && self.checksum() != expected => {
+                    return Err(PlatformError::MarketProviderError {
+                        message: format!(
+                            "Depth checksum mismatch for {} at update id {}. Expected: {}, got: {}",
+                            self.symbol,
+                            update.last_update_id,
+                            expected,
+                            self.checksum()
+                        ),
+                    });
+                }
+                _ => {}
+            }
+
             return Ok(true);
         }
 
@@ -138,6 +217,250 @@ impl DepthState {
     }
 }
 
+// 深度快照来源，测试中可替换为mock
+#[async_trait]
+pub(crate) trait DepthSnapshotFetcher: Send + Sync {
+    async fn fetch_depth(&self, symbol: &str, limit: u32) -> Result<models::DepthData>;
+}
+
+#[async_trait]
+impl DepthSnapshotFetcher for MarketApi {
+    async fn fetch_depth(&self, symbol: &str, limit: u32) -> Result<models::DepthData> {
+        self.get_depth(requests::GetDepthRequest {
+            symbol: symbol.to_string(),
+            limit: Some(limit),
+        })
+        .await
+        .map_err(|e| PlatformError::BinanceError {
+            context: format!("Failed to fetch depth snapshot for {}", symbol),
+            source: e,
+        })
+    }
+}
+
+// 单个symbol深度处理任务的状态
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DepthTaskHealth {
+    pub running: bool,              // 任务是否在运行
+    pub synced: bool,               // 本地盘口是否与交易所同步，false时等待重新拉取快照
+    pub restarts: u64,              // 任务异常退出（panic等）后被重启的次数
+    pub last_error: Option<String>, // 最近一次错误
+    pub last_update_ts: u64,        // 最近发布的盘口时间戳
+}
+
+pub(crate) type DepthHealthMap = Arc<RwLock<HashMap<String, DepthTaskHealth>>>;
+
+// stream推送的行情数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarketDataType {
+    Kline,
+    Trade,
+    Depth,
+    Ticker,
+}
+
+impl MarketDataType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MarketDataType::Kline => "kline",
+            MarketDataType::Trade => "trade",
+            MarketDataType::Depth => "depth",
+            MarketDataType::Ticker => "ticker",
+        }
+    }
+}
+
+// (数据类型, symbol) -> 最近收到stream消息的本地时间（毫秒）
+pub(crate) type LastReceivedMap = Arc<RwLock<HashMap<(MarketDataType, String), u64>>>;
+
+async fn record_received(last_received: &LastReceivedMap, data_type: MarketDataType, symbol: &str) {
+    let now = time::get_current_milli_timestamp();
+    last_received
+        .write()
+        .await
+        .insert((data_type, symbol.to_string()), now);
+}
+
+// 行情provider的连接状态快照
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProviderHealth {
+    pub stream_connected: bool,  // 行情stream当前是否连接
+    pub reconnects: u64,         // 累计重连成功次数
+    pub reconnect_failures: u64, // 累计重连失败次数
+    pub stale_reconnects: u64,   // 其中因stream静默超时触发的重连次数
+    pub last_received_ts: HashMap<(MarketDataType, String), u64>, // 跨stream重连保留
+    pub depth: HashMap<String, DepthTaskHealth>, // symbol -> 深度处理任务状态，synced表示本地盘口已同步
+}
+
+// 单个symbol的深度处理任务：应用增量更新，必要时拉取快照重建本地盘口
+pub(crate) struct DepthTask {
+    pub symbol: String,
+    pub fetcher: Arc<dyn DepthSnapshotFetcher>,
+    pub state_lock: Arc<RwLock<Option<DepthState>>>,
+    pub depth_sender: EventSender<DepthData>,
+    pub verify_depth_checksum: bool,
+    pub snapshot_limit: u32,
+    pub health: DepthHealthMap,
+}
+
+impl DepthTask {
+    async fn update_health(&self, f: impl FnOnce(&mut DepthTaskHealth)) {
+        let mut health = self.health.write().await;
+        f(health.entry(self.symbol.clone()).or_default());
+    }
+
+    // 任务panic或意外退出时重置盘口并在退避后重启，直到shutdown
+    pub fn spawn_supervised(
+        self,
+        receiver: broadcast::Receiver<models::DepthUpdate>,
+        shutdown_token: CancellationToken,
+        restart_backoff: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let task = Arc::new(self);
+        tokio::spawn(async move {
+            loop {
+                task.update_health(|h| h.running = true).await;
+                let handle = tokio::spawn({
+                    let task = task.clone();
+                    let receiver = receiver.resubscribe();
+                    let shutdown_token = shutdown_token.clone();
+                    async move { task.run(receiver, shutdown_token).await }
+                });
+                let exit_reason = match handle.await {
+                    Ok(()) => "exited".to_string(),
+                    Err(e) if e.is_panic() => format!("panicked: {:?}", e.into_panic()),
+                    Err(e) => e.to_string(),
+                };
+                if shutdown_token.is_cancelled() {
+                    break;
+                }
+
+                error!(
+                    "Depth task for symbol {} {}, restart after {:?}",
+                    task.symbol, exit_reason, restart_backoff
+                );
+                *task.state_lock.write().await = None;
+                task.update_health(|h| {
+                    h.synced = false;
+                    h.restarts += 1;
+                    h.last_error = Some(format!("depth task {}", exit_reason));
+                })
+                .await;
+                tokio::select! {
+                    _ = shutdown_token.cancelled() => break,
+                    _ = tokio::time::sleep(restart_backoff) => {}
+                }
+            }
+            task.update_health(|h| h.running = false).await;
+        })
+    }
+
+    async fn run(
+        &self,
+        mut receiver: broadcast::Receiver<models::DepthUpdate>,
+        shutdown_token: CancellationToken,
+    ) {
+        let symbol = &self.symbol;
+        loop {
+            let recv_result = tokio::select! {
+                biased;
+                _ = shutdown_token.cancelled() => break,
+                recv_result = receiver.recv() => recv_result,
+            };
+            let _lg = LatencyGuard::new("BinanceSpotMarketProvider::depth_update_handler");
+            let update = match recv_result {
+                Ok(update) => update,
+                // 丢失增量更新后本地盘口不再可信，下一次更新时重新拉取快照
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    error!(
+                        "Depth update receiver lagged for symbol {}, dropped {} updates, resync",
+                        symbol, n
+                    );
+                    *self.state_lock.write().await = None;
+                    self.update_health(|h| h.synced = false).await;
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    error!("Depth update channel closed for symbol {}", symbol);
+                    break;
+                }
+            };
+            let _lg_1 = LatencyGuard::new(
+                "BinanceSpotMarketProvider::depth_update_handler::process_update",
+            );
+            let mut state_guard = self.state_lock.write().await;
+            if let Some(state) = state_guard.as_mut() {
+                match state.update_depth(&update) {
+                    Ok(updated) => {
+                        if updated {
+                            let depth_data = state.depth();
+                            drop(state_guard);
+                            self.publish(depth_data).await;
+                        }
+                        continue;
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to apply depth update for symbol {}, resync: {}",
+                            symbol, e
+                        );
+                        self.update_health(|h| {
+                            h.synced = false;
+                            h.last_error = Some(e.to_string());
+                        })
+                        .await;
+                    }
+                }
+            }
+            drop(state_guard);
+            drop(_lg_1);
+
+            let _lg_2 = LatencyGuard::new(
+                "BinanceSpotMarketProvider::depth_update_handler::fetch_initial_and_update_depth",
+            );
+            let depth_state = match self.fetcher.fetch_depth(symbol, self.snapshot_limit).await {
+                Ok(depth) => {
+                    let mut depth_state = DepthState::from_depth(&depth);
+                    depth_state.set_verify_checksum(self.verify_depth_checksum);
+                    match depth_state.update_depth(&update) {
+                        // 快照本身交叉时不发布，等待下一次更新重新拉取
+                        Ok(_) if depth_state.crossed_prices().is_some() => {
+                            error!("Fetched depth snapshot is crossed for symbol {}", symbol);
+                            continue;
+                        }
+                        Ok(_) => depth_state,
+                        Err(e) => {
+                            error!("Failed to apply depth update after fetching initial depth for symbol {}: {}", symbol, e);
+                            continue;
+                        }
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to fetch initial depth for symbol {}: {}", symbol, e);
+                    self.update_health(|h| h.last_error = Some(e.to_string()))
+                        .await;
+                    continue;
+                }
+            };
+            let depth = depth_state.depth();
+            *self.state_lock.write().await = Some(depth_state);
+            drop(_lg_2);
+
+            self.publish(depth).await;
+        }
+    }
+
+    async fn publish(&self, depth: DepthData) {
+        let timestamp = depth.timestamp;
+        self.depth_sender.send(depth).await;
+        self.update_health(|h| {
+            h.synced = true;
+            h.last_update_ts = timestamp;
+        })
+        .await;
+    }
+}
+
 pub struct BinanceSpotMarketProvider {
     config: Arc<MarketConfig>,
     proxy: Option<Proxy>,
@@ -147,16 +470,19 @@ pub struct BinanceSpotMarketProvider {
     market_api: Option<Arc<MarketApi>>,
     market_stream: Option<Arc<ArcSwap<MarketStream>>>, // stream当前订阅后初始化，不支持后续订阅或取消订阅
 
-    kline_sender: broadcast::Sender<KlineData>,
-    kline_receiver: broadcast::Receiver<KlineData>,
-    trade_sender: broadcast::Sender<Trade>,
-    trade_receiver: broadcast::Receiver<Trade>,
-    depth_sender: broadcast::Sender<DepthData>,
-    depth_receiver: broadcast::Receiver<DepthData>,
-    ticker_sender: broadcast::Sender<Ticker24hr>,
-    ticker_receiver: broadcast::Receiver<Ticker24hr>,
+    kline_sender: EventSender<KlineData>,
+    trade_sender: EventSender<Trade>,
+    depth_sender: EventSender<DepthData>,
+    ticker_sender: EventSender<Ticker24hr>,
+    depth_health: DepthHealthMap, // symbol -> 深度处理任务状态，跨stream重连保留
+    last_received: LastReceivedMap,
+    reconnects: Arc<AtomicU64>,
+    reconnect_failures: Arc<AtomicU64>,
+    stale_reconnects: Arc<AtomicU64>,
+    stream_started_ts: Arc<AtomicU64>, // 当前stream建立的时间，静默检测从此时开始计时
 
     shutdown_token: CancellationToken,
+    metrics: Arc<dyn Metrics>,
 }
 
 impl BinanceSpotMarketProvider {
@@ -164,15 +490,22 @@ impl BinanceSpotMarketProvider {
         let api_rate_limiters = config.api_rate_limiters.clone();
         let stream_rate_limiters = config.stream_rate_limiters.clone();
 
-        let kline_chan_cap = config.kline_event_channel_capacity;
-        let trade_chan_cap = config.trade_event_channel_capacity;
-        let depth_chan_cap = config.depth_event_channel_capacity;
-        let ticker_chan_cap = config.ticker_event_channel_capacity;
-
-        let (kline_sender, kline_receiver) = broadcast::channel(kline_chan_cap);
-        let (trade_sender, trade_receiver) = broadcast::channel(trade_chan_cap);
-        let (depth_sender, depth_receiver) = broadcast::channel(depth_chan_cap);
-        let (ticker_sender, ticker_receiver) = broadcast::channel(ticker_chan_cap);
+        let kline_sender = EventSender::new(
+            config.kline_event_channel_capacity,
+            config.event_channel_overflow_policy("kline"),
+        );
+        let trade_sender = EventSender::new(
+            config.trade_event_channel_capacity,
+            config.event_channel_overflow_policy("trade"),
+        );
+        let depth_sender = EventSender::new(
+            config.depth_event_channel_capacity,
+            config.event_channel_overflow_policy("depth"),
+        );
+        let ticker_sender = EventSender::new(
+            config.ticker_event_channel_capacity,
+            config.event_channel_overflow_policy("ticker"),
+        );
 
         Ok(Self {
             config,
@@ -182,16 +515,123 @@ impl BinanceSpotMarketProvider {
             market_api: None,
             market_stream: None,
             kline_sender,
-            kline_receiver,
             trade_sender,
-            trade_receiver,
             depth_sender,
-            depth_receiver,
             ticker_sender,
-            ticker_receiver,
+            depth_health: Arc::new(RwLock::new(HashMap::new())),
+            last_received: Arc::new(RwLock::new(HashMap::new())),
+            reconnects: Arc::new(AtomicU64::new(0)),
+            reconnect_failures: Arc::new(AtomicU64::new(0)),
+            stale_reconnects: Arc::new(AtomicU64::new(0)),
+            stream_started_ts: Arc::new(AtomicU64::new(0)),
             shutdown_token: CancellationToken::new(),
+            metrics: noop_metrics(),
         })
     }
+
+    // 上报 market_provider.stream_reconnect/stream_reconnect_failed/stream_stale 计数
+    pub fn set_metrics(&mut self, metrics: Arc<dyn Metrics>) {
+        self.metrics = metrics;
+    }
+
+    // 各symbol深度处理任务的状态
+    pub async fn depth_health(&self) -> HashMap<String, DepthTaskHealth> {
+        self.depth_health.read().await.clone()
+    }
+
+    pub async fn health(&self) -> ProviderHealth {
+        let stream_connected = self
+            .market_stream
+            .as_ref()
+            .and_then(|stream| stream.load().get_ws_shutdown_token())
+            .is_some_and(|token| !token.is_cancelled());
+        ProviderHealth {
+            stream_connected,
+            reconnects: self.reconnects.load(Ordering::SeqCst),
+            reconnect_failures: self.reconnect_failures.load(Ordering::SeqCst),
+            stale_reconnects: self.stale_reconnects.load(Ordering::SeqCst),
+            last_received_ts: self.last_received.read().await.clone(),
+            depth: self.depth_health().await,
+        }
+    }
+
+    // 静默检测：订阅的(symbol, 数据类型)超时未收到消息时断开当前stream，由重连任务重建并重新订阅
+    // 未收到过消息的按stream建立时间计时；所有超时均为0时不启动
+    fn spawn_stale_watchdog(&self) {
+        let mut data_types = vec![
+            MarketDataType::Trade,
+            MarketDataType::Depth,
+            MarketDataType::Ticker,
+        ];
+        if !self.config.subscribed_kline_intervals.is_empty() {
+            data_types.push(MarketDataType::Kline);
+        }
+        let watched: Vec<((MarketDataType, String), u64)> = self
+            .config
+            .subscribed_symbols
+            .iter()
+            .flat_map(|symbol| {
+                data_types.iter().map(move |data_type| {
+                    let timeout = self.config.stream_stale_timeout(symbol, data_type.as_str());
+                    ((*data_type, symbol.clone()), timeout)
+                })
+            })
+            .filter(|(_, timeout)| *timeout > 0)
+            .collect();
+        let Some(min_timeout) = watched.iter().map(|(_, timeout)| *timeout).min() else {
+            return;
+        };
+        let check_interval = Duration::from_millis((min_timeout / 2).max(10));
+
+        let shutdown_token = self.shutdown_token.clone();
+        let market_stream = self.market_stream.as_ref().unwrap().clone();
+        let last_received = self.last_received.clone();
+        let stream_started_ts = self.stream_started_ts.clone();
+        let stale_reconnects = self.stale_reconnects.clone();
+        let metrics = self.metrics.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown_token.cancelled() => break,
+                    _ = tokio::time::sleep(check_interval) => {}
+                }
+                // 已断开的stream等待重连任务处理
+                let Some(stream_shutdown_token) = market_stream.load().get_ws_shutdown_token()
+                else {
+                    continue;
+                };
+                if stream_shutdown_token.is_cancelled() {
+                    continue;
+                }
+                let now = time::get_current_milli_timestamp();
+                let started_ts = stream_started_ts.load(Ordering::SeqCst);
+                let stale: Vec<&(MarketDataType, String)> = {
+                    let last_received = last_received.read().await;
+                    watched
+                        .iter()
+                        .filter(|(key, timeout)| {
+                            let last_ts = last_received.get(key).copied().unwrap_or(0);
+                            now.saturating_sub(last_ts.max(started_ts)) > *timeout
+                        })
+                        .map(|(key, _)| key)
+                        .collect()
+                };
+                if stale.is_empty() {
+                    continue;
+                }
+                for (data_type, symbol) in stale.iter() {
+                    error!(
+                        "No {} message of symbol {} received within stale timeout, force stream reconnect",
+                        data_type.as_str(),
+                        symbol
+                    );
+                }
+                stale_reconnects.fetch_add(1, Ordering::SeqCst);
+                metrics.incr("market_provider.stream_stale", 1);
+                stream_shutdown_token.cancel();
+            }
+        });
+    }
 }
 
 fn create_market_api(
@@ -200,38 +640,86 @@ fn create_market_api(
     rate_limiters: Option<Arc<Vec<RateLimiter>>>,
 ) -> Result<MarketApi> {
     let base_url: String = config.api_base_url.clone();
-    let proxy_url: Option<String> = proxy.as_ref().map(|p| p.url.clone());
+    let proxy_url: Option<String> = proxy.as_ref().map(|p| p.url_with_auth()).transpose()?;
     let timeout_milli_secs: u64 = config.api_timeout_milli_secs;
 
     let mut market_api = MarketApi::new(base_url, proxy_url, rate_limiters, timeout_milli_secs);
-    market_api
-        .init()
-        .map_err(|e| PlatformError::MarketProviderError {
-            message: format!("Failed to init market_api: {}", e),
-        })?;
+    market_api.set_api_key(&config.api_key);
+    for (endpoint, timeout_milli_secs) in config.api_endpoint_timeout_milli_secs.iter() {
+        market_api.set_endpoint_timeout(endpoint, *timeout_milli_secs);
+    }
+    if config.api_retry_times > 0 {
+        market_api.set_retry_policy(Some(RetryPolicy {
+            max_retries: config.api_retry_times,
+            backoff_milli_secs: config.api_retry_backoff_milli_secs,
+        }));
+    }
+    market_api.init().map_err(|e| PlatformError::BinanceError {
+        context: "Failed to init market_api".to_string(),
+        source: e,
+    })?;
 
     Ok(market_api)
 }
 
+// historicalTrades只能按id查询，按时间查询时先用aggTrades定位起始时刻对应的逐笔成交id
+pub(crate) async fn get_raw_trades(api: &MarketApi, req: GetTradesRequest) -> Result<Vec<Trade>> {
+    let map_err = |e| PlatformError::BinanceError {
+        context: "Failed to get historical trades".to_string(),
+        source: e,
+    };
+    let (start_time, end_time) = (req.start_time, req.end_time);
+    let mut ex_req: requests::GetHistoricalTradesRequest = req.into();
+    if let (None, Some(start_time)) = (ex_req.from_id, start_time) {
+        let anchor = api
+            .get_agg_trades(requests::GetAggTradesRequest {
+                symbol: ex_req.symbol.clone(),
+                from_id: None,
+                start_time: Some(start_time),
+                end_time: None,
+                limit: Some(1),
+            })
+            .await
+            .map_err(map_err)?;
+        match anchor.first() {
+            None => return Ok(vec![]),
+            Some(agg_trade) => ex_req.from_id = Some(agg_trade.first_trade_id),
+        }
+    }
+
+    let trades = api.get_historical_trades(ex_req).await.map_err(map_err)?;
+    Ok(trades
+        .into_iter()
+        .filter(|t| start_time.is_none_or(|ts| t.timestamp >= ts))
+        .filter(|t| end_time.is_none_or(|ts| t.timestamp <= ts))
+        .map(|t| t.into())
+        .collect())
+}
+
 async fn create_market_stream(
     market_api: Arc<MarketApi>,
     config: Arc<MarketConfig>,
     proxy: Option<Proxy>,
     rate_limiters: Option<Arc<Vec<RateLimiter>>>,
-    kline_sender: broadcast::Sender<KlineData>,
-    trade_sender: broadcast::Sender<Trade>,
-    depth_sender: broadcast::Sender<DepthData>,
-    ticker_sender: broadcast::Sender<Ticker24hr>,
+    kline_sender: EventSender<KlineData>,
+    trade_sender: EventSender<Trade>,
+    depth_sender: EventSender<DepthData>,
+    ticker_sender: EventSender<Ticker24hr>,
+    depth_health: DepthHealthMap,
+    last_received: LastReceivedMap,
 ) -> Result<MarketStream> {
     let stream_base_url: String = config.stream_base_url.clone();
-    let proxy_url: Option<String> = proxy.as_ref().map(|p| p.url.clone());
+    let proxy_url: Option<String> = proxy.as_ref().map(|p| p.url_with_auth()).transpose()?;
 
     let mut market_stream = MarketStream::new(stream_base_url, proxy_url, rate_limiters);
 
     let subscribed_symbols = config.subscribed_symbols.clone();
     let subscribed_kline_intervals = config.subscribed_kline_intervals.clone();
     for symbol in subscribed_symbols.iter() {
-        market_stream.subscribe_agg_trade(symbol);
+        match config.trade_stream {
+            TradeStreamType::Agg => market_stream.subscribe_agg_trade(symbol),
+            TradeStreamType::Raw => market_stream.subscribe_trade(symbol),
+        }
         market_stream.subscribe_depth_update(symbol);
         market_stream.subscribe_ticker(symbol);
         for interval in subscribed_kline_intervals.iter() {
@@ -239,36 +727,44 @@ async fn create_market_stream(
         }
     }
 
+    let raw_trade_sender = trade_sender.clone();
+    let last_received_clone = last_received.clone();
     market_stream.register_agg_trade_callback(move |trade| {
         let trade_sender = trade_sender.clone();
+        let last_received = last_received_clone.clone();
         Box::pin(async move {
-            let _ = trade_sender
-                .send(trade.into())
-                .map_err(|e| WsError::HandleError {
-                    message: format!("Failed to send trade event: {}", e),
-                })?;
+            record_received(&last_received, MarketDataType::Trade, &trade.symbol).await;
+            trade_sender.send(trade.into()).await;
+            Ok(())
+        })
+    });
+    let last_received_clone = last_received.clone();
+    market_stream.register_trade_callback(move |trade| {
+        let trade_sender = raw_trade_sender.clone();
+        let last_received = last_received_clone.clone();
+        Box::pin(async move {
+            record_received(&last_received, MarketDataType::Trade, &trade.symbol).await;
+            trade_sender.send(trade.into()).await;
             Ok(())
         })
     });
+    let last_received_clone = last_received.clone();
     market_stream.register_kline_callback(move |kline| {
         let kline_sender = kline_sender.clone();
+        let last_received = last_received_clone.clone();
         Box::pin(async move {
-            let _ = kline_sender
-                .send(kline.into())
-                .map_err(|e| WsError::HandleError {
-                    message: format!("Failed to send kline event: {}", e),
-                })?;
+            record_received(&last_received, MarketDataType::Kline, &kline.symbol).await;
+            kline_sender.send(kline.into()).await;
             Ok(())
         })
     });
+    let last_received_clone = last_received.clone();
     market_stream.register_ticker_callback(move |ticker| {
         let ticker_sender = ticker_sender.clone();
+        let last_received = last_received_clone.clone();
         Box::pin(async move {
-            let _ = ticker_sender
-                .send(ticker.into())
-                .map_err(|e| WsError::HandleError {
-                    message: format!("Failed to send ticker event: {}", e),
-                })?;
+            record_received(&last_received, MarketDataType::Ticker, &ticker.symbol).await;
+            ticker_sender.send(ticker.into()).await;
             Ok(())
         })
     });
@@ -276,6 +772,7 @@ async fn create_market_stream(
     // websocket都区分symbol发送到独立的channel
     // 每一个独立的channel单独运行在一个协程中处理并发送到depth chan
     let depth_cache_chan_cap = config.depth_cache_channel_capacity;
+    let verify_depth_checksum = config.verify_depth_checksum;
     let depth_updates = Arc::new(
         subscribed_symbols
             .iter()
@@ -292,7 +789,9 @@ async fn create_market_stream(
     let depth_updates_clone = depth_updates.clone();
     market_stream.register_depth_update_callback(move |update| {
         let depth_updates = depth_updates_clone.clone();
+        let last_received = last_received.clone();
         Box::pin(async move {
+            record_received(&last_received, MarketDataType::Depth, &update.symbol).await;
             if !depth_updates.contains_key(&update.symbol) {
                 return Err(WsError::HandleError {
                     message: format!("Failed to find depth update symbol: {}", &update.symbol),
@@ -309,94 +808,30 @@ async fn create_market_stream(
     });
 
     let init_latency_guard = time::LatencyGuard::new("BinanceSpotMarketStream::init");
-    let shutdown_token =
-        market_stream
-            .init()
-            .await
-            .map_err(|e| PlatformError::MarketProviderError {
-                message: format!("Failed to init market_stream: {}", e),
-            })?;
+    let shutdown_token = market_stream
+        .init()
+        .await
+        .map_err(|e| PlatformError::BinanceError {
+            context: "Failed to init market_stream".to_string(),
+            source: e,
+        })?;
     drop(init_latency_guard);
 
     for (symbol, (state_lock, _, receiver)) in depth_updates.iter() {
-        let market_api = market_api.clone();
-        let depth_sender = depth_sender.clone();
-        let shutdown_token = shutdown_token.clone();
-        let state_lock = state_lock.clone();
-        let mut receiver = receiver.resubscribe();
-        let symbol = symbol.to_string();
-        tokio::spawn(async move {
-            loop {
-                tokio::select! {
-                    biased;
-                    _ = shutdown_token.cancelled() => {
-                        break;
-                    }
-                    recv_result = receiver.recv() => {
-                        let _lg = LatencyGuard::new("BinanceSpotMarketProvider::depth_update_handler");
-                        let update = match recv_result {
-                            Ok(update) => update,
-                            Err(e) => {
-                                error!("Depth update receive error for symbol {}: {}", symbol, e);
-                                continue;
-                            }
-                        };
-                        let _lg_1 = LatencyGuard::new("BinanceSpotMarketProvider::depth_update_handler::process_update");
-                        let mut state_guard = state_lock.write().await;
-                        if state_guard.is_some() {
-                            if let Some(updated) = state_guard.as_mut().unwrap().update_depth(&update).ok() {
-                                if updated {
-                                    let depth_data = state_guard.as_ref().unwrap().depth();
-                                    let _ = depth_sender.send(depth_data).map_err(|e| {
-                                        error!("send depth data error for symbol {}: {}", symbol, e);
-                                    });
-                                }
-                                continue;
-                            } else {
-                                error!("Failed to apply depth update for symbol {}", symbol);
-                            }
-                        }
-                        drop(state_guard);
-                        drop(_lg_1);
-
-                        let _lg_2 = LatencyGuard::new("BinanceSpotMarketProvider::depth_update_handler::fetch_initial_and_update_depth");
-                        let depth_data = market_api
-                            .get_depth(requests::GetDepthRequest {
-                                symbol: symbol.clone(),
-                                limit: Some(5000),
-                            })
-                            .await;
-
-                        let depth_state = match depth_data {
-                            Ok(depth) => {
-                                let mut depth_state = DepthState::from_depth(&depth);
-                                match depth_state.update_depth(&update) {
-                                    Ok(_) => depth_state,
-                                    Err(e) => {
-                                        error!("Failed to apply depth update after fetching initial depth for symbol {}: {}", symbol, e);
-                                        continue;
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                error!("Failed to fetch initial depth for symbol {}: {}", symbol, e);
-                                continue;
-                            }
-                        };
-                        let depth = depth_state.depth();
-
-                        let mut state_guard = state_lock.write().await;
-                        *state_guard = Some(depth_state);
-                        drop(state_guard);
-                        drop(_lg_2);
-
-                        let _ = depth_sender.send(depth).map_err(|e| {
-                            error!("send depth data error for symbol {}: {}", symbol, e);
-                        });
-                    }
-                }
-            }
-        });
+        let task = DepthTask {
+            symbol: symbol.to_string(),
+            fetcher: market_api.clone(),
+            state_lock: state_lock.clone(),
+            depth_sender: depth_sender.clone(),
+            verify_depth_checksum,
+            snapshot_limit: config.depth_snapshot_limit,
+            health: depth_health.clone(),
+        };
+        task.spawn_supervised(
+            receiver.resubscribe(),
+            shutdown_token.clone(),
+            Duration::from_millis(DEPTH_TASK_RESTART_BACKOFF_MILLI_SECS),
+        );
     }
 
     Ok(market_stream)
@@ -420,11 +855,16 @@ impl MarketProvider for BinanceSpotMarketProvider {
             self.trade_sender.clone(),
             self.depth_sender.clone(),
             self.ticker_sender.clone(),
+            self.depth_health.clone(),
+            self.last_received.clone(),
         )
         .await?;
 
         self.market_api = Some(market_api);
         self.market_stream = Some(Arc::new(ArcSwap::new(Arc::new(market_stream))));
+        self.stream_started_ts
+            .store(time::get_current_milli_timestamp(), Ordering::SeqCst);
+        self.spawn_stale_watchdog();
 
         // stream断连自动重连
         let shutdown_token = self.shutdown_token.clone();
@@ -437,6 +877,12 @@ impl MarketProvider for BinanceSpotMarketProvider {
         let trade_sender = self.trade_sender.clone();
         let depth_sender = self.depth_sender.clone();
         let ticker_sender = self.ticker_sender.clone();
+        let depth_health = self.depth_health.clone();
+        let last_received = self.last_received.clone();
+        let reconnects = self.reconnects.clone();
+        let reconnect_failures = self.reconnect_failures.clone();
+        let stream_started_ts = self.stream_started_ts.clone();
+        let metrics = self.metrics.clone();
         tokio::spawn(async move {
             let retry_interval = config.stream_reconnect_interval_milli_secs;
             let mut latest_retry_ts = 0u64;
@@ -462,12 +908,19 @@ impl MarketProvider for BinanceSpotMarketProvider {
                             trade_sender.clone(),
                             depth_sender.clone(),
                             ticker_sender.clone(),
+                            depth_health.clone(),
+                            last_received.clone(),
                         ).await;
                         match new_stream {
                             Ok(stream) => {
                                 market_stream.store(Arc::new(stream));
+                                stream_started_ts.store(time::get_current_milli_timestamp(), Ordering::SeqCst);
+                                reconnects.fetch_add(1, Ordering::SeqCst);
+                                metrics.incr("market_provider.stream_reconnect", 1);
                             },
                             Err(e) => {
+                                reconnect_failures.fetch_add(1, Ordering::SeqCst);
+                                metrics.incr("market_provider.stream_reconnect_failed", 1);
                                 error!("Failed to recreate market stream: {}", e);
                             }
                         }
@@ -487,12 +940,13 @@ impl MarketProvider for BinanceSpotMarketProvider {
                 message: "Market API not initialized".to_string(),
             })?;
 
-        let klines =
-            api.get_klines(req.into())
-                .await
-                .map_err(|e| PlatformError::MarketProviderError {
-                    message: format!("Failed to get klines: {}", e),
-                })?;
+        let klines = api
+            .get_klines(req.into())
+            .await
+            .map_err(|e| PlatformError::BinanceError {
+                context: "Failed to get klines".to_string(),
+                source: e,
+            })?;
 
         Ok(klines.into_iter().map(|k| k.into()).collect())
     }
@@ -505,16 +959,21 @@ impl MarketProvider for BinanceSpotMarketProvider {
                 message: "Market API not initialized".to_string(),
             })?;
 
-        let trades = api.get_agg_trades(req.into()).await.map_err(|e| {
-            PlatformError::MarketProviderError {
-                message: format!("Failed to get trades: {}", e),
+        let map_err = |e| PlatformError::BinanceError {
+            context: "Failed to get trades".to_string(),
+            source: e,
+        };
+        // 与订阅的成交流保持相同的id序列，保证缓存按seq_id衔接
+        match self.config.trade_stream {
+            TradeStreamType::Agg => {
+                let trades = api.get_agg_trades(req.into()).await.map_err(map_err)?;
+                Ok(trades.into_iter().map(|t| t.into()).collect())
             }
-        })?;
-
-        Ok(trades.into_iter().map(|t| t.into()).collect())
+            TradeStreamType::Raw => get_raw_trades(api, req).await,
+        }
     }
 
-    async fn get_depth(&self, req: GetDepthRequest) -> Result<DepthData> {
+    async fn get_depth(&self, req: GetDepthRequest) -> Result<Option<DepthData>> {
         let api = self
             .market_api
             .as_ref()
@@ -522,14 +981,19 @@ impl MarketProvider for BinanceSpotMarketProvider {
                 message: "Market API not initialized".to_string(),
             })?;
 
-        let depth =
-            api.get_depth(req.into())
-                .await
-                .map_err(|e| PlatformError::MarketProviderError {
-                    message: format!("Failed to get depth: {}", e),
-                })?;
+        let depth = api
+            .get_depth(req.into())
+            .await
+            .map_err(|e| PlatformError::BinanceError {
+                context: "Failed to get depth".to_string(),
+                source: e,
+            })?;
 
-        Ok(depth.into())
+        // 买卖盘均为空视为尚无盘口
+        if depth.bids.is_empty() && depth.asks.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(depth.into()))
     }
 
     async fn get_ticker_24hr(&self, req: GetTicker24hrRequest) -> Result<Vec<Ticker24hr>> {
@@ -540,11 +1004,13 @@ impl MarketProvider for BinanceSpotMarketProvider {
                 message: "Market API not initialized".to_string(),
             })?;
 
-        let ticker = api.get_ticker_24hr(req.into()).await.map_err(|e| {
-            PlatformError::MarketProviderError {
-                message: format!("Failed to get ticker: {}", e),
-            }
-        })?;
+        let ticker =
+            api.get_ticker_24hr(req.into())
+                .await
+                .map_err(|e| PlatformError::BinanceError {
+                    context: "Failed to get ticker".to_string(),
+                    source: e,
+                })?;
 
         Ok(ticker.into_iter().map(|t| t.into()).collect())
     }
@@ -557,29 +1023,31 @@ impl MarketProvider for BinanceSpotMarketProvider {
                 message: "Market API not initialized".to_string(),
             })?;
 
-        let info = api.get_exchange_info(req.into()).await.map_err(|e| {
-            PlatformError::MarketProviderError {
-                message: format!("Failed to get exchange info: {}", e),
-            }
-        })?;
+        let info =
+            api.get_exchange_info(req.into())
+                .await
+                .map_err(|e| PlatformError::BinanceError {
+                    context: "Failed to get exchange info".to_string(),
+                    source: e,
+                })?;
 
         Ok(info.into())
     }
 
-    fn subscribe_kline(&self) -> broadcast::Receiver<KlineData> {
-        self.kline_receiver.resubscribe()
+    fn subscribe_kline(&self) -> EventReceiver<KlineData> {
+        self.kline_sender.subscribe()
     }
 
-    fn subscribe_trade(&self) -> broadcast::Receiver<Trade> {
-        self.trade_receiver.resubscribe()
+    fn subscribe_trade(&self) -> EventReceiver<Trade> {
+        self.trade_sender.subscribe()
     }
 
-    fn subscribe_depth(&self) -> broadcast::Receiver<DepthData> {
-        self.depth_receiver.resubscribe()
+    fn subscribe_depth(&self) -> EventReceiver<DepthData> {
+        self.depth_sender.subscribe()
     }
 
-    fn subscribe_ticker(&self) -> broadcast::Receiver<Ticker24hr> {
-        self.ticker_receiver.resubscribe()
+    fn subscribe_ticker(&self) -> EventReceiver<Ticker24hr> {
+        self.ticker_sender.subscribe()
     }
 }
 
@@ -1,17 +1,39 @@
 use crate::{
-    config::{Config, PlatformConfig},
-    market_provider::{binance_spot_market_provider::BinanceSpotMarketProvider, MarketProvider},
+    config::{ChannelOverflowPolicy, Config, PlatformConfig},
+    market_provider::{
+        binance_spot_market_provider::{
+            get_raw_trades, BinanceSpotMarketProvider, DepthSnapshotFetcher, DepthState, DepthTask,
+            MarketDataType,
+        },
+        EventSender, MarketProvider,
+    },
     models::{
         DepthData, GetDepthRequest, GetExchangeInfoRequest, GetKlinesRequest, GetTicker24hrRequest,
         GetTradesRequest, KlineInterval, MarketType,
     },
 };
+use async_trait::async_trait;
 use env_logger::Env;
+use exchange::binance::{
+    errors::BinanceError,
+    spot::{market_api::MarketApi, models as ex_models},
+};
+use futures_util::{SinkExt, StreamExt};
 use json::dump;
 use log::info;
-use std::{sync::Arc, time::Duration};
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tempfile::NamedTempFile;
+use time::InMemoryMetrics;
 use tokio::{sync::Mutex, time::sleep};
+use tokio_tungstenite::tungstenite::Message;
+use ws::WsError;
 
 #[tokio::test]
 async fn test_binance_spot_market_provider() {
@@ -100,7 +122,7 @@ async fn test_binance_spot_market_provider() {
         symbol: "BTCUSDT".to_string(),
         limit: Some(100),
     };
-    let depth = provider.get_depth(depth_req).await.unwrap();
+    let depth = provider.get_depth(depth_req).await.unwrap().unwrap();
     assert!(!depth.bids.is_empty());
     assert!(!depth.asks.is_empty());
     dump(&depth, "test_depth.json").unwrap();
@@ -192,3 +214,727 @@ async fn test_binance_spot_market_provider() {
     assert!(!tickers_collected.is_empty());
     dump(&*tickers_collected, "collected_tickers.json").unwrap();
 }
+
+#[tokio::test]
+async fn test_market_stream_error_source_chain() {
+    // 占用后释放端口，保证连接被拒绝
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let config_content = format!(
+        r#"
+markets = ["binance_spot"]
+db_path = "test_db_path"
+
+[binance_spot]
+api_base_url = "http://127.0.0.1:{port}"
+stream_base_url = "ws://127.0.0.1:{port}/stream"
+stream_api_base_url = "ws://127.0.0.1:{port}/ws-api/v3"
+api_key = ""
+secret_key = ""
+subscribed_symbols = ["BTCUSDT"]
+subscribed_kline_intervals = ["1m"]
+"#
+    );
+    let mut config_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config_file, config_content.as_bytes()).unwrap();
+    let config = Config::from_toml(config_file.path().to_str().unwrap()).unwrap();
+    let platform_config = PlatformConfig::from_config(config).unwrap();
+
+    let mut provider = BinanceSpotMarketProvider::new(
+        platform_config.configs[&MarketType::BinanceSpot].clone(),
+        None,
+    )
+    .unwrap();
+    let err = provider.init().await.err().unwrap();
+    assert_eq!(err.code_name(), "BINANCE_ERROR");
+
+    let source = err.source().unwrap();
+    assert!(matches!(
+        source.downcast_ref::<BinanceError>(),
+        Some(BinanceError::WsError(_))
+    ));
+    let ws_err = source.source().unwrap().downcast_ref::<WsError>().unwrap();
+    assert!(matches!(ws_err, WsError::Connection { .. }));
+}
+
+// 模拟行情ws：回复订阅请求，第一个连接随后断开，之后的连接保持
+async fn start_mock_market_stream() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let mut conn_count = 0;
+        while let Ok((stream, _)) = listener.accept().await {
+            conn_count += 1;
+            let close_after_subscribe = conn_count == 1;
+            tokio::spawn(async move {
+                let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+                while let Some(Ok(msg)) = ws_stream.next().await {
+                    if let Message::Text(text) = msg {
+                        let req: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        let resp = serde_json::json!({"result": null, "id": req["id"]});
+                        ws_stream
+                            .send(Message::Text(resp.to_string().into()))
+                            .await
+                            .unwrap();
+                        if close_after_subscribe {
+                            sleep(Duration::from_millis(100)).await;
+                            let _ = ws_stream.close(None).await;
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    port
+}
+
+#[tokio::test]
+async fn test_market_provider_reconnect_metrics() {
+    let port = start_mock_market_stream().await;
+    let config_content = format!(
+        r#"
+markets = ["binance_spot"]
+db_path = "test_db_path"
+
+[binance_spot]
+api_base_url = "http://127.0.0.1:{port}"
+stream_base_url = "ws://127.0.0.1:{port}/stream"
+stream_api_base_url = "ws://127.0.0.1:{port}/ws-api/v3"
+api_key = ""
+secret_key = ""
+subscribed_symbols = ["BTCUSDT"]
+subscribed_kline_intervals = ["1m"]
+stream_reconnect_interval_milli_secs = 100
+"#
+    );
+    let mut config_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config_file, config_content.as_bytes()).unwrap();
+    let config = Config::from_toml(config_file.path().to_str().unwrap()).unwrap();
+    let platform_config = PlatformConfig::from_config(config).unwrap();
+
+    let metrics = Arc::new(InMemoryMetrics::new());
+    let mut provider = BinanceSpotMarketProvider::new(
+        platform_config.configs[&MarketType::BinanceSpot].clone(),
+        None,
+    )
+    .unwrap();
+    provider.set_metrics(metrics.clone());
+    provider.init().await.unwrap();
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while metrics.counter("market_provider.stream_reconnect") == 0 {
+            sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("expect stream reconnect");
+    assert_eq!(
+        metrics.counter("market_provider.stream_reconnect_failed"),
+        0
+    );
+}
+
+// 模拟行情ws：第一个连接回复订阅后推送一笔归集成交与一次深度增量，随后断开；
+// 之后的连接延迟500ms完成握手，便于观察断连状态
+async fn start_mock_health_stream() -> u16 {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        let mut conn_count = 0;
+        while let Ok((stream, _)) = listener.accept().await {
+            conn_count += 1;
+            let first_conn = conn_count == 1;
+            tokio::spawn(async move {
+                if !first_conn {
+                    sleep(Duration::from_millis(500)).await;
+                }
+                let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+                while let Some(Ok(msg)) = ws_stream.next().await {
+                    if let Message::Text(text) = msg {
+                        let req: serde_json::Value = serde_json::from_str(&text).unwrap();
+                        let resp = serde_json::json!({"result": null, "id": req["id"]});
+                        ws_stream
+                            .send(Message::Text(resp.to_string().into()))
+                            .await
+                            .unwrap();
+                        if first_conn {
+                            let events = [
+                                r#"{"stream":"btcusdt@aggTrade","data":{"e":"aggTrade","E":1000,"s":"BTCUSDT","a":1,"p":"100","q":"1","f":1,"l":1,"T":1000,"m":true}}"#,
+                                r#"{"stream":"btcusdt@depth@100ms","data":{"e":"depthUpdate","E":1000,"s":"BTCUSDT","U":11,"u":11,"b":[["100","2"]],"a":[]}}"#,
+                            ];
+                            // 等待深度处理任务启动
+                            sleep(Duration::from_millis(100)).await;
+                            for event in events {
+                                ws_stream.send(Message::Text(event.into())).await.unwrap();
+                            }
+                            sleep(Duration::from_millis(300)).await;
+                            let _ = ws_stream.close(None).await;
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+    });
+    port
+}
+
+// 模拟行情ws：每个连接回复订阅后持续推送BTCUSDT成交（间隔50ms），ETHUSDT只推送一笔后静默，返回(端口, 连接数)
+async fn start_mock_quiet_stream() -> (u16, Arc<AtomicU32>) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let conn_count = Arc::new(AtomicU32::new(0));
+    let server_conn_count = conn_count.clone();
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            server_conn_count.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(async move {
+                let mut ws_stream = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let Some(Ok(Message::Text(text))) = ws_stream.next().await else {
+                    return;
+                };
+                let req: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let resp = serde_json::json!({"result": null, "id": req["id"]});
+                if ws_stream
+                    .send(Message::Text(resp.to_string().into()))
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+                let agg_trade = |symbol: &str, id: u64| {
+                    format!(
+                        r#"{{"stream":"{}@aggTrade","data":{{"e":"aggTrade","E":{},"s":"{}","a":{},"p":"100","q":"1","f":{},"l":{},"T":{},"m":true}}}}"#,
+                        symbol.to_lowercase(),
+                        id,
+                        symbol,
+                        id,
+                        id,
+                        id,
+                        id
+                    )
+                };
+                let _ = ws_stream
+                    .send(Message::Text(agg_trade("ETHUSDT", 1).into()))
+                    .await;
+                for id in 1.. {
+                    if ws_stream
+                        .send(Message::Text(agg_trade("BTCUSDT", id).into()))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                    sleep(Duration::from_millis(50)).await;
+                }
+            });
+        }
+    });
+    (port, conn_count)
+}
+
+async fn init_quiet_stream_provider(port: u16, stale_timeouts: &str) -> BinanceSpotMarketProvider {
+    let config_content = format!(
+        r#"
+markets = ["binance_spot"]
+db_path = "test_db_path"
+
+[binance_spot]
+api_base_url = "http://127.0.0.1:{port}"
+stream_base_url = "ws://127.0.0.1:{port}/stream"
+stream_api_base_url = "ws://127.0.0.1:{port}/ws-api/v3"
+api_key = ""
+secret_key = ""
+subscribed_symbols = ["BTCUSDT", "ETHUSDT"]
+subscribed_kline_intervals = []
+stream_reconnect_interval_milli_secs = 100
+stream_stale_timeouts = {stale_timeouts}
+"#
+    );
+    let mut config_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config_file, config_content.as_bytes()).unwrap();
+    let config = Config::from_toml(config_file.path().to_str().unwrap()).unwrap();
+    let platform_config = PlatformConfig::from_config(config).unwrap();
+
+    let mut provider = BinanceSpotMarketProvider::new(
+        platform_config.configs[&MarketType::BinanceSpot].clone(),
+        None,
+    )
+    .unwrap();
+    provider.init().await.unwrap();
+    provider
+}
+
+#[tokio::test]
+async fn test_stale_stream_watchdog_reconnects() {
+    let (port, conn_count) = start_mock_quiet_stream().await;
+    let provider = init_quiet_stream_provider(port, r#"{ trade = 400 }"#).await;
+    let eth_key = (MarketDataType::Trade, "ETHUSDT".to_string());
+
+    let first_eth_ts = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            if let Some(ts) = provider.health().await.last_received_ts.get(&eth_key) {
+                return *ts;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("expect ETHUSDT trade");
+
+    // ETHUSDT静默超时后强制重连，新连接重新订阅并恢复推送
+    let health = tokio::time::timeout(Duration::from_secs(3), async {
+        loop {
+            let health = provider.health().await;
+            if health.reconnects >= 1 && health.last_received_ts[&eth_key] > first_eth_ts {
+                return health;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("expect watchdog reconnect");
+    assert!(health.stale_reconnects >= 1);
+    assert_eq!(health.reconnect_failures, 0);
+    assert!(conn_count.load(Ordering::SeqCst) >= 2);
+    assert!(health.stream_connected);
+}
+
+#[tokio::test]
+async fn test_stale_stream_watchdog_quiet_symbol_override() {
+    let (port, conn_count) = start_mock_quiet_stream().await;
+    // ETHUSDT成交稀少，不做静默检测
+    let provider =
+        init_quiet_stream_provider(port, r#"{ trade = 400, "ETHUSDT:trade" = 0 }"#).await;
+
+    sleep(Duration::from_millis(1200)).await;
+    let health = provider.health().await;
+    assert_eq!(health.stale_reconnects, 0);
+    assert_eq!(health.reconnects, 0);
+    assert_eq!(conn_count.load(Ordering::SeqCst), 1);
+    assert!(health
+        .last_received_ts
+        .contains_key(&(MarketDataType::Trade, "BTCUSDT".to_string())));
+}
+
+// 模拟depth快照REST
+async fn start_mock_depth_api() -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let body = r#"{"lastUpdateId":10,"bids":[["100","1"]],"asks":[["101","1"]]}"#;
+                let resp = format!(
+                    "HTTP/1.1 200 MOCK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(resp.as_bytes()).await;
+            });
+        }
+    });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_market_provider_health() {
+    let port = start_mock_health_stream().await;
+    let api_base_url = start_mock_depth_api().await;
+    let config_content = format!(
+        r#"
+markets = ["binance_spot"]
+db_path = "test_db_path"
+
+[binance_spot]
+api_base_url = "{api_base_url}"
+stream_base_url = "ws://127.0.0.1:{port}/stream"
+stream_api_base_url = "ws://127.0.0.1:{port}/ws-api/v3"
+api_key = ""
+secret_key = ""
+subscribed_symbols = ["BTCUSDT"]
+subscribed_kline_intervals = ["1m"]
+stream_reconnect_interval_milli_secs = 100
+"#
+    );
+    let mut config_file = NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config_file, config_content.as_bytes()).unwrap();
+    let config = Config::from_toml(config_file.path().to_str().unwrap()).unwrap();
+    let platform_config = PlatformConfig::from_config(config).unwrap();
+
+    let mut provider = BinanceSpotMarketProvider::new(
+        platform_config.configs[&MarketType::BinanceSpot].clone(),
+        None,
+    )
+    .unwrap();
+    let health = provider.health().await;
+    assert!(!health.stream_connected);
+    assert!(health.last_received_ts.is_empty());
+
+    let start_ts = time::get_current_milli_timestamp();
+    provider.init().await.unwrap();
+    assert!(provider.health().await.stream_connected);
+
+    // 收到成交与深度增量，深度拉取快照后同步
+    let health = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            let health = provider.health().await;
+            if health.depth.get("BTCUSDT").is_some_and(|h| h.synced) {
+                return health;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("expect depth synced");
+    let trade_key = (MarketDataType::Trade, "BTCUSDT".to_string());
+    let depth_key = (MarketDataType::Depth, "BTCUSDT".to_string());
+    let trade_ts = health.last_received_ts[&trade_key];
+    assert!(trade_ts >= start_ts);
+    assert!(health.last_received_ts[&depth_key] >= trade_ts);
+    assert!(!health
+        .last_received_ts
+        .contains_key(&(MarketDataType::Kline, "BTCUSDT".to_string())));
+    assert_eq!(health.reconnects, 0);
+
+    // 服务端断开，新连接握手完成前为断连状态
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while provider.health().await.stream_connected {
+            sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("expect stream disconnected");
+
+    let health = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let health = provider.health().await;
+            if health.reconnects == 1 {
+                return health;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("expect stream reconnect");
+    assert!(health.stream_connected);
+    assert_eq!(health.reconnect_failures, 0);
+    // 重连后保留断连前的接收时间
+    assert_eq!(health.last_received_ts[&trade_key], trade_ts);
+    assert!(health.depth["BTCUSDT"].running);
+}
+
+// 模拟行情REST：aggTrades返回首笔逐笔成交id为agg_first_trade_id的归集成交（为None时返回空），
+// historicalTrades从fromId开始返回3笔成交（timestamp = 1000 + id），并记录请求行
+async fn start_mock_trades_api(
+    agg_first_trade_id: Option<u64>,
+    requests: Arc<std::sync::Mutex<Vec<String>>>,
+) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let requests = requests.clone();
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let line = request.lines().next().unwrap_or_default().to_string();
+                requests.lock().unwrap().push(line.clone());
+                let body = if line.contains("/api/v3/aggTrades") {
+                    match agg_first_trade_id {
+                        Some(id) => format!(
+                            r#"[{{"a":1,"p":"100","q":"1","f":{},"l":{},"T":{},"m":false}}]"#,
+                            id,
+                            id + 1,
+                            1000 + id
+                        ),
+                        None => "[]".to_string(),
+                    }
+                } else {
+                    let from_id: u64 = line
+                        .split("fromId=")
+                        .nth(1)
+                        .and_then(|v| v.split('&').next())
+                        .map(|v| v.parse().unwrap())
+                        .unwrap_or(1);
+                    let trades: Vec<String> = (from_id..from_id + 3)
+                        .map(|id| {
+                            format!(
+                                r#"{{"id":{},"price":"100","qty":"1","quoteQty":"100","time":{},"isBuyerMaker":false,"isBestMatch":true}}"#,
+                                id,
+                                1000 + id
+                            )
+                        })
+                        .collect();
+                    format!("[{}]", trades.join(","))
+                };
+                let resp = format!(
+                    "HTTP/1.1 200 MOCK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(resp.as_bytes()).await;
+            });
+        }
+    });
+    format!("http://{}", addr)
+}
+
+#[tokio::test]
+async fn test_get_raw_trades_by_start_time() {
+    let requests = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let base_url = start_mock_trades_api(Some(42), requests.clone()).await;
+    let mut api = MarketApi::new(base_url, None, None, 5000);
+    api.set_api_key("key");
+    api.init().unwrap();
+    let req = |from_id: Option<&str>, start_time, end_time| GetTradesRequest {
+        symbol: "BTCUSDT".to_string(),
+        from_id: from_id.map(|id| id.to_string()),
+        start_time,
+        end_time,
+        limit: Some(1000),
+    };
+
+    // 按时间查询时以aggTrades的首笔逐笔成交id为起点，并按end_time截断
+    let trades = get_raw_trades(&api, req(None, Some(1042), Some(1043)))
+        .await
+        .unwrap();
+    assert_eq!(
+        trades.iter().map(|t| t.seq_id).collect::<Vec<_>>(),
+        vec![42, 43]
+    );
+    assert_eq!(trades[0].trade_id, "42");
+    {
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].contains("/api/v3/aggTrades?"));
+        assert!(requests[0].contains("startTime=1042"));
+        assert!(requests[1].contains("/api/v3/historicalTrades?fromId=42&"));
+    }
+
+    // 指定from_id时直接查询historicalTrades
+    requests.lock().unwrap().clear();
+    let trades = get_raw_trades(&api, req(Some("7"), None, None))
+        .await
+        .unwrap();
+    assert_eq!(
+        trades.iter().map(|t| t.seq_id).collect::<Vec<_>>(),
+        vec![7, 8, 9]
+    );
+    assert_eq!(requests.lock().unwrap().len(), 1);
+
+    // 起始时刻之后没有成交
+    let base_url = start_mock_trades_api(None, requests.clone()).await;
+    let mut api = MarketApi::new(base_url, None, None, 5000);
+    api.set_api_key("key");
+    api.init().unwrap();
+    assert!(get_raw_trades(&api, req(None, Some(5000), None))
+        .await
+        .unwrap()
+        .is_empty());
+
+    // 未配置api key
+    let mut api = MarketApi::new("http://127.0.0.1:1".to_string(), None, None, 5000);
+    api.init().unwrap();
+    let err = get_raw_trades(&api, req(Some("1"), None, None))
+        .await
+        .unwrap_err();
+    assert!(err.source().unwrap().to_string().contains("api key"));
+}
+
+fn ex_levels(levels: &[(&str, &str)]) -> Vec<ex_models::PriceLevel> {
+    levels
+        .iter()
+        .map(|(price, quantity)| ex_models::PriceLevel {
+            price: price.parse().unwrap(),
+            quantity: quantity.parse().unwrap(),
+        })
+        .collect()
+}
+
+fn depth_snapshot(asks: &[(&str, &str)]) -> ex_models::DepthData {
+    ex_models::DepthData {
+        symbol: "BTCUSDT".to_string(),
+        last_update_id: 100,
+        bids: ex_levels(&[("100.00", "2"), ("100.10", "1.5"), ("99.9", "0.25")]),
+        asks: ex_levels(asks),
+        timestamp: 1000,
+    }
+}
+
+fn depth_update(checksum: Option<i32>) -> ex_models::DepthUpdate {
+    ex_models::DepthUpdate {
+        symbol: "BTCUSDT".to_string(),
+        first_update_id: 101,
+        last_update_id: 101,
+        bids: vec![],
+        asks: ex_levels(&[("100.4", "2")]),
+        timestamp: 2000,
+        checksum,
+    }
+}
+
+#[test]
+fn test_depth_checksum_known_book() {
+    // "100.10:1.5:100.20:1:100.00:2:100.3:3:99.9:0.25" 的CRC32
+    let state = DepthState::from_depth(&depth_snapshot(&[("100.20", "1"), ("100.3", "3")]));
+    assert_eq!(state.checksum(), 1663052633);
+
+    let mut state = DepthState::from_depth(&depth_snapshot(&[("100.20", "1"), ("100.3", "3")]));
+    state.set_verify_checksum(true);
+    assert!(state.update_depth(&depth_update(Some(175904393))).unwrap());
+    assert_eq!(state.checksum(), 175904393);
+}
+
+#[test]
+fn test_depth_checksum_mismatch_forces_resync() {
+    // 本地快照100.3档数量错误，序号连续但盘口已损坏
+    let corrupted = depth_snapshot(&[("100.20", "1"), ("100.3", "4")]);
+    let update = depth_update(Some(175904393));
+
+    // 未开启校验时无法发现
+    let mut state = DepthState::from_depth(&corrupted);
+    assert!(state.update_depth(&update).unwrap());
+
+    let mut state = DepthState::from_depth(&corrupted);
+    state.set_verify_checksum(true);
+    let err = state.update_depth(&update).unwrap_err();
+    assert!(err.to_string().contains("checksum mismatch"), "{}", err);
+
+    // 重新拉取快照后应用同一更新，校验通过
+    let mut state = DepthState::from_depth(&depth_snapshot(&[("100.20", "1"), ("100.3", "3")]));
+    state.set_verify_checksum(true);
+    assert!(state.update_depth(&update).unwrap());
+
+    // 推送未带checksum时不校验
+    let mut state = DepthState::from_depth(&corrupted);
+    state.set_verify_checksum(true);
+    assert!(state.update_depth(&depth_update(None)).unwrap());
+}
+
+#[test]
+fn test_crossed_book_rejected() {
+    let mut state = DepthState::from_depth(&depth_snapshot(&[("100.20", "1"), ("100.3", "3")]));
+    assert!(state.crossed_prices().is_none());
+
+    // 漏掉了100.20卖档的删除，新买单100.25与之交叉
+    let mut update = depth_update(None);
+    update.bids = ex_levels(&[("100.25", "1")]);
+    update.asks = vec![];
+    let err = state.update_depth(&update).unwrap_err();
+    assert!(err.to_string().contains("crossed"), "{}", err);
+    assert_eq!(
+        state.crossed_prices(),
+        Some(("100.25".parse().unwrap(), "100.20".parse().unwrap()))
+    );
+
+    // 重新拉取的快照中100.20已成交，同一更新应用后盘口正常
+    let mut state = DepthState::from_depth(&depth_snapshot(&[("100.3", "3")]));
+    assert!(state.update_depth(&update).unwrap());
+    assert!(state.crossed_prices().is_none());
+    let depth = state.depth();
+    assert_eq!(depth.bids[0].price.to_string(), "100.25");
+    assert_eq!(depth.asks[0].price.to_string(), "100.3");
+}
+
+// 前panic_times次拉取快照panic，随后error_times次返回错误，之后返回正常快照，并记录请求档位
+struct FlakyDepthFetcher {
+    calls: std::sync::atomic::AtomicU32,
+    panic_times: u32,
+    error_times: u32,
+    limits: std::sync::Mutex<Vec<u32>>,
+}
+
+#[async_trait]
+impl DepthSnapshotFetcher for FlakyDepthFetcher {
+    async fn fetch_depth(
+        &self,
+        _symbol: &str,
+        limit: u32,
+    ) -> crate::errors::Result<ex_models::DepthData> {
+        self.limits.lock().unwrap().push(limit);
+        let n = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        if n < self.panic_times {
+            panic!("mock depth fetcher panic {}", n);
+        }
+        if n < self.panic_times + self.error_times {
+            return Err(crate::errors::PlatformError::MarketProviderError {
+                message: format!("mock depth fetch error {}", n),
+            });
+        }
+        Ok(depth_snapshot(&[("100.20", "1"), ("100.3", "3")]))
+    }
+}
+
+#[tokio::test]
+async fn test_depth_task_restarts_and_recovers() {
+    let fetcher = Arc::new(FlakyDepthFetcher {
+        calls: std::sync::atomic::AtomicU32::new(0),
+        panic_times: 2,
+        error_times: 2,
+        limits: std::sync::Mutex::new(vec![]),
+    });
+    let (update_sender, update_receiver) = tokio::sync::broadcast::channel(16);
+    let depth_sender = EventSender::new(16, ChannelOverflowPolicy::DropOldest);
+    let mut depth_receiver = depth_sender.subscribe();
+    let health = Arc::new(tokio::sync::RwLock::new(std::collections::HashMap::new()));
+    let shutdown_token = tokio_util::sync::CancellationToken::new();
+    let task = DepthTask {
+        symbol: "BTCUSDT".to_string(),
+        fetcher: fetcher.clone(),
+        state_lock: Arc::new(tokio::sync::RwLock::new(None)),
+        depth_sender,
+        verify_depth_checksum: false,
+        snapshot_limit: 500,
+        health: health.clone(),
+    };
+    let handle = task.spawn_supervised(
+        update_receiver,
+        shutdown_token.clone(),
+        Duration::from_millis(10),
+    );
+
+    // 持续推送增量，每次触发拉取快照，前两次panic导致任务重启，随后两次失败后恢复
+    let depth = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let _ = update_sender.send(depth_update(None));
+            if let Ok(Ok(depth)) =
+                tokio::time::timeout(Duration::from_millis(20), depth_receiver.recv()).await
+            {
+                return depth;
+            }
+        }
+    })
+    .await
+    .expect("expect depth recovered");
+    assert_eq!(depth.asks[2].price.to_string(), "100.4");
+    assert_eq!(depth.timestamp, 2000);
+    assert_eq!(fetcher.calls.load(std::sync::atomic::Ordering::SeqCst), 5);
+    // 每次重建盘口都按配置的档位拉取快照
+    assert_eq!(*fetcher.limits.lock().unwrap(), vec![500; 5]);
+
+    let symbol_health = health.read().await["BTCUSDT"].clone();
+    assert!(symbol_health.running);
+    assert!(symbol_health.synced);
+    assert_eq!(symbol_health.restarts, 2);
+    assert_eq!(symbol_health.last_update_ts, 2000);
+    assert!(symbol_health
+        .last_error
+        .unwrap()
+        .contains("mock depth fetch error 3"));
+
+    shutdown_token.cancel();
+    handle.await.unwrap();
+    assert!(!health.read().await["BTCUSDT"].running);
+}
@@ -0,0 +1,111 @@
+use crate::config::ChannelOverflowPolicy;
+use std::sync::Arc;
+use tokio::sync::{broadcast, Notify};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventRecvError {
+    Lagged(u64), // 仅Error策略下返回，为被覆盖的消息数
+    Closed,
+}
+
+// 按ChannelOverflowPolicy发送的broadcast channel
+#[derive(Clone)]
+pub struct EventSender<T> {
+    sender: broadcast::Sender<T>,
+    capacity: usize,
+    policy: ChannelOverflowPolicy,
+    consumed: Arc<Notify>, // 订阅者消费或退订时通知，唤醒Block策略下等待的发送方
+}
+
+impl<T: Clone> EventSender<T> {
+    pub fn new(capacity: usize, policy: ChannelOverflowPolicy) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            capacity,
+            policy,
+            consumed: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn policy(&self) -> &ChannelOverflowPolicy {
+        &self.policy
+    }
+
+    pub fn subscribe(&self) -> EventReceiver<T> {
+        EventReceiver {
+            receiver: self.sender.subscribe(),
+            policy: self.policy.clone(),
+            filter: None,
+            consumed: ConsumedNotifier(self.consumed.clone()),
+        }
+    }
+
+    // 返回收到消息的订阅者数量，无订阅者时丢弃消息并返回0
+    // Block策略下channel已满时等待最慢的订阅者消费
+    pub async fn send(&self, value: T) -> usize {
+        if self.policy == ChannelOverflowPolicy::Block {
+            loop {
+                // 先注册等待再检查，避免检查与等待之间的消费通知丢失
+                let consumed = self.consumed.notified();
+                tokio::pin!(consumed);
+                consumed.as_mut().enable();
+                if self.sender.receiver_count() == 0 || self.sender.len() < self.capacity {
+                    break;
+                }
+                consumed.await;
+            }
+        }
+        self.sender.send(value).unwrap_or(0)
+    }
+}
+
+type EventFilter<T> = Box<dyn Fn(&T) -> bool + Send + Sync>;
+
+pub struct EventReceiver<T> {
+    receiver: broadcast::Receiver<T>,
+    policy: ChannelOverflowPolicy,
+    filter: Option<EventFilter<T>>,
+    consumed: ConsumedNotifier, // 在receiver之后析构，退订时通知发送方
+}
+
+struct ConsumedNotifier(Arc<Notify>);
+
+impl Drop for ConsumedNotifier {
+    fn drop(&mut self) {
+        self.0.notify_waiters();
+    }
+}
+
+impl<T: Clone + 'static> EventReceiver<T> {
+    // 只返回满足条件的事件，其余在recv内直接跳过；多次调用时条件取交集
+    // 过滤发生在订阅端，Error策略下Lagged计数包含被过滤的事件
+    pub fn with_filter(mut self, filter: impl Fn(&T) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(match self.filter.take() {
+            None => Box::new(filter),
+            Some(prev) => Box::new(move |value| prev(value) && filter(value)),
+        });
+        self
+    }
+
+    // DropOldest/Block策略下跳过被覆盖的消息，Error策略下返回Lagged
+    pub async fn recv(&mut self) -> Result<T, EventRecvError> {
+        loop {
+            let received = self.receiver.recv().await;
+            self.consumed.0.notify_waiters();
+            match received {
+                Ok(value) => match &self.filter {
+                    Some(filter) if !filter(&value) => continue,
+                    _ => return Ok(value),
+                },
+                Err(broadcast::error::RecvError::Closed) => return Err(EventRecvError::Closed),
+                Err(broadcast::error::RecvError::Lagged(n)) => match self.policy {
+                    ChannelOverflowPolicy::Error => return Err(EventRecvError::Lagged(n)),
+                    _ => {
+                        log::warn!("event receiver lagged, dropped {} oldest events", n);
+                    }
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    config::ChannelOverflowPolicy,
+    market_provider::{EventRecvError, EventSender},
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::time::{sleep, timeout};
+
+#[tokio::test]
+async fn test_drop_oldest_skips_lagged_events() {
+    let sender = EventSender::new(2, ChannelOverflowPolicy::DropOldest);
+    let mut receiver = sender.subscribe();
+    // 订阅者未消费时持续发送，不阻塞生产者
+    for i in 1..=5 {
+        assert_eq!(sender.send(i).await, 1);
+    }
+    assert_eq!(receiver.recv().await, Ok(4));
+    assert_eq!(receiver.recv().await, Ok(5));
+}
+
+#[tokio::test]
+async fn test_error_surfaces_lagged() {
+    let sender = EventSender::new(2, ChannelOverflowPolicy::Error);
+    let mut receiver = sender.subscribe();
+    for i in 1..=5 {
+        sender.send(i).await;
+    }
+    assert_eq!(receiver.recv().await, Err(EventRecvError::Lagged(3)));
+    assert_eq!(receiver.recv().await, Ok(4));
+    assert_eq!(receiver.recv().await, Ok(5));
+
+    drop(sender);
+    assert_eq!(receiver.recv().await, Err(EventRecvError::Closed));
+}
+
+#[tokio::test]
+async fn test_block_backpressures_producer() {
+    let sender = EventSender::new(2, ChannelOverflowPolicy::Block);
+    let mut receiver = sender.subscribe();
+    let sent = Arc::new(AtomicUsize::new(0));
+
+    let producer_sent = sent.clone();
+    let producer = tokio::spawn(async move {
+        for i in 1..=5 {
+            sender.send(i).await;
+            producer_sent.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+
+    // channel满后生产者等待
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(sent.load(Ordering::SeqCst), 2);
+
+    // 慢消费者逐条消费，不丢消息
+    let mut received = vec![];
+    for _ in 1..=5 {
+        sleep(Duration::from_millis(10)).await;
+        received.push(
+            timeout(Duration::from_secs(1), receiver.recv())
+                .await
+                .unwrap()
+                .unwrap(),
+        );
+    }
+    assert_eq!(received, vec![1, 2, 3, 4, 5]);
+    producer.await.unwrap();
+    assert_eq!(sent.load(Ordering::SeqCst), 5);
+}
+
+#[tokio::test]
+async fn test_block_resumes_when_subscriber_drops() {
+    let sender = EventSender::new(1, ChannelOverflowPolicy::Block);
+    let receiver = sender.subscribe();
+    sender.send(1).await;
+
+    let producer = tokio::spawn({
+        let sender = sender.clone();
+        async move { sender.send(2).await }
+    });
+    sleep(Duration::from_millis(50)).await;
+    assert!(!producer.is_finished());
+
+    // 唯一的订阅者退订后不再等待
+    drop(receiver);
+    assert_eq!(
+        timeout(Duration::from_secs(1), producer)
+            .await
+            .unwrap()
+            .unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_send_without_subscribers() {
+    // 无订阅者时不阻塞也不报错
+    let sender = EventSender::new(1, ChannelOverflowPolicy::Block);
+    for i in 0..3 {
+        assert_eq!(sender.send(i).await, 0);
+    }
+}
+
+#[tokio::test]
+async fn test_filtered_receiver() {
+    let sender = EventSender::new(16, ChannelOverflowPolicy::DropOldest);
+    // 多个条件取交集
+    let mut receiver = sender
+        .subscribe()
+        .with_filter(|v: &i32| v % 2 == 0)
+        .with_filter(|v: &i32| *v > 2);
+    for i in 1..=8 {
+        sender.send(i).await;
+    }
+    drop(sender);
+    let mut received = vec![];
+    while let Ok(v) = receiver.recv().await {
+        received.push(v);
+    }
+    assert_eq!(received, vec![4, 6, 8]);
+}
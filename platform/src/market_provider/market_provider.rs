@@ -1,12 +1,12 @@
 use crate::{
     errors::Result,
+    market_provider::EventReceiver,
     models::{
         DepthData, ExchangeInfo, GetDepthRequest, GetExchangeInfoRequest, GetKlinesRequest,
-        GetTicker24hrRequest, GetTradesRequest, KlineData, Ticker24hr, Trade,
+        GetTicker24hrRequest, GetTradesRequest, KlineData, KlineInterval, Ticker24hr, Trade,
     },
 };
 use async_trait::async_trait;
-use tokio::sync::broadcast;
 
 #[async_trait]
 pub trait MarketProvider: Send + Sync {
@@ -14,12 +14,41 @@ pub trait MarketProvider: Send + Sync {
 
     async fn get_klines(&self, req: GetKlinesRequest) -> Result<Vec<KlineData>>;
     async fn get_trades(&self, req: GetTradesRequest) -> Result<Vec<Trade>>;
-    async fn get_depth(&self, req: GetDepthRequest) -> Result<DepthData>;
+    // 无数据（如尚无挂单）时返回None，请求失败返回Err
+    async fn get_depth(&self, req: GetDepthRequest) -> Result<Option<DepthData>>;
     async fn get_ticker_24hr(&self, req: GetTicker24hrRequest) -> Result<Vec<Ticker24hr>>;
+    // 单个symbol的24hr ticker，语义同get_depth
+    async fn get_ticker(&self, symbol: &str) -> Result<Option<Ticker24hr>> {
+        let tickers = self
+            .get_ticker_24hr(GetTicker24hrRequest {
+                symbol: Some(symbol.to_string()),
+                symbols: None,
+            })
+            .await?;
+        Ok(tickers.into_iter().find(|ticker| ticker.symbol == symbol))
+    }
     async fn get_exchange_info(&self, req: GetExchangeInfoRequest) -> Result<ExchangeInfo>;
 
-    fn subscribe_kline(&self) -> broadcast::Receiver<KlineData>;
-    fn subscribe_trade(&self) -> broadcast::Receiver<Trade>;
-    fn subscribe_depth(&self) -> broadcast::Receiver<DepthData>;
-    fn subscribe_ticker(&self) -> broadcast::Receiver<Ticker24hr>;
+    fn subscribe_kline(&self) -> EventReceiver<KlineData>;
+    fn subscribe_trade(&self) -> EventReceiver<Trade>;
+    fn subscribe_depth(&self) -> EventReceiver<DepthData>;
+    fn subscribe_ticker(&self) -> EventReceiver<Ticker24hr>;
+
+    // 只接收单个symbol（及周期）的推送，其他symbol的事件在接收端跳过，不会返回给订阅者
+    fn subscribe_trade_for(&self, symbol: &str) -> EventReceiver<Trade> {
+        let symbol = symbol.to_string();
+        self.subscribe_trade()
+            .with_filter(move |trade| trade.symbol == symbol)
+    }
+
+    fn subscribe_kline_for(
+        &self,
+        symbol: &str,
+        interval: &KlineInterval,
+    ) -> EventReceiver<KlineData> {
+        let symbol = symbol.to_string();
+        let interval = interval.clone();
+        self.subscribe_kline()
+            .with_filter(move |kline| kline.symbol == symbol && kline.interval == interval)
+    }
 }
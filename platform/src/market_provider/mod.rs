@@ -1,8 +1,18 @@
 pub mod market_provider;
 pub use market_provider::*;
 
+pub mod event_channel;
+pub use event_channel::*;
+
 pub mod binance_spot_market_provider;
 pub use binance_spot_market_provider::*;
 
+pub mod replay_market_provider;
+pub use replay_market_provider::*;
+
 #[cfg(test)]
 mod binance_spot_market_provider_tests;
+#[cfg(test)]
+mod event_channel_tests;
+#[cfg(test)]
+mod replay_market_provider_tests;
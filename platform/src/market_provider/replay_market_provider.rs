@@ -0,0 +1,321 @@
+use crate::{
+    config::MarketConfig,
+    data_manager::{
+        db::{get_all_symbol_info, get_depth_snapshot_before, get_klines, get_trades},
+        depth_replay::replay_depth_at,
+        local_data_manager::{Clock, ClockHook},
+    },
+    errors::{PlatformError, Result},
+    market_provider::{EventReceiver, EventSender, MarketProvider},
+    models::{
+        DepthData, ExchangeInfo, GetDepthRequest, GetExchangeInfoRequest, GetKlinesRequest,
+        GetTicker24hrRequest, GetTradesRequest, KlineData, KlineInterval, MarketType, Ticker24hr,
+        Trade,
+    },
+};
+use async_trait::async_trait;
+use db::sqlite::SQLiteDB;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+// 每次从db加载的kline/trade条数
+const REPLAY_PAGE_LIMIT: u64 = 1000;
+
+enum ReplayEvent {
+    Kline(KlineData),
+    Trade(Trade),
+    Depth(DepthData),
+}
+
+impl ReplayEvent {
+    fn timestamp(&self) -> u64 {
+        match self {
+            ReplayEvent::Kline(kline) => kline.close_time,
+            ReplayEvent::Trade(trade) => trade.timestamp,
+            ReplayEvent::Depth(depth) => depth.timestamp,
+        }
+    }
+}
+
+// 回放历史行情的MarketProvider：REST接口按clock当前时间读取db，不返回未来数据；
+// 注册为ClockHook后，每次推进时钟将(上次推进时间, cur_ts]内收盘的kline、成交及盘口变化按时间顺序推送到订阅channel
+// db中没有ticker数据，get_ticker_24hr返回空，也不推送ticker
+pub struct ReplayMarketProvider {
+    db: Arc<SQLiteDB>,
+    market_type: MarketType,
+    clock: Arc<Clock>,
+    symbols: Vec<String>,
+    kline_intervals: Vec<KlineInterval>,
+    replayed_ts: Mutex<u64>, // 已推送到的时间，同时串行化并发的推进
+    kline_sender: EventSender<KlineData>,
+    trade_sender: EventSender<Trade>,
+    depth_sender: EventSender<DepthData>,
+    ticker_sender: EventSender<Ticker24hr>,
+}
+
+impl ReplayMarketProvider {
+    // 订阅的symbol与kline周期、channel容量取自config，创建时clock之前的数据视为历史，只能通过REST接口获取
+    pub fn new(
+        config: Arc<MarketConfig>,
+        market_type: MarketType,
+        db: Arc<SQLiteDB>,
+        clock: Arc<Clock>,
+    ) -> Self {
+        let replayed_ts = clock.cur_ts();
+        Self {
+            db,
+            market_type,
+            clock,
+            symbols: config.subscribed_symbols.clone(),
+            kline_intervals: config.subscribed_kline_intervals.clone(),
+            replayed_ts: Mutex::new(replayed_ts),
+            kline_sender: EventSender::new(
+                config.kline_event_channel_capacity,
+                config.event_channel_overflow_policy("kline"),
+            ),
+            trade_sender: EventSender::new(
+                config.trade_event_channel_capacity,
+                config.event_channel_overflow_policy("trade"),
+            ),
+            depth_sender: EventSender::new(
+                config.depth_event_channel_capacity,
+                config.event_channel_overflow_policy("depth"),
+            ),
+            ticker_sender: EventSender::new(
+                config.ticker_event_channel_capacity,
+                config.event_channel_overflow_policy("ticker"),
+            ),
+        }
+    }
+
+    // close_time在(after, until]内的kline
+    fn load_klines(
+        &self,
+        symbol: &str,
+        interval: &KlineInterval,
+        after: u64,
+        until: u64,
+    ) -> Result<Vec<KlineData>> {
+        let mut result = Vec::new();
+        // start_time为0时db按倒序取最新数据，从1开始保证升序分页；从上一根bar起取，覆盖after+1所在的bar
+        let mut from = interval.prev_open_time(after + 1).unwrap_or(0).max(1);
+        loop {
+            let page = get_klines(
+                self.db.clone(),
+                &self.market_type,
+                symbol,
+                interval,
+                Some(from),
+                Some(until),
+                Some(REPLAY_PAGE_LIMIT),
+            )?;
+            let fetched = page.len() as u64;
+            if let Some(last) = page.last() {
+                from = last.open_time + 1;
+            }
+            result.extend(
+                page.into_iter()
+                    .filter(|k| k.close_time > after && k.close_time <= until),
+            );
+            if fetched < REPLAY_PAGE_LIMIT {
+                break;
+            }
+        }
+        Ok(result)
+    }
+
+    // timestamp在(after, until]内的成交，首页按时间定位，之后按seq_id分页，避免同一时间戳的成交跨页遗漏
+    fn load_trades(&self, symbol: &str, after: u64, until: u64) -> Result<Vec<Trade>> {
+        let mut result: Vec<Trade> = Vec::new();
+        let mut page = get_trades(
+            self.db.clone(),
+            &self.market_type,
+            symbol,
+            Some(after + 1),
+            Some(until),
+            None,
+            Some(REPLAY_PAGE_LIMIT),
+        )?;
+        loop {
+            let fetched = page.len() as u64;
+            let next_seq_id = page.last().map(|t| t.seq_id + 1);
+            let before = result.len();
+            result.extend(
+                page.into_iter()
+                    .filter(|t| t.timestamp > after && t.timestamp <= until),
+            );
+            match next_seq_id {
+                Some(seq_id) if fetched == REPLAY_PAGE_LIMIT && result.len() > before => {
+                    page = get_trades(
+                        self.db.clone(),
+                        &self.market_type,
+                        symbol,
+                        None,
+                        None,
+                        Some(seq_id),
+                        Some(REPLAY_PAGE_LIMIT),
+                    )?;
+                }
+                _ => break,
+            }
+        }
+        Ok(result)
+    }
+
+    // 盘口在(after, until]内有变化时返回until时刻的盘口，增量缺失时不推送
+    fn load_depth(&self, symbol: &str, after: u64, until: u64) -> Result<Option<DepthData>> {
+        if get_depth_snapshot_before(self.db.clone(), &self.market_type, symbol, until)?.is_none() {
+            return Ok(None);
+        }
+        let replayed = replay_depth_at(self.db.clone(), &self.market_type, symbol, until)?;
+        if replayed.stale || replayed.depth.timestamp <= after {
+            return Ok(None);
+        }
+        Ok(Some(replayed.depth))
+    }
+
+    async fn replay(&self, after: u64, until: u64) -> Result<()> {
+        let mut events = Vec::new();
+        for symbol in self.symbols.iter() {
+            for interval in self.kline_intervals.iter() {
+                events.extend(
+                    self.load_klines(symbol, interval, after, until)?
+                        .into_iter()
+                        .map(ReplayEvent::Kline),
+                );
+            }
+            events.extend(
+                self.load_trades(symbol, after, until)?
+                    .into_iter()
+                    .map(ReplayEvent::Trade),
+            );
+            if let Some(depth) = self.load_depth(symbol, after, until)? {
+                events.push(ReplayEvent::Depth(depth));
+            }
+        }
+        // 稳定排序，同一时间戳内保持kline、trade、depth及各自的原有顺序
+        events.sort_by_key(|event| event.timestamp());
+
+        for event in events {
+            match event {
+                ReplayEvent::Kline(kline) => self.kline_sender.send(kline).await,
+                ReplayEvent::Trade(trade) => self.trade_sender.send(trade).await,
+                ReplayEvent::Depth(depth) => self.depth_sender.send(depth).await,
+            };
+        }
+        Ok(())
+    }
+
+    // kline只返回cur_ts前已收盘的bar，即next_open_time不晚于cur_ts + 1
+    fn last_closed_open_time(&self, interval: &KlineInterval) -> Option<u64> {
+        interval.prev_open_time(self.clock.cur_ts() + 1)
+    }
+}
+
+#[async_trait]
+impl ClockHook for ReplayMarketProvider {
+    async fn on_clock_advance(&self, cur_ts: u64) -> Result<()> {
+        let mut replayed_ts = self.replayed_ts.lock().await;
+        if cur_ts <= *replayed_ts {
+            return Ok(());
+        }
+        self.replay(*replayed_ts, cur_ts).await?;
+        *replayed_ts = cur_ts;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl MarketProvider for ReplayMarketProvider {
+    async fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn get_klines(&self, req: GetKlinesRequest) -> Result<Vec<KlineData>> {
+        let end_time = match self.last_closed_open_time(&req.interval) {
+            Some(open_time) => req.end_time.unwrap_or(i64::MAX as u64).min(open_time),
+            None => return Ok(vec![]),
+        };
+        get_klines(
+            self.db.clone(),
+            &self.market_type,
+            &req.symbol,
+            &req.interval,
+            req.start_time,
+            Some(end_time),
+            req.limit.map(|limit| limit as u64),
+        )
+    }
+
+    async fn get_trades(&self, req: GetTradesRequest) -> Result<Vec<Trade>> {
+        let cur_ts = self.clock.cur_ts();
+        let from_seq_id =
+            match req.from_id {
+                Some(from_id) => Some(from_id.parse::<u64>().map_err(|e| {
+                    PlatformError::MarketProviderError {
+                        message: format!("invalid from_id {}: {}", from_id, e),
+                    }
+                })?),
+                None => None,
+            };
+        let mut trades = get_trades(
+            self.db.clone(),
+            &self.market_type,
+            &req.symbol,
+            req.start_time,
+            Some(req.end_time.unwrap_or(i64::MAX as u64).min(cur_ts)),
+            from_seq_id,
+            req.limit.map(|limit| limit as u64),
+        )?;
+        // 按seq_id查询时不带时间条件，过滤掉未来的成交
+        trades.retain(|t| t.timestamp <= cur_ts);
+        Ok(trades)
+    }
+
+    async fn get_depth(&self, req: GetDepthRequest) -> Result<Option<DepthData>> {
+        let cur_ts = self.clock.cur_ts();
+        if get_depth_snapshot_before(self.db.clone(), &self.market_type, &req.symbol, cur_ts)?
+            .is_none()
+        {
+            return Ok(None);
+        }
+        let mut depth =
+            replay_depth_at(self.db.clone(), &self.market_type, &req.symbol, cur_ts)?.depth;
+        if let Some(limit) = req.limit {
+            depth.bids.truncate(limit as usize);
+            depth.asks.truncate(limit as usize);
+        }
+        Ok(Some(depth))
+    }
+
+    async fn get_ticker_24hr(&self, _req: GetTicker24hrRequest) -> Result<Vec<Ticker24hr>> {
+        Ok(vec![])
+    }
+
+    async fn get_exchange_info(&self, req: GetExchangeInfoRequest) -> Result<ExchangeInfo> {
+        let mut symbols = get_all_symbol_info(self.db.clone(), &self.market_type)?;
+        if let Some(symbol) = req.symbol {
+            symbols.retain(|info| info.symbol == symbol);
+        }
+        if let Some(filter) = req.symbols {
+            symbols.retain(|info| filter.contains(&info.symbol));
+        }
+        Ok(ExchangeInfo { symbols })
+    }
+
+    fn subscribe_kline(&self) -> EventReceiver<KlineData> {
+        self.kline_sender.subscribe()
+    }
+
+    fn subscribe_trade(&self) -> EventReceiver<Trade> {
+        self.trade_sender.subscribe()
+    }
+
+    fn subscribe_depth(&self) -> EventReceiver<DepthData> {
+        self.depth_sender.subscribe()
+    }
+
+    fn subscribe_ticker(&self) -> EventReceiver<Ticker24hr> {
+        self.ticker_sender.subscribe()
+    }
+}
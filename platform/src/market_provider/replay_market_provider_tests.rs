@@ -0,0 +1,361 @@
+use crate::{
+    backtest::test_utils::{symbol_info, test_config},
+    config::PlatformConfig,
+    data_manager::{db::*, local_data_manager::Clock, market_data::MarketData, MarketDataManager},
+    market_provider::{replay_market_provider::ReplayMarketProvider, MarketProvider},
+    models::{
+        DepthDiff, DepthSnapshot, GetKlinesRequest, GetTradesRequest, KlineData, KlineInterval,
+        MarketType, PriceLevel, Trade,
+    },
+};
+use db::sqlite::SQLiteDB;
+use rust_decimal::Decimal;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tempfile::NamedTempFile;
+
+const SYMBOL: &str = "BTCUSDT";
+
+fn kline(open_time: u64) -> KlineData {
+    KlineData {
+        symbol: SYMBOL.to_string(),
+        interval: KlineInterval::OneMinute,
+        open_time,
+        close_time: open_time + 59_999,
+        open: Decimal::from(100),
+        high: Decimal::from(100),
+        low: Decimal::from(100),
+        close: Decimal::from(100),
+        volume: Decimal::ONE,
+        quote_volume: Decimal::from(100),
+        taker_buy_volume: Decimal::ZERO,
+        taker_buy_quote_volume: Decimal::ZERO,
+        is_closed: 1,
+    }
+}
+
+fn trade(seq_id: u64, timestamp: u64) -> Trade {
+    Trade {
+        symbol: SYMBOL.to_string(),
+        trade_id: seq_id.to_string(),
+        price: Decimal::from(100),
+        quantity: Decimal::ONE,
+        timestamp,
+        is_buyer_maker: 0,
+        seq_id,
+    }
+}
+
+fn level(price: u64, quantity: u64) -> PriceLevel {
+    PriceLevel {
+        price: Decimal::from(price),
+        quantity: Decimal::from(quantity),
+    }
+}
+
+struct TestEnv {
+    _db_file: NamedTempFile,
+    config: Arc<PlatformConfig>,
+    clock: Arc<Clock>,
+    provider: Arc<ReplayMarketProvider>,
+}
+
+// kline: 0..5分钟的1m bar；depth: 50s快照，70s和200s各一条增量
+fn setup(trades: &[Trade], cur_ts: u64) -> TestEnv {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    let market_type = MarketType::BinanceSpot;
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    create_depth_tables(db.clone()).unwrap();
+    update_symbol_info(
+        db.clone(),
+        &market_type,
+        &[symbol_info(SYMBOL, "BTC", "USDT")],
+    )
+    .unwrap();
+    let klines: Vec<KlineData> = (0..5).map(|i| kline(i * 60_000)).collect();
+    update_kline_data(db.clone(), &market_type, &klines).unwrap();
+    update_trade_data(db.clone(), &market_type, trades).unwrap();
+    update_depth_snapshot(
+        db.clone(),
+        &market_type,
+        &DepthSnapshot {
+            symbol: SYMBOL.to_string(),
+            last_update_id: 10,
+            bids: vec![level(99, 1)],
+            asks: vec![level(101, 1)],
+            timestamp: 50_000,
+        },
+    )
+    .unwrap();
+    let diffs: Vec<DepthDiff> = [(70_000, 98), (200_000, 97)]
+        .iter()
+        .enumerate()
+        .map(|(i, (timestamp, bid))| DepthDiff {
+            symbol: SYMBOL.to_string(),
+            first_update_id: 11 + i as u64,
+            last_update_id: 11 + i as u64,
+            bids: vec![level(*bid, 2)],
+            asks: vec![],
+            timestamp: *timestamp,
+        })
+        .collect();
+    update_depth_diffs(db.clone(), &market_type, &diffs).unwrap();
+
+    let config = test_config(
+        db_file.path().to_str().unwrap(),
+        "[\"BTCUSDT\"]",
+        "[\"1m\"]",
+    );
+    let clock = Arc::new(Clock::new(cur_ts));
+    let provider = Arc::new(ReplayMarketProvider::new(
+        config.configs[&market_type].clone(),
+        market_type,
+        db,
+        clock.clone(),
+    ));
+    clock.register_hook(provider.clone());
+    TestEnv {
+        _db_file: db_file,
+        config,
+        clock,
+        provider,
+    }
+}
+
+#[tokio::test]
+async fn test_replay_emits_events_in_timestamp_order() {
+    // 超过一页的成交，每100ms一笔
+    let trades: Vec<Trade> = (1..=1500).map(|i| trade(i, 60_000 + i * 100)).collect();
+    let env = setup(&trades, 60_000);
+    let mut kline_sub = env.provider.subscribe_kline();
+    let mut trade_sub = env.provider.subscribe_trade();
+    let mut depth_sub = env.provider.subscribe_depth();
+
+    for ts in [150_000, 150_000, 250_000] {
+        env.clock.advance_to(ts).await.unwrap();
+    }
+
+    let mut replayed_trades = vec![];
+    while let Ok(Ok(trade)) =
+        tokio::time::timeout(Duration::from_millis(100), trade_sub.recv()).await
+    {
+        replayed_trades.push(trade);
+    }
+    // 起始时间前的成交不推送，重复推进同一时间不重复推送
+    assert_eq!(replayed_trades.len(), 1500);
+    assert!(replayed_trades
+        .windows(2)
+        .all(|w| w[0].timestamp <= w[1].timestamp && w[0].seq_id + 1 == w[1].seq_id));
+
+    // 只推送推进区间内收盘的bar
+    let mut close_times = vec![];
+    while let Ok(Ok(kline)) =
+        tokio::time::timeout(Duration::from_millis(100), kline_sub.recv()).await
+    {
+        close_times.push(kline.close_time);
+    }
+    assert_eq!(close_times, vec![119_999, 179_999, 239_999]);
+
+    // 每次推进最多推送一次盘口，时间为最后一条增量的时间
+    let mut depths = vec![];
+    while let Ok(Ok(depth)) =
+        tokio::time::timeout(Duration::from_millis(100), depth_sub.recv()).await
+    {
+        depths.push(depth);
+    }
+    assert_eq!(
+        depths.iter().map(|d| d.timestamp).collect::<Vec<_>>(),
+        vec![70_000, 200_000]
+    );
+    assert_eq!(
+        depths[1]
+            .bids
+            .iter()
+            .map(|l| (l.price, l.quantity))
+            .collect::<Vec<_>>(),
+        [(99, 1), (98, 2), (97, 2)]
+            .map(|(p, q)| (Decimal::from(p), Decimal::from(q)))
+            .to_vec()
+    );
+
+    // REST接口不返回clock之后的数据
+    env.clock.set_cur_ts(100_000);
+    let trades = env
+        .provider
+        .get_trades(GetTradesRequest {
+            symbol: SYMBOL.to_string(),
+            from_id: Some("390".to_string()),
+            start_time: None,
+            end_time: None,
+            limit: Some(100),
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        trades.iter().map(|t| t.seq_id).collect::<Vec<_>>(),
+        (390..=400).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_market_data_caches_fill_from_replay() {
+    let market_type = MarketType::BinanceSpot;
+    let symbol = SYMBOL.to_string();
+    let interval = KlineInterval::OneMinute;
+    // 每3s一笔，共50笔
+    let trades: Vec<Trade> = (1..=50).map(|i| trade(i, 60_000 + i * 3000)).collect();
+    let env = setup(&trades, 120_000);
+
+    let mut market_providers: HashMap<MarketType, Arc<dyn MarketProvider>> = HashMap::new();
+    market_providers.insert(market_type.clone(), env.provider.clone());
+    let market_data = MarketData::new(env.config.clone(), Arc::new(market_providers)).unwrap();
+    market_data.init().await.unwrap();
+
+    // 初始化时只加载clock之前的历史
+    let open_times =
+        |klines: Vec<KlineData>| klines.iter().map(|k| k.open_time).collect::<Vec<_>>();
+    assert_eq!(
+        open_times(
+            market_data
+                .get_klines(&market_type, &symbol, &interval, None)
+                .await
+                .unwrap()
+        ),
+        vec![0, 60_000]
+    );
+    let cached = market_data
+        .get_trades(&market_type, &symbol, None)
+        .await
+        .unwrap();
+    assert_eq!(cached.len(), 20);
+    assert_eq!(cached.last().unwrap().timestamp, 120_000);
+    let depth = market_data
+        .get_depth(&market_type, &symbol)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(depth.timestamp, 70_000);
+
+    // 推进后回放的事件写入cache
+    env.clock.advance_to(250_000).await.unwrap();
+    for _ in 0..100 {
+        let trades = market_data
+            .get_trades(&market_type, &symbol, None)
+            .await
+            .unwrap();
+        let depth = market_data.get_depth(&market_type, &symbol).await.unwrap();
+        let klines = market_data
+            .get_klines(&market_type, &symbol, &interval, None)
+            .await
+            .unwrap();
+        if trades.len() == 50 && depth.is_some_and(|d| d.timestamp == 200_000) && klines.len() == 4
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let cached = market_data
+        .get_trades(&market_type, &symbol, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        cached.iter().map(|t| t.seq_id).collect::<Vec<_>>(),
+        (1..=50).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        open_times(
+            market_data
+                .get_klines(&market_type, &symbol, &interval, None)
+                .await
+                .unwrap()
+        ),
+        vec![0, 60_000, 120_000, 180_000]
+    );
+    let depth = market_data
+        .get_depth(&market_type, &symbol)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(depth.timestamp, 200_000);
+    assert_eq!(depth.bids.len(), 3);
+}
+
+#[tokio::test]
+async fn test_monthly_klines_follow_calendar_months() {
+    let db_file = NamedTempFile::new().unwrap();
+    let db = Arc::new(SQLiteDB::new(db_file.path().to_str().unwrap()).unwrap());
+    let market_type = MarketType::BinanceSpot;
+    create_symbol_info_table(db.clone()).unwrap();
+    create_kline_table(db.clone()).unwrap();
+    create_trade_table(db.clone()).unwrap();
+    create_depth_tables(db.clone()).unwrap();
+    update_symbol_info(
+        db.clone(),
+        &market_type,
+        &[symbol_info(SYMBOL, "BTC", "USDT")],
+    )
+    .unwrap();
+    // 2024-01起的5根月线，1月与3月为31天
+    let month = KlineInterval::OneMonth;
+    let jan_2024 = 1_704_067_200_000;
+    let mut open_times = vec![jan_2024];
+    for _ in 1..5 {
+        open_times.push(month.next_open_time(*open_times.last().unwrap()));
+    }
+    let klines: Vec<KlineData> = open_times
+        .iter()
+        .map(|&open_time| KlineData {
+            interval: KlineInterval::OneMonth,
+            close_time: month.next_open_time(open_time) - 1,
+            ..kline(open_time)
+        })
+        .collect();
+    update_kline_data(db.clone(), &market_type, &klines).unwrap();
+
+    let config = test_config(
+        db_file.path().to_str().unwrap(),
+        "[\"BTCUSDT\"]",
+        "[\"1M\"]",
+    );
+    // 1月31日12点，距1月开盘已超过30天
+    let day = 24 * 60 * 60 * 1000;
+    let clock = Arc::new(Clock::new(jan_2024 + 30 * day + day / 2));
+    let provider = Arc::new(ReplayMarketProvider::new(
+        config.configs[&market_type].clone(),
+        market_type,
+        db,
+        clock.clone(),
+    ));
+    clock.register_hook(provider.clone());
+    let mut kline_sub = provider.subscribe_kline();
+
+    // 推进跨过1月收盘，1月的bar被推送
+    clock.advance_to(open_times[1] + 60_000).await.unwrap();
+    let mut close_times = vec![];
+    while let Ok(Ok(kline)) =
+        tokio::time::timeout(Duration::from_millis(100), kline_sub.recv()).await
+    {
+        close_times.push(kline.close_time);
+    }
+    assert_eq!(close_times, vec![open_times[1] - 1]);
+
+    // 3月31日12点，3月的bar尚未收盘
+    clock.set_cur_ts(open_times[3] - day / 2);
+    let klines = provider
+        .get_klines(GetKlinesRequest {
+            symbol: SYMBOL.to_string(),
+            interval: KlineInterval::OneMonth,
+            start_time: None,
+            end_time: None,
+            limit: None,
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        klines.iter().map(|k| k.open_time).collect::<Vec<_>>(),
+        open_times[..2].to_vec()
+    );
+}
@@ -73,10 +73,13 @@ pub enum OrderType {
     TakeProfit,
     TakeProfitLimit,
     LimitMaker,
+    // 交易所返回的未知类型，保留原始值
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl OrderType {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             OrderType::Limit => "LIMIT",
             OrderType::Market => "MARKET",
@@ -85,6 +88,7 @@ impl OrderType {
             OrderType::TakeProfit => "TAKE_PROFIT",
             OrderType::TakeProfitLimit => "TAKE_PROFIT_LIMIT",
             OrderType::LimitMaker => "LIMIT_MAKER",
+            OrderType::Unknown(s) => s,
         }
     }
 }
@@ -101,10 +105,26 @@ pub enum OrderStatus {
     Rejected,
     Expired,
     ExpiredInMatch,
+    // 交易所返回的未知状态，保留原始值，不视为终态
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl OrderStatus {
-    pub fn as_str(&self) -> &'static str {
+    // 除Unknown外的全部状态
+    pub const KNOWN: &'static [OrderStatus] = &[
+        OrderStatus::New,
+        OrderStatus::PendingNew,
+        OrderStatus::PartiallyFilled,
+        OrderStatus::Filled,
+        OrderStatus::Canceled,
+        OrderStatus::PendingCancel,
+        OrderStatus::Rejected,
+        OrderStatus::Expired,
+        OrderStatus::ExpiredInMatch,
+    ];
+
+    pub fn as_str(&self) -> &str {
         match self {
             OrderStatus::New => "NEW",
             OrderStatus::PendingNew => "PENDING_NEW",
@@ -115,8 +135,21 @@ impl OrderStatus {
             OrderStatus::Rejected => "REJECTED",
             OrderStatus::Expired => "EXPIRED",
             OrderStatus::ExpiredInMatch => "EXPIRED_IN_MATCH",
+            OrderStatus::Unknown(s) => s,
         }
     }
+
+    // 终态订单不会再变为在途状态
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Canceled
+                | OrderStatus::Rejected
+                | OrderStatus::Expired
+                | OrderStatus::ExpiredInMatch
+        )
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -125,14 +158,18 @@ pub enum TimeInForce {
     Gtc,
     Ioc,
     Fok,
+    // 交易所返回的未知类型，保留原始值
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 impl TimeInForce {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             TimeInForce::Gtc => "GTC",
             TimeInForce::Ioc => "IOC",
             TimeInForce::Fok => "FOK",
+            TimeInForce::Unknown(s) => s,
         }
     }
 }
@@ -173,7 +210,35 @@ pub enum KlineInterval {
     OneMonth,
 }
 
+const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
+
+// 1970-01-01起的天数转换为(年, 月, 日)，算法见 http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// civil_from_days的逆运算
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = month as i64;
+    let doy = (153 * if month > 2 { month - 3 } else { month + 9 } + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
 impl KlineInterval {
+    // 固定的周期长度，1M按30天计，需要准确的bar边界时使用is_aligned/next_open_time/prev_open_time
     pub fn to_millis(&self) -> u64 {
         match self {
             KlineInterval::OneSecond => 1000,
@@ -195,6 +260,60 @@ impl KlineInterval {
         }
     }
 
+    // open_time是否为bar的起始时间：日内周期按epoch整除；1d、3d为UTC零点；
+    // 1w为周一UTC零点；1M为每月1日UTC零点（3d的起点交易所未说明，只校验UTC零点）
+    pub fn is_aligned(&self, open_time: u64) -> bool {
+        match self {
+            KlineInterval::OneDay | KlineInterval::ThreeDays => {
+                open_time.is_multiple_of(DAY_MILLIS)
+            }
+            // 1970-01-01为周四
+            KlineInterval::OneWeek => {
+                open_time.is_multiple_of(DAY_MILLIS)
+                    && (open_time / DAY_MILLIS + 3).is_multiple_of(7)
+            }
+            KlineInterval::OneMonth => {
+                open_time.is_multiple_of(DAY_MILLIS)
+                    && civil_from_days((open_time / DAY_MILLIS) as i64).2 == 1
+            }
+            _ => open_time.is_multiple_of(self.to_millis()),
+        }
+    }
+
+    // 下一根bar的open_time，1M为下月1日UTC零点
+    pub fn next_open_time(&self, open_time: u64) -> u64 {
+        match self {
+            KlineInterval::OneMonth => {
+                let (year, month, _) = civil_from_days((open_time / DAY_MILLIS) as i64);
+                let (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                days_from_civil(year, month, 1) as u64 * DAY_MILLIS
+            }
+            _ => open_time + self.to_millis(),
+        }
+    }
+
+    // 上一根bar的open_time，1M为上月1日UTC零点；open_time之前已没有完整bar时返回None
+    pub fn prev_open_time(&self, open_time: u64) -> Option<u64> {
+        match self {
+            KlineInterval::OneMonth => {
+                let (year, month, _) = civil_from_days((open_time / DAY_MILLIS) as i64);
+                let (year, month) = if month == 1 {
+                    (year - 1, 12)
+                } else {
+                    (year, month - 1)
+                };
+                u64::try_from(days_from_civil(year, month, 1))
+                    .ok()
+                    .map(|days| days * DAY_MILLIS)
+            }
+            _ => open_time.checked_sub(self.to_millis()),
+        }
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             KlineInterval::OneSecond => "1s",
@@ -238,3 +357,11 @@ impl KlineInterval {
         }
     }
 }
+
+// 获取kline时缺失bar的处理方式
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum GapPolicy {
+    Sparse, // 缺失位置返回None
+    Dense,  // 用前一根bar填充，标记为synthetic
+    Skip,   // 忽略缺失位置，只返回实际存在的bar
+}
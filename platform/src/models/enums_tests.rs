@@ -0,0 +1,120 @@
+use crate::models::{KlineInterval, OrderStatus};
+
+const DAY: u64 = 24 * 60 * 60 * 1000;
+const MONDAY_2024_01_01: u64 = 1_704_067_200_000;
+
+#[test]
+fn test_weekly_kline_aligned_to_monday() {
+    let week = KlineInterval::OneWeek;
+    // 2024-01-01为周一，按epoch整除周长不成立
+    assert_ne!(MONDAY_2024_01_01 % week.to_millis(), 0);
+    assert!(week.is_aligned(MONDAY_2024_01_01));
+    assert!(week.is_aligned(MONDAY_2024_01_01 + 7 * DAY));
+    assert_eq!(
+        week.next_open_time(MONDAY_2024_01_01),
+        MONDAY_2024_01_01 + 7 * DAY
+    );
+
+    // epoch整除周长的时间为周四
+    let thursday = MONDAY_2024_01_01 / week.to_millis() * week.to_millis();
+    assert_eq!(MONDAY_2024_01_01 - thursday, 4 * DAY);
+    assert!(!week.is_aligned(thursday));
+    assert!(!week.is_aligned(MONDAY_2024_01_01 + 60_000));
+}
+
+#[test]
+fn test_daily_and_monthly_alignment() {
+    assert!(KlineInterval::OneDay.is_aligned(MONDAY_2024_01_01 + DAY));
+    assert!(!KlineInterval::OneDay.is_aligned(MONDAY_2024_01_01 + 8 * 60 * 60 * 1000));
+    assert!(KlineInterval::ThreeDays.is_aligned(MONDAY_2024_01_01 + DAY));
+
+    let month = KlineInterval::OneMonth;
+    let feb_2024 = MONDAY_2024_01_01 + 31 * DAY;
+    assert!(month.is_aligned(MONDAY_2024_01_01));
+    assert!(month.is_aligned(feb_2024));
+    assert!(!month.is_aligned(MONDAY_2024_01_01 + 30 * DAY));
+    // 闰年2月29天，12月跨年
+    assert_eq!(month.next_open_time(feb_2024), feb_2024 + 29 * DAY);
+    assert_eq!(
+        month.next_open_time(MONDAY_2024_01_01 - 31 * DAY),
+        MONDAY_2024_01_01
+    );
+    assert!(month.is_aligned(0));
+    assert_eq!(month.next_open_time(0), 31 * DAY);
+}
+
+#[test]
+fn test_intraday_alignment_by_epoch() {
+    let hour = 60 * 60 * 1000;
+    assert!(KlineInterval::FourHours.is_aligned(MONDAY_2024_01_01 + 4 * hour));
+    assert!(!KlineInterval::FourHours.is_aligned(MONDAY_2024_01_01 + hour));
+    assert!(KlineInterval::OneMinute.is_aligned(60_000));
+    assert!(!KlineInterval::OneMinute.is_aligned(59_999));
+    assert_eq!(
+        KlineInterval::FifteenMinutes.next_open_time(MONDAY_2024_01_01),
+        MONDAY_2024_01_01 + 15 * 60 * 1000
+    );
+}
+
+#[test]
+fn test_month_boundaries_cover_every_day() {
+    // 从1970-01起逐月推进到2100年，每月长度在28到31天之间且起点都是1日
+    let month = KlineInterval::OneMonth;
+    let mut open_time = 0;
+    for _ in 0..(130 * 12) {
+        let next = month.next_open_time(open_time);
+        assert!((28 * DAY..=31 * DAY).contains(&(next - open_time)));
+        assert!(month.is_aligned(next));
+        for day in 1..(next - open_time) / DAY {
+            assert!(!month.is_aligned(open_time + day * DAY));
+        }
+        open_time = next;
+    }
+    // 2100-01-01
+    assert_eq!(open_time, 4_102_444_800_000);
+}
+
+#[test]
+fn test_prev_open_time_inverts_next_open_time() {
+    let month = KlineInterval::OneMonth;
+    let mut open_time = 0;
+    for _ in 0..(130 * 12) {
+        let next = month.next_open_time(open_time);
+        assert_eq!(month.prev_open_time(next), Some(open_time));
+        // 月中任意时刻的上一根bar为上月1日
+        assert_eq!(month.prev_open_time(next + 15 * DAY), Some(open_time));
+        open_time = next;
+    }
+    assert_eq!(month.prev_open_time(0), None);
+    assert_eq!(month.prev_open_time(31 * DAY - 1), None);
+
+    let minute = KlineInterval::OneMinute;
+    assert_eq!(minute.prev_open_time(120_000), Some(60_000));
+    assert_eq!(minute.prev_open_time(59_999), None);
+}
+
+#[test]
+fn test_known_order_statuses() {
+    // KNOWN与serde名称一致，覆盖全部非Unknown状态
+    for status in OrderStatus::KNOWN {
+        let parsed: OrderStatus =
+            serde_json::from_str(&format!("\"{}\"", status.as_str())).unwrap();
+        assert_eq!(&parsed, status);
+    }
+    let terminal = OrderStatus::KNOWN
+        .iter()
+        .filter(|status| status.is_terminal())
+        .map(|status| status.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        terminal,
+        vec![
+            "FILLED",
+            "CANCELED",
+            "REJECTED",
+            "EXPIRED",
+            "EXPIRED_IN_MATCH"
+        ]
+    );
+    assert!(!OrderStatus::Unknown("FILLED_LATER".to_string()).is_terminal());
+}
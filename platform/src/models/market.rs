@@ -1,6 +1,7 @@
 use crate::models::{KlineInterval, SymbolStatus};
-use rust_decimal::Decimal;
+use rust_decimal::{Decimal, RoundingStrategy};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KlineData {
@@ -19,6 +20,13 @@ pub struct KlineData {
     pub is_closed: u64,
 }
 
+// 按GapPolicy处理后的kline，synthetic为true表示由前值填充的bar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GapFilledKline {
+    pub kline: KlineData,
+    pub synthetic: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ticker24hr {
     pub symbol: String,
@@ -52,6 +60,65 @@ pub struct DepthData {
     pub timestamp: u64,
 }
 
+impl DepthData {
+    // 按bin_size合并价格档位，同一价格区间的数量相加
+    // 买单向下、卖单向上取整到区间边界，合并后买一不高于原买一、卖一不低于原卖一，不会交叉
+    // bin_size不大于0时原样返回
+    pub fn aggregate(&self, bin_size: Decimal) -> DepthData {
+        if bin_size <= Decimal::ZERO {
+            return self.clone();
+        }
+        let merge = |levels: &[PriceLevel], strategy: RoundingStrategy| {
+            let mut bins: BTreeMap<Decimal, Decimal> = BTreeMap::new();
+            for level in levels {
+                let price = ((level.price / bin_size).round_dp_with_strategy(0, strategy)
+                    * bin_size)
+                    .normalize();
+                *bins.entry(price).or_default() += level.quantity;
+            }
+            bins.into_iter()
+                .map(|(price, quantity)| PriceLevel { price, quantity })
+        };
+        DepthData {
+            symbol: self.symbol.clone(),
+            bids: merge(&self.bids, RoundingStrategy::ToNegativeInfinity)
+                .rev()
+                .collect(),
+            asks: merge(&self.asks, RoundingStrategy::ToPositiveInfinity).collect(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+// 持久化的盘口快照，last_update_id用于衔接增量
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthSnapshot {
+    pub symbol: String,
+    pub last_update_id: u64,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+    pub timestamp: u64,
+}
+
+// 持久化的盘口增量，quantity为0表示删除该档
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthDiff {
+    pub symbol: String,
+    pub first_update_id: u64,
+    pub last_update_id: u64,
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+    pub timestamp: u64,
+}
+
+// 回放得到的历史盘口，stale为true表示增量缺失，depth为回放起点的快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayedDepth {
+    pub depth: DepthData,
+    pub last_update_id: u64,
+    pub stale: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trade {
     pub symbol: String,
@@ -95,7 +162,196 @@ pub struct SymbolInfo {
     pub min_notional: Option<Decimal>,
 }
 
+impl SymbolInfo {
+    // 按price_tick_size四舍五入，未配置时原样返回
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        self.round_price_with(price, RoundingStrategy::MidpointAwayFromZero)
+    }
+
+    // 按price_tick_size以指定方式取整，如买单向下、卖单向上
+    pub fn round_price_with(&self, price: Decimal, strategy: RoundingStrategy) -> Decimal {
+        Self::round_to_step(price, self.price_tick_size, strategy)
+    }
+
+    // 按quantity_step_size向下取整，保证不超过可用数量
+    pub fn round_quantity(&self, quantity: Decimal) -> Decimal {
+        Self::round_to_step(
+            quantity,
+            self.quantity_step_size,
+            RoundingStrategy::ToNegativeInfinity,
+        )
+    }
+
+    // 市价单按market_quantity_step_size向下取整，未配置时按quantity_step_size
+    pub fn round_market_quantity(&self, quantity: Decimal) -> Decimal {
+        Self::round_to_step(
+            quantity,
+            self.market_quantity_step_size.or(self.quantity_step_size),
+            RoundingStrategy::ToNegativeInfinity,
+        )
+    }
+
+    fn round_to_step(value: Decimal, step: Option<Decimal>, strategy: RoundingStrategy) -> Decimal {
+        match step {
+            Some(step) if step > Decimal::ZERO => {
+                ((value / step).round_dp_with_strategy(0, strategy) * step).normalize()
+            }
+            _ => value,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExchangeInfo {
     pub symbols: Vec<SymbolInfo>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::test_utils::unfiltered_symbol_info;
+    use rust_decimal_macros::dec;
+
+    fn symbol_info(
+        price_tick_size: Option<Decimal>,
+        quantity_step_size: Option<Decimal>,
+    ) -> SymbolInfo {
+        SymbolInfo {
+            price_tick_size,
+            quantity_step_size,
+            ..unfiltered_symbol_info("BTCUSDT", "BTC", "USDT")
+        }
+    }
+
+    #[test]
+    fn test_round_price() {
+        // 交易所返回的tick size带尾随0
+        let info = symbol_info(Some(dec!(0.01000000)), None);
+        assert_eq!(info.round_price(dec!(65432.104)).to_string(), "65432.1");
+        assert_eq!(info.round_price(dec!(65432.105)).to_string(), "65432.11");
+        assert_eq!(info.round_price(dec!(65432.1)).to_string(), "65432.1");
+        assert_eq!(
+            info.round_price_with(dec!(65432.109), RoundingStrategy::ToNegativeInfinity),
+            dec!(65432.10)
+        );
+        assert_eq!(
+            info.round_price_with(dec!(65432.101), RoundingStrategy::ToPositiveInfinity),
+            dec!(65432.11)
+        );
+
+        let info = symbol_info(Some(dec!(0.0001)), None);
+        assert_eq!(info.round_price(dec!(0.12345)), dec!(0.1235));
+        assert_eq!(info.round_price(dec!(0.12344999)), dec!(0.1234));
+        // 非10的幂的tick size
+        let info = symbol_info(Some(dec!(0.05)), None);
+        assert_eq!(info.round_price(dec!(1.07)), dec!(1.05));
+        assert_eq!(info.round_price(dec!(1.08)), dec!(1.10));
+
+        // 0.1 + 0.2 在浮点下有误差，Decimal按tick取整后精确
+        let info = symbol_info(Some(dec!(0.1)), None);
+        assert_eq!(info.round_price(dec!(0.1) + dec!(0.2)).to_string(), "0.3");
+    }
+
+    #[test]
+    fn test_round_quantity() {
+        let info = symbol_info(None, Some(dec!(0.00001000)));
+        assert_eq!(
+            info.round_quantity(dec!(0.123456789)).to_string(),
+            "0.12345"
+        );
+        assert_eq!(info.round_quantity(dec!(0.12345)), dec!(0.12345));
+        assert_eq!(info.round_quantity(dec!(0.000009)), Decimal::ZERO);
+
+        let info = symbol_info(None, Some(dec!(0.0001)));
+        assert_eq!(info.round_quantity(dec!(2.99999)), dec!(2.9999));
+        assert_eq!(info.round_quantity(dec!(3) / dec!(7)), dec!(0.4285));
+
+        let info = symbol_info(None, Some(dec!(1)));
+        assert_eq!(info.round_quantity(dec!(12.9)), dec!(12));
+    }
+
+    #[test]
+    fn test_round_without_filters() {
+        let info = symbol_info(None, None);
+        assert_eq!(info.round_price(dec!(1.23456789)), dec!(1.23456789));
+        assert_eq!(info.round_quantity(dec!(1.23456789)), dec!(1.23456789));
+        // 交易所返回0表示无限制
+        let info = symbol_info(Some(Decimal::ZERO), Some(Decimal::ZERO));
+        assert_eq!(info.round_price(dec!(1.23456789)), dec!(1.23456789));
+        assert_eq!(info.round_quantity(dec!(1.23456789)), dec!(1.23456789));
+    }
+
+    fn levels(levels: &[(Decimal, Decimal)]) -> Vec<PriceLevel> {
+        levels
+            .iter()
+            .map(|(price, quantity)| PriceLevel {
+                price: *price,
+                quantity: *quantity,
+            })
+            .collect()
+    }
+
+    fn prices(levels: &[PriceLevel]) -> Vec<(Decimal, Decimal)> {
+        levels.iter().map(|l| (l.price, l.quantity)).collect()
+    }
+
+    #[test]
+    fn test_depth_aggregate() {
+        let depth = DepthData {
+            symbol: "BTCUSDT".to_string(),
+            bids: levels(&[
+                (dec!(100.09), dec!(1)),
+                (dec!(100.00), dec!(2)),
+                (dec!(99.95), dec!(0.5)),
+                (dec!(99.90), dec!(3)),
+                (dec!(99.71), dec!(1.25)),
+            ]),
+            asks: levels(&[
+                (dec!(100.11), dec!(1)),
+                (dec!(100.20), dec!(2)),
+                (dec!(100.21), dec!(0.5)),
+                (dec!(100.35), dec!(4)),
+            ]),
+            timestamp: 1000,
+        };
+
+        let aggregated = depth.aggregate(dec!(0.1));
+        assert_eq!(aggregated.symbol, "BTCUSDT");
+        assert_eq!(aggregated.timestamp, 1000);
+        // 买单向下取整：100.09/100.00 -> 100，99.95/99.90 -> 99.9，边界价格保持在本区间
+        assert_eq!(
+            prices(&aggregated.bids),
+            vec![
+                (dec!(100), dec!(3)),
+                (dec!(99.9), dec!(3.5)),
+                (dec!(99.7), dec!(1.25)),
+            ]
+        );
+        // 卖单向上取整：100.11/100.20 -> 100.2，100.21/100.35 -> 100.3/100.4
+        assert_eq!(
+            prices(&aggregated.asks),
+            vec![
+                (dec!(100.2), dec!(3)),
+                (dec!(100.3), dec!(0.5)),
+                (dec!(100.4), dec!(4)),
+            ]
+        );
+        // 合并前后总量不变
+        let total = |levels: &[PriceLevel]| levels.iter().map(|l| l.quantity).sum::<Decimal>();
+        assert_eq!(total(&aggregated.bids), total(&depth.bids));
+        assert_eq!(total(&aggregated.asks), total(&depth.asks));
+
+        // 较大区间
+        let aggregated = depth.aggregate(dec!(0.5));
+        assert_eq!(
+            prices(&aggregated.bids),
+            vec![(dec!(100), dec!(3)), (dec!(99.5), dec!(4.75))]
+        );
+        assert_eq!(prices(&aggregated.asks), vec![(dec!(100.5), dec!(7.5))]);
+
+        // bin_size非法时原样返回
+        let aggregated = depth.aggregate(Decimal::ZERO);
+        assert_eq!(prices(&aggregated.bids), prices(&depth.bids));
+        assert_eq!(prices(&aggregated.asks), prices(&depth.asks));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::models::KlineInterval;
+use crate::{
+    errors::{PlatformError, Result},
+    models::KlineInterval,
+};
 
 #[derive(Debug, Clone)]
 pub struct GetKlinesRequest {
@@ -35,3 +38,152 @@ pub struct GetTicker24hrRequest {
     pub symbol: Option<String>,
     pub symbols: Option<Vec<String>>,
 }
+
+// 交易所单次请求返回的最大条数
+pub const MAX_REQUEST_LIMIT: u32 = 1000;
+
+pub(crate) fn required_field<T>(value: Option<T>, request: &str, field: &str) -> Result<T> {
+    value.ok_or_else(|| PlatformError::ValidationError {
+        message: format!("{}: {} is required", request, field),
+    })
+}
+
+fn check_limit(limit: Option<u32>, request: &str) -> Result<()> {
+    match limit {
+        Some(limit) if limit == 0 || limit > MAX_REQUEST_LIMIT => {
+            Err(PlatformError::ValidationError {
+                message: format!(
+                    "{}: limit {} out of range [1, {}]",
+                    request, limit, MAX_REQUEST_LIMIT
+                ),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_time_range(start_time: Option<u64>, end_time: Option<u64>, request: &str) -> Result<()> {
+    match (start_time, end_time) {
+        (Some(start_time), Some(end_time)) if start_time > end_time => {
+            Err(PlatformError::ValidationError {
+                message: format!(
+                    "{}: start_time {} is after end_time {}",
+                    request, start_time, end_time
+                ),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+impl GetKlinesRequest {
+    pub fn builder() -> GetKlinesRequestBuilder {
+        GetKlinesRequestBuilder::default()
+    }
+}
+
+// symbol、interval必填，build时校验limit与时间范围
+#[derive(Debug, Clone, Default)]
+pub struct GetKlinesRequestBuilder {
+    symbol: Option<String>,
+    interval: Option<KlineInterval>,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+    limit: Option<u32>,
+}
+
+impl GetKlinesRequestBuilder {
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn interval(mut self, interval: KlineInterval) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    pub fn start_time(mut self, start_time: u64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    pub fn end_time(mut self, end_time: u64) -> Self {
+        self.end_time = Some(end_time);
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn build(self) -> Result<GetKlinesRequest> {
+        let request = "GetKlinesRequest";
+        check_limit(self.limit, request)?;
+        check_time_range(self.start_time, self.end_time, request)?;
+        Ok(GetKlinesRequest {
+            symbol: required_field(self.symbol, request, "symbol")?,
+            interval: required_field(self.interval, request, "interval")?,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            limit: self.limit,
+        })
+    }
+}
+
+impl GetTradesRequest {
+    pub fn builder() -> GetTradesRequestBuilder {
+        GetTradesRequestBuilder::default()
+    }
+}
+
+// symbol必填，build时校验limit与时间范围
+#[derive(Debug, Clone, Default)]
+pub struct GetTradesRequestBuilder {
+    symbol: Option<String>,
+    from_id: Option<String>,
+    start_time: Option<u64>,
+    end_time: Option<u64>,
+    limit: Option<u32>,
+}
+
+impl GetTradesRequestBuilder {
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn from_id(mut self, from_id: impl Into<String>) -> Self {
+        self.from_id = Some(from_id.into());
+        self
+    }
+
+    pub fn start_time(mut self, start_time: u64) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    pub fn end_time(mut self, end_time: u64) -> Self {
+        self.end_time = Some(end_time);
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn build(self) -> Result<GetTradesRequest> {
+        let request = "GetTradesRequest";
+        check_limit(self.limit, request)?;
+        check_time_range(self.start_time, self.end_time, request)?;
+        Ok(GetTradesRequest {
+            symbol: required_field(self.symbol, request, "symbol")?,
+            from_id: self.from_id,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            limit: self.limit,
+        })
+    }
+}
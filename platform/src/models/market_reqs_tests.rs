@@ -0,0 +1,83 @@
+use crate::{
+    errors::PlatformError,
+    models::{GetKlinesRequest, GetTradesRequest, KlineInterval, MAX_REQUEST_LIMIT},
+};
+
+#[test]
+fn test_klines_builder_sets_fields() {
+    let req = GetKlinesRequest::builder()
+        .symbol("BTCUSDT")
+        .interval(KlineInterval::OneHour)
+        .start_time(1000)
+        .end_time(1000)
+        .limit(MAX_REQUEST_LIMIT)
+        .build()
+        .unwrap();
+    assert_eq!(req.symbol, "BTCUSDT");
+    assert_eq!(req.interval, KlineInterval::OneHour);
+    assert_eq!((req.start_time, req.end_time), (Some(1000), Some(1000)));
+    assert_eq!(req.limit, Some(1000));
+
+    // 未设置的可选字段为None
+    let req = GetKlinesRequest::builder()
+        .symbol("BTCUSDT")
+        .interval(KlineInterval::OneMinute)
+        .build()
+        .unwrap();
+    assert_eq!(
+        (req.start_time, req.end_time, req.limit),
+        (None, None, None)
+    );
+}
+
+#[test]
+fn test_klines_builder_rejects_invalid_request() {
+    let builder = || {
+        GetKlinesRequest::builder()
+            .symbol("BTCUSDT")
+            .interval(KlineInterval::OneMinute)
+    };
+    let err = builder()
+        .start_time(2000)
+        .end_time(1000)
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, PlatformError::ValidationError { .. }));
+    assert!(err
+        .to_string()
+        .contains("start_time 2000 is after end_time 1000"));
+
+    let err = builder().limit(MAX_REQUEST_LIMIT + 1).build().unwrap_err();
+    assert!(matches!(err, PlatformError::ValidationError { .. }));
+    assert!(err.to_string().contains("limit 1001"));
+    assert!(builder().limit(0).build().is_err());
+
+    let err = GetKlinesRequest::builder()
+        .symbol("BTCUSDT")
+        .build()
+        .unwrap_err();
+    assert!(err.to_string().contains("interval is required"));
+}
+
+#[test]
+fn test_trades_builder_validates() {
+    let req = GetTradesRequest::builder()
+        .symbol("BTCUSDT")
+        .from_id("100")
+        .limit(500)
+        .build()
+        .unwrap();
+    assert_eq!(req.from_id, Some("100".to_string()));
+    assert_eq!(req.limit, Some(500));
+
+    let builder = || GetTradesRequest::builder().symbol("BTCUSDT");
+    assert!(matches!(
+        builder().start_time(2000).end_time(1999).build(),
+        Err(PlatformError::ValidationError { .. })
+    ));
+    assert!(matches!(
+        builder().limit(5000).build(),
+        Err(PlatformError::ValidationError { .. })
+    ));
+    assert!(GetTradesRequest::builder().limit(10).build().is_err());
+}
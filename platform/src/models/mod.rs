@@ -3,12 +3,18 @@ pub use market::*;
 
 pub mod enums;
 pub use enums::*;
+#[cfg(test)]
+mod enums_tests;
 
 pub mod trade;
 pub use trade::*;
 
 pub mod market_reqs;
 pub use market_reqs::*;
+#[cfg(test)]
+mod market_reqs_tests;
 
 pub mod trade_reqs;
 pub use trade_reqs::*;
+#[cfg(test)]
+mod trade_reqs_tests;
@@ -17,6 +17,7 @@ pub struct Order {
     pub time_in_force: TimeInForce,
     pub stop_price: Decimal,
     pub iceberg_qty: Decimal,
+    pub quote_order_qty: Decimal, // 按quote金额下的市价买单，其余为0
     pub create_time: u64,
     pub update_time: u64,
 }
@@ -37,6 +38,7 @@ impl Order {
             time_in_force: req.time_in_force.clone().unwrap_or(TimeInForce::Gtc),
             stop_price: req.stop_price.unwrap_or_else(|| Decimal::new(0, 0)),
             iceberg_qty: req.iceberg_qty.unwrap_or_else(|| Decimal::new(0, 0)),
+            quote_order_qty: req.quote_order_qty.unwrap_or_else(|| Decimal::new(0, 0)),
             create_time: 0,
             update_time: 0,
         }
@@ -57,6 +59,92 @@ pub struct UserTrade {
     pub timestamp: u64,
 }
 
+// 由成交累计的仓位，quantity为正表示多头、为负表示空头
+// avg_entry_price与realized_pnl以quote资产计价，均已计入手续费
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Position {
+    pub symbol: String,
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub quantity: Decimal,
+    pub avg_entry_price: Decimal,
+    pub realized_pnl: Decimal,
+}
+
+impl Position {
+    pub fn new(symbol: &str, base_asset: &str, quote_asset: &str) -> Self {
+        Position {
+            symbol: symbol.to_string(),
+            base_asset: base_asset.to_string(),
+            quote_asset: quote_asset.to_string(),
+            quantity: Decimal::ZERO,
+            avg_entry_price: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+        }
+    }
+
+    // 加仓时更新持仓成本，减仓时计算已实现盈亏，反向成交超过持仓时先平仓再反向开仓
+    // 手续费为base资产时从成交数量中扣除，为quote资产时计入成交金额，其他资产（如BNB）不计入
+    pub fn apply_fill(&mut self, trade: &UserTrade) {
+        if trade.symbol != self.symbol {
+            return;
+        }
+        let is_buy = trade.order_side == OrderSide::Buy;
+        let notional = trade.trade_price * trade.trade_quantity;
+        // quantity: 仓位变化的绝对值，value: 支付（买）或收到（卖）的quote金额
+        let (quantity, value) = if trade.commission_asset == self.base_asset {
+            if is_buy {
+                (trade.trade_quantity - trade.commission, notional)
+            } else {
+                (trade.trade_quantity + trade.commission, notional)
+            }
+        } else if trade.commission_asset == self.quote_asset {
+            if is_buy {
+                (trade.trade_quantity, notional + trade.commission)
+            } else {
+                (trade.trade_quantity, notional - trade.commission)
+            }
+        } else {
+            (trade.trade_quantity, notional)
+        };
+        if quantity <= Decimal::ZERO {
+            return;
+        }
+        let sign = if is_buy { Decimal::ONE } else { -Decimal::ONE };
+
+        // 反向成交先平仓
+        let close_quantity =
+            if self.quantity.is_zero() || self.quantity.is_sign_positive() == is_buy {
+                Decimal::ZERO
+            } else {
+                self.quantity.abs().min(quantity)
+            };
+        if close_quantity > Decimal::ZERO {
+            let close_value = value * close_quantity / quantity;
+            let entry_value = self.avg_entry_price * close_quantity;
+            self.realized_pnl += if is_buy {
+                entry_value - close_value
+            } else {
+                close_value - entry_value
+            };
+            self.quantity += sign * close_quantity;
+            if self.quantity.is_zero() {
+                self.avg_entry_price = Decimal::ZERO;
+            }
+        }
+
+        // 剩余部分同向开仓/加仓
+        let open_quantity = quantity - close_quantity;
+        if open_quantity > Decimal::ZERO {
+            let open_value = value * open_quantity / quantity;
+            let cur_quantity = self.quantity.abs();
+            self.avg_entry_price =
+                (self.avg_entry_price * cur_quantity + open_value) / (cur_quantity + open_quantity);
+            self.quantity += sign * open_quantity;
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Balance {
     pub asset: String,
@@ -75,3 +163,116 @@ pub struct AccountUpdate {
     pub balances: Vec<Balance>,
     pub timestamp: u64,
 }
+
+// 某一时刻的资产余额，balance_history中的一行
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BalanceSnapshot {
+    pub asset: String,
+    pub free: Decimal,
+    pub locked: Decimal,
+    pub timestamp: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fill(
+        side: OrderSide,
+        price: Decimal,
+        quantity: Decimal,
+        commission: Decimal,
+        commission_asset: &str,
+    ) -> UserTrade {
+        UserTrade {
+            trade_id: "1".to_string(),
+            order_id: "1".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            order_side: side,
+            trade_price: price,
+            trade_quantity: quantity,
+            commission,
+            commission_asset: commission_asset.to_string(),
+            is_maker: 0,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_position_apply_fill() {
+        let mut position = Position::new("BTCUSDT", "BTC", "USDT");
+        // (成交, 仓位, 均价, 已实现盈亏)
+        let steps = [
+            (
+                fill(OrderSide::Buy, dec!(100), dec!(1), dec!(0.1), "USDT"),
+                dec!(1),
+                dec!(100.1),
+                dec!(0),
+            ),
+            (
+                fill(OrderSide::Buy, dec!(110), dec!(1), dec!(0.11), "USDT"),
+                dec!(2),
+                dec!(105.105),
+                dec!(0),
+            ),
+            // 减仓：119.88 - 105.105
+            (
+                fill(OrderSide::Sell, dec!(120), dec!(1), dec!(0.12), "USDT"),
+                dec!(1),
+                dec!(105.105),
+                dec!(14.775),
+            ),
+            // 多翻空：平1（99.9 - 105.105），剩余2以99.9开空
+            (
+                fill(OrderSide::Sell, dec!(100), dec!(3), dec!(0.3), "USDT"),
+                dec!(-2),
+                dec!(99.9),
+                dec!(9.57),
+            ),
+            // 平空：199.8 - 180.18
+            (
+                fill(OrderSide::Buy, dec!(90), dec!(2), dec!(0.18), "USDT"),
+                dec!(0),
+                dec!(0),
+                dec!(29.19),
+            ),
+        ];
+        for (i, (trade, quantity, avg_entry_price, realized_pnl)) in steps.iter().enumerate() {
+            position.apply_fill(trade);
+            assert_eq!(position.quantity, *quantity, "step {}", i);
+            assert_eq!(position.avg_entry_price, *avg_entry_price, "step {}", i);
+            assert_eq!(position.realized_pnl, *realized_pnl, "step {}", i);
+        }
+    }
+
+    #[test]
+    fn test_position_commission_assets() {
+        let mut position = Position::new("BTCUSDT", "BTC", "USDT");
+        // base资产手续费从到账数量中扣除：100 USDT买到0.8 BTC
+        position.apply_fill(&fill(OrderSide::Buy, dec!(100), dec!(1), dec!(0.2), "BTC"));
+        assert_eq!(position.quantity, dec!(0.8));
+        assert_eq!(position.avg_entry_price, dec!(125));
+        position.apply_fill(&fill(
+            OrderSide::Sell,
+            dec!(150),
+            dec!(0.8),
+            dec!(1.2),
+            "USDT",
+        ));
+        assert_eq!(position.quantity, dec!(0));
+        assert_eq!(position.realized_pnl, dec!(18.8));
+
+        // 其他资产支付的手续费不计入
+        position.apply_fill(&fill(OrderSide::Buy, dec!(100), dec!(1), dec!(0.01), "BNB"));
+        assert_eq!(position.quantity, dec!(1));
+        assert_eq!(position.avg_entry_price, dec!(100));
+        assert_eq!(position.realized_pnl, dec!(18.8));
+
+        // 其他symbol的成交忽略
+        let mut other = fill(OrderSide::Buy, dec!(100), dec!(1), dec!(0), "USDT");
+        other.symbol = "ETHUSDT".to_string();
+        position.apply_fill(&other);
+        assert_eq!(position.quantity, dec!(1));
+    }
+}
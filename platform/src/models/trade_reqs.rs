@@ -1,4 +1,7 @@
-use crate::models::{OrderSide, OrderType, TimeInForce};
+use crate::{
+    errors::{PlatformError, Result},
+    models::{market_reqs::required_field, OrderSide, OrderType, TimeInForce},
+};
 use rust_decimal::Decimal;
 
 #[derive(Clone)]
@@ -12,6 +15,165 @@ pub struct PlaceOrderRequest {
     pub client_order_id: String,
     pub stop_price: Option<Decimal>, // STOP_LOSS/STOP_LOSS_LIMIT/TAKE_PROFIT/TAKE_PROFIT_LIMIT
     pub iceberg_qty: Option<Decimal>, // LIMIT/LIMIT_MAKER
+    pub quote_order_qty: Option<Decimal>, // MARKET，按quote金额下单（含手续费），与quantity二选一
+}
+
+impl PlaceOrderRequest {
+    pub fn builder() -> PlaceOrderRequestBuilder {
+        PlaceOrderRequestBuilder::default()
+    }
+}
+
+// symbol、side、type、client_order_id必填，build时按订单类型校验各字段是否必填/允许
+#[derive(Clone, Default)]
+pub struct PlaceOrderRequestBuilder {
+    symbol: Option<String>,
+    side: Option<OrderSide>,
+    r#type: Option<OrderType>,
+    time_in_force: Option<TimeInForce>,
+    quantity: Option<Decimal>,
+    price: Option<Decimal>,
+    client_order_id: Option<String>,
+    stop_price: Option<Decimal>,
+    iceberg_qty: Option<Decimal>,
+    quote_order_qty: Option<Decimal>,
+}
+
+impl PlaceOrderRequestBuilder {
+    pub fn symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    pub fn side(mut self, side: OrderSide) -> Self {
+        self.side = Some(side);
+        self
+    }
+
+    pub fn r#type(mut self, r#type: OrderType) -> Self {
+        self.r#type = Some(r#type);
+        self
+    }
+
+    pub fn time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = Some(time_in_force);
+        self
+    }
+
+    pub fn quantity(mut self, quantity: Decimal) -> Self {
+        self.quantity = Some(quantity);
+        self
+    }
+
+    pub fn price(mut self, price: Decimal) -> Self {
+        self.price = Some(price);
+        self
+    }
+
+    pub fn client_order_id(mut self, client_order_id: impl Into<String>) -> Self {
+        self.client_order_id = Some(client_order_id.into());
+        self
+    }
+
+    pub fn stop_price(mut self, stop_price: Decimal) -> Self {
+        self.stop_price = Some(stop_price);
+        self
+    }
+
+    pub fn iceberg_qty(mut self, iceberg_qty: Decimal) -> Self {
+        self.iceberg_qty = Some(iceberg_qty);
+        self
+    }
+
+    pub fn quote_order_qty(mut self, quote_order_qty: Decimal) -> Self {
+        self.quote_order_qty = Some(quote_order_qty);
+        self
+    }
+
+    pub fn build(self) -> Result<PlaceOrderRequest> {
+        let request = "PlaceOrderRequest";
+        let order_type = required_field(self.r#type, request, "type")?;
+        let invalid = |message: String| PlatformError::ValidationError {
+            message: format!("{}: {}", request, message),
+        };
+        let (needs_price, needs_time_in_force, needs_stop_price) = match order_type {
+            OrderType::Limit => (true, true, false),
+            OrderType::Market => (false, false, false),
+            OrderType::StopLoss | OrderType::TakeProfit => (false, false, true),
+            OrderType::StopLossLimit | OrderType::TakeProfitLimit => (true, true, true),
+            OrderType::LimitMaker => (true, false, false),
+            OrderType::Unknown(_) => {
+                return Err(invalid(format!("unsupported order type {:?}", order_type)))
+            }
+        };
+        // 字段按订单类型必填或不允许
+        let check_presence = |field: &str, present: bool, needed: bool| match (present, needed) {
+            (false, true) => Err(invalid(format!(
+                "{} is required for {:?}",
+                field, order_type
+            ))),
+            (true, false) => Err(invalid(format!(
+                "{} is not allowed for {:?}",
+                field, order_type
+            ))),
+            _ => Ok(()),
+        };
+        check_presence("price", self.price.is_some(), needs_price)?;
+        check_presence(
+            "time_in_force",
+            self.time_in_force.is_some(),
+            needs_time_in_force,
+        )?;
+        check_presence("stop_price", self.stop_price.is_some(), needs_stop_price)?;
+        if self.iceberg_qty.is_some()
+            && !matches!(order_type, OrderType::Limit | OrderType::LimitMaker)
+        {
+            return Err(invalid(format!(
+                "iceberg_qty is not allowed for {:?}",
+                order_type
+            )));
+        }
+        // 市价单quantity与quote_order_qty二选一，其他类型只能用quantity
+        match (self.quantity.is_some(), self.quote_order_qty.is_some()) {
+            (true, true) => {
+                return Err(invalid(
+                    "quantity and quote_order_qty are mutually exclusive".to_string(),
+                ))
+            }
+            (false, true) if order_type != OrderType::Market => {
+                check_presence("quote_order_qty", true, false)?
+            }
+            (false, false) => check_presence("quantity", false, true)?,
+            _ => {}
+        }
+        for (field, value) in [
+            ("quantity", self.quantity),
+            ("price", self.price),
+            ("stop_price", self.stop_price),
+            ("iceberg_qty", self.iceberg_qty),
+            ("quote_order_qty", self.quote_order_qty),
+        ] {
+            if let Some(value) = value.filter(|v| *v <= Decimal::ZERO) {
+                return Err(invalid(format!(
+                    "{} must be positive, got {}",
+                    field, value
+                )));
+            }
+        }
+
+        Ok(PlaceOrderRequest {
+            symbol: required_field(self.symbol, request, "symbol")?,
+            side: required_field(self.side, request, "side")?,
+            r#type: order_type,
+            time_in_force: self.time_in_force,
+            quantity: self.quantity,
+            price: self.price,
+            client_order_id: required_field(self.client_order_id, request, "client_order_id")?,
+            stop_price: self.stop_price,
+            iceberg_qty: self.iceberg_qty,
+            quote_order_qty: self.quote_order_qty,
+        })
+    }
 }
 
 #[derive(Clone)]
@@ -21,6 +183,25 @@ pub struct CancelOrderRequest {
     pub client_order_id: String,
 }
 
+// 修改在途订单的价格/数量，仅支持Limit订单
+#[derive(Clone)]
+pub struct AmendOrderRequest {
+    pub symbol: String,
+    pub order_id: Option<String>,
+    pub client_order_id: String,
+    pub new_client_order_id: String, // 修改后订单的client_order_id，交易所撤单重下时需与原订单不同
+    pub price: Option<Decimal>,      // 不修改时为None
+    pub quantity: Option<Decimal>,   // 修改后的订单总数量（含已成交部分），不修改时为None
+}
+
+// 撤销订单并下新单，撤单失败时不下新单
+#[derive(Clone)]
+pub struct CancelReplaceOrderRequest {
+    pub cancel_order_id: Option<String>,
+    pub cancel_client_order_id: String,
+    pub new_order: PlaceOrderRequest,
+}
+
 pub struct GetOrderRequest {
     pub symbol: String,
     pub order_id: Option<String>,
@@ -0,0 +1,88 @@
+use crate::{
+    errors::PlatformError,
+    models::{OrderSide, OrderType, PlaceOrderRequest, PlaceOrderRequestBuilder, TimeInForce},
+};
+use rust_decimal::Decimal;
+
+fn limit_order() -> PlaceOrderRequestBuilder {
+    PlaceOrderRequest::builder()
+        .symbol("BTCUSDT")
+        .side(OrderSide::Buy)
+        .r#type(OrderType::Limit)
+        .time_in_force(TimeInForce::Gtc)
+        .quantity(Decimal::ONE)
+        .price(Decimal::from(100))
+        .client_order_id("order-1")
+}
+
+fn error_message(builder: PlaceOrderRequestBuilder) -> String {
+    match builder.build() {
+        Err(err @ PlatformError::ValidationError { .. }) => err.to_string(),
+        Err(err) => panic!("unexpected error: {}", err),
+        Ok(_) => panic!("expected validation error"),
+    }
+}
+
+#[test]
+fn test_place_order_builder_sets_fields() {
+    let req = limit_order().build().unwrap();
+    assert_eq!(req.symbol, "BTCUSDT");
+    assert_eq!(req.side, OrderSide::Buy);
+    assert_eq!(req.r#type, OrderType::Limit);
+    assert_eq!(req.time_in_force, Some(TimeInForce::Gtc));
+    assert_eq!(req.quantity, Some(Decimal::ONE));
+    assert_eq!(req.price, Some(Decimal::from(100)));
+    assert_eq!(req.client_order_id, "order-1");
+    assert_eq!((req.stop_price, req.iceberg_qty), (None, None));
+
+    let req = PlaceOrderRequest::builder()
+        .symbol("BTCUSDT")
+        .side(OrderSide::Buy)
+        .r#type(OrderType::Market)
+        .quote_order_qty(Decimal::from(50))
+        .client_order_id("order-2")
+        .build()
+        .unwrap();
+    assert_eq!(req.quantity, None);
+    assert_eq!(req.quote_order_qty, Some(Decimal::from(50)));
+}
+
+#[test]
+fn test_place_order_builder_checks_fields_by_type() {
+    let market = || limit_order().r#type(OrderType::Market);
+    assert!(error_message(market()).contains("price is not allowed for Market"));
+
+    let no_price = PlaceOrderRequest::builder()
+        .symbol("BTCUSDT")
+        .side(OrderSide::Sell)
+        .r#type(OrderType::StopLossLimit)
+        .time_in_force(TimeInForce::Gtc)
+        .quantity(Decimal::ONE)
+        .stop_price(Decimal::from(90))
+        .client_order_id("order-3");
+    assert!(error_message(no_price.clone()).contains("price is required for StopLossLimit"));
+    assert!(no_price.price(Decimal::from(89)).build().is_ok());
+
+    assert!(error_message(limit_order().r#type(OrderType::StopLoss))
+        .contains("price is not allowed for StopLoss"));
+    assert!(
+        error_message(limit_order().quote_order_qty(Decimal::from(50)))
+            .contains("mutually exclusive")
+    );
+    assert!(error_message(limit_order().quantity(Decimal::ZERO)).contains("must be positive"));
+    assert!(error_message(
+        limit_order()
+            .iceberg_qty(Decimal::ONE)
+            .r#type(OrderType::StopLossLimit)
+            .stop_price(Decimal::from(90))
+    )
+    .contains("iceberg_qty is not allowed"));
+
+    let no_client_order_id = PlaceOrderRequest::builder()
+        .symbol("BTCUSDT")
+        .side(OrderSide::Buy)
+        .r#type(OrderType::LimitMaker)
+        .quantity(Decimal::ONE)
+        .price(Decimal::from(100));
+    assert!(error_message(no_client_order_id).contains("client_order_id is required"));
+}
@@ -1,7 +1,9 @@
 use crate::{
     config::PlatformConfig,
     data_manager::{
-        market_data::MarketData, trade_data::TradeData, MarketDataManager, TradeDataManager,
+        market_data::MarketData,
+        trade_data::{AccountKey, TradeData},
+        MarketDataManager, TradeDataManager,
     },
     errors::Result,
     market_provider::{BinanceSpotMarketProvider, MarketProvider},
@@ -14,7 +16,7 @@ pub struct Platform {
     config: Arc<PlatformConfig>,
 
     market_providers: Option<Arc<HashMap<MarketType, Arc<dyn MarketProvider>>>>,
-    trade_providers: Option<Arc<HashMap<MarketType, Arc<dyn TradeProvider>>>>,
+    trade_providers: Option<Arc<HashMap<AccountKey, Arc<dyn TradeProvider>>>>,
 
     market_data_manager: Option<Arc<dyn MarketDataManager>>,
     trade_data_manager: Option<Arc<dyn TradeDataManager>>,
@@ -35,7 +37,7 @@ impl Platform {
         let market_types: Vec<MarketType> = self.config.markets.clone();
 
         let mut market_providers: HashMap<MarketType, Arc<dyn MarketProvider>> = HashMap::new();
-        let mut trade_providers: HashMap<MarketType, Arc<dyn TradeProvider>> = HashMap::new();
+        let mut trade_providers: HashMap<AccountKey, Arc<dyn TradeProvider>> = HashMap::new();
         for market_type in market_types {
             match market_type {
                 MarketType::BinanceSpot => {
@@ -49,15 +51,20 @@ impl Platform {
                         Arc::new(market_provider) as Arc<dyn MarketProvider>,
                     );
 
-                    let mut trade_provider = BinanceSpotTradeProvider::new(
-                        self.config.configs[&MarketType::BinanceSpot].clone(),
-                        self.config.proxy.clone(),
-                    )?;
-                    trade_provider.init().await?;
-                    trade_providers.insert(
-                        MarketType::BinanceSpot,
-                        Arc::new(trade_provider) as Arc<dyn TradeProvider>,
-                    );
+                    // 每个账户独立的trade provider
+                    let market_config = &self.config.configs[&MarketType::BinanceSpot];
+                    for account_id in market_config.account_ids() {
+                        let account_config = market_config.for_account(&account_id).unwrap();
+                        let mut trade_provider = BinanceSpotTradeProvider::new(
+                            Arc::new(account_config),
+                            self.config.proxy.clone(),
+                        )?;
+                        trade_provider.init().await?;
+                        trade_providers.insert(
+                            (MarketType::BinanceSpot, account_id),
+                            Arc::new(trade_provider) as Arc<dyn TradeProvider>,
+                        );
+                    }
                 }
             }
         }
@@ -2,13 +2,15 @@ use crate::{
     config::{MarketConfig, Proxy},
     errors::{PlatformError, Result},
     models::{
-        Account, AccountUpdate, CancelOrderRequest, GetAllOrdersRequest, GetOpenOrdersRequest,
-        GetOrderRequest, GetUserTradesRequest, Order, PlaceOrderRequest, UserTrade,
+        Account, AccountUpdate, CancelOrderRequest, CancelReplaceOrderRequest, GetAllOrdersRequest,
+        GetOpenOrdersRequest, GetOrderRequest, GetUserTradesRequest, Order, PlaceOrderRequest,
+        UserTrade,
     },
     trade_provider::TradeProvider,
 };
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
+use exchange::binance::errors::BinanceError;
 use exchange::binance::spot::{
     requests::{self},
     trade_api::TradeApi,
@@ -77,7 +79,7 @@ fn create_trade_api(
     rate_limiters: Option<Arc<Vec<RateLimiter>>>,
 ) -> Result<TradeApi> {
     let base_url: String = config.api_base_url.clone();
-    let proxy_url: Option<String> = proxy.as_ref().map(|p| p.url.clone());
+    let proxy_url: Option<String> = proxy.as_ref().map(|p| p.url_with_auth()).transpose()?;
     let timeout_milli_secs: u64 = config.api_timeout_milli_secs;
     let api_key: String = config.api_key.clone();
     let secret_key: String = config.secret_key.clone();
@@ -91,10 +93,15 @@ fn create_trade_api(
         timeout_milli_secs,
     );
     trade_api
-        .init()
-        .map_err(|e| PlatformError::TradeProviderError {
-            message: format!("Failed to init trade_api: {}", e),
+        .set_recv_window(config.api_recv_window_milli_secs)
+        .map_err(|e| PlatformError::BinanceError {
+            context: "Failed to set trade_api recv_window".to_string(),
+            source: e,
         })?;
+    trade_api.init().map_err(|e| PlatformError::BinanceError {
+        context: "Failed to init trade_api".to_string(),
+        source: e,
+    })?;
 
     Ok(trade_api)
 }
@@ -108,7 +115,7 @@ async fn create_trade_stream(
     account_update_sender: broadcast::Sender<AccountUpdate>,
 ) -> Result<TradeStream> {
     let stream_api_base_url: String = config.stream_api_base_url.clone();
-    let proxy_url: Option<String> = proxy.as_ref().map(|p| p.url.clone());
+    let proxy_url: Option<String> = proxy.as_ref().map(|p| p.url_with_auth()).transpose()?;
     let api_key: String = config.api_key.clone();
     let secret_key: String = config.secret_key.clone();
 
@@ -119,6 +126,12 @@ async fn create_trade_stream(
         api_key,
         secret_key,
     );
+    trade_stream
+        .set_recv_window(config.api_recv_window_milli_secs)
+        .map_err(|e| PlatformError::BinanceError {
+            context: "Failed to set trade_stream recv_window".to_string(),
+            source: e,
+        })?;
 
     trade_stream.register_execution_report_callback(move |execution_report| {
         let order_sender = order_sender.clone();
@@ -157,13 +170,53 @@ async fn create_trade_stream(
     let _ = trade_stream
         .init()
         .await
-        .map_err(|e| PlatformError::TradeProviderError {
-            message: format!("Failed to init trade_stream: {}", e),
+        .map_err(|e| PlatformError::BinanceError {
+            context: "Failed to init trade_stream".to_string(),
+            source: e,
         })?;
 
     Ok(trade_stream)
 }
 
+// 交易所业务错误保留错误码，其余按provider错误返回
+fn trade_error(context: &str, e: BinanceError) -> PlatformError {
+    match e {
+        BinanceError::ApiError {
+            status: status @ (418 | 429),
+            code,
+            message,
+        } => PlatformError::RateLimited {
+            status,
+            code,
+            message: format!("{}: {}", context, message),
+        },
+        BinanceError::ApiError { code, message, .. } => PlatformError::ExchangeError {
+            code,
+            message: format!("{}: {}", context, message),
+        },
+        e => PlatformError::BinanceError {
+            context: context.to_string(),
+            source: e,
+        },
+    }
+}
+
+// API下单使用FULL响应，下单时立即成交的部分推送为UserTrade
+pub(crate) async fn place_order_via_api(
+    api: &TradeApi,
+    user_trade_sender: &broadcast::Sender<UserTrade>,
+    req: PlaceOrderRequest,
+) -> Result<Order> {
+    let response = api
+        .place_order_full(req.into())
+        .await
+        .map_err(|e| trade_error("Failed to place order via API", e))?;
+    for fill in response.fills {
+        let _ = user_trade_sender.send(fill.into());
+    }
+    Ok(response.order.into())
+}
+
 #[async_trait]
 impl TradeProvider for BinanceSpotTradeProvider {
     async fn init(&mut self) -> Result<()> {
@@ -256,24 +309,18 @@ impl TradeProvider for BinanceSpotTradeProvider {
         };
 
         if ok {
-            match stream.unwrap().place_order(req.into()).await {
-                Ok(response) => Ok(response.into()),
-                Err(e) => Err(PlatformError::TradeProviderError {
-                    message: format!("Failed to place order via stream: {}", e),
-                }),
-            }
+            stream
+                .unwrap()
+                .place_order(req.into())
+                .await
+                .map(|response| response.into())
+                .map_err(|e| trade_error("Failed to place order via stream", e))
         } else {
             match &self.trade_api {
                 None => Err(PlatformError::TradeProviderError {
                     message: "Trade API not initialized".to_string(),
                 }),
-                Some(api) => api
-                    .place_order(req.into())
-                    .await
-                    .map(|o| o.into())
-                    .map_err(|e| PlatformError::TradeProviderError {
-                        message: format!("Failed to place order via API: {}", e),
-                    }),
+                Some(api) => place_order_via_api(api, &self.user_trade_sender, req).await,
             }
         }
     }
@@ -296,12 +343,12 @@ impl TradeProvider for BinanceSpotTradeProvider {
         };
 
         if ok {
-            match stream.unwrap().cancel_order(req.into()).await {
-                Ok(response) => Ok(response.into()),
-                Err(e) => Err(PlatformError::TradeProviderError {
-                    message: format!("Failed to place order via stream: {}", e),
-                }),
-            }
+            stream
+                .unwrap()
+                .cancel_order(req.into())
+                .await
+                .map(|response| response.into())
+                .map_err(|e| trade_error("Failed to cancel order via stream", e))
         } else {
             match &self.trade_api {
                 None => Err(PlatformError::TradeProviderError {
@@ -311,13 +358,31 @@ impl TradeProvider for BinanceSpotTradeProvider {
                     .cancel_order(req.into())
                     .await
                     .map(|o| o.into())
-                    .map_err(|e| PlatformError::TradeProviderError {
-                        message: format!("Failed to place order via API: {}", e),
-                    }),
+                    .map_err(|e| trade_error("Failed to cancel order via API", e)),
             }
         }
     }
 
+    // 仅通过API撤单重下
+    async fn cancel_replace_order(&self, req: CancelReplaceOrderRequest) -> Result<Order> {
+        let api = self
+            .trade_api
+            .as_ref()
+            .ok_or(PlatformError::TradeProviderError {
+                message: "Trade API not initialized".to_string(),
+            })?;
+
+        let order = api
+            .cancel_replace_order(req.try_into()?)
+            .await
+            .map_err(|e| PlatformError::BinanceError {
+                context: "Failed to cancel replace order".to_string(),
+                source: e,
+            })?;
+
+        Ok(order.into())
+    }
+
     async fn get_order(&self, req: GetOrderRequest) -> Result<Order> {
         let api = self
             .trade_api
@@ -326,12 +391,13 @@ impl TradeProvider for BinanceSpotTradeProvider {
                 message: "Trade API not initialized".to_string(),
             })?;
 
-        let order =
-            api.get_order(req.into())
-                .await
-                .map_err(|e| PlatformError::TradeProviderError {
-                    message: format!("Failed to get order: {}", e),
-                })?;
+        let order = api
+            .get_order(req.into())
+            .await
+            .map_err(|e| PlatformError::BinanceError {
+                context: "Failed to get order".to_string(),
+                source: e,
+            })?;
 
         Ok(order.into())
     }
@@ -344,11 +410,13 @@ impl TradeProvider for BinanceSpotTradeProvider {
                 message: "Trade API not initialized".to_string(),
             })?;
 
-        let orders = api.get_open_orders(req.into()).await.map_err(|e| {
-            PlatformError::TradeProviderError {
-                message: format!("Failed to get open orders: {}", e),
-            }
-        })?;
+        let orders =
+            api.get_open_orders(req.into())
+                .await
+                .map_err(|e| PlatformError::BinanceError {
+                    context: "Failed to get open orders".to_string(),
+                    source: e,
+                })?;
 
         Ok(orders.into_iter().map(|o| o.into()).collect())
     }
@@ -361,11 +429,13 @@ impl TradeProvider for BinanceSpotTradeProvider {
                 message: "Trade API not initialized".to_string(),
             })?;
 
-        let orders = api.get_all_orders(req.into()).await.map_err(|e| {
-            PlatformError::TradeProviderError {
-                message: format!("Failed to get all orders: {}", e),
-            }
-        })?;
+        let orders =
+            api.get_all_orders(req.into())
+                .await
+                .map_err(|e| PlatformError::BinanceError {
+                    context: "Failed to get all orders".to_string(),
+                    source: e,
+                })?;
 
         Ok(orders.into_iter().map(|o| o.into()).collect())
     }
@@ -378,12 +448,13 @@ impl TradeProvider for BinanceSpotTradeProvider {
                 message: "Trade API not initialized".to_string(),
             })?;
 
-        let trades =
-            api.get_trades(req.into())
-                .await
-                .map_err(|e| PlatformError::TradeProviderError {
-                    message: format!("Failed to get user trades: {}", e),
-                })?;
+        let trades = api
+            .get_trades(req.into())
+            .await
+            .map_err(|e| PlatformError::BinanceError {
+                context: "Failed to get user trades".to_string(),
+                source: e,
+            })?;
 
         Ok(trades.into_iter().map(|t| t.into()).collect())
     }
@@ -399,8 +470,9 @@ impl TradeProvider for BinanceSpotTradeProvider {
         let account = api
             .get_account(requests::GetAccountRequest {})
             .await
-            .map_err(|e| PlatformError::TradeProviderError {
-                message: format!("Failed to get account: {}", e),
+            .map_err(|e| PlatformError::BinanceError {
+                context: "Failed to get account".to_string(),
+                source: e,
             })?;
 
         Ok(account.into())
@@ -1,18 +1,23 @@
 use crate::{
     config::{Config, PlatformConfig},
+    errors::PlatformError,
     models::{
-        CancelOrderRequest, GetAllOrdersRequest, GetOpenOrdersRequest, GetOrderRequest,
-        GetUserTradesRequest, MarketType, OrderSide, OrderStatus, OrderType, PlaceOrderRequest,
-        TimeInForce,
+        CancelOrderRequest, CancelReplaceOrderRequest, GetAllOrdersRequest, GetOpenOrdersRequest,
+        GetOrderRequest, GetUserTradesRequest, MarketType, OrderSide, OrderStatus, OrderType,
+        PlaceOrderRequest, TimeInForce,
+    },
+    trade_provider::{
+        binance_spot_trade_provider::{place_order_via_api, BinanceSpotTradeProvider},
+        TradeProvider,
     },
-    trade_provider::{binance_spot_trade_provider::BinanceSpotTradeProvider, TradeProvider},
 };
 use env_logger::Env;
+use exchange::binance::spot::trade_api::TradeApi;
 use log::info;
 use rust_decimal::Decimal;
 use std::{str::FromStr, sync::Arc, time::Duration};
 use tempfile::NamedTempFile;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 
 #[tokio::test]
 async fn test_binance_spot_trade_provider() {
@@ -125,6 +130,7 @@ async fn test_binance_spot_trade_provider() {
         client_order_id: format!("test_buy_limit_{}", time::get_current_milli_timestamp()),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let order = provider.place_order(buy_limit_order.clone()).await.unwrap();
@@ -152,6 +158,7 @@ async fn test_binance_spot_trade_provider() {
         client_order_id: format!("test_buy_limit_{}", time::get_current_milli_timestamp()),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
 
     let order = provider.place_order(buy_limit_order.clone()).await.unwrap();
@@ -204,6 +211,7 @@ async fn test_binance_spot_trade_provider() {
         client_order_id: format!("test_sell_market_{}", time::get_current_milli_timestamp()),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
     let order = provider
         .place_order(sell_market_order.clone())
@@ -233,6 +241,7 @@ async fn test_binance_spot_trade_provider() {
         client_order_id: format!("test_sell_fail_{}", time::get_current_milli_timestamp()),
         stop_price: None,
         iceberg_qty: None,
+        quote_order_qty: None,
     };
     provider
         .place_order(large_sell_order.clone())
@@ -282,3 +291,177 @@ async fn test_binance_spot_trade_provider() {
     )
     .unwrap();
 }
+
+// 本地mock交易接口，所有请求返回同一响应
+async fn start_mock_trade_api(status: u16, body: &'static str) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = vec![0u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let resp = format!(
+                    "HTTP/1.1 {} MOCK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(resp.as_bytes()).await;
+            });
+        }
+    });
+    format!("http://{}", addr)
+}
+
+fn mock_trade_api(base_url: String) -> TradeApi {
+    let mut api = TradeApi::new(
+        base_url,
+        None,
+        None,
+        "key".to_string(),
+        "secret".to_string(),
+        5000,
+    );
+    api.init().unwrap();
+    api
+}
+
+fn market_buy_request() -> PlaceOrderRequest {
+    PlaceOrderRequest {
+        symbol: "BTCUSDT".to_string(),
+        side: OrderSide::Buy,
+        r#type: OrderType::Market,
+        time_in_force: None,
+        quantity: Some(Decimal::from_str("0.003").unwrap()),
+        price: None,
+        client_order_id: "full_resp_buy".to_string(),
+        stop_price: None,
+        iceberg_qty: None,
+        quote_order_qty: None,
+    }
+}
+
+#[tokio::test]
+async fn test_place_order_via_api_full_response() {
+    let base_url = start_mock_trade_api(
+        200,
+        r#"{
+            "symbol": "BTCUSDT",
+            "orderId": 1001,
+            "orderListId": -1,
+            "clientOrderId": "full_resp_buy",
+            "transactTime": 1700000000123,
+            "price": "0.00000000",
+            "origQty": "0.00300000",
+            "executedQty": "0.00200000",
+            "cummulativeQuoteQty": "120.01000000",
+            "status": "PARTIALLY_FILLED",
+            "timeInForce": "GTC",
+            "type": "MARKET",
+            "side": "BUY",
+            "fills": [
+                {"price": "60000.00", "qty": "0.00100000", "commission": "0.00000100", "commissionAsset": "BTC", "tradeId": 7001},
+                {"price": "60010.00", "qty": "0.00100000", "commission": "0.00000100", "commissionAsset": "BTC", "tradeId": 7002}
+            ]
+        }"#,
+    )
+    .await;
+    let api = mock_trade_api(base_url);
+    let (user_trade_sender, mut user_trade_receiver) = broadcast::channel(16);
+
+    let order = place_order_via_api(&api, &user_trade_sender, market_buy_request())
+        .await
+        .unwrap();
+    assert_eq!(order.order_id, "1001");
+    assert_eq!(order.client_order_id, "full_resp_buy");
+    assert_eq!(order.order_status, OrderStatus::PartiallyFilled);
+    assert_eq!(order.order_quantity, Decimal::from_str("0.003").unwrap());
+    assert_eq!(order.executed_qty, Decimal::from_str("0.002").unwrap());
+    assert_eq!(
+        order.cummulative_quote_qty,
+        Decimal::from_str("120.01").unwrap()
+    );
+    assert_eq!(order.update_time, 1700000000123);
+
+    let mut trades = Vec::new();
+    while let Ok(trade) = user_trade_receiver.try_recv() {
+        trades.push(trade);
+    }
+    assert_eq!(trades.len(), 2);
+    assert_eq!(trades[0].trade_id, "7001");
+    assert_eq!(trades[1].trade_id, "7002");
+    for trade in &trades {
+        assert_eq!(trade.order_id, "1001");
+        assert_eq!(trade.symbol, "BTCUSDT");
+        assert_eq!(trade.order_side, OrderSide::Buy);
+        assert_eq!(trade.trade_quantity, Decimal::from_str("0.001").unwrap());
+        assert_eq!(trade.commission_asset, "BTC");
+        assert_eq!(trade.is_maker, 0);
+        assert_eq!(trade.timestamp, 1700000000123);
+    }
+    assert_eq!(trades[1].trade_price, Decimal::from_str("60010").unwrap());
+}
+
+#[tokio::test]
+async fn test_place_order_via_api_exchange_error() {
+    let base_url =
+        start_mock_trade_api(400, r#"{"code":-1013,"msg":"Filter failure: LOT_SIZE"}"#).await;
+    let api = mock_trade_api(base_url);
+    let (user_trade_sender, _user_trade_receiver) = broadcast::channel(16);
+
+    match place_order_via_api(&api, &user_trade_sender, market_buy_request()).await {
+        Err(PlatformError::ExchangeError { code, message }) => {
+            assert_eq!(code, -1013);
+            assert!(message.contains("LOT_SIZE"));
+        }
+        other => panic!("expect exchange error, got {:?}", other.map(|o| o.order_id)),
+    }
+}
+
+#[tokio::test]
+async fn test_place_order_via_api_rate_limited() {
+    let base_url = start_mock_trade_api(
+        429,
+        r#"{"code":-1003,"msg":"Too many requests; current limit is 1200 request weight per 1 MINUTE."}"#,
+    )
+    .await;
+    let api = mock_trade_api(base_url);
+    let (user_trade_sender, _user_trade_receiver) = broadcast::channel(16);
+
+    match place_order_via_api(&api, &user_trade_sender, market_buy_request()).await {
+        Err(err @ PlatformError::RateLimited { .. }) => {
+            assert_eq!(err.code_name(), "RATE_LIMITED");
+            if let PlatformError::RateLimited { status, code, .. } = err {
+                assert_eq!(status, 429);
+                assert_eq!(code, -1003);
+            }
+        }
+        other => panic!("expect rate limited, got {:?}", other.map(|o| o.order_id)),
+    }
+}
+
+#[test]
+fn test_cancel_replace_rejects_invalid_order_id() {
+    let req = CancelReplaceOrderRequest {
+        cancel_order_id: Some("local-1".to_string()),
+        cancel_client_order_id: "amend_1".to_string(),
+        new_order: PlaceOrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: OrderSide::Buy,
+            r#type: OrderType::Limit,
+            time_in_force: Some(TimeInForce::Gtc),
+            quantity: Some(Decimal::ONE),
+            price: Some(Decimal::from(100)),
+            client_order_id: "amend_2".to_string(),
+            stop_price: None,
+            iceberg_qty: None,
+            quote_order_qty: None,
+        },
+    };
+    let result: Result<exchange::binance::spot::requests::CancelReplaceOrderRequest, _> =
+        req.try_into();
+    assert!(matches!(result, Err(PlatformError::ValidationError { .. })));
+}
@@ -0,0 +1,183 @@
+use crate::{
+    errors::{PlatformError, Result},
+    models::{
+        Account, AccountUpdate, Balance, CancelOrderRequest, CancelReplaceOrderRequest,
+        GetAllOrdersRequest, GetOpenOrdersRequest, GetOrderRequest, GetUserTradesRequest, Order,
+        PlaceOrderRequest, UserTrade,
+    },
+    trade_provider::TradeProvider,
+};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex,
+    },
+};
+use tokio::sync::broadcast;
+
+// 测试用交易provider：REST接口返回预置数据，可按顺序编排账户快照与下单回报，
+// 并通过push_*手动推送订单/成交/账户更新，驱动TradeData的同步流程
+pub(crate) struct MockTradeProvider {
+    pub account: Mutex<Account>,
+    pub open_orders: Mutex<Vec<Order>>,
+    pub all_orders: Mutex<Vec<Order>>,
+    pub user_trades: Mutex<Vec<UserTrade>>,
+    pub all_orders_fail_times: AtomicU32, // get_all_orders前fail_times次调用返回错误
+    pub open_orders_calls: AtomicU32,
+    pub all_orders_calls: AtomicU32,
+    pub all_orders_windows: Mutex<Vec<(u64, u64)>>, // 每次get_all_orders请求的(start_time, end_time)
+    account_snapshots: Mutex<VecDeque<Account>>,
+    order_acks: Mutex<VecDeque<Result<Order>>>,
+    placed_orders: Mutex<Vec<PlaceOrderRequest>>,
+    order_sender: broadcast::Sender<Order>,
+    user_trade_sender: broadcast::Sender<UserTrade>,
+    account_update_sender: broadcast::Sender<AccountUpdate>,
+}
+
+impl MockTradeProvider {
+    pub fn new(all_orders_fail_times: u32) -> Self {
+        Self {
+            account: Mutex::new(Account {
+                balances: vec![Balance {
+                    asset: "USDT".to_string(),
+                    free: Decimal::from(1000),
+                    locked: Decimal::ZERO,
+                }],
+                timestamp: 1,
+            }),
+            open_orders: Mutex::new(vec![]),
+            all_orders: Mutex::new(vec![]),
+            user_trades: Mutex::new(vec![]),
+            all_orders_fail_times: AtomicU32::new(all_orders_fail_times),
+            open_orders_calls: AtomicU32::new(0),
+            all_orders_calls: AtomicU32::new(0),
+            all_orders_windows: Mutex::new(vec![]),
+            account_snapshots: Mutex::new(VecDeque::new()),
+            order_acks: Mutex::new(VecDeque::new()),
+            placed_orders: Mutex::new(vec![]),
+            order_sender: broadcast::channel(100).0,
+            user_trade_sender: broadcast::channel(100).0,
+            account_update_sender: broadcast::channel(100).0,
+        }
+    }
+
+    // get_account依次返回排队的快照，用完后保持返回最后一个
+    pub fn queue_account(&self, account: Account) {
+        self.account_snapshots.lock().unwrap().push_back(account);
+    }
+
+    // place_order依次返回排队的回报，用完后按请求生成NEW订单
+    pub fn queue_order_ack(&self, ack: Result<Order>) {
+        self.order_acks.lock().unwrap().push_back(ack);
+    }
+
+    pub fn placed_orders(&self) -> Vec<PlaceOrderRequest> {
+        self.placed_orders.lock().unwrap().clone()
+    }
+
+    // 返回收到推送的订阅者数量
+    pub fn push_order(&self, order: Order) -> usize {
+        self.order_sender.send(order).unwrap_or(0)
+    }
+
+    pub fn push_user_trade(&self, trade: UserTrade) -> usize {
+        self.user_trade_sender.send(trade).unwrap_or(0)
+    }
+
+    pub fn push_account_update(&self, update: AccountUpdate) -> usize {
+        self.account_update_sender.send(update).unwrap_or(0)
+    }
+}
+
+#[async_trait]
+impl TradeProvider for MockTradeProvider {
+    async fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn place_order(&self, req: PlaceOrderRequest) -> Result<Order> {
+        self.placed_orders.lock().unwrap().push(req.clone());
+        match self.order_acks.lock().unwrap().pop_front() {
+            Some(ack) => ack,
+            None => Ok(Order::new_order_from_place_order_req(&req)),
+        }
+    }
+
+    async fn cancel_order(&self, _req: CancelOrderRequest) -> Result<()> {
+        Ok(())
+    }
+
+    async fn cancel_replace_order(&self, req: CancelReplaceOrderRequest) -> Result<Order> {
+        Ok(Order::new_order_from_place_order_req(&req.new_order))
+    }
+
+    async fn get_order(&self, _req: GetOrderRequest) -> Result<Order> {
+        Err(PlatformError::TradeProviderError {
+            message: "not supported".to_string(),
+        })
+    }
+
+    async fn get_open_orders(&self, _req: GetOpenOrdersRequest) -> Result<Vec<Order>> {
+        self.open_orders_calls.fetch_add(1, Ordering::SeqCst);
+        Ok(self.open_orders.lock().unwrap().clone())
+    }
+
+    async fn get_all_orders(&self, req: GetAllOrdersRequest) -> Result<Vec<Order>> {
+        self.all_orders_calls.fetch_add(1, Ordering::SeqCst);
+        self.all_orders_windows.lock().unwrap().push((
+            req.start_time.unwrap_or_default(),
+            req.end_time.unwrap_or_default(),
+        ));
+        if self
+            .all_orders_fail_times
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |v| v.checked_sub(1))
+            .is_ok()
+        {
+            return Err(PlatformError::TradeProviderError {
+                message: "mock get all orders failed".to_string(),
+            });
+        }
+        Ok(self
+            .all_orders
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|o| o.symbol == req.symbol)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_user_trades(&self, req: GetUserTradesRequest) -> Result<Vec<UserTrade>> {
+        Ok(self
+            .user_trades
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| t.symbol == req.symbol)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_account(&self) -> Result<Account> {
+        let mut account = self.account.lock().unwrap();
+        if let Some(snapshot) = self.account_snapshots.lock().unwrap().pop_front() {
+            *account = snapshot;
+        }
+        Ok(account.clone())
+    }
+
+    fn subscribe_order(&self) -> broadcast::Receiver<Order> {
+        self.order_sender.subscribe()
+    }
+
+    fn subscribe_user_trade(&self) -> broadcast::Receiver<UserTrade> {
+        self.user_trade_sender.subscribe()
+    }
+
+    fn subscribe_account_update(&self) -> broadcast::Receiver<AccountUpdate> {
+        self.account_update_sender.subscribe()
+    }
+}
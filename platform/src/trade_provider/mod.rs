@@ -6,3 +6,5 @@ pub use binance_spot_trade_provider::*;
 
 #[cfg(test)]
 mod binance_spot_trade_provider_tests;
+#[cfg(test)]
+pub(crate) mod mock_trade_provider;
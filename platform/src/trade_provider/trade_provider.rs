@@ -1,8 +1,9 @@
 use crate::{
-    errors::Result,
+    errors::{PlatformError, Result},
     models::{
-        Account, AccountUpdate, CancelOrderRequest, GetAllOrdersRequest, GetOpenOrdersRequest,
-        GetOrderRequest, GetUserTradesRequest, Order, PlaceOrderRequest, UserTrade,
+        Account, AccountUpdate, CancelOrderRequest, CancelReplaceOrderRequest, GetAllOrdersRequest,
+        GetOpenOrdersRequest, GetOrderRequest, GetUserTradesRequest, Order, PlaceOrderRequest,
+        UserTrade,
     },
 };
 use async_trait::async_trait;
@@ -14,6 +15,12 @@ pub trait TradeProvider: Send + Sync {
 
     async fn place_order(&self, req: PlaceOrderRequest) -> Result<Order>;
     async fn cancel_order(&self, req: CancelOrderRequest) -> Result<()>;
+    // 原子地撤单并下新单，返回新订单；交易所不支持时默认返回错误
+    async fn cancel_replace_order(&self, _req: CancelReplaceOrderRequest) -> Result<Order> {
+        Err(PlatformError::TradeProviderError {
+            message: "cancel replace order not supported".to_string(),
+        })
+    }
     async fn get_order(&self, req: GetOrderRequest) -> Result<Order>;
     async fn get_open_orders(&self, req: GetOpenOrdersRequest) -> Result<Vec<Order>>;
     async fn get_all_orders(&self, req: GetAllOrdersRequest) -> Result<Vec<Order>>;
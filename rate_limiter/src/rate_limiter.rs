@@ -1,7 +1,10 @@
 use crate::error::{RateLimiterError, Result};
 use log::info;
-use std::time::Duration;
-use time::get_current_nano_timestamp;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use time::{get_current_nano_timestamp, noop_metrics, Metrics};
 use tokio::sync::Mutex;
 
 #[derive(Clone)]
@@ -33,6 +36,7 @@ pub struct RateLimiter {
     max_window_range: Duration,
     max_weight_limit: u64,
     inner: Mutex<Inner>,
+    metrics: Arc<dyn Metrics>,
 }
 
 impl RateLimiter {
@@ -49,9 +53,16 @@ impl RateLimiter {
                 size: 0,
                 weight_sum: 0,
             }),
+            metrics: noop_metrics(),
         }
     }
 
+    // 触发限流时上报 rate_limiter.limited 计数及 rate_limiter.wait 等待耗时
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
     pub async fn allow(&self, weight: u64) -> Result<()> {
         if weight == 0 {
             return Err(RateLimiterError::invalid_weight());
@@ -66,6 +77,7 @@ impl RateLimiter {
         inner.cleanup(timestamp - self.max_window_range.as_nanos());
 
         if inner.weight_sum + weight > self.max_weight_limit {
+            self.metrics.incr("rate_limiter.limited", 1);
             return Err(RateLimiterError::Limited);
         }
 
@@ -86,6 +98,7 @@ impl RateLimiter {
             return Err(RateLimiterError::weight_exceeded(self.max_weight_limit));
         }
 
+        let mut wait_start: Option<Instant> = None;
         loop {
             let mut inner = self.inner.lock().await;
 
@@ -98,8 +111,16 @@ impl RateLimiter {
                 inner.end = (inner.end + 1) % inner.data.len();
                 inner.size += 1;
                 inner.weight_sum += weight;
+                if let Some(wait_start) = wait_start {
+                    self.metrics
+                        .timing("rate_limiter.wait", wait_start.elapsed());
+                }
                 return Ok(());
             }
+            if wait_start.is_none() {
+                self.metrics.incr("rate_limiter.limited", 1);
+                wait_start = Some(Instant::now());
+            }
 
             let sleep_duration = {
                 let earliest_timestamp = inner.data[inner.start].0;
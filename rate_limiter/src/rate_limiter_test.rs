@@ -303,3 +303,18 @@ async fn test_very_short_window() {
     // 应该可以再次添加
     assert!(limiter.allow(5).await.is_ok());
 }
+
+#[tokio::test]
+async fn test_metrics() {
+    let metrics = Arc::new(time::InMemoryMetrics::new());
+    let limiter = RateLimiter::new(Duration::from_millis(100), 10).with_metrics(metrics.clone());
+
+    assert!(limiter.allow(10).await.is_ok());
+    assert!(limiter.allow(1).await.is_err());
+    assert_eq!(metrics.counter("rate_limiter.limited"), 1);
+
+    // wait 等待窗口释放，计数一次并记录等待耗时
+    assert!(limiter.wait(5).await.is_ok());
+    assert_eq!(metrics.counter("rate_limiter.limited"), 2);
+    assert_eq!(metrics.timing_count("rate_limiter.wait"), 1);
+}
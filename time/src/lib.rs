@@ -3,3 +3,6 @@ pub use time::*;
 
 pub mod latency;
 pub use latency::*;
+
+pub mod metrics;
+pub use metrics::*;
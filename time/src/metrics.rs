@@ -0,0 +1,138 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+// 指标上报接口，name 使用 "模块.指标" 形式，如 "trade.order_placed"
+pub trait Metrics: Send + Sync {
+    // 计数器累加
+    fn incr(&self, name: &str, value: u64);
+    // 瞬时值
+    fn gauge(&self, name: &str, value: f64);
+    // 耗时
+    fn timing(&self, name: &str, duration: Duration);
+}
+
+// 默认实现，不做任何处理
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {
+    fn incr(&self, _name: &str, _value: u64) {}
+
+    fn gauge(&self, _name: &str, _value: f64) {}
+
+    fn timing(&self, _name: &str, _duration: Duration) {}
+}
+
+pub fn noop_metrics() -> Arc<dyn Metrics> {
+    Arc::new(NoopMetrics)
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MetricsSnapshot {
+    pub counters: HashMap<String, u64>,
+    pub gauges: HashMap<String, f64>,
+    // (次数, 总耗时, 最大耗时)
+    pub timings: HashMap<String, (u64, Duration, Duration)>,
+}
+
+// 内存指标，用于测试及定期导出
+#[derive(Default)]
+pub struct InMemoryMetrics {
+    inner: Mutex<MetricsSnapshot>,
+}
+
+impl InMemoryMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn counter(&self, name: &str) -> u64 {
+        let inner = self.inner.lock().unwrap();
+        inner.counters.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn gauge_value(&self, name: &str) -> Option<f64> {
+        let inner = self.inner.lock().unwrap();
+        inner.gauges.get(name).copied()
+    }
+
+    pub fn timing_count(&self, name: &str) -> u64 {
+        let inner = self.inner.lock().unwrap();
+        inner.timings.get(name).map(|t| t.0).unwrap_or(0)
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.inner.lock().unwrap().clone()
+    }
+
+    // 文本格式，每行一个指标，按名称排序
+    pub fn render(&self) -> String {
+        let snapshot = self.snapshot();
+        let mut lines = Vec::new();
+        for (name, value) in snapshot.counters.iter() {
+            lines.push(format!("counter {} {}", name, value));
+        }
+        for (name, value) in snapshot.gauges.iter() {
+            lines.push(format!("gauge {} {}", name, value));
+        }
+        for (name, (count, total, max)) in snapshot.timings.iter() {
+            lines.push(format!(
+                "timing {} count={} avg_us={} max_us={}",
+                name,
+                count,
+                total.as_micros() / (*count as u128).max(1),
+                max.as_micros()
+            ));
+        }
+        lines.sort_by(|a, b| a.split(' ').nth(1).cmp(&b.split(' ').nth(1)));
+        let mut text = String::new();
+        for line in lines {
+            let _ = writeln!(text, "{}", line);
+        }
+        text
+    }
+}
+
+impl Metrics for InMemoryMetrics {
+    fn incr(&self, name: &str, value: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner.counters.entry(name.to_string()).or_insert(0) += value;
+    }
+
+    fn gauge(&self, name: &str, value: f64) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.gauges.insert(name.to_string(), value);
+    }
+
+    fn timing(&self, name: &str, duration: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        let timing =
+            inner
+                .timings
+                .entry(name.to_string())
+                .or_insert((0, Duration::ZERO, Duration::ZERO));
+        timing.0 += 1;
+        timing.1 += duration;
+        timing.2 = timing.2.max(duration);
+    }
+}
+
+// 导出示例：将内存指标定期输出到日志，可参照实现其他导出方式
+pub struct LogExporter {
+    metrics: Arc<InMemoryMetrics>,
+}
+
+impl LogExporter {
+    pub fn new(metrics: Arc<InMemoryMetrics>) -> Self {
+        Self { metrics }
+    }
+
+    pub fn export(&self) {
+        for line in self.metrics.render().lines() {
+            log::info!("metrics: {}", line);
+        }
+    }
+}
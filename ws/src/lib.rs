@@ -1,7 +1,9 @@
 pub mod error;
 pub use error::{Result, WsError};
+pub mod tls;
+pub use tls::{spki_sha256, TlsConfig};
 pub mod ws_client;
-pub use ws_client::{Client, Config, RecvMsg, SendMsg};
+pub use ws_client::{redact_url, Client, Config, RecvMsg, SendMsg};
 
 #[cfg(test)]
 mod ws_client_test;
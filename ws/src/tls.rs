@@ -0,0 +1,150 @@
+use crate::{Result, WsError};
+use log::error;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::server::ParsedCertificate;
+use rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+// TLS配置，默认（Default）使用webpki内置根证书
+// root_store: 自定义根证书，替换内置根证书
+// pinned_spki_sha256: 服务端证书公钥（DER编码的SubjectPublicKeyInfo）的sha256，非空时只接受匹配的证书；
+//   未同时配置root_store时以公钥为信任锚，不再校验证书链（可用于自签名证书），仍校验域名与握手签名
+#[derive(Clone, Default)]
+pub struct TlsConfig {
+    pub root_store: Option<Arc<RootCertStore>>,
+    pub pinned_spki_sha256: Vec<[u8; 32]>,
+}
+
+impl TlsConfig {
+    pub fn client_config(&self) -> Result<Arc<ClientConfig>> {
+        let builder = ClientConfig::builder();
+        let config = if self.pinned_spki_sha256.is_empty() {
+            let root_store = self
+                .root_store
+                .clone()
+                .unwrap_or_else(|| Arc::new(webpki_root_store()));
+            builder
+                .with_root_certificates(root_store)
+                .with_no_client_auth()
+        } else {
+            let provider = builder.crypto_provider().clone();
+            let chain_verifier = match self.root_store.as_ref() {
+                Some(root_store) => Some(
+                    WebPkiServerVerifier::builder_with_provider(
+                        root_store.clone(),
+                        provider.clone(),
+                    )
+                    .build()
+                    .map_err(|e| WsError::config(format!("invalid root store: {}", e)))?,
+                ),
+                None => None,
+            };
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinnedServerVerifier {
+                    pins: self.pinned_spki_sha256.clone(),
+                    chain_verifier,
+                    provider,
+                }))
+                .with_no_client_auth()
+        };
+        Ok(Arc::new(config))
+    }
+}
+
+pub fn webpki_root_store() -> RootCertStore {
+    RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.iter().cloned().collect(),
+    }
+}
+
+// 计算证书公钥的pin，用于配置pinned_spki_sha256
+pub fn spki_sha256(cert: &CertificateDer<'_>) -> Result<[u8; 32]> {
+    let parsed = ParsedCertificate::try_from(cert)
+        .map_err(|e| WsError::config(format!("invalid certificate: {}", e)))?;
+    Ok(Sha256::digest(parsed.subject_public_key_info().as_ref()).into())
+}
+
+#[derive(Debug)]
+struct PinnedServerVerifier {
+    pins: Vec<[u8; 32]>,
+    chain_verifier: Option<Arc<WebPkiServerVerifier>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedServerVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        let parsed = ParsedCertificate::try_from(end_entity)?;
+        match self.chain_verifier.as_ref() {
+            Some(verifier) => {
+                verifier.verify_server_cert(
+                    end_entity,
+                    intermediates,
+                    server_name,
+                    ocsp_response,
+                    now,
+                )?;
+            }
+            None => rustls::client::verify_server_name(&parsed, server_name)?,
+        }
+        let pin: [u8; 32] = Sha256::digest(parsed.subject_public_key_info().as_ref()).into();
+        if !self.pins.contains(&pin) {
+            error!(
+                "certificate pin mismatch for {}: {:02x?}",
+                server_name.to_str(),
+                pin
+            );
+            return Err(rustls::Error::InvalidCertificate(
+                CertificateError::ApplicationVerificationFailure,
+            ));
+        }
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
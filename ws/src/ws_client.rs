@@ -1,19 +1,29 @@
-use crate::{Result, WsError};
+use crate::{Result, TlsConfig, WsError};
 use dashmap::DashMap;
 use futures_util::stream::{SplitSink, SplitStream, StreamExt};
 use futures_util::SinkExt;
 use log::{self, error, info};
+use percent_encoding::percent_decode_str;
 use rate_limiter::RateLimiter;
 use scopeguard::defer;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use time::LatencyGuard;
-use tokio::io::{AsyncRead, AsyncWrite};
-use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::mpsc::{
+    channel, unbounded_channel, Receiver, Sender, UnboundedReceiver, UnboundedSender,
+};
 use tokio::task::JoinHandle;
 use tokio_socks::tcp::Socks5Stream;
-use tokio_tungstenite::{client_async, connect_async, tungstenite::Message, WebSocketStream};
+use tokio_socks::{IntoTargetAddr, TargetAddr};
+use tokio_tungstenite::{
+    client_async, connect_async_tls_with_config, tungstenite::Message, Connector, WebSocketStream,
+};
 use tokio_util::sync::CancellationToken;
 use url::Url;
 
@@ -176,6 +186,8 @@ pub struct Config {
     pub connect_timeout: Duration,
     pub call_timeout: Duration,
     pub heartbeat_interval: Duration,
+    pub record_to: Option<PathBuf>, // 按行追加记录收到的Text/Binary帧及接收时间，用于离线复现
+    pub tls: Option<TlsConfig>,     // wss连接的根证书/证书公钥固定，None时使用内置根证书
 }
 
 impl Config {
@@ -197,13 +209,34 @@ impl Config {
             connect_timeout: Duration::from_millis(10000),
             call_timeout: Duration::from_millis(10000),
             heartbeat_interval: Duration::from_secs(30),
+            record_to: None,
+            tls: None,
         }
     }
 }
 
+// 发送队列：所有待发送消息（含心跳与Pong）经send_loop按rate_limiters限速后依次发出
+// depth包含channel中排队的消息以及send_loop中正在等待限流的消息
+#[derive(Clone)]
+struct OutboundQueue {
+    tx: Sender<SendMsg>,
+    depth: Arc<AtomicUsize>,
+}
+
+impl OutboundQueue {
+    async fn push(&self, msg: SendMsg) -> std::result::Result<(), SendError<SendMsg>> {
+        self.depth.fetch_add(1, Ordering::SeqCst);
+        let result = self.tx.send(msg).await;
+        if result.is_err() {
+            self.depth.fetch_sub(1, Ordering::SeqCst);
+        }
+        result
+    }
+}
+
 pub struct Client {
     config: Config,
-    send_tx: Option<Sender<SendMsg>>,
+    send_tx: Option<OutboundQueue>,
     shutdown_token: CancellationToken,
     sync_call_chs: Arc<DashMap<String, Sender<RecvMsg>>>,
     join_handles: Vec<JoinHandle<Result<()>>>,
@@ -240,18 +273,45 @@ impl Client {
         return self.shutdown_token.clone();
     }
 
+    // 尚未发出的消息数，未连接时为0
+    pub fn queue_depth(&self) -> usize {
+        self.send_tx
+            .as_ref()
+            .map(|queue| queue.depth.load(Ordering::SeqCst))
+            .unwrap_or(0)
+    }
+
     pub async fn connect(&mut self) -> Result<()> {
         let _lg = LatencyGuard::new("WsClient::connect");
         let connect_timeout = self.config.connect_timeout;
         if let Some(proxy_url_str) = self.config.proxy_url.as_ref() {
+            // 错误信息中不暴露代理账号密码
+            let redacted_proxy_url = redact_url(proxy_url_str);
             let proxy_url = Url::parse(proxy_url_str)
-                .map_err(|_| WsError::invalid_proxy_url(proxy_url_str.clone()))?;
+                .map_err(|_| WsError::invalid_proxy_url(redacted_proxy_url.clone()))?;
+            // socks5:// 本地解析目标域名，socks5h:// 由代理解析
+            let remote_dns = match proxy_url.scheme() {
+                "socks5" => false,
+                "socks5h" => true,
+                scheme => {
+                    return Err(WsError::invalid_proxy_url(format!(
+                        "{} (unsupported scheme {}, expect socks5 or socks5h)",
+                        redacted_proxy_url, scheme
+                    )));
+                }
+            };
             let proxy_host = proxy_url
                 .host_str()
-                .ok_or_else(|| WsError::invalid_proxy_url(proxy_url_str.clone()))?;
+                .ok_or_else(|| WsError::invalid_proxy_url(redacted_proxy_url.clone()))?;
             let proxy_port = proxy_url
                 .port()
-                .ok_or_else(|| WsError::invalid_proxy_url(proxy_url_str.clone()))?;
+                .ok_or_else(|| WsError::invalid_proxy_url(redacted_proxy_url.clone()))?;
+            let username = percent_decode_str(proxy_url.username())
+                .decode_utf8()
+                .map_err(|_| WsError::invalid_proxy_url(redacted_proxy_url.clone()))?;
+            let password = percent_decode_str(proxy_url.password().unwrap_or_default())
+                .decode_utf8()
+                .map_err(|_| WsError::invalid_proxy_url(redacted_proxy_url.clone()))?;
 
             let url = Url::parse(&self.config.url)
                 .map_err(|_| WsError::invalid_url(self.config.url.clone()))?;
@@ -262,23 +322,44 @@ impl Client {
                 .port()
                 .unwrap_or_else(|| if url.scheme() == "wss" { 443 } else { 80 });
 
-            let proxy_stream = tokio::time::timeout(
-                connect_timeout,
-                Socks5Stream::connect((proxy_host, proxy_port), (host, port)),
-            )
+            let proxy_stream = tokio::time::timeout(connect_timeout, async {
+                let target = if remote_dns {
+                    (host, port)
+                        .into_target_addr()
+                        .map_err(|e| WsError::connection_failed(self.config.url.clone(), e))?
+                } else {
+                    let addr = tokio::net::lookup_host((host, port))
+                        .await
+                        .map_err(|e| WsError::connection_failed(self.config.url.clone(), e))?
+                        .next()
+                        .ok_or_else(|| WsError::invalid_url(self.config.url.clone()))?;
+                    TargetAddr::Ip(addr)
+                };
+                if username.is_empty() {
+                    Socks5Stream::connect((proxy_host, proxy_port), target).await
+                } else {
+                    Socks5Stream::connect_with_password(
+                        (proxy_host, proxy_port),
+                        target,
+                        &username,
+                        &password,
+                    )
+                    .await
+                }
+                .map_err(|e| WsError::connection_failed(redacted_proxy_url.clone(), e))
+            })
             .await
-            .map_err(|_| WsError::connection_timeout(proxy_url_str.clone(), connect_timeout))?
-            .map_err(|e| WsError::connection_failed(proxy_url_str.clone(), e))?;
+            .map_err(|_| {
+                WsError::connection_timeout(redacted_proxy_url.clone(), connect_timeout)
+            })??;
 
             if url.scheme() == "wss" {
-                let root_cert_store = rustls::RootCertStore {
-                    roots: webpki_roots::TLS_SERVER_ROOTS.iter().cloned().collect(),
-                };
-                let config = Arc::new(
-                    tokio_rustls::rustls::ClientConfig::builder()
-                        .with_root_certificates(root_cert_store)
-                        .with_no_client_auth(),
-                );
+                let config = self
+                    .config
+                    .tls
+                    .clone()
+                    .unwrap_or_default()
+                    .client_config()?;
                 let connector = tokio_rustls::TlsConnector::from(config);
                 let domain = rustls::pki_types::ServerName::try_from(host.to_string())
                     .map_err(|_| WsError::invalid_url(self.config.url.clone()))?;
@@ -297,13 +378,17 @@ impl Client {
                 self.initialize_stream(ws_stream).await
             }
         } else {
-            let (ws_stream, _) =
-                tokio::time::timeout(connect_timeout, connect_async(&self.config.url))
-                    .await
-                    .map_err(|_| {
-                        WsError::connection_timeout(self.config.url.clone(), connect_timeout)
-                    })?
-                    .map_err(|e| WsError::connection_failed(self.config.url.clone(), e))?;
+            let connector = match self.config.tls.as_ref() {
+                Some(tls) => Some(Connector::Rustls(tls.client_config()?)),
+                None => None,
+            };
+            let (ws_stream, _) = tokio::time::timeout(
+                connect_timeout,
+                connect_async_tls_with_config(&self.config.url, None, false, connector),
+            )
+            .await
+            .map_err(|_| WsError::connection_timeout(self.config.url.clone(), connect_timeout))?
+            .map_err(|e| WsError::connection_failed(self.config.url.clone(), e))?;
             self.initialize_stream(ws_stream).await
         }
     }
@@ -316,14 +401,36 @@ impl Client {
 
         let rate_limiters = self.config.rate_limiters.clone();
 
-        let (send_tx, send_rx) = channel::<SendMsg>(self.config.send_buf_size);
+        let (tx, send_rx) = channel::<SendMsg>(self.config.send_buf_size);
+        let send_tx = OutboundQueue {
+            tx,
+            depth: Arc::new(AtomicUsize::new(0)),
+        };
         self.send_tx = Some(send_tx.clone());
 
         let shutdown_token1 = self.shutdown_token.clone();
+        let depth = send_tx.depth.clone();
         let send_loop_handle = tokio::spawn(async move {
-            Self::send_loop(sender, send_rx, rate_limiters, shutdown_token1).await
+            Self::send_loop(sender, send_rx, depth, rate_limiters, shutdown_token1).await
         });
 
+        // 记录由独立任务缓冲写入，recv_loop只投递到channel，不阻塞收包
+        // 不加入join_handles：recv_loop退出后channel关闭，写入任务写完剩余记录并flush后自行退出
+        let recorder = match self.config.record_to.as_ref() {
+            Some(path) => {
+                let file = tokio::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .await
+                    .map_err(|e| WsError::External(e.into()))?;
+                let (record_tx, record_rx) = unbounded_channel::<String>();
+                tokio::spawn(Self::record_loop(file, record_rx));
+                Some(record_tx)
+            }
+            None => None,
+        };
+
         let calc_recv_msg_id = self.config.calc_recv_msg_id.clone();
         let sync_call_chs = self.sync_call_chs.clone();
         let handle = self.config.handle.clone();
@@ -336,6 +443,7 @@ impl Client {
                 sync_call_chs,
                 handle,
                 send_tx1,
+                recorder,
                 shutdown_token2,
             )
             .await
@@ -361,7 +469,7 @@ impl Client {
             None => return Err(WsError::disconnected()),
         };
         send_tx
-            .send(msg)
+            .push(msg)
             .await
             .map_err(|e| WsError::channel_closed("send_tx".to_string(), e.to_string()))
     }
@@ -384,7 +492,7 @@ impl Client {
                 return Err(WsError::duplicated_message_id(msg_id));
             }
             self.sync_call_chs.insert(msg_id.clone(), resp_tx);
-            if let Err(e) = send_tx.send(msg).await {
+            if let Err(e) = send_tx.push(msg).await {
                 self.sync_call_chs.remove(&msg_id);
                 return Err(WsError::channel_closed(
                     "send_tx".to_string(),
@@ -417,7 +525,8 @@ impl Client {
         handle: Arc<
             dyn Fn(RecvMsg) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> + Send + Sync,
         >,
-        send_tx: Sender<SendMsg>,
+        send_tx: OutboundQueue,
+        recorder: Option<UnboundedSender<String>>,
         shutdown_token: CancellationToken,
     ) -> Result<()>
     where
@@ -447,6 +556,9 @@ impl Client {
                     let msg = msg.unwrap();
                     if let Some(recv_msg) = RecvMsg::from_websocket_message(msg, calc_recv_msg_id.as_ref()) {
                         let _lg = time::LatencyGuard::new("WsClient::recv_loop::handle");
+                        if let Some(Err(e)) = recorder.as_ref().map(|tx| Self::record(tx, &recv_msg)) {
+                            error!("failed to record message: {}", e);
+                        }
                         match &recv_msg {
                             RecvMsg::Text { .. } | RecvMsg::Binary { .. } => {
                                 let mut ch: Option<Sender<RecvMsg>> = None;
@@ -470,7 +582,7 @@ impl Client {
                                     data: data.clone(),
                                     weight: None
                                 };
-                                if let Err(e) = send_tx.send(pong_msg).await {
+                                if let Err(e) = send_tx.push(pong_msg).await {
                                     error!("failed to send Pong: {}", e);
                                     return Err(WsError::channel_closed("send_tx".to_string(), e.to_string()));
                                 }
@@ -493,9 +605,98 @@ impl Client {
         }
     }
 
+    // 接收时间在收包时确定，写入由record_loop完成
+    fn record(
+        tx: &UnboundedSender<String>,
+        msg: &RecvMsg,
+    ) -> std::result::Result<(), SendError<String>> {
+        let recv_ts = time::get_current_nano_timestamp() / 1000000;
+        let record = match msg {
+            RecvMsg::Text { content, .. } => {
+                json!({"recv_ts": recv_ts, "type": "text", "content": content})
+            }
+            RecvMsg::Binary { data, .. } => {
+                json!({"recv_ts": recv_ts, "type": "binary", "data": data})
+            }
+            _ => return Ok(()),
+        };
+        tx.send(format!("{}\n", record))
+    }
+
+    // 批量写入记录，channel暂时取空时flush，保证空闲时文件内容完整
+    async fn record_loop(file: tokio::fs::File, mut rx: UnboundedReceiver<String>) {
+        let mut writer = BufWriter::new(file);
+        while let Some(line) = rx.recv().await {
+            let mut result = writer.write_all(line.as_bytes()).await;
+            while result.is_ok() {
+                match rx.try_recv() {
+                    Ok(line) => result = writer.write_all(line.as_bytes()).await,
+                    Err(_) => break,
+                }
+            }
+            if let Err(e) = result {
+                error!("failed to write record: {}", e);
+            }
+            if let Err(e) = writer.flush().await {
+                error!("failed to flush record: {}", e);
+            }
+        }
+    }
+
+    // 将record_to记录的帧按顺序交给config.handle处理，返回处理的帧数
+    pub async fn replay<P: AsRef<Path>>(config: &Config, path: P) -> Result<usize> {
+        let file = tokio::fs::File::open(path.as_ref())
+            .await
+            .map_err(|e| WsError::External(e.into()))?;
+        let mut lines = BufReader::new(file).lines();
+        let mut count = 0;
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .map_err(|e| WsError::External(e.into()))?
+        {
+            if line.is_empty() {
+                continue;
+            }
+            let invalid_record =
+                || WsError::client(format!("invalid record in {:?}: {}", path.as_ref(), line));
+            let record: Value = serde_json::from_str(&line).map_err(|_| invalid_record())?;
+            let recv_msg = match record.get("type").and_then(|v| v.as_str()) {
+                Some("text") => {
+                    let content = record
+                        .get("content")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(invalid_record)?
+                        .to_string();
+                    RecvMsg::Text {
+                        msg_id: (config.calc_recv_msg_id)(&content),
+                        content,
+                    }
+                }
+                Some("binary") => {
+                    let data: Vec<u8> = record
+                        .get("data")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .ok_or_else(invalid_record)?;
+                    let msg_id = std::str::from_utf8(&data)
+                        .ok()
+                        .and_then(|s| (config.calc_recv_msg_id)(s));
+                    RecvMsg::Binary { msg_id, data }
+                }
+                _ => return Err(invalid_record()),
+            };
+            if let Err(e) = (config.handle)(recv_msg).await {
+                error!("failed to handle replayed message: {}", e);
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
     async fn send_loop<S>(
         mut sender: SplitSink<WebSocketStream<S>, Message>,
         mut send_rx: Receiver<SendMsg>,
+        depth: Arc<AtomicUsize>,
         rate_limiters: Option<Arc<Vec<RateLimiter>>>,
         shutdown_token: CancellationToken,
     ) -> Result<()>
@@ -513,6 +714,10 @@ impl Client {
                 }
                 msg = send_rx.recv() => {
                     if let Some(msg) = msg {
+                        // 发出（或失败退出）后才从depth中扣除
+                        defer!(
+                            depth.fetch_sub(1, Ordering::SeqCst);
+                        );
                         let weight = msg.weight().unwrap_or(1);
                         if let Some(limiters) = rate_limiters.as_ref() {
                             for limiter in limiters.iter() {
@@ -533,7 +738,7 @@ impl Client {
     }
 
     async fn heartbeat(
-        send_tx: Sender<SendMsg>,
+        send_tx: OutboundQueue,
         interval: Duration,
         shutdown_token: CancellationToken,
     ) -> Result<()> {
@@ -552,7 +757,7 @@ impl Client {
                         data: now_ts.to_string().as_bytes().to_vec(),
                         weight: None,
                     };
-                    if let Err(e) = send_tx.send(heartbeat_msg).await {
+                    if let Err(e) = send_tx.push(heartbeat_msg).await {
                         error!("Failed to send heartbeat: {}", e);
                         return Err(WsError::channel_closed("send_tx".to_string(), e.to_string()));
                     }
@@ -572,3 +777,13 @@ impl Drop for Client {
         }
     }
 }
+
+// 将url中的账号密码替换为***，用于日志和错误信息
+pub fn redact_url(url: &str) -> String {
+    match (url.find("://"), url.rfind('@')) {
+        (Some(scheme_end), Some(at)) if scheme_end + 3 < at => {
+            format!("{}***@{}", &url[..scheme_end + 3], &url[at + 1..])
+        }
+        _ => url.to_string(),
+    }
+}